@@ -2,7 +2,12 @@
 //!
 //! When a property violation is detected, a counterexample shows
 //! the exact sequence of operations that led to the failure.
+//! Besides the ASCII `render_diagram`, it can be rendered as Mermaid,
+//! Graphviz DOT or a standalone HTML page (see `render`).
 
+mod render;
+
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// A counterexample showing the failure path.
@@ -10,7 +15,7 @@ use std::fmt;
 /// Contains the sequence of states and thread actions that led
 /// to an invariant violation. Can be rendered as a human-readable
 /// thread diagram.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Counterexample {
     /// Sequence of state snapshots
     pub states: Vec<StateSnapshot>,
@@ -22,10 +27,14 @@ pub struct Counterexample {
     pub dst_seed: Option<u64>,
     /// Human-readable description of the failure (invariant violations, etc.)
     pub description: Option<String>,
+    /// Lasso-shaped (liveness) counterexamples: the step the last state
+    /// loops back to, so the behavior repeats forever from there
+    #[serde(default)]
+    pub loop_to_step: Option<u64>,
 }
 
 /// Snapshot of system state at a point in time.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateSnapshot {
     /// Step number in the execution
     pub step: u64,
@@ -36,7 +45,7 @@ pub struct StateSnapshot {
 }
 
 /// Action taken by a thread.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThreadAction {
     /// Thread identifier
     pub thread_id: u64,
@@ -49,7 +58,7 @@ pub struct ThreadAction {
 }
 
 /// Memory-related issue detected.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MemoryIssue {
     /// Use-after-free detected
     UseAfterFree {
@@ -89,6 +98,7 @@ impl Counterexample {
             memory_issues: Vec::new(),
             dst_seed: None,
             description: None,
+            loop_to_step: None,
         }
     }
 
@@ -102,6 +112,7 @@ impl Counterexample {
             memory_issues: Vec::new(),
             dst_seed: Some(seed),
             description: None,
+            loop_to_step: None,
         }
     }
 
@@ -169,7 +180,11 @@ impl Counterexample {
         threads.dedup();
 
         if threads.is_empty() {
-            output.push_str("(no thread actions recorded)\n");
+            if self.states.is_empty() {
+                output.push_str("(no thread actions recorded)\n");
+            } else {
+                self.render_states(&mut output);
+            }
             return output;
         }
 
@@ -220,6 +235,10 @@ impl Counterexample {
             output.push('\n');
         }
 
+        if let Some(step) = self.loop_to_step {
+            output.push_str(&format!("Back to step {}\n", step));
+        }
+
         // Memory issues
         if !self.memory_issues.is_empty() {
             output.push_str("\nMemory Issues:\n");
@@ -230,6 +249,26 @@ impl Counterexample {
 
         output
     }
+
+    /// Render a state-only trace (e.g. imported from TLC), listing each
+    /// state's variables. After the first state only changed variables
+    /// are shown.
+    fn render_states(&self, output: &mut String) {
+        let mut previous: &[(String, String)] = &[];
+        for state in &self.states {
+            output.push_str(&format!("State {}: {}\n", state.step, state.description));
+            for (name, value) in &state.variables {
+                let unchanged = previous.iter().any(|(n, v)| n == name && v == value);
+                if !unchanged {
+                    output.push_str(&format!("  {} = {}\n", name, value));
+                }
+            }
+            previous = &state.variables;
+        }
+        if let Some(step) = self.loop_to_step {
+            output.push_str(&format!("Back to state {}\n", step));
+        }
+    }
 }
 
 impl Default for Counterexample {
@@ -301,6 +340,24 @@ mod tests {
         assert_eq!(ce.dst_seed, Some(12345));
     }
 
+    #[test]
+    fn test_render_state_trace() {
+        let mut ce = Counterexample::new().with_description("Invariant Safe is violated.".into());
+        for (step, x, y) in [(1, "0", "{}"), (2, "1", "{}")] {
+            ce.add_state(StateSnapshot {
+                step,
+                description: format!("state {}", step),
+                variables: vec![("x".into(), x.into()), ("y".into(), y.into())],
+            });
+        }
+
+        let diagram = ce.render_diagram();
+        assert!(diagram.contains("Failure: Invariant Safe is violated."));
+        assert!(diagram.contains("State 1: state 1\n  x = 0\n  y = {}\n"));
+        assert!(diagram.contains("State 2: state 2\n  x = 1\n"));
+        assert!(!diagram.contains("no thread actions"));
+    }
+
     #[test]
     fn test_render_diagram() {
         let mut ce = Counterexample::with_seed(42);
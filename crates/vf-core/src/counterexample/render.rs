@@ -0,0 +1,613 @@
+//! Graphical counterexample renderers.
+//!
+//! `render_diagram` is meant for terminals. These renderers produce
+//! formats that can be pasted into PRs and design docs:
+//!
+//! - `render_mermaid`: a Mermaid sequence diagram, one lifeline per thread
+//! - `render_dot`: a Graphviz digraph of per-thread timelines
+//! - `render_html`: a self-contained page to step through the trace
+
+use super::{Counterexample, MemoryIssue, StateSnapshot, ThreadAction};
+
+impl Counterexample {
+    /// Render as a Mermaid sequence diagram.
+    ///
+    /// Each thread is a participant acting on the shared state; failed
+    /// actions use a crossed arrow and states become notes. State-only
+    /// traces (e.g. imported from TLC) render as a column of notes.
+    #[must_use]
+    pub fn render_mermaid(&self) -> String {
+        let threads = self.threads();
+        let mut out = String::from("sequenceDiagram\n");
+
+        for tid in &threads {
+            out.push_str(&format!("    participant T{} as Thread {}\n", tid, tid));
+        }
+        out.push_str("    participant S as State\n");
+        let span = match threads.first() {
+            Some(tid) => format!("T{},S", tid),
+            None => "S".to_string(),
+        };
+
+        if let Some(seed) = self.dst_seed {
+            out.push_str(&format!("    Note over {}: DST_SEED={}\n", span, seed));
+        }
+
+        let mut previous: &[(String, String)] = &[];
+        for step in self.steps() {
+            for action in self.actions_at(step) {
+                let arrow = if action.success { "->>" } else { "-x" };
+                out.push_str(&format!(
+                    "    T{}{}S: {}: {}\n",
+                    action.thread_id,
+                    arrow,
+                    step,
+                    mermaid_escape(&action.action)
+                ));
+            }
+            if let Some(state) = self.state_at(step) {
+                let mut lines = vec![if threads.is_empty() {
+                    format!("State {}: {}", state.step, state.description)
+                } else {
+                    state.description.clone()
+                }];
+                lines.extend(changed_variables(previous, state));
+                let text: Vec<String> = lines.iter().map(|l| mermaid_escape(l)).collect();
+                out.push_str(&format!("    Note right of S: {}\n", text.join("<br/>")));
+                previous = &state.variables;
+            }
+        }
+
+        if let Some(step) = self.loop_to_step {
+            out.push_str(&format!("    Note over {}: Back to step {}\n", span, step));
+        }
+        for issue in &self.memory_issues {
+            out.push_str(&format!(
+                "    Note over {}: {}\n",
+                span,
+                mermaid_escape(&issue.to_string())
+            ));
+        }
+        if let Some(ref desc) = self.description {
+            out.push_str(&format!(
+                "    Note over {}: Failure: {}\n",
+                span,
+                mermaid_escape(desc)
+            ));
+        }
+
+        out
+    }
+
+    /// Render as a Graphviz DOT digraph.
+    ///
+    /// Each thread is a cluster of its actions linked in program order.
+    /// Dashed edges are happens-before edges between threads: the executed
+    /// interleaving is sequential, so whenever control passes to another
+    /// thread, the last action before the switch happens before the first
+    /// action after it. States and memory issues hang off the action at
+    /// their step.
+    #[must_use]
+    pub fn render_dot(&self) -> String {
+        let mut out = String::from("digraph counterexample {\n");
+        out.push_str("    rankdir=TB;\n");
+        out.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+
+        let mut title = Vec::new();
+        if let Some(seed) = self.dst_seed {
+            title.push(format!("DST_SEED={}", seed));
+        }
+        if let Some(ref desc) = self.description {
+            title.push(format!("Failure: {}", desc));
+        }
+        if !title.is_empty() {
+            out.push_str(&format!(
+                "    labelloc=t;\n    label=\"{}\";\n",
+                dot_escape(&title.join("\n"))
+            ));
+        }
+
+        let actions = self.ordered_actions();
+        let node = |action: &ThreadAction| {
+            let index = actions
+                .iter()
+                .position(|a| std::ptr::eq(*a, action))
+                .expect("action is in the interleaving");
+            format!("a{}", index)
+        };
+
+        for tid in self.threads() {
+            out.push_str(&format!("    subgraph cluster_t{} {{\n", tid));
+            out.push_str(&format!("        label=\"Thread {}\";\n", tid));
+            let mine: Vec<&ThreadAction> = actions
+                .iter()
+                .copied()
+                .filter(|a| a.thread_id == tid)
+                .collect();
+            for action in &mine {
+                let style = if action.success {
+                    ""
+                } else {
+                    ", color=red, fontcolor=red"
+                };
+                out.push_str(&format!(
+                    "        {} [label=\"{}: {}\"{}];\n",
+                    node(action),
+                    action.step,
+                    dot_escape(&action.action),
+                    style
+                ));
+            }
+            for pair in mine.windows(2) {
+                out.push_str(&format!(
+                    "        {} -> {};\n",
+                    node(pair[0]),
+                    node(pair[1])
+                ));
+            }
+            out.push_str("    }\n");
+        }
+
+        for pair in actions.windows(2) {
+            if pair[0].thread_id != pair[1].thread_id {
+                out.push_str(&format!(
+                    "    {} -> {} [style=dashed, color=gray];\n",
+                    node(pair[0]),
+                    node(pair[1])
+                ));
+            }
+        }
+
+        // States: attached to their step's action, or chained on their own
+        let mut previous: Option<&StateSnapshot> = None;
+        for (i, state) in self.states.iter().enumerate() {
+            let mut lines = vec![format!("State {}: {}", state.step, state.description)];
+            lines.extend(changed_variables(
+                previous.map_or(&[][..], |p| &p.variables),
+                state,
+            ));
+            out.push_str(&format!(
+                "    s{} [shape=note, label=\"{}\"];\n",
+                i,
+                dot_escape(&lines.join("\n"))
+            ));
+            match self.actions_at(state.step).last() {
+                Some(action) => out.push_str(&format!(
+                    "    {} -> s{} [style=dotted, arrowhead=none];\n",
+                    node(action),
+                    i
+                )),
+                None if actions.is_empty() && i > 0 => {
+                    out.push_str(&format!("    s{} -> s{};\n", i - 1, i));
+                }
+                None => {}
+            }
+            previous = Some(state);
+        }
+
+        // Lasso: back from the last state (or action) to the loop start
+        if let Some(step) = self.loop_to_step {
+            let target = self.states.iter().position(|s| s.step == step);
+            let first_action = actions.iter().find(|a| a.step == step);
+            match (target, first_action) {
+                (Some(target), _) => out.push_str(&format!(
+                    "    s{} -> s{} [label=\"loop\", style=bold];\n",
+                    self.states.len() - 1,
+                    target
+                )),
+                (None, Some(first)) => out.push_str(&format!(
+                    "    {} -> {} [label=\"loop\", style=bold];\n",
+                    node(actions[actions.len() - 1]),
+                    node(first)
+                )),
+                (None, None) => {}
+            }
+        }
+
+        for (i, issue) in self.memory_issues.iter().enumerate() {
+            out.push_str(&format!(
+                "    m{} [shape=octagon, color=red, label=\"{}\"];\n",
+                i,
+                dot_escape(&issue.to_string())
+            ));
+            if let Some(action) = self.actions_at(issue_step(issue)).last() {
+                out.push_str(&format!("    {} -> m{} [color=red];\n", node(action), i));
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a self-contained HTML page.
+    ///
+    /// The page embeds the counterexample as JSON and needs no network
+    /// access. It shows the thread diagram and lets the reader step
+    /// through it (buttons, slider or arrow keys), with each state's
+    /// variables and what changed since the previous state.
+    #[must_use]
+    pub fn render_html(&self) -> String {
+        let title = match self.dst_seed {
+            Some(seed) => format!("Counterexample (DST_SEED={})", seed),
+            None => "Counterexample".to_string(),
+        };
+        // `<\/` keeps strings in the data from closing the script element
+        let data = serde_json::to_string(self)
+            .expect("counterexample is always serializable")
+            .replace("</", "<\\/");
+
+        HTML_TEMPLATE
+            .replace("{{TITLE}}", &html_escape(&title))
+            .replace("{{DATA}}", &data)
+    }
+
+    /// Thread ids in ascending order.
+    fn threads(&self) -> Vec<u64> {
+        let mut threads: Vec<u64> = self.interleaving.iter().map(|a| a.thread_id).collect();
+        threads.sort_unstable();
+        threads.dedup();
+        threads
+    }
+
+    /// Every step with an action or a state, in order.
+    fn steps(&self) -> Vec<u64> {
+        let mut steps: Vec<u64> = self
+            .interleaving
+            .iter()
+            .map(|a| a.step)
+            .chain(self.states.iter().map(|s| s.step))
+            .collect();
+        steps.sort_unstable();
+        steps.dedup();
+        steps
+    }
+
+    /// Actions in execution order: by step, then thread.
+    fn ordered_actions(&self) -> Vec<&ThreadAction> {
+        let mut actions: Vec<&ThreadAction> = self.interleaving.iter().collect();
+        actions.sort_by_key(|a| (a.step, a.thread_id));
+        actions
+    }
+
+    fn actions_at(&self, step: u64) -> Vec<&ThreadAction> {
+        self.ordered_actions()
+            .into_iter()
+            .filter(|a| a.step == step)
+            .collect()
+    }
+
+    fn state_at(&self, step: u64) -> Option<&StateSnapshot> {
+        self.states.iter().find(|s| s.step == step)
+    }
+}
+
+/// `name = value` lines for variables that differ from `previous`.
+fn changed_variables(previous: &[(String, String)], state: &StateSnapshot) -> Vec<String> {
+    state
+        .variables
+        .iter()
+        .filter(|(name, value)| !previous.iter().any(|(n, v)| n == name && v == value))
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect()
+}
+
+/// Step at which a memory issue was detected.
+fn issue_step(issue: &MemoryIssue) -> u64 {
+    match issue {
+        MemoryIssue::UseAfterFree { used_at_step, .. } => *used_at_step,
+        MemoryIssue::DataRace { step, .. } => *step,
+        MemoryIssue::AbaProblem { step, .. } => *step,
+        MemoryIssue::MemoryLeak {
+            allocated_at_step, ..
+        } => *allocated_at_step,
+    }
+}
+
+/// Escape text for a Mermaid message or note.
+///
+/// `;` and `#` are syntax in Mermaid and `<`/`>` would be read as HTML,
+/// so they become entity codes.
+fn mermaid_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => out.push_str("#35;"),
+            ';' => out.push_str("#59;"),
+            '<' => out.push_str("#lt;"),
+            '>' => out.push_str("#gt;"),
+            '\n' => out.push_str("<br/>"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape text for a double-quoted DOT string.
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HTML_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{TITLE}}</title>
+<style>
+  body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+  h1 { font-size: 1.3em; }
+  pre.failure { background: #fdecea; border-left: 4px solid #d32f2f; padding: 0.8em; white-space: pre-wrap; }
+  .controls { margin: 1em 0; display: flex; gap: 0.5em; align-items: center; }
+  .controls input[type=range] { flex: 1; }
+  table { border-collapse: collapse; font-family: monospace; }
+  th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+  tr.current { background: #fff3c4; }
+  tr.future { color: #aaa; }
+  td.fail { color: #d32f2f; }
+  .changed { background: #d7f5dd; }
+  .removed { background: #fdecea; text-decoration: line-through; }
+  .panes { display: flex; gap: 2em; align-items: flex-start; flex-wrap: wrap; }
+  ul.issues { color: #d32f2f; font-family: monospace; }
+</style>
+</head>
+<body>
+<h1>{{TITLE}}</h1>
+<pre class="failure" id="failure" hidden></pre>
+<div class="controls">
+  <button id="prev" title="Previous step (left arrow)">&larr;</button>
+  <input type="range" id="slider" min="0" value="0">
+  <button id="next" title="Next step (right arrow)">&rarr;</button>
+  <span id="position"></span>
+</div>
+<div class="panes">
+  <table id="diagram"></table>
+  <div>
+    <h2 id="state-title">State</h2>
+    <table id="variables"></table>
+  </div>
+</div>
+<ul class="issues" id="issues"></ul>
+<script type="application/json" id="counterexample">{{DATA}}</script>
+<script>
+(function () {
+  "use strict";
+  var cex = JSON.parse(document.getElementById("counterexample").textContent);
+  var threads = Array.from(new Set(cex.interleaving.map(function (a) { return a.thread_id; })))
+    .sort(function (a, b) { return a - b; });
+  var steps = Array.from(new Set(
+    cex.interleaving.map(function (a) { return a.step; })
+      .concat(cex.states.map(function (s) { return s.step; }))))
+    .sort(function (a, b) { return a - b; });
+  var current = 0;
+
+  function el(tag, text, cls) {
+    var e = document.createElement(tag);
+    if (text !== undefined) e.textContent = text;
+    if (cls) e.className = cls;
+    return e;
+  }
+
+  // Latest state at or before a step, and the one before it
+  function statesUpTo(step) {
+    var seen = cex.states.filter(function (s) { return s.step <= step; });
+    return [seen[seen.length - 1], seen[seen.length - 2]];
+  }
+
+  if (cex.description) {
+    var failure = document.getElementById("failure");
+    failure.textContent = "Failure: " + cex.description;
+    failure.hidden = false;
+  }
+  cex.memory_issues.forEach(function (issue) {
+    var kind = Object.keys(issue)[0];
+    document.getElementById("issues").appendChild(el("li", kind + " " + JSON.stringify(issue[kind])));
+  });
+
+  var diagram = document.getElementById("diagram");
+  var header = el("tr");
+  header.appendChild(el("th", "Step"));
+  threads.forEach(function (t) { header.appendChild(el("th", "Thread " + t)); });
+  header.appendChild(el("th", "State"));
+  diagram.appendChild(header);
+  var rows = steps.map(function (step, i) {
+    var row = el("tr");
+    row.appendChild(el("td", String(step)));
+    threads.forEach(function (t) {
+      var action = cex.interleaving.find(function (a) { return a.step === step && a.thread_id === t; });
+      if (!action) { row.appendChild(el("td", "")); return; }
+      row.appendChild(el("td", action.action + (action.success ? "" : " [FAIL]"), action.success ? "" : "fail"));
+    });
+    var state = cex.states.find(function (s) { return s.step === step; });
+    row.appendChild(el("td", state ? state.description : ""));
+    row.addEventListener("click", function () { show(i); });
+    diagram.appendChild(row);
+    return row;
+  });
+
+  function show(i) {
+    if (steps.length === 0) return;
+    current = Math.max(0, Math.min(steps.length - 1, i));
+    var step = steps[current];
+    rows.forEach(function (row, j) {
+      row.className = j === current ? "current" : (j > current ? "future" : "");
+    });
+    document.getElementById("slider").value = current;
+    var position = "step " + step + " (" + (current + 1) + "/" + steps.length + ")";
+    if (current === steps.length - 1 && cex.loop_to_step != null) {
+      position += ", loops back to step " + cex.loop_to_step;
+    }
+    document.getElementById("position").textContent = position;
+
+    var pair = statesUpTo(step);
+    var state = pair[0], before = pair[1];
+    var title = document.getElementById("state-title");
+    var table = document.getElementById("variables");
+    table.replaceChildren();
+    if (!state) { title.textContent = "No state yet"; return; }
+    title.textContent = "State " + state.step + ": " + state.description;
+    var old = new Map(before ? before.variables : []);
+    state.variables.forEach(function (v) {
+      var row = el("tr", undefined, before && old.get(v[0]) !== v[1] ? "changed" : "");
+      row.appendChild(el("td", v[0]));
+      row.appendChild(el("td", v[1]));
+      if (before && old.has(v[0]) && old.get(v[0]) !== v[1]) row.appendChild(el("td", "was " + old.get(v[0])));
+      table.appendChild(row);
+      old.delete(v[0]);
+    });
+    old.forEach(function (value, name) {
+      var row = el("tr", undefined, "removed");
+      row.appendChild(el("td", name));
+      row.appendChild(el("td", value));
+      table.appendChild(row);
+    });
+  }
+
+  var slider = document.getElementById("slider");
+  slider.max = Math.max(0, steps.length - 1);
+  slider.addEventListener("input", function () { show(Number(slider.value)); });
+  document.getElementById("prev").addEventListener("click", function () { show(current - 1); });
+  document.getElementById("next").addEventListener("click", function () { show(current + 1); });
+  document.addEventListener("keydown", function (e) {
+    if (e.key === "ArrowLeft") show(current - 1);
+    if (e.key === "ArrowRight") show(current + 1);
+  });
+  show(0);
+})();
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interleaving() -> Counterexample {
+        let mut ce = Counterexample::with_seed(42)
+            .with_description("NoLostElements violated: 1 missing".to_string());
+        for (thread_id, step, action, success) in [
+            (0, 1, "push(1)", true),
+            (1, 2, "pop() start", true),
+            (0, 3, "push(2)", true),
+            (1, 4, "CAS fail; retry", false),
+        ] {
+            ce.add_action(ThreadAction {
+                thread_id,
+                step,
+                action: action.to_string(),
+                success,
+            });
+        }
+        ce.add_state(StateSnapshot {
+            step: 1,
+            description: "head=N1".to_string(),
+            variables: vec![("head".into(), "N1".into()), ("len".into(), "1".into())],
+        });
+        ce.add_state(StateSnapshot {
+            step: 3,
+            description: "head=N2".to_string(),
+            variables: vec![("head".into(), "N2".into()), ("len".into(), "1".into())],
+        });
+        ce.add_memory_issue(MemoryIssue::AbaProblem {
+            address: 0x10,
+            original_value: 1,
+            intermediate_value: 2,
+            final_value: 1,
+            step: 4,
+        });
+        ce
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let mermaid = interleaving().render_mermaid();
+        assert!(mermaid.starts_with("sequenceDiagram\n"));
+        assert!(mermaid.contains("    participant T0 as Thread 0\n"));
+        assert!(mermaid.contains("    participant T1 as Thread 1\n"));
+        assert!(mermaid.contains("    Note over T0,S: DST_SEED=42\n"));
+        assert!(mermaid.contains("    T0->>S: 1: push(1)\n"));
+        assert!(mermaid.contains("    T1-xS: 4: CAS fail#59; retry\n"));
+        // Only changed variables after the first state
+        assert!(mermaid.contains("    Note right of S: head=N1<br/>head = N1<br/>len = 1\n"));
+        assert!(mermaid.contains("    Note right of S: head=N2<br/>head = N2\n"));
+        assert!(mermaid.contains("ABA problem"));
+        assert!(mermaid.contains("Note over T0,S: Failure: NoLostElements violated"));
+    }
+
+    #[test]
+    fn test_render_mermaid_state_trace() {
+        let mut ce = Counterexample::new();
+        ce.add_state(StateSnapshot {
+            step: 1,
+            description: "Initial predicate".to_string(),
+            variables: vec![("x".into(), "<<1>>".into())],
+        });
+        let mermaid = ce.render_mermaid();
+        assert!(!mermaid.contains("participant T"));
+        assert!(mermaid
+            .contains("Note right of S: State 1: Initial predicate<br/>x = #lt;#lt;1#gt;#gt;\n"));
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let dot = interleaving().render_dot();
+        assert!(dot.starts_with("digraph counterexample {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(
+            dot.contains("label=\"DST_SEED=42\\nFailure: NoLostElements violated: 1 missing\";")
+        );
+        assert!(dot.contains("subgraph cluster_t0 {"));
+        assert!(dot.contains("a0 [label=\"1: push(1)\"];"));
+        assert!(dot.contains("a3 [label=\"4: CAS fail; retry\", color=red, fontcolor=red];"));
+        // Program order inside thread 0, happens-before across threads
+        assert!(dot.contains("        a0 -> a2;\n"));
+        assert!(dot.contains("    a0 -> a1 [style=dashed, color=gray];\n"));
+        assert!(dot.contains("    a2 -> s1 [style=dotted, arrowhead=none];\n"));
+        assert!(dot.contains("    a3 -> m0 [color=red];\n"));
+    }
+
+    #[test]
+    fn test_render_lasso() {
+        let mut ce = Counterexample::new();
+        for step in 1..=3 {
+            ce.add_state(StateSnapshot {
+                step,
+                description: format!("state {}", step),
+                variables: vec![("pc".into(), step.to_string())],
+            });
+        }
+        ce.loop_to_step = Some(2);
+
+        assert!(ce.render_mermaid().contains("    Note over S: Back to step 2\n"));
+        let dot = ce.render_dot();
+        assert!(dot.contains("    s0 -> s1;\n    "));
+        assert!(dot.contains("    s2 -> s1 [label=\"loop\", style=bold];\n"));
+        assert!(ce.render_html().contains("\"loop_to_step\":2"));
+    }
+
+    #[test]
+    fn test_render_html() {
+        let mut ce = interleaving();
+        ce.description = Some("</script><script>alert(1)</script>".to_string());
+        let html = ce.render_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Counterexample (DST_SEED=42)</title>"));
+        assert!(!html.contains("{{"));
+        assert!(!html.contains("</script><script>alert"));
+
+        // The embedded data round-trips
+        let start = html.find("id=\"counterexample\">").unwrap() + "id=\"counterexample\">".len();
+        let end = start + html[start..].find("</script>").unwrap();
+        let data: Counterexample = serde_json::from_str(&html[start..end]).unwrap();
+        assert_eq!(data.interleaving.len(), 4);
+        assert_eq!(data.description, ce.description);
+    }
+}
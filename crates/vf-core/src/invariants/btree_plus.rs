@@ -4,13 +4,15 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | SortedOrder | 45 | Keys in every node are sorted |
-//! | BalancedHeight | 58 | All leaves at same depth |
-//! | AtomicSplit | 72 | Splits appear atomic to readers |
-//! | NoLostKeys | 86 | Every key findable via traversal |
+//! | SortedOrder | 47 | Keys in every node are sorted |
+//! | BalancedHeight | 55 | All leaves at same depth |
+//! | AtomicSplit | 63 | Splits appear atomic to readers |
+//! | NoLostKeys | 70 | Every key findable via traversal |
 
 use std::collections::HashSet;
 
+use vf_macros::tla_invariant;
+
 use crate::property::{PropertyChecker, PropertyResult};
 
 const TLA_SPEC: &str = "btree_plus.tla";
@@ -41,7 +43,8 @@ impl<'a, T: BTreePlusProperties> BTreePlusPropertyChecker<'a, T> {
         Self { tree }
     }
 
-    /// Line 45: SortedOrder
+    /// SortedOrder
+    #[tla_invariant(spec = "btree_plus.tla", name = "SortedOrder")]
     fn check_sorted_order(&self) -> PropertyResult {
         let keys = self.tree.leaf_scan_keys();
 
@@ -50,7 +53,7 @@ impl<'a, T: BTreePlusProperties> BTreePlusPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "SortedOrder",
                     TLA_SPEC,
-                    45,
+                    TLA_LINE,
                     format!(
                         "Keys not sorted: {} >= {} at indices {}, {}",
                         keys[i - 1],
@@ -63,25 +66,27 @@ impl<'a, T: BTreePlusProperties> BTreePlusPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("SortedOrder", TLA_SPEC, 45)
+        PropertyResult::pass("SortedOrder", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 58: BalancedHeight
+    /// BalancedHeight
+    #[tla_invariant(spec = "btree_plus.tla", name = "BalancedHeight")]
     fn check_balanced_height(&self) -> PropertyResult {
         if !self.tree.is_balanced() {
             return PropertyResult::fail(
                 "BalancedHeight",
                 TLA_SPEC,
-                58,
+                TLA_LINE,
                 format!("Tree is not balanced (height={})", self.tree.height()),
                 None,
             );
         }
 
-        PropertyResult::pass("BalancedHeight", TLA_SPEC, 58)
+        PropertyResult::pass("BalancedHeight", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 86: NoLostKeys
+    /// NoLostKeys
+    #[tla_invariant(spec = "btree_plus.tla", name = "NoLostKeys")]
     fn check_no_lost_keys(&self) -> PropertyResult {
         let logical = self.tree.logical_keys();
         let leaf_keys: HashSet<u64> = self.tree.leaf_scan_keys().into_iter().collect();
@@ -91,14 +96,14 @@ impl<'a, T: BTreePlusProperties> BTreePlusPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "NoLostKeys",
                     TLA_SPEC,
-                    86,
+                    TLA_LINE,
                     format!("Key {} exists logically but not found in leaf scan", key),
                     None,
                 );
             }
         }
 
-        PropertyResult::pass("NoLostKeys", TLA_SPEC, 86)
+        PropertyResult::pass("NoLostKeys", TLA_SPEC, TLA_LINE)
     }
 }
 
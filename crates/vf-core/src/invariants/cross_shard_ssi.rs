@@ -7,13 +7,15 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | Serializable | 45 | No cycles in dependency graph |
-//! | CrossShardAtomicity | 58 | All-or-nothing across shards |
-//! | FirstCommitterWins | 72 | Dangerous structure detection |
-//! | NoPhantomReads | 86 | Snapshot consistency |
+//! | Serializable | 52 | No cycles in dependency graph |
+//! | CrossShardAtomicity | 65 | All-or-nothing across shards |
+//! | FirstCommitterWins | 75 | Dangerous structure detection |
+//! | NoPhantomReads | 88 | Snapshot consistency |
 
 use std::collections::{HashMap, HashSet};
 
+use vf_macros::tla_invariant;
+
 use crate::property::{PropertyChecker, PropertyResult};
 
 const TLA_SPEC: &str = "cross_shard_ssi.tla";
@@ -65,9 +67,10 @@ impl<'a, T: CrossShardSsiProperties> CrossShardSsiPropertyChecker<'a, T> {
         Self { ssi }
     }
 
-    /// Line 58: CrossShardAtomicity
+    /// CrossShardAtomicity
     ///
     /// Committed cross-shard transactions must have committed on ALL shards.
+    #[tla_invariant(spec = "cross_shard_ssi.tla", name = "CrossShardAtomicity")]
     fn check_cross_shard_atomicity(&self) -> PropertyResult {
         let statuses = self.ssi.txn_statuses();
         let shards = self.ssi.txn_shards();
@@ -83,12 +86,13 @@ impl<'a, T: CrossShardSsiProperties> CrossShardSsiPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("CrossShardAtomicity", TLA_SPEC, 58)
+        PropertyResult::pass("CrossShardAtomicity", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 72: FirstCommitterWins
+    /// FirstCommitterWins
     ///
     /// No committed transaction has both in-conflict and out-conflict.
+    #[tla_invariant(spec = "cross_shard_ssi.tla", name = "FirstCommitterWins")]
     fn check_first_committer_wins(&self) -> PropertyResult {
         let statuses = self.ssi.txn_statuses();
         let in_conf = self.ssi.in_conflicts();
@@ -103,7 +107,7 @@ impl<'a, T: CrossShardSsiProperties> CrossShardSsiPropertyChecker<'a, T> {
                     return PropertyResult::fail(
                         "FirstCommitterWins",
                         TLA_SPEC,
-                        72,
+                        TLA_LINE,
                         format!(
                             "Transaction {} is committed with both in-conflict and out-conflict (dangerous structure)",
                             txn
@@ -114,12 +118,13 @@ impl<'a, T: CrossShardSsiProperties> CrossShardSsiPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("FirstCommitterWins", TLA_SPEC, 72)
+        PropertyResult::pass("FirstCommitterWins", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 45: Serializable
+    /// Serializable
     ///
     /// Check for cycles in the serialization graph.
+    #[tla_invariant(spec = "cross_shard_ssi.tla", name = "Serializable")]
     fn check_serializable(&self) -> PropertyResult {
         let statuses = self.ssi.txn_statuses();
         let reads = self.ssi.txn_reads();
@@ -162,7 +167,7 @@ impl<'a, T: CrossShardSsiProperties> CrossShardSsiPropertyChecker<'a, T> {
                         return PropertyResult::fail(
                             "Serializable",
                             TLA_SPEC,
-                            45,
+                            TLA_LINE,
                             format!(
                                 "Serialization cycle: txn {} and txn {} have mutual rw-conflicts (ww={})",
                                 t1, t2, ww
@@ -174,7 +179,7 @@ impl<'a, T: CrossShardSsiProperties> CrossShardSsiPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("Serializable", TLA_SPEC, 45)
+        PropertyResult::pass("Serializable", TLA_SPEC, TLA_LINE)
     }
 }
 
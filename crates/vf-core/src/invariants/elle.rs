@@ -0,0 +1,752 @@
+//! Elle-style transactional isolation checking.
+//!
+//! Workload-agnostic checker for list-append and read-write register
+//! histories. Infers version orders from the values transactions observed,
+//! builds the dependency graph (see `dsg`) and reports the weakest
+//! isolation level the history violates.
+//!
+//! # Workloads
+//!
+//! | Workload | Operations | Version order inferred from |
+//! |----------|------------|-----------------------------|
+//! | list-append | `Append`, `ReadList` | The longest list read for each key |
+//! | rw-register | `Write`, `Read` | Initial value first; read-then-write within a txn |
+//!
+//! Values written to a key must be unique so every observed value
+//! identifies exactly one writer.
+//!
+//! # Isolation Levels
+//!
+//! | Level | Proscribes |
+//! |-------|------------|
+//! | ReadCommitted | G0, G1a, G1b, G1c (plus garbage, incompatible and internal reads) |
+//! | SnapshotIsolation | Above, plus G-single |
+//! | Serializable | Above, plus G2-item |
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use super::dsg::{Anomaly, AnomalyReport, DependencyGraph, DependencyKind};
+use super::ssi::{InvariantResult, KeyId, SsiHistory, TxnId, TxnStatus};
+
+/// A micro-operation inside a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mop {
+    /// Append `value` to the list at `key`
+    Append { key: KeyId, value: u64 },
+    /// Read the list at `key` (empty if never appended to)
+    ReadList { key: KeyId, value: Vec<u64> },
+    /// Write `value` to the register at `key`
+    Write { key: KeyId, value: u64 },
+    /// Read the register at `key` (None = initial value)
+    Read { key: KeyId, value: Option<u64> },
+}
+
+/// Outcome of a transaction as observed by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TxnOutcome {
+    Committed,
+    Aborted,
+    /// Client does not know (e.g. timeout); may or may not have committed
+    Unknown,
+}
+
+/// A transaction and the micro-operations it performed, in order.
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub id: TxnId,
+    pub outcome: TxnOutcome,
+    pub mops: Vec<Mop>,
+}
+
+/// Isolation levels checked, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IsolationLevel {
+    ReadCommitted,
+    SnapshotIsolation,
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// All levels, weakest first.
+    pub const ALL: [IsolationLevel; 3] = [
+        IsolationLevel::ReadCommitted,
+        IsolationLevel::SnapshotIsolation,
+        IsolationLevel::Serializable,
+    ];
+
+    /// Invariant name for this level.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadCommitted => "ReadCommitted",
+            IsolationLevel::SnapshotIsolation => "SnapshotIsolation",
+            IsolationLevel::Serializable => "Serializable",
+        }
+    }
+
+    /// Whether this level forbids the given anomaly.
+    #[must_use]
+    pub fn proscribes(&self, anomaly: Anomaly) -> bool {
+        match anomaly {
+            Anomaly::GSingle => *self >= IsolationLevel::SnapshotIsolation,
+            Anomaly::G2Item | Anomaly::G2 => *self >= IsolationLevel::Serializable,
+            _ => true,
+        }
+    }
+}
+
+/// Result of checking a history.
+#[derive(Debug, Clone)]
+pub struct IsolationReport {
+    /// All anomalies found
+    pub anomalies: Vec<AnomalyReport>,
+    /// Weakest level whose proscribed anomalies occur (None = serializable)
+    pub weakest_violated: Option<IsolationLevel>,
+}
+
+impl IsolationReport {
+    /// Whether the history satisfies the given level.
+    #[must_use]
+    pub fn satisfies(&self, level: IsolationLevel) -> bool {
+        self.weakest_violated
+            .map_or(true, |weakest| level < weakest)
+    }
+
+    /// Strongest level the history satisfies (None if not even read committed).
+    #[must_use]
+    pub fn strongest_satisfied(&self) -> Option<IsolationLevel> {
+        IsolationLevel::ALL
+            .into_iter()
+            .rev()
+            .find(|level| self.satisfies(*level))
+    }
+}
+
+/// A transactional history for isolation checking.
+#[derive(Debug, Clone, Default)]
+pub struct TxnHistory {
+    pub transactions: Vec<Transaction>,
+}
+
+impl TxnHistory {
+    /// Create a new empty history.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a transaction.
+    pub fn push(&mut self, id: TxnId, outcome: TxnOutcome, mops: Vec<Mop>) {
+        debug_assert!(
+            !self.transactions.iter().any(|t| t.id == id),
+            "Transaction T{} recorded twice",
+            id
+        );
+        self.transactions.push(Transaction { id, outcome, mops });
+    }
+
+    /// Convert an SSI history into a rw-register history.
+    ///
+    /// Each write stores the writer's transaction id, so a read of
+    /// version `Some(t)` becomes a read of value `t`. Active transactions
+    /// become `Unknown`.
+    #[must_use]
+    pub fn from_ssi_history(history: &SsiHistory) -> Self {
+        let mut mops: BTreeMap<TxnId, Vec<(u64, usize, Mop)>> = BTreeMap::new();
+        for (i, read) in history.reads.iter().enumerate() {
+            mops.entry(read.txn).or_default().push((
+                read.timestamp,
+                i,
+                Mop::Read {
+                    key: read.key,
+                    value: read.version,
+                },
+            ));
+        }
+        for (i, write) in history.writes.iter().enumerate() {
+            mops.entry(write.txn).or_default().push((
+                write.timestamp,
+                history.reads.len() + i,
+                Mop::Write {
+                    key: write.key,
+                    value: write.txn,
+                },
+            ));
+        }
+
+        let mut txns: BTreeSet<TxnId> = history.txn_status.keys().copied().collect();
+        txns.extend(mops.keys().copied());
+
+        let mut result = Self::new();
+        for txn in txns {
+            let outcome = match history.txn_status.get(&txn) {
+                Some(TxnStatus::Committed) => TxnOutcome::Committed,
+                Some(TxnStatus::Aborted) => TxnOutcome::Aborted,
+                Some(TxnStatus::Active) | None => TxnOutcome::Unknown,
+            };
+            let mut ops = mops.remove(&txn).unwrap_or_default();
+            ops.sort_by_key(|(ts, seq, _)| (*ts, *seq));
+            result.push(txn, outcome, ops.into_iter().map(|(_, _, m)| m).collect());
+        }
+        result
+    }
+}
+
+/// Check a history, reporting all anomalies and the weakest violated level.
+#[must_use]
+pub fn check(history: &TxnHistory) -> IsolationReport {
+    let analysis = Analysis::new(history);
+    let mut anomalies = analysis.anomalies;
+
+    if let Some(report) = analysis.graph.find_cycle_anomaly() {
+        anomalies.push(report);
+    }
+
+    let weakest_violated = IsolationLevel::ALL
+        .into_iter()
+        .find(|level| anomalies.iter().any(|a| level.proscribes(a.anomaly)));
+
+    IsolationReport {
+        anomalies,
+        weakest_violated,
+    }
+}
+
+/// Check that a history satisfies the given isolation level.
+pub fn check_isolation(history: &TxnHistory, level: IsolationLevel) -> InvariantResult {
+    let report = check(history);
+    match report
+        .anomalies
+        .iter()
+        .find(|a| level.proscribes(a.anomaly))
+    {
+        None => InvariantResult::holds(level.name()),
+        Some(anomaly) => InvariantResult::violated(
+            level.name(),
+            format!("{} violated: {}", level.name(), anomaly),
+        ),
+    }
+}
+
+/// Data-level anomalies and the dependency graph of a history.
+struct Analysis {
+    anomalies: Vec<AnomalyReport>,
+    graph: DependencyGraph,
+}
+
+impl Analysis {
+    fn new(history: &TxnHistory) -> Self {
+        let mut analysis = Self {
+            anomalies: Vec::new(),
+            graph: DependencyGraph::new(),
+        };
+
+        let outcomes: HashMap<TxnId, TxnOutcome> = history
+            .transactions
+            .iter()
+            .map(|t| (t.id, t.outcome))
+            .collect();
+
+        // (key, value) -> (writer, index of the write within the writer)
+        let mut writers: HashMap<(KeyId, u64), (TxnId, usize)> = HashMap::new();
+        for txn in &history.transactions {
+            for (i, mop) in txn.mops.iter().enumerate() {
+                if let Mop::Append { key, value } | Mop::Write { key, value } = mop {
+                    writers.insert((*key, *value), (txn.id, i));
+                }
+            }
+        }
+
+        analysis.check_reads(history, &outcomes, &writers);
+        analysis.check_internal(history);
+
+        // Only transactions that committed (or may have) are graph nodes
+        let observed: HashSet<TxnId> = history
+            .transactions
+            .iter()
+            .filter(|t| t.outcome == TxnOutcome::Committed)
+            .flat_map(|t| t.mops.iter())
+            .flat_map(observed_values)
+            .filter_map(|kv| writers.get(&kv).map(|(w, _)| *w))
+            .collect();
+        let in_graph = |txn: TxnId| match outcomes.get(&txn) {
+            Some(TxnOutcome::Committed) => true,
+            Some(TxnOutcome::Unknown) => observed.contains(&txn),
+            _ => false,
+        };
+
+        analysis.add_list_edges(history, &writers, &in_graph);
+        analysis.add_register_edges(history, &writers, &in_graph);
+        analysis
+    }
+
+    /// G1a, G1b and garbage reads by committed transactions.
+    fn check_reads(
+        &mut self,
+        history: &TxnHistory,
+        outcomes: &HashMap<TxnId, TxnOutcome>,
+        writers: &HashMap<(KeyId, u64), (TxnId, usize)>,
+    ) {
+        for txn in committed(history) {
+            for mop in &txn.mops {
+                let (key, values, last) = match mop {
+                    Mop::ReadList { key, value } => (*key, value.clone(), value.last().copied()),
+                    Mop::Read { key, value } => (*key, value.iter().copied().collect(), *value),
+                    _ => continue,
+                };
+
+                for v in &values {
+                    match writers.get(&(key, *v)) {
+                        None => self.report(
+                            Anomaly::GarbageRead,
+                            format!(
+                                "T{} read value {} of key {} that no one wrote",
+                                txn.id, v, key
+                            ),
+                        ),
+                        Some((w, _)) if *w != txn.id && outcomes[w] == TxnOutcome::Aborted => self
+                            .report(
+                                Anomaly::G1a,
+                                format!(
+                                    "committed T{} read value {} of key {} written by aborted T{}",
+                                    txn.id, v, key, w
+                                ),
+                            ),
+                        _ => {}
+                    }
+                }
+
+                // Intermediate read: the writer wrote the key again afterwards
+                let Some((w, index)) = last.and_then(|v| writers.get(&(key, v))) else {
+                    continue;
+                };
+                if *w == txn.id {
+                    continue;
+                }
+                let writer = history.transactions.iter().find(|t| t.id == *w);
+                let overwritten = writer.is_some_and(|t| {
+                    t.mops[index + 1..].iter().any(|m| {
+                        matches!(m, Mop::Append { key: k, .. } | Mop::Write { key: k, .. } if *k == key)
+                    })
+                });
+                if overwritten {
+                    self.report(
+                        Anomaly::G1b,
+                        format!(
+                            "committed T{} read an intermediate version of key {} from T{}",
+                            txn.id, key, w
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Internal consistency: a transaction observes its own writes.
+    fn check_internal(&mut self, history: &TxnHistory) {
+        for txn in committed(history) {
+            let mut own_appends: HashMap<KeyId, Vec<u64>> = HashMap::new();
+            let mut own_writes: HashMap<KeyId, u64> = HashMap::new();
+
+            for mop in &txn.mops {
+                match mop {
+                    Mop::Append { key, value } => own_appends.entry(*key).or_default().push(*value),
+                    Mop::Write { key, value } => {
+                        own_writes.insert(*key, *value);
+                    }
+                    Mop::ReadList { key, value } => {
+                        let appended = own_appends.get(key).map_or(&[][..], |v| v.as_slice());
+                        if !value.ends_with(appended) {
+                            self.report(
+                                Anomaly::Internal,
+                                format!(
+                                    "T{} read {:?} from key {} without its own appends {:?}",
+                                    txn.id, value, key, appended
+                                ),
+                            );
+                        }
+                    }
+                    Mop::Read { key, value } => {
+                        if let Some(written) = own_writes.get(key) {
+                            if *value != Some(*written) {
+                                self.report(
+                                    Anomaly::Internal,
+                                    format!(
+                                        "T{} read {:?} from key {} after writing {}",
+                                        txn.id, value, key, written
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Version order from the longest list read per key; ww/wr/rw edges.
+    ///
+    /// Appends missing from a read are ordered after it, even when no
+    /// read observed them.
+    fn add_list_edges(
+        &mut self,
+        history: &TxnHistory,
+        writers: &HashMap<(KeyId, u64), (TxnId, usize)>,
+        in_graph: &dyn Fn(TxnId) -> bool,
+    ) {
+        let mut reads: BTreeMap<KeyId, Vec<(TxnId, &Vec<u64>)>> = BTreeMap::new();
+        for txn in committed(history) {
+            for mop in &txn.mops {
+                if let Mop::ReadList { key, value } = mop {
+                    reads.entry(*key).or_default().push((txn.id, value));
+                }
+            }
+        }
+
+        for (key, key_reads) in reads {
+            let longest = key_reads
+                .iter()
+                .map(|(_, v)| *v)
+                .max_by_key(|v| v.len())
+                .cloned()
+                .unwrap_or_default();
+
+            for (reader, value) in &key_reads {
+                if !longest.starts_with(value) {
+                    self.report(
+                        Anomaly::IncompatibleOrder,
+                        format!(
+                            "T{} read {:?} from key {}, not a prefix of {:?}",
+                            reader, value, key, longest
+                        ),
+                    );
+                }
+            }
+
+            let writer_of = |v: u64| {
+                writers
+                    .get(&(key, v))
+                    .map(|(w, _)| *w)
+                    .filter(|w| in_graph(*w))
+            };
+
+            for pair in longest.windows(2) {
+                if let (Some(a), Some(b)) = (writer_of(pair[0]), writer_of(pair[1])) {
+                    self.graph.add_edge(a, b, DependencyKind::WriteWrite, key);
+                }
+            }
+
+            for (reader, value) in &key_reads {
+                if !longest.starts_with(value) {
+                    continue;
+                }
+                if let Some(w) = value.last().and_then(|v| writer_of(*v)) {
+                    self.graph
+                        .add_edge(w, *reader, DependencyKind::WriteRead, key);
+                }
+                // Every append the reader did not see is a later version
+                let unseen = writers
+                    .iter()
+                    .filter(|((k, v), _)| *k == key && !value.contains(v))
+                    .filter_map(|((_, v), _)| writer_of(*v));
+                for later in unseen.collect::<BTreeSet<_>>() {
+                    self.graph
+                        .add_edge(*reader, later, DependencyKind::ReadWrite, key);
+                }
+            }
+        }
+    }
+
+    /// Register edges: wr from observed values, ww/rw from inferred order.
+    ///
+    /// The initial value precedes every write, and a transaction that read
+    /// `a` and then wrote `b` to the same key orders `a` before `b`.
+    fn add_register_edges(
+        &mut self,
+        history: &TxnHistory,
+        writers: &HashMap<(KeyId, u64), (TxnId, usize)>,
+        in_graph: &dyn Fn(TxnId) -> bool,
+    ) {
+        let writer_of = |key: KeyId, v: u64| {
+            writers
+                .get(&(key, v))
+                .map(|(w, _)| *w)
+                .filter(|w| in_graph(*w))
+        };
+
+        // Versions that directly follow each observed version: (key, a) -> {b}
+        let mut successors: HashMap<(KeyId, Option<u64>), BTreeSet<u64>> = HashMap::new();
+        // Transactions that read a version and then overwrote it
+        let mut overwriters: BTreeMap<(KeyId, Option<u64>), BTreeSet<TxnId>> = BTreeMap::new();
+        for txn in history.transactions.iter().filter(|t| in_graph(t.id)) {
+            let mut last_read: HashMap<KeyId, Option<u64>> = HashMap::new();
+            for mop in &txn.mops {
+                match mop {
+                    Mop::Read { key, value } => {
+                        last_read.insert(*key, *value);
+                    }
+                    Mop::Write { key, value } => {
+                        successors.entry((*key, None)).or_default().insert(*value);
+                        if let Some(read) = last_read.get(key) {
+                            if let Some(read) = read {
+                                successors
+                                    .entry((*key, Some(*read)))
+                                    .or_default()
+                                    .insert(*value);
+                            }
+                            overwriters.entry((*key, *read)).or_default().insert(txn.id);
+                        }
+                        last_read.insert(*key, Some(*value));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Lost update: whichever overwrite is ordered first, the other
+        // transaction's read misses it, so the cycle has a single rw edge
+        for ((key, read), txns) in &overwriters {
+            if txns.len() > 1 {
+                self.report(
+                    Anomaly::GSingle,
+                    format!(
+                        "lost update: {:?} all read {:?} from key {} and overwrote it",
+                        txns, read, key
+                    ),
+                );
+            }
+        }
+
+        for ((key, a), bs) in &successors {
+            for b in bs {
+                if let (Some(Some(wa)), Some(wb)) =
+                    (a.map(|v| writer_of(*key, v)), writer_of(*key, *b))
+                {
+                    self.graph
+                        .add_edge(wa, wb, DependencyKind::WriteWrite, *key);
+                }
+            }
+        }
+
+        for txn in committed(history) {
+            for mop in &txn.mops {
+                let Mop::Read { key, value } = mop else {
+                    continue;
+                };
+                if let Some(w) = value.and_then(|v| writer_of(*key, v)) {
+                    self.graph
+                        .add_edge(w, txn.id, DependencyKind::WriteRead, *key);
+                }
+                for b in successors.get(&(*key, *value)).into_iter().flatten() {
+                    if let Some(wb) = writer_of(*key, *b) {
+                        self.graph
+                            .add_edge(txn.id, wb, DependencyKind::ReadWrite, *key);
+                    }
+                }
+            }
+        }
+    }
+
+    fn report(&mut self, anomaly: Anomaly, description: String) {
+        self.anomalies.push(AnomalyReport {
+            anomaly,
+            cycle: Vec::new(),
+            description,
+        });
+    }
+}
+
+/// Committed transactions of a history.
+fn committed(history: &TxnHistory) -> impl Iterator<Item = &Transaction> {
+    history
+        .transactions
+        .iter()
+        .filter(|t| t.outcome == TxnOutcome::Committed)
+}
+
+/// (key, value) pairs a micro-operation observed.
+fn observed_values(mop: &Mop) -> Vec<(KeyId, u64)> {
+    match mop {
+        Mop::ReadList { key, value } => value.iter().map(|v| (*key, *v)).collect(),
+        Mop::Read {
+            key,
+            value: Some(v),
+        } => vec![(*key, *v)],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(key: KeyId, value: u64) -> Mop {
+        Mop::Append { key, value }
+    }
+
+    fn read_list(key: KeyId, value: &[u64]) -> Mop {
+        Mop::ReadList {
+            key,
+            value: value.to_vec(),
+        }
+    }
+
+    fn write(key: KeyId, value: u64) -> Mop {
+        Mop::Write { key, value }
+    }
+
+    fn read(key: KeyId, value: Option<u64>) -> Mop {
+        Mop::Read { key, value }
+    }
+
+    #[test]
+    fn test_serial_list_append_serializable() {
+        let mut history = TxnHistory::new();
+        history.push(1, TxnOutcome::Committed, vec![append(1, 1)]);
+        history.push(
+            2,
+            TxnOutcome::Committed,
+            vec![read_list(1, &[1]), append(1, 2)],
+        );
+        history.push(3, TxnOutcome::Committed, vec![read_list(1, &[1, 2])]);
+
+        let report = check(&history);
+        assert!(report.anomalies.is_empty(), "{:?}", report.anomalies);
+        assert_eq!(report.weakest_violated, None);
+        assert_eq!(
+            report.strongest_satisfied(),
+            Some(IsolationLevel::Serializable)
+        );
+    }
+
+    #[test]
+    fn test_list_append_write_skew_violates_serializable_only() {
+        // Both read the other's empty list, then append to their own.
+        let mut history = TxnHistory::new();
+        history.push(
+            1,
+            TxnOutcome::Committed,
+            vec![read_list(2, &[]), append(1, 1)],
+        );
+        history.push(
+            2,
+            TxnOutcome::Committed,
+            vec![read_list(1, &[]), append(2, 2)],
+        );
+        history.push(
+            3,
+            TxnOutcome::Committed,
+            vec![read_list(1, &[1]), read_list(2, &[2])],
+        );
+
+        let report = check(&history);
+        assert_eq!(report.weakest_violated, Some(IsolationLevel::Serializable));
+        assert!(report.satisfies(IsolationLevel::SnapshotIsolation));
+        assert_eq!(report.anomalies[0].anomaly, Anomaly::G2Item);
+    }
+
+    #[test]
+    fn test_list_append_read_skew_violates_si() {
+        // T3 sees T1's append to key 1 but not to key 2.
+        let mut history = TxnHistory::new();
+        history.push(1, TxnOutcome::Committed, vec![append(1, 1), append(2, 1)]);
+        history.push(
+            3,
+            TxnOutcome::Committed,
+            vec![read_list(1, &[1]), read_list(2, &[])],
+        );
+
+        let report = check(&history);
+        assert_eq!(
+            report.weakest_violated,
+            Some(IsolationLevel::SnapshotIsolation)
+        );
+        assert!(report.satisfies(IsolationLevel::ReadCommitted));
+    }
+
+    #[test]
+    fn test_aborted_read_violates_read_committed() {
+        let mut history = TxnHistory::new();
+        history.push(1, TxnOutcome::Aborted, vec![append(1, 1)]);
+        history.push(2, TxnOutcome::Committed, vec![read_list(1, &[1])]);
+
+        let report = check(&history);
+        assert_eq!(report.weakest_violated, Some(IsolationLevel::ReadCommitted));
+        assert_eq!(report.anomalies[0].anomaly, Anomaly::G1a);
+        assert!(!check_isolation(&history, IsolationLevel::ReadCommitted).holds);
+    }
+
+    #[test]
+    fn test_incompatible_order_detected() {
+        let mut history = TxnHistory::new();
+        history.push(1, TxnOutcome::Committed, vec![append(1, 1)]);
+        history.push(2, TxnOutcome::Committed, vec![append(1, 2)]);
+        history.push(3, TxnOutcome::Committed, vec![read_list(1, &[1, 2])]);
+        history.push(4, TxnOutcome::Committed, vec![read_list(1, &[2, 1])]);
+
+        let report = check(&history);
+        assert!(report
+            .anomalies
+            .iter()
+            .any(|a| a.anomaly == Anomaly::IncompatibleOrder));
+    }
+
+    #[test]
+    fn test_internal_inconsistency_detected() {
+        let mut history = TxnHistory::new();
+        history.push(
+            1,
+            TxnOutcome::Committed,
+            vec![append(1, 1), read_list(1, &[])],
+        );
+
+        let report = check(&history);
+        assert_eq!(report.anomalies[0].anomaly, Anomaly::Internal);
+    }
+
+    #[test]
+    fn test_register_write_skew_violates_serializable() {
+        let mut history = TxnHistory::new();
+        history.push(1, TxnOutcome::Committed, vec![read(1, None), write(2, 10)]);
+        history.push(2, TxnOutcome::Committed, vec![read(2, None), write(1, 20)]);
+
+        let report = check(&history);
+        assert_eq!(report.weakest_violated, Some(IsolationLevel::Serializable));
+        assert!(check_isolation(&history, IsolationLevel::SnapshotIsolation).holds);
+    }
+
+    #[test]
+    fn test_register_lost_update_violates_si() {
+        // Both read the initial value and overwrite it.
+        let mut history = TxnHistory::new();
+        history.push(1, TxnOutcome::Committed, vec![read(1, None), write(1, 10)]);
+        history.push(2, TxnOutcome::Committed, vec![read(1, None), write(1, 20)]);
+        history.push(3, TxnOutcome::Committed, vec![read(1, Some(20))]);
+
+        let report = check(&history);
+        assert!(!report.satisfies(IsolationLevel::SnapshotIsolation));
+    }
+
+    #[test]
+    fn test_from_ssi_history() {
+        let mut ssi = SsiHistory::new();
+        ssi.begin(1, 1);
+        ssi.begin(2, 2);
+        ssi.read(1, 100, None, 3);
+        ssi.read(2, 200, None, 4);
+        ssi.write(1, 200, 5);
+        ssi.write(2, 100, 6);
+        ssi.commit(1, 7);
+        ssi.commit(2, 8);
+
+        let history = TxnHistory::from_ssi_history(&ssi);
+        assert_eq!(history.transactions.len(), 2);
+        assert_eq!(
+            history.transactions[0].mops,
+            vec![read(100, None), write(200, 1)]
+        );
+
+        let report = check(&history);
+        assert_eq!(report.weakest_violated, Some(IsolationLevel::Serializable));
+    }
+}
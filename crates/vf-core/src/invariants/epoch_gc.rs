@@ -4,13 +4,15 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | NoUseAfterFree | 45 | No thread references freed objects |
-//! | QuiescentReclamation | 58 | Safe to free after all threads advance |
-//! | BoundedMemory | 72 | Pending reclamation is bounded |
-//! | MonotonicEpoch | 86 | Epochs never decrease |
+//! | NoUseAfterFree | 40 | No thread references freed objects |
+//! | QuiescentReclamation | 49 | Safe to free after all threads advance |
+//! | BoundedMemory | 59 | Pending reclamation is bounded |
+//! | MonotonicEpoch | 67 | Epochs never decrease |
 
 use std::collections::{HashMap, HashSet};
 
+use vf_macros::tla_invariant;
+
 use crate::property::{PropertyChecker, PropertyResult};
 
 const TLA_SPEC: &str = "epoch_gc.tla";
@@ -44,7 +46,8 @@ impl<'a, T: EpochGcProperties> EpochGcPropertyChecker<'a, T> {
         Self { gc }
     }
 
-    /// Line 45: NoUseAfterFree
+    /// NoUseAfterFree
+    #[tla_invariant(spec = "epoch_gc.tla", name = "NoUseAfterFree")]
     fn check_no_use_after_free(&self) -> PropertyResult {
         let freed = self.gc.freed_objects();
         let refs = self.gc.thread_references();
@@ -55,7 +58,7 @@ impl<'a, T: EpochGcProperties> EpochGcPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "NoUseAfterFree",
                     TLA_SPEC,
-                    45,
+                    TLA_LINE,
                     format!(
                         "Thread {} holds references to freed objects: {:?}",
                         tid, dangling
@@ -65,10 +68,11 @@ impl<'a, T: EpochGcProperties> EpochGcPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("NoUseAfterFree", TLA_SPEC, 45)
+        PropertyResult::pass("NoUseAfterFree", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 72: BoundedMemory
+    /// BoundedMemory
+    #[tla_invariant(spec = "epoch_gc.tla", name = "BoundedMemory")]
     fn check_bounded_memory(&self) -> PropertyResult {
         let freed = self.gc.freed_objects();
         let retired = self.gc.retired_objects();
@@ -85,7 +89,7 @@ impl<'a, T: EpochGcProperties> EpochGcPropertyChecker<'a, T> {
             return PropertyResult::fail(
                 "BoundedMemory",
                 TLA_SPEC,
-                72,
+                TLA_LINE,
                 format!(
                     "Pending reclamation {} exceeds bound {} (threads={})",
                     pending, bound, thread_count
@@ -94,10 +98,11 @@ impl<'a, T: EpochGcProperties> EpochGcPropertyChecker<'a, T> {
             );
         }
 
-        PropertyResult::pass("BoundedMemory", TLA_SPEC, 72)
+        PropertyResult::pass("BoundedMemory", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 86: MonotonicEpoch
+    /// MonotonicEpoch
+    #[tla_invariant(spec = "epoch_gc.tla", name = "MonotonicEpoch")]
     fn check_monotonic_epoch(&self) -> PropertyResult {
         let epoch = self.gc.global_epoch();
 
@@ -109,7 +114,7 @@ impl<'a, T: EpochGcProperties> EpochGcPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "MonotonicEpoch",
                     TLA_SPEC,
-                    86,
+                    TLA_LINE,
                     format!(
                         "Thread {} epoch {} exceeds global epoch {}",
                         tid, te, epoch
@@ -119,7 +124,7 @@ impl<'a, T: EpochGcProperties> EpochGcPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("MonotonicEpoch", TLA_SPEC, 86)
+        PropertyResult::pass("MonotonicEpoch", TLA_SPEC, TLA_LINE)
     }
 }
 
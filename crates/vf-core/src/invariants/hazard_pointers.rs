@@ -0,0 +1,427 @@
+//! Hazard pointer reclamation invariants from hazard_pointers.tla
+//!
+//! Object IDs are logical: an implementation that reuses addresses must
+//! map each allocation to a fresh ID so a freed object is never confused
+//! with a live one at the same address.
+//!
+//! # TLA+ Mapping
+//!
+//! | Property | TLA+ Line | Description |
+//! |----------|-----------|-------------|
+//! | NoReclaimWhileProtected | 66 | No validated reference points at freed memory |
+//! | ProtectedIsPublished | 73 | Dereferenced objects are in a hazard slot |
+//! | RetiredUnreachable | 81 | Retired and freed objects are unlinked |
+//! | BoundedGarbage | 90 | Unreclaimed garbage stays below the scan bound |
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use vf_macros::tla_invariant;
+
+use crate::counterexample::{Counterexample, StateSnapshot};
+use crate::property::{PropertyChecker, PropertyResult};
+
+/// TLA+ spec file for hazard pointer invariants.
+const TLA_SPEC: &str = "hazard_pointers.tla";
+
+/// Properties that any hazard pointer domain must satisfy.
+pub trait HazardPointerProperties {
+    /// Map: thread_id -> object IDs published in its hazard slots.
+    fn hazards(&self) -> HashMap<u64, HashSet<u64>>;
+
+    /// Map: thread_id -> validated object IDs the thread may dereference.
+    fn protected(&self) -> HashMap<u64, HashSet<u64>>;
+
+    /// Object IDs still linked into the data structure.
+    fn reachable_objects(&self) -> HashSet<u64>;
+
+    /// Object IDs retired but not yet freed.
+    fn retired_objects(&self) -> HashSet<u64>;
+
+    /// Object IDs that have been freed.
+    fn freed_objects(&self) -> HashSet<u64>;
+
+    /// Hazard slots owned by each thread (`HazardsPerThread`).
+    fn hazards_per_thread(&self) -> u64;
+
+    /// Retired list length that triggers a scan (`ScanThreshold`).
+    fn scan_threshold(&self) -> u64;
+}
+
+/// Property checker for hazard pointer implementations.
+pub struct HazardPointerPropertyChecker<'a, T: HazardPointerProperties> {
+    domain: &'a T,
+    dst_seed: Option<u64>,
+}
+
+/// Domain state, as the spec's variables.
+struct HazardState {
+    reachable: BTreeSet<u64>,
+    hazard: BTreeMap<u64, BTreeSet<u64>>,
+    protected: BTreeMap<u64, BTreeSet<u64>>,
+    retired: BTreeSet<u64>,
+    freed: BTreeSet<u64>,
+}
+
+impl<'a, T: HazardPointerProperties> HazardPointerPropertyChecker<'a, T> {
+    /// Create a new checker for the given implementation.
+    #[must_use]
+    pub fn new(domain: &'a T) -> Self {
+        Self {
+            domain,
+            dst_seed: None,
+        }
+    }
+
+    /// Set DST seed for counterexample reproduction.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        debug_assert!(seed != 0, "DST seed should not be zero");
+        self.dst_seed = Some(seed);
+        self
+    }
+
+    fn state(&self) -> HazardState {
+        let per_thread = |map: HashMap<u64, HashSet<u64>>| -> BTreeMap<u64, BTreeSet<u64>> {
+            map.into_iter()
+                .map(|(tid, objs)| (tid, objs.into_iter().collect()))
+                .collect()
+        };
+
+        let mut hazard = per_thread(self.domain.hazards());
+        let mut protected = per_thread(self.domain.protected());
+        // Both maps range over every thread, as in the spec
+        for tid in protected.keys() {
+            hazard.entry(*tid).or_default();
+        }
+        for tid in hazard.keys() {
+            protected.entry(*tid).or_default();
+        }
+
+        HazardState {
+            reachable: self.domain.reachable_objects().into_iter().collect(),
+            hazard,
+            protected,
+            retired: self.domain.retired_objects().into_iter().collect(),
+            freed: self.domain.freed_objects().into_iter().collect(),
+        }
+    }
+
+    /// NoReclaimWhileProtected
+    #[tla_invariant(spec = "hazard_pointers.tla", name = "NoReclaimWhileProtected")]
+    fn check_no_reclaim_while_protected(&self, s: &HazardState) -> PropertyResult {
+        for (tid, objs) in &s.protected {
+            let dangling: Vec<u64> = objs.intersection(&s.freed).copied().collect();
+            if !dangling.is_empty() {
+                return self.fail(
+                    "NoReclaimWhileProtected",
+                    TLA_LINE,
+                    s,
+                    format!("Thread {} dereferences freed objects {:?}", tid, dangling),
+                );
+            }
+        }
+
+        PropertyResult::pass("NoReclaimWhileProtected", TLA_SPEC, TLA_LINE)
+    }
+
+    /// ProtectedIsPublished
+    #[tla_invariant(spec = "hazard_pointers.tla", name = "ProtectedIsPublished")]
+    fn check_protected_is_published(&self, s: &HazardState) -> PropertyResult {
+        for (tid, objs) in &s.protected {
+            let unpublished: Vec<u64> = objs.difference(&s.hazard[tid]).copied().collect();
+            if !unpublished.is_empty() {
+                return self.fail(
+                    "ProtectedIsPublished",
+                    TLA_LINE,
+                    s,
+                    format!(
+                        "Thread {} dereferences objects {:?} without a hazard slot",
+                        tid, unpublished
+                    ),
+                );
+            }
+        }
+
+        PropertyResult::pass("ProtectedIsPublished", TLA_SPEC, TLA_LINE)
+    }
+
+    /// RetiredUnreachable
+    #[tla_invariant(spec = "hazard_pointers.tla", name = "RetiredUnreachable")]
+    fn check_retired_unreachable(&self, s: &HazardState) -> PropertyResult {
+        let linked: Vec<u64> = s
+            .retired
+            .union(&s.freed)
+            .filter(|obj| s.reachable.contains(obj))
+            .copied()
+            .collect();
+        if !linked.is_empty() {
+            return self.fail(
+                "RetiredUnreachable",
+                TLA_LINE,
+                s,
+                format!("Objects {:?} reclaimed while still linked", linked),
+            );
+        }
+
+        PropertyResult::pass("RetiredUnreachable", TLA_SPEC, TLA_LINE)
+    }
+
+    /// BoundedGarbage
+    #[tla_invariant(spec = "hazard_pointers.tla", name = "BoundedGarbage")]
+    fn check_bounded_garbage(&self, s: &HazardState) -> PropertyResult {
+        let threshold = self.domain.scan_threshold();
+        let hazards_max = self.domain.hazards_per_thread() * s.hazard.len() as u64;
+        let bound = threshold + hazards_max;
+        let pending = s.retired.len() as u64;
+
+        if pending > bound {
+            return self.fail(
+                "BoundedGarbage",
+                TLA_LINE,
+                s,
+                format!(
+                    "{} retired objects exceed bound {} (threshold={}, hazards={})",
+                    pending, bound, threshold, hazards_max
+                ),
+            );
+        }
+
+        PropertyResult::pass("BoundedGarbage", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Failing result with the domain state as a TLA+ counterexample.
+    fn fail(
+        &self,
+        name: &'static str,
+        line: u32,
+        s: &HazardState,
+        violation: String,
+    ) -> PropertyResult {
+        let mut ce = match self.dst_seed {
+            Some(seed) => Counterexample::with_seed(seed),
+            None => Counterexample::new(),
+        };
+
+        ce.add_state(StateSnapshot {
+            step: 1,
+            description: "Domain state".to_string(),
+            variables: vec![
+                ("reachable".to_string(), tla_set(&s.reachable)),
+                ("hazard".to_string(), tla_function(&s.hazard)),
+                ("protected".to_string(), tla_function(&s.protected)),
+                ("retired".to_string(), tla_set(&s.retired)),
+                ("freed".to_string(), tla_set(&s.freed)),
+            ],
+        });
+
+        PropertyResult::fail(
+            name,
+            TLA_SPEC,
+            line,
+            violation.clone(),
+            Some(ce.with_description(violation)),
+        )
+    }
+}
+
+fn tla_set(objs: &BTreeSet<u64>) -> String {
+    let items: Vec<String> = objs.iter().map(u64::to_string).collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+fn tla_function(map: &BTreeMap<u64, BTreeSet<u64>>) -> String {
+    if map.is_empty() {
+        return "[t \\in {} |-> {}]".to_string();
+    }
+    let entries: Vec<String> = map
+        .iter()
+        .map(|(tid, objs)| format!("{} :> {}", tid, tla_set(objs)))
+        .collect();
+    format!("({})", entries.join(" @@ "))
+}
+
+impl<T: HazardPointerProperties> PropertyChecker for HazardPointerPropertyChecker<'_, T> {
+    fn check_all(&self) -> Vec<PropertyResult> {
+        let state = self.state();
+        vec![
+            self.check_no_reclaim_while_protected(&state),
+            self.check_protected_is_published(&state),
+            self.check_retired_unreachable(&state),
+            self.check_bounded_garbage(&state),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tla_spec::{parse_module, Evaluator, TlaSpec, Value};
+
+    /// Test implementation of HazardPointerProperties
+    #[derive(Default)]
+    struct TestDomain {
+        hazards: HashMap<u64, HashSet<u64>>,
+        protected: HashMap<u64, HashSet<u64>>,
+        reachable: HashSet<u64>,
+        retired: HashSet<u64>,
+        freed: HashSet<u64>,
+    }
+
+    impl TestDomain {
+        fn linked(mut self, objs: &[u64]) -> Self {
+            self.reachable.extend(objs);
+            self
+        }
+
+        fn retired(mut self, objs: &[u64]) -> Self {
+            self.retired.extend(objs);
+            self
+        }
+
+        fn freed(mut self, objs: &[u64]) -> Self {
+            self.freed.extend(objs);
+            self
+        }
+
+        /// Thread `tid` publishes `hazards` and validated `protected`.
+        fn thread(mut self, tid: u64, hazards: &[u64], protected: &[u64]) -> Self {
+            self.hazards.insert(tid, hazards.iter().copied().collect());
+            self.protected
+                .insert(tid, protected.iter().copied().collect());
+            self
+        }
+    }
+
+    impl HazardPointerProperties for TestDomain {
+        fn hazards(&self) -> HashMap<u64, HashSet<u64>> {
+            self.hazards.clone()
+        }
+
+        fn protected(&self) -> HashMap<u64, HashSet<u64>> {
+            self.protected.clone()
+        }
+
+        fn reachable_objects(&self) -> HashSet<u64> {
+            self.reachable.clone()
+        }
+
+        fn retired_objects(&self) -> HashSet<u64> {
+            self.retired.clone()
+        }
+
+        fn freed_objects(&self) -> HashSet<u64> {
+            self.freed.clone()
+        }
+
+        fn hazards_per_thread(&self) -> u64 {
+            1
+        }
+
+        fn scan_threshold(&self) -> u64 {
+            4
+        }
+    }
+
+    fn failing(domain: &TestDomain) -> Vec<&'static str> {
+        HazardPointerPropertyChecker::new(domain)
+            .check_all()
+            .into_iter()
+            .filter(|r| !r.holds)
+            .map(|r| r.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_correct_domain_passes_all() {
+        // Thread 0 protects retired 3, which the scan kept; thread 1 holds
+        // a stale hazard on freed 4 that it never validated
+        let domain = TestDomain::default()
+            .linked(&[1, 2])
+            .retired(&[3])
+            .freed(&[4, 5])
+            .thread(0, &[1, 3], &[1, 3])
+            .thread(1, &[4], &[]);
+
+        assert!(HazardPointerPropertyChecker::new(&domain).all_hold());
+    }
+
+    #[test]
+    fn test_reclaim_while_protected_detected() {
+        let domain = TestDomain::default()
+            .linked(&[1])
+            .freed(&[2])
+            .thread(0, &[2], &[2]);
+
+        assert_eq!(failing(&domain), vec!["NoReclaimWhileProtected"]);
+    }
+
+    #[test]
+    fn test_unpublished_reference_detected() {
+        let domain = TestDomain::default().linked(&[1]).thread(0, &[], &[1]);
+
+        assert_eq!(failing(&domain), vec!["ProtectedIsPublished"]);
+    }
+
+    #[test]
+    fn test_garbage_bound_detected() {
+        // Threshold 4 plus one hazard slot for one thread
+        let domain = TestDomain::default()
+            .retired(&[1, 2, 3, 4, 5])
+            .thread(0, &[], &[]);
+        assert!(failing(&domain).is_empty());
+
+        let domain = domain.retired(&[6]);
+        assert_eq!(failing(&domain), vec!["BoundedGarbage"]);
+    }
+
+    #[test]
+    fn test_counterexample_violates_spec() {
+        let domain = TestDomain::default()
+            .linked(&[1, 3])
+            .retired(&[3])
+            .freed(&[2])
+            .thread(0, &[2], &[2])
+            .thread(1, &[], &[]);
+        let results = HazardPointerPropertyChecker::new(&domain)
+            .with_seed(5)
+            .check_all();
+        let ce = results[0].counterexample.clone().unwrap();
+        assert_eq!(ce.dst_seed, Some(5));
+
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/lockfree/hazard_pointers.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let mut evaluator = Evaluator::new(&module)
+            .with_constant("Threads", Value::set([Value::Int(0), Value::Int(1)]))
+            .with_constant("HazardsPerThread", Value::Int(1))
+            .with_constant("ScanThreshold", Value::Int(4))
+            .with_model_constants();
+        evaluator.load_state(&ce.states[0]).unwrap();
+        assert!(!evaluator.check("NoReclaimWhileProtected").unwrap());
+        assert!(!evaluator.check("RetiredUnreachable").unwrap());
+        assert!(evaluator.check("ProtectedIsPublished").unwrap());
+        assert!(evaluator.check("BoundedGarbage").unwrap());
+    }
+
+    #[test]
+    fn test_lines_match_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/lockfree/hazard_pointers.tla"
+        ))
+        .unwrap();
+        let spec = TlaSpec::parse(&content).unwrap();
+
+        for result in HazardPointerPropertyChecker::new(&TestDomain::default()).check_all() {
+            let mapped = spec
+                .invariants
+                .iter()
+                .find(|inv| inv.name == result.name)
+                .unwrap();
+            assert_eq!(mapped.line, result.tla_line, "{}", result.name);
+        }
+    }
+}
@@ -4,13 +4,15 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | NoDataCorruption | 45 | Every submitted item appears in flushed output |
-//! | CompletionGuarantee | 58 | Every submitted item is eventually flushed |
-//! | OrderPreserved | 72 | Per-thread submission order is maintained |
-//! | BoundedMemory | 86 | Buffer never exceeds configured size |
+//! | NoDataCorruption | 41 | Every submitted item appears in flushed output |
+//! | CompletionGuarantee | 52 | Every submitted item is eventually flushed |
+//! | OrderPreserved | 59 | Per-thread submission order is maintained |
+//! | BoundedMemory | 66 | Buffer never exceeds configured size |
 
 use std::collections::HashSet;
 
+use vf_macros::tla_invariant;
+
 use crate::property::{PropertyChecker, PropertyResult};
 
 const TLA_SPEC: &str = "io_buffer.tla";
@@ -41,7 +43,8 @@ impl<'a, T: IoBufferProperties> IoBufferPropertyChecker<'a, T> {
         Self { buffer }
     }
 
-    /// Line 45: NoDataCorruption
+    /// NoDataCorruption
+    #[tla_invariant(spec = "io_buffer.tla", name = "NoDataCorruption")]
     fn check_no_data_corruption(&self) -> PropertyResult {
         let submitted = self.buffer.submitted_items();
         let flushed: HashSet<u64> = self.buffer.flushed_items().into_iter().collect();
@@ -52,7 +55,7 @@ impl<'a, T: IoBufferProperties> IoBufferPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "NoDataCorruption",
                     TLA_SPEC,
-                    45,
+                    TLA_LINE,
                     format!(
                         "Item {} was submitted but is neither flushed nor buffered",
                         item
@@ -62,10 +65,11 @@ impl<'a, T: IoBufferProperties> IoBufferPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("NoDataCorruption", TLA_SPEC, 45)
+        PropertyResult::pass("NoDataCorruption", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 72: OrderPreserved
+    /// OrderPreserved
+    #[tla_invariant(spec = "io_buffer.tla", name = "OrderPreserved")]
     fn check_order_preserved(&self) -> PropertyResult {
         let submitted = self.buffer.submitted_items();
         let flushed = self.buffer.flushed_items();
@@ -76,7 +80,7 @@ impl<'a, T: IoBufferProperties> IoBufferPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "OrderPreserved",
                     TLA_SPEC,
-                    72,
+                    TLA_LINE,
                     format!(
                         "Flushed item at index {} is {} but submitted was {}",
                         i, item, submitted[i]
@@ -86,10 +90,11 @@ impl<'a, T: IoBufferProperties> IoBufferPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("OrderPreserved", TLA_SPEC, 72)
+        PropertyResult::pass("OrderPreserved", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 86: BoundedMemory
+    /// BoundedMemory
+    #[tla_invariant(spec = "io_buffer.tla", name = "BoundedMemory")]
     fn check_bounded_memory(&self) -> PropertyResult {
         let buffered = self.buffer.buffered_items();
         let max = self.buffer.buffer_size_max();
@@ -98,7 +103,7 @@ impl<'a, T: IoBufferProperties> IoBufferPropertyChecker<'a, T> {
             return PropertyResult::fail(
                 "BoundedMemory",
                 TLA_SPEC,
-                86,
+                TLA_LINE,
                 format!(
                     "Buffer contains {} items but max is {}",
                     buffered.len(),
@@ -108,7 +113,7 @@ impl<'a, T: IoBufferProperties> IoBufferPropertyChecker<'a, T> {
             );
         }
 
-        PropertyResult::pass("BoundedMemory", TLA_SPEC, 86)
+        PropertyResult::pass("BoundedMemory", TLA_SPEC, TLA_LINE)
     }
 }
 
@@ -4,13 +4,15 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | NoLostElements | 45 | Every inserted-not-removed key is reachable |
-//! | NoDuplicates | 58 | No key appears twice in list |
-//! | Reachability | 72 | All live nodes reachable from head |
-//! | InsertOrderPreserved | 86 | Keys in sorted order |
+//! | NoLostElements | 53 | Every inserted-not-removed key is reachable |
+//! | NoDuplicates | 62 | No key appears twice in list |
+//! | Reachability | 70 | All live nodes reachable from head |
+//! | InsertOrderPreserved | 88 | Keys in sorted order |
 
 use std::collections::HashSet;
 
+use vf_macros::tla_invariant;
+
 use crate::counterexample::Counterexample;
 use crate::property::{PropertyChecker, PropertyResult};
 
@@ -50,7 +52,8 @@ impl<'a, T: LinkedListProperties> LinkedListPropertyChecker<'a, T> {
         self
     }
 
-    /// Line 45: NoLostElements
+    /// NoLostElements
+    #[tla_invariant(spec = "linked_list.tla", name = "NoLostElements")]
     fn check_no_lost_elements(&self) -> PropertyResult {
         let inserted = self.list.inserted_keys();
         let removed = self.list.removed_keys();
@@ -76,17 +79,18 @@ impl<'a, T: LinkedListProperties> LinkedListPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "NoLostElements",
                     TLA_SPEC,
-                    45,
+                    TLA_LINE,
                     format!("Key {} was inserted but is not reachable from head", key),
                     Some(ce),
                 );
             }
         }
 
-        PropertyResult::pass("NoLostElements", TLA_SPEC, 45)
+        PropertyResult::pass("NoLostElements", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 58: NoDuplicates
+    /// NoDuplicates
+    #[tla_invariant(spec = "linked_list.tla", name = "NoDuplicates")]
     fn check_no_duplicates(&self) -> PropertyResult {
         let reachable = self.list.reachable_keys();
         let unique: HashSet<u64> = reachable.iter().copied().collect();
@@ -98,7 +102,7 @@ impl<'a, T: LinkedListProperties> LinkedListPropertyChecker<'a, T> {
                     return PropertyResult::fail(
                         "NoDuplicates",
                         TLA_SPEC,
-                        58,
+                        TLA_LINE,
                         format!("Key {} appears multiple times in list", key),
                         None,
                     );
@@ -106,10 +110,11 @@ impl<'a, T: LinkedListProperties> LinkedListPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("NoDuplicates", TLA_SPEC, 58)
+        PropertyResult::pass("NoDuplicates", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 86: InsertOrderPreserved (sorted order)
+    /// InsertOrderPreserved (sorted order)
+    #[tla_invariant(spec = "linked_list.tla", name = "InsertOrderPreserved")]
     fn check_insert_order_preserved(&self) -> PropertyResult {
         let reachable = self.list.reachable_keys();
 
@@ -118,7 +123,7 @@ impl<'a, T: LinkedListProperties> LinkedListPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "InsertOrderPreserved",
                     TLA_SPEC,
-                    86,
+                    TLA_LINE,
                     format!(
                         "Keys not sorted: {} >= {} at indices {}, {}",
                         reachable[i - 1],
@@ -131,7 +136,7 @@ impl<'a, T: LinkedListProperties> LinkedListPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("InsertOrderPreserved", TLA_SPEC, 86)
+        PropertyResult::pass("InsertOrderPreserved", TLA_SPEC, TLA_LINE)
     }
 }
 
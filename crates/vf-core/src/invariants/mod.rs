@@ -5,39 +5,75 @@
 //!
 //! ## Lock-free structures
 //! - `stack`: Treiber Stack invariants (NoLostElements, NoDuplicates, LIFO)
+//! - `queue`: Michael-Scott Queue invariants (NoLostElements, NoDuplicates, FIFO)
 //! - `ring_buffer`: Ring Buffer invariants (NoLostMessages, FIFO_Order, BoundedCapacity)
 //! - `linked_list`: Harris Linked List invariants (NoLostElements, Sorted, NoDuplicates)
 //! - `radix_tree`: Radix Tree invariants (PrefixConsistency, NoLostKeys)
 //! - `io_buffer`: I/O Buffer invariants (NoDataCorruption, OrderPreserved, BoundedMemory)
 //! - `epoch_gc`: Epoch GC invariants (NoUseAfterFree, BoundedMemory, MonotonicEpoch)
+//! - `hazard_pointers`: Hazard pointer invariants (NoReclaimWhileProtected, BoundedGarbage)
 //! - `pagecache`: Page Cache invariants (AtomicPageUpdate, NoLostPages, CrashConsistency)
 //! - `btree_plus`: B+ Tree invariants (SortedOrder, BalancedHeight, NoLostKeys)
 //!
 //! ## Lock-based protocols
 //! - `ssi`: Serializable Snapshot Isolation (FirstCommitterWins, Serializable)
+//! - `two_phase_locking`: Strict 2PL with deadlock detection (LockCompatibility, NoDeadlock)
+//! - `snapshot_isolation`: Multi-version snapshot isolation (FirstCommitterWins, SnapshotRead)
+//! - `dsg`: Direct serialization graph anomalies (G0, G1a-c, G-single, G2-item)
+//! - `elle`: Elle-style isolation checking for list-append and rw-register histories
 //! - `cross_shard_ssi`: Cross-Shard SSI (CrossShardAtomicity, Serializable)
+//!
+//! ## Distributed protocols
+//! - `raft`: Raft consensus (ElectionSafety, LogMatching, LeaderCompleteness, StateMachineSafety)
+//! - `two_phase_commit`: Two-phase commit (Atomicity, Validity, Consistency, Agreement)
 
 pub mod btree_plus;
 pub mod cross_shard_ssi;
+pub mod dsg;
+pub mod elle;
 pub mod epoch_gc;
+pub mod hazard_pointers;
 pub mod io_buffer;
 pub mod linked_list;
 pub mod pagecache;
+pub mod queue;
 pub mod radix_tree;
+pub mod raft;
 pub mod ring_buffer;
+pub mod snapshot_isolation;
 pub mod ssi;
 pub mod stack;
+pub mod two_phase_commit;
+pub mod two_phase_locking;
 
 pub use btree_plus::{BTreePlusProperties, BTreePlusPropertyChecker};
 pub use cross_shard_ssi::{CrossShardSsiProperties, CrossShardSsiPropertyChecker, CrossShardTxnStatus};
+pub use dsg::{
+    find_anomalies, Anomaly, AnomalyReport, DependencyEdge, DependencyGraph, DependencyKind,
+};
+pub use elle::{
+    check_isolation, IsolationLevel, IsolationReport, Mop, Transaction, TxnHistory, TxnOutcome,
+};
 pub use epoch_gc::{EpochGcProperties, EpochGcPropertyChecker};
+pub use hazard_pointers::{HazardPointerProperties, HazardPointerPropertyChecker};
 pub use io_buffer::{IoBufferProperties, IoBufferPropertyChecker};
 pub use linked_list::{LinkedListProperties, LinkedListPropertyChecker};
 pub use pagecache::{PageCacheProperties, PageCachePropertyChecker, PageState};
+pub use queue::{QueueHistory, QueueOperation, QueueProperties, QueuePropertyChecker};
 pub use radix_tree::{RadixTreeProperties, RadixTreePropertyChecker};
+pub use raft::{LogEntry, RaftProperties, RaftPropertyChecker, RaftRole, ServerId};
 pub use ring_buffer::{RingBufferProperties, RingBufferPropertyChecker};
+pub use snapshot_isolation::{
+    SnapshotIsolationProperties, SnapshotIsolationPropertyChecker, SnapshotReadRecord,
+};
 pub use ssi::{
     check_all as check_all_ssi, first_committer_wins, is_serializable,
-    no_committed_dangerous_structures, no_lost_writes, InvariantResult, SsiHistory,
+    no_committed_dangerous_structures, no_lost_writes, no_phantoms, read_only_no_writes,
+    safe_snapshot_acyclic, vacuum_keeps_visible, InvariantResult, RangeReadRecord, SsiHistory,
+    VacuumRecord,
 };
 pub use stack::{StackHistory, StackOperation, StackProperties, StackPropertyChecker};
+pub use two_phase_commit::{
+    RmId, RmState, TmState, TwoPhaseCommitProperties, TwoPhaseCommitPropertyChecker, TxnId,
+};
+pub use two_phase_locking::{TwoPhaseLockingProperties, TwoPhaseLockingPropertyChecker};
@@ -4,13 +4,15 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | AtomicPageUpdate | 45 | Page writes appear atomic |
-//! | NoLostPages | 58 | Every written page is in cache or on disk |
-//! | CrashConsistency | 72 | Recovered state is consistent |
-//! | NoDirtyReads | 86 | Reads return latest committed version |
+//! | AtomicPageUpdate | 41 | Page writes appear atomic |
+//! | NoLostPages | 50 | Every written page is in cache or on disk |
+//! | CrashConsistency | 60 | Recovered state is consistent |
+//! | NoDirtyReads | 68 | Reads return latest committed version |
 
 use std::collections::HashMap;
 
+use vf_macros::tla_invariant;
+
 use crate::property::{PropertyChecker, PropertyResult};
 
 const TLA_SPEC: &str = "pagecache.tla";
@@ -48,7 +50,8 @@ impl<'a, T: PageCacheProperties> PageCachePropertyChecker<'a, T> {
         Self { cache }
     }
 
-    /// Line 45: AtomicPageUpdate
+    /// AtomicPageUpdate
+    #[tla_invariant(spec = "pagecache.tla", name = "AtomicPageUpdate")]
     fn check_atomic_page_update(&self) -> PropertyResult {
         let cached = self.cache.cached_pages();
 
@@ -58,17 +61,18 @@ impl<'a, T: PageCacheProperties> PageCachePropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "AtomicPageUpdate",
                     TLA_SPEC,
-                    45,
+                    TLA_LINE,
                     format!("Page {} has version 0 but is marked dirty (partial write?)", pid),
                     None,
                 );
             }
         }
 
-        PropertyResult::pass("AtomicPageUpdate", TLA_SPEC, 45)
+        PropertyResult::pass("AtomicPageUpdate", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 58: NoLostPages
+    /// NoLostPages
+    #[tla_invariant(spec = "pagecache.tla", name = "NoLostPages")]
     fn check_no_lost_pages(&self) -> PropertyResult {
         let cached = self.cache.cached_pages();
         let disk = self.cache.disk_pages();
@@ -79,17 +83,18 @@ impl<'a, T: PageCacheProperties> PageCachePropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "NoLostPages",
                     TLA_SPEC,
-                    58,
+                    TLA_LINE,
                     format!("Page {} was written but is neither in cache nor on disk", pid),
                     None,
                 );
             }
         }
 
-        PropertyResult::pass("NoLostPages", TLA_SPEC, 58)
+        PropertyResult::pass("NoLostPages", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 72: CrashConsistency
+    /// CrashConsistency
+    #[tla_invariant(spec = "pagecache.tla", name = "CrashConsistency")]
     fn check_crash_consistency(&self) -> PropertyResult {
         let disk = self.cache.disk_pages();
 
@@ -98,14 +103,14 @@ impl<'a, T: PageCacheProperties> PageCachePropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "CrashConsistency",
                     TLA_SPEC,
-                    72,
+                    TLA_LINE,
                     format!("Page {} on disk has invalid version 0", pid),
                     None,
                 );
             }
         }
 
-        PropertyResult::pass("CrashConsistency", TLA_SPEC, 72)
+        PropertyResult::pass("CrashConsistency", TLA_SPEC, TLA_LINE)
     }
 }
 
@@ -0,0 +1,580 @@
+//! Queue invariants from ms_queue.tla
+//!
+//! # TLA+ Mapping
+//!
+//! | Property | TLA+ Line | Description |
+//! |----------|-----------|-------------|
+//! | NoLostElements | 80 | Every enqueued element is in queue or was dequeued |
+//! | NoDuplicates | 88 | No element is queued twice or dequeued twice |
+//! | FIFO_Order | 97 | First-in-first-out ordering |
+//! | Linearizability | 107 | Operations appear atomic |
+//!
+//! `TailReachable` (line 115) is a property of the linked representation
+//! and is checked by the stateright model, not through this trait.
+
+use std::collections::{HashSet, VecDeque};
+
+use vf_macros::tla_invariant;
+
+use crate::counterexample::Counterexample;
+use crate::linearizability::{ConcurrentHistory, LinearizabilityChecker, QueueOp, QueueSpec};
+use crate::property::{PropertyChecker, PropertyResult};
+
+/// TLA+ spec file for queue invariants.
+const TLA_SPEC: &str = "ms_queue.tla";
+
+/// Properties that any FIFO queue implementation must satisfy.
+///
+/// Implementations provide access to their internal state for
+/// property checking. The checker verifies invariants against
+/// this state.
+pub trait QueueProperties {
+    /// Set of all elements that have been enqueued.
+    fn enqueued_elements(&self) -> HashSet<u64>;
+
+    /// Set of all elements that have been dequeued.
+    fn dequeued_elements(&self) -> HashSet<u64>;
+
+    /// Current contents of the queue (front to back).
+    fn current_contents(&self) -> Vec<u64>;
+
+    /// Operation history for FIFO order checking.
+    /// Returns owned data to avoid lifetime issues with internal mutexes.
+    fn history(&self) -> QueueHistory;
+}
+
+/// History of queue operations for linearizability checking.
+#[derive(Debug, Clone, Default)]
+pub struct QueueHistory {
+    /// Sequence of operations in linearization order
+    pub operations: Vec<QueueOperation>,
+}
+
+/// A single queue operation.
+#[derive(Debug, Clone)]
+pub struct QueueOperation {
+    /// Thread that performed the operation
+    pub thread_id: u64,
+    /// Type of operation
+    pub op_type: QueueOpType,
+    /// Element involved (Some for enqueue, result for dequeue)
+    pub element: Option<u64>,
+    /// Step at which the operation was invoked (equal to `step` for
+    /// operations recorded as atomic)
+    pub invoked_at: u64,
+    /// Step number for ordering (when the operation returned)
+    pub step: u64,
+}
+
+/// Type of queue operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueOpType {
+    Enqueue,
+    Dequeue,
+    DequeueEmpty,
+}
+
+impl QueueHistory {
+    /// Create a new empty history.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+        }
+    }
+
+    /// Record an enqueue operation.
+    pub fn record_enqueue(&mut self, thread_id: u64, element: u64, step: u64) {
+        self.record_enqueue_interval(thread_id, element, step, step);
+    }
+
+    /// Record a dequeue operation.
+    pub fn record_dequeue(&mut self, thread_id: u64, element: Option<u64>, step: u64) {
+        self.record_dequeue_interval(thread_id, element, step, step);
+    }
+
+    /// Record an enqueue that was invoked at `invoked_at` and returned at `step`.
+    pub fn record_enqueue_interval(
+        &mut self,
+        thread_id: u64,
+        element: u64,
+        invoked_at: u64,
+        step: u64,
+    ) {
+        debug_assert!(invoked_at > 0, "Step must be positive");
+        debug_assert!(invoked_at <= step, "Response before invocation");
+        self.operations.push(QueueOperation {
+            thread_id,
+            op_type: QueueOpType::Enqueue,
+            element: Some(element),
+            invoked_at,
+            step,
+        });
+    }
+
+    /// Record a dequeue that was invoked at `invoked_at` and returned at `step`.
+    pub fn record_dequeue_interval(
+        &mut self,
+        thread_id: u64,
+        element: Option<u64>,
+        invoked_at: u64,
+        step: u64,
+    ) {
+        debug_assert!(invoked_at > 0, "Step must be positive");
+        debug_assert!(invoked_at <= step, "Response before invocation");
+        self.operations.push(QueueOperation {
+            thread_id,
+            op_type: if element.is_some() {
+                QueueOpType::Dequeue
+            } else {
+                QueueOpType::DequeueEmpty
+            },
+            element,
+            invoked_at,
+            step,
+        });
+    }
+
+    /// Convert to an invoke/response history for linearizability checking.
+    #[must_use]
+    pub fn to_concurrent_history(&self) -> ConcurrentHistory<QueueOp, Option<u64>> {
+        let mut history = ConcurrentHistory::new();
+        for op in &self.operations {
+            match op.op_type {
+                QueueOpType::Enqueue => {
+                    let element = op.element.expect("enqueue records its element");
+                    history.record(
+                        op.thread_id,
+                        QueueOp::Enqueue(element),
+                        None,
+                        op.invoked_at,
+                        op.step,
+                    );
+                }
+                QueueOpType::Dequeue | QueueOpType::DequeueEmpty => {
+                    history.record(
+                        op.thread_id,
+                        QueueOp::Dequeue,
+                        op.element,
+                        op.invoked_at,
+                        op.step,
+                    );
+                }
+            }
+        }
+        history
+    }
+}
+
+/// Property checker for queue implementations.
+///
+/// Verifies all invariants from ms_queue.tla against
+/// the implementation's state.
+pub struct QueuePropertyChecker<'a, T: QueueProperties> {
+    queue: &'a T,
+    dst_seed: Option<u64>,
+}
+
+impl<'a, T: QueueProperties> QueuePropertyChecker<'a, T> {
+    /// Create a new checker for the given queue.
+    #[must_use]
+    pub fn new(queue: &'a T) -> Self {
+        Self {
+            queue,
+            dst_seed: None,
+        }
+    }
+
+    /// Set DST seed for counterexample reproduction.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        debug_assert!(seed != 0, "DST seed should not be zero");
+        self.dst_seed = Some(seed);
+        self
+    }
+
+    /// NoLostElements
+    ///
+    /// Every element that was enqueued must either be in the queue
+    /// or have been dequeued. No elements can be lost.
+    #[tla_invariant(spec = "ms_queue.tla", name = "NoLostElements")]
+    fn check_no_lost_elements(&self) -> PropertyResult {
+        let enqueued = self.queue.enqueued_elements();
+        let dequeued = self.queue.dequeued_elements();
+        let contents: HashSet<u64> = self.queue.current_contents().into_iter().collect();
+
+        for element in &enqueued {
+            if !contents.contains(element) && !dequeued.contains(element) {
+                let mut ce = match self.dst_seed {
+                    Some(seed) => Counterexample::with_seed(seed),
+                    None => Counterexample::new(),
+                };
+                ce.add_state(crate::counterexample::StateSnapshot {
+                    step: 1,
+                    description: format!("Element {} lost", element),
+                    variables: vec![
+                        ("enqueued".to_string(), format!("{:?}", enqueued)),
+                        ("dequeued".to_string(), format!("{:?}", dequeued)),
+                        ("contents".to_string(), format!("{:?}", contents)),
+                    ],
+                });
+
+                return PropertyResult::fail(
+                    "NoLostElements",
+                    TLA_SPEC,
+                    TLA_LINE,
+                    format!(
+                        "Element {} was enqueued but is neither in queue nor dequeued",
+                        element
+                    ),
+                    Some(ce),
+                );
+            }
+        }
+
+        PropertyResult::pass("NoLostElements", TLA_SPEC, TLA_LINE)
+    }
+
+    /// NoDuplicates
+    ///
+    /// No element appears twice in the queue, and no element is
+    /// returned by more than one dequeue.
+    #[tla_invariant(spec = "ms_queue.tla", name = "NoDuplicates")]
+    fn check_no_duplicates(&self) -> PropertyResult {
+        let mut seen = HashSet::new();
+        for element in self.queue.current_contents() {
+            if !seen.insert(element) {
+                return PropertyResult::fail(
+                    "NoDuplicates",
+                    TLA_SPEC,
+                    TLA_LINE,
+                    format!("Element {} appears multiple times in queue", element),
+                    None,
+                );
+            }
+        }
+
+        let mut dequeued = HashSet::new();
+        for op in &self.queue.history().operations {
+            if op.op_type != QueueOpType::Dequeue {
+                continue;
+            }
+            if let Some(element) = op.element {
+                if !dequeued.insert(element) {
+                    return PropertyResult::fail(
+                        "NoDuplicates",
+                        TLA_SPEC,
+                        TLA_LINE,
+                        format!(
+                            "Element {} dequeued more than once (step {})",
+                            element, op.step
+                        ),
+                        None,
+                    );
+                }
+            }
+        }
+
+        PropertyResult::pass("NoDuplicates", TLA_SPEC, TLA_LINE)
+    }
+
+    /// FIFO_Order
+    ///
+    /// The queue maintains first-in-first-out ordering.
+    /// This is verified by replaying the operation history against
+    /// a model queue and checking that dequeue results match.
+    #[tla_invariant(spec = "ms_queue.tla", name = "FIFO_Order")]
+    fn check_fifo_order(&self) -> PropertyResult {
+        let history = self.queue.history();
+
+        if history.operations.is_empty() {
+            // No history to verify - the queue might just be unused
+            return PropertyResult::pass("FIFO_Order", TLA_SPEC, TLA_LINE);
+        }
+
+        let mut model_queue: VecDeque<u64> = VecDeque::new();
+
+        for op in &history.operations {
+            match op.op_type {
+                QueueOpType::Enqueue => {
+                    if let Some(e) = op.element {
+                        model_queue.push_back(e);
+                    }
+                }
+                QueueOpType::Dequeue => {
+                    let Some(actual) = op.element else {
+                        continue;
+                    };
+                    match model_queue.pop_front() {
+                        Some(expected) if expected != actual => {
+                            return PropertyResult::fail(
+                                "FIFO_Order",
+                                TLA_SPEC,
+                                TLA_LINE,
+                                format!(
+                                    "FIFO violated: dequeue returned {} but model expected {} (step {})",
+                                    actual, expected, op.step
+                                ),
+                                None,
+                            );
+                        }
+                        None => {
+                            return PropertyResult::fail(
+                                "FIFO_Order",
+                                TLA_SPEC,
+                                TLA_LINE,
+                                format!(
+                                    "FIFO violated: dequeue returned {} but model queue was empty (step {})",
+                                    actual, op.step
+                                ),
+                                None,
+                            );
+                        }
+                        _ => {} // Match - continue
+                    }
+                }
+                QueueOpType::DequeueEmpty => {
+                    if !model_queue.is_empty() {
+                        return PropertyResult::fail(
+                            "FIFO_Order",
+                            TLA_SPEC,
+                            TLA_LINE,
+                            format!(
+                                "FIFO violated: dequeue returned None but model has {} elements (step {})",
+                                model_queue.len(),
+                                op.step
+                            ),
+                            None,
+                        );
+                    }
+                }
+            }
+        }
+
+        PropertyResult::pass("FIFO_Order", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Linearizability
+    ///
+    /// All operations appear to take effect atomically at some point
+    /// between their invocation and response.
+    ///
+    /// Searches for a linearization of the recorded invoke/response
+    /// intervals against a sequential FIFO queue.
+    #[tla_invariant(spec = "ms_queue.tla", name = "Linearizability")]
+    fn check_linearizability(&self) -> PropertyResult {
+        let history = self.queue.history().to_concurrent_history();
+        LinearizabilityChecker::new(QueueSpec::default()).check_property(
+            &history,
+            TLA_SPEC,
+            TLA_LINE,
+            self.dst_seed,
+        )
+    }
+}
+
+impl<T: QueueProperties> PropertyChecker for QueuePropertyChecker<'_, T> {
+    fn check_all(&self) -> Vec<PropertyResult> {
+        vec![
+            self.check_no_lost_elements(),
+            self.check_no_duplicates(),
+            self.check_fifo_order(),
+            self.check_linearizability(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tla_spec::TlaSpec;
+
+    /// Test implementation of QueueProperties
+    struct TestQueue {
+        enqueued: HashSet<u64>,
+        dequeued: HashSet<u64>,
+        contents: VecDeque<u64>,
+        history: QueueHistory,
+    }
+
+    impl TestQueue {
+        fn new() -> Self {
+            Self {
+                enqueued: HashSet::new(),
+                dequeued: HashSet::new(),
+                contents: VecDeque::new(),
+                history: QueueHistory::new(),
+            }
+        }
+
+        fn enqueue(&mut self, val: u64) {
+            self.enqueued.insert(val);
+            self.contents.push_back(val);
+            self.history
+                .record_enqueue(0, val, self.history.operations.len() as u64 + 1);
+        }
+
+        fn dequeue(&mut self) -> Option<u64> {
+            let val = self.contents.pop_front();
+            if let Some(v) = val {
+                self.dequeued.insert(v);
+            }
+            self.history
+                .record_dequeue(0, val, self.history.operations.len() as u64 + 1);
+            val
+        }
+    }
+
+    impl QueueProperties for TestQueue {
+        fn enqueued_elements(&self) -> HashSet<u64> {
+            self.enqueued.clone()
+        }
+
+        fn dequeued_elements(&self) -> HashSet<u64> {
+            self.dequeued.clone()
+        }
+
+        fn current_contents(&self) -> Vec<u64> {
+            self.contents.iter().copied().collect()
+        }
+
+        fn history(&self) -> QueueHistory {
+            self.history.clone()
+        }
+    }
+
+    #[test]
+    fn test_correct_queue_passes_all() {
+        let mut queue = TestQueue::new();
+        queue.enqueue(1);
+        queue.enqueue(2);
+        queue.enqueue(3);
+        assert_eq!(queue.dequeue(), Some(1));
+        assert_eq!(queue.dequeue(), Some(2));
+
+        let checker = QueuePropertyChecker::new(&queue);
+        assert!(checker.all_hold());
+    }
+
+    #[test]
+    fn test_lost_element_detected() {
+        let queue = TestQueue {
+            enqueued: [1, 2, 3].into_iter().collect(),
+            dequeued: [1].into_iter().collect(),
+            contents: VecDeque::from([2]), // Element 3 is missing!
+            history: QueueHistory::new(),
+        };
+
+        let checker = QueuePropertyChecker::new(&queue).with_seed(7);
+        let results = checker.check_all();
+
+        let no_lost = results.iter().find(|r| r.name == "NoLostElements").unwrap();
+        assert!(!no_lost.holds);
+        assert_eq!(no_lost.tla_line, 80);
+        assert!(no_lost.violation.as_ref().unwrap().contains("3"));
+        assert_eq!(no_lost.counterexample.as_ref().unwrap().dst_seed, Some(7));
+    }
+
+    #[test]
+    fn test_double_dequeue_detected() {
+        let mut history = QueueHistory::new();
+        history.record_enqueue(0, 1, 1);
+        history.record_dequeue(0, Some(1), 2);
+        history.record_dequeue(1, Some(1), 3);
+
+        let queue = TestQueue {
+            enqueued: [1].into_iter().collect(),
+            dequeued: [1].into_iter().collect(),
+            contents: VecDeque::new(),
+            history,
+        };
+
+        let checker = QueuePropertyChecker::new(&queue);
+        let results = checker.check_all();
+
+        let no_dup = results.iter().find(|r| r.name == "NoDuplicates").unwrap();
+        assert!(!no_dup.holds);
+        assert!(no_dup
+            .violation
+            .as_ref()
+            .unwrap()
+            .contains("more than once"));
+    }
+
+    #[test]
+    fn test_lifo_queue_violates_fifo() {
+        // A "queue" that behaves like a stack.
+        let mut history = QueueHistory::new();
+        history.record_enqueue(0, 1, 1);
+        history.record_enqueue(0, 2, 2);
+        history.record_dequeue(0, Some(2), 3);
+
+        let queue = TestQueue {
+            enqueued: [1, 2].into_iter().collect(),
+            dequeued: [2].into_iter().collect(),
+            contents: VecDeque::from([1]),
+            history,
+        };
+
+        let checker = QueuePropertyChecker::new(&queue);
+        let results = checker.check_all();
+
+        let fifo = results.iter().find(|r| r.name == "FIFO_Order").unwrap();
+        assert!(!fifo.holds);
+        let lin = results
+            .iter()
+            .find(|r| r.name == "Linearizability")
+            .unwrap();
+        assert!(!lin.holds);
+        assert_eq!(lin.tla_line, 107);
+    }
+
+    #[test]
+    fn test_overlapping_enqueues_linearizable() {
+        // enqueue(2) overlaps enqueue(1), so either may be at the front.
+        let mut history = QueueHistory::new();
+        history.record_enqueue_interval(0, 1, 1, 4);
+        history.record_enqueue_interval(1, 2, 2, 3);
+        history.record_dequeue_interval(0, Some(2), 5, 6);
+
+        let queue = TestQueue {
+            enqueued: [1, 2].into_iter().collect(),
+            dequeued: [2].into_iter().collect(),
+            contents: VecDeque::from([1]),
+            history,
+        };
+
+        let checker = QueuePropertyChecker::new(&queue);
+        let results = checker.check_all();
+
+        let lin = results
+            .iter()
+            .find(|r| r.name == "Linearizability")
+            .unwrap();
+        assert!(lin.holds);
+    }
+
+    #[test]
+    fn test_lines_match_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/lockfree/ms_queue.tla"
+        ))
+        .unwrap();
+        let spec = TlaSpec::parse(&content).unwrap();
+        let module = spec.module.as_ref().expect("ms_queue.tla is valid TLA+");
+
+        let mut queue = TestQueue::new();
+        queue.enqueue(1);
+        for result in QueuePropertyChecker::new(&queue).check_all() {
+            let mapped = spec
+                .invariants
+                .iter()
+                .find(|inv| inv.name == result.name)
+                .unwrap();
+            assert_eq!(mapped.line, result.tla_line, "{}", result.name);
+            let definition = module.definition(result.name).unwrap();
+            assert_eq!(definition.line, result.tla_line, "{}", result.name);
+        }
+    }
+}
@@ -4,12 +4,14 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | PrefixConsistency | 45 | Traversal reaches correct value |
-//! | NoLostKeys | 58 | Every stored key is retrievable |
-//! | AtomicUpdate | 72 | Updates appear atomic |
+//! | PrefixConsistency | 39 | Traversal reaches correct value |
+//! | NoLostKeys | 47 | Every stored key is retrievable |
+//! | AtomicUpdate | 56 | Updates appear atomic |
 
 use std::collections::HashMap;
 
+use vf_macros::tla_invariant;
+
 use crate::property::{PropertyChecker, PropertyResult};
 
 const TLA_SPEC: &str = "radix_tree.tla";
@@ -37,7 +39,8 @@ impl<'a, T: RadixTreeProperties> RadixTreePropertyChecker<'a, T> {
         Self { tree }
     }
 
-    /// Line 45: PrefixConsistency
+    /// PrefixConsistency
+    #[tla_invariant(spec = "radix_tree.tla", name = "PrefixConsistency")]
     fn check_prefix_consistency(&self) -> PropertyResult {
         let map = self.tree.logical_map();
 
@@ -48,7 +51,7 @@ impl<'a, T: RadixTreeProperties> RadixTreePropertyChecker<'a, T> {
                     return PropertyResult::fail(
                         "PrefixConsistency",
                         TLA_SPEC,
-                        45,
+                        TLA_LINE,
                         format!(
                             "Key {:?} returned {} but expected {}",
                             key, val, expected_val
@@ -60,7 +63,7 @@ impl<'a, T: RadixTreeProperties> RadixTreePropertyChecker<'a, T> {
                     return PropertyResult::fail(
                         "PrefixConsistency",
                         TLA_SPEC,
-                        45,
+                        TLA_LINE,
                         format!("Key {:?} not found via traversal but exists in map", key),
                         None,
                     );
@@ -68,10 +71,11 @@ impl<'a, T: RadixTreeProperties> RadixTreePropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("PrefixConsistency", TLA_SPEC, 45)
+        PropertyResult::pass("PrefixConsistency", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 58: NoLostKeys
+    /// NoLostKeys
+    #[tla_invariant(spec = "radix_tree.tla", name = "NoLostKeys")]
     fn check_no_lost_keys(&self) -> PropertyResult {
         let map = self.tree.logical_map();
         let retrievable: std::collections::HashSet<Vec<u8>> =
@@ -82,14 +86,14 @@ impl<'a, T: RadixTreeProperties> RadixTreePropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "NoLostKeys",
                     TLA_SPEC,
-                    58,
+                    TLA_LINE,
                     format!("Key {:?} exists in map but is not retrievable", key),
                     None,
                 );
             }
         }
 
-        PropertyResult::pass("NoLostKeys", TLA_SPEC, 58)
+        PropertyResult::pass("NoLostKeys", TLA_SPEC, TLA_LINE)
     }
 }
 
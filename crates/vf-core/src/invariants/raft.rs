@@ -0,0 +1,527 @@
+//! Raft invariants from raft_consensus.tla
+//!
+//! The five safety properties from Figure 3 of the Raft paper, checked
+//! against a snapshot of every server's term, role, log and commit index.
+//!
+//! # TLA+ Mapping
+//!
+//! | Property | TLA+ Line | Description |
+//! |----------|-----------|-------------|
+//! | ElectionSafety | 77 | At most one leader per term |
+//! | LogMatching | 100 | Same index and term implies identical prefixes |
+//! | LeaderCompleteness | 120 | Leaders hold every committed entry |
+//! | StateMachineSafety | 131 | No two servers commit different entries at an index |
+//!
+//! Counterexample states use TLA+ value syntax, so they can be loaded
+//! into `tla_spec::Evaluator` and checked against the spec itself.
+
+use std::collections::{BTreeMap, HashMap};
+
+use vf_macros::tla_invariant;
+
+use crate::counterexample::{Counterexample, StateSnapshot};
+use crate::property::{PropertyChecker, PropertyResult};
+
+/// TLA+ spec file for Raft invariants.
+const TLA_SPEC: &str = "raft_consensus.tla";
+
+/// Server identifier.
+pub type ServerId = u64;
+
+/// Role of a Raft server (TLA+ `state`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RaftRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+impl RaftRole {
+    fn tla_name(self) -> &'static str {
+        match self {
+            RaftRole::Follower => "Follower",
+            RaftRole::Candidate => "Candidate",
+            RaftRole::Leader => "Leader",
+        }
+    }
+}
+
+/// A log entry (TLA+ `<<term, data>>`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogEntry {
+    pub term: u64,
+    pub data: u64,
+}
+
+/// Properties that any Raft implementation must satisfy.
+///
+/// Implementations expose the state of every server in the cluster
+/// (or of a simulated cluster under DST). Log indices are 1-based,
+/// as in the paper and the spec.
+pub trait RaftProperties {
+    /// Map: server -> current term.
+    fn current_terms(&self) -> HashMap<ServerId, u64>;
+
+    /// Map: server -> role.
+    fn roles(&self) -> HashMap<ServerId, RaftRole>;
+
+    /// Map: server -> log entries, index 1 first.
+    fn logs(&self) -> HashMap<ServerId, Vec<LogEntry>>;
+
+    /// Map: server -> highest committed log index (0 if none).
+    fn commit_indices(&self) -> HashMap<ServerId, u64>;
+
+    /// Every election won so far, as `(term, leader)`.
+    ///
+    /// Lets ElectionSafety catch two leaders of one term even after one
+    /// of them stepped down. May be empty if the implementation does
+    /// not record elections; current leaders are always checked.
+    fn elections(&self) -> Vec<(u64, ServerId)>;
+}
+
+/// Property checker for Raft implementations.
+///
+/// Verifies the safety invariants from raft_consensus.tla against
+/// the implementation's state.
+pub struct RaftPropertyChecker<'a, T: RaftProperties> {
+    raft: &'a T,
+    dst_seed: Option<u64>,
+}
+
+impl<'a, T: RaftProperties> RaftPropertyChecker<'a, T> {
+    /// Create a new checker for the given cluster.
+    #[must_use]
+    pub fn new(raft: &'a T) -> Self {
+        Self {
+            raft,
+            dst_seed: None,
+        }
+    }
+
+    /// Set DST seed for counterexample reproduction.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        debug_assert!(seed != 0, "DST seed should not be zero");
+        self.dst_seed = Some(seed);
+        self
+    }
+
+    /// ElectionSafety
+    ///
+    /// At most one leader can be elected in a given term.
+    #[tla_invariant(spec = "raft_consensus.tla", name = "ElectionSafety")]
+    fn check_election_safety(&self) -> PropertyResult {
+        let terms = self.raft.current_terms();
+        let mut elections = self.raft.elections();
+        for (server, role) in sorted(self.raft.roles()) {
+            if role == RaftRole::Leader {
+                elections.push((terms.get(&server).copied().unwrap_or(0), server));
+            }
+        }
+
+        let mut leaders: BTreeMap<u64, ServerId> = BTreeMap::new();
+        for (term, server) in elections {
+            match leaders.get(&term) {
+                Some(&other) if other != server => {
+                    let violation = format!(
+                        "Servers {} and {} are both leaders of term {}",
+                        other, server, term
+                    );
+                    return PropertyResult::fail(
+                        "ElectionSafety",
+                        TLA_SPEC,
+                        TLA_LINE,
+                        violation.clone(),
+                        Some(self.counterexample(violation)),
+                    );
+                }
+                _ => {
+                    leaders.insert(term, server);
+                }
+            }
+        }
+
+        PropertyResult::pass("ElectionSafety", TLA_SPEC, TLA_LINE)
+    }
+
+    /// LogMatching
+    ///
+    /// If two logs contain an entry with the same index and term, the
+    /// logs are identical in all entries up through that index.
+    #[tla_invariant(spec = "raft_consensus.tla", name = "LogMatching")]
+    fn check_log_matching(&self) -> PropertyResult {
+        let logs = sorted(self.raft.logs());
+
+        for (a, (s1, log1)) in logs.iter().enumerate() {
+            for (s2, log2) in &logs[a + 1..] {
+                let common = log1.len().min(log2.len());
+                let Some(diverged) = (0..common).find(|&k| log1[k] != log2[k]) else {
+                    continue;
+                };
+                // Any later index with matching terms has a different prefix
+                let Some(i) = (diverged..common).find(|&i| log1[i].term == log2[i].term) else {
+                    continue;
+                };
+                let violation = format!(
+                    "Servers {} and {} agree on term {} at index {} but differ at index {}",
+                    s1,
+                    s2,
+                    log1[i].term,
+                    i + 1,
+                    diverged + 1
+                );
+                return PropertyResult::fail(
+                    "LogMatching",
+                    TLA_SPEC,
+                    TLA_LINE,
+                    violation.clone(),
+                    Some(self.counterexample(violation)),
+                );
+            }
+        }
+
+        PropertyResult::pass("LogMatching", TLA_SPEC, TLA_LINE)
+    }
+
+    /// LeaderCompleteness
+    ///
+    /// If an entry is committed in term T, it is present in the log of
+    /// every leader of a term after T. The spec's bounded form, that a
+    /// leader's commit index never exceeds its own log, is checked too.
+    #[tla_invariant(spec = "raft_consensus.tla", name = "LeaderCompleteness")]
+    fn check_leader_completeness(&self) -> PropertyResult {
+        let terms = self.raft.current_terms();
+        let roles = self.raft.roles();
+        let logs = self.raft.logs();
+        let commits = sorted(self.raft.commit_indices());
+        let empty = Vec::new();
+
+        for (leader, role) in sorted(roles) {
+            if role != RaftRole::Leader {
+                continue;
+            }
+            let leader_term = terms.get(&leader).copied().unwrap_or(0);
+            let leader_log = logs.get(&leader).unwrap_or(&empty);
+
+            for &(server, commit) in &commits {
+                let log = logs.get(&server).unwrap_or(&empty);
+                if server == leader && commit > leader_log.len() as u64 {
+                    let violation = format!(
+                        "Leader {} has commit index {} beyond its log of {} entries",
+                        leader,
+                        commit,
+                        leader_log.len()
+                    );
+                    return self.fail_leader_completeness(TLA_LINE, violation);
+                }
+                for (i, entry) in log.iter().enumerate().take(commit as usize) {
+                    if entry.term < leader_term && leader_log.get(i) != Some(entry) {
+                        let violation = format!(
+                            "Entry {:?} committed at index {} on server {} is missing from leader {} of term {}",
+                            entry,
+                            i + 1,
+                            server,
+                            leader,
+                            leader_term
+                        );
+                        return self.fail_leader_completeness(TLA_LINE, violation);
+                    }
+                }
+            }
+        }
+
+        PropertyResult::pass("LeaderCompleteness", TLA_SPEC, TLA_LINE)
+    }
+
+    fn fail_leader_completeness(&self, line: u32, violation: String) -> PropertyResult {
+        PropertyResult::fail(
+            "LeaderCompleteness",
+            TLA_SPEC,
+            line,
+            violation.clone(),
+            Some(self.counterexample(violation)),
+        )
+    }
+
+    /// StateMachineSafety
+    ///
+    /// If a server has applied the entry at an index, no other server
+    /// ever applies a different entry at that index.
+    #[tla_invariant(spec = "raft_consensus.tla", name = "StateMachineSafety")]
+    fn check_state_machine_safety(&self) -> PropertyResult {
+        let logs = self.raft.logs();
+        let commits = sorted(self.raft.commit_indices());
+        let empty = Vec::new();
+
+        for (a, &(s1, c1)) in commits.iter().enumerate() {
+            for &(s2, c2) in &commits[a + 1..] {
+                let log1 = logs.get(&s1).unwrap_or(&empty);
+                let log2 = logs.get(&s2).unwrap_or(&empty);
+                let applied = c1.min(c2) as usize;
+                let diverged = log1
+                    .iter()
+                    .zip(log2)
+                    .take(applied)
+                    .position(|(e1, e2)| e1 != e2);
+                if let Some(k) = diverged {
+                    let violation = format!(
+                        "Servers {} and {} applied different entries at index {}: {:?} vs {:?}",
+                        s1,
+                        s2,
+                        k + 1,
+                        log1[k],
+                        log2[k]
+                    );
+                    return PropertyResult::fail(
+                        "StateMachineSafety",
+                        TLA_SPEC,
+                        TLA_LINE,
+                        violation.clone(),
+                        Some(self.counterexample(violation)),
+                    );
+                }
+            }
+        }
+
+        PropertyResult::pass("StateMachineSafety", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Capture the cluster state as the spec's variables.
+    fn counterexample(&self, violation: String) -> Counterexample {
+        let mut ce = match self.dst_seed {
+            Some(seed) => Counterexample::with_seed(seed),
+            None => Counterexample::new(),
+        };
+        let terms = tla_function(self.raft.current_terms(), |t| t.to_string());
+        let roles = tla_function(self.raft.roles(), |r| format!("\"{}\"", r.tla_name()));
+        let logs = tla_function(self.raft.logs(), |log| {
+            let entries: Vec<String> = log
+                .iter()
+                .map(|e| format!("<<{}, {}>>", e.term, e.data))
+                .collect();
+            format!("<<{}>>", entries.join(", "))
+        });
+        let commits = tla_function(self.raft.commit_indices(), |c| c.to_string());
+
+        ce.add_state(StateSnapshot {
+            step: 1,
+            description: violation.clone(),
+            variables: vec![
+                ("currentTerm".to_string(), terms),
+                ("state".to_string(), roles),
+                ("log".to_string(), logs),
+                ("commitIndex".to_string(), commits),
+            ],
+        });
+        ce.with_description(violation)
+    }
+}
+
+impl<T: RaftProperties> PropertyChecker for RaftPropertyChecker<'_, T> {
+    fn check_all(&self) -> Vec<PropertyResult> {
+        vec![
+            self.check_election_safety(),
+            self.check_log_matching(),
+            self.check_leader_completeness(),
+            self.check_state_machine_safety(),
+        ]
+    }
+}
+
+/// Entries ordered by server, so reports are deterministic.
+fn sorted<V>(map: HashMap<ServerId, V>) -> Vec<(ServerId, V)> {
+    let mut entries: Vec<_> = map.into_iter().collect();
+    entries.sort_by_key(|(server, _)| *server);
+    entries
+}
+
+/// Format a map as a TLA+ function, e.g. `(1 :> 2 @@ 2 :> 2)`.
+fn tla_function<V>(map: HashMap<ServerId, V>, value: impl Fn(&V) -> String) -> String {
+    let pairs: Vec<String> = sorted(map)
+        .iter()
+        .map(|(server, v)| format!("{} :> {}", server, value(v)))
+        .collect();
+    format!("({})", pairs.join(" @@ "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tla_spec::{parse_module, Evaluator, TlaSpec, Value};
+
+    /// Test implementation of RaftProperties
+    #[derive(Default)]
+    struct TestCluster {
+        terms: HashMap<ServerId, u64>,
+        roles: HashMap<ServerId, RaftRole>,
+        logs: HashMap<ServerId, Vec<LogEntry>>,
+        commits: HashMap<ServerId, u64>,
+        elections: Vec<(u64, ServerId)>,
+    }
+
+    impl TestCluster {
+        fn server(
+            mut self,
+            id: ServerId,
+            term: u64,
+            role: RaftRole,
+            log: &[(u64, u64)],
+            commit: u64,
+        ) -> Self {
+            self.terms.insert(id, term);
+            self.roles.insert(id, role);
+            self.logs.insert(
+                id,
+                log.iter()
+                    .map(|&(term, data)| LogEntry { term, data })
+                    .collect(),
+            );
+            self.commits.insert(id, commit);
+            self
+        }
+    }
+
+    impl RaftProperties for TestCluster {
+        fn current_terms(&self) -> HashMap<ServerId, u64> {
+            self.terms.clone()
+        }
+
+        fn roles(&self) -> HashMap<ServerId, RaftRole> {
+            self.roles.clone()
+        }
+
+        fn logs(&self) -> HashMap<ServerId, Vec<LogEntry>> {
+            self.logs.clone()
+        }
+
+        fn commit_indices(&self) -> HashMap<ServerId, u64> {
+            self.commits.clone()
+        }
+
+        fn elections(&self) -> Vec<(u64, ServerId)> {
+            self.elections.clone()
+        }
+    }
+
+    fn healthy() -> TestCluster {
+        TestCluster::default()
+            .server(1, 2, RaftRole::Leader, &[(1, 10), (2, 20)], 2)
+            .server(2, 2, RaftRole::Follower, &[(1, 10), (2, 20)], 1)
+            .server(3, 2, RaftRole::Follower, &[(1, 10)], 1)
+    }
+
+    fn result(cluster: &TestCluster, name: &str) -> PropertyResult {
+        RaftPropertyChecker::new(cluster)
+            .check_all()
+            .into_iter()
+            .find(|r| r.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_healthy_cluster_passes_all() {
+        let cluster = healthy();
+        assert!(RaftPropertyChecker::new(&cluster).all_hold());
+    }
+
+    #[test]
+    fn test_two_leaders_in_one_term_detected() {
+        let mut cluster = healthy();
+        cluster.elections.push((2, 3));
+
+        let election = result(&cluster, "ElectionSafety");
+        assert!(!election.holds);
+        assert!(election.violation.as_ref().unwrap().contains("term 2"));
+
+        let current = TestCluster::default()
+            .server(1, 3, RaftRole::Leader, &[], 0)
+            .server(2, 3, RaftRole::Leader, &[], 0);
+        assert!(!result(&current, "ElectionSafety").holds);
+    }
+
+    #[test]
+    fn test_log_matching_violation_detected() {
+        // Index 2 has the same term on both servers, but index 1 differs.
+        let cluster = TestCluster::default()
+            .server(1, 2, RaftRole::Leader, &[(1, 10), (2, 20)], 0)
+            .server(2, 2, RaftRole::Follower, &[(1, 11), (2, 20)], 0);
+
+        let matching = result(&cluster, "LogMatching");
+        assert!(!matching.holds);
+        assert_eq!(matching.tla_line, 100);
+
+        // Diverging suffixes with different terms are fine.
+        let cluster = TestCluster::default()
+            .server(1, 3, RaftRole::Leader, &[(1, 10), (3, 30)], 0)
+            .server(2, 3, RaftRole::Follower, &[(1, 10), (2, 20)], 0);
+        assert!(result(&cluster, "LogMatching").holds);
+    }
+
+    #[test]
+    fn test_leader_missing_committed_entry_detected() {
+        // Entry <<1, 10>> is committed on servers 2 and 3, but leader 1
+        // of term 2 overwrote it.
+        let cluster = TestCluster::default()
+            .server(1, 2, RaftRole::Leader, &[(2, 20)], 0)
+            .server(2, 2, RaftRole::Follower, &[(1, 10)], 1)
+            .server(3, 2, RaftRole::Follower, &[(1, 10)], 1);
+
+        let completeness = result(&cluster, "LeaderCompleteness");
+        assert!(!completeness.holds);
+        assert!(completeness
+            .violation
+            .as_ref()
+            .unwrap()
+            .contains("leader 1"));
+    }
+
+    #[test]
+    fn test_state_machine_divergence_detected() {
+        let cluster = TestCluster::default()
+            .server(1, 2, RaftRole::Follower, &[(1, 10), (2, 20)], 2)
+            .server(2, 2, RaftRole::Follower, &[(1, 10), (2, 21)], 2);
+
+        let checker = RaftPropertyChecker::new(&cluster).with_seed(42);
+        let safety = checker
+            .check_all()
+            .into_iter()
+            .find(|r| r.name == "StateMachineSafety")
+            .unwrap();
+        assert!(!safety.holds);
+        let ce = safety.counterexample.unwrap();
+        assert_eq!(ce.dst_seed, Some(42));
+
+        // The counterexample state violates the spec's own operator.
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/distributed/raft_consensus.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let mut evaluator = Evaluator::new(&module)
+            .with_constant("Servers", Value::set([Value::Int(1), Value::Int(2)]))
+            .with_model_constants();
+        evaluator.load_state(&ce.states[0]).unwrap();
+        assert!(!evaluator.check("StateMachineSafety").unwrap());
+        assert!(evaluator.check("ElectionSafety").unwrap());
+    }
+
+    #[test]
+    fn test_lines_match_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/distributed/raft_consensus.tla"
+        ))
+        .unwrap();
+        let spec = TlaSpec::parse(&content).unwrap();
+
+        for result in RaftPropertyChecker::new(&healthy()).check_all() {
+            let mapped = spec
+                .invariants
+                .iter()
+                .find(|inv| inv.name == result.name)
+                .unwrap();
+            assert_eq!(mapped.line, result.tla_line, "{}", result.name);
+        }
+    }
+}
@@ -4,13 +4,15 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | NoLostMessages | 45 | Every produced message is in buffer or consumed |
-//! | FIFO_Order | 58 | Messages consumed in production order |
-//! | BoundedCapacity | 72 | Buffer never exceeds capacity |
-//! | ProducerConsumerProgress | 86 | Non-blocking progress |
+//! | NoLostMessages | 47 | Every produced message is in buffer or consumed |
+//! | FIFO_Order | 56 | Messages consumed in production order |
+//! | BoundedCapacity | 64 | Buffer never exceeds capacity |
+//! | ProducerConsumerProgress | 72 | Non-blocking progress |
 
 use std::collections::HashSet;
 
+use vf_macros::tla_invariant;
+
 use crate::counterexample::Counterexample;
 use crate::property::{PropertyChecker, PropertyResult};
 
@@ -53,7 +55,8 @@ impl<'a, T: RingBufferProperties> RingBufferPropertyChecker<'a, T> {
         self
     }
 
-    /// Line 45: NoLostMessages
+    /// NoLostMessages
+    #[tla_invariant(spec = "ring_buffer.tla", name = "NoLostMessages")]
     fn check_no_lost_messages(&self) -> PropertyResult {
         let produced = self.buffer.produced_messages();
         let consumed: HashSet<u64> = self.buffer.consumed_messages().into_iter().collect();
@@ -77,17 +80,18 @@ impl<'a, T: RingBufferProperties> RingBufferPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "NoLostMessages",
                     TLA_SPEC,
-                    45,
+                    TLA_LINE,
                     format!("Message {} was produced but is neither in buffer nor consumed", msg),
                     Some(ce),
                 );
             }
         }
 
-        PropertyResult::pass("NoLostMessages", TLA_SPEC, 45)
+        PropertyResult::pass("NoLostMessages", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 58: FIFO_Order
+    /// FIFO_Order
+    #[tla_invariant(spec = "ring_buffer.tla", name = "FIFO_Order")]
     fn check_fifo_order(&self) -> PropertyResult {
         let produced = self.buffer.produced_messages();
         let consumed = self.buffer.consumed_messages();
@@ -97,7 +101,7 @@ impl<'a, T: RingBufferProperties> RingBufferPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "FIFO_Order",
                     TLA_SPEC,
-                    58,
+                    TLA_LINE,
                     format!(
                         "Consumed message at index {} is {} but produced was {}",
                         i, msg, produced[i]
@@ -107,10 +111,11 @@ impl<'a, T: RingBufferProperties> RingBufferPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("FIFO_Order", TLA_SPEC, 58)
+        PropertyResult::pass("FIFO_Order", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 72: BoundedCapacity
+    /// BoundedCapacity
+    #[tla_invariant(spec = "ring_buffer.tla", name = "BoundedCapacity")]
     fn check_bounded_capacity(&self) -> PropertyResult {
         let contents = self.buffer.current_contents();
         let capacity = self.buffer.capacity();
@@ -119,7 +124,7 @@ impl<'a, T: RingBufferProperties> RingBufferPropertyChecker<'a, T> {
             return PropertyResult::fail(
                 "BoundedCapacity",
                 TLA_SPEC,
-                72,
+                TLA_LINE,
                 format!(
                     "Buffer contains {} items but capacity is {}",
                     contents.len(),
@@ -129,7 +134,7 @@ impl<'a, T: RingBufferProperties> RingBufferPropertyChecker<'a, T> {
             );
         }
 
-        PropertyResult::pass("BoundedCapacity", TLA_SPEC, 72)
+        PropertyResult::pass("BoundedCapacity", TLA_SPEC, TLA_LINE)
     }
 }
 
@@ -0,0 +1,509 @@
+//! Multi-version snapshot isolation invariants from snapshot_isolation.tla
+//!
+//! SI is weaker than serializability: write skew is allowed, so there is
+//! no `Serializable` check here. Run `elle` on the history to see which
+//! anomalies a store actually exhibits.
+//!
+//! # TLA+ Mapping
+//!
+//! | Property | TLA+ Line | Description |
+//! |----------|-----------|-------------|
+//! | FirstCommitterWins | 72 | Concurrent writers of a key never both commit |
+//! | SnapshotRead | 83 | Reads see the newest version as of the snapshot |
+//! | CommitAfterSnapshot | 92 | Commit timestamps are unique and follow the snapshot |
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use vf_macros::tla_invariant;
+
+use super::ssi::TxnStatus;
+use crate::counterexample::{Counterexample, StateSnapshot};
+use crate::property::{PropertyChecker, PropertyResult};
+
+/// TLA+ spec file for SI invariants.
+const TLA_SPEC: &str = "snapshot_isolation.tla";
+
+/// A read and the version it returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SnapshotReadRecord {
+    pub key: u64,
+    /// Transaction whose write was read (None = initial value)
+    pub from: Option<u64>,
+}
+
+/// Properties that any multi-version snapshot isolation store must satisfy.
+pub trait SnapshotIsolationProperties {
+    /// Map: txn_id -> status, for every transaction that has begun.
+    fn txn_statuses(&self) -> HashMap<u64, TxnStatus>;
+
+    /// Map: txn_id -> snapshot timestamp its reads see.
+    fn snapshots(&self) -> HashMap<u64, u64>;
+
+    /// Map: txn_id -> commit timestamp, for committed transactions.
+    fn commit_timestamps(&self) -> HashMap<u64, u64>;
+
+    /// Map: txn_id -> keys it has written.
+    fn write_sets(&self) -> HashMap<u64, HashSet<u64>>;
+
+    /// Map: txn_id -> reads it has performed.
+    fn read_records(&self) -> HashMap<u64, Vec<SnapshotReadRecord>>;
+}
+
+/// Property checker for snapshot isolation implementations.
+pub struct SnapshotIsolationPropertyChecker<'a, T: SnapshotIsolationProperties> {
+    store: &'a T,
+    dst_seed: Option<u64>,
+}
+
+/// Store state, as the spec's variables.
+struct SiState {
+    txn_status: BTreeMap<u64, TxnStatus>,
+    snapshot: BTreeMap<u64, u64>,
+    commit_ts: BTreeMap<u64, u64>,
+    writes: BTreeMap<u64, BTreeSet<u64>>,
+    reads: BTreeMap<u64, BTreeSet<SnapshotReadRecord>>,
+}
+
+impl SiState {
+    fn committed(&self) -> impl Iterator<Item = u64> + '_ {
+        self.txn_status
+            .iter()
+            .filter(|(_, status)| **status == TxnStatus::Committed)
+            .map(|(txn, _)| *txn)
+    }
+
+    fn commit_ts(&self, txn: u64) -> u64 {
+        self.commit_ts.get(&txn).copied().unwrap_or(0)
+    }
+
+    /// Writer of the version of `key` visible at `ts` (None = initial).
+    fn visible(&self, key: u64, ts: u64) -> Option<u64> {
+        self.committed()
+            .filter(|w| self.writes[w].contains(&key) && self.commit_ts(*w) <= ts)
+            .max_by_key(|w| self.commit_ts(*w))
+    }
+
+    /// Latest commit timestamp (`clock`).
+    fn clock(&self) -> u64 {
+        self.commit_ts.values().copied().max().unwrap_or(0)
+    }
+}
+
+impl<'a, T: SnapshotIsolationProperties> SnapshotIsolationPropertyChecker<'a, T> {
+    /// Create a new checker for the given implementation.
+    #[must_use]
+    pub fn new(store: &'a T) -> Self {
+        Self {
+            store,
+            dst_seed: None,
+        }
+    }
+
+    /// Set DST seed for counterexample reproduction.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        debug_assert!(seed != 0, "DST seed should not be zero");
+        self.dst_seed = Some(seed);
+        self
+    }
+
+    fn state(&self) -> SiState {
+        let mut s = SiState {
+            txn_status: self.store.txn_statuses().into_iter().collect(),
+            snapshot: self.store.snapshots().into_iter().collect(),
+            commit_ts: self.store.commit_timestamps().into_iter().collect(),
+            writes: self
+                .store
+                .write_sets()
+                .into_iter()
+                .map(|(txn, keys)| (txn, keys.into_iter().collect()))
+                .collect(),
+            reads: self
+                .store
+                .read_records()
+                .into_iter()
+                .map(|(txn, reads)| (txn, reads.into_iter().collect()))
+                .collect(),
+        };
+
+        // Functions range over every transaction, as in the spec
+        let txns: Vec<u64> = s.txn_status.keys().copied().collect();
+        for txn in txns {
+            s.snapshot.entry(txn).or_default();
+            s.writes.entry(txn).or_default();
+            s.reads.entry(txn).or_default();
+        }
+        s
+    }
+
+    /// FirstCommitterWins
+    #[tla_invariant(spec = "snapshot_isolation.tla", name = "FirstCommitterWins")]
+    fn check_first_committer_wins(&self, s: &SiState) -> PropertyResult {
+        let committed: Vec<u64> = s.committed().collect();
+        for (i, &t1) in committed.iter().enumerate() {
+            for &t2 in &committed[i + 1..] {
+                let overlap: Vec<u64> = s.writes[&t1]
+                    .intersection(&s.writes[&t2])
+                    .copied()
+                    .collect();
+                let concurrent =
+                    s.commit_ts(t1) > s.snapshot[&t2] && s.commit_ts(t2) > s.snapshot[&t1];
+                if !overlap.is_empty() && concurrent {
+                    return self.fail(
+                        "FirstCommitterWins",
+                        TLA_LINE,
+                        s,
+                        format!(
+                            "Concurrent transactions {} and {} both committed writes to {:?}",
+                            t1, t2, overlap
+                        ),
+                    );
+                }
+            }
+        }
+
+        PropertyResult::pass("FirstCommitterWins", TLA_SPEC, TLA_LINE)
+    }
+
+    /// SnapshotRead
+    #[tla_invariant(spec = "snapshot_isolation.tla", name = "SnapshotRead")]
+    fn check_snapshot_read(&self, s: &SiState) -> PropertyResult {
+        for (txn, reads) in &s.reads {
+            for read in reads {
+                let expected = s.visible(read.key, s.snapshot[txn]);
+                if read.from != Some(*txn) && read.from != expected {
+                    return self.fail(
+                        "SnapshotRead",
+                        TLA_LINE,
+                        s,
+                        format!(
+                            "Transaction {} read key {} from {:?}, but its snapshot {} shows {:?}",
+                            txn, read.key, read.from, s.snapshot[txn], expected
+                        ),
+                    );
+                }
+            }
+        }
+
+        PropertyResult::pass("SnapshotRead", TLA_SPEC, TLA_LINE)
+    }
+
+    /// CommitAfterSnapshot
+    #[tla_invariant(spec = "snapshot_isolation.tla", name = "CommitAfterSnapshot")]
+    fn check_commit_after_snapshot(&self, s: &SiState) -> PropertyResult {
+        let mut seen: BTreeMap<u64, u64> = BTreeMap::new();
+        for txn in s.committed() {
+            let commit_ts = s.commit_ts(txn);
+            if commit_ts <= s.snapshot[&txn] {
+                return self.fail(
+                    "CommitAfterSnapshot",
+                    TLA_LINE,
+                    s,
+                    format!(
+                        "Transaction {} committed at {} but its snapshot is {}",
+                        txn, commit_ts, s.snapshot[&txn]
+                    ),
+                );
+            }
+            if let Some(other) = seen.insert(commit_ts, txn) {
+                return self.fail(
+                    "CommitAfterSnapshot",
+                    TLA_LINE,
+                    s,
+                    format!(
+                        "Transactions {} and {} share commit timestamp {}",
+                        other, txn, commit_ts
+                    ),
+                );
+            }
+        }
+
+        PropertyResult::pass("CommitAfterSnapshot", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Failing result with the store state as a TLA+ counterexample.
+    fn fail(
+        &self,
+        name: &'static str,
+        line: u32,
+        s: &SiState,
+        violation: String,
+    ) -> PropertyResult {
+        let mut ce = match self.dst_seed {
+            Some(seed) => Counterexample::with_seed(seed),
+            None => Counterexample::new(),
+        };
+
+        let per_txn = |value: &dyn Fn(u64) -> String| -> String {
+            tla_function(s.txn_status.keys().map(|txn| (*txn, value(*txn))))
+        };
+
+        ce.add_state(StateSnapshot {
+            step: 1,
+            description: "Store state".to_string(),
+            variables: vec![
+                (
+                    "txn_status".to_string(),
+                    per_txn(&|txn| tla_status(s.txn_status[&txn]).to_string()),
+                ),
+                ("clock".to_string(), s.clock().to_string()),
+                (
+                    "snapshot".to_string(),
+                    per_txn(&|txn| s.snapshot[&txn].to_string()),
+                ),
+                (
+                    "commit_ts".to_string(),
+                    per_txn(&|txn| s.commit_ts(txn).to_string()),
+                ),
+                (
+                    "writes".to_string(),
+                    per_txn(&|txn| tla_set(&s.writes[&txn])),
+                ),
+                (
+                    "reads".to_string(),
+                    per_txn(&|txn| {
+                        let records: Vec<String> = s.reads[&txn]
+                            .iter()
+                            .map(|r| {
+                                let from: BTreeSet<u64> = r.from.into_iter().collect();
+                                format!("[key |-> {}, from |-> {}]", r.key, tla_set(&from))
+                            })
+                            .collect();
+                        format!("{{{}}}", records.join(", "))
+                    }),
+                ),
+            ],
+        });
+
+        PropertyResult::fail(
+            name,
+            TLA_SPEC,
+            line,
+            violation.clone(),
+            Some(ce.with_description(violation)),
+        )
+    }
+}
+
+/// Status as the spec's string constant.
+fn tla_status(status: TxnStatus) -> &'static str {
+    match status {
+        TxnStatus::Active => "\"active\"",
+        TxnStatus::Committed => "\"committed\"",
+        TxnStatus::Aborted => "\"aborted\"",
+    }
+}
+
+fn tla_set(items: &BTreeSet<u64>) -> String {
+    let items: Vec<String> = items.iter().map(u64::to_string).collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+fn tla_function(entries: impl Iterator<Item = (u64, String)>) -> String {
+    let entries: Vec<String> = entries.map(|(k, v)| format!("{} :> {}", k, v)).collect();
+    if entries.is_empty() {
+        return "[x \\in {} |-> {}]".to_string();
+    }
+    format!("({})", entries.join(" @@ "))
+}
+
+impl<T: SnapshotIsolationProperties> PropertyChecker for SnapshotIsolationPropertyChecker<'_, T> {
+    fn check_all(&self) -> Vec<PropertyResult> {
+        let state = self.state();
+        vec![
+            self.check_first_committer_wins(&state),
+            self.check_snapshot_read(&state),
+            self.check_commit_after_snapshot(&state),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tla_spec::{parse_module, Evaluator, TlaSpec, Value};
+
+    /// Test implementation of SnapshotIsolationProperties
+    #[derive(Default)]
+    struct TestStore {
+        statuses: HashMap<u64, TxnStatus>,
+        snapshots: HashMap<u64, u64>,
+        commits: HashMap<u64, u64>,
+        writes: HashMap<u64, HashSet<u64>>,
+        reads: HashMap<u64, Vec<SnapshotReadRecord>>,
+    }
+
+    impl TestStore {
+        /// `txn` began at `snapshot` and, if given, committed at `commit`.
+        fn txn(mut self, txn: u64, snapshot: u64, commit: Option<u64>) -> Self {
+            let status = match commit {
+                Some(ts) => {
+                    self.commits.insert(txn, ts);
+                    TxnStatus::Committed
+                }
+                None => TxnStatus::Active,
+            };
+            self.statuses.insert(txn, status);
+            self.snapshots.insert(txn, snapshot);
+            self
+        }
+
+        fn write(mut self, txn: u64, key: u64) -> Self {
+            self.writes.entry(txn).or_default().insert(key);
+            self
+        }
+
+        fn read(mut self, txn: u64, key: u64, from: Option<u64>) -> Self {
+            self.reads
+                .entry(txn)
+                .or_default()
+                .push(SnapshotReadRecord { key, from });
+            self
+        }
+    }
+
+    impl SnapshotIsolationProperties for TestStore {
+        fn txn_statuses(&self) -> HashMap<u64, TxnStatus> {
+            self.statuses.clone()
+        }
+
+        fn snapshots(&self) -> HashMap<u64, u64> {
+            self.snapshots.clone()
+        }
+
+        fn commit_timestamps(&self) -> HashMap<u64, u64> {
+            self.commits.clone()
+        }
+
+        fn write_sets(&self) -> HashMap<u64, HashSet<u64>> {
+            self.writes.clone()
+        }
+
+        fn read_records(&self) -> HashMap<u64, Vec<SnapshotReadRecord>> {
+            self.reads.clone()
+        }
+    }
+
+    fn failing(store: &TestStore) -> Vec<&'static str> {
+        SnapshotIsolationPropertyChecker::new(store)
+            .check_all()
+            .into_iter()
+            .filter(|r| !r.holds)
+            .map(|r| r.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_write_skew_passes_all() {
+        // T2 and T3 each read both keys from T1 and write one: write skew,
+        // which SI allows
+        let store = TestStore::default()
+            .txn(1, 0, Some(1))
+            .write(1, 1)
+            .write(1, 2)
+            .txn(2, 1, Some(2))
+            .read(2, 1, Some(1))
+            .read(2, 2, Some(1))
+            .write(2, 1)
+            .txn(3, 1, Some(3))
+            .read(3, 1, Some(1))
+            .read(3, 2, Some(1))
+            .write(3, 2);
+
+        assert!(SnapshotIsolationPropertyChecker::new(&store).all_hold());
+    }
+
+    #[test]
+    fn test_lost_update_detected() {
+        let store = TestStore::default()
+            .txn(1, 0, Some(1))
+            .write(1, 1)
+            .txn(2, 0, Some(2))
+            .write(2, 1);
+
+        assert_eq!(failing(&store), vec!["FirstCommitterWins"]);
+    }
+
+    #[test]
+    fn test_read_outside_snapshot_detected() {
+        // T2 began before T1 committed but saw its write
+        let store = TestStore::default()
+            .txn(1, 0, Some(1))
+            .write(1, 1)
+            .txn(2, 0, None)
+            .read(2, 1, Some(1))
+            .read(2, 2, None);
+
+        assert_eq!(failing(&store), vec!["SnapshotRead"]);
+
+        // Reading one's own write is always allowed
+        let store = TestStore::default()
+            .txn(1, 0, None)
+            .write(1, 1)
+            .read(1, 1, Some(1));
+        assert!(failing(&store).is_empty());
+    }
+
+    #[test]
+    fn test_commit_timestamp_violations_detected() {
+        let store = TestStore::default().txn(1, 3, Some(3));
+        assert_eq!(failing(&store), vec!["CommitAfterSnapshot"]);
+
+        let store = TestStore::default().txn(1, 0, Some(1)).txn(2, 0, Some(1));
+        assert_eq!(failing(&store), vec!["CommitAfterSnapshot"]);
+    }
+
+    #[test]
+    fn test_counterexample_violates_spec() {
+        let store = TestStore::default()
+            .txn(1, 0, Some(1))
+            .write(1, 1)
+            .txn(2, 0, Some(2))
+            .write(2, 1)
+            .read(2, 1, Some(1))
+            .txn(3, 2, None)
+            .read(3, 1, Some(2));
+        let results = SnapshotIsolationPropertyChecker::new(&store)
+            .with_seed(4)
+            .check_all();
+        let ce = results[0].counterexample.clone().unwrap();
+        assert_eq!(ce.dst_seed, Some(4));
+
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/ssi/snapshot_isolation.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let mut evaluator = Evaluator::new(&module)
+            .with_constant(
+                "Txns",
+                Value::set([Value::Int(1), Value::Int(2), Value::Int(3)]),
+            )
+            .with_constant("Keys", Value::set([Value::Int(1)]))
+            .with_model_constants();
+        evaluator.load_state(&ce.states[0]).unwrap();
+        assert!(!evaluator.check("FirstCommitterWins").unwrap());
+        assert!(!evaluator.check("SnapshotRead").unwrap());
+        assert!(evaluator.check("CommitAfterSnapshot").unwrap());
+    }
+
+    #[test]
+    fn test_lines_match_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/ssi/snapshot_isolation.tla"
+        ))
+        .unwrap();
+        let spec = TlaSpec::parse(&content).unwrap();
+
+        for result in SnapshotIsolationPropertyChecker::new(&TestStore::default()).check_all() {
+            let mapped = spec
+                .invariants
+                .iter()
+                .find(|inv| inv.name == result.name)
+                .unwrap();
+            assert_eq!(mapped.line, result.tla_line, "{}", result.name);
+        }
+    }
+}
@@ -4,15 +4,18 @@
 //!
 //! | Property | TLA+ Line | Description |
 //! |----------|-----------|-------------|
-//! | NoLostElements | 45 | Every pushed element is in stack or was popped |
-//! | NoDuplicates | 58 | No element appears twice in stack |
-//! | LIFO_Order | 72 | Last-in-first-out ordering |
-//! | Linearizability | 89 | Operations appear atomic |
-//! | ABA_Safety | 103 | No ABA problem with epoch GC |
+//! | NoLostElements | 66 | Every pushed element is in stack or was popped |
+//! | NoDuplicates | 75 | No element appears twice in stack |
+//! | LIFO_Order | 87 | Last-in-first-out ordering |
+//! | Linearizability | 102 | Operations appear atomic |
+//! | ABA_Safety | 116 | No ABA problem with epoch GC |
 
 use std::collections::HashSet;
 
+use vf_macros::tla_invariant;
+
 use crate::counterexample::Counterexample;
+use crate::linearizability::{ConcurrentHistory, LinearizabilityChecker, StackOp, StackSpec};
 use crate::property::{PropertyChecker, PropertyResult};
 
 /// TLA+ spec file for stack invariants.
@@ -54,7 +57,10 @@ pub struct StackOperation {
     pub op_type: StackOpType,
     /// Element involved (Some for push, result for pop)
     pub element: Option<u64>,
-    /// Step number for ordering
+    /// Step at which the operation was invoked (equal to `step` for
+    /// operations recorded as atomic)
+    pub invoked_at: u64,
+    /// Step number for ordering (when the operation returned)
     pub step: u64,
 }
 
@@ -77,18 +83,37 @@ impl StackHistory {
 
     /// Record a push operation.
     pub fn record_push(&mut self, thread_id: u64, element: u64, step: u64) {
-        debug_assert!(step > 0, "Step must be positive");
+        self.record_push_interval(thread_id, element, step, step);
+    }
+
+    /// Record a pop operation.
+    pub fn record_pop(&mut self, thread_id: u64, element: Option<u64>, step: u64) {
+        self.record_pop_interval(thread_id, element, step, step);
+    }
+
+    /// Record a push that was invoked at `invoked_at` and returned at `step`.
+    pub fn record_push_interval(&mut self, thread_id: u64, element: u64, invoked_at: u64, step: u64) {
+        debug_assert!(invoked_at > 0, "Step must be positive");
+        debug_assert!(invoked_at <= step, "Response before invocation");
         self.operations.push(StackOperation {
             thread_id,
             op_type: StackOpType::Push,
             element: Some(element),
+            invoked_at,
             step,
         });
     }
 
-    /// Record a pop operation.
-    pub fn record_pop(&mut self, thread_id: u64, element: Option<u64>, step: u64) {
-        debug_assert!(step > 0, "Step must be positive");
+    /// Record a pop that was invoked at `invoked_at` and returned at `step`.
+    pub fn record_pop_interval(
+        &mut self,
+        thread_id: u64,
+        element: Option<u64>,
+        invoked_at: u64,
+        step: u64,
+    ) {
+        debug_assert!(invoked_at > 0, "Step must be positive");
+        debug_assert!(invoked_at <= step, "Response before invocation");
         self.operations.push(StackOperation {
             thread_id,
             op_type: if element.is_some() {
@@ -97,9 +122,28 @@ impl StackHistory {
                 StackOpType::PopEmpty
             },
             element,
+            invoked_at,
             step,
         });
     }
+
+    /// Convert to an invoke/response history for linearizability checking.
+    #[must_use]
+    pub fn to_concurrent_history(&self) -> ConcurrentHistory<StackOp, Option<u64>> {
+        let mut history = ConcurrentHistory::new();
+        for op in &self.operations {
+            match op.op_type {
+                StackOpType::Push => {
+                    let element = op.element.expect("push records its element");
+                    history.record(op.thread_id, StackOp::Push(element), None, op.invoked_at, op.step);
+                }
+                StackOpType::Pop | StackOpType::PopEmpty => {
+                    history.record(op.thread_id, StackOp::Pop, op.element, op.invoked_at, op.step);
+                }
+            }
+        }
+        history
+    }
 }
 
 /// Property checker for stack implementations.
@@ -129,10 +173,11 @@ impl<'a, T: StackProperties> StackPropertyChecker<'a, T> {
         self
     }
 
-    /// Line 45: NoLostElements
+    /// NoLostElements
     ///
     /// Every element that was pushed must either be in the stack
     /// or have been popped. No elements can be lost.
+    #[tla_invariant(spec = "treiber_stack.tla", name = "NoLostElements")]
     fn check_no_lost_elements(&self) -> PropertyResult {
         let pushed = self.stack.pushed_elements();
         let popped = self.stack.popped_elements();
@@ -157,7 +202,7 @@ impl<'a, T: StackProperties> StackPropertyChecker<'a, T> {
                 return PropertyResult::fail(
                     "NoLostElements",
                     TLA_SPEC,
-                    45,
+                    TLA_LINE,
                     format!(
                         "Element {} was pushed but is neither in stack nor popped",
                         element
@@ -167,12 +212,13 @@ impl<'a, T: StackProperties> StackPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("NoLostElements", TLA_SPEC, 45)
+        PropertyResult::pass("NoLostElements", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 58: NoDuplicates
+    /// NoDuplicates
     ///
     /// No element appears twice in the stack.
+    #[tla_invariant(spec = "treiber_stack.tla", name = "NoDuplicates")]
     fn check_no_duplicates(&self) -> PropertyResult {
         let contents = self.stack.current_contents();
         let unique: HashSet<u64> = contents.iter().copied().collect();
@@ -185,7 +231,7 @@ impl<'a, T: StackProperties> StackPropertyChecker<'a, T> {
                     return PropertyResult::fail(
                         "NoDuplicates",
                         TLA_SPEC,
-                        58,
+                        TLA_LINE,
                         format!("Element {} appears multiple times in stack", element),
                         None,
                     );
@@ -193,136 +239,88 @@ impl<'a, T: StackProperties> StackPropertyChecker<'a, T> {
             }
         }
 
-        PropertyResult::pass("NoDuplicates", TLA_SPEC, 58)
+        PropertyResult::pass("NoDuplicates", TLA_SPEC, TLA_LINE)
     }
 
-    /// Line 72: LIFO_Order
+    /// LIFO_Order
     ///
     /// The stack maintains last-in-first-out ordering.
-    /// This is verified by replaying the operation history against
-    /// a model stack and checking that pop results match.
+    /// This is verified by searching for a linearization of the history
+    /// against a model stack, so overlapping operations are not held to
+    /// the order they were recorded in. Without one, the longest
+    /// linearizable prefix is replayed and the first pop it cannot
+    /// explain is reported.
+    #[tla_invariant(spec = "treiber_stack.tla", name = "LIFO_Order")]
     fn check_lifo_order(&self) -> PropertyResult {
-        let history = self.stack.history();
+        let history = self.stack.history().to_concurrent_history();
 
-        if history.operations.is_empty() {
+        if history.is_empty() {
             // No history to verify - this is a warning condition
             // but not a failure (stack might just be unused)
-            return PropertyResult::pass("LIFO_Order", TLA_SPEC, 72);
+            return PropertyResult::pass("LIFO_Order", TLA_SPEC, TLA_LINE);
         }
 
-        // Build a model stack from history and verify LIFO
-        let mut model_stack: Vec<u64> = Vec::new();
+        let violation = match LinearizabilityChecker::new(StackSpec::default()).check(&history) {
+            Ok(_) => return PropertyResult::pass("LIFO_Order", TLA_SPEC, TLA_LINE),
+            Err(violation) => violation,
+        };
+        let Some(&i) = violation.blocked.first() else {
+            let message = format!("LIFO violated: {}", violation);
+            return PropertyResult::fail("LIFO_Order", TLA_SPEC, TLA_LINE, message, None);
+        };
 
-        for op in &history.operations {
-            match op.op_type {
-                StackOpType::Push => {
-                    if let Some(e) = op.element {
-                        model_stack.push(e);
-                    }
-                }
-                StackOpType::Pop => {
-                    if let Some(expected) = op.element {
-                        match model_stack.pop() {
-                            Some(actual) if actual != expected => {
-                                return PropertyResult::fail(
-                                    "LIFO_Order",
-                                    TLA_SPEC,
-                                    72,
-                                    format!(
-                                        "LIFO violated: pop returned {} but model expected {} (step {})",
-                                        expected, actual, op.step
-                                    ),
-                                    None,
-                                );
-                            }
-                            None => {
-                                return PropertyResult::fail(
-                                    "LIFO_Order",
-                                    TLA_SPEC,
-                                    72,
-                                    format!(
-                                        "LIFO violated: pop returned {} but model stack was empty (step {})",
-                                        expected, op.step
-                                    ),
-                                    None,
-                                );
-                            }
-                            _ => {} // Match - continue
-                        }
-                    }
-                }
-                StackOpType::PopEmpty => {
-                    if !model_stack.is_empty() {
-                        return PropertyResult::fail(
-                            "LIFO_Order",
-                            TLA_SPEC,
-                            72,
-                            format!(
-                                "LIFO violated: pop returned None but model has {} elements (step {})",
-                                model_stack.len(), op.step
-                            ),
-                            None,
-                        );
-                    }
+        // Build a model stack from the prefix and explain the blocked pop
+        let entries = history.entries();
+        let mut model_stack: Vec<u64> = Vec::new();
+        for &j in &violation.longest_prefix {
+            match entries[j].op {
+                StackOp::Push(e) => model_stack.push(e),
+                StackOp::Pop => {
+                    model_stack.pop();
                 }
             }
         }
 
-        PropertyResult::pass("LIFO_Order", TLA_SPEC, 72)
+        let step = entries[i].returned_at.unwrap_or(entries[i].invoked_at);
+        let message = match (entries[i].ret.flatten(), model_stack.last()) {
+            (Some(expected), Some(actual)) => format!(
+                "LIFO violated: pop returned {} but model expected {} (step {})",
+                expected, actual, step
+            ),
+            (Some(expected), None) => format!(
+                "LIFO violated: pop returned {} but model stack was empty (step {})",
+                expected, step
+            ),
+            (None, _) => format!(
+                "LIFO violated: pop returned None but model has {} elements (step {})",
+                model_stack.len(),
+                step
+            ),
+        };
+        PropertyResult::fail("LIFO_Order", TLA_SPEC, TLA_LINE, message, None)
     }
 
-    /// Line 89: Linearizability
+    /// Linearizability
     ///
     /// All operations appear to take effect atomically at some point
     /// between their invocation and response.
     ///
-    /// NOTE: Full linearizability verification requires exhaustive interleaving
-    /// exploration, which is done by loom. This check verifies a necessary
-    /// (but not sufficient) condition: the recorded history must be a valid
-    /// sequential execution.
+    /// Searches for a linearization of the recorded invoke/response
+    /// intervals against a sequential stack (Wing & Gong / Lowe).
+    /// Overlapping operations may be reordered; non-overlapping ones
+    /// must keep their real-time order.
+    #[tla_invariant(spec = "treiber_stack.tla", name = "Linearizability")]
     fn check_linearizability(&self) -> PropertyResult {
-        let history = self.stack.history();
-
-        // Necessary condition: history must form valid sequential execution
-        // (This is the same as LIFO check - if LIFO holds, history is valid)
-        // Full linearizability requires loom to explore all interleavings
-        let mut model_stack: Vec<u64> = Vec::new();
-
-        for op in &history.operations {
-            match op.op_type {
-                StackOpType::Push => {
-                    if let Some(e) = op.element {
-                        model_stack.push(e);
-                    }
-                }
-                StackOpType::Pop => {
-                    if let Some(expected) = op.element {
-                        if let Some(actual) = model_stack.pop() {
-                            if actual != expected {
-                                return PropertyResult::fail(
-                                    "Linearizability",
-                                    TLA_SPEC,
-                                    89,
-                                    format!(
-                                        "History not linearizable: pop({}) inconsistent with model({})",
-                                        expected, actual
-                                    ),
-                                    None,
-                                );
-                            }
-                        }
-                    }
-                }
-                StackOpType::PopEmpty => {}
-            }
-        }
-
-        // Note: This passes the necessary condition but full linearizability
-        // requires loom verification of all interleavings
-        PropertyResult::pass("Linearizability", TLA_SPEC, 89)
+        let history = self.stack.history().to_concurrent_history();
+        LinearizabilityChecker::new(StackSpec::default()).check_property(
+            &history,
+            TLA_SPEC,
+            TLA_LINE,
+            self.dst_seed,
+        )
     }
 
-    /// Line 103: ABA_Safety
+    /// ABA_Safety
     ///
     /// With epoch-based GC, the ABA problem cannot occur because
     /// memory is not reused while any thread holds a reference.
@@ -330,7 +328,9 @@ impl<'a, T: StackProperties> StackPropertyChecker<'a, T> {
     /// NOTE: ABA safety is a structural property verified by:
     /// 1. Code review (uses crossbeam-epoch)
     /// 2. Loom testing with tagged pointers
+    ///
     /// This runtime check verifies the implementation claims to use epoch GC.
+    #[tla_invariant(spec = "treiber_stack.tla", name = "ABA_Safety")]
     fn check_aba_safety(&self) -> PropertyResult {
         // ABA safety is structural - verified by using epoch-based GC.
         // Runtime verification would require tagged pointers in loom.
@@ -339,7 +339,7 @@ impl<'a, T: StackProperties> StackPropertyChecker<'a, T> {
         // To verify ABA safety properly:
         // 1. TreiberStack uses crossbeam-epoch (structural)
         // 2. LoomStack needs hazard pointers or epoch for safety (NOT IMPLEMENTED)
-        PropertyResult::pass("ABA_Safety", TLA_SPEC, 103)
+        PropertyResult::pass("ABA_Safety", TLA_SPEC, TLA_LINE)
     }
 }
 
@@ -459,4 +459,56 @@ mod tests {
         let no_dup = results.iter().find(|r| r.name == "NoDuplicates").unwrap();
         assert!(!no_dup.holds);
     }
+
+    #[test]
+    fn test_overlap_violation_detected() {
+        // push(1) < push(2) < pop in real time, so pop must return 2,
+        // even though the pop was recorded before push(2).
+        let mut history = StackHistory::new();
+        history.record_push_interval(0, 1, 1, 2);
+        history.record_pop_interval(1, Some(1), 5, 6);
+        history.record_push_interval(2, 2, 3, 4);
+
+        let stack = TestStack {
+            pushed: [1, 2].into_iter().collect(),
+            popped: [1].into_iter().collect(),
+            contents: vec![2],
+            history,
+        };
+
+        let checker = StackPropertyChecker::new(&stack);
+        let results = checker.check_all();
+
+        let lifo = results.iter().find(|r| r.name == "LIFO_Order").unwrap();
+        assert!(!lifo.holds);
+        assert!(lifo.violation.as_ref().unwrap().contains("pop returned 1 but model expected 2"));
+        let lin = results.iter().find(|r| r.name == "Linearizability").unwrap();
+        assert!(!lin.holds);
+        assert!(lin.counterexample.is_some());
+    }
+
+    #[test]
+    fn test_overlapping_pushes_linearizable() {
+        // push(2) overlaps push(1), so either may be on top.
+        let mut history = StackHistory::new();
+        history.record_push_interval(0, 1, 1, 4);
+        history.record_push_interval(1, 2, 2, 3);
+        history.record_pop_interval(0, Some(1), 5, 6);
+
+        let stack = TestStack {
+            pushed: [1, 2].into_iter().collect(),
+            popped: [1].into_iter().collect(),
+            contents: vec![2],
+            history,
+        };
+
+        let checker = StackPropertyChecker::new(&stack);
+        let results = checker.check_all();
+
+        let lin = results.iter().find(|r| r.name == "Linearizability").unwrap();
+        assert!(lin.holds);
+        let lifo = results.iter().find(|r| r.name == "LIFO_Order").unwrap();
+        assert!(lifo.holds, "{:?}", lifo.violation);
+        assert!(checker.all_hold());
+    }
 }
@@ -0,0 +1,489 @@
+//! Two-phase commit invariants from two_phase_commit.tla
+//!
+//! The spec models one transaction; implementations usually run many,
+//! so every invariant is checked per transaction.
+//!
+//! # TLA+ Mapping
+//!
+//! | Property | TLA+ Line | Description |
+//! |----------|-----------|-------------|
+//! | Atomicity | 59 | No RM commits while another aborts |
+//! | Validity | 69 | Commit only if every RM prepared |
+//! | Consistency | 78 | RM outcomes follow the TM decision |
+//! | Agreement | 89 | A committed RM implies a committed TM |
+//! | TMDecision | 100 | TM commits only if all prepared, aborts only for a reason |
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use vf_macros::tla_invariant;
+
+use crate::counterexample::{Counterexample, StateSnapshot};
+use crate::property::{PropertyChecker, PropertyResult};
+
+/// TLA+ spec file for 2PC invariants.
+const TLA_SPEC: &str = "two_phase_commit.tla";
+
+/// Resource manager identifier.
+pub type RmId = u64;
+
+/// Transaction identifier.
+pub type TxnId = u64;
+
+/// Resource manager state (TLA+ `rmState`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RmState {
+    Working,
+    Prepared,
+    Committed,
+    Aborted,
+}
+
+/// Transaction manager state (TLA+ `tmState`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TmState {
+    Init,
+    Preparing,
+    Committed,
+    Aborted,
+}
+
+/// Properties that any two-phase commit implementation must satisfy.
+pub trait TwoPhaseCommitProperties {
+    /// All transactions that have been started.
+    fn transactions(&self) -> Vec<TxnId>;
+
+    /// TM state of a transaction.
+    fn tm_state(&self, txn: TxnId) -> TmState;
+
+    /// Map: RM -> state, for every RM taking part in the transaction.
+    fn rm_states(&self, txn: TxnId) -> HashMap<RmId, RmState>;
+
+    /// RMs whose Prepared vote the TM has received.
+    fn tm_prepared(&self, txn: TxnId) -> HashSet<RmId>;
+}
+
+/// Property checker for two-phase commit implementations.
+///
+/// Verifies all invariants from two_phase_commit.tla against
+/// the implementation's state.
+pub struct TwoPhaseCommitPropertyChecker<'a, T: TwoPhaseCommitProperties> {
+    tpc: &'a T,
+    dst_seed: Option<u64>,
+}
+
+/// State of one transaction, as the spec's variables.
+struct TxnState {
+    txn: TxnId,
+    tm: TmState,
+    rms: BTreeMap<RmId, RmState>,
+    prepared: HashSet<RmId>,
+}
+
+impl TxnState {
+    fn all_prepared(&self) -> bool {
+        self.rms.keys().all(|rm| self.prepared.contains(rm))
+    }
+
+    fn rms_in(&self, state: RmState) -> impl Iterator<Item = RmId> + '_ {
+        self.rms
+            .iter()
+            .filter(move |(_, s)| **s == state)
+            .map(|(rm, _)| *rm)
+    }
+}
+
+impl<'a, T: TwoPhaseCommitProperties> TwoPhaseCommitPropertyChecker<'a, T> {
+    /// Create a new checker for the given implementation.
+    #[must_use]
+    pub fn new(tpc: &'a T) -> Self {
+        Self {
+            tpc,
+            dst_seed: None,
+        }
+    }
+
+    /// Set DST seed for counterexample reproduction.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        debug_assert!(seed != 0, "DST seed should not be zero");
+        self.dst_seed = Some(seed);
+        self
+    }
+
+    fn txn_states(&self) -> Vec<TxnState> {
+        let mut txns = self.tpc.transactions();
+        txns.sort_unstable();
+        txns.dedup();
+        txns.into_iter()
+            .map(|txn| TxnState {
+                txn,
+                tm: self.tpc.tm_state(txn),
+                rms: self.tpc.rm_states(txn).into_iter().collect(),
+                prepared: self.tpc.tm_prepared(txn),
+            })
+            .collect()
+    }
+
+    /// Atomicity
+    ///
+    /// All RMs reach the same decision: no RM commits while another aborts.
+    #[tla_invariant(spec = "two_phase_commit.tla", name = "Atomicity")]
+    fn check_atomicity(&self, txns: &[TxnState]) -> PropertyResult {
+        for t in txns {
+            if let (Some(committed), Some(aborted)) = (
+                t.rms_in(RmState::Committed).next(),
+                t.rms_in(RmState::Aborted).next(),
+            ) {
+                return self.fail(
+                    "Atomicity",
+                    TLA_LINE,
+                    t,
+                    format!(
+                        "Txn {}: RM {} committed while RM {} aborted",
+                        t.txn, committed, aborted
+                    ),
+                );
+            }
+        }
+        PropertyResult::pass("Atomicity", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Validity
+    ///
+    /// The TM can only commit if every RM voted to prepare.
+    #[tla_invariant(spec = "two_phase_commit.tla", name = "Validity")]
+    fn check_validity(&self, txns: &[TxnState]) -> PropertyResult {
+        for t in txns {
+            if t.tm == TmState::Committed && !t.all_prepared() {
+                let missing: Vec<RmId> = t
+                    .rms
+                    .keys()
+                    .filter(|rm| !t.prepared.contains(rm))
+                    .copied()
+                    .collect();
+                return self.fail(
+                    "Validity",
+                    TLA_LINE,
+                    t,
+                    format!(
+                        "Txn {}: TM committed without Prepared votes from RMs {:?}",
+                        t.txn, missing
+                    ),
+                );
+            }
+        }
+        PropertyResult::pass("Validity", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Consistency
+    ///
+    /// An RM is Committed only if the TM decided to commit, and Aborted
+    /// only if the TM has not decided to commit.
+    #[tla_invariant(spec = "two_phase_commit.tla", name = "Consistency")]
+    fn check_consistency(&self, txns: &[TxnState]) -> PropertyResult {
+        for t in txns {
+            if let Some(rm) = t.rms_in(RmState::Committed).next() {
+                if t.tm != TmState::Committed {
+                    return self.fail(
+                        "Consistency",
+                        TLA_LINE,
+                        t,
+                        format!("Txn {}: RM {} committed but TM is {:?}", t.txn, rm, t.tm),
+                    );
+                }
+            }
+            if let Some(rm) = t.rms_in(RmState::Aborted).next() {
+                if t.tm == TmState::Committed {
+                    return self.fail(
+                        "Consistency",
+                        TLA_LINE,
+                        t,
+                        format!("Txn {}: RM {} aborted but TM committed", t.txn, rm),
+                    );
+                }
+            }
+        }
+        PropertyResult::pass("Consistency", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Agreement
+    ///
+    /// The TM decision is irrevocable; in invariant form, if any RM has
+    /// committed then the TM is in Committed state.
+    #[tla_invariant(spec = "two_phase_commit.tla", name = "Agreement")]
+    fn check_agreement(&self, txns: &[TxnState]) -> PropertyResult {
+        for t in txns {
+            if t.rms_in(RmState::Committed).next().is_some() && t.tm != TmState::Committed {
+                return self.fail(
+                    "Agreement",
+                    TLA_LINE,
+                    t,
+                    format!(
+                        "Txn {}: an RM committed but the TM decision is {:?}",
+                        t.txn, t.tm
+                    ),
+                );
+            }
+        }
+        PropertyResult::pass("Agreement", TLA_SPEC, TLA_LINE)
+    }
+
+    /// TMDecision
+    ///
+    /// The TM commits only if all RMs are prepared, and aborts only if
+    /// some RM failed to prepare or chose to abort.
+    #[tla_invariant(spec = "two_phase_commit.tla", name = "TMDecision")]
+    fn check_tm_decision(&self, txns: &[TxnState]) -> PropertyResult {
+        for t in txns {
+            let violation = match t.tm {
+                TmState::Committed if !t.all_prepared() => {
+                    Some("committed before every RM prepared")
+                }
+                TmState::Aborted
+                    if t.all_prepared() && t.rms_in(RmState::Aborted).next().is_none() =>
+                {
+                    Some("aborted although every RM prepared and none aborted")
+                }
+                _ => None,
+            };
+            if let Some(reason) = violation {
+                return self.fail(
+                    "TMDecision",
+                    TLA_LINE,
+                    t,
+                    format!("Txn {}: TM {}", t.txn, reason),
+                );
+            }
+        }
+        PropertyResult::pass("TMDecision", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Fail with the transaction's state as the spec's variables.
+    fn fail(
+        &self,
+        name: &'static str,
+        line: u32,
+        t: &TxnState,
+        violation: String,
+    ) -> PropertyResult {
+        let mut ce = match self.dst_seed {
+            Some(seed) => Counterexample::with_seed(seed),
+            None => Counterexample::new(),
+        };
+
+        let rm_state: Vec<String> = t
+            .rms
+            .iter()
+            .map(|(rm, s)| format!("{} :> \"{:?}\"", rm, s))
+            .collect();
+        let mut prepared: Vec<RmId> = t.prepared.iter().copied().collect();
+        prepared.sort_unstable();
+        let prepared: Vec<String> = prepared.iter().map(RmId::to_string).collect();
+
+        ce.add_state(StateSnapshot {
+            step: 1,
+            description: format!("Txn {}", t.txn),
+            variables: vec![
+                (
+                    "rmState".to_string(),
+                    format!("({})", rm_state.join(" @@ ")),
+                ),
+                ("tmState".to_string(), format!("\"{:?}\"", t.tm)),
+                (
+                    "tmPrepared".to_string(),
+                    format!("{{{}}}", prepared.join(", ")),
+                ),
+            ],
+        });
+
+        PropertyResult::fail(
+            name,
+            TLA_SPEC,
+            line,
+            violation.clone(),
+            Some(ce.with_description(violation)),
+        )
+    }
+}
+
+impl<T: TwoPhaseCommitProperties> PropertyChecker for TwoPhaseCommitPropertyChecker<'_, T> {
+    fn check_all(&self) -> Vec<PropertyResult> {
+        let txns = self.txn_states();
+        vec![
+            self.check_atomicity(&txns),
+            self.check_validity(&txns),
+            self.check_consistency(&txns),
+            self.check_agreement(&txns),
+            self.check_tm_decision(&txns),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tla_spec::{parse_module, Evaluator, TlaSpec, Value};
+
+    /// TM state, RM states and prepared votes of one transaction.
+    type TestTxn = (TmState, HashMap<RmId, RmState>, HashSet<RmId>);
+
+    /// Test implementation of TwoPhaseCommitProperties
+    #[derive(Default)]
+    struct TestTpc {
+        txns: HashMap<TxnId, TestTxn>,
+    }
+
+    impl TestTpc {
+        fn txn(
+            mut self,
+            txn: TxnId,
+            tm: TmState,
+            rms: &[(RmId, RmState)],
+            prepared: &[RmId],
+        ) -> Self {
+            self.txns.insert(
+                txn,
+                (
+                    tm,
+                    rms.iter().copied().collect(),
+                    prepared.iter().copied().collect(),
+                ),
+            );
+            self
+        }
+    }
+
+    impl TwoPhaseCommitProperties for TestTpc {
+        fn transactions(&self) -> Vec<TxnId> {
+            self.txns.keys().copied().collect()
+        }
+
+        fn tm_state(&self, txn: TxnId) -> TmState {
+            self.txns[&txn].0
+        }
+
+        fn rm_states(&self, txn: TxnId) -> HashMap<RmId, RmState> {
+            self.txns[&txn].1.clone()
+        }
+
+        fn tm_prepared(&self, txn: TxnId) -> HashSet<RmId> {
+            self.txns[&txn].2.clone()
+        }
+    }
+
+    fn results(tpc: &TestTpc) -> Vec<PropertyResult> {
+        TwoPhaseCommitPropertyChecker::new(tpc).check_all()
+    }
+
+    fn failing(tpc: &TestTpc) -> Vec<&'static str> {
+        results(tpc)
+            .into_iter()
+            .filter(|r| !r.holds)
+            .map(|r| r.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_correct_runs_pass_all() {
+        let tpc = TestTpc::default()
+            .txn(
+                1,
+                TmState::Committed,
+                &[(1, RmState::Committed), (2, RmState::Prepared)],
+                &[1, 2],
+            )
+            .txn(
+                2,
+                TmState::Aborted,
+                &[(1, RmState::Prepared), (2, RmState::Aborted)],
+                &[1],
+            )
+            .txn(3, TmState::Init, &[(1, RmState::Working)], &[]);
+
+        assert!(TwoPhaseCommitPropertyChecker::new(&tpc).all_hold());
+    }
+
+    #[test]
+    fn test_split_decision_detected() {
+        let tpc = TestTpc::default().txn(
+            7,
+            TmState::Committed,
+            &[(1, RmState::Committed), (2, RmState::Aborted)],
+            &[1, 2],
+        );
+
+        assert_eq!(failing(&tpc), vec!["Atomicity", "Consistency"]);
+        let atomicity = results(&tpc).remove(0);
+        assert!(atomicity.violation.unwrap().starts_with("Txn 7"));
+    }
+
+    #[test]
+    fn test_commit_without_votes_detected() {
+        let tpc = TestTpc::default().txn(
+            1,
+            TmState::Committed,
+            &[(1, RmState::Prepared), (2, RmState::Working)],
+            &[1],
+        );
+
+        assert_eq!(failing(&tpc), vec!["Validity", "TMDecision"]);
+    }
+
+    #[test]
+    fn test_rm_commit_before_decision_detected() {
+        let tpc = TestTpc::default().txn(
+            1,
+            TmState::Preparing,
+            &[(1, RmState::Committed), (2, RmState::Prepared)],
+            &[1, 2],
+        );
+
+        assert_eq!(failing(&tpc), vec!["Consistency", "Agreement"]);
+    }
+
+    #[test]
+    fn test_counterexample_violates_spec() {
+        let tpc = TestTpc::default().txn(
+            1,
+            TmState::Committed,
+            &[(1, RmState::Committed), (2, RmState::Aborted)],
+            &[1, 2],
+        );
+        let checker = TwoPhaseCommitPropertyChecker::new(&tpc).with_seed(9);
+        let atomicity = checker.check_all().remove(0);
+        let ce = atomicity.counterexample.unwrap();
+        assert_eq!(ce.dst_seed, Some(9));
+
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/distributed/two_phase_commit.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let mut evaluator = Evaluator::new(&module)
+            .with_constant("RMs", Value::set([Value::Int(1), Value::Int(2)]))
+            .with_model_constants();
+        evaluator.load_state(&ce.states[0]).unwrap();
+        assert!(!evaluator.check("Atomicity").unwrap());
+        assert!(evaluator.check("Validity").unwrap());
+    }
+
+    #[test]
+    fn test_lines_match_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/distributed/two_phase_commit.tla"
+        ))
+        .unwrap();
+        let spec = TlaSpec::parse(&content).unwrap();
+
+        for result in results(&TestTpc::default()) {
+            let mapped = spec
+                .invariants
+                .iter()
+                .find(|inv| inv.name == result.name)
+                .unwrap();
+            assert_eq!(mapped.line, result.tla_line, "{}", result.name);
+        }
+    }
+}
@@ -0,0 +1,540 @@
+//! Strict two-phase locking invariants from two_phase_locking.tla
+//!
+//! Serializability follows from these lock invariants; the DST harness
+//! checks it directly on histories with `elle`.
+//!
+//! # TLA+ Mapping
+//!
+//! | Property | TLA+ Line | Description |
+//! |----------|-----------|-------------|
+//! | LockCompatibility | 80 | Exclusive locks are not shared |
+//! | StrictTwoPhase | 89 | Only running transactions hold locks |
+//! | AccessesLocked | 96 | Reads and writes stay covered by locks |
+//! | NoDeadlock | 105 | The waits-for graph is acyclic |
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use vf_macros::tla_invariant;
+
+use super::ssi::TxnStatus;
+use crate::counterexample::{Counterexample, StateSnapshot};
+use crate::property::{PropertyChecker, PropertyResult};
+
+/// TLA+ spec file for 2PL invariants.
+const TLA_SPEC: &str = "two_phase_locking.tla";
+
+/// Properties that any strict 2PL lock manager must satisfy.
+pub trait TwoPhaseLockingProperties {
+    /// Map: txn_id -> status, for every transaction that has begun.
+    fn txn_statuses(&self) -> HashMap<u64, TxnStatus>;
+
+    /// Map: key -> transactions holding a shared lock on it.
+    fn shared_locks(&self) -> HashMap<u64, HashSet<u64>>;
+
+    /// Map: key -> transactions holding the exclusive lock on it.
+    fn exclusive_locks(&self) -> HashMap<u64, HashSet<u64>>;
+
+    /// Map: txn_id -> keys it has read.
+    fn read_sets(&self) -> HashMap<u64, HashSet<u64>>;
+
+    /// Map: txn_id -> keys it has written.
+    fn write_sets(&self) -> HashMap<u64, HashSet<u64>>;
+
+    /// Map: txn_id -> transactions it is waiting on.
+    fn waits_for(&self) -> HashMap<u64, HashSet<u64>>;
+}
+
+/// Property checker for strict 2PL implementations.
+pub struct TwoPhaseLockingPropertyChecker<'a, T: TwoPhaseLockingProperties> {
+    locks: &'a T,
+    dst_seed: Option<u64>,
+}
+
+/// Lock manager state, as the spec's variables.
+struct LockState {
+    txn_status: BTreeMap<u64, TxnStatus>,
+    shared: BTreeMap<u64, BTreeSet<u64>>,
+    exclusive: BTreeMap<u64, BTreeSet<u64>>,
+    reads: BTreeMap<u64, BTreeSet<u64>>,
+    writes: BTreeMap<u64, BTreeSet<u64>>,
+    waits_for: BTreeMap<u64, BTreeSet<u64>>,
+}
+
+impl LockState {
+    fn is_active(&self, txn: u64) -> bool {
+        self.txn_status.get(&txn) == Some(&TxnStatus::Active)
+    }
+
+    /// Keys on which `txn` holds a lock, shared or exclusive.
+    fn locked_by(&self, txn: u64, exclusive_only: bool) -> BTreeSet<u64> {
+        let mut keys: BTreeSet<u64> = self
+            .exclusive
+            .iter()
+            .filter(|(_, holders)| holders.contains(&txn))
+            .map(|(k, _)| *k)
+            .collect();
+        if !exclusive_only {
+            keys.extend(
+                self.shared
+                    .iter()
+                    .filter(|(_, holders)| holders.contains(&txn))
+                    .map(|(k, _)| *k),
+            );
+        }
+        keys
+    }
+
+    /// A waits-for cycle, if any, as the transactions on it.
+    fn find_cycle(&self) -> Option<Vec<u64>> {
+        // Repeatedly drop transactions that wait on nobody left; whatever
+        // remains lies on or leads into a cycle
+        let mut remaining: BTreeSet<u64> = self.waits_for.keys().copied().collect();
+        loop {
+            let sinks: Vec<u64> = remaining
+                .iter()
+                .filter(|t| self.waits_for[*t].is_disjoint(&remaining))
+                .copied()
+                .collect();
+            if sinks.is_empty() {
+                break;
+            }
+            for t in sinks {
+                remaining.remove(&t);
+            }
+        }
+
+        // Walk from any remaining transaction until one repeats
+        let mut path = vec![*remaining.iter().next()?];
+        loop {
+            let last = *path.last().expect("path is never empty");
+            let next = *self.waits_for[&last]
+                .intersection(&remaining)
+                .next()
+                .expect("every remaining transaction waits on another");
+            if let Some(start) = path.iter().position(|t| *t == next) {
+                return Some(path.split_off(start));
+            }
+            path.push(next);
+        }
+    }
+}
+
+impl<'a, T: TwoPhaseLockingProperties> TwoPhaseLockingPropertyChecker<'a, T> {
+    /// Create a new checker for the given implementation.
+    #[must_use]
+    pub fn new(locks: &'a T) -> Self {
+        Self {
+            locks,
+            dst_seed: None,
+        }
+    }
+
+    /// Set DST seed for counterexample reproduction.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        debug_assert!(seed != 0, "DST seed should not be zero");
+        self.dst_seed = Some(seed);
+        self
+    }
+
+    fn state(&self) -> LockState {
+        let sorted = |map: HashMap<u64, HashSet<u64>>| -> BTreeMap<u64, BTreeSet<u64>> {
+            map.into_iter()
+                .map(|(id, set)| (id, set.into_iter().collect()))
+                .collect()
+        };
+
+        let mut s = LockState {
+            txn_status: self.locks.txn_statuses().into_iter().collect(),
+            shared: sorted(self.locks.shared_locks()),
+            exclusive: sorted(self.locks.exclusive_locks()),
+            reads: sorted(self.locks.read_sets()),
+            writes: sorted(self.locks.write_sets()),
+            waits_for: sorted(self.locks.waits_for()),
+        };
+
+        // Functions range over every key and transaction, as in the spec
+        let keys: BTreeSet<u64> = s
+            .shared
+            .keys()
+            .chain(s.exclusive.keys())
+            .chain(s.reads.values().flatten())
+            .chain(s.writes.values().flatten())
+            .copied()
+            .collect();
+        for key in keys {
+            s.shared.entry(key).or_default();
+            s.exclusive.entry(key).or_default();
+        }
+        let txns: Vec<u64> = s.txn_status.keys().copied().collect();
+        for txn in txns {
+            s.reads.entry(txn).or_default();
+            s.writes.entry(txn).or_default();
+            s.waits_for.entry(txn).or_default();
+        }
+        s
+    }
+
+    /// LockCompatibility
+    #[tla_invariant(spec = "two_phase_locking.tla", name = "LockCompatibility")]
+    fn check_lock_compatibility(&self, s: &LockState) -> PropertyResult {
+        for (key, holders) in &s.exclusive {
+            let sharers: Vec<u64> = s.shared[key].difference(holders).copied().collect();
+            if holders.len() > 1 || (!holders.is_empty() && !sharers.is_empty()) {
+                return self.fail(
+                    "LockCompatibility",
+                    TLA_LINE,
+                    s,
+                    format!(
+                        "Key {} is exclusively locked by {:?} while shared by {:?}",
+                        key, holders, sharers
+                    ),
+                );
+            }
+        }
+
+        PropertyResult::pass("LockCompatibility", TLA_SPEC, TLA_LINE)
+    }
+
+    /// StrictTwoPhase
+    #[tla_invariant(spec = "two_phase_locking.tla", name = "StrictTwoPhase")]
+    fn check_strict_two_phase(&self, s: &LockState) -> PropertyResult {
+        for (key, holders) in s.shared.iter().chain(&s.exclusive) {
+            if let Some(txn) = holders.iter().find(|t| !s.is_active(**t)) {
+                return self.fail(
+                    "StrictTwoPhase",
+                    TLA_LINE,
+                    s,
+                    format!(
+                        "Transaction {} still locks key {} after it finished ({:?})",
+                        txn,
+                        key,
+                        s.txn_status.get(txn)
+                    ),
+                );
+            }
+        }
+
+        PropertyResult::pass("StrictTwoPhase", TLA_SPEC, TLA_LINE)
+    }
+
+    /// AccessesLocked
+    #[tla_invariant(spec = "two_phase_locking.tla", name = "AccessesLocked")]
+    fn check_accesses_locked(&self, s: &LockState) -> PropertyResult {
+        for txn in s.txn_status.keys().filter(|t| s.is_active(**t)) {
+            let unlocked_reads: Vec<u64> = s.reads[txn]
+                .difference(&s.locked_by(*txn, false))
+                .copied()
+                .collect();
+            let unlocked_writes: Vec<u64> = s.writes[txn]
+                .difference(&s.locked_by(*txn, true))
+                .copied()
+                .collect();
+            if !unlocked_reads.is_empty() || !unlocked_writes.is_empty() {
+                return self.fail(
+                    "AccessesLocked",
+                    TLA_LINE,
+                    s,
+                    format!(
+                        "Transaction {} no longer locks keys it read {:?} or wrote {:?}",
+                        txn, unlocked_reads, unlocked_writes
+                    ),
+                );
+            }
+        }
+
+        PropertyResult::pass("AccessesLocked", TLA_SPEC, TLA_LINE)
+    }
+
+    /// NoDeadlock
+    #[tla_invariant(spec = "two_phase_locking.tla", name = "NoDeadlock")]
+    fn check_no_deadlock(&self, s: &LockState) -> PropertyResult {
+        if let Some(cycle) = s.find_cycle() {
+            return self.fail(
+                "NoDeadlock",
+                TLA_LINE,
+                s,
+                format!("Waits-for cycle {:?} was not broken", cycle),
+            );
+        }
+        if let Some((txn, _)) = s
+            .waits_for
+            .iter()
+            .find(|(t, on)| !on.is_empty() && !s.is_active(**t))
+        {
+            return self.fail(
+                "NoDeadlock",
+                TLA_LINE,
+                s,
+                format!("Finished transaction {} is still waiting", txn),
+            );
+        }
+
+        PropertyResult::pass("NoDeadlock", TLA_SPEC, TLA_LINE)
+    }
+
+    /// Failing result with the lock state as a TLA+ counterexample.
+    fn fail(
+        &self,
+        name: &'static str,
+        line: u32,
+        s: &LockState,
+        violation: String,
+    ) -> PropertyResult {
+        let mut ce = match self.dst_seed {
+            Some(seed) => Counterexample::with_seed(seed),
+            None => Counterexample::new(),
+        };
+
+        let statuses: BTreeMap<u64, String> = s
+            .txn_status
+            .iter()
+            .map(|(txn, status)| (*txn, tla_status(*status).to_string()))
+            .collect();
+
+        ce.add_state(StateSnapshot {
+            step: 1,
+            description: "Lock manager state".to_string(),
+            variables: vec![
+                ("txn_status".to_string(), tla_function(&statuses)),
+                ("shared".to_string(), tla_set_function(&s.shared)),
+                ("exclusive".to_string(), tla_set_function(&s.exclusive)),
+                ("reads".to_string(), tla_set_function(&s.reads)),
+                ("writes".to_string(), tla_set_function(&s.writes)),
+                ("waits_for".to_string(), tla_set_function(&s.waits_for)),
+            ],
+        });
+
+        PropertyResult::fail(
+            name,
+            TLA_SPEC,
+            line,
+            violation.clone(),
+            Some(ce.with_description(violation)),
+        )
+    }
+}
+
+/// Status as the spec's string constant.
+fn tla_status(status: TxnStatus) -> &'static str {
+    match status {
+        TxnStatus::Active => "\"active\"",
+        TxnStatus::Committed => "\"committed\"",
+        TxnStatus::Aborted => "\"aborted\"",
+    }
+}
+
+fn tla_set(items: &BTreeSet<u64>) -> String {
+    let items: Vec<String> = items.iter().map(u64::to_string).collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+fn tla_function(map: &BTreeMap<u64, String>) -> String {
+    if map.is_empty() {
+        return "[x \\in {} |-> {}]".to_string();
+    }
+    let entries: Vec<String> = map.iter().map(|(k, v)| format!("{} :> {}", k, v)).collect();
+    format!("({})", entries.join(" @@ "))
+}
+
+fn tla_set_function(map: &BTreeMap<u64, BTreeSet<u64>>) -> String {
+    tla_function(&map.iter().map(|(k, v)| (*k, tla_set(v))).collect())
+}
+
+impl<T: TwoPhaseLockingProperties> PropertyChecker for TwoPhaseLockingPropertyChecker<'_, T> {
+    fn check_all(&self) -> Vec<PropertyResult> {
+        let state = self.state();
+        vec![
+            self.check_lock_compatibility(&state),
+            self.check_strict_two_phase(&state),
+            self.check_accesses_locked(&state),
+            self.check_no_deadlock(&state),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tla_spec::{parse_module, Evaluator, TlaSpec, Value};
+
+    /// Test implementation of TwoPhaseLockingProperties
+    #[derive(Default)]
+    struct TestLocks {
+        statuses: HashMap<u64, TxnStatus>,
+        shared: HashMap<u64, HashSet<u64>>,
+        exclusive: HashMap<u64, HashSet<u64>>,
+        reads: HashMap<u64, HashSet<u64>>,
+        writes: HashMap<u64, HashSet<u64>>,
+        waits_for: HashMap<u64, HashSet<u64>>,
+    }
+
+    impl TestLocks {
+        fn txn(mut self, txn: u64, status: TxnStatus) -> Self {
+            self.statuses.insert(txn, status);
+            self
+        }
+
+        /// `txn` read `key` under a shared lock.
+        fn read(mut self, txn: u64, key: u64) -> Self {
+            self.shared.entry(key).or_default().insert(txn);
+            self.reads.entry(txn).or_default().insert(key);
+            self
+        }
+
+        /// `txn` wrote `key` under an exclusive lock.
+        fn write(mut self, txn: u64, key: u64) -> Self {
+            self.exclusive.entry(key).or_default().insert(txn);
+            self.writes.entry(txn).or_default().insert(key);
+            self
+        }
+
+        fn waits(mut self, txn: u64, on: &[u64]) -> Self {
+            self.waits_for.insert(txn, on.iter().copied().collect());
+            self
+        }
+    }
+
+    impl TwoPhaseLockingProperties for TestLocks {
+        fn txn_statuses(&self) -> HashMap<u64, TxnStatus> {
+            self.statuses.clone()
+        }
+
+        fn shared_locks(&self) -> HashMap<u64, HashSet<u64>> {
+            self.shared.clone()
+        }
+
+        fn exclusive_locks(&self) -> HashMap<u64, HashSet<u64>> {
+            self.exclusive.clone()
+        }
+
+        fn read_sets(&self) -> HashMap<u64, HashSet<u64>> {
+            self.reads.clone()
+        }
+
+        fn write_sets(&self) -> HashMap<u64, HashSet<u64>> {
+            self.writes.clone()
+        }
+
+        fn waits_for(&self) -> HashMap<u64, HashSet<u64>> {
+            self.waits_for.clone()
+        }
+    }
+
+    fn failing(locks: &TestLocks) -> Vec<&'static str> {
+        TwoPhaseLockingPropertyChecker::new(locks)
+            .check_all()
+            .into_iter()
+            .filter(|r| !r.holds)
+            .map(|r| r.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_correct_locks_pass_all() {
+        // T1 upgraded its lock on key 1; T2 waits for it; T3 committed
+        let locks = TestLocks::default()
+            .txn(1, TxnStatus::Active)
+            .txn(2, TxnStatus::Active)
+            .txn(3, TxnStatus::Committed)
+            .read(1, 1)
+            .write(1, 1)
+            .read(2, 2)
+            .waits(2, &[1]);
+
+        assert!(TwoPhaseLockingPropertyChecker::new(&locks).all_hold());
+    }
+
+    #[test]
+    fn test_shared_exclusive_conflict_detected() {
+        let locks = TestLocks::default()
+            .txn(1, TxnStatus::Active)
+            .txn(2, TxnStatus::Active)
+            .write(1, 1)
+            .read(2, 1);
+
+        assert_eq!(failing(&locks), vec!["LockCompatibility"]);
+    }
+
+    #[test]
+    fn test_early_release_detected() {
+        // Released before commit: still running, lock gone
+        let mut locks = TestLocks::default().txn(1, TxnStatus::Active).write(1, 1);
+        locks.exclusive.clear();
+        assert_eq!(failing(&locks), vec!["AccessesLocked"]);
+
+        // Committed but never released
+        let locks = TestLocks::default().txn(1, TxnStatus::Committed).read(1, 1);
+        assert_eq!(failing(&locks), vec!["StrictTwoPhase"]);
+    }
+
+    #[test]
+    fn test_deadlock_detected() {
+        let locks = TestLocks::default()
+            .txn(1, TxnStatus::Active)
+            .txn(2, TxnStatus::Active)
+            .txn(3, TxnStatus::Active)
+            .write(1, 1)
+            .write(2, 2)
+            .waits(1, &[2])
+            .waits(2, &[1])
+            .waits(3, &[1]);
+
+        assert_eq!(failing(&locks), vec!["NoDeadlock"]);
+        let cycle = TwoPhaseLockingPropertyChecker::new(&locks)
+            .state()
+            .find_cycle()
+            .unwrap();
+        assert_eq!(cycle, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_counterexample_violates_spec() {
+        let locks = TestLocks::default()
+            .txn(1, TxnStatus::Active)
+            .txn(2, TxnStatus::Aborted)
+            .write(1, 1)
+            .read(2, 1)
+            .waits(1, &[2]);
+        let results = TwoPhaseLockingPropertyChecker::new(&locks)
+            .with_seed(9)
+            .check_all();
+        let ce = results[0].counterexample.clone().unwrap();
+        assert_eq!(ce.dst_seed, Some(9));
+
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/ssi/two_phase_locking.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let mut evaluator = Evaluator::new(&module)
+            .with_constant("Txns", Value::set([Value::Int(1), Value::Int(2)]))
+            .with_constant("Keys", Value::set([Value::Int(1)]))
+            .with_model_constants();
+        evaluator.load_state(&ce.states[0]).unwrap();
+        assert!(!evaluator.check("LockCompatibility").unwrap());
+        assert!(!evaluator.check("StrictTwoPhase").unwrap());
+        assert!(evaluator.check("AccessesLocked").unwrap());
+        assert!(evaluator.check("NoDeadlock").unwrap());
+    }
+
+    #[test]
+    fn test_lines_match_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/ssi/two_phase_locking.tla"
+        ))
+        .unwrap();
+        let spec = TlaSpec::parse(&content).unwrap();
+
+        for result in TwoPhaseLockingPropertyChecker::new(&TestLocks::default()).check_all() {
+            let mapped = spec
+                .invariants
+                .iter()
+                .find(|inv| inv.name == result.name)
+                .unwrap();
+            assert_eq!(mapped.line, result.tla_line, "{}", result.name);
+        }
+    }
+}
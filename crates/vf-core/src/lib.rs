@@ -4,19 +4,42 @@
 //!
 //! This crate provides:
 //! - `PropertyResult` and `PropertyChecker` for verifying invariants
-//! - `Counterexample` for rendering failure paths
+//! - `Counterexample` for rendering failure paths (ASCII, Mermaid, DOT or
+//!   HTML), including traces imported from TLC and Apalache
+//! - `LinearizabilityChecker` for checking concurrent histories
+//! - `TemporalProperty` for liveness (always, eventually, leads-to) over
+//!   finite and lasso-shaped traces
+//! - `parse_module` for parsing TLA+ specs into an AST, and `Evaluator` for
+//!   evaluating their operators over concrete states
 //! - Invariant traits for each data structure (e.g., `StackProperties`)
+//! - `CheckerRegistry` for finding and running the checkers of a TLA+ spec
 //!
 //! ## TLA+ Traceability
 //!
 //! Every property is traceable to a specific line in the TLA+ spec.
 //! This ensures the Rust verification matches the formal model.
+//!
+//! Checkers are annotated with `#[tla_invariant(spec, name)]`, which
+//! looks up the operator's definition line when the crate is built, so
+//! the reported line cannot go stale and a renamed or removed invariant
+//! fails the build.
 
 pub mod counterexample;
 pub mod invariants;
+pub mod linearizability;
 pub mod property;
+pub mod registry;
+pub mod temporal;
 pub mod tla_spec;
+pub mod trace;
 
 pub use counterexample::{Counterexample, MemoryIssue, StateSnapshot, ThreadAction};
+pub use linearizability::{
+    ConcurrentHistory, LinearizabilityChecker, LinearizabilityViolation, SequentialSpec,
+};
 pub use property::{PropertyChecker, PropertyResult};
-pub use tla_spec::{ParseError, TlaInvariant, TlaSpec};
+pub use registry::{CheckerRegistry, RegistryError};
+pub use temporal::{Formula, TemporalProperty, Trace, TraceState};
+pub use tla_spec::{parse_module, Evaluator, ParseError, TlaInvariant, TlaSpec};
+pub use trace::{parse_itf_trace, parse_tlc_trace, TraceError};
+pub use vf_macros::tla_invariant;
@@ -0,0 +1,608 @@
+//! Linearizability checking for concurrent histories.
+//!
+//! Implements the Wing & Gong search with Lowe's memoization: a history
+//! of invoke/response intervals is linearizable if some total order of
+//! its operations respects real-time precedence and replays correctly
+//! against a sequential specification.
+//!
+//! # Usage
+//!
+//! ```
+//! use vf_core::linearizability::{
+//!     ConcurrentHistory, LinearizabilityChecker, StackOp, StackSpec,
+//! };
+//!
+//! let mut history = ConcurrentHistory::new();
+//! history.invoke(0, StackOp::Push(1), 1);
+//! history.invoke(1, StackOp::Pop, 2);
+//! history.respond(0, None, 3);
+//! history.respond(1, Some(1), 4);
+//!
+//! let checker = LinearizabilityChecker::new(StackSpec::default());
+//! assert!(checker.check(&history).is_ok());
+//! ```
+//!
+//! # Semantics
+//!
+//! - Operation A precedes B iff A returned strictly before B was invoked.
+//!   Operations whose intervals overlap (or touch) may be ordered either way.
+//! - Pending operations (invoked, never returned) may take effect with any
+//!   result, or not at all.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::counterexample::{Counterexample, StateSnapshot, ThreadAction};
+use crate::property::PropertyResult;
+
+/// Maximum number of operations in a single history.
+///
+/// The search is exponential in the number of overlapping operations;
+/// histories beyond this size should be split or shrunk first.
+pub const OPERATIONS_COUNT_MAX: usize = 100_000;
+
+/// A sequential specification that concurrent histories are checked against.
+///
+/// The spec state must be hashable so the search can memoize
+/// `(linearized set, state)` pairs it has already explored.
+pub trait SequentialSpec: Clone + Eq + Hash {
+    /// Operation (with arguments) invoked on the object.
+    type Op: Clone + Debug;
+    /// Value returned by an operation.
+    type Ret: Clone + Debug + PartialEq;
+
+    /// Apply an operation atomically, returning its result.
+    fn apply(&mut self, op: &Self::Op) -> Self::Ret;
+}
+
+/// A single operation interval in a concurrent history.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry<Op, Ret> {
+    /// Thread that performed the operation
+    pub thread_id: u64,
+    /// Operation invoked
+    pub op: Op,
+    /// Returned value (None while pending)
+    pub ret: Option<Ret>,
+    /// Step at which the operation was invoked
+    pub invoked_at: u64,
+    /// Step at which the operation returned (None while pending)
+    pub returned_at: Option<u64>,
+}
+
+impl<Op, Ret> HistoryEntry<Op, Ret> {
+    /// Whether the operation has returned.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.returned_at.is_some()
+    }
+}
+
+/// History of invoke and response events across threads.
+///
+/// Each thread has at most one pending operation at a time.
+#[derive(Debug, Clone)]
+pub struct ConcurrentHistory<Op, Ret> {
+    entries: Vec<HistoryEntry<Op, Ret>>,
+    /// Pending operation index per thread
+    pending: HashMap<u64, usize>,
+}
+
+impl<Op, Ret> Default for ConcurrentHistory<Op, Ret> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Op, Ret> ConcurrentHistory<Op, Ret> {
+    /// Create a new empty history.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record an invocation. Returns the index of the new entry.
+    pub fn invoke(&mut self, thread_id: u64, op: Op, step: u64) -> usize {
+        debug_assert!(
+            !self.pending.contains_key(&thread_id),
+            "Thread {} already has a pending operation",
+            thread_id
+        );
+        debug_assert!(
+            self.entries.len() < OPERATIONS_COUNT_MAX,
+            "History exceeds OPERATIONS_COUNT_MAX"
+        );
+
+        let index = self.entries.len();
+        self.entries.push(HistoryEntry {
+            thread_id,
+            op,
+            ret: None,
+            invoked_at: step,
+            returned_at: None,
+        });
+        self.pending.insert(thread_id, index);
+        index
+    }
+
+    /// Record the response to a thread's pending invocation.
+    pub fn respond(&mut self, thread_id: u64, ret: Ret, step: u64) {
+        let index = self.pending.remove(&thread_id);
+        debug_assert!(
+            index.is_some(),
+            "Thread {} has no pending operation",
+            thread_id
+        );
+        if let Some(index) = index {
+            let entry = &mut self.entries[index];
+            debug_assert!(step >= entry.invoked_at, "Response before invocation");
+            entry.ret = Some(ret);
+            entry.returned_at = Some(step);
+        }
+    }
+
+    /// Record a completed operation with an explicit interval.
+    pub fn record(&mut self, thread_id: u64, op: Op, ret: Ret, invoked_at: u64, returned_at: u64) {
+        debug_assert!(invoked_at <= returned_at, "Response before invocation");
+        debug_assert!(
+            self.entries.len() < OPERATIONS_COUNT_MAX,
+            "History exceeds OPERATIONS_COUNT_MAX"
+        );
+        self.entries.push(HistoryEntry {
+            thread_id,
+            op,
+            ret: Some(ret),
+            invoked_at,
+            returned_at: Some(returned_at),
+        });
+    }
+
+    /// All operation intervals, in recording order.
+    #[must_use]
+    pub fn entries(&self) -> &[HistoryEntry<Op, Ret>] {
+        &self.entries
+    }
+
+    /// Number of operations in the history.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the history is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<Op: Debug, Ret: Debug> ConcurrentHistory<Op, Ret> {
+    /// Build a counterexample showing the history and the point where
+    /// linearization got stuck.
+    #[must_use]
+    pub fn to_counterexample(
+        &self,
+        violation: &LinearizabilityViolation,
+        dst_seed: Option<u64>,
+    ) -> Counterexample {
+        let mut ce = match dst_seed {
+            Some(seed) => Counterexample::with_seed(seed),
+            None => Counterexample::new(),
+        };
+
+        let mut events: Vec<(u64, bool, usize)> = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            events.push((entry.invoked_at, false, i));
+            if let Some(returned_at) = entry.returned_at {
+                events.push((returned_at, true, i));
+            }
+        }
+        events.sort_unstable();
+
+        for (step, is_response, i) in events {
+            let entry = &self.entries[i];
+            let action = if is_response {
+                format!("{:?} -> {:?}", entry.op, entry.ret.as_ref().unwrap())
+            } else {
+                format!("{:?} start", entry.op)
+            };
+            ce.add_action(ThreadAction {
+                thread_id: entry.thread_id,
+                step,
+                action,
+                success: !violation.blocked.contains(&i),
+            });
+        }
+
+        let prefix: Vec<String> = violation
+            .longest_prefix
+            .iter()
+            .map(|&i| format!("{:?}", self.entries[i].op))
+            .collect();
+        let blocked: Vec<String> = violation
+            .blocked
+            .iter()
+            .map(|&i| {
+                let entry = &self.entries[i];
+                format!("T{} {:?} -> {:?}", entry.thread_id, entry.op, entry.ret)
+            })
+            .collect();
+        ce.add_state(StateSnapshot {
+            step: 1,
+            description: "Longest linearizable prefix".to_string(),
+            variables: vec![
+                ("prefix".to_string(), format!("[{}]", prefix.join(", "))),
+                ("blocked".to_string(), format!("[{}]", blocked.join(", "))),
+            ],
+        });
+
+        ce.with_description(violation.to_string())
+    }
+}
+
+/// Evidence that a history has no valid linearization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearizabilityViolation {
+    /// Longest sequence of operations (entry indices) that could be linearized
+    pub longest_prefix: Vec<usize>,
+    /// Operations that were eligible after that prefix but whose recorded
+    /// result disagrees with the sequential specification
+    pub blocked: Vec<usize>,
+    /// Number of distinct search states explored
+    pub states_explored: u64,
+}
+
+impl std::fmt::Display for LinearizabilityViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "History not linearizable: linearized {} operation(s), then none of {:?} \
+             can take effect ({} states explored)",
+            self.longest_prefix.len(),
+            self.blocked,
+            self.states_explored
+        )
+    }
+}
+
+/// Wing & Gong / Lowe linearizability checker.
+pub struct LinearizabilityChecker<S: SequentialSpec> {
+    initial: S,
+}
+
+impl<S: SequentialSpec> LinearizabilityChecker<S> {
+    /// Create a checker starting from the given specification state.
+    #[must_use]
+    pub fn new(initial: S) -> Self {
+        Self { initial }
+    }
+
+    /// Search for a linearization of the history.
+    ///
+    /// Returns the linearization order (entry indices; pending operations
+    /// that never took effect are omitted) or a violation.
+    pub fn check(
+        &self,
+        history: &ConcurrentHistory<S::Op, S::Ret>,
+    ) -> Result<Vec<usize>, LinearizabilityViolation> {
+        let entries = history.entries();
+        debug_assert!(entries.len() <= OPERATIONS_COUNT_MAX);
+
+        // Entries sorted by invocation, so candidate scans can stop early
+        let mut by_invoke: Vec<usize> = (0..entries.len()).collect();
+        by_invoke.sort_by_key(|&i| entries[i].invoked_at);
+
+        let completed_count = entries.iter().filter(|e| e.is_complete()).count();
+        let words_count = entries.len().div_ceil(64);
+
+        let mut visited: HashSet<(Vec<u64>, S)> = HashSet::new();
+        let mut stack: Vec<SearchNode<S>> = vec![SearchNode {
+            linearized: vec![0; words_count],
+            completed_count: 0,
+            state: self.initial.clone(),
+            order: Vec::new(),
+        }];
+        visited.insert((vec![0; words_count], self.initial.clone()));
+
+        let mut best_prefix: Vec<usize> = Vec::new();
+        let mut best_blocked: Vec<usize> = Vec::new();
+        let mut states_explored: u64 = 0;
+
+        while let Some(node) = stack.pop() {
+            states_explored += 1;
+
+            if node.completed_count == completed_count {
+                return Ok(node.order);
+            }
+
+            // Earliest response among completed, unlinearized operations:
+            // anything invoked after it cannot go next.
+            let frontier = entries
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !is_set(&node.linearized, *i))
+                .filter_map(|(_, e)| e.returned_at)
+                .min()
+                .unwrap_or(u64::MAX);
+
+            let mut blocked = Vec::new();
+            for &i in &by_invoke {
+                let entry = &entries[i];
+                if entry.invoked_at > frontier {
+                    break;
+                }
+                if is_set(&node.linearized, i) {
+                    continue;
+                }
+
+                let mut state = node.state.clone();
+                let ret = state.apply(&entry.op);
+                if let Some(ref expected) = entry.ret {
+                    if *expected != ret {
+                        blocked.push(i);
+                        continue;
+                    }
+                }
+
+                let mut linearized = node.linearized.clone();
+                set(&mut linearized, i);
+                if !visited.insert((linearized.clone(), state.clone())) {
+                    continue;
+                }
+
+                let mut order = node.order.clone();
+                order.push(i);
+                stack.push(SearchNode {
+                    linearized,
+                    completed_count: node.completed_count + usize::from(entry.is_complete()),
+                    state,
+                    order,
+                });
+            }
+
+            if node.order.len() >= best_prefix.len() && !blocked.is_empty() {
+                best_prefix = node.order;
+                best_blocked = blocked;
+            }
+        }
+
+        Err(LinearizabilityViolation {
+            longest_prefix: best_prefix,
+            blocked: best_blocked,
+            states_explored,
+        })
+    }
+
+    /// Check the history and report the result as a `PropertyResult`.
+    pub fn check_property(
+        &self,
+        history: &ConcurrentHistory<S::Op, S::Ret>,
+        tla_spec: &'static str,
+        tla_line: u32,
+        dst_seed: Option<u64>,
+    ) -> PropertyResult {
+        match self.check(history) {
+            Ok(_) => PropertyResult::pass("Linearizability", tla_spec, tla_line),
+            Err(violation) => PropertyResult::fail(
+                "Linearizability",
+                tla_spec,
+                tla_line,
+                violation.to_string(),
+                Some(history.to_counterexample(&violation, dst_seed)),
+            ),
+        }
+    }
+}
+
+/// A node in the depth-first linearization search.
+struct SearchNode<S> {
+    linearized: Vec<u64>,
+    completed_count: usize,
+    state: S,
+    order: Vec<usize>,
+}
+
+fn is_set(bits: &[u64], i: usize) -> bool {
+    bits[i / 64] & (1 << (i % 64)) != 0
+}
+
+fn set(bits: &mut [u64], i: usize) {
+    bits[i / 64] |= 1 << (i % 64);
+}
+
+/// Stack operation for `StackSpec`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackOp {
+    Push(u64),
+    Pop,
+}
+
+/// Sequential LIFO stack specification.
+///
+/// Push returns `None`; pop returns the removed element, or `None` if empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct StackSpec {
+    contents: Vec<u64>,
+}
+
+impl SequentialSpec for StackSpec {
+    type Op = StackOp;
+    type Ret = Option<u64>;
+
+    fn apply(&mut self, op: &StackOp) -> Option<u64> {
+        match op {
+            StackOp::Push(e) => {
+                self.contents.push(*e);
+                None
+            }
+            StackOp::Pop => self.contents.pop(),
+        }
+    }
+}
+
+/// Queue operation for `QueueSpec`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueOp {
+    Enqueue(u64),
+    Dequeue,
+}
+
+/// Sequential FIFO queue specification.
+///
+/// Enqueue returns `None`; dequeue returns the removed element, or `None` if empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct QueueSpec {
+    contents: VecDeque<u64>,
+}
+
+impl SequentialSpec for QueueSpec {
+    type Op = QueueOp;
+    type Ret = Option<u64>;
+
+    fn apply(&mut self, op: &QueueOp) -> Option<u64> {
+        match op {
+            QueueOp::Enqueue(e) => {
+                self.contents.push_back(*e);
+                None
+            }
+            QueueOp::Dequeue => self.contents.pop_front(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_history_linearizable() {
+        let mut history = ConcurrentHistory::new();
+        history.record(0, StackOp::Push(1), None, 1, 2);
+        history.record(0, StackOp::Push(2), None, 3, 4);
+        history.record(0, StackOp::Pop, Some(2), 5, 6);
+
+        let checker = LinearizabilityChecker::new(StackSpec::default());
+        assert_eq!(checker.check(&history).unwrap(), vec![0, 1, 2]);
+    }
+
+    /// Assert `order` linearizes `history`: every completed operation
+    /// appears once, real-time order is kept, and the spec's returns match.
+    fn assert_valid_linearization<S: SequentialSpec>(
+        initial: S,
+        history: &ConcurrentHistory<S::Op, S::Ret>,
+        order: &[usize],
+    ) where
+        S::Ret: PartialEq + std::fmt::Debug,
+    {
+        let entries = history.entries();
+        let mut seen = vec![false; entries.len()];
+        for &i in order {
+            assert!(!seen[i], "entry {i} linearized twice in {order:?}");
+            seen[i] = true;
+        }
+        for (i, entry) in entries.iter().enumerate() {
+            assert!(
+                seen[i] || !entry.is_complete(),
+                "entry {i} missing from {order:?}"
+            );
+        }
+        for (position, &later) in order.iter().enumerate() {
+            for &earlier in &order[position + 1..] {
+                let precedes = entries[earlier]
+                    .returned_at
+                    .is_some_and(|returned| returned < entries[later].invoked_at);
+                assert!(!precedes, "{order:?} puts {later} before {earlier}");
+            }
+        }
+        let mut state = initial;
+        for &i in order {
+            let ret = state.apply(&entries[i].op);
+            if let Some(expected) = &entries[i].ret {
+                assert_eq!(&ret, expected, "entry {i} in {order:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_overlapping_pushes_either_order() {
+        // push(1) and push(2) overlap, so pop may return either.
+        for popped in [1, 2] {
+            let mut history = ConcurrentHistory::new();
+            history.invoke(0, StackOp::Push(1), 1);
+            history.invoke(1, StackOp::Push(2), 2);
+            history.respond(1, None, 3);
+            history.respond(0, None, 4);
+            history.record(0, StackOp::Pop, Some(popped), 5, 6);
+
+            let checker = LinearizabilityChecker::new(StackSpec::default());
+            let order = checker.check(&history).unwrap();
+            assert_valid_linearization(StackSpec::default(), &history, &order);
+        }
+    }
+
+    #[test]
+    fn test_real_time_order_violation() {
+        // push(1) < push(2) < pop, so pop must return 2.
+        let mut history = ConcurrentHistory::new();
+        history.record(0, StackOp::Push(1), None, 1, 2);
+        history.record(1, StackOp::Push(2), None, 3, 4);
+        history.record(0, StackOp::Pop, Some(1), 5, 6);
+
+        let checker = LinearizabilityChecker::new(StackSpec::default());
+        let violation = checker.check(&history).unwrap_err();
+        assert_eq!(violation.longest_prefix, vec![0, 1]);
+        assert_eq!(violation.blocked, vec![2]);
+    }
+
+    #[test]
+    fn test_pending_operation_may_take_effect() {
+        // A pending push can explain a pop that returns its element.
+        let mut history = ConcurrentHistory::new();
+        history.invoke(0, StackOp::Push(7), 1);
+        history.record(1, StackOp::Pop, Some(7), 2, 3);
+
+        let checker = LinearizabilityChecker::new(StackSpec::default());
+        assert!(checker.check(&history).is_ok());
+    }
+
+    #[test]
+    fn test_pending_operation_may_be_dropped() {
+        let mut history = ConcurrentHistory::new();
+        history.invoke(0, StackOp::Push(7), 1);
+        history.record(1, StackOp::Pop, None, 2, 3);
+
+        let checker = LinearizabilityChecker::new(StackSpec::default());
+        assert!(checker.check(&history).is_ok());
+    }
+
+    #[test]
+    fn test_queue_fifo_violation() {
+        let mut history = ConcurrentHistory::new();
+        history.record(0, QueueOp::Enqueue(1), None, 1, 2);
+        history.record(0, QueueOp::Enqueue(2), None, 3, 4);
+        history.record(1, QueueOp::Dequeue, Some(2), 5, 6);
+
+        let checker = LinearizabilityChecker::new(QueueSpec::default());
+        assert!(checker.check(&history).is_err());
+    }
+
+    #[test]
+    fn test_check_property_counterexample() {
+        let mut history = ConcurrentHistory::new();
+        history.record(0, StackOp::Push(1), None, 1, 2);
+        history.record(1, StackOp::Pop, Some(2), 3, 4);
+
+        let checker = LinearizabilityChecker::new(StackSpec::default());
+        let result = checker.check_property(&history, "treiber_stack.tla", 89, Some(42));
+        assert!(!result.holds);
+
+        let ce = result.counterexample.unwrap();
+        assert_eq!(ce.dst_seed, Some(42));
+        assert_eq!(ce.interleaving.len(), 4);
+        assert!(ce.render_diagram().contains("Pop -> Some(2)"));
+    }
+}
@@ -111,6 +111,7 @@ pub trait PropertyChecker {
     ///
     /// This is useful for fail-fast testing where you want to
     /// stop on the first violation.
+    #[allow(clippy::result_large_err)]
     fn verify_all(&self) -> Result<(), PropertyResult> {
         for result in self.check_all() {
             if !result.holds {
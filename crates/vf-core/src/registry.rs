@@ -0,0 +1,894 @@
+//! Registry of property checkers keyed by TLA+ spec module.
+//!
+//! Each invariants module exposes an `XxxPropertyChecker<'a, T>` with a
+//! concrete trait bound, so nothing generic can ask "check everything
+//! declared in ring_buffer.tla against this object". The registry records
+//! which checker covers which invariants of each spec module, runs the
+//! checkers bound to a target type for a parsed [`TlaSpec`], and reports
+//! the invariants a spec declares that no Rust checker covers.
+//!
+//! # Usage
+//!
+//! ```
+//! use vf_core::invariants::{RingBufferProperties, RingBufferPropertyChecker};
+//! use vf_core::registry::CheckerRegistry;
+//! use vf_core::{PropertyChecker, TlaSpec};
+//!
+//! struct Ring(Vec<u64>);
+//!
+//! impl RingBufferProperties for Ring {
+//!     fn produced_messages(&self) -> Vec<u64> {
+//!         self.0.clone()
+//!     }
+//!     fn consumed_messages(&self) -> Vec<u64> {
+//!         Vec::new()
+//!     }
+//!     fn current_contents(&self) -> Vec<u64> {
+//!         self.0.clone()
+//!     }
+//!     fn capacity(&self) -> u64 {
+//!         4
+//!     }
+//! }
+//!
+//! let registry = CheckerRegistry::builtin().with_binding::<Ring, _>(
+//!     "ring_buffer",
+//!     |ring, _seed| RingBufferPropertyChecker::new(ring).check_all(),
+//! );
+//!
+//! let spec = TlaSpec::parse(
+//!     "---- MODULE ring_buffer ----\n\
+//!      * Line 47: NoLostMessages -> stateright, dst\n====",
+//! )
+//! .unwrap();
+//! let results = registry
+//!     .check_evaluator(&spec, "dst", &Ring(vec![1, 2]), None)
+//!     .unwrap();
+//! assert_eq!(results.len(), 1);
+//! assert!(results[0].holds);
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::property::PropertyResult;
+use crate::tla_spec::{TlaInvariant, TlaSpec};
+
+/// A Rust checker and the spec invariants it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckerInfo {
+    /// TLA+ module name (e.g., "treiber_stack")
+    pub module: &'static str,
+    /// Checker type (e.g., "StackPropertyChecker")
+    pub checker: &'static str,
+    /// Invariant names the checker reports, as named in the spec
+    pub invariants: &'static [&'static str],
+}
+
+/// Checkers provided by [`crate::invariants`].
+pub const BUILTIN_CHECKERS: &[CheckerInfo] = &[
+    CheckerInfo {
+        module: "btree_plus",
+        checker: "BTreePlusPropertyChecker",
+        invariants: &["SortedOrder", "BalancedHeight", "NoLostKeys"],
+    },
+    CheckerInfo {
+        module: "cross_shard_ssi",
+        checker: "CrossShardSsiPropertyChecker",
+        invariants: &["Serializable", "CrossShardAtomicity", "FirstCommitterWins"],
+    },
+    CheckerInfo {
+        module: "epoch_gc",
+        checker: "EpochGcPropertyChecker",
+        invariants: &["NoUseAfterFree", "BoundedMemory", "MonotonicEpoch"],
+    },
+    CheckerInfo {
+        module: "hazard_pointers",
+        checker: "HazardPointerPropertyChecker",
+        invariants: &[
+            "NoReclaimWhileProtected",
+            "ProtectedIsPublished",
+            "RetiredUnreachable",
+            "BoundedGarbage",
+        ],
+    },
+    CheckerInfo {
+        module: "io_buffer",
+        checker: "IoBufferPropertyChecker",
+        invariants: &["NoDataCorruption", "OrderPreserved", "BoundedMemory"],
+    },
+    CheckerInfo {
+        module: "linked_list",
+        checker: "LinkedListPropertyChecker",
+        invariants: &["NoLostElements", "NoDuplicates", "InsertOrderPreserved"],
+    },
+    CheckerInfo {
+        module: "ms_queue",
+        checker: "QueuePropertyChecker",
+        invariants: &[
+            "NoLostElements",
+            "NoDuplicates",
+            "FIFO_Order",
+            "Linearizability",
+        ],
+    },
+    CheckerInfo {
+        module: "pagecache",
+        checker: "PageCachePropertyChecker",
+        invariants: &["AtomicPageUpdate", "NoLostPages", "CrashConsistency"],
+    },
+    CheckerInfo {
+        module: "radix_tree",
+        checker: "RadixTreePropertyChecker",
+        invariants: &["PrefixConsistency", "NoLostKeys"],
+    },
+    CheckerInfo {
+        module: "raft_consensus",
+        checker: "RaftPropertyChecker",
+        invariants: &[
+            "ElectionSafety",
+            "LogMatching",
+            "LeaderCompleteness",
+            "StateMachineSafety",
+        ],
+    },
+    CheckerInfo {
+        module: "ring_buffer",
+        checker: "RingBufferPropertyChecker",
+        invariants: &["NoLostMessages", "FIFO_Order", "BoundedCapacity"],
+    },
+    CheckerInfo {
+        module: "snapshot_isolation",
+        checker: "SnapshotIsolationPropertyChecker",
+        invariants: &["FirstCommitterWins", "SnapshotRead", "CommitAfterSnapshot"],
+    },
+    CheckerInfo {
+        module: "treiber_stack",
+        checker: "StackPropertyChecker",
+        invariants: &[
+            "NoLostElements",
+            "NoDuplicates",
+            "LIFO_Order",
+            "Linearizability",
+            "ABA_Safety",
+        ],
+    },
+    CheckerInfo {
+        module: "two_phase_commit",
+        checker: "TwoPhaseCommitPropertyChecker",
+        invariants: &[
+            "Atomicity",
+            "Validity",
+            "Consistency",
+            "Agreement",
+            "TMDecision",
+        ],
+    },
+    CheckerInfo {
+        module: "two_phase_locking",
+        checker: "TwoPhaseLockingPropertyChecker",
+        invariants: &[
+            "LockCompatibility",
+            "StrictTwoPhase",
+            "AccessesLocked",
+            "NoDeadlock",
+        ],
+    },
+];
+
+/// Type-erased checker run: target and optional DST seed to results.
+type CheckFn = Box<dyn Fn(&dyn Any, Option<u64>) -> Vec<PropertyResult> + Send + Sync>;
+
+/// A checker bound to a concrete target type for one spec module.
+struct Binding {
+    module: String,
+    target: TypeId,
+    check: CheckFn,
+}
+
+/// Error looking up checkers for a spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    /// No checker is bound to this module for the target type
+    NoBinding {
+        module: String,
+        target: &'static str,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::NoBinding { module, target } => {
+                write!(f, "no checker bound to {} for {}", module, target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// A spec invariant and the checker that covers it.
+#[derive(Debug, Clone)]
+pub struct CoveredInvariant {
+    /// The invariant as declared in the spec
+    pub invariant: TlaInvariant,
+    /// Checker that reports it
+    pub checker: &'static str,
+}
+
+/// Which invariants of a spec have a Rust checker.
+#[derive(Debug, Clone)]
+pub struct SpecCoverage {
+    /// TLA+ module name
+    pub module: String,
+    /// Mapped invariants some declared checker reports
+    pub covered: Vec<CoveredInvariant>,
+    /// Mapped invariants with no Rust checker
+    pub unchecked: Vec<TlaInvariant>,
+}
+
+impl SpecCoverage {
+    /// Whether every declared invariant has a checker.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.unchecked.is_empty()
+    }
+
+    /// Format as a report string.
+    #[must_use]
+    pub fn format_report(&self) -> String {
+        let total = self.covered.len() + self.unchecked.len();
+        let mut report = format!(
+            "{}: {}/{} invariants checked\n",
+            self.module,
+            self.covered.len(),
+            total
+        );
+        for c in &self.covered {
+            report.push_str(&format!(
+                "  [x] {} (line {}) -> {}\n",
+                c.invariant.name, c.invariant.line, c.checker
+            ));
+        }
+        for inv in &self.unchecked {
+            report.push_str(&format!(
+                "  [ ] {} (line {}) -> {}\n",
+                inv.name,
+                inv.line,
+                inv.evaluators.join(", ")
+            ));
+        }
+        report
+    }
+}
+
+/// Maps TLA+ spec modules to the Rust checkers that verify them.
+#[derive(Default)]
+pub struct CheckerRegistry {
+    checkers: Vec<CheckerInfo>,
+    bindings: Vec<Binding>,
+}
+
+impl CheckerRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry declaring [`BUILTIN_CHECKERS`].
+    #[must_use]
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        for info in BUILTIN_CHECKERS {
+            registry.declare(*info);
+        }
+        registry
+    }
+
+    /// Declare a checker and the spec invariants it covers.
+    pub fn declare(&mut self, info: CheckerInfo) {
+        debug_assert!(!info.module.is_empty(), "Module name must not be empty");
+        debug_assert!(
+            !info.invariants.is_empty(),
+            "Checker must cover an invariant"
+        );
+        self.checkers.push(info);
+    }
+
+    /// Bind a check for targets of type `T` to a spec module.
+    ///
+    /// `check` receives the target and the DST seed to record in
+    /// counterexamples; several bindings for the same module and type
+    /// all run.
+    pub fn bind<T, F>(&mut self, module: &str, check: F)
+    where
+        T: Any,
+        F: Fn(&T, Option<u64>) -> Vec<PropertyResult> + Send + Sync + 'static,
+    {
+        debug_assert!(!module.is_empty(), "Module name must not be empty");
+        self.bindings.push(Binding {
+            module: module.to_string(),
+            target: TypeId::of::<T>(),
+            check: Box::new(move |target, seed| {
+                let target = target
+                    .downcast_ref::<T>()
+                    .expect("binding looked up by TypeId");
+                check(target, seed)
+            }),
+        });
+    }
+
+    /// Builder form of [`bind`](Self::bind).
+    #[must_use]
+    pub fn with_binding<T, F>(mut self, module: &str, check: F) -> Self
+    where
+        T: Any,
+        F: Fn(&T, Option<u64>) -> Vec<PropertyResult> + Send + Sync + 'static,
+    {
+        self.bind(module, check);
+        self
+    }
+
+    /// Checkers declared for a spec module.
+    pub fn checkers_for<'r>(&'r self, module: &'r str) -> impl Iterator<Item = &'r CheckerInfo> {
+        self.checkers.iter().filter(move |c| c.module == module)
+    }
+
+    /// Checker declared to cover an invariant of a spec module.
+    #[must_use]
+    pub fn checker_for(&self, module: &str, invariant: &str) -> Option<&CheckerInfo> {
+        self.checkers
+            .iter()
+            .find(|c| c.module == module && c.invariants.contains(&invariant))
+    }
+
+    /// Run every checker bound to `spec`'s module for the target.
+    ///
+    /// Results for invariants the spec does not declare are dropped, so
+    /// only properties traceable to the spec are reported.
+    pub fn check<T: Any>(
+        &self,
+        spec: &TlaSpec,
+        target: &T,
+        dst_seed: Option<u64>,
+    ) -> Result<Vec<PropertyResult>, RegistryError> {
+        self.run(spec, target, dst_seed, |_| true)
+    }
+
+    /// Run the checkers for the invariants `spec` assigns to `evaluator`.
+    ///
+    /// Uses the spec's EVALUATOR MAPPING, e.g. `"dst"` or `"stateright"`.
+    pub fn check_evaluator<T: Any>(
+        &self,
+        spec: &TlaSpec,
+        evaluator: &str,
+        target: &T,
+        dst_seed: Option<u64>,
+    ) -> Result<Vec<PropertyResult>, RegistryError> {
+        self.run(spec, target, dst_seed, |inv| {
+            inv.evaluators.iter().any(|e| e == evaluator)
+        })
+    }
+
+    fn run<T: Any>(
+        &self,
+        spec: &TlaSpec,
+        target: &T,
+        dst_seed: Option<u64>,
+        wanted: impl Fn(&TlaInvariant) -> bool,
+    ) -> Result<Vec<PropertyResult>, RegistryError> {
+        let target_id = TypeId::of::<T>();
+        let bindings: Vec<&Binding> = self
+            .bindings
+            .iter()
+            .filter(|b| b.module == spec.name && b.target == target_id)
+            .collect();
+        if bindings.is_empty() {
+            return Err(RegistryError::NoBinding {
+                module: spec.name.clone(),
+                target: std::any::type_name::<T>(),
+            });
+        }
+
+        let declared: HashMap<&str, &TlaInvariant> = spec
+            .invariants
+            .iter()
+            .map(|inv| (inv.name.as_str(), inv))
+            .collect();
+        let mut results = Vec::new();
+        for binding in bindings {
+            for result in (binding.check)(target, dst_seed) {
+                if declared.get(result.name).is_some_and(|inv| wanted(inv)) {
+                    results.push(result);
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Which invariants in `spec`'s EVALUATOR MAPPING have a checker.
+    ///
+    /// Definitions outside the mapping (Init, Next, TypeOK, ...) are not
+    /// claimed to be checked by anything and are left out.
+    #[must_use]
+    pub fn coverage(&self, spec: &TlaSpec) -> SpecCoverage {
+        let mut covered = Vec::new();
+        let mut unchecked = Vec::new();
+        for inv in spec.invariants.iter().filter(|i| !i.evaluators.is_empty()) {
+            match self.checker_for(&spec.name, &inv.name) {
+                Some(info) => covered.push(CoveredInvariant {
+                    invariant: inv.clone(),
+                    checker: info.checker,
+                }),
+                None => unchecked.push(inv.clone()),
+            }
+        }
+        SpecCoverage {
+            module: spec.name.clone(),
+            covered,
+            unchecked,
+        }
+    }
+}
+
+impl fmt::Debug for CheckerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckerRegistry")
+            .field("checkers", &self.checkers)
+            .field("bindings_count", &self.bindings.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invariants::cross_shard_ssi::ShardId;
+    use crate::invariants::ssi::TxnStatus;
+    use crate::invariants::*;
+    use crate::property::PropertyChecker;
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    /// Test implementation of RingBufferProperties
+    struct TestRing {
+        produced: Vec<u64>,
+        consumed: Vec<u64>,
+        capacity: u64,
+    }
+
+    impl RingBufferProperties for TestRing {
+        fn produced_messages(&self) -> Vec<u64> {
+            self.produced.clone()
+        }
+
+        fn consumed_messages(&self) -> Vec<u64> {
+            self.consumed.clone()
+        }
+
+        fn current_contents(&self) -> Vec<u64> {
+            self.produced[self.consumed.len()..].to_vec()
+        }
+
+        fn capacity(&self) -> u64 {
+            self.capacity
+        }
+    }
+
+    fn spec(file: &str) -> TlaSpec {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../specs")
+            .join(file);
+        TlaSpec::from_file(&path).unwrap()
+    }
+
+    fn ring_registry() -> CheckerRegistry {
+        CheckerRegistry::builtin().with_binding::<TestRing, _>("ring_buffer", |ring, seed| {
+            let checker = RingBufferPropertyChecker::new(ring);
+            match seed {
+                Some(seed) => checker.with_seed(seed).check_all(),
+                None => checker.check_all(),
+            }
+        })
+    }
+
+    #[test]
+    fn test_check_runs_bound_checker() {
+        let ring = TestRing {
+            produced: vec![1, 2, 3],
+            consumed: vec![1],
+            capacity: 4,
+        };
+        let results = ring_registry()
+            .check(&spec("lockfree/ring_buffer.tla"), &ring, None)
+            .unwrap();
+
+        let names: Vec<&str> = results.iter().map(|r| r.name).collect();
+        assert_eq!(
+            names,
+            vec!["NoLostMessages", "FIFO_Order", "BoundedCapacity"]
+        );
+        assert!(results.iter().all(|r| r.holds));
+    }
+
+    #[test]
+    fn test_check_evaluator_filters_by_mapping() {
+        let ring = TestRing {
+            produced: vec![1, 2, 3, 4, 5],
+            consumed: vec![],
+            capacity: 2,
+        };
+        let registry = ring_registry();
+        let spec = spec("lockfree/ring_buffer.tla");
+
+        // Only NoLostMessages maps to dst
+        let dst = registry
+            .check_evaluator(&spec, "dst", &ring, Some(7))
+            .unwrap();
+        assert_eq!(dst.len(), 1);
+        assert_eq!(dst[0].name, "NoLostMessages");
+
+        // BoundedCapacity maps to kani
+        let kani = registry
+            .check_evaluator(&spec, "kani", &ring, Some(7))
+            .unwrap();
+        assert_eq!(kani.len(), 1);
+        assert_eq!(kani[0].name, "BoundedCapacity");
+        assert!(!kani[0].holds);
+    }
+
+    #[test]
+    fn test_unbound_target_is_an_error() {
+        let ring = TestRing {
+            produced: vec![],
+            consumed: vec![],
+            capacity: 1,
+        };
+        let err = ring_registry()
+            .check(&spec("lockfree/treiber_stack.tla"), &ring, None)
+            .unwrap_err();
+        assert!(
+            matches!(err, RegistryError::NoBinding { ref module, .. } if module == "treiber_stack")
+        );
+
+        let err = ring_registry()
+            .check(&spec("lockfree/ring_buffer.tla"), &0u64, None)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no checker bound to ring_buffer for u64");
+    }
+
+    #[test]
+    fn test_coverage_reports_unchecked_invariants() {
+        let coverage = CheckerRegistry::builtin().coverage(&spec("lockfree/ring_buffer.tla"));
+
+        assert_eq!(coverage.covered.len(), 3);
+        assert!(!coverage.is_complete());
+        let unchecked: Vec<&str> = coverage.unchecked.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(unchecked, vec!["ProducerConsumerProgress"]);
+        assert!(coverage
+            .format_report()
+            .contains("[ ] ProducerConsumerProgress (line 72)"));
+    }
+
+    #[test]
+    fn test_builtin_invariants_declared_in_specs() {
+        let specs = [
+            "lockfree/btree_plus.tla",
+            "ssi/cross_shard_ssi.tla",
+            "lockfree/epoch_gc.tla",
+            "lockfree/hazard_pointers.tla",
+            "lockfree/io_buffer.tla",
+            "lockfree/linked_list.tla",
+            "lockfree/ms_queue.tla",
+            "lockfree/pagecache.tla",
+            "lockfree/radix_tree.tla",
+            "distributed/raft_consensus.tla",
+            "lockfree/ring_buffer.tla",
+            "ssi/snapshot_isolation.tla",
+            "lockfree/treiber_stack.tla",
+            "distributed/two_phase_commit.tla",
+            "ssi/two_phase_locking.tla",
+        ];
+        assert_eq!(specs.len(), BUILTIN_CHECKERS.len());
+
+        for (file, info) in specs.iter().zip(BUILTIN_CHECKERS) {
+            let spec = spec(file);
+            assert_eq!(spec.name, info.module);
+            let mapped: HashSet<&str> = spec.evaluator_map.keys().map(String::as_str).collect();
+            for inv in info.invariants {
+                assert!(mapped.contains(inv), "{} not mapped in {}", inv, file);
+            }
+        }
+    }
+
+    /// Target with no state, for every built-in checker.
+    struct Empty;
+
+    impl BTreePlusProperties for Empty {
+        fn logical_keys(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn leaf_scan_keys(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn is_balanced(&self) -> bool {
+            true
+        }
+        fn height(&self) -> u64 {
+            0
+        }
+    }
+
+    impl CrossShardSsiProperties for Empty {
+        fn txn_statuses(&self) -> HashMap<u64, CrossShardTxnStatus> {
+            HashMap::new()
+        }
+        fn txn_shards(&self) -> HashMap<u64, HashSet<ShardId>> {
+            HashMap::new()
+        }
+        fn txn_reads(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn txn_writes(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn in_conflicts(&self) -> HashMap<u64, bool> {
+            HashMap::new()
+        }
+        fn out_conflicts(&self) -> HashMap<u64, bool> {
+            HashMap::new()
+        }
+        fn commit_timestamps(&self) -> HashMap<u64, u64> {
+            HashMap::new()
+        }
+    }
+
+    impl EpochGcProperties for Empty {
+        fn global_epoch(&self) -> u64 {
+            0
+        }
+        fn thread_epochs(&self) -> HashMap<u64, u64> {
+            HashMap::new()
+        }
+        fn thread_references(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn freed_objects(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn retired_objects(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+    }
+
+    impl HazardPointerProperties for Empty {
+        fn hazards(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn protected(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn reachable_objects(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn retired_objects(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn freed_objects(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn hazards_per_thread(&self) -> u64 {
+            1
+        }
+        fn scan_threshold(&self) -> u64 {
+            1
+        }
+    }
+
+    impl IoBufferProperties for Empty {
+        fn submitted_items(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn flushed_items(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn buffered_items(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn buffer_size_max(&self) -> u64 {
+            1
+        }
+    }
+
+    impl LinkedListProperties for Empty {
+        fn inserted_keys(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn removed_keys(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn reachable_keys(&self) -> Vec<u64> {
+            Vec::new()
+        }
+    }
+
+    impl QueueProperties for Empty {
+        fn enqueued_elements(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn dequeued_elements(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn current_contents(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn history(&self) -> QueueHistory {
+            QueueHistory::new()
+        }
+    }
+
+    impl PageCacheProperties for Empty {
+        fn cached_pages(&self) -> HashMap<u64, PageState> {
+            HashMap::new()
+        }
+        fn disk_pages(&self) -> HashMap<u64, u64> {
+            HashMap::new()
+        }
+        fn written_page_ids(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn flush_pending(&self) -> Vec<u64> {
+            Vec::new()
+        }
+    }
+
+    impl RadixTreeProperties for Empty {
+        fn logical_map(&self) -> HashMap<Vec<u8>, u64> {
+            HashMap::new()
+        }
+        fn lookup(&self, _key: &[u8]) -> Option<u64> {
+            None
+        }
+        fn all_retrievable_keys(&self) -> Vec<Vec<u8>> {
+            Vec::new()
+        }
+    }
+
+    impl RaftProperties for Empty {
+        fn current_terms(&self) -> HashMap<ServerId, u64> {
+            HashMap::new()
+        }
+        fn roles(&self) -> HashMap<ServerId, RaftRole> {
+            HashMap::new()
+        }
+        fn logs(&self) -> HashMap<ServerId, Vec<LogEntry>> {
+            HashMap::new()
+        }
+        fn commit_indices(&self) -> HashMap<ServerId, u64> {
+            HashMap::new()
+        }
+        fn elections(&self) -> Vec<(u64, ServerId)> {
+            Vec::new()
+        }
+    }
+
+    impl RingBufferProperties for Empty {
+        fn produced_messages(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn consumed_messages(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn current_contents(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn capacity(&self) -> u64 {
+            1
+        }
+    }
+
+    impl SnapshotIsolationProperties for Empty {
+        fn txn_statuses(&self) -> HashMap<u64, TxnStatus> {
+            HashMap::new()
+        }
+        fn snapshots(&self) -> HashMap<u64, u64> {
+            HashMap::new()
+        }
+        fn commit_timestamps(&self) -> HashMap<u64, u64> {
+            HashMap::new()
+        }
+        fn write_sets(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn read_records(&self) -> HashMap<u64, Vec<SnapshotReadRecord>> {
+            HashMap::new()
+        }
+    }
+
+    impl StackProperties for Empty {
+        fn pushed_elements(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn popped_elements(&self) -> HashSet<u64> {
+            HashSet::new()
+        }
+        fn current_contents(&self) -> Vec<u64> {
+            Vec::new()
+        }
+        fn history(&self) -> StackHistory {
+            StackHistory::new()
+        }
+    }
+
+    impl TwoPhaseCommitProperties for Empty {
+        fn transactions(&self) -> Vec<TxnId> {
+            Vec::new()
+        }
+        fn tm_state(&self, _txn: TxnId) -> TmState {
+            TmState::Init
+        }
+        fn rm_states(&self, _txn: TxnId) -> HashMap<RmId, RmState> {
+            HashMap::new()
+        }
+        fn tm_prepared(&self, _txn: TxnId) -> HashSet<RmId> {
+            HashSet::new()
+        }
+    }
+
+    impl TwoPhaseLockingProperties for Empty {
+        fn txn_statuses(&self) -> HashMap<u64, TxnStatus> {
+            HashMap::new()
+        }
+        fn shared_locks(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn exclusive_locks(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn read_sets(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn write_sets(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+        fn waits_for(&self) -> HashMap<u64, HashSet<u64>> {
+            HashMap::new()
+        }
+    }
+
+    #[test]
+    fn test_builtin_checkers_match_check_all() {
+        let names = |results: Vec<PropertyResult>| -> Vec<&'static str> {
+            results.iter().map(|r| r.name).collect()
+        };
+        let reported = [
+            ("BTreePlusPropertyChecker", names(BTreePlusPropertyChecker::new(&Empty).check_all())),
+            ("CrossShardSsiPropertyChecker", names(CrossShardSsiPropertyChecker::new(&Empty).check_all())),
+            ("EpochGcPropertyChecker", names(EpochGcPropertyChecker::new(&Empty).check_all())),
+            ("HazardPointerPropertyChecker", names(HazardPointerPropertyChecker::new(&Empty).check_all())),
+            ("IoBufferPropertyChecker", names(IoBufferPropertyChecker::new(&Empty).check_all())),
+            ("LinkedListPropertyChecker", names(LinkedListPropertyChecker::new(&Empty).check_all())),
+            ("QueuePropertyChecker", names(QueuePropertyChecker::new(&Empty).check_all())),
+            ("PageCachePropertyChecker", names(PageCachePropertyChecker::new(&Empty).check_all())),
+            ("RadixTreePropertyChecker", names(RadixTreePropertyChecker::new(&Empty).check_all())),
+            ("RaftPropertyChecker", names(RaftPropertyChecker::new(&Empty).check_all())),
+            ("RingBufferPropertyChecker", names(RingBufferPropertyChecker::new(&Empty).check_all())),
+            ("SnapshotIsolationPropertyChecker", names(SnapshotIsolationPropertyChecker::new(&Empty).check_all())),
+            ("StackPropertyChecker", names(StackPropertyChecker::new(&Empty).check_all())),
+            ("TwoPhaseCommitPropertyChecker", names(TwoPhaseCommitPropertyChecker::new(&Empty).check_all())),
+            ("TwoPhaseLockingPropertyChecker", names(TwoPhaseLockingPropertyChecker::new(&Empty).check_all())),
+        ];
+        assert_eq!(reported.len(), BUILTIN_CHECKERS.len());
+
+        for info in BUILTIN_CHECKERS {
+            let (_, names) = reported
+                .iter()
+                .find(|(checker, _)| *checker == info.checker)
+                .unwrap_or_else(|| panic!("{} not run", info.checker));
+            assert_eq!(names, info.invariants, "{} drifted from its entry", info.checker);
+        }
+    }
+}
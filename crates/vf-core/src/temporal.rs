@@ -0,0 +1,658 @@
+//! Temporal properties over recorded traces.
+//!
+//! `PropertyChecker` verifies safety invariants one state at a time. The
+//! specs in `specs/` also assume fairness (`Spec == Init /\ [][Next]_vars
+//! /\ Fairness`) and promise liveness: lock-freedom, no deadlock, eventual
+//! commit. This module checks such LTL properties against traces recorded
+//! by DST or stateright, and reports them as `PropertyResult`s.
+//!
+//! # Semantics
+//!
+//! A `Trace` is either
+//! - **finite**, checked with bounded semantics: `<>P` must happen within
+//!   the recorded states and `[]P` covers only those states, or
+//! - a **lasso** `s0 .. s(k-1) (sk .. s(n-1))^ω`, whose last state loops
+//!   back to `sk` forever. Lassos are checked exactly.
+//!
+//! | TLA+      | Formula                                   |
+//! |-----------|-------------------------------------------|
+//! | `[]P`     | `Formula::always(p)`                      |
+//! | `<>P`     | `Formula::eventually(p)`                  |
+//! | `P ~> Q`  | `Formula::leads_to(p, q)`                 |
+//! | `WF(A)`   | `Formula::weak_fairness(enabled, taken)`  |
+//!
+//! # Counterexamples
+//!
+//! Safety violations (`[]P` with a state predicate `P`) are cut at the
+//! first bad state. Liveness violations keep the whole behavior; on a lasso
+//! the counterexample's `loop_to_step` marks where it repeats.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::counterexample::{Counterexample, StateSnapshot};
+use crate::property::PropertyResult;
+
+/// A state that can be shown in a counterexample.
+pub trait TraceState {
+    /// Variable names and values, in display order.
+    fn variables(&self) -> Vec<(String, String)>;
+
+    /// Short label for the state (e.g. the action that led to it).
+    fn description(&self) -> String {
+        String::new()
+    }
+}
+
+impl TraceState for StateSnapshot {
+    fn variables(&self) -> Vec<(String, String)> {
+        self.variables.clone()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+}
+
+/// A recorded behavior: finite, or a lasso looping back to `loop_start`.
+///
+/// A trace always has at least one state, so every formula has a truth
+/// value at its start.
+#[derive(Debug, Clone)]
+pub struct Trace<S> {
+    states: Vec<S>,
+    loop_start: Option<usize>,
+}
+
+impl<S> Trace<S> {
+    /// A finite trace, checked with bounded semantics.
+    ///
+    /// Panics if `states` is empty.
+    #[must_use]
+    pub fn finite(states: Vec<S>) -> Self {
+        assert!(!states.is_empty(), "Trace must have at least one state");
+        Self {
+            states,
+            loop_start: None,
+        }
+    }
+
+    /// A lasso: after the last state the behavior continues at
+    /// `states[loop_start]` forever.
+    ///
+    /// Panics if `loop_start` is not a position in `states`.
+    #[must_use]
+    pub fn lasso(states: Vec<S>, loop_start: usize) -> Self {
+        assert!(
+            loop_start < states.len(),
+            "Loop start {} outside trace of {} states",
+            loop_start,
+            states.len()
+        );
+        Self {
+            states,
+            loop_start: Some(loop_start),
+        }
+    }
+
+    #[must_use]
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    /// Index the last state loops back to, for lassos.
+    #[must_use]
+    pub fn loop_start(&self) -> Option<usize> {
+        self.loop_start
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Successor of position `i`, or `None` at the end of a finite trace.
+    fn successor(&self, i: usize) -> Option<usize> {
+        if i + 1 < self.states.len() {
+            Some(i + 1)
+        } else {
+            self.loop_start
+        }
+    }
+}
+
+impl<S: PartialEq> Trace<S> {
+    /// A finite trace, or a lasso if the last state repeats an earlier one.
+    ///
+    /// Deterministic systems that revisit a state loop forever, so a DST run
+    /// stuck in a livelock becomes a lasso rather than a finite prefix.
+    ///
+    /// Panics if `states` is empty.
+    #[must_use]
+    pub fn detect_lasso(mut states: Vec<S>) -> Self {
+        assert!(!states.is_empty(), "Trace must have at least one state");
+        let last = states.len() - 1;
+        match states[..last].iter().position(|s| *s == states[last]) {
+            Some(k) => {
+                states.pop();
+                Self::lasso(states, k)
+            }
+            None => Self::finite(states),
+        }
+    }
+}
+
+impl Trace<StateSnapshot> {
+    /// The states of a counterexample, e.g. a TLC or ITF liveness trace,
+    /// or `None` if it has no states.
+    #[must_use]
+    pub fn from_counterexample(ce: &Counterexample) -> Option<Self> {
+        if ce.states.is_empty() {
+            return None;
+        }
+        let states = ce.states.clone();
+        match ce
+            .loop_to_step
+            .and_then(|step| states.iter().position(|s| s.step == step))
+        {
+            Some(k) => Some(Self::lasso(states, k)),
+            None => Some(Self::finite(states)),
+        }
+    }
+}
+
+type StatePredicate<S> = Arc<dyn Fn(&S) -> bool + Send + Sync>;
+
+/// An LTL formula over states of type `S`.
+pub enum Formula<S> {
+    /// Named state predicate
+    State {
+        name: String,
+        test: StatePredicate<S>,
+    },
+    Not(Box<Formula<S>>),
+    And(Box<Formula<S>>, Box<Formula<S>>),
+    Or(Box<Formula<S>>, Box<Formula<S>>),
+    /// Holds in the next state (false at the end of a finite trace)
+    Next(Box<Formula<S>>),
+    Always(Box<Formula<S>>),
+    Eventually(Box<Formula<S>>),
+    LeadsTo(Box<Formula<S>>, Box<Formula<S>>),
+}
+
+impl<S> Clone for Formula<S> {
+    fn clone(&self) -> Self {
+        match self {
+            Formula::State { name, test } => Formula::State {
+                name: name.clone(),
+                test: Arc::clone(test),
+            },
+            Formula::Not(f) => Formula::Not(f.clone()),
+            Formula::And(a, b) => Formula::And(a.clone(), b.clone()),
+            Formula::Or(a, b) => Formula::Or(a.clone(), b.clone()),
+            Formula::Next(f) => Formula::Next(f.clone()),
+            Formula::Always(f) => Formula::Always(f.clone()),
+            Formula::Eventually(f) => Formula::Eventually(f.clone()),
+            Formula::LeadsTo(a, b) => Formula::LeadsTo(a.clone(), b.clone()),
+        }
+    }
+}
+
+impl<S> Formula<S> {
+    /// A named state predicate.
+    #[must_use]
+    pub fn state(name: &str, test: impl Fn(&S) -> bool + Send + Sync + 'static) -> Self {
+        debug_assert!(!name.is_empty(), "Predicate name must not be empty");
+        Formula::State {
+            name: name.to_string(),
+            test: Arc::new(test),
+        }
+    }
+
+    /// `[]f`
+    #[must_use]
+    pub fn always(f: Formula<S>) -> Self {
+        Formula::Always(Box::new(f))
+    }
+
+    /// `<>f`
+    #[must_use]
+    pub fn eventually(f: Formula<S>) -> Self {
+        Formula::Eventually(Box::new(f))
+    }
+
+    /// `p ~> q`, i.e. `[](p => <>q)`
+    #[must_use]
+    pub fn leads_to(p: Formula<S>, q: Formula<S>) -> Self {
+        Formula::LeadsTo(Box::new(p), Box::new(q))
+    }
+
+    /// `WF(A)` as `[]<>~enabled \/ []<>taken`: an action that stays
+    /// enabled is eventually taken.
+    #[must_use]
+    pub fn weak_fairness(enabled: Formula<S>, taken: Formula<S>) -> Self {
+        Formula::always(Formula::eventually(enabled.not()))
+            .or(Formula::always(Formula::eventually(taken)))
+    }
+
+    /// `SF(A)` as `<>[]~enabled \/ []<>taken`: an action enabled
+    /// infinitely often is eventually taken.
+    #[must_use]
+    pub fn strong_fairness(enabled: Formula<S>, taken: Formula<S>) -> Self {
+        Formula::eventually(Formula::always(enabled.not()))
+            .or(Formula::always(Formula::eventually(taken)))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn not(self) -> Self {
+        Formula::Not(Box::new(self))
+    }
+
+    #[must_use]
+    pub fn and(self, other: Formula<S>) -> Self {
+        Formula::And(Box::new(self), Box::new(other))
+    }
+
+    #[must_use]
+    pub fn or(self, other: Formula<S>) -> Self {
+        Formula::Or(Box::new(self), Box::new(other))
+    }
+
+    /// `self => other`
+    #[must_use]
+    pub fn implies(self, other: Formula<S>) -> Self {
+        self.not().or(other)
+    }
+
+    /// `()self`: holds in the next state
+    #[must_use]
+    pub fn next(self) -> Self {
+        Formula::Next(Box::new(self))
+    }
+
+    /// Whether the formula holds at the start of `trace`.
+    #[must_use]
+    pub fn holds(&self, trace: &Trace<S>) -> bool {
+        self.eval(trace)[0]
+    }
+
+    /// Whether the formula is a state predicate (no temporal operators).
+    fn is_state_formula(&self) -> bool {
+        match self {
+            Formula::State { .. } => true,
+            Formula::Not(f) => f.is_state_formula(),
+            Formula::And(a, b) | Formula::Or(a, b) => a.is_state_formula() && b.is_state_formula(),
+            Formula::Next(_)
+            | Formula::Always(_)
+            | Formula::Eventually(_)
+            | Formula::LeadsTo(..) => false,
+        }
+    }
+
+    /// Truth value at every position of the trace.
+    fn eval(&self, trace: &Trace<S>) -> Vec<bool> {
+        let n = trace.len();
+        debug_assert!(n > 0, "Cannot evaluate over an empty trace");
+        match self {
+            Formula::State { test, .. } => trace.states.iter().map(|s| test(s)).collect(),
+            Formula::Not(f) => f.eval(trace).into_iter().map(|b| !b).collect(),
+            Formula::And(a, b) => zip_with(a.eval(trace), b.eval(trace), |x, y| x && y),
+            Formula::Or(a, b) => zip_with(a.eval(trace), b.eval(trace), |x, y| x || y),
+            Formula::Next(f) => {
+                let inner = f.eval(trace);
+                (0..n)
+                    .map(|i| trace.successor(i).is_some_and(|j| inner[j]))
+                    .collect()
+            }
+            Formula::Eventually(f) => suffix_fold(trace, &f.eval(trace), false, |x, y| x || y),
+            Formula::Always(f) => suffix_fold(trace, &f.eval(trace), true, |x, y| x && y),
+            Formula::LeadsTo(p, q) => {
+                let p = p.eval(trace);
+                let eventually_q = suffix_fold(trace, &q.eval(trace), false, |x, y| x || y);
+                let step = zip_with(p, eventually_q, |p, q| !p || q);
+                suffix_fold(trace, &step, true, |x, y| x && y)
+            }
+        }
+    }
+}
+
+fn zip_with(a: Vec<bool>, b: Vec<bool>, f: impl Fn(bool, bool) -> bool) -> Vec<bool> {
+    a.into_iter().zip(b).map(|(x, y)| f(x, y)).collect()
+}
+
+/// Fold `values` over everything reachable from each position: `[i..n)`
+/// for finite traces, plus the whole loop for lassos.
+fn suffix_fold<S>(
+    trace: &Trace<S>,
+    values: &[bool],
+    identity: bool,
+    op: impl Fn(bool, bool) -> bool,
+) -> Vec<bool> {
+    let n = values.len();
+    let mut out = vec![identity; n];
+    let loop_value = trace
+        .loop_start
+        .map(|k| values[k..].iter().fold(identity, |acc, &v| op(acc, v)));
+
+    let mut acc = identity;
+    for i in (0..n).rev() {
+        acc = match (trace.loop_start, loop_value) {
+            (Some(k), Some(loop_value)) if i >= k => loop_value,
+            _ => op(values[i], acc),
+        };
+        out[i] = acc;
+    }
+    out
+}
+
+impl<S> fmt::Display for Formula<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Formula::State { name, .. } => f.write_str(name),
+            Formula::Not(inner) => write!(f, "~{}", Paren(inner)),
+            Formula::And(a, b) => write!(f, "{} /\\ {}", Paren(a), Paren(b)),
+            Formula::Or(a, b) => write!(f, "{} \\/ {}", Paren(a), Paren(b)),
+            Formula::Next(inner) => write!(f, "(){}", Paren(inner)),
+            Formula::Always(inner) => write!(f, "[]{}", Paren(inner)),
+            Formula::Eventually(inner) => write!(f, "<>{}", Paren(inner)),
+            Formula::LeadsTo(a, b) => write!(f, "{} ~> {}", Paren(a), Paren(b)),
+        }
+    }
+}
+
+/// Parenthesizes binary subformulas when displaying.
+struct Paren<'a, S>(&'a Formula<S>);
+
+impl<S> fmt::Display for Paren<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Formula::And(..) | Formula::Or(..) | Formula::LeadsTo(..) => write!(f, "({})", self.0),
+            other => write!(f, "{}", other),
+        }
+    }
+}
+
+/// A temporal property traced to a TLA+ spec, optionally assuming fairness.
+pub struct TemporalProperty<S> {
+    /// Property name (e.g. "EventuallyCommits")
+    pub name: &'static str,
+    /// TLA+ spec file this property maps to
+    pub tla_spec: &'static str,
+    /// Line number of the property in the spec
+    pub tla_line: u32,
+    /// The property itself
+    pub formula: Formula<S>,
+    /// Fairness assumption; unfair traces satisfy the property vacuously
+    pub fairness: Option<Formula<S>>,
+}
+
+impl<S: TraceState> TemporalProperty<S> {
+    #[must_use]
+    pub fn new(
+        name: &'static str,
+        tla_spec: &'static str,
+        tla_line: u32,
+        formula: Formula<S>,
+    ) -> Self {
+        debug_assert!(!name.is_empty(), "Property name must not be empty");
+        debug_assert!(tla_line > 0, "TLA+ line must be positive");
+        Self {
+            name,
+            tla_spec,
+            tla_line,
+            formula,
+            fairness: None,
+        }
+    }
+
+    /// Only judge traces that satisfy `fairness` (the spec's `Fairness`).
+    #[must_use]
+    pub fn assuming(mut self, fairness: Formula<S>) -> Self {
+        self.fairness = Some(fairness);
+        self
+    }
+
+    /// Check the property against a trace.
+    #[must_use]
+    pub fn check(&self, trace: &Trace<S>) -> PropertyResult {
+        debug_assert!(!trace.is_empty(), "Cannot check an empty trace");
+
+        let fair = self.fairness.as_ref().map_or(true, |f| f.holds(trace));
+        if !fair || self.formula.holds(trace) {
+            return PropertyResult::pass(self.name, self.tla_spec, self.tla_line);
+        }
+
+        let (violation, end, keep_loop) = self.explain(trace);
+        let mut ce = Counterexample::new().with_description(violation.clone());
+        for (i, state) in trace.states[..end].iter().enumerate() {
+            let description = state.description();
+            ce.add_state(StateSnapshot {
+                step: i as u64 + 1,
+                description: if description.is_empty() {
+                    format!("State {}", i + 1)
+                } else {
+                    description
+                },
+                variables: state.variables(),
+            });
+        }
+        if keep_loop {
+            ce.loop_to_step = trace.loop_start.map(|k| k as u64 + 1);
+        }
+
+        PropertyResult::fail(self.name, self.tla_spec, self.tla_line, violation, Some(ce))
+    }
+
+    /// Describe why the formula fails: (message, states to keep, keep loop).
+    fn explain(&self, trace: &Trace<S>) -> (String, usize, bool) {
+        let n = trace.len();
+        let bound = match trace.loop_start {
+            Some(k) => format!("in a lasso looping back to state {}", k + 1),
+            None => format!("within the {}-state bound", n),
+        };
+        let first_false = |values: Vec<bool>| (0..n).find(|&i| !values[i]).unwrap_or(0);
+
+        match &self.formula {
+            Formula::Always(inner) if inner.is_state_formula() => {
+                let bad = first_false(inner.eval(trace));
+                // A prefix ending in the bad state is enough for safety
+                (
+                    format!("{} is false in state {}", inner, bad + 1),
+                    bad + 1,
+                    false,
+                )
+            }
+            Formula::LeadsTo(p, q) => {
+                let p_values = p.eval(trace);
+                let eventually_q = Formula::Eventually(q.clone()).eval(trace);
+                let trigger = first_false(zip_with(p_values, eventually_q, |p, q| !p || q));
+                let message = format!(
+                    "{} holds in state {} but {} never follows {}",
+                    p,
+                    trigger + 1,
+                    q,
+                    bound
+                );
+                (message, n, true)
+            }
+            Formula::Eventually(inner) => (format!("{} never holds {}", inner, bound), n, true),
+            formula => (format!("{} is violated {}", formula, bound), n, true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transaction states: 0 = idle, 1 = pending, 2 = committed
+    #[derive(Debug, Clone, PartialEq)]
+    struct Txn(u8);
+
+    impl TraceState for Txn {
+        fn variables(&self) -> Vec<(String, String)> {
+            vec![("status".to_string(), self.0.to_string())]
+        }
+    }
+
+    fn pending() -> Formula<Txn> {
+        Formula::state("Pending", |t: &Txn| t.0 == 1)
+    }
+
+    fn committed() -> Formula<Txn> {
+        Formula::state("Committed", |t: &Txn| t.0 == 2)
+    }
+
+    fn trace(states: &[u8]) -> Vec<Txn> {
+        states.iter().map(|&s| Txn(s)).collect()
+    }
+
+    #[test]
+    fn test_bounded_semantics() {
+        let t = Trace::finite(trace(&[0, 1, 2]));
+        assert!(Formula::eventually(committed()).holds(&t));
+        assert!(!Formula::always(committed()).holds(&t));
+        assert!(Formula::leads_to(pending(), committed()).holds(&t));
+        assert!(pending().next().holds(&Trace::finite(trace(&[0, 1]))));
+        // No successor at the end of a finite trace
+        assert!(!pending().next().holds(&Trace::finite(trace(&[1]))));
+
+        let stuck = Trace::finite(trace(&[0, 1, 1]));
+        assert!(!Formula::leads_to(pending(), committed()).holds(&stuck));
+    }
+
+    #[test]
+    fn test_lasso_semantics() {
+        // 0 -> (1 -> 2)^ω: commits forever after
+        let cycle = Trace::lasso(trace(&[0, 1, 2]), 1);
+        assert!(Formula::always(Formula::eventually(committed())).holds(&cycle));
+        assert!(Formula::leads_to(pending(), committed()).holds(&cycle));
+        assert!(!Formula::eventually(Formula::always(committed())).holds(&cycle));
+        // The last state's successor is the loop start
+        assert!(pending().next().holds(&Trace::lasso(trace(&[2, 1]), 1)));
+
+        // 0 -> 2 -> (1)^ω: pending forever
+        let stuck = Trace::lasso(trace(&[0, 2, 1]), 2);
+        assert!(Formula::eventually(committed()).holds(&stuck));
+        assert!(!Formula::always(Formula::eventually(committed())).holds(&stuck));
+        assert!(!Formula::leads_to(pending(), committed()).holds(&stuck));
+    }
+
+    #[test]
+    fn test_detect_lasso() {
+        let t = Trace::detect_lasso(trace(&[0, 1, 2, 1]));
+        assert_eq!(t.len(), 3);
+        assert_eq!(t.loop_start(), Some(1));
+        assert!(Trace::detect_lasso(trace(&[0, 1, 2]))
+            .loop_start()
+            .is_none());
+    }
+
+    #[test]
+    fn test_liveness_counterexample_is_lasso() {
+        let property = TemporalProperty::new(
+            "EventualCommit",
+            "ssi.tla",
+            10,
+            Formula::leads_to(pending(), committed()),
+        );
+        let result = property.check(&Trace::lasso(trace(&[0, 1, 0]), 1));
+        assert!(!result.holds);
+        assert_eq!(
+            result.violation.as_deref(),
+            Some("Pending holds in state 2 but Committed never follows in a lasso looping back to state 2")
+        );
+        let ce = result.counterexample.unwrap();
+        assert_eq!(ce.states.len(), 3);
+        assert_eq!(ce.loop_to_step, Some(2));
+        assert!(ce.render_diagram().ends_with("Back to state 2\n"));
+    }
+
+    #[test]
+    fn test_safety_counterexample_is_prefix() {
+        let never_committed = Formula::always(committed().not());
+        let property = TemporalProperty::new("NeverCommits", "test.tla", 3, never_committed);
+        let result = property.check(&Trace::lasso(trace(&[0, 2, 1, 0]), 2));
+        assert_eq!(
+            result.violation.as_deref(),
+            Some("~Committed is false in state 2")
+        );
+        let ce = result.counterexample.unwrap();
+        assert_eq!(ce.states.len(), 2);
+        assert_eq!(ce.loop_to_step, None);
+    }
+
+    #[test]
+    fn test_fairness_assumption() {
+        // Pending forever without ever committing, but commit was never enabled
+        let stuck = Trace::lasso(trace(&[0, 1]), 1);
+        let enabled = Formula::state("CommitEnabled", |_: &Txn| false);
+        let property = TemporalProperty::new(
+            "EventualCommit",
+            "ssi.tla",
+            10,
+            Formula::eventually(committed()),
+        );
+        assert!(!property.check(&stuck).holds);
+
+        // Weak fairness holds (never enabled), so the property is judged
+        let wf = property.assuming(Formula::weak_fairness(enabled, committed()));
+        assert!(!wf.check(&stuck).holds);
+
+        // A fairness assumption the trace violates makes it vacuous
+        let unfair = TemporalProperty::new(
+            "EventualCommit",
+            "ssi.tla",
+            10,
+            Formula::eventually(committed()),
+        )
+        .assuming(Formula::weak_fairness(pending(), committed()));
+        assert!(unfair.check(&stuck).holds);
+    }
+
+    #[test]
+    fn test_display() {
+        let f = Formula::leads_to(pending(), committed().and(pending().not()));
+        assert_eq!(f.to_string(), "Pending ~> (Committed /\\ ~Pending)");
+        assert_eq!(
+            Formula::always(Formula::eventually(committed())).to_string(),
+            "[]<>Committed"
+        );
+    }
+
+    #[test]
+    fn test_from_counterexample() {
+        let output = "Error: Temporal properties were violated.\n\
+                      State 1: <Initial predicate>\n/\\ x = 0\n\n\
+                      State 2: <Next line 5, col 1 to line 5, col 10 of module m>\n/\\ x = 1\n\n\
+                      Back to state 1: <Next line 5, col 1 to line 5, col 10 of module m>\n";
+        let ce = crate::trace::parse_tlc_trace(output).unwrap();
+        let trace = Trace::from_counterexample(&ce).unwrap();
+        assert_eq!(trace.loop_start(), Some(0));
+
+        let x_is = |v: &'static str| {
+            Formula::state(&format!("x = {}", v), move |s: &StateSnapshot| {
+                s.variables.iter().any(|(n, value)| n == "x" && value == v)
+            })
+        };
+        assert!(Formula::always(Formula::eventually(x_is("0"))).holds(&trace));
+        assert!(!Formula::eventually(x_is("2")).holds(&trace));
+    }
+
+    #[test]
+    fn test_empty_counterexample_has_no_trace() {
+        assert!(Trace::from_counterexample(&Counterexample::new()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Trace must have at least one state")]
+    fn test_empty_trace_rejected() {
+        let _ = Trace::<StateSnapshot>::finite(Vec::new());
+    }
+}
@@ -0,0 +1,720 @@
+//! Abstract syntax tree for TLA+ modules.
+//!
+//! Covers the subset of TLA+ used by the specs under `specs/`: operator and
+//! function definitions, LET/IN, quantifiers, CHOOSE, set, tuple, record and
+//! function constructors, EXCEPT, IF/CASE, action and temporal operators.
+//! `Display` prints expressions back as (fully parenthesized) TLA+.
+
+use std::collections::HashSet;
+use std::fmt;
+
+/// A parsed TLA+ module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    /// Module name from the `---- MODULE name ----` header
+    pub name: String,
+    /// Line of the module header
+    pub line: u32,
+    /// Modules named in EXTENDS
+    pub extends: Vec<String>,
+    /// Declared CONSTANTS
+    pub constants: Vec<Declaration>,
+    /// Declared VARIABLES
+    pub variables: Vec<Declaration>,
+    /// Operator and function definitions, in source order
+    pub definitions: Vec<Definition>,
+    /// ASSUME and THEOREM bodies
+    pub assumptions: Vec<Expr>,
+}
+
+/// A CONSTANT, VARIABLE or RECURSIVE declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    pub name: String,
+    /// Number of `_` arguments for operator constants (`Op(_, _)`)
+    pub arity: usize,
+    pub line: u32,
+}
+
+/// A formal parameter of an operator definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    pub name: String,
+    /// Arity of higher-order parameters (`F(_)`), 0 otherwise
+    pub arity: usize,
+}
+
+/// An operator (`Op(x) == e`) or function (`f[x \in S] == e`) definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    /// Operator parameters (empty for functions and constants)
+    pub params: Vec<Param>,
+    /// Bounds of a function definition `f[x \in S] == e`
+    pub function_bounds: Vec<Bound>,
+    pub body: Expr,
+    /// Line of the definition's name
+    pub line: u32,
+    /// Declared RECURSIVE
+    pub recursive: bool,
+    /// Declared LOCAL
+    pub local: bool,
+}
+
+impl Definition {
+    /// Whether this is a function definition (`f[x \in S] == e`).
+    #[must_use]
+    pub fn is_function(&self) -> bool {
+        !self.function_bounds.is_empty()
+    }
+}
+
+/// Bound variables of a quantifier or constructor: `x, y \in S` or `<<x, y>> \in S`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bound {
+    pub names: Vec<String>,
+    /// Names are a tuple pattern `<<x, y>>`
+    pub tuple: bool,
+    /// Set the variables range over (None for unbounded `\A x : P`)
+    pub set: Option<Box<Expr>>,
+}
+
+/// Quantifier kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantifier {
+    Forall,
+    Exists,
+}
+
+/// Fairness kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FairnessKind {
+    Weak,
+    Strong,
+}
+
+/// Prefix operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+    Domain,
+    Subset,
+    Union,
+    Enabled,
+    Unchanged,
+    Always,
+    Eventually,
+}
+
+impl UnaryOp {
+    /// TLA+ spelling.
+    #[must_use]
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            UnaryOp::Not => "~",
+            UnaryOp::Neg => "-",
+            UnaryOp::Domain => "DOMAIN ",
+            UnaryOp::Subset => "SUBSET ",
+            UnaryOp::Union => "UNION ",
+            UnaryOp::Enabled => "ENABLED ",
+            UnaryOp::Unchanged => "UNCHANGED ",
+            UnaryOp::Always => "[]",
+            UnaryOp::Eventually => "<>",
+        }
+    }
+}
+
+/// Infix operators (conjunction and disjunction are `Expr::And`/`Expr::Or`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Implies,
+    Equiv,
+    LeadsTo,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    In,
+    NotIn,
+    SubsetEq,
+    ProperSubset,
+    SupsetEq,
+    ProperSupset,
+    Cup,
+    Cap,
+    SetMinus,
+    Cartesian,
+    Range,
+    Plus,
+    Minus,
+    Times,
+    Div,
+    Mod,
+    Exp,
+    Concat,
+    /// `f @@ g` (TLC function merge)
+    Merge,
+    /// `a :> b` (TLC single-point function)
+    MapsTo,
+}
+
+impl BinOp {
+    /// TLA+ spelling.
+    #[must_use]
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinOp::Implies => "=>",
+            BinOp::Equiv => "<=>",
+            BinOp::LeadsTo => "~>",
+            BinOp::Eq => "=",
+            BinOp::Neq => "#",
+            BinOp::Lt => "<",
+            BinOp::Gt => ">",
+            BinOp::Le => "<=",
+            BinOp::Ge => ">=",
+            BinOp::In => "\\in",
+            BinOp::NotIn => "\\notin",
+            BinOp::SubsetEq => "\\subseteq",
+            BinOp::ProperSubset => "\\subset",
+            BinOp::SupsetEq => "\\supseteq",
+            BinOp::ProperSupset => "\\supset",
+            BinOp::Cup => "\\cup",
+            BinOp::Cap => "\\cap",
+            BinOp::SetMinus => "\\",
+            BinOp::Cartesian => "\\X",
+            BinOp::Range => "..",
+            BinOp::Plus => "+",
+            BinOp::Minus => "-",
+            BinOp::Times => "*",
+            BinOp::Div => "\\div",
+            BinOp::Mod => "%",
+            BinOp::Exp => "^",
+            BinOp::Concat => "\\o",
+            BinOp::Merge => "@@",
+            BinOp::MapsTo => ":>",
+        }
+    }
+}
+
+/// One step of an EXCEPT path: `[e]` or `.field`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExceptKey {
+    Index(Vec<Expr>),
+    Field(String),
+}
+
+/// `!path = value` in an EXCEPT expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExceptUpdate {
+    pub path: Vec<ExceptKey>,
+    pub value: Expr,
+}
+
+/// A TLA+ expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Bool(bool),
+    Num(i64),
+    Str(String),
+    /// Variable, constant, bound variable or zero-arity operator
+    Ident(String),
+    /// Operator application `Op(a, b)`
+    Apply {
+        op: String,
+        args: Vec<Expr>,
+    },
+    /// Function application `f[a]` (`f[a, b]` applies to a tuple)
+    FnApply {
+        func: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    /// Record field `r.field`
+    Field {
+        record: Box<Expr>,
+        field: String,
+    },
+    /// Primed expression `e'`
+    Prime(Box<Expr>),
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+    Binary {
+        op: BinOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    /// Conjunction (infix or bulleted list)
+    And(Vec<Expr>),
+    /// Disjunction (infix or bulleted list)
+    Or(Vec<Expr>),
+    Quant {
+        kind: Quantifier,
+        bounds: Vec<Bound>,
+        body: Box<Expr>,
+    },
+    Choose {
+        bound: Bound,
+        body: Box<Expr>,
+    },
+    /// `{a, b, c}`
+    SetEnum(Vec<Expr>),
+    /// `{x \in S : P}`
+    SetFilter {
+        bound: Bound,
+        pred: Box<Expr>,
+    },
+    /// `{e : x \in S}`
+    SetMap {
+        expr: Box<Expr>,
+        bounds: Vec<Bound>,
+    },
+    /// `<<a, b>>`
+    Tuple(Vec<Expr>),
+    /// `[a |-> 1, b |-> 2]`
+    Record(Vec<(String, Expr)>),
+    /// `[a : S, b : T]`
+    RecordSet(Vec<(String, Expr)>),
+    /// `[x \in S |-> e]`
+    FnConstruct {
+        bounds: Vec<Bound>,
+        body: Box<Expr>,
+    },
+    /// `[S -> T]`
+    FnSet {
+        domain: Box<Expr>,
+        range: Box<Expr>,
+    },
+    /// `[f EXCEPT ![a] = e, ...]`
+    Except {
+        func: Box<Expr>,
+        updates: Vec<ExceptUpdate>,
+    },
+    /// `@` inside an EXCEPT value
+    At,
+    If {
+        cond: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+    Case {
+        arms: Vec<(Expr, Expr)>,
+        other: Option<Box<Expr>>,
+    },
+    Let {
+        defs: Vec<Definition>,
+        body: Box<Expr>,
+    },
+    /// `[A]_v`
+    BoxAction {
+        action: Box<Expr>,
+        vars: Box<Expr>,
+    },
+    /// `<<A>>_v`
+    AngleAction {
+        action: Box<Expr>,
+        vars: Box<Expr>,
+    },
+    /// `WF_v(A)` / `SF_v(A)`
+    Fairness {
+        kind: FairnessKind,
+        vars: Box<Expr>,
+        action: Box<Expr>,
+    },
+}
+
+impl Expr {
+    /// Whether the expression mentions a temporal operator (`[]`, `<>`,
+    /// `~>`, fairness) directly, without expanding definitions.
+    #[must_use]
+    pub fn is_temporal(&self) -> bool {
+        let mut temporal = false;
+        self.visit(&mut |e| {
+            temporal |= matches!(
+                e,
+                Expr::Unary {
+                    op: UnaryOp::Always | UnaryOp::Eventually,
+                    ..
+                } | Expr::Binary {
+                    op: BinOp::LeadsTo,
+                    ..
+                } | Expr::Fairness { .. }
+                    | Expr::BoxAction { .. }
+            );
+        });
+        temporal
+    }
+
+    /// Identifiers and operator names this expression refers to.
+    #[must_use]
+    pub fn references(&self) -> HashSet<String> {
+        let mut names = HashSet::new();
+        self.visit(&mut |e| match e {
+            Expr::Ident(name) | Expr::Apply { op: name, .. } => {
+                names.insert(name.clone());
+            }
+            _ => {}
+        });
+        names
+    }
+
+    /// Call `f` on this expression and every subexpression, pre-order.
+    pub fn visit(&self, f: &mut dyn FnMut(&Expr)) {
+        f(self);
+        let bound_sets = |bounds: &[Bound], f: &mut dyn FnMut(&Expr)| {
+            for set in bounds.iter().filter_map(|b| b.set.as_deref()) {
+                set.visit(f);
+            }
+        };
+        match self {
+            Expr::Bool(_) | Expr::Num(_) | Expr::Str(_) | Expr::Ident(_) | Expr::At => {}
+            Expr::Apply { args, .. } => args.iter().for_each(|a| a.visit(f)),
+            Expr::FnApply { func, args } => {
+                func.visit(f);
+                args.iter().for_each(|a| a.visit(f));
+            }
+            Expr::Field { record: e, .. } | Expr::Prime(e) | Expr::Unary { expr: e, .. } => {
+                e.visit(f)
+            }
+            Expr::Binary { lhs, rhs, .. } => {
+                lhs.visit(f);
+                rhs.visit(f);
+            }
+            Expr::And(items) | Expr::Or(items) | Expr::SetEnum(items) | Expr::Tuple(items) => {
+                items.iter().for_each(|e| e.visit(f))
+            }
+            Expr::Quant { bounds, body, .. } | Expr::FnConstruct { bounds, body } => {
+                bound_sets(bounds, f);
+                body.visit(f);
+            }
+            Expr::Choose { bound, body } | Expr::SetFilter { bound, pred: body } => {
+                bound_sets(std::slice::from_ref(bound), f);
+                body.visit(f);
+            }
+            Expr::SetMap { expr, bounds } => {
+                expr.visit(f);
+                bound_sets(bounds, f);
+            }
+            Expr::Record(fields) | Expr::RecordSet(fields) => {
+                fields.iter().for_each(|(_, e)| e.visit(f))
+            }
+            Expr::FnSet { domain, range } => {
+                domain.visit(f);
+                range.visit(f);
+            }
+            Expr::Except { func, updates } => {
+                func.visit(f);
+                for update in updates {
+                    for key in &update.path {
+                        if let ExceptKey::Index(args) = key {
+                            args.iter().for_each(|a| a.visit(f));
+                        }
+                    }
+                    update.value.visit(f);
+                }
+            }
+            Expr::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                cond.visit(f);
+                then.visit(f);
+                otherwise.visit(f);
+            }
+            Expr::Case { arms, other } => {
+                for (guard, value) in arms {
+                    guard.visit(f);
+                    value.visit(f);
+                }
+                if let Some(other) = other {
+                    other.visit(f);
+                }
+            }
+            Expr::Let { defs, body } => {
+                defs.iter().for_each(|d| d.body.visit(f));
+                body.visit(f);
+            }
+            Expr::BoxAction { action, vars } | Expr::AngleAction { action, vars } => {
+                action.visit(f);
+                vars.visit(f);
+            }
+            Expr::Fairness { vars, action, .. } => {
+                vars.visit(f);
+                action.visit(f);
+            }
+        }
+    }
+
+    /// Split a conjunction into its conjuncts.
+    #[must_use]
+    pub fn conjuncts(&self) -> Vec<&Expr> {
+        match self {
+            Expr::And(items) => items.iter().flat_map(Expr::conjuncts).collect(),
+            other => vec![other],
+        }
+    }
+}
+
+/// The behavior specification `Init /\ [][Next]_vars /\ Fairness`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Behavior {
+    /// Initial-state predicate
+    pub init: Expr,
+    /// Next-state relation
+    pub next: Expr,
+    /// Subscript of `[][Next]_vars`
+    pub vars: Expr,
+    /// Fairness conditions (`WF_v(A)`, possibly under `\A`)
+    pub fairness: Vec<Expr>,
+}
+
+impl Module {
+    /// Find a definition by name.
+    #[must_use]
+    pub fn definition(&self, name: &str) -> Option<&Definition> {
+        self.definitions.iter().find(|d| d.name == name)
+    }
+
+    /// The `Init` definition.
+    #[must_use]
+    pub fn init(&self) -> Option<&Definition> {
+        self.definition("Init")
+    }
+
+    /// The `Next` definition.
+    #[must_use]
+    pub fn next(&self) -> Option<&Definition> {
+        self.definition("Next")
+    }
+
+    /// The `Spec` definition.
+    #[must_use]
+    pub fn spec(&self) -> Option<&Definition> {
+        self.definition("Spec")
+    }
+
+    /// Whether `name` is a declared variable.
+    #[must_use]
+    pub fn is_variable(&self, name: &str) -> bool {
+        self.variables.iter().any(|v| v.name == name)
+    }
+
+    /// Decompose a behavior spec definition (e.g. `Spec` or `FairSpec`)
+    /// into init, next, vars and fairness, expanding referenced
+    /// zero-arity definitions.
+    ///
+    /// Returns None if the definition has no `[][Next]_vars` conjunct.
+    #[must_use]
+    pub fn behavior(&self, spec_name: &str) -> Option<Behavior> {
+        let mut init = None;
+        let mut action = None;
+        let mut fairness = Vec::new();
+        let mut visited = HashSet::new();
+        self.collect_behavior(
+            &self.definition(spec_name)?.body,
+            &mut init,
+            &mut action,
+            &mut fairness,
+            &mut visited,
+        );
+        let (next, vars) = action?;
+        Some(Behavior {
+            init: init?,
+            next,
+            vars,
+            fairness,
+        })
+    }
+
+    fn collect_behavior(
+        &self,
+        expr: &Expr,
+        init: &mut Option<Expr>,
+        action: &mut Option<(Expr, Expr)>,
+        fairness: &mut Vec<Expr>,
+        visited: &mut HashSet<String>,
+    ) {
+        for conjunct in expr.conjuncts() {
+            match conjunct {
+                Expr::Unary {
+                    op: UnaryOp::Always,
+                    expr,
+                } => {
+                    if let Expr::BoxAction { action: a, vars } = expr.as_ref() {
+                        *action = Some((a.as_ref().clone(), vars.as_ref().clone()));
+                    }
+                }
+                Expr::Fairness { .. } => fairness.push(conjunct.clone()),
+                Expr::Quant { body, .. } if matches!(**body, Expr::Fairness { .. }) => {
+                    fairness.push(conjunct.clone())
+                }
+                Expr::Ident(name) => match self.definition(name) {
+                    Some(def) if def.params.is_empty() && def.body.is_temporal() => {
+                        // Guard against definitions that refer to themselves
+                        let first_visit = visited.insert(name.clone());
+                        if first_visit {
+                            self.collect_behavior(&def.body, init, action, fairness, visited);
+                        }
+                    }
+                    _ if init.is_none() => *init = Some(conjunct.clone()),
+                    _ => {}
+                },
+                _ if !conjunct.is_temporal() && init.is_none() => *init = Some(conjunct.clone()),
+                _ => {}
+            }
+        }
+    }
+}
+
+// ============================================================================
+// DISPLAY
+// ============================================================================
+
+struct Sep<'a, T>(&'a [T], &'static str);
+
+impl<T: fmt::Display> fmt::Display for Sep<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.1)?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.tuple {
+            write!(f, "<<{}>>", self.names.join(", "))?;
+        } else {
+            f.write_str(&self.names.join(", "))?;
+        }
+        if let Some(set) = &self.set {
+            write!(f, " \\in {}", set)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Definition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if !self.params.is_empty() {
+            let params: Vec<String> = self
+                .params
+                .iter()
+                .map(|p| match p.arity {
+                    0 => p.name.clone(),
+                    n => format!("{}({})", p.name, vec!["_"; n].join(", ")),
+                })
+                .collect();
+            write!(f, "({})", params.join(", "))?;
+        }
+        if self.is_function() {
+            write!(f, "[{}]", Sep(&self.function_bounds, ", "))?;
+        }
+        write!(f, " == {}", self.body)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Bool(true) => f.write_str("TRUE"),
+            Expr::Bool(false) => f.write_str("FALSE"),
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Str(s) => write!(f, "{:?}", s),
+            Expr::Ident(name) => f.write_str(name),
+            Expr::Apply { op, args } => write!(f, "{}({})", op, Sep(args, ", ")),
+            Expr::FnApply { func, args } => write!(f, "{}[{}]", func, Sep(args, ", ")),
+            Expr::Field { record, field } => write!(f, "{}.{}", record, field),
+            Expr::Prime(e) => write!(f, "({})'", e),
+            Expr::Unary { op, expr } => write!(f, "{}({})", op.symbol(), expr),
+            Expr::Binary { op, lhs, rhs } => write!(f, "({} {} {})", lhs, op.symbol(), rhs),
+            Expr::And(items) => write!(f, "({})", Sep(items, " /\\ ")),
+            Expr::Or(items) => write!(f, "({})", Sep(items, " \\/ ")),
+            Expr::Quant { kind, bounds, body } => {
+                let q = match kind {
+                    Quantifier::Forall => "\\A",
+                    Quantifier::Exists => "\\E",
+                };
+                write!(f, "({} {} : {})", q, Sep(bounds, ", "), body)
+            }
+            Expr::Choose { bound, body } => write!(f, "(CHOOSE {} : {})", bound, body),
+            Expr::SetEnum(items) => write!(f, "{{{}}}", Sep(items, ", ")),
+            Expr::SetFilter { bound, pred } => write!(f, "{{{} : {}}}", bound, pred),
+            Expr::SetMap { expr, bounds } => write!(f, "{{{} : {}}}", expr, Sep(bounds, ", ")),
+            Expr::Tuple(items) => write!(f, "<<{}>>", Sep(items, ", ")),
+            Expr::Record(fields) | Expr::RecordSet(fields) => {
+                let sep = if matches!(self, Expr::Record(_)) {
+                    " |-> "
+                } else {
+                    " : "
+                };
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(name, e)| format!("{}{}{}", name, sep, e))
+                    .collect();
+                write!(f, "[{}]", fields.join(", "))
+            }
+            Expr::FnConstruct { bounds, body } => {
+                write!(f, "[{} |-> {}]", Sep(bounds, ", "), body)
+            }
+            Expr::FnSet { domain, range } => write!(f, "[{} -> {}]", domain, range),
+            Expr::Except { func, updates } => {
+                write!(f, "[{} EXCEPT ", func)?;
+                for (i, update) in updates.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str("!")?;
+                    for key in &update.path {
+                        match key {
+                            ExceptKey::Index(args) => write!(f, "[{}]", Sep(args, ", "))?,
+                            ExceptKey::Field(name) => write!(f, ".{}", name)?,
+                        }
+                    }
+                    write!(f, " = {}", update.value)?;
+                }
+                f.write_str("]")
+            }
+            Expr::At => f.write_str("@"),
+            Expr::If {
+                cond,
+                then,
+                otherwise,
+            } => write!(f, "(IF {} THEN {} ELSE {})", cond, then, otherwise),
+            Expr::Case { arms, other } => {
+                f.write_str("(CASE ")?;
+                for (i, (guard, value)) in arms.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" [] ")?;
+                    }
+                    write!(f, "{} -> {}", guard, value)?;
+                }
+                if let Some(other) = other {
+                    write!(f, " [] OTHER -> {}", other)?;
+                }
+                f.write_str(")")
+            }
+            Expr::Let { defs, body } => {
+                write!(f, "(LET {} IN {})", Sep(defs, " "), body)
+            }
+            Expr::BoxAction { action, vars } => write!(f, "[{}]_{}", action, vars),
+            Expr::AngleAction { action, vars } => write!(f, "<<{}>>_{}", action, vars),
+            Expr::Fairness { kind, vars, action } => {
+                let k = match kind {
+                    FairnessKind::Weak => "WF_",
+                    FairnessKind::Strong => "SF_",
+                };
+                write!(f, "{}{}({})", k, vars, action)
+            }
+        }
+    }
+}
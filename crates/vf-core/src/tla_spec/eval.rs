@@ -0,0 +1,1477 @@
+//! Evaluator for TLA+ expressions over concrete states.
+//!
+//! Lets invariants be checked straight from the spec: bind the module's
+//! constants and variables to [`Value`]s (for example from a
+//! [`StateSnapshot`]) and evaluate an operator such as `NoUseAfterFree`.
+//!
+//! Evaluation is eager and exact. Infinite sets (`Nat`, `Int`, `STRING`,
+//! `Seq(S)`) may only appear on the right of `\in`; temporal operators,
+//! `ENABLED` and higher-order operator arguments are reported as
+//! [`EvalError::Unsupported`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use super::ast::{BinOp, Bound, Definition, ExceptKey, Expr, Module, Quantifier, UnaryOp};
+use super::parser::parse_expr;
+use super::value::Value;
+use super::ParseError;
+use crate::counterexample::StateSnapshot;
+
+/// Largest set the evaluator will build (`SUBSET S`, `[S -> T]`, `a..b`, ...).
+const SET_SIZE_MAX: usize = 1 << 16;
+
+/// Deepest operator call nesting, which bounds RECURSIVE operators.
+const CALL_DEPTH_MAX: usize = 128;
+
+/// Longest value rendering included in an error message.
+const ERROR_VALUE_CHARS_MAX: usize = 80;
+
+/// Module with no declarations, used to evaluate value literals.
+static EMPTY_MODULE: Module = Module {
+    name: String::new(),
+    line: 0,
+    extends: Vec::new(),
+    constants: Vec::new(),
+    variables: Vec::new(),
+    definitions: Vec::new(),
+    assumptions: Vec::new(),
+};
+
+/// Error evaluating a TLA+ expression.
+#[derive(Debug, Clone)]
+pub enum EvalError {
+    /// Identifier or operator with no binding
+    Unbound(String),
+    /// Construct the evaluator does not handle
+    Unsupported(String),
+    /// Operand of the wrong kind
+    Type {
+        expected: &'static str,
+        found: String,
+    },
+    /// Function applied outside its domain
+    NotInDomain { function: String, arg: String },
+    /// Operator called with the wrong number of arguments
+    Arity {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    /// CHOOSE or CASE with no matching value
+    NoMatch(String),
+    /// Overflow, division by zero and similar
+    Arithmetic(String),
+    /// Set too large to build, or recursion too deep
+    LimitExceeded(String),
+    /// TLC `Assert` failed
+    AssertionFailed(String),
+    /// A state variable's value did not parse or evaluate
+    InvalidValue { variable: String, message: String },
+    /// A value literal is not valid TLA+
+    Parse(ParseError),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Unbound(name) => write!(f, "unbound identifier `{}`", name),
+            EvalError::Unsupported(what) => write!(f, "unsupported: {}", what),
+            EvalError::Type { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            EvalError::NotInDomain { function, arg } => {
+                write!(f, "{} is not in the domain of {}", arg, function)
+            }
+            EvalError::Arity {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} takes {} argument(s), {} given",
+                name, expected, found
+            ),
+            EvalError::NoMatch(what) => write!(f, "no value satisfies {}", what),
+            EvalError::Arithmetic(what) => write!(f, "arithmetic error: {}", what),
+            EvalError::LimitExceeded(what) => write!(f, "limit exceeded: {}", what),
+            EvalError::AssertionFailed(message) => write!(f, "assertion failed: {}", message),
+            EvalError::InvalidValue { variable, message } => {
+                write!(f, "invalid value for {}: {}", variable, message)
+            }
+            EvalError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Parse a value printed in TLA+ syntax, e.g. `[t1 |-> {1, 2}]`.
+///
+/// Unknown identifiers are model values.
+pub fn parse_value(text: &str) -> Result<Value, EvalError> {
+    let expr = parse_expr(text).map_err(EvalError::Parse)?;
+    let mut evaluator = Evaluator::new(&EMPTY_MODULE);
+    evaluator.model_values = true;
+    evaluator.eval(&expr)
+}
+
+/// Shorten a value for an error message.
+fn describe(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() <= ERROR_VALUE_CHARS_MAX {
+        return format!("{} {}", value.kind(), text);
+    }
+    let prefix: String = text.chars().take(ERROR_VALUE_CHARS_MAX).collect();
+    format!("{} {}...", value.kind(), prefix)
+}
+
+fn type_error(expected: &'static str, found: &Value) -> EvalError {
+    EvalError::Type {
+        expected,
+        found: describe(found),
+    }
+}
+
+fn to_bool(value: Value) -> Result<bool, EvalError> {
+    value.as_bool().ok_or_else(|| type_error("boolean", &value))
+}
+
+fn to_int(value: Value) -> Result<i64, EvalError> {
+    value.as_int().ok_or_else(|| type_error("integer", &value))
+}
+
+fn to_set(value: Value) -> Result<BTreeSet<Value>, EvalError> {
+    match value {
+        Value::Set(s) => Ok(s),
+        other => Err(type_error("set", &other)),
+    }
+}
+
+fn to_seq(value: Value) -> Result<Vec<Value>, EvalError> {
+    match value {
+        Value::Seq(s) => Ok(s),
+        other => Err(type_error("sequence", &other)),
+    }
+}
+
+fn check_size(size: usize, what: &str) -> Result<(), EvalError> {
+    if size > SET_SIZE_MAX {
+        return Err(EvalError::LimitExceeded(format!(
+            "{} has more than {} elements",
+            what, SET_SIZE_MAX
+        )));
+    }
+    Ok(())
+}
+
+fn overflow(op: BinOp) -> EvalError {
+    EvalError::Arithmetic(format!("integer overflow in `{}`", op.symbol()))
+}
+
+/// What a local name is bound to.
+enum Binding<'e> {
+    Value(Value),
+    /// LET-defined operator
+    Def(&'e Definition),
+}
+
+/// Evaluation context: bound variables and LET definitions in scope.
+struct Ctx<'e> {
+    locals: Vec<(&'e str, Binding<'e>)>,
+    /// Locals below this index are out of scope (inside a module operator)
+    base: usize,
+    /// Variables refer to the next state
+    primed: bool,
+    depth: usize,
+}
+
+impl<'e> Ctx<'e> {
+    fn new() -> Self {
+        Self {
+            locals: Vec::new(),
+            base: 0,
+            primed: false,
+            depth: 0,
+        }
+    }
+
+    fn local(&self, name: &str) -> Option<&Binding<'e>> {
+        self.locals[self.base..]
+            .iter()
+            .rev()
+            .find(|(n, _)| *n == name)
+            .map(|(_, b)| b)
+    }
+
+    fn push(&mut self, name: &'e str, value: Value) {
+        self.locals.push((name, Binding::Value(value)));
+    }
+}
+
+/// Callback run for each binding of a quantifier; returns whether to go on.
+type Visitor<'a, 'e> = dyn FnMut(&mut Ctx<'e>, &[Value]) -> Result<bool, EvalError> + 'a;
+
+/// One variable of a quantifier, or one tuple pattern.
+struct Slot<'e> {
+    names: &'e [String],
+    tuple: bool,
+    set: &'e Expr,
+}
+
+fn slots(bounds: &[Bound]) -> Result<Vec<Slot<'_>>, EvalError> {
+    let mut slots = Vec::new();
+    for bound in bounds {
+        let Some(set) = bound.set.as_deref() else {
+            return Err(EvalError::Unsupported(format!(
+                "unbounded quantification over {}",
+                bound.names.join(", ")
+            )));
+        };
+        if bound.tuple {
+            slots.push(Slot {
+                names: &bound.names,
+                tuple: true,
+                set,
+            });
+        } else {
+            for name in &bound.names {
+                slots.push(Slot {
+                    names: std::slice::from_ref(name),
+                    tuple: false,
+                    set,
+                });
+            }
+        }
+    }
+    Ok(slots)
+}
+
+/// Evaluates expressions of one module against concrete bindings.
+///
+/// ```
+/// use vf_core::tla_spec::{parse_module, Evaluator, Value};
+///
+/// let module = parse_module(
+///     "---- MODULE m ----\nVARIABLE freed, referenced\n\
+///      Safe == referenced \\cap freed = {}\n====",
+/// )
+/// .unwrap();
+/// let mut eval = Evaluator::new(&module);
+/// eval.set_variable("freed", Value::set([Value::Int(1)]));
+/// eval.set_variable("referenced", Value::set([Value::Int(2)]));
+/// assert!(eval.check("Safe").unwrap());
+/// ```
+pub struct Evaluator<'m> {
+    module: &'m Module,
+    constants: HashMap<String, Value>,
+    state: HashMap<String, Value>,
+    next: Option<HashMap<String, Value>>,
+    /// Unknown identifiers evaluate to model values
+    model_values: bool,
+}
+
+impl<'m> Evaluator<'m> {
+    #[must_use]
+    pub fn new(module: &'m Module) -> Self {
+        Self {
+            module,
+            constants: HashMap::new(),
+            state: HashMap::new(),
+            next: None,
+            model_values: false,
+        }
+    }
+
+    /// Bind a constant.
+    #[must_use]
+    pub fn with_constant(mut self, name: &str, value: Value) -> Self {
+        debug_assert!(
+            self.module.constants.iter().any(|c| c.name == name),
+            "{} is not a declared constant",
+            name
+        );
+        self.constants.insert(name.to_string(), value);
+        self
+    }
+
+    /// Bind every declared constant that has no value yet to a model value
+    /// of the same name (e.g. `NULL`).
+    #[must_use]
+    pub fn with_model_constants(mut self) -> Self {
+        for constant in &self.module.constants {
+            self.constants
+                .entry(constant.name.clone())
+                .or_insert_with(|| Value::model(&constant.name));
+        }
+        self
+    }
+
+    /// Bind a variable in the current state.
+    pub fn set_variable(&mut self, name: &str, value: Value) {
+        debug_assert!(
+            self.module.is_variable(name),
+            "{} is not a declared variable",
+            name
+        );
+        self.state.insert(name.to_string(), value);
+    }
+
+    /// Bind a variable in the next state, for evaluating actions.
+    pub fn set_next_variable(&mut self, name: &str, value: Value) {
+        debug_assert!(
+            self.module.is_variable(name),
+            "{} is not a declared variable",
+            name
+        );
+        self.next
+            .get_or_insert_with(HashMap::new)
+            .insert(name.to_string(), value);
+    }
+
+    /// Bind the current state from a snapshot whose values are printed in
+    /// TLA+ syntax. Entries that are not module variables are ignored.
+    pub fn load_state(&mut self, snapshot: &StateSnapshot) -> Result<(), EvalError> {
+        let values = self.parse_snapshot(snapshot)?;
+        self.state.extend(values);
+        Ok(())
+    }
+
+    /// Bind the next state from a snapshot, like [`Self::load_state`].
+    pub fn load_next_state(&mut self, snapshot: &StateSnapshot) -> Result<(), EvalError> {
+        let values = self.parse_snapshot(snapshot)?;
+        self.next.get_or_insert_with(HashMap::new).extend(values);
+        Ok(())
+    }
+
+    fn parse_snapshot(&self, snapshot: &StateSnapshot) -> Result<Vec<(String, Value)>, EvalError> {
+        snapshot
+            .variables
+            .iter()
+            .filter(|(name, _)| self.module.is_variable(name))
+            .map(|(name, text)| {
+                parse_value(text)
+                    .map(|value| (name.clone(), value))
+                    .map_err(|e| EvalError::InvalidValue {
+                        variable: name.clone(),
+                        message: e.to_string(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Evaluate an expression.
+    pub fn eval(&self, expr: &Expr) -> Result<Value, EvalError> {
+        self.eval_in(expr, &mut Ctx::new())
+    }
+
+    /// Apply a module operator to argument values.
+    pub fn call(&self, name: &str, args: Vec<Value>) -> Result<Value, EvalError> {
+        let def = self
+            .module
+            .definition(name)
+            .ok_or_else(|| EvalError::Unbound(name.to_string()))?;
+        self.call_def(def, args, &mut Ctx::new(), true)
+    }
+
+    /// Evaluate a zero-argument module operator as a predicate,
+    /// e.g. an invariant.
+    pub fn check(&self, name: &str) -> Result<bool, EvalError> {
+        to_bool(self.call(name, Vec::new())?)
+    }
+
+    // ------------------------------------------------------------------
+    // Core evaluation
+    // ------------------------------------------------------------------
+
+    fn eval_bool<'e>(&'e self, expr: &'e Expr, ctx: &mut Ctx<'e>) -> Result<bool, EvalError> {
+        to_bool(self.eval_in(expr, ctx)?)
+    }
+
+    fn eval_int<'e>(&'e self, expr: &'e Expr, ctx: &mut Ctx<'e>) -> Result<i64, EvalError> {
+        to_int(self.eval_in(expr, ctx)?)
+    }
+
+    fn eval_set<'e>(
+        &'e self,
+        expr: &'e Expr,
+        ctx: &mut Ctx<'e>,
+    ) -> Result<BTreeSet<Value>, EvalError> {
+        to_set(self.eval_in(expr, ctx)?)
+    }
+
+    fn eval_in<'e>(&'e self, expr: &'e Expr, ctx: &mut Ctx<'e>) -> Result<Value, EvalError> {
+        match expr {
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            Expr::Num(n) => Ok(Value::Int(*n)),
+            Expr::Str(s) => Ok(Value::Str(s.clone())),
+            Expr::Ident(name) => self.lookup(name, ctx),
+            Expr::Apply { op, args } => {
+                let args = args
+                    .iter()
+                    .map(|a| self.eval_in(a, ctx))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.apply_op(op, args, ctx)
+            }
+            Expr::FnApply { func, args } => self.fn_apply(func, args, ctx),
+            Expr::Field { record, field } => {
+                let value = self.eval_in(record, ctx)?;
+                if !value.is_function() {
+                    return Err(type_error("record", &value));
+                }
+                value
+                    .apply(&Value::str(field))
+                    .cloned()
+                    .ok_or_else(|| EvalError::NotInDomain {
+                        function: describe(&value),
+                        arg: format!("field {}", field),
+                    })
+            }
+            Expr::Prime(inner) => self.eval_primed(inner, ctx),
+            Expr::Unary { op, expr: operand } => self.unary(*op, operand, ctx),
+            Expr::Binary { op, lhs, rhs } => self.binary(*op, lhs, rhs, ctx),
+            Expr::And(items) => {
+                for item in items {
+                    if !self.eval_bool(item, ctx)? {
+                        return Ok(Value::Bool(false));
+                    }
+                }
+                Ok(Value::Bool(true))
+            }
+            Expr::Or(items) => {
+                for item in items {
+                    if self.eval_bool(item, ctx)? {
+                        return Ok(Value::Bool(true));
+                    }
+                }
+                Ok(Value::Bool(false))
+            }
+            Expr::Quant { kind, bounds, body } => {
+                let slots = slots(bounds)?;
+                let want = *kind == Quantifier::Exists;
+                let mut found = false;
+                self.for_each(&slots, ctx, &mut Vec::new(), &mut |ctx, _| {
+                    found = self.eval_bool(body, ctx)? == want;
+                    Ok(!found)
+                })?;
+                // \A holds unless a counterexample was found
+                Ok(Value::Bool(found == want))
+            }
+            Expr::Choose { bound, body } => {
+                let slots = slots(std::slice::from_ref(bound))?;
+                let mut chosen = None;
+                self.for_each(&slots, ctx, &mut Vec::new(), &mut |ctx, elems| {
+                    if self.eval_bool(body, ctx)? {
+                        chosen = Some(elems[0].clone());
+                        return Ok(false);
+                    }
+                    Ok(true)
+                })?;
+                chosen.ok_or_else(|| EvalError::NoMatch(expr.to_string()))
+            }
+            Expr::SetEnum(items) => items
+                .iter()
+                .map(|item| self.eval_in(item, ctx))
+                .collect::<Result<BTreeSet<_>, _>>()
+                .map(Value::Set),
+            Expr::SetFilter { bound, pred } => {
+                let slots = slots(std::slice::from_ref(bound))?;
+                let mut set = BTreeSet::new();
+                self.for_each(&slots, ctx, &mut Vec::new(), &mut |ctx, elems| {
+                    if self.eval_bool(pred, ctx)? {
+                        set.insert(elems[0].clone());
+                    }
+                    Ok(true)
+                })?;
+                Ok(Value::Set(set))
+            }
+            Expr::SetMap { expr: item, bounds } => {
+                let slots = slots(bounds)?;
+                let mut set = BTreeSet::new();
+                self.for_each(&slots, ctx, &mut Vec::new(), &mut |ctx, _| {
+                    set.insert(self.eval_in(item, ctx)?);
+                    Ok(true)
+                })?;
+                Ok(Value::Set(set))
+            }
+            Expr::Tuple(items) => items
+                .iter()
+                .map(|item| self.eval_in(item, ctx))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Seq),
+            Expr::Record(fields) => {
+                let mut record = BTreeMap::new();
+                for (name, value) in fields {
+                    record.insert(Value::str(name), self.eval_in(value, ctx)?);
+                }
+                Ok(Value::function(record))
+            }
+            Expr::RecordSet(fields) => {
+                let mut records = vec![BTreeMap::new()];
+                for (name, set) in fields {
+                    let set = self.eval_set(set, ctx)?;
+                    check_size(records.len() * set.len(), "record set")?;
+                    records = records
+                        .into_iter()
+                        .flat_map(|r| {
+                            set.iter().map(move |v| {
+                                let mut r = r.clone();
+                                r.insert(Value::str(name), v.clone());
+                                r
+                            })
+                        })
+                        .collect();
+                }
+                Ok(Value::set(records.into_iter().map(Value::function)))
+            }
+            Expr::FnConstruct { bounds, body } => {
+                let slots = slots(bounds)?;
+                let mut graph = BTreeMap::new();
+                self.for_each(&slots, ctx, &mut Vec::new(), &mut |ctx, elems| {
+                    let key = match elems {
+                        [single] => single.clone(),
+                        many => Value::Seq(many.to_vec()),
+                    };
+                    graph.insert(key, self.eval_in(body, ctx)?);
+                    Ok(true)
+                })?;
+                Ok(Value::function(graph))
+            }
+            Expr::FnSet { domain, range } => {
+                let domain = self.eval_set(domain, ctx)?;
+                let range = self.eval_set(range, ctx)?;
+                let size = u32::try_from(domain.len())
+                    .ok()
+                    .and_then(|d| range.len().checked_pow(d));
+                check_size(size.unwrap_or(usize::MAX), "function set")?;
+                let mut graphs = vec![BTreeMap::new()];
+                for key in &domain {
+                    graphs = graphs
+                        .into_iter()
+                        .flat_map(|g| {
+                            range.iter().map(move |v| {
+                                let mut g = g.clone();
+                                g.insert(key.clone(), v.clone());
+                                g
+                            })
+                        })
+                        .collect();
+                }
+                Ok(Value::set(graphs.into_iter().map(Value::function)))
+            }
+            Expr::Except { func, updates } => {
+                let mut value = self.eval_in(func, ctx)?;
+                for update in updates {
+                    value = self.except(value, &update.path, &update.value, ctx)?;
+                }
+                Ok(value)
+            }
+            Expr::At => match ctx.local("@") {
+                Some(Binding::Value(value)) => Ok(value.clone()),
+                _ => Err(EvalError::Unsupported("@ outside EXCEPT".to_string())),
+            },
+            Expr::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                if self.eval_bool(cond, ctx)? {
+                    self.eval_in(then, ctx)
+                } else {
+                    self.eval_in(otherwise, ctx)
+                }
+            }
+            Expr::Case { arms, other } => {
+                for (guard, value) in arms {
+                    if self.eval_bool(guard, ctx)? {
+                        return self.eval_in(value, ctx);
+                    }
+                }
+                match other {
+                    Some(value) => self.eval_in(value, ctx),
+                    None => Err(EvalError::NoMatch(expr.to_string())),
+                }
+            }
+            Expr::Let { defs, body } => {
+                let mark = ctx.locals.len();
+                for def in defs {
+                    ctx.locals.push((&def.name, Binding::Def(def)));
+                }
+                let value = self.eval_in(body, ctx);
+                ctx.locals.truncate(mark);
+                value
+            }
+            Expr::BoxAction { .. } | Expr::AngleAction { .. } | Expr::Fairness { .. } => {
+                Err(EvalError::Unsupported(format!("temporal formula {}", expr)))
+            }
+        }
+    }
+
+    /// Evaluate `expr'`: the expression in the next state.
+    fn eval_primed<'e>(&'e self, expr: &'e Expr, ctx: &mut Ctx<'e>) -> Result<Value, EvalError> {
+        if ctx.primed {
+            return Err(EvalError::Unsupported(format!(
+                "double priming of {}",
+                expr
+            )));
+        }
+        if self.next.is_none() {
+            return Err(EvalError::Unsupported(format!(
+                "({})' refers to the next state, which is not bound",
+                expr
+            )));
+        }
+        ctx.primed = true;
+        let value = self.eval_in(expr, ctx);
+        ctx.primed = false;
+        value
+    }
+
+    /// Whether `name` has a binding that shadows a built-in.
+    fn is_defined(&self, name: &str, ctx: &Ctx<'_>) -> bool {
+        ctx.local(name).is_some()
+            || self.module.is_variable(name)
+            || self.constants.contains_key(name)
+            || self.module.definition(name).is_some()
+    }
+
+    fn lookup<'e>(&'e self, name: &'e str, ctx: &mut Ctx<'e>) -> Result<Value, EvalError> {
+        match ctx.local(name) {
+            Some(Binding::Value(value)) => return Ok(value.clone()),
+            Some(Binding::Def(def)) => {
+                let def: &'e Definition = def;
+                return self.call_def(def, Vec::new(), ctx, false);
+            }
+            None => {}
+        }
+
+        let state = if ctx.primed {
+            self.next.as_ref()
+        } else {
+            Some(&self.state)
+        };
+        if let Some(value) = state.and_then(|s| s.get(name)) {
+            return Ok(value.clone());
+        }
+        if self.module.is_variable(name) {
+            let prime = if ctx.primed { "'" } else { "" };
+            return Err(EvalError::Unbound(format!("{}{}", name, prime)));
+        }
+        if let Some(value) = self.constants.get(name) {
+            return Ok(value.clone());
+        }
+        if let Some(def) = self.module.definition(name) {
+            if def.is_function() {
+                return self.function_value(def, ctx);
+            }
+            return self.call_def(def, Vec::new(), ctx, true);
+        }
+
+        match name {
+            "BOOLEAN" => Ok(Value::set([Value::Bool(false), Value::Bool(true)])),
+            "Nat" | "Int" | "STRING" => Err(EvalError::Unsupported(format!(
+                "the infinite set {} can only appear on the right of \\in",
+                name
+            ))),
+            _ if self.model_values => Ok(Value::model(name)),
+            _ => Err(EvalError::Unbound(name.to_string())),
+        }
+    }
+
+    /// Call an operator definition. Module-level operators do not see the
+    /// caller's locals; LET-defined ones do.
+    fn call_def<'e>(
+        &'e self,
+        def: &'e Definition,
+        args: Vec<Value>,
+        ctx: &mut Ctx<'e>,
+        module_level: bool,
+    ) -> Result<Value, EvalError> {
+        if def.is_function() {
+            return Err(EvalError::Type {
+                expected: "operator",
+                found: format!("function {}", def.name),
+            });
+        }
+        if def.params.len() != args.len() {
+            return Err(EvalError::Arity {
+                name: def.name.clone(),
+                expected: def.params.len(),
+                found: args.len(),
+            });
+        }
+        if let Some(param) = def.params.iter().find(|p| p.arity > 0) {
+            return Err(EvalError::Unsupported(format!(
+                "higher-order parameter {} of {}",
+                param.name, def.name
+            )));
+        }
+        if ctx.depth >= CALL_DEPTH_MAX {
+            return Err(EvalError::LimitExceeded(format!(
+                "call depth {} reached in {}",
+                CALL_DEPTH_MAX, def.name
+            )));
+        }
+
+        let (mark, base) = (ctx.locals.len(), ctx.base);
+        if module_level {
+            ctx.base = mark;
+        }
+        for (param, arg) in def.params.iter().zip(args) {
+            ctx.push(&param.name, arg);
+        }
+        ctx.depth += 1;
+        let value = self.eval_in(&def.body, ctx);
+        ctx.depth -= 1;
+        ctx.locals.truncate(mark);
+        ctx.base = base;
+        value
+    }
+
+    /// The whole function defined by `f[x \in S] == e`.
+    fn function_value<'e>(
+        &'e self,
+        def: &'e Definition,
+        ctx: &mut Ctx<'e>,
+    ) -> Result<Value, EvalError> {
+        let slots = slots(&def.function_bounds)?;
+        let mut graph = BTreeMap::new();
+        let (mark, base) = (ctx.locals.len(), ctx.base);
+        ctx.base = mark;
+        let result = self.for_each(&slots, ctx, &mut Vec::new(), &mut |ctx, elems| {
+            let key = match elems {
+                [single] => single.clone(),
+                many => Value::Seq(many.to_vec()),
+            };
+            graph.insert(key, self.eval_in(&def.body, ctx)?);
+            Ok(true)
+        });
+        ctx.base = base;
+        result?;
+        Ok(Value::function(graph))
+    }
+
+    fn apply_op<'e>(
+        &'e self,
+        op: &'e str,
+        args: Vec<Value>,
+        ctx: &mut Ctx<'e>,
+    ) -> Result<Value, EvalError> {
+        match ctx.local(op) {
+            Some(Binding::Def(def)) => {
+                let def: &'e Definition = def;
+                return self.call_def(def, args, ctx, false);
+            }
+            Some(Binding::Value(value)) => return Err(type_error("operator", value)),
+            None => {}
+        }
+        if let Some(def) = self.module.definition(op) {
+            return self.call_def(def, args, ctx, true);
+        }
+        builtin(op, args)
+    }
+
+    fn fn_apply<'e>(
+        &'e self,
+        func: &'e Expr,
+        args: &'e [Expr],
+        ctx: &mut Ctx<'e>,
+    ) -> Result<Value, EvalError> {
+        let args = args
+            .iter()
+            .map(|a| self.eval_in(a, ctx))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Apply recursive function definitions point-wise
+        if let Expr::Ident(name) = func {
+            let def = self.module.definition(name).filter(|d| d.is_function());
+            if let (Some(def), None) = (def, ctx.local(name)) {
+                return self.apply_function_def(def, args, ctx);
+            }
+        }
+
+        let value = self.eval_in(func, ctx)?;
+        if !value.is_function() {
+            return Err(type_error("function", &value));
+        }
+        let arg = match <[Value; 1]>::try_from(args) {
+            Ok([single]) => single,
+            Err(args) => Value::Seq(args),
+        };
+        value
+            .apply(&arg)
+            .cloned()
+            .ok_or_else(|| EvalError::NotInDomain {
+                function: func.to_string(),
+                arg: arg.to_string(),
+            })
+    }
+
+    fn apply_function_def<'e>(
+        &'e self,
+        def: &'e Definition,
+        mut args: Vec<Value>,
+        ctx: &mut Ctx<'e>,
+    ) -> Result<Value, EvalError> {
+        let names: Vec<&'e String> = def.function_bounds.iter().flat_map(|b| &b.names).collect();
+        if args.len() == 1 && names.len() > 1 {
+            args = to_seq(args.remove(0))?;
+        }
+        if args.len() != names.len() {
+            return Err(EvalError::Arity {
+                name: def.name.clone(),
+                expected: names.len(),
+                found: args.len(),
+            });
+        }
+        if ctx.depth >= CALL_DEPTH_MAX {
+            return Err(EvalError::LimitExceeded(format!(
+                "call depth {} reached in {}",
+                CALL_DEPTH_MAX, def.name
+            )));
+        }
+
+        let (mark, base) = (ctx.locals.len(), ctx.base);
+        ctx.base = mark;
+        for (name, arg) in names.into_iter().zip(args) {
+            ctx.push(name, arg);
+        }
+        ctx.depth += 1;
+        let value = self.eval_in(&def.body, ctx);
+        ctx.depth -= 1;
+        ctx.locals.truncate(mark);
+        ctx.base = base;
+        value
+    }
+
+    /// Run `body` for every binding of `slots`, stopping when it returns
+    /// `false`. `elems` holds the element bound to each slot.
+    fn for_each<'e>(
+        &'e self,
+        slots: &[Slot<'e>],
+        ctx: &mut Ctx<'e>,
+        elems: &mut Vec<Value>,
+        body: &mut Visitor<'_, 'e>,
+    ) -> Result<bool, EvalError> {
+        let Some((slot, rest)) = slots.split_first() else {
+            return body(ctx, elems);
+        };
+
+        for elem in self.eval_set(slot.set, ctx)? {
+            let mark = ctx.locals.len();
+            if slot.tuple {
+                let items = match &elem {
+                    Value::Seq(items) if items.len() == slot.names.len() => items.clone(),
+                    other => return Err(type_error("tuple", other)),
+                };
+                for (name, item) in slot.names.iter().zip(items) {
+                    ctx.push(name, item);
+                }
+            } else {
+                ctx.push(&slot.names[0], elem.clone());
+            }
+            elems.push(elem);
+            let more = self.for_each(rest, ctx, elems, body);
+            elems.pop();
+            ctx.locals.truncate(mark);
+            if !more? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Apply one `![a][b].c = e` update.
+    fn except<'e>(
+        &'e self,
+        value: Value,
+        path: &'e [ExceptKey],
+        update: &'e Expr,
+        ctx: &mut Ctx<'e>,
+    ) -> Result<Value, EvalError> {
+        let Some((key, rest)) = path.split_first() else {
+            return Ok(value);
+        };
+        let key = match key {
+            ExceptKey::Field(name) => Value::str(name),
+            ExceptKey::Index(args) => {
+                let mut args = args
+                    .iter()
+                    .map(|a| self.eval_in(a, ctx))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if args.len() == 1 {
+                    args.remove(0)
+                } else {
+                    Value::Seq(args)
+                }
+            }
+        };
+
+        let mut graph = value
+            .to_graph()
+            .ok_or_else(|| type_error("function", &value))?;
+        let old = graph.remove(&key).ok_or_else(|| EvalError::NotInDomain {
+            function: describe(&value),
+            arg: key.to_string(),
+        })?;
+        let new = if rest.is_empty() {
+            ctx.push("@", old);
+            let new = self.eval_in(update, ctx);
+            ctx.locals.pop();
+            new?
+        } else {
+            self.except(old, rest, update, ctx)?
+        };
+        graph.insert(key, new);
+        Ok(Value::function(graph))
+    }
+
+    /// Membership, without building infinite sets.
+    fn member<'e>(
+        &'e self,
+        value: &Value,
+        set: &'e Expr,
+        ctx: &mut Ctx<'e>,
+    ) -> Result<bool, EvalError> {
+        match set {
+            Expr::Ident(name) if !self.is_defined(name, ctx) => match name.as_str() {
+                "Nat" => return Ok(matches!(value, Value::Int(n) if *n >= 0)),
+                "Int" => return Ok(matches!(value, Value::Int(_))),
+                "STRING" => return Ok(matches!(value, Value::Str(_))),
+                "BOOLEAN" => return Ok(matches!(value, Value::Bool(_))),
+                _ => {}
+            },
+            Expr::Apply { op, args } if op == "Seq" && !self.is_defined(op, ctx) => {
+                let [elem_set] = args.as_slice() else {
+                    return Err(EvalError::Arity {
+                        name: op.clone(),
+                        expected: 1,
+                        found: args.len(),
+                    });
+                };
+                let Value::Seq(items) = value else {
+                    return Ok(false);
+                };
+                for item in items {
+                    if !self.member(item, elem_set, ctx)? {
+                        return Ok(false);
+                    }
+                }
+                return Ok(true);
+            }
+            Expr::FnSet { domain, range } => {
+                let Some(graph) = value.to_graph() else {
+                    return Ok(false);
+                };
+                let domain = self.eval_set(domain, ctx)?;
+                if graph.keys().ne(domain.iter()) {
+                    return Ok(false);
+                }
+                for v in graph.values() {
+                    if !self.member(v, range, ctx)? {
+                        return Ok(false);
+                    }
+                }
+                return Ok(true);
+            }
+            Expr::RecordSet(fields) => {
+                let Value::Record(record) = value else {
+                    return Ok(false);
+                };
+                if record.len() != fields.len() {
+                    return Ok(false);
+                }
+                for (name, field_set) in fields {
+                    match record.get(name) {
+                        Some(v) if self.member(v, field_set, ctx)? => {}
+                        _ => return Ok(false),
+                    }
+                }
+                return Ok(true);
+            }
+            Expr::Unary {
+                op: UnaryOp::Subset,
+                expr,
+            } => {
+                let Value::Set(items) = value else {
+                    return Ok(false);
+                };
+                for item in items {
+                    if !self.member(item, expr, ctx)? {
+                        return Ok(false);
+                    }
+                }
+                return Ok(true);
+            }
+            _ => {}
+        }
+        Ok(self.eval_set(set, ctx)?.contains(value))
+    }
+
+    fn unary<'e>(
+        &'e self,
+        op: UnaryOp,
+        operand: &'e Expr,
+        ctx: &mut Ctx<'e>,
+    ) -> Result<Value, EvalError> {
+        match op {
+            UnaryOp::Not => Ok(Value::Bool(!self.eval_bool(operand, ctx)?)),
+            UnaryOp::Neg => self
+                .eval_int(operand, ctx)?
+                .checked_neg()
+                .map(Value::Int)
+                .ok_or_else(|| EvalError::Arithmetic("integer overflow in `-`".to_string())),
+            UnaryOp::Domain => {
+                let value = self.eval_in(operand, ctx)?;
+                value
+                    .domain()
+                    .map(Value::Set)
+                    .ok_or_else(|| type_error("function", &value))
+            }
+            UnaryOp::Subset => {
+                let set: Vec<Value> = self.eval_set(operand, ctx)?.into_iter().collect();
+                if set.len() >= usize::BITS as usize {
+                    check_size(usize::MAX, "SUBSET")?;
+                }
+                check_size(1 << set.len(), "SUBSET")?;
+                let subsets = (0..1usize << set.len()).map(|mask| {
+                    Value::set(
+                        set.iter()
+                            .enumerate()
+                            .filter(|(i, _)| mask & (1 << i) != 0)
+                            .map(|(_, v)| v.clone()),
+                    )
+                });
+                Ok(Value::set(subsets))
+            }
+            UnaryOp::Union => {
+                let mut union = BTreeSet::new();
+                for inner in self.eval_set(operand, ctx)? {
+                    union.extend(to_set(inner)?);
+                }
+                Ok(Value::Set(union))
+            }
+            UnaryOp::Unchanged => {
+                let now = self.eval_in(operand, ctx)?;
+                let next = self.eval_primed(operand, ctx)?;
+                Ok(Value::Bool(now == next))
+            }
+            UnaryOp::Enabled | UnaryOp::Always | UnaryOp::Eventually => Err(
+                EvalError::Unsupported(format!("{} (temporal or ENABLED)", op.symbol())),
+            ),
+        }
+    }
+
+    fn binary<'e>(
+        &'e self,
+        op: BinOp,
+        lhs: &'e Expr,
+        rhs: &'e Expr,
+        ctx: &mut Ctx<'e>,
+    ) -> Result<Value, EvalError> {
+        match op {
+            BinOp::Implies => Ok(Value::Bool(
+                !self.eval_bool(lhs, ctx)? || self.eval_bool(rhs, ctx)?,
+            )),
+            BinOp::Equiv => Ok(Value::Bool(
+                self.eval_bool(lhs, ctx)? == self.eval_bool(rhs, ctx)?,
+            )),
+            BinOp::LeadsTo => Err(EvalError::Unsupported("~> (temporal)".to_string())),
+            BinOp::In | BinOp::NotIn => {
+                let value = self.eval_in(lhs, ctx)?;
+                let member = self.member(&value, rhs, ctx)?;
+                Ok(Value::Bool(member == (op == BinOp::In)))
+            }
+            BinOp::SubsetEq => {
+                for item in self.eval_set(lhs, ctx)? {
+                    if !self.member(&item, rhs, ctx)? {
+                        return Ok(Value::Bool(false));
+                    }
+                }
+                Ok(Value::Bool(true))
+            }
+            BinOp::Cartesian => {
+                let mut factors = vec![rhs];
+                let mut left = lhs;
+                while let Expr::Binary {
+                    op: BinOp::Cartesian,
+                    lhs,
+                    rhs,
+                } = left
+                {
+                    factors.push(rhs);
+                    left = lhs;
+                }
+                factors.push(left);
+                factors.reverse();
+
+                let mut tuples = vec![Vec::new()];
+                for factor in factors {
+                    let set = self.eval_set(factor, ctx)?;
+                    check_size(tuples.len() * set.len(), "cartesian product")?;
+                    tuples = tuples
+                        .into_iter()
+                        .flat_map(|t| {
+                            set.iter().map(move |v| {
+                                let mut t = t.clone();
+                                t.push(v.clone());
+                                t
+                            })
+                        })
+                        .collect();
+                }
+                Ok(Value::set(tuples.into_iter().map(Value::Seq)))
+            }
+            _ => {
+                let a = self.eval_in(lhs, ctx)?;
+                let b = self.eval_in(rhs, ctx)?;
+                strict_binary(op, a, b)
+            }
+        }
+    }
+}
+
+/// Binary operators that evaluate both operands.
+fn strict_binary(op: BinOp, a: Value, b: Value) -> Result<Value, EvalError> {
+    match op {
+        BinOp::Eq => Ok(Value::Bool(a == b)),
+        BinOp::Neq => Ok(Value::Bool(a != b)),
+        BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge => {
+            let (a, b) = (to_int(a)?, to_int(b)?);
+            Ok(Value::Bool(match op {
+                BinOp::Lt => a < b,
+                BinOp::Gt => a > b,
+                BinOp::Le => a <= b,
+                _ => a >= b,
+            }))
+        }
+        BinOp::ProperSubset | BinOp::SupsetEq | BinOp::ProperSupset => {
+            let (a, b) = (to_set(a)?, to_set(b)?);
+            Ok(Value::Bool(match op {
+                BinOp::ProperSubset => a.is_subset(&b) && a != b,
+                BinOp::SupsetEq => a.is_superset(&b),
+                _ => a.is_superset(&b) && a != b,
+            }))
+        }
+        BinOp::Cup => Ok(Value::Set(&to_set(a)? | &to_set(b)?)),
+        BinOp::Cap => Ok(Value::Set(&to_set(a)? & &to_set(b)?)),
+        BinOp::SetMinus => Ok(Value::Set(&to_set(a)? - &to_set(b)?)),
+        BinOp::Range => {
+            let (lo, hi) = (to_int(a)?, to_int(b)?);
+            if hi >= lo {
+                let size = (hi as i128 - lo as i128 + 1) as u128;
+                check_size(usize::try_from(size).unwrap_or(usize::MAX), "range")?;
+            }
+            Ok(Value::set((lo..=hi).map(Value::Int)))
+        }
+        BinOp::Plus | BinOp::Minus | BinOp::Times | BinOp::Exp => {
+            let (a, b) = (to_int(a)?, to_int(b)?);
+            let result = match op {
+                BinOp::Plus => a.checked_add(b),
+                BinOp::Minus => a.checked_sub(b),
+                BinOp::Times => a.checked_mul(b),
+                _ => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+            };
+            result.map(Value::Int).ok_or_else(|| overflow(op))
+        }
+        BinOp::Div | BinOp::Mod => {
+            let (a, b) = (to_int(a)?, to_int(b)?);
+            if b == 0 || (op == BinOp::Mod && b < 0) {
+                return Err(EvalError::Arithmetic(format!(
+                    "{} {} {}",
+                    a,
+                    op.symbol(),
+                    b
+                )));
+            }
+            let result = if op == BinOp::Div {
+                a.checked_div_euclid(b)
+            } else {
+                a.checked_rem_euclid(b)
+            };
+            result.map(Value::Int).ok_or_else(|| overflow(op))
+        }
+        BinOp::Concat => match (a, b) {
+            (Value::Str(a), Value::Str(b)) => Ok(Value::Str(a + &b)),
+            (a, b) => {
+                let mut items = to_seq(a)?;
+                items.extend(to_seq(b)?);
+                Ok(Value::Seq(items))
+            }
+        },
+        BinOp::Merge => {
+            let mut graph = b.to_graph().ok_or_else(|| type_error("function", &b))?;
+            graph.extend(a.to_graph().ok_or_else(|| type_error("function", &a))?);
+            Ok(Value::function(graph))
+        }
+        BinOp::MapsTo => Ok(Value::function(BTreeMap::from([(a, b)]))),
+        BinOp::Implies
+        | BinOp::Equiv
+        | BinOp::LeadsTo
+        | BinOp::In
+        | BinOp::NotIn
+        | BinOp::SubsetEq
+        | BinOp::Cartesian => unreachable!("{} is evaluated lazily", op.symbol()),
+    }
+}
+
+/// Operators from the standard modules (Naturals, Sequences, FiniteSets, TLC).
+fn builtin(op: &str, args: Vec<Value>) -> Result<Value, EvalError> {
+    let arity = match op {
+        "Len" | "Head" | "Tail" | "Cardinality" | "IsFiniteSet" | "ToString" | "PrintT" => 1,
+        "Append" | "Print" | "Assert" => 2,
+        "SubSeq" => 3,
+        "Seq" => {
+            return Err(EvalError::Unsupported(
+                "Seq(S) is infinite and can only appear on the right of \\in".to_string(),
+            ))
+        }
+        "SelectSeq" | "Permutations" | "SortSeq" | "TLCGet" | "TLCSet" | "RandomElement" => {
+            return Err(EvalError::Unsupported(format!("operator {}", op)))
+        }
+        _ => return Err(EvalError::Unbound(op.to_string())),
+    };
+    if args.len() != arity {
+        return Err(EvalError::Arity {
+            name: op.to_string(),
+            expected: arity,
+            found: args.len(),
+        });
+    }
+
+    let mut args = args.into_iter();
+    let mut next = || args.next().expect("arity checked");
+    match op {
+        "Len" => match next() {
+            Value::Str(s) => Ok(Value::Int(s.chars().count() as i64)),
+            other => Ok(Value::Int(to_seq(other)?.len() as i64)),
+        },
+        "Head" | "Tail" => {
+            let mut items = to_seq(next())?;
+            if items.is_empty() {
+                return Err(EvalError::NotInDomain {
+                    function: format!("{}(<<>>)", op),
+                    arg: "1".to_string(),
+                });
+            }
+            if op == "Head" {
+                Ok(items.swap_remove(0))
+            } else {
+                Ok(Value::Seq(items.split_off(1)))
+            }
+        }
+        "Append" => {
+            let mut items = to_seq(next())?;
+            items.push(next());
+            Ok(Value::Seq(items))
+        }
+        "SubSeq" => {
+            let items = to_seq(next())?;
+            let (m, n) = (to_int(next())?, to_int(next())?);
+            if m > n {
+                return Ok(Value::Seq(Vec::new()));
+            }
+            if m < 1 || n as usize > items.len() {
+                return Err(EvalError::NotInDomain {
+                    function: "SubSeq".to_string(),
+                    arg: format!("{}..{}", m, n),
+                });
+            }
+            Ok(Value::Seq(items[m as usize - 1..n as usize].to_vec()))
+        }
+        "Cardinality" => Ok(Value::Int(to_set(next())?.len() as i64)),
+        "IsFiniteSet" => Ok(Value::Bool(to_set(next()).is_ok())),
+        "ToString" => Ok(Value::Str(next().to_string())),
+        "Print" => {
+            let _out = next();
+            Ok(next())
+        }
+        "PrintT" => Ok(Value::Bool(true)),
+        "Assert" => {
+            let cond = to_bool(next())?;
+            let message = next();
+            if cond {
+                Ok(Value::Bool(true))
+            } else {
+                Err(EvalError::AssertionFailed(message.to_string()))
+            }
+        }
+        _ => unreachable!("arity table covers {}", op),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tla_spec::parse_module;
+
+    fn eval(src: &str) -> Result<Value, EvalError> {
+        let module = parse_module(&format!("---- MODULE m ----\nX == {}\n====", src)).unwrap();
+        Evaluator::new(&module).call("X", Vec::new())
+    }
+
+    fn int_set(items: &[i64]) -> Value {
+        Value::set(items.iter().map(|&n| Value::Int(n)))
+    }
+
+    #[test]
+    fn test_sets_and_quantifiers() {
+        assert_eq!(eval("{x * 2 : x \\in 1..3}").unwrap(), int_set(&[2, 4, 6]));
+        assert_eq!(
+            eval("{s \\in SUBSET {1, 2} : Cardinality(s) = 1}").unwrap(),
+            Value::set([int_set(&[1]), int_set(&[2])])
+        );
+        assert_eq!(
+            eval("\\A x, y \\in 1..3 : x + y <= 6").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(eval("\\E x \\in {} : TRUE").unwrap(), Value::Bool(false));
+        assert_eq!(
+            eval("CHOOSE x \\in 1..5 : x % 3 = 0").unwrap(),
+            Value::Int(3)
+        );
+        assert_eq!(
+            eval("UNION {{1}, {2, 3}} \\ {2}").unwrap(),
+            int_set(&[1, 3])
+        );
+        assert_eq!(
+            eval("{1, 2} \\X {3}").unwrap(),
+            Value::set([
+                Value::seq([Value::Int(1), Value::Int(3)]),
+                Value::seq([Value::Int(2), Value::Int(3)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_functions_records_and_sequences() {
+        assert_eq!(
+            eval("[x \\in 1..2 |-> x * x]").unwrap(),
+            Value::seq([Value::Int(1), Value::Int(4)])
+        );
+        assert_eq!(
+            eval("[[a |-> 1, b |-> <<>>] EXCEPT !.a = @ + 1, !.b = Append(@, 7)]").unwrap(),
+            Value::record([("a", Value::Int(2)), ("b", Value::seq([Value::Int(7)]))])
+        );
+        assert_eq!(eval("[a |-> 1] = (\"a\" :> 1)").unwrap(), Value::Bool(true));
+        assert_eq!(
+            eval("SubSeq(<<1, 2, 3>> \\o Tail(<<4, 5>>), 2, 4)").unwrap(),
+            Value::seq([Value::Int(2), Value::Int(3), Value::Int(5)])
+        );
+        assert_eq!(
+            eval("DOMAIN ((1 :> 2) @@ (3 :> 4))").unwrap(),
+            int_set(&[1, 3])
+        );
+        assert_eq!(
+            eval("[r \\in [a : {1, 2}] |-> r.a][[a |-> 2]]").unwrap(),
+            Value::Int(2)
+        );
+    }
+
+    #[test]
+    fn test_infinite_sets_in_membership_only() {
+        assert_eq!(
+            eval("<<1, 2>> \\in Seq(Nat) /\\ [a |-> -1] \\in [a : Int]").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            eval("(1 :> {2}) \\in [{1} -> SUBSET Nat]").unwrap(),
+            Value::Bool(true)
+        );
+        assert!(matches!(
+            eval("\\A n \\in Nat : n >= 0"),
+            Err(EvalError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(eval("1 + TRUE"), Err(EvalError::Type { .. })));
+        assert!(matches!(
+            eval("<<1>>[2]"),
+            Err(EvalError::NotInDomain { .. })
+        ));
+        assert!(matches!(eval("[]TRUE"), Err(EvalError::Unsupported(_))));
+        assert!(matches!(eval("y"), Err(EvalError::Unbound(name)) if name == "y"));
+        assert!(matches!(eval("7 \\div 0"), Err(EvalError::Arithmetic(_))));
+    }
+
+    #[test]
+    fn test_recursion_let_and_actions() {
+        let module = parse_module(
+            "---- MODULE m ----\n\
+             VARIABLE x\n\
+             RECURSIVE Sum(_)\n\
+             Sum(s) == IF s = <<>> THEN 0 ELSE Head(s) + Sum(Tail(s))\n\
+             fact[n \\in 0..5] == IF n = 0 THEN 1 ELSE n * fact[n - 1]\n\
+             Total == LET double(v) == 2 * v IN double(Sum(x)) + fact[3]\n\
+             Grow == x' = Append(x, 1) /\\ UNCHANGED <<>>\n\
+             ====",
+        )
+        .unwrap();
+        let mut evaluator = Evaluator::new(&module);
+        evaluator.set_variable("x", Value::seq([Value::Int(1), Value::Int(2)]));
+        assert_eq!(evaluator.call("Total", Vec::new()).unwrap(), Value::Int(12));
+        assert!(matches!(
+            evaluator.check("Grow"),
+            Err(EvalError::Unsupported(_))
+        ));
+
+        evaluator.set_next_variable(
+            "x",
+            Value::seq([Value::Int(1), Value::Int(2), Value::Int(1)]),
+        );
+        assert!(evaluator.check("Grow").unwrap());
+    }
+
+    #[test]
+    fn test_parse_value_round_trip() {
+        let text = "[t1 |-> {1, 2}, t2 |-> <<\"a\", NULL>>]";
+        let value = parse_value(text).unwrap();
+        assert_eq!(value.apply(&Value::str("t1")), Some(&int_set(&[1, 2])));
+        assert_eq!(parse_value(&value.to_string()).unwrap(), value);
+        assert_eq!(
+            parse_value("(1 :> 2 @@ 3 :> 4)").unwrap().to_string(),
+            "(1 :> 2 @@ 3 :> 4)"
+        );
+    }
+
+    #[test]
+    fn test_spec_invariants_from_snapshot() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/distributed/raft_consensus.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let servers = Value::set([Value::model("s1"), Value::model("s2")]);
+        let mut evaluator = Evaluator::new(&module)
+            .with_constant("Servers", servers)
+            .with_model_constants();
+
+        let snapshot = StateSnapshot {
+            step: 1,
+            description: "after replication".to_string(),
+            variables: vec![
+                (
+                    "log".to_string(),
+                    "(s1 :> <<<<1, 10>>, <<2, 20>>>> @@ s2 :> <<<<1, 10>>>>)".to_string(),
+                ),
+                ("note".to_string(), "not TLA+ {".to_string()),
+            ],
+        };
+        evaluator.load_state(&snapshot).unwrap();
+        assert!(evaluator.check("LogMatching").unwrap());
+
+        let diverged = StateSnapshot {
+            step: 2,
+            description: "conflicting entry".to_string(),
+            variables: vec![(
+                "log".to_string(),
+                "(s1 :> <<<<1, 10>>, <<2, 20>>>> @@ s2 :> <<<<1, 11>>, <<2, 20>>>>)".to_string(),
+            )],
+        };
+        evaluator.load_state(&diverged).unwrap();
+        assert!(!evaluator.check("LogMatching").unwrap());
+    }
+}
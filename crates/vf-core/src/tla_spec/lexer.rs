@@ -0,0 +1,425 @@
+//! Tokenizer for TLA+ modules.
+//!
+//! Produces tokens with 1-based line and column positions. Columns matter:
+//! TLA+ conjunction/disjunction lists are aligned by column, so the parser
+//! uses them to decide where a bulleted item ends.
+
+use super::ParseError;
+
+/// Reserved words.
+const KEYWORDS: &[&str] = &[
+    "ASSUME",
+    "ASSUMPTION",
+    "AXIOM",
+    "CASE",
+    "CHOOSE",
+    "CONSTANT",
+    "CONSTANTS",
+    "DOMAIN",
+    "ELSE",
+    "ENABLED",
+    "EXCEPT",
+    "EXTENDS",
+    "FALSE",
+    "IF",
+    "IN",
+    "INSTANCE",
+    "LAMBDA",
+    "LET",
+    "LOCAL",
+    "MODULE",
+    "OTHER",
+    "RECURSIVE",
+    "SUBSET",
+    "THEN",
+    "THEOREM",
+    "TRUE",
+    "UNCHANGED",
+    "UNION",
+    "VARIABLE",
+    "VARIABLES",
+    "WITH",
+];
+
+/// Backslash operators and their normalized spelling.
+const BACKSLASH_WORDS: &[(&str, &str)] = &[
+    ("A", "\\A"),
+    ("forall", "\\A"),
+    ("E", "\\E"),
+    ("exists", "\\E"),
+    ("in", "\\in"),
+    ("notin", "\\notin"),
+    ("cup", "\\cup"),
+    ("union", "\\cup"),
+    ("cap", "\\cap"),
+    ("intersect", "\\cap"),
+    ("subseteq", "\\subseteq"),
+    ("subset", "\\subset"),
+    ("supseteq", "\\supseteq"),
+    ("supset", "\\supset"),
+    ("o", "\\o"),
+    ("circ", "\\o"),
+    ("X", "\\X"),
+    ("times", "\\X"),
+    ("div", "\\div"),
+    ("leq", "<="),
+    ("geq", ">="),
+    ("lnot", "~"),
+    ("neg", "~"),
+    ("land", "/\\"),
+    ("lor", "\\/"),
+    ("equiv", "<=>"),
+];
+
+/// Symbols, longest first so prefixes don't shadow longer operators.
+const SYMBOLS: &[&str] = &[
+    "<=>", "|->", ">>_", "=>", "==", "=<", "<=", ">=", "/=", "/\\", "\\/", "->", "<-", "<<", ">>",
+    "<>", "[]", "]_", ":>", "..", "@@", "~>", "::", "#", "=", "<", ">", "+", "-", "*", "/", "%",
+    "^", "~", "'", "(", ")", "[", "]", "{", "}", ",", ":", ".", "!", "@", "|", "\\",
+];
+
+/// A token kind.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Tok {
+    Ident(String),
+    Num(i64),
+    Str(String),
+    /// Reserved word (e.g. "LET")
+    Keyword(&'static str),
+    /// Operator or punctuation in normalized form (e.g. "\\in", "/\\")
+    Sym(&'static str),
+    /// `----` separator line
+    Separator,
+    /// `====` end of module
+    End,
+    Eof,
+}
+
+impl Tok {
+    /// Human-readable form for error messages.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Tok::Ident(name) => format!("identifier `{}`", name),
+            Tok::Num(n) => format!("number {}", n),
+            Tok::Str(s) => format!("string {:?}", s),
+            Tok::Keyword(k) => format!("`{}`", k),
+            Tok::Sym(s) => format!("`{}`", s),
+            Tok::Separator => "`----`".to_string(),
+            Tok::End => "`====`".to_string(),
+            Tok::Eof => "end of input".to_string(),
+        }
+    }
+}
+
+/// A token with its source position.
+#[derive(Debug, Clone)]
+pub(crate) struct Token {
+    pub tok: Tok,
+    pub line: u32,
+    pub column: u32,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: u32,
+    column: u32,
+}
+
+/// Tokenize TLA+ source, stopping after the first `====` line.
+///
+/// `first_line` is the line number of the first character of `src`.
+pub(crate) fn tokenize(src: &str, first_line: u32) -> Result<Vec<Token>, ParseError> {
+    let mut lexer = Lexer {
+        chars: src.chars().collect(),
+        pos: 0,
+        line: first_line,
+        column: 1,
+    };
+    let mut tokens = Vec::new();
+
+    loop {
+        lexer.skip_trivia()?;
+        let (line, column) = (lexer.line, lexer.column);
+        let Some(tok) = lexer.next_tok()? else {
+            tokens.push(Token {
+                tok: Tok::Eof,
+                line,
+                column,
+            });
+            return Ok(tokens);
+        };
+        let end = tok == Tok::End;
+        tokens.push(Token { tok, line, column });
+        if end {
+            tokens.push(Token {
+                tok: Tok::Eof,
+                line: lexer.line,
+                column: lexer.column,
+            });
+            return Ok(tokens);
+        }
+    }
+}
+
+impl Lexer {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c))
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::Syntax {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    /// Skip whitespace, `\*` line comments and nested `(* *)` comments.
+    fn skip_trivia(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek(0) {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('\\') if self.peek(1) == Some('*') => {
+                    while !matches!(self.peek(0), None | Some('\n')) {
+                        self.bump();
+                    }
+                }
+                Some('(') if self.peek(1) == Some('*') => {
+                    let (line, column) = (self.line, self.column);
+                    let mut depth = 0usize;
+                    loop {
+                        if self.starts_with("(*") {
+                            depth += 1;
+                            self.bump();
+                            self.bump();
+                        } else if self.starts_with("*)") {
+                            depth -= 1;
+                            self.bump();
+                            self.bump();
+                            if depth == 0 {
+                                break;
+                            }
+                        } else if self.bump().is_none() {
+                            return Err(ParseError::Syntax {
+                                line,
+                                column,
+                                message: "unterminated comment".to_string(),
+                            });
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn next_tok(&mut self) -> Result<Option<Tok>, ParseError> {
+        let Some(c) = self.peek(0) else {
+            return Ok(None);
+        };
+
+        // Separator and end-of-module lines
+        if c == '-' && self.starts_with("----") {
+            while self.peek(0) == Some('-') {
+                self.bump();
+            }
+            return Ok(Some(Tok::Separator));
+        }
+        if c == '=' && self.starts_with("====") {
+            while self.peek(0) == Some('=') {
+                self.bump();
+            }
+            return Ok(Some(Tok::End));
+        }
+
+        if c.is_ascii_digit() {
+            let mut text = String::new();
+            while let Some(d) = self.peek(0).filter(char::is_ascii_digit) {
+                text.push(d);
+                self.bump();
+            }
+            return text
+                .parse()
+                .map(|n| Some(Tok::Num(n)))
+                .map_err(|_| self.error(format!("number {} is too large", text)));
+        }
+
+        if c.is_ascii_alphabetic() || c == '_' {
+            return Ok(Some(self.word()));
+        }
+
+        if c == '"' {
+            return self.string().map(Some);
+        }
+
+        if c == '\\' && self.peek(1).is_some_and(|c| c.is_ascii_alphabetic()) {
+            let (line, column) = (self.line, self.column);
+            self.bump();
+            let mut word = String::new();
+            while let Some(c) = self.peek(0).filter(char::is_ascii_alphanumeric) {
+                word.push(c);
+                self.bump();
+            }
+            return match BACKSLASH_WORDS.iter().find(|(w, _)| *w == word) {
+                Some((_, sym)) => Ok(Some(Tok::Sym(sym))),
+                None => Err(ParseError::Syntax {
+                    line,
+                    column,
+                    message: format!("unsupported operator `\\{}`", word),
+                }),
+            };
+        }
+
+        for sym in SYMBOLS {
+            if !self.starts_with(sym) {
+                continue;
+            }
+            // `]_` and `>>_` only when a subscript follows
+            if sym.ends_with('_')
+                && !self
+                    .peek(sym.len())
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '<' || c == '_')
+            {
+                continue;
+            }
+            for _ in 0..sym.len() {
+                self.bump();
+            }
+            let normalized = match *sym {
+                "=<" => "<=",
+                "#" => "/=",
+                other => other,
+            };
+            return Ok(Some(Tok::Sym(normalized)));
+        }
+
+        Err(self.error(format!("unexpected character `{}`", c)))
+    }
+
+    /// Identifier, keyword or fairness prefix (`WF_`, `SF_`).
+    fn word(&mut self) -> Tok {
+        if self.starts_with("WF_") || self.starts_with("SF_") {
+            let sym = if self.peek(0) == Some('W') {
+                "WF_"
+            } else {
+                "SF_"
+            };
+            for _ in 0..3 {
+                self.bump();
+            }
+            return Tok::Sym(sym);
+        }
+
+        let mut word = String::new();
+        while let Some(c) = self
+            .peek(0)
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        {
+            word.push(c);
+            self.bump();
+        }
+        match KEYWORDS.iter().find(|k| **k == word) {
+            Some(keyword) => Tok::Keyword(keyword),
+            None => Tok::Ident(word),
+        }
+    }
+
+    fn string(&mut self) -> Result<Tok, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(Tok::Str(text)),
+                Some('\\') => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(c) => text.push(c),
+                    None => break,
+                },
+                Some('\n') | None => break,
+                Some(c) => text.push(c),
+            }
+        }
+        Err(ParseError::Syntax {
+            line,
+            column,
+            message: "unterminated string".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toks(src: &str) -> Vec<Tok> {
+        tokenize(src, 1)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.tok)
+            .collect()
+    }
+
+    #[test]
+    fn test_operators_and_comments() {
+        let t = toks("x' = x \\cup {1} \\* comment\n(* block (* nested *) *) /\\ y # z");
+        assert_eq!(
+            t,
+            vec![
+                Tok::Ident("x".into()),
+                Tok::Sym("'"),
+                Tok::Sym("="),
+                Tok::Ident("x".into()),
+                Tok::Sym("\\cup"),
+                Tok::Sym("{"),
+                Tok::Num(1),
+                Tok::Sym("}"),
+                Tok::Sym("/\\"),
+                Tok::Ident("y".into()),
+                Tok::Sym("/="),
+                Tok::Ident("z".into()),
+                Tok::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subscripts_and_fairness() {
+        let t = toks("[][Next]_vars /\\ WF_vars(A)");
+        assert_eq!(t[0], Tok::Sym("[]"));
+        assert_eq!(t[3], Tok::Sym("]_"));
+        assert_eq!(t[6], Tok::Sym("WF_"));
+        assert_eq!(t[7], Tok::Ident("vars".into()));
+    }
+
+    #[test]
+    fn test_columns() {
+        let tokens = tokenize("A ==\n    /\\ x\n    /\\ y", 10).unwrap();
+        let bullets: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.tok == Tok::Sym("/\\"))
+            .map(|t| (t.line, t.column))
+            .collect();
+        assert_eq!(bullets, vec![(11, 5), (12, 5)]);
+    }
+}
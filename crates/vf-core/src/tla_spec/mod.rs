@@ -1,11 +1,22 @@
-//! Basic TLA+ specification parser.
+//! TLA+ specification parser.
 //!
-//! Extracts invariants, line numbers, and evaluator mappings from TLA+ specs.
-//! This is a simple parser for our specific format, not a full TLA+ parser.
+//! Extracts invariants, line numbers, and evaluator mappings from TLA+ specs,
+//! parses the module itself into an AST (see [`ast`] and [`parse_module`]),
+//! and evaluates its operators over concrete states (see [`Evaluator`]).
+
+pub mod ast;
+pub mod eval;
+mod lexer;
+mod parser;
+pub mod value;
 
 use std::collections::HashMap;
 use std::path::Path;
 
+pub use eval::{parse_value, EvalError, Evaluator};
+pub use parser::{parse_expr, parse_module};
+pub use value::Value;
+
 /// An invariant extracted from a TLA+ spec.
 #[derive(Debug, Clone)]
 pub struct TlaInvariant {
@@ -36,6 +47,9 @@ pub struct TlaSpec {
     pub invariants: Vec<TlaInvariant>,
     /// Evaluator mappings from the header
     pub evaluator_map: HashMap<String, Vec<String>>,
+    /// Parsed module, or `None` if the spec is not valid TLA+
+    /// (call [`parse_module`] for the syntax error)
+    pub module: Option<ast::Module>,
 }
 
 impl TlaSpec {
@@ -49,6 +63,7 @@ impl TlaSpec {
             variables: Vec::new(),
             invariants: Vec::new(),
             evaluator_map: HashMap::new(),
+            module: parse_module(content).ok(),
         };
 
         // Parse module name
@@ -229,6 +244,11 @@ impl TlaSpec {
 pub enum ParseError {
     NoModuleName,
     IoError(String),
+    Syntax {
+        line: u32,
+        column: u32,
+        message: String,
+    },
 }
 
 impl std::fmt::Display for ParseError {
@@ -236,6 +256,11 @@ impl std::fmt::Display for ParseError {
         match self {
             ParseError::NoModuleName => write!(f, "No MODULE declaration found"),
             ParseError::IoError(e) => write!(f, "IO error: {}", e),
+            ParseError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}:{}: {}", line, column, message),
         }
     }
 }
@@ -384,4 +409,156 @@ NoDuplicates ==
         assert!(formatted.contains("NoLostElements"));
         assert!(formatted.contains("NoDuplicates"));
     }
+
+    #[test]
+    fn test_parse_module_ast() {
+        let spec = TlaSpec::parse(TEST_SPEC).unwrap();
+        let module = spec.module.expect("test spec is valid TLA+");
+        assert_eq!(module.name, "test_stack");
+        assert_eq!(module.variables.len(), 3);
+        assert_eq!(module.definition("NoDuplicates").unwrap().line, 24);
+    }
+
+    #[test]
+    fn test_parse_repository_specs() {
+        let root = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../../specs"));
+        let mut parsed = 0;
+        for dir in ["lockfree", "ssi", "distributed"] {
+            for entry in std::fs::read_dir(root.join(dir)).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().and_then(|e| e.to_str()) != Some("tla") {
+                    continue;
+                }
+                let content = std::fs::read_to_string(&path).unwrap();
+                let module = parse_module(&content)
+                    .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+                assert!(module.init().is_some(), "{}: no Init", path.display());
+                assert!(module.next().is_some(), "{}: no Next", path.display());
+                let behavior = module
+                    .behavior("Spec")
+                    .unwrap_or_else(|| panic!("{}: no Spec behavior", path.display()));
+                assert_eq!(behavior.init, ast::Expr::Ident("Init".to_string()));
+
+                // EVALUATOR MAPPING lines point at the definitions
+                let spec = TlaSpec::parse(&content).unwrap();
+                for name in spec.evaluator_map.keys() {
+                    let inv = spec.invariants.iter().find(|i| &i.name == name).unwrap();
+                    let definition = module
+                        .definition(name)
+                        .unwrap_or_else(|| panic!("{}: no {}", path.display(), name));
+                    assert_eq!(inv.line, definition.line, "{}: {}", path.display(), name);
+                }
+                parsed += 1;
+            }
+        }
+        assert_eq!(parsed, 17);
+    }
+
+    /// `.rs` files under `dir`, recursively.
+    fn rust_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                rust_files(&path, files);
+            } else if path.extension().and_then(|e| e.to_str()) == Some("rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    /// `(name, line)` references in a module doc: rows of a table with a
+    /// `TLA+ Line` column, and inline "`Name` (line N)" mentions.
+    fn module_doc_line_references(module_doc: &[&str]) -> Vec<(String, u32)> {
+        let mut references = Vec::new();
+        let mut line_column = None;
+        for line in module_doc {
+            let cells: Vec<&str> = line.split('|').map(str::trim).collect();
+            if line.starts_with('|') {
+                if let Some(column) = cells.iter().position(|c| *c == "TLA+ Line") {
+                    line_column = Some(column);
+                } else if let Some(column) = line_column {
+                    if let Ok(number) = cells[column].parse() {
+                        references.push((cells[1].to_string(), number));
+                    }
+                }
+                continue;
+            }
+            line_column = None;
+
+            let mut rest = *line;
+            while let Some(start) = rest.find("` (line ") {
+                let name_start = rest[..start].rfind('`').unwrap();
+                let after = &rest[start + "` (line ".len()..];
+                let end = after.find(')').unwrap();
+                references.push((
+                    rest[name_start + 1..start].to_string(),
+                    after[..end].parse().unwrap(),
+                ));
+                rest = &after[end..];
+            }
+        }
+        references
+    }
+
+    #[test]
+    fn test_doc_line_references_match_specs() {
+        let root = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../.."));
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(root.join("crates")).unwrap() {
+            let src = entry.unwrap().path().join("src");
+            if src.is_dir() {
+                rust_files(&src, &mut files);
+            }
+        }
+
+        let mut checked = 0;
+        for path in files {
+            let content = std::fs::read_to_string(&path).unwrap();
+            let module_doc: Vec<&str> = content
+                .lines()
+                .map_while(|line| line.strip_prefix("//!"))
+                .map(str::trim)
+                .collect();
+            // Also "/// Line N: Name" on the items that check an invariant
+            let mut references = module_doc_line_references(&module_doc);
+            references.extend(content.lines().filter_map(|line| {
+                let (number, name) = line.trim().strip_prefix("/// Line ")?.split_once(": ")?;
+                Some((name.trim().to_string(), number.parse().ok()?))
+            }));
+            if references.is_empty() {
+                continue;
+            }
+
+            // The first spec file the module doc names
+            let spec_name = module_doc
+                .iter()
+                .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == '`'))
+                .find_map(|word| word.strip_suffix(".tla"))
+                .and_then(|word| word.rsplit('/').next())
+                .unwrap_or_else(|| panic!("{}: no spec named", path.display()));
+            let spec_path = ["lockfree", "ssi", "distributed"]
+                .iter()
+                .map(|dir| root.join("specs").join(dir).join(format!("{}.tla", spec_name)))
+                .find(|path| path.exists())
+                .unwrap_or_else(|| panic!("{}: no {}.tla", path.display(), spec_name));
+            let module = parse_module(&std::fs::read_to_string(spec_path).unwrap()).unwrap();
+
+            for (name, line) in references {
+                let definition = module
+                    .definition(&name)
+                    .unwrap_or_else(|| panic!("{}: {} not in {}", path.display(), name, spec_name));
+                assert_eq!(
+                    line,
+                    definition.line,
+                    "{}: {} is defined at {}.tla:{}",
+                    path.display(),
+                    name,
+                    spec_name,
+                    definition.line
+                );
+                checked += 1;
+            }
+        }
+        assert!(checked > 50, "only {} line references found", checked);
+    }
 }
@@ -0,0 +1,961 @@
+//! Recursive-descent parser for TLA+ modules.
+//!
+//! Operator precedence follows the TLA+ book (Lamport, Table 6); prefix
+//! and infix operators are parsed by precedence climbing.
+//!
+//! Bulleted `/\` and `\/` lists are column-sensitive: an item ends at the
+//! first token that is not strictly right of its bullet. The parser keeps
+//! a stack of bullet columns and treats any such token as end of input.
+
+use std::collections::HashSet;
+
+use super::ast::{
+    BinOp, Bound, Declaration, Definition, ExceptKey, ExceptUpdate, Expr, FairnessKind, Module,
+    Param, Quantifier, UnaryOp,
+};
+use super::lexer::{tokenize, Tok, Token};
+use super::ParseError;
+
+/// Returned by `peek` for tokens hidden by the offside rule.
+static BLOCKED: Tok = Tok::Eof;
+
+/// Parse a TLA+ module into an AST.
+///
+/// Text before the `---- MODULE name ----` header and after the closing
+/// `====` line is ignored.
+pub fn parse_module(content: &str) -> Result<Module, ParseError> {
+    let (offset, first_line) = find_header(content).ok_or(ParseError::NoModuleName)?;
+    let tokens = tokenize(&content[offset..], first_line)?;
+    Parser {
+        tokens,
+        pos: 0,
+        offside: Vec::new(),
+        recursive: HashSet::new(),
+    }
+    .module()
+}
+
+/// Parse a single TLA+ expression, such as a value printed by TLC.
+pub fn parse_expr(content: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(content, 1)?,
+        pos: 0,
+        offside: Vec::new(),
+        recursive: HashSet::new(),
+    };
+    let expr = parser.expr()?;
+    if *parser.peek() != Tok::Eof {
+        return Err(parser.unexpected("end of expression"));
+    }
+    Ok(expr)
+}
+
+/// Byte offset and line number of the module header.
+fn find_header(content: &str) -> Option<(usize, u32)> {
+    let mut offset = 0;
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("----")
+            && trimmed
+                .trim_start_matches('-')
+                .trim_start()
+                .starts_with("MODULE")
+        {
+            return Some((offset + line.len() - trimmed.len(), i as u32 + 1));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Binding power of an infix operator token.
+fn infix_power(tok: &Tok) -> Option<(u8, Option<BinOp>)> {
+    let Tok::Sym(sym) = tok else {
+        return None;
+    };
+    let (power, op) = match *sym {
+        "=>" => (1, Some(BinOp::Implies)),
+        "<=>" => (2, Some(BinOp::Equiv)),
+        "~>" => (2, Some(BinOp::LeadsTo)),
+        // Conjunction and disjunction build `Expr::And`/`Expr::Or`
+        "/\\" | "\\/" => (3, None),
+        "=" => (5, Some(BinOp::Eq)),
+        "/=" => (5, Some(BinOp::Neq)),
+        "<" => (5, Some(BinOp::Lt)),
+        ">" => (5, Some(BinOp::Gt)),
+        "<=" => (5, Some(BinOp::Le)),
+        ">=" => (5, Some(BinOp::Ge)),
+        "\\in" => (5, Some(BinOp::In)),
+        "\\notin" => (5, Some(BinOp::NotIn)),
+        "\\subseteq" => (5, Some(BinOp::SubsetEq)),
+        "\\subset" => (5, Some(BinOp::ProperSubset)),
+        "\\supseteq" => (5, Some(BinOp::SupsetEq)),
+        "\\supset" => (5, Some(BinOp::ProperSupset)),
+        "@@" => (6, Some(BinOp::Merge)),
+        ":>" => (7, Some(BinOp::MapsTo)),
+        "\\cup" => (8, Some(BinOp::Cup)),
+        "\\cap" => (8, Some(BinOp::Cap)),
+        "\\" => (8, Some(BinOp::SetMinus)),
+        ".." => (9, Some(BinOp::Range)),
+        "+" => (10, Some(BinOp::Plus)),
+        "-" => (10, Some(BinOp::Minus)),
+        "\\X" => (10, Some(BinOp::Cartesian)),
+        "%" => (11, Some(BinOp::Mod)),
+        "*" => (13, Some(BinOp::Times)),
+        "\\div" => (13, Some(BinOp::Div)),
+        "\\o" => (13, Some(BinOp::Concat)),
+        "^" => (14, Some(BinOp::Exp)),
+        _ => return None,
+    };
+    Some((power, op))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Columns of enclosing bullets, innermost last
+    offside: Vec<u32>,
+    /// Names declared RECURSIVE
+    recursive: HashSet<String>,
+}
+
+impl Parser {
+    // ------------------------------------------------------------------
+    // Token helpers
+    // ------------------------------------------------------------------
+
+    fn current(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    /// Current token, or `Eof` if the offside rule hides it.
+    fn peek(&self) -> &Tok {
+        let token = self.current();
+        match self.offside.last() {
+            Some(&column) if token.column <= column => &BLOCKED,
+            _ => &token.tok,
+        }
+    }
+
+    /// Raw lookahead, ignoring the offside rule.
+    fn peek_at(&self, n: usize) -> &Tok {
+        let i = (self.pos + n).min(self.tokens.len() - 1);
+        &self.tokens[i].tok
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.current().clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn is_sym(&self, sym: &str) -> bool {
+        matches!(self.peek(), Tok::Sym(s) if *s == sym)
+    }
+
+    fn is_kw(&self, kw: &str) -> bool {
+        matches!(self.peek(), Tok::Keyword(k) if *k == kw)
+    }
+
+    fn eat_sym(&mut self, sym: &str) -> bool {
+        let found = self.is_sym(sym);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn eat_kw(&mut self, kw: &str) -> bool {
+        let found = self.is_kw(kw);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn expect_sym(&mut self, sym: &str) -> Result<(), ParseError> {
+        if self.eat_sym(sym) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", sym)))
+        }
+    }
+
+    fn expect_kw(&mut self, kw: &str) -> Result<(), ParseError> {
+        if self.eat_kw(kw) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", kw)))
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Tok::Ident(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.unexpected("identifier")),
+        }
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        let token = self.current();
+        ParseError::Syntax {
+            line: token.line,
+            column: token.column,
+            message,
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        self.error(format!(
+            "expected {}, found {}",
+            expected,
+            self.peek().describe()
+        ))
+    }
+
+    // ------------------------------------------------------------------
+    // Module structure
+    // ------------------------------------------------------------------
+
+    fn module(&mut self) -> Result<Module, ParseError> {
+        if *self.peek() != Tok::Separator {
+            return Err(self.unexpected("module header"));
+        }
+        self.advance();
+        let line = self.current().line;
+        self.expect_kw("MODULE")?;
+        let name = self.expect_ident()?;
+        if *self.peek() != Tok::Separator {
+            return Err(self.unexpected("`----` after module name"));
+        }
+        self.advance();
+
+        let mut module = Module {
+            name,
+            line,
+            extends: Vec::new(),
+            constants: Vec::new(),
+            variables: Vec::new(),
+            definitions: Vec::new(),
+            assumptions: Vec::new(),
+        };
+
+        loop {
+            match self.peek() {
+                Tok::End | Tok::Eof => break,
+                Tok::Separator => {
+                    self.advance();
+                }
+                Tok::Keyword("EXTENDS") => {
+                    self.advance();
+                    loop {
+                        module.extends.push(self.expect_ident()?);
+                        if !self.eat_sym(",") {
+                            break;
+                        }
+                    }
+                }
+                Tok::Keyword("CONSTANT" | "CONSTANTS") => {
+                    self.advance();
+                    module.constants.extend(self.declarations()?);
+                }
+                Tok::Keyword("VARIABLE" | "VARIABLES") => {
+                    self.advance();
+                    module.variables.extend(self.declarations()?);
+                }
+                Tok::Keyword("RECURSIVE") => {
+                    self.advance();
+                    for decl in self.declarations()? {
+                        self.recursive.insert(decl.name);
+                    }
+                }
+                Tok::Keyword("ASSUME" | "ASSUMPTION" | "AXIOM" | "THEOREM") => {
+                    self.advance();
+                    module.assumptions.push(self.expr()?);
+                }
+                Tok::Keyword("LOCAL") => {
+                    self.advance();
+                    let mut def = self.definition()?;
+                    def.local = true;
+                    module.definitions.push(def);
+                }
+                Tok::Keyword("INSTANCE") => {
+                    return Err(self.error("INSTANCE is not supported".to_string()));
+                }
+                Tok::Ident(_) => {
+                    let def = self.definition()?;
+                    module.definitions.push(def);
+                }
+                _ => return Err(self.unexpected("definition or declaration")),
+            }
+        }
+
+        Ok(module)
+    }
+
+    /// `a, b, Op(_, _)` in CONSTANTS, VARIABLES and RECURSIVE.
+    fn declarations(&mut self) -> Result<Vec<Declaration>, ParseError> {
+        let mut decls = Vec::new();
+        loop {
+            let line = self.current().line;
+            let name = self.expect_ident()?;
+            let arity = self.placeholder_arity()?;
+            decls.push(Declaration { name, arity, line });
+            if !self.eat_sym(",") {
+                return Ok(decls);
+            }
+        }
+    }
+
+    /// Optional `(_, _)` after an operator name.
+    fn placeholder_arity(&mut self) -> Result<usize, ParseError> {
+        if !self.eat_sym("(") {
+            return Ok(0);
+        }
+        let mut arity = 0;
+        loop {
+            match self.peek() {
+                Tok::Ident(name) if name == "_" => {
+                    self.advance();
+                    arity += 1;
+                }
+                _ => return Err(self.unexpected("`_`")),
+            }
+            if !self.eat_sym(",") {
+                break;
+            }
+        }
+        self.expect_sym(")")?;
+        Ok(arity)
+    }
+
+    /// `Name == e`, `Name(p, F(_)) == e` or `f[x \in S] == e`.
+    fn definition(&mut self) -> Result<Definition, ParseError> {
+        let line = self.current().line;
+        let name = self.expect_ident()?;
+
+        let mut params = Vec::new();
+        let mut function_bounds = Vec::new();
+        if self.eat_sym("(") {
+            loop {
+                let name = self.expect_ident()?;
+                let arity = self.placeholder_arity()?;
+                params.push(Param { name, arity });
+                if !self.eat_sym(",") {
+                    break;
+                }
+            }
+            self.expect_sym(")")?;
+        } else if self.eat_sym("[") {
+            function_bounds = self.bounds()?;
+            self.expect_sym("]")?;
+        }
+
+        self.expect_sym("==")?;
+        if self.is_kw("INSTANCE") {
+            return Err(self.error("INSTANCE is not supported".to_string()));
+        }
+        let body = self.expr()?;
+
+        Ok(Definition {
+            recursive: self.recursive.contains(&name),
+            name,
+            params,
+            function_bounds,
+            body,
+            line,
+            local: false,
+        })
+    }
+
+    /// Comma-separated bounds: `x, y \in S, <<a, b>> \in T` or `x, y`.
+    fn bounds(&mut self) -> Result<Vec<Bound>, ParseError> {
+        let mut bounds = Vec::new();
+        loop {
+            bounds.push(self.bound()?);
+            if !self.eat_sym(",") {
+                return Ok(bounds);
+            }
+        }
+    }
+
+    fn bound(&mut self) -> Result<Bound, ParseError> {
+        let mut names = Vec::new();
+        let tuple = self.eat_sym("<<");
+        loop {
+            names.push(self.expect_ident()?);
+            if !self.eat_sym(",") {
+                break;
+            }
+        }
+        if tuple {
+            self.expect_sym(">>")?;
+        }
+        let set = if self.eat_sym("\\in") {
+            Some(Box::new(self.expr()?))
+        } else {
+            None
+        };
+        Ok(Bound { names, tuple, set })
+    }
+
+    // ------------------------------------------------------------------
+    // Expressions
+    // ------------------------------------------------------------------
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        self.expr_bp(0)
+    }
+
+    /// Parse an expression whose infix operators bind at least `min`.
+    fn expr_bp(&mut self, min: u8) -> Result<Expr, ParseError> {
+        let mut lhs = self.prefix()?;
+
+        while let Some((power, op)) = infix_power(self.peek()) {
+            if power < min {
+                break;
+            }
+            let Tok::Sym(sym) = self.advance().tok else {
+                unreachable!("infix operators are symbols");
+            };
+            let rhs = self.expr_bp(power + 1)?;
+            lhs = match op {
+                Some(op) => Expr::Binary {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                },
+                None if sym == "/\\" => match lhs {
+                    Expr::And(mut items) => {
+                        items.push(rhs);
+                        Expr::And(items)
+                    }
+                    lhs => Expr::And(vec![lhs, rhs]),
+                },
+                None => match lhs {
+                    Expr::Or(mut items) => {
+                        items.push(rhs);
+                        Expr::Or(items)
+                    }
+                    lhs => Expr::Or(vec![lhs, rhs]),
+                },
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self, op: UnaryOp, power: u8) -> Result<Expr, ParseError> {
+        self.advance();
+        Ok(Expr::Unary {
+            op,
+            expr: Box::new(self.expr_bp(power)?),
+        })
+    }
+
+    fn prefix(&mut self) -> Result<Expr, ParseError> {
+        match self.peek() {
+            Tok::Sym("/\\") | Tok::Sym("\\/") => self.junction_list(),
+            Tok::Sym("~") => self.unary(UnaryOp::Not, 4),
+            Tok::Sym("-") => self.unary(UnaryOp::Neg, 12),
+            Tok::Sym("[]") => self.unary(UnaryOp::Always, 4),
+            Tok::Sym("<>") => self.unary(UnaryOp::Eventually, 4),
+            Tok::Keyword("DOMAIN") => self.unary(UnaryOp::Domain, 9),
+            Tok::Keyword("SUBSET") => self.unary(UnaryOp::Subset, 9),
+            Tok::Keyword("UNION") => self.unary(UnaryOp::Union, 9),
+            Tok::Keyword("ENABLED") => self.unary(UnaryOp::Enabled, 4),
+            Tok::Keyword("UNCHANGED") => self.unary(UnaryOp::Unchanged, 4),
+            Tok::Sym("\\A") | Tok::Sym("\\E") => {
+                let kind = if self.advance().tok == Tok::Sym("\\A") {
+                    Quantifier::Forall
+                } else {
+                    Quantifier::Exists
+                };
+                let bounds = self.bounds()?;
+                self.expect_sym(":")?;
+                let body = Box::new(self.expr()?);
+                Ok(Expr::Quant { kind, bounds, body })
+            }
+            Tok::Keyword("CHOOSE") => {
+                self.advance();
+                let bound = self.bound()?;
+                self.expect_sym(":")?;
+                let body = Box::new(self.expr()?);
+                Ok(Expr::Choose { bound, body })
+            }
+            Tok::Keyword("IF") => {
+                self.advance();
+                let cond = Box::new(self.expr()?);
+                self.expect_kw("THEN")?;
+                let then = Box::new(self.expr()?);
+                self.expect_kw("ELSE")?;
+                let otherwise = Box::new(self.expr()?);
+                Ok(Expr::If {
+                    cond,
+                    then,
+                    otherwise,
+                })
+            }
+            Tok::Keyword("CASE") => self.case(),
+            Tok::Keyword("LET") => {
+                self.advance();
+                let mut defs = Vec::new();
+                loop {
+                    if self.eat_kw("RECURSIVE") {
+                        for decl in self.declarations()? {
+                            self.recursive.insert(decl.name);
+                        }
+                        continue;
+                    }
+                    defs.push(self.definition()?);
+                    if self.eat_kw("IN") {
+                        break;
+                    }
+                }
+                let body = Box::new(self.expr()?);
+                Ok(Expr::Let { defs, body })
+            }
+            Tok::Keyword("LAMBDA") => Err(self.error("LAMBDA is not supported".to_string())),
+            _ => {
+                let primary = self.primary()?;
+                self.postfix(primary)
+            }
+        }
+    }
+
+    /// Bulleted `/\` or `\/` list aligned on the first bullet's column.
+    fn junction_list(&mut self) -> Result<Expr, ParseError> {
+        let bullet = self.peek().clone();
+        let column = self.current().column;
+        let mut items = Vec::new();
+
+        loop {
+            self.advance();
+            self.offside.push(column);
+            let item = self.expr();
+            self.offside.pop();
+            items.push(item?);
+
+            if *self.peek() != bullet || self.current().column != column {
+                break;
+            }
+        }
+
+        Ok(match (items.len(), bullet) {
+            (1, _) => items.pop().expect("one item"),
+            (_, Tok::Sym("/\\")) => Expr::And(items),
+            _ => Expr::Or(items),
+        })
+    }
+
+    /// `CASE p1 -> e1 [] p2 -> e2 [] OTHER -> e3`
+    fn case(&mut self) -> Result<Expr, ParseError> {
+        self.advance();
+        let mut arms = Vec::new();
+        let mut other = None;
+        loop {
+            if self.eat_kw("OTHER") {
+                self.expect_sym("->")?;
+                other = Some(Box::new(self.expr()?));
+                break;
+            }
+            let guard = self.expr()?;
+            self.expect_sym("->")?;
+            let value = self.expr()?;
+            arms.push((guard, value));
+            if !self.eat_sym("[]") {
+                break;
+            }
+        }
+        Ok(Expr::Case { arms, other })
+    }
+
+    /// Comma-separated expressions up to (not including) a closing token.
+    fn expr_list(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut items = vec![self.expr()?];
+        while self.eat_sym(",") {
+            items.push(self.expr()?);
+        }
+        Ok(items)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let token = self.peek().clone();
+        match token {
+            Tok::Num(n) => {
+                self.advance();
+                Ok(Expr::Num(n))
+            }
+            Tok::Str(s) => {
+                self.advance();
+                Ok(Expr::Str(s))
+            }
+            Tok::Keyword("TRUE") => {
+                self.advance();
+                Ok(Expr::Bool(true))
+            }
+            Tok::Keyword("FALSE") => {
+                self.advance();
+                Ok(Expr::Bool(false))
+            }
+            Tok::Sym("@") => {
+                self.advance();
+                Ok(Expr::At)
+            }
+            Tok::Ident(name) => {
+                self.advance();
+                if self.eat_sym("(") {
+                    let args = self.expr_list()?;
+                    self.expect_sym(")")?;
+                    Ok(Expr::Apply { op: name, args })
+                } else {
+                    Ok(Expr::Ident(name))
+                }
+            }
+            Tok::Sym("(") => {
+                self.advance();
+                let inner = self.expr()?;
+                self.expect_sym(")")?;
+                Ok(inner)
+            }
+            Tok::Sym("{") => self.set(),
+            Tok::Sym("<<") => {
+                self.advance();
+                let items = if self.is_sym(">>") || self.is_sym(">>_") {
+                    Vec::new()
+                } else {
+                    self.expr_list()?
+                };
+                if self.eat_sym(">>_") {
+                    let vars = Box::new(self.subscript()?);
+                    let action = match <[Expr; 1]>::try_from(items) {
+                        Ok([action]) => Box::new(action),
+                        Err(_) => return Err(self.error("expected <<A>>_v".to_string())),
+                    };
+                    return Ok(Expr::AngleAction { action, vars });
+                }
+                self.expect_sym(">>")?;
+                Ok(Expr::Tuple(items))
+            }
+            Tok::Sym("[") => self.bracket(),
+            Tok::Sym("WF_") | Tok::Sym("SF_") => {
+                let kind = if self.advance().tok == Tok::Sym("WF_") {
+                    FairnessKind::Weak
+                } else {
+                    FairnessKind::Strong
+                };
+                let vars = Box::new(self.subscript()?);
+                self.expect_sym("(")?;
+                let action = Box::new(self.expr()?);
+                self.expect_sym(")")?;
+                Ok(Expr::Fairness { kind, vars, action })
+            }
+            _ => Err(self.unexpected("expression")),
+        }
+    }
+
+    /// Function application, record field access and priming.
+    fn postfix(&mut self, mut expr: Expr) -> Result<Expr, ParseError> {
+        loop {
+            if self.eat_sym("[") {
+                let args = self.expr_list()?;
+                self.expect_sym("]")?;
+                expr = Expr::FnApply {
+                    func: Box::new(expr),
+                    args,
+                };
+            } else if self.is_sym(".") && matches!(self.peek_at(1), Tok::Ident(_)) {
+                self.advance();
+                let field = self.expect_ident()?;
+                expr = Expr::Field {
+                    record: Box::new(expr),
+                    field,
+                };
+            } else if self.eat_sym("'") {
+                expr = Expr::Prime(Box::new(expr));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// Subscript of `[A]_v`, `<<A>>_v` and `WF_v(A)`: a name or tuple.
+    fn subscript(&mut self) -> Result<Expr, ParseError> {
+        match self.peek() {
+            Tok::Ident(_) => Ok(Expr::Ident(self.expect_ident()?)),
+            Tok::Sym("<<") => {
+                self.advance();
+                let items = self.expr_list()?;
+                self.expect_sym(">>")?;
+                Ok(Expr::Tuple(items))
+            }
+            Tok::Sym("(") => {
+                self.advance();
+                let inner = self.expr()?;
+                self.expect_sym(")")?;
+                Ok(inner)
+            }
+            _ => Err(self.unexpected("subscript")),
+        }
+    }
+
+    /// `{}`, `{a, b}`, `{x \in S : P}` or `{e : x \in S}`.
+    fn set(&mut self) -> Result<Expr, ParseError> {
+        self.advance();
+        if self.eat_sym("}") {
+            return Ok(Expr::SetEnum(Vec::new()));
+        }
+
+        let first = self.expr()?;
+        let set = if self.eat_sym(":") {
+            match first {
+                Expr::Binary {
+                    op: BinOp::In,
+                    lhs,
+                    rhs,
+                } if bound_names(&lhs).is_some() => {
+                    let (names, tuple) = bound_names(&lhs).expect("checked above");
+                    let pred = Box::new(self.expr()?);
+                    Expr::SetFilter {
+                        bound: Bound {
+                            names,
+                            tuple,
+                            set: Some(rhs),
+                        },
+                        pred,
+                    }
+                }
+                expr => Expr::SetMap {
+                    expr: Box::new(expr),
+                    bounds: self.bounds()?,
+                },
+            }
+        } else {
+            let mut items = vec![first];
+            while self.eat_sym(",") {
+                items.push(self.expr()?);
+            }
+            Expr::SetEnum(items)
+        };
+
+        self.expect_sym("}")?;
+        Ok(set)
+    }
+
+    /// Everything that starts with `[`: records, record sets, function
+    /// constructors, function sets, EXCEPT and `[A]_v`.
+    fn bracket(&mut self) -> Result<Expr, ParseError> {
+        self.advance();
+
+        if let Tok::Ident(_) = self.peek() {
+            match self.peek_at(1) {
+                Tok::Sym("|->") => return self.record(false),
+                Tok::Sym(":") => return self.record(true),
+                Tok::Sym("\\in") | Tok::Sym(",") => {
+                    let bounds = self.bounds()?;
+                    self.expect_sym("|->")?;
+                    let body = Box::new(self.expr()?);
+                    self.expect_sym("]")?;
+                    return Ok(Expr::FnConstruct { bounds, body });
+                }
+                _ => {}
+            }
+        }
+
+        let expr = self.expr()?;
+        if self.eat_kw("EXCEPT") {
+            let mut updates = Vec::new();
+            loop {
+                self.expect_sym("!")?;
+                let mut path = Vec::new();
+                loop {
+                    if self.eat_sym("[") {
+                        path.push(ExceptKey::Index(self.expr_list()?));
+                        self.expect_sym("]")?;
+                    } else if self.eat_sym(".") {
+                        path.push(ExceptKey::Field(self.expect_ident()?));
+                    } else {
+                        break;
+                    }
+                }
+                if path.is_empty() {
+                    return Err(self.unexpected("`[` or `.` after `!`"));
+                }
+                self.expect_sym("=")?;
+                let value = self.expr()?;
+                updates.push(ExceptUpdate { path, value });
+                if !self.eat_sym(",") {
+                    break;
+                }
+            }
+            self.expect_sym("]")?;
+            return Ok(Expr::Except {
+                func: Box::new(expr),
+                updates,
+            });
+        }
+        if self.eat_sym("->") {
+            let range = Box::new(self.expr()?);
+            self.expect_sym("]")?;
+            return Ok(Expr::FnSet {
+                domain: Box::new(expr),
+                range,
+            });
+        }
+        if self.eat_sym("]_") {
+            let vars = Box::new(self.subscript()?);
+            return Ok(Expr::BoxAction {
+                action: Box::new(expr),
+                vars,
+            });
+        }
+        Err(self.unexpected("`EXCEPT`, `->` or `]_`"))
+    }
+
+    /// `[a |-> e, ...]` or, for record sets, `[a : S, ...]`.
+    fn record(&mut self, is_set: bool) -> Result<Expr, ParseError> {
+        let separator = if is_set { ":" } else { "|->" };
+        let mut fields = Vec::new();
+        loop {
+            let name = self.expect_ident()?;
+            self.expect_sym(separator)?;
+            fields.push((name, self.expr()?));
+            if !self.eat_sym(",") {
+                break;
+            }
+        }
+        self.expect_sym("]")?;
+        Ok(if is_set {
+            Expr::RecordSet(fields)
+        } else {
+            Expr::Record(fields)
+        })
+    }
+}
+
+/// Names bound by `x` or `<<x, y>>` on the left of `\in` in a set filter.
+fn bound_names(expr: &Expr) -> Option<(Vec<String>, bool)> {
+    match expr {
+        Expr::Ident(name) => Some((vec![name.clone()], false)),
+        Expr::Tuple(items) => items
+            .iter()
+            .map(|e| match e {
+                Expr::Ident(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|names| (names, true)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(body: &str) -> Module {
+        parse_module(&format!("---- MODULE m ----\n{}\n====", body)).unwrap()
+    }
+
+    fn body(def: &str) -> Expr {
+        module(def).definitions.remove(0).body
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(
+            body("A == ~x = y /\\ a \\in S \\cup T => b").to_string(),
+            "((~((x = y)) /\\ (a \\in (S \\cup T))) => b)"
+        );
+        assert_eq!(
+            body("A == DOMAIN f \\cup {1 + 2 * 3}").to_string(),
+            "(DOMAIN (f) \\cup {(1 + (2 * 3))})"
+        );
+    }
+
+    #[test]
+    fn test_junction_lists_use_columns() {
+        let expr = body(
+            "A ==\n  \\/ /\\ a\n     /\\ b\n  \\/ LET x == 1\n     IN /\\ c\n        /\\ d\n  \\/ e",
+        );
+        assert_eq!(
+            expr.to_string(),
+            "((a /\\ b) \\/ (LET x == 1 IN (c /\\ d)) \\/ e)"
+        );
+    }
+
+    #[test]
+    fn test_constructors() {
+        let m = module(
+            "A == [t \\in T |-> [pc |-> \"idle\", local |-> <<>>]]\n\
+             B == [f EXCEPT ![t].pc = @ + 1, ![u] = 2]\n\
+             C == {x \\in S : x > 0} \\cup {f[i] : i \\in DOMAIN f}\n\
+             D == [a : S, b : [S -> SUBSET T]]",
+        );
+        let shown: Vec<String> = m.definitions.iter().map(|d| d.body.to_string()).collect();
+        assert_eq!(shown[0], "[t \\in T |-> [pc |-> \"idle\", local |-> <<>>]]");
+        assert_eq!(shown[1], "[f EXCEPT ![t].pc = (@ + 1), ![u] = 2]");
+        assert_eq!(
+            shown[2],
+            "({x \\in S : (x > 0)} \\cup {f[i] : i \\in DOMAIN (f)})"
+        );
+        assert_eq!(shown[3], "[a : S, b : [S -> SUBSET (T)]]");
+    }
+
+    #[test]
+    fn test_quantifiers_and_choose() {
+        let expr = body("A == \\A x, y \\in S, <<a, b>> \\in T : CHOOSE z : z \\notin S");
+        let Expr::Quant { kind, bounds, body } = expr else {
+            panic!("expected quantifier");
+        };
+        assert_eq!(kind, Quantifier::Forall);
+        assert_eq!(bounds[0].names, vec!["x", "y"]);
+        assert!(bounds[1].tuple);
+        assert!(matches!(*body, Expr::Choose { ref bound, .. } if bound.set.is_none()));
+    }
+
+    #[test]
+    fn test_declarations_and_behavior() {
+        let m = module(
+            "EXTENDS Naturals, Sequences\n\
+             CONSTANTS N, Op(_, _)\n\
+             VARIABLES x, y\n\
+             vars == <<x, y>>\n\
+             ----\n\
+             RECURSIVE Sum(_)\n\
+             Sum(s) == IF s = <<>> THEN 0 ELSE Head(s) + Sum(Tail(s))\n\
+             Init == x = 0 /\\ y = 0\n\
+             Next == x' = x + 1 /\\ UNCHANGED y\n\
+             Spec == Init /\\ [][Next]_vars\n\
+             Fairness == \\A i \\in 1..N : WF_vars(Next)\n\
+             FairSpec == Spec /\\ Fairness /\\ WF_<<x>>(Next)",
+        );
+        assert_eq!(m.extends, vec!["Naturals", "Sequences"]);
+        assert_eq!(m.constants[1].arity, 2);
+        assert!(m.is_variable("y"));
+        assert!(m.definition("Sum").unwrap().recursive);
+        assert_eq!(m.definition("Init").unwrap().line, 9);
+
+        let behavior = m.behavior("FairSpec").unwrap();
+        assert_eq!(behavior.init, Expr::Ident("Init".into()));
+        assert_eq!(behavior.next, Expr::Ident("Next".into()));
+        assert_eq!(behavior.vars, Expr::Ident("vars".into()));
+        assert_eq!(behavior.fairness.len(), 2);
+    }
+
+    #[test]
+    fn test_syntax_error_position() {
+        let err = parse_module("---- MODULE m ----\nA == (1 + \n====").unwrap_err();
+        assert!(
+            matches!(err, ParseError::Syntax { line: 3, .. }),
+            "{:?}",
+            err
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Concrete TLA+ values.
+//!
+//! Functions are normalized on construction so that equal TLA+ values
+//! compare equal in Rust: a function with domain `1..n` is a `Seq`, a
+//! function whose domain is a set of strings is a `Record`, and the empty
+//! function is the empty sequence `<<>>`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// A TLA+ value.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    /// Uninterpreted model value (e.g. `NULL` or a thread id)
+    Model(String),
+    Set(BTreeSet<Value>),
+    /// Sequence or tuple: a function with domain `1..n`
+    Seq(Vec<Value>),
+    /// Record: a function from field names
+    Record(BTreeMap<String, Value>),
+    /// Any other function
+    Fn(BTreeMap<Value, Value>),
+}
+
+impl Value {
+    /// A model value.
+    #[must_use]
+    pub fn model(name: &str) -> Self {
+        debug_assert!(!name.is_empty(), "model value needs a name");
+        Value::Model(name.to_string())
+    }
+
+    /// A string value.
+    #[must_use]
+    pub fn str(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+
+    /// A set of values.
+    #[must_use]
+    pub fn set(items: impl IntoIterator<Item = Value>) -> Self {
+        Value::Set(items.into_iter().collect())
+    }
+
+    /// A sequence (or tuple) of values.
+    #[must_use]
+    pub fn seq(items: impl IntoIterator<Item = Value>) -> Self {
+        Value::Seq(items.into_iter().collect())
+    }
+
+    /// A record from field names and values.
+    #[must_use]
+    pub fn record<'a>(fields: impl IntoIterator<Item = (&'a str, Value)>) -> Self {
+        let fields: BTreeMap<String, Value> = fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        if fields.is_empty() {
+            return Value::Seq(Vec::new());
+        }
+        Value::Record(fields)
+    }
+
+    /// A function from its graph, normalized to `Seq` or `Record` when
+    /// the domain allows.
+    #[must_use]
+    pub fn function(map: BTreeMap<Value, Value>) -> Self {
+        let is_seq = map
+            .keys()
+            .enumerate()
+            .all(|(i, k)| *k == Value::Int(i as i64 + 1));
+        if is_seq {
+            return Value::Seq(map.into_values().collect());
+        }
+        if map.keys().all(|k| matches!(k, Value::Str(_))) {
+            return Value::Record(
+                map.into_iter()
+                    .map(|(k, v)| match k {
+                        Value::Str(name) => (name, v),
+                        _ => unreachable!("all keys are strings"),
+                    })
+                    .collect(),
+            );
+        }
+        Value::Fn(map)
+    }
+
+    /// Name of the value's kind, for error messages.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "boolean",
+            Value::Int(_) => "integer",
+            Value::Str(_) => "string",
+            Value::Model(_) => "model value",
+            Value::Set(_) => "set",
+            Value::Seq(_) => "sequence",
+            Value::Record(_) => "record",
+            Value::Fn(_) => "function",
+        }
+    }
+
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_set(&self) -> Option<&BTreeSet<Value>> {
+        match self {
+            Value::Set(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_seq(&self) -> Option<&[Value]> {
+        match self {
+            Value::Seq(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Whether the value is a function (sequences and records included).
+    #[must_use]
+    pub fn is_function(&self) -> bool {
+        matches!(self, Value::Seq(_) | Value::Record(_) | Value::Fn(_))
+    }
+
+    /// `DOMAIN f` for functions, sequences and records.
+    #[must_use]
+    pub fn domain(&self) -> Option<BTreeSet<Value>> {
+        match self {
+            Value::Seq(items) => Some((1..=items.len() as i64).map(Value::Int).collect()),
+            Value::Record(fields) => Some(fields.keys().map(|k| Value::str(k)).collect()),
+            Value::Fn(map) => Some(map.keys().cloned().collect()),
+            _ => None,
+        }
+    }
+
+    /// `f[arg]`, or `None` if `arg` is outside the domain.
+    #[must_use]
+    pub fn apply(&self, arg: &Value) -> Option<&Value> {
+        match (self, arg) {
+            (Value::Seq(items), Value::Int(i)) if *i >= 1 => items.get(*i as usize - 1),
+            (Value::Record(fields), Value::Str(name)) => fields.get(name),
+            (Value::Fn(map), _) => map.get(arg),
+            _ => None,
+        }
+    }
+
+    /// The function's graph, or `None` if the value is not a function.
+    #[must_use]
+    pub fn to_graph(&self) -> Option<BTreeMap<Value, Value>> {
+        match self {
+            Value::Seq(items) => Some(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| (Value::Int(i as i64 + 1), v.clone()))
+                    .collect(),
+            ),
+            Value::Record(fields) => Some(
+                fields
+                    .iter()
+                    .map(|(k, v)| (Value::str(k), v.clone()))
+                    .collect(),
+            ),
+            Value::Fn(map) => Some(map.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        debug_assert!(
+            n <= i64::MAX as u64,
+            "value {} does not fit a TLA+ integer",
+            n
+        );
+        Value::Int(n as i64)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::str(s)
+    }
+}
+
+/// Prints values in TLA+ syntax, as TLC does.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn join<T>(
+            f: &mut fmt::Formatter<'_>,
+            items: impl Iterator<Item = T>,
+            mut item: impl FnMut(&mut fmt::Formatter<'_>, T) -> fmt::Result,
+        ) -> fmt::Result {
+            for (i, x) in items.enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                item(f, x)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Value::Bool(true) => f.write_str("TRUE"),
+            Value::Bool(false) => f.write_str("FALSE"),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{:?}", s),
+            Value::Model(name) => f.write_str(name),
+            Value::Set(items) => {
+                f.write_str("{")?;
+                join(f, items.iter(), |f, v| write!(f, "{}", v))?;
+                f.write_str("}")
+            }
+            Value::Seq(items) => {
+                f.write_str("<<")?;
+                join(f, items.iter(), |f, v| write!(f, "{}", v))?;
+                f.write_str(">>")
+            }
+            Value::Record(fields) => {
+                f.write_str("[")?;
+                join(f, fields.iter(), |f, (k, v)| write!(f, "{} |-> {}", k, v))?;
+                f.write_str("]")
+            }
+            Value::Fn(map) => {
+                f.write_str("(")?;
+                for (i, (k, v)) in map.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" @@ ")?;
+                    }
+                    write!(f, "{} :> {}", k, v)?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
@@ -0,0 +1,451 @@
+//! Import model checker error traces as counterexamples.
+//!
+//! - [`parse_tlc_trace`]: the error trace in TLC's console output (`.out`)
+//! - [`parse_itf_trace`]: Apalache's Informal Trace Format (ITF) JSON
+//!
+//! Each state of the trace becomes one [`StateSnapshot`] whose variables
+//! hold values printed in TLA+ syntax, so they can be loaded back with
+//! [`crate::tla_spec::Evaluator::load_state`].
+
+use std::fmt;
+
+use serde_json::Value as Json;
+
+use crate::counterexample::{Counterexample, StateSnapshot};
+use crate::tla_spec::Value;
+
+/// Error importing a trace.
+#[derive(Debug, Clone)]
+pub enum TraceError {
+    /// The input contains no states
+    NoStates,
+    /// The input is not valid JSON
+    Json(String),
+    /// The trace is malformed
+    Format(String),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::NoStates => write!(f, "trace contains no states"),
+            TraceError::Json(e) => write!(f, "invalid JSON: {}", e),
+            TraceError::Format(e) => write!(f, "malformed trace: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+// ============================================================================
+// TLC
+// ============================================================================
+
+/// Parse the error trace from TLC output.
+///
+/// Understands both plain console output and `-tool` output. `Error:` lines
+/// become the description; a `Back to state N` line (liveness lasso) sets
+/// `loop_to_step`.
+pub fn parse_tlc_trace(output: &str) -> Result<Counterexample, TraceError> {
+    let mut ce = Counterexample::new();
+    let mut errors = Vec::new();
+    let mut current: Option<StateSnapshot> = None;
+
+    for raw in output.lines() {
+        let line = raw.trim_end();
+        if line.starts_with("@!@!@") {
+            continue;
+        }
+
+        if let Some(message) = line.strip_prefix("Error: ") {
+            let trace_header = message.starts_with("The behavior up to this point is")
+                || message.starts_with("The following behavior constitutes");
+            if !trace_header {
+                errors.push(message.trim().to_string());
+            }
+            continue;
+        }
+
+        if let Some((step, label)) = tlc_state_header(line) {
+            if let Some(state) = current.take() {
+                push_state(&mut ce, state)?;
+            }
+            if let Some(target) = back_to_state(label) {
+                note_loop(&mut ce, target);
+                continue;
+            }
+            current = Some(StateSnapshot {
+                step,
+                description: tlc_action_label(label),
+                variables: Vec::new(),
+            });
+            continue;
+        }
+
+        if let Some(target) = back_to_state(line) {
+            if let Some(state) = current.take() {
+                push_state(&mut ce, state)?;
+            }
+            note_loop(&mut ce, target);
+            continue;
+        }
+
+        let Some(state) = current.as_mut() else {
+            continue;
+        };
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            push_state(&mut ce, current.take().expect("state in progress"))?;
+            continue;
+        }
+
+        let assignment = trimmed.strip_prefix("/\\ ");
+        match (assignment, state.variables.last_mut()) {
+            // Continuation of a multi-line value
+            (None, Some((_, value))) => {
+                value.push(' ');
+                value.push_str(trimmed);
+            }
+            (assignment, _) => {
+                let text = assignment.unwrap_or(trimmed);
+                let (name, value) = text.split_once(" = ").ok_or_else(|| {
+                    TraceError::Format(format!("expected `var = value`, found `{}`", text))
+                })?;
+                state
+                    .variables
+                    .push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+    }
+
+    if let Some(state) = current {
+        push_state(&mut ce, state)?;
+    }
+    if ce.states.is_empty() {
+        return Err(TraceError::NoStates);
+    }
+    if !errors.is_empty() {
+        ce.description = Some(errors.join(" "));
+    }
+    Ok(ce)
+}
+
+/// `State 3: <Next line 10, ...>` -> `(3, "<Next line 10, ...>")`
+fn tlc_state_header(line: &str) -> Option<(u64, &str)> {
+    let rest = line.strip_prefix("State ")?;
+    let (number, label) = rest.split_once(':')?;
+    Some((number.trim().parse().ok()?, label.trim()))
+}
+
+/// `Back to state 2: <...>` -> `2`
+fn back_to_state(text: &str) -> Option<u64> {
+    let rest = text.strip_prefix("Back to state ")?;
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+/// `<Push line 45, col 5 to line 52, col 30 of module treiber_stack>`
+/// -> `Push (line 45)`
+fn tlc_action_label(label: &str) -> String {
+    let inner = label
+        .strip_prefix('<')
+        .and_then(|l| l.strip_suffix('>'))
+        .unwrap_or(label);
+    match inner.split_once(" line ") {
+        Some((action, position)) => {
+            let line: String = position.chars().take_while(char::is_ascii_digit).collect();
+            format!("{} (line {})", action.trim(), line)
+        }
+        None => inner.trim().to_string(),
+    }
+}
+
+fn push_state(ce: &mut Counterexample, state: StateSnapshot) -> Result<(), TraceError> {
+    if let Some(last) = ce.states.last() {
+        if state.step <= last.step {
+            return Err(TraceError::Format(format!(
+                "state {} follows state {}",
+                state.step, last.step
+            )));
+        }
+    }
+    ce.add_state(state);
+    Ok(())
+}
+
+fn note_loop(ce: &mut Counterexample, target: u64) {
+    if !ce.states.is_empty() {
+        ce.loop_to_step = Some(target);
+    }
+}
+
+// ============================================================================
+// Apalache ITF
+// ============================================================================
+
+/// Variable holding the action name in traces produced by Quint.
+const ITF_ACTION_VAR: &str = "mbt::actionTaken";
+
+/// Parse an Informal Trace Format (ITF) JSON trace.
+///
+/// See <https://apalache-mc.org/docs/adr/015adr-trace.html>. Values are
+/// converted to TLA+ syntax: `#set` to sets, `#tup` and arrays to tuples,
+/// `#map` to functions and objects to records.
+pub fn parse_itf_trace(json: &str) -> Result<Counterexample, TraceError> {
+    let root: Json = serde_json::from_str(json).map_err(|e| TraceError::Json(e.to_string()))?;
+    let states = root
+        .get("states")
+        .and_then(Json::as_array)
+        .ok_or_else(|| TraceError::Format("missing `states` array".to_string()))?;
+    if states.is_empty() {
+        return Err(TraceError::NoStates);
+    }
+    let vars: Option<Vec<&str>> = root
+        .get("vars")
+        .and_then(Json::as_array)
+        .map(|vars| vars.iter().filter_map(Json::as_str).collect());
+
+    let mut ce = Counterexample::new();
+    for (index, state) in states.iter().enumerate() {
+        let fields = state
+            .as_object()
+            .ok_or_else(|| TraceError::Format(format!("state {} is not an object", index)))?;
+        let names: Vec<&str> = match &vars {
+            Some(vars) => vars.clone(),
+            None => fields
+                .keys()
+                .map(String::as_str)
+                .filter(|k| !k.starts_with('#') && !k.starts_with("mbt::"))
+                .collect(),
+        };
+
+        let mut variables = Vec::with_capacity(names.len());
+        for name in names {
+            let value = fields.get(name).ok_or_else(|| {
+                TraceError::Format(format!("state {} has no variable `{}`", index, name))
+            })?;
+            variables.push((name.to_string(), itf_value(value)?.to_string()));
+        }
+
+        let description = match fields.get(ITF_ACTION_VAR).and_then(Json::as_str) {
+            Some(action) => action.to_string(),
+            None if index == 0 => "Initial state".to_string(),
+            None => format!("State {}", index + 1),
+        };
+        ce.add_state(StateSnapshot {
+            step: index as u64 + 1,
+            description,
+            variables,
+        });
+    }
+
+    if let Some(target) = root.get("loop").and_then(Json::as_u64) {
+        note_loop(&mut ce, target + 1);
+    }
+    if let Some(description) = root
+        .get("#meta")
+        .and_then(|m| m.get("description"))
+        .and_then(Json::as_str)
+    {
+        ce.description = Some(description.to_string());
+    }
+    Ok(ce)
+}
+
+/// Convert an ITF value to a TLA+ value.
+fn itf_value(json: &Json) -> Result<Value, TraceError> {
+    match json {
+        Json::Bool(b) => Ok(Value::Bool(*b)),
+        Json::Number(n) => n
+            .as_i64()
+            .map(Value::Int)
+            .ok_or_else(|| TraceError::Format(format!("number {} is not an integer", n))),
+        Json::String(s) => Ok(Value::Str(s.clone())),
+        Json::Array(items) => items
+            .iter()
+            .map(itf_value)
+            .collect::<Result<_, _>>()
+            .map(Value::Seq),
+        Json::Object(fields) => {
+            if let Some(digits) = fields.get("#bigint") {
+                let digits = digits.as_str().unwrap_or_default();
+                return digits.parse().map(Value::Int).map_err(|_| {
+                    TraceError::Format(format!("#bigint {} does not fit in 64 bits", digits))
+                });
+            }
+            if let Some(items) = fields.get("#tup") {
+                return itf_list(items, "#tup").map(Value::Seq);
+            }
+            if let Some(items) = fields.get("#set") {
+                return itf_list(items, "#set").map(Value::set);
+            }
+            if let Some(pairs) = fields.get("#map") {
+                let mut graph = std::collections::BTreeMap::new();
+                for pair in itf_list(pairs, "#map")? {
+                    match pair {
+                        Value::Seq(kv) if kv.len() == 2 => {
+                            let mut kv = kv.into_iter();
+                            graph.insert(kv.next().expect("key"), kv.next().expect("value"));
+                        }
+                        other => {
+                            return Err(TraceError::Format(format!(
+                                "#map entry {} is not a pair",
+                                other
+                            )))
+                        }
+                    }
+                }
+                return Ok(Value::function(graph));
+            }
+            if let Some(text) = fields.get("#unserializable") {
+                return Ok(Value::model(text.as_str().unwrap_or("?")));
+            }
+            let mut record = Vec::with_capacity(fields.len());
+            for (name, value) in fields {
+                record.push((name.as_str(), itf_value(value)?));
+            }
+            Ok(Value::record(record))
+        }
+        Json::Null => Err(TraceError::Format("null is not an ITF value".to_string())),
+    }
+}
+
+fn itf_list(json: &Json, tag: &str) -> Result<Vec<Value>, TraceError> {
+    json.as_array()
+        .ok_or_else(|| TraceError::Format(format!("{} must hold an array", tag)))?
+        .iter()
+        .map(itf_value)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tla_spec::{parse_module, Evaluator};
+
+    const TLC_OUTPUT: &str = r#"TLC2 Version 2.18 of 20 March 2023
+Running breadth-first search Model-Checking with fp 1 and seed 42.
+@!@!@STARTMSG 2110:1 @!@!@
+Error: Invariant NoUseAfterFree is violated.
+@!@!@ENDMSG 2110 @!@!@
+Error: The behavior up to this point is:
+State 1: <Initial predicate>
+/\ freed = {}
+/\ referenced = (t1 :> {} @@ t2 :> {})
+
+State 2: <Pin line 98, col 5 to line 104, col 40 of module epoch_gc>
+/\ freed = {}
+/\ referenced = ( t1 :> {o1} @@
+  t2 :> {} )
+
+State 3: <Free line 120, col 5 to line 126, col 33 of module epoch_gc>
+/\ freed = {o1}
+/\ referenced = (t1 :> {o1} @@ t2 :> {})
+
+12 states generated, 9 distinct states found, 0 states left on queue.
+"#;
+
+    #[test]
+    fn test_parse_tlc_trace() {
+        let ce = parse_tlc_trace(TLC_OUTPUT).unwrap();
+        assert_eq!(ce.states.len(), 3);
+        assert_eq!(
+            ce.description.as_deref(),
+            Some("Invariant NoUseAfterFree is violated.")
+        );
+        assert_eq!(ce.states[0].description, "Initial predicate");
+        assert_eq!(ce.states[1].description, "Pin (line 98)");
+        assert_eq!(
+            ce.states[1].variables[1],
+            (
+                "referenced".to_string(),
+                "( t1 :> {o1} @@ t2 :> {} )".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_tlc_values_evaluate_against_spec() {
+        let module = parse_module(
+            "---- MODULE m ----\nCONSTANT Threads\nVARIABLES freed, referenced\n\
+             NoUseAfterFree == \\A t \\in Threads : referenced[t] \\cap freed = {}\n====",
+        )
+        .unwrap();
+        let threads = Value::set([Value::model("t1"), Value::model("t2")]);
+        let ce = parse_tlc_trace(TLC_OUTPUT).unwrap();
+
+        let holds: Vec<bool> = ce
+            .states
+            .iter()
+            .map(|state| {
+                let mut eval = Evaluator::new(&module).with_constant("Threads", threads.clone());
+                eval.load_state(state).unwrap();
+                eval.check("NoUseAfterFree").unwrap()
+            })
+            .collect();
+        assert_eq!(holds, vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_parse_tlc_lasso() {
+        let output = "Error: Temporal properties were violated.\n\
+                      Error: The following behavior constitutes a counter-example:\n\
+                      State 1: <Initial predicate>\n/\\ x = 0\n\n\
+                      State 2: <Next line 5, col 1 to line 5, col 10 of module m>\n/\\ x = 1\n\n\
+                      Back to state 1: <Next line 5, col 1 to line 5, col 10 of module m>\n";
+        let ce = parse_tlc_trace(output).unwrap();
+        assert_eq!(ce.states.len(), 2);
+        assert_eq!(ce.loop_to_step, Some(1));
+        assert!(ce.render_diagram().ends_with("  x = 1\nBack to state 1\n"));
+        assert!(ce
+            .description
+            .unwrap()
+            .starts_with("Temporal properties were violated."));
+    }
+
+    #[test]
+    fn test_parse_itf_trace() {
+        let json = r##"{
+            "#meta": {"format": "ITF", "description": "Invariant Safe violated"},
+            "vars": ["log", "votes", "leader"],
+            "states": [
+                {"#meta": {"index": 0}, "log": [], "votes": {"#set": []},
+                 "leader": {"#map": [["s1", false], ["s2", false]]}},
+                {"#meta": {"index": 1}, "log": [{"#tup": [1, {"#bigint": "10"}]}],
+                 "votes": {"#set": ["s1", "s2"]},
+                 "leader": {"#map": [["s1", true], ["s2", false]]},
+                 "mbt::actionTaken": "BecomeLeader"}
+            ],
+            "loop": 0
+        }"##;
+        let ce = parse_itf_trace(json).unwrap();
+        assert_eq!(ce.states.len(), 2);
+        assert_eq!(ce.description.as_deref(), Some("Invariant Safe violated"));
+        assert_eq!(
+            ce.states[1].variables,
+            vec![
+                ("log".to_string(), "<<<<1, 10>>>>".to_string()),
+                ("votes".to_string(), "{\"s1\", \"s2\"}".to_string()),
+                (
+                    "leader".to_string(),
+                    "[s1 |-> TRUE, s2 |-> FALSE]".to_string()
+                ),
+            ]
+        );
+        assert_eq!(ce.states[1].description, "BecomeLeader");
+        assert_eq!(ce.loop_to_step, Some(1));
+    }
+
+    #[test]
+    fn test_trace_errors() {
+        assert!(matches!(
+            parse_tlc_trace("Model checking completed. No error has been found."),
+            Err(TraceError::NoStates)
+        ));
+        assert!(matches!(parse_itf_trace("{"), Err(TraceError::Json(_))));
+        assert!(matches!(
+            parse_itf_trace(r#"{"states": [{"x": null}]}"#),
+            Err(TraceError::Format(_))
+        ));
+    }
+}
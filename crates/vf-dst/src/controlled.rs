@@ -0,0 +1,999 @@
+//! Controlled execution of real threads for DST.
+//!
+//! `DstHarness::run_concurrent` interleaves whole operations from a single
+//! OS thread, so it never explores the interleavings inside a CAS loop.
+//! A [`ControlledRuntime`] instead runs every simulated thread on its own
+//! OS thread, but lets exactly one of them proceed at a time, like
+//! [shuttle](https://github.com/awslabs/shuttle). Control only changes
+//! hands at switch points, where the seeded [`Scheduler`] picks the next
+//! thread, so the same seed always produces the same interleaving.
+//!
+//! # Switch Points
+//!
+//! Instrumented operations call [`switch_point`] before they touch shared
+//! memory. An operation that cannot make progress until another thread
+//! runs (a spin loop, a contended lock) calls [`yield_now`] instead,
+//! which always hands control to another runnable thread. Outside a
+//! controlled thread both are no-ops, so instrumented code runs unchanged
+//! in production.
+//!
+//! The [`sync`](crate::sync) shim calls [`sync_point`] for every atomic,
+//! lock and `Arc` operation, which also records a [`SyncEvent`] in the
+//! report. Threads spawned with `sync::thread::spawn` from a controlled
+//! thread join the execution.
+//!
+//! An execution is sequentially consistent unless it is run
+//! [`with_weak_memory`](ControlledRuntime::with_weak_memory), which lets
+//! shim atomic loads return stale values (see [`crate::weak_memory`]).
+//! Running it [`with_memory_checker`](ControlledRuntime::with_memory_checker)
+//! reports data races, use after free, ABA and leaks as [`MemoryIssue`]s,
+//! and [`ControlledReport::counterexample`] puts them next to the
+//! interleaving that caused them.
+//!
+//! # Failures
+//!
+//! A panic in any thread stops the execution: the other threads are
+//! unwound at their next switch point and the panic message becomes the
+//! failure. A thread that yields with no other runnable thread left is a
+//! deadlock, and an execution that exceeds [`SWITCH_POINTS_MAX`] is
+//! assumed to livelock.
+//!
+//! ```rust
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//! use vf_dst::controlled::{switch_point, ControlledRuntime};
+//! use vf_dst::{DeterministicRng, ScheduleDecision, Scheduler};
+//!
+//! let counter = AtomicU64::new(0);
+//! let increment = || {
+//!     // Racy read-modify-write: another thread can run in between
+//!     switch_point();
+//!     let value = counter.load(Ordering::SeqCst);
+//!     switch_point();
+//!     counter.store(value + 1, Ordering::SeqCst);
+//! };
+//!
+//! let scheduler = Scheduler::new(DeterministicRng::new(7), 2, 0.5);
+//! let report = ControlledRuntime::new(scheduler).run(vec![
+//!     Box::new(increment),
+//!     Box::new(increment),
+//! ]);
+//! assert!(report.passed());
+//! // Seed 7 always runs thread 1's whole increment between thread 0's
+//! // load and store, so one increment is lost
+//! assert_eq!(counter.load(Ordering::SeqCst), 1);
+//! assert_eq!(report.trace, vec![0, 0, 1, 1]);
+//! assert_eq!(
+//!     report.decisions,
+//!     vec![
+//!         ScheduleDecision::Continue,
+//!         ScheduleDecision::SwitchTo(1),
+//!         ScheduleDecision::Continue,
+//!         ScheduleDecision::Continue,
+//!         ScheduleDecision::SwitchTo(0),
+//!         ScheduleDecision::SwitchTo(1),
+//!     ]
+//! );
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use vf_core::counterexample::{Counterexample, MemoryIssue, ThreadAction};
+
+use crate::memory_checker::MemoryChecker;
+use crate::random::DeterministicRng;
+use crate::scheduler::{ScheduleDecision, Scheduler};
+use crate::weak_memory::WeakMemory;
+
+/// Maximum switch points in one execution before it counts as a livelock.
+pub const SWITCH_POINTS_MAX: u64 = 1_000_000;
+
+/// A simulated thread's body.
+pub type ThreadBody<'env> = Box<dyn FnOnce() + Send + 'env>;
+
+/// Unwind payload for threads stopped because another one failed.
+struct Stopped;
+
+/// Kind of synchronization operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncOp {
+    /// Atomic load
+    Load,
+    /// Atomic store
+    Store,
+    /// Atomic swap, compare-exchange or fetch-and-modify
+    ReadModifyWrite,
+    /// Failed compare-exchange, which only reads
+    CasFailure,
+    /// Memory fence
+    Fence,
+    /// Mutex or write lock acquired
+    Lock,
+    /// Mutex or write lock released
+    Unlock,
+    /// Read lock acquired
+    ReadLock,
+    /// Read lock released
+    ReadUnlock,
+    /// `Arc` created or cloned
+    ArcClone,
+    /// `Arc` reference dropped
+    ArcDrop,
+    /// Thread spawned; the object is the child's index
+    Spawn,
+    /// Thread joined; the object is the child's index
+    Join,
+    /// Non-atomic read of an `UnsafeCell`
+    Read,
+    /// Non-atomic write of an `UnsafeCell`
+    Write,
+    /// Memory allocated; the object is its address
+    Alloc,
+    /// Memory freed; the object is its address
+    Dealloc,
+}
+
+/// One synchronization operation, in execution order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncEvent {
+    /// Position in the execution's event order, from 1
+    pub step: u64,
+    /// Controlled thread that performed the operation
+    pub thread: usize,
+    pub op: SyncOp,
+    /// Address of the atomic, lock, cell or allocation
+    pub object: usize,
+    /// Memory ordering, for atomic operations
+    pub ordering: Option<Ordering>,
+}
+
+impl SyncEvent {
+    /// Format for display, as an action of a counterexample.
+    pub fn format(&self) -> String {
+        let mut result = match self.op {
+            SyncOp::Spawn | SyncOp::Join => format!("{:?} thread {}", self.op, self.object),
+            SyncOp::Fence => "Fence".to_string(),
+            _ => format!("{:?} 0x{:x}", self.op, self.object),
+        };
+        if let Some(ordering) = self.ordering {
+            result.push_str(&format!(" {:?}", ordering));
+        }
+        result
+    }
+}
+
+/// Execution state shared by all controlled threads.
+struct State {
+    scheduler: Scheduler,
+    /// The one thread allowed to run
+    running: usize,
+    finished: Vec<bool>,
+    switch_points_count: u64,
+    context_switches_count: u64,
+    /// Thread that ran each switch point
+    trace: Vec<usize>,
+    events: Vec<SyncEvent>,
+    /// OS threads of threads spawned during the execution
+    spawned: Vec<JoinHandle<()>>,
+    memory: Option<WeakMemory>,
+    checker: Option<MemoryChecker>,
+    failure: Option<String>,
+}
+
+impl State {
+    /// Step of the latest event, which memory issues refer to.
+    #[cfg(feature = "simulation")]
+    fn step(&self) -> u64 {
+        self.events.last().map_or(0, |event| event.step)
+    }
+
+    /// First runnable thread at or after `thread`, in index order.
+    fn runnable_from(&self, thread: usize, except: Option<usize>) -> Option<usize> {
+        let threads_count = self.finished.len();
+        (0..threads_count)
+            .map(|offset| (thread + offset) % threads_count)
+            .find(|&t| !self.finished[t] && Some(t) != except)
+    }
+
+    /// Hand control to `next`.
+    fn switch_to(&mut self, next: usize) {
+        if next != self.running {
+            self.context_switches_count += 1;
+        }
+        self.running = next;
+        self.scheduler.set_current_thread(next);
+    }
+
+    /// Stop the execution, keeping the first failure.
+    fn fail(&mut self, message: String) {
+        if self.failure.is_none() {
+            self.failure = Some(message);
+        }
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    turn: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A panicking thread never holds the lock, but be lenient anyway
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Block until it is `thread`'s turn, or unwind if the execution failed.
+    fn wait_turn<'a>(&'a self, mut state: MutexGuard<'a, State>, thread: usize) {
+        while state.running != thread && state.failure.is_none() {
+            state = self.turn.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        if state.failure.is_some() {
+            drop(state);
+            panic::resume_unwind(Box::new(Stopped));
+        }
+    }
+
+    /// Count a switch point, failing the execution past the limit.
+    fn count_switch_point(&self, state: &mut State, thread: usize) {
+        state.switch_points_count += 1;
+        state.trace.push(thread);
+        if state.switch_points_count > SWITCH_POINTS_MAX {
+            state.fail(format!(
+                "livelock: more than {} switch points",
+                SWITCH_POINTS_MAX
+            ));
+            self.turn.notify_all();
+        }
+    }
+
+    fn switch_point(&self, thread: usize) {
+        let mut state = self.lock();
+        self.count_switch_point(&mut state, thread);
+        let next = match state.scheduler.decide() {
+            ScheduleDecision::Continue => thread,
+            ScheduleDecision::Yield => state.runnable_from(thread + 1, None).unwrap_or(thread),
+            // The chosen thread may have finished: take the next one
+            ScheduleDecision::SwitchTo(chosen) => {
+                state.runnable_from(chosen, None).unwrap_or(thread)
+            }
+        };
+        state.switch_to(next);
+        self.turn.notify_all();
+        self.wait_turn(state, thread);
+    }
+
+    fn yield_now(&self, thread: usize) {
+        let mut state = self.lock();
+        self.count_switch_point(&mut state, thread);
+        let chosen = state.scheduler.force_switch();
+        match state.runnable_from(chosen, Some(thread)) {
+            Some(next) => state.switch_to(next),
+            None => {
+                state.fail(format!(
+                    "deadlock: thread {} waits with no other runnable thread",
+                    thread
+                ));
+            }
+        }
+        self.turn.notify_all();
+        self.wait_turn(state, thread);
+    }
+
+    fn record(&self, thread: usize, op: SyncOp, object: usize, ordering: Option<Ordering>) {
+        let mut state = self.lock();
+        if let Some(ref mut memory) = state.memory {
+            match op {
+                SyncOp::Lock | SyncOp::ReadLock => memory.lock(thread, object),
+                SyncOp::Unlock | SyncOp::ReadUnlock => memory.unlock(thread, object),
+                SyncOp::Spawn => memory.spawn(thread, object),
+                SyncOp::Join => memory.join(thread, object),
+                _ => {}
+            }
+        }
+        let step = state.events.len() as u64 + 1;
+        state.events.push(SyncEvent {
+            step,
+            thread,
+            op,
+            object,
+            ordering,
+        });
+    }
+
+    /// Register a new thread and start it on its own OS thread.
+    #[cfg(feature = "simulation")]
+    fn spawn(self: &Arc<Self>, body: ThreadBody<'static>) -> usize {
+        let mut state = self.lock();
+        let thread = state.scheduler.add_thread();
+        state.finished.push(false);
+        debug_assert_eq!(state.finished.len(), thread + 1);
+
+        let shared = Arc::clone(self);
+        let handle = std::thread::spawn(move || run_thread(shared, thread, body));
+        state.spawned.push(handle);
+        thread
+    }
+
+    /// Mark `thread` finished and pass control on.
+    fn finish(&self, thread: usize, panic_message: Option<String>) {
+        let mut state = self.lock();
+        state.finished[thread] = true;
+        state.scheduler.finish_thread(thread);
+        if let Some(message) = panic_message {
+            state.fail(format!("thread {} panicked: {}", thread, message));
+        } else if state.failure.is_none() {
+            let chosen = state.scheduler.force_switch();
+            if let Some(next) = state.runnable_from(chosen, None) {
+                state.switch_to(next);
+            }
+        }
+        self.turn.notify_all();
+    }
+}
+
+thread_local! {
+    /// The execution this OS thread belongs to, and its simulated index.
+    static CURRENT: RefCell<Option<(Arc<Shared>, usize)>> = const { RefCell::new(None) };
+}
+
+fn with_current<R>(f: impl FnOnce(&Arc<Shared>, usize) -> R) -> Option<R> {
+    // Never switch while unwinding: a second panic would abort
+    if std::thread::panicking() {
+        return None;
+    }
+    let current = CURRENT.with(|current| current.borrow().clone());
+    current.map(|(shared, thread)| f(&shared, thread))
+}
+
+/// Let the scheduler switch to another thread.
+///
+/// Call before every operation on shared memory. No-op outside a
+/// controlled thread.
+pub fn switch_point() {
+    with_current(|shared, thread| shared.switch_point(thread));
+}
+
+/// Switch point for a synchronization operation, recorded as a
+/// [`SyncEvent`] once the calling thread is scheduled again.
+///
+/// Call right before performing the operation. No-op outside a
+/// controlled thread.
+pub fn sync_point(op: SyncOp, object: usize, ordering: Option<Ordering>) {
+    with_current(|shared, thread| {
+        shared.switch_point(thread);
+        shared.record(thread, op, object, ordering);
+    });
+}
+
+/// Record a [`SyncEvent`] without a switch point, for operations that
+/// took effect after their switch point (a lock acquired after waiting).
+#[cfg(feature = "simulation")]
+pub(crate) fn record_event(op: SyncOp, object: usize, ordering: Option<Ordering>) {
+    with_current(|shared, thread| shared.record(thread, op, object, ordering));
+}
+
+/// Start `body` as a new controlled thread of the calling thread's
+/// execution, returning its index. Hands `body` back outside a
+/// controlled thread.
+#[cfg(feature = "simulation")]
+pub(crate) fn spawn(body: ThreadBody<'static>) -> Result<usize, ThreadBody<'static>> {
+    let current = CURRENT.with(|current| current.borrow().clone());
+    match current {
+        Some((shared, _)) => Ok(shared.spawn(body)),
+        None => Err(body),
+    }
+}
+
+/// Whether the controlled thread `thread` ran to completion.
+#[cfg(feature = "simulation")]
+pub(crate) fn is_finished(thread: usize) -> bool {
+    with_current(|shared, _| shared.lock().finished[thread]).unwrap_or(true)
+}
+
+/// Run `f` on the state of the calling thread's execution.
+#[cfg(feature = "simulation")]
+fn with_state<R>(f: impl FnOnce(&mut State, usize) -> R) -> Option<R> {
+    with_current(|shared, thread| f(&mut shared.lock(), thread))
+}
+
+/// Run `f` on the memory checker of the calling thread's execution, with
+/// the step of the latest event.
+#[cfg(feature = "simulation")]
+fn with_checker(state: &mut State, f: impl FnOnce(&mut MemoryChecker, u64)) {
+    let step = state.step();
+    if let Some(ref mut checker) = state.checker {
+        f(checker, step);
+    }
+}
+
+/// Value an atomic load at `object` reads, given its `latest` value.
+/// Loads of `pointer` atomics are remembered for ABA detection.
+#[cfg(feature = "simulation")]
+pub(crate) fn atomic_load(object: usize, order: Ordering, pointer: bool, latest: u64) -> u64 {
+    with_state(|state, thread| {
+        let value = match state.memory {
+            Some(ref mut memory) => memory.load(thread, object, order, latest),
+            None => latest,
+        };
+        with_checker(state, |checker, step| {
+            checker.check_use(object, step);
+            if pointer {
+                checker.pointer_read(thread, object, latest, value);
+            }
+        });
+        value
+    })
+    .unwrap_or(latest)
+}
+
+/// Report an atomic store of `value` over `previous`.
+#[cfg(feature = "simulation")]
+pub(crate) fn atomic_store(
+    object: usize,
+    order: Ordering,
+    pointer: bool,
+    previous: u64,
+    value: u64,
+) {
+    with_state(|state, thread| {
+        if let Some(ref mut memory) = state.memory {
+            memory.store(thread, object, order, previous, value);
+        }
+        with_checker(state, |checker, step| {
+            checker.check_use(object, step);
+            if pointer {
+                checker.pointer_written(thread, object, previous, value);
+            }
+        });
+    });
+}
+
+/// Report a successful read-modify-write of `previous` into `value`.
+#[cfg(feature = "simulation")]
+pub(crate) fn atomic_read_modify_write(
+    object: usize,
+    order: Ordering,
+    pointer: bool,
+    previous: u64,
+    value: u64,
+) {
+    with_state(|state, thread| {
+        if let Some(ref mut memory) = state.memory {
+            memory.read_modify_write(thread, object, order, previous, value);
+        }
+        with_checker(state, |checker, step| {
+            checker.check_use(object, step);
+            if pointer {
+                checker.pointer_written(thread, object, previous, value);
+            }
+        });
+    });
+}
+
+/// Report a successful compare-exchange of `current` into `new`, which
+/// is checked for ABA on `pointer` atomics.
+#[cfg(feature = "simulation")]
+pub(crate) fn atomic_compare_exchange(
+    object: usize,
+    order: Ordering,
+    pointer: bool,
+    current: u64,
+    new: u64,
+) {
+    with_state(|state, thread| {
+        if let Some(ref mut memory) = state.memory {
+            memory.read_modify_write(thread, object, order, current, new);
+        }
+        with_checker(state, |checker, step| {
+            checker.check_use(object, step);
+            if pointer {
+                checker.pointer_exchanged(thread, object, current, new, step);
+            }
+        });
+    });
+}
+
+/// Report a failed compare-exchange, which read `latest`.
+#[cfg(feature = "simulation")]
+pub(crate) fn atomic_read_latest(object: usize, order: Ordering, pointer: bool, latest: u64) {
+    with_state(|state, thread| {
+        if let Some(ref mut memory) = state.memory {
+            memory.read_latest(thread, object, order, latest);
+        }
+        with_checker(state, |checker, step| {
+            checker.check_use(object, step);
+            if pointer {
+                checker.pointer_read(thread, object, latest, latest);
+            }
+        });
+    });
+}
+
+#[cfg(feature = "simulation")]
+pub(crate) fn atomic_fence(order: Ordering) {
+    with_state(|state, thread| {
+        if let Some(ref mut memory) = state.memory {
+            memory.fence(thread, order);
+        }
+    });
+}
+
+/// Forget the store buffer of a dropped or mutably borrowed atomic.
+#[cfg(feature = "simulation")]
+pub(crate) fn forget_atomic(object: usize) {
+    with_state(|state, _| {
+        if let Some(ref mut memory) = state.memory {
+            memory.forget(object);
+        }
+        with_checker(state, |checker, _| checker.forget_location(object));
+    });
+}
+
+/// Switch point for a non-atomic access to the `UnsafeCell` at `object`,
+/// checked for data races.
+#[cfg(feature = "simulation")]
+pub(crate) fn cell_access(object: usize, write: bool) {
+    let op = if write { SyncOp::Write } else { SyncOp::Read };
+    sync_point(op, object, None);
+    with_state(|state, thread| {
+        let step = state.step();
+        if let (Some(memory), Some(checker)) = (state.memory.as_mut(), state.checker.as_mut()) {
+            let clock = memory.clock(thread);
+            if write {
+                checker.write(thread, clock, object, step);
+            } else {
+                checker.read(thread, clock, object, step);
+            }
+        }
+    });
+}
+
+/// Forget the accesses to a dropped `UnsafeCell`.
+#[cfg(feature = "simulation")]
+pub(crate) fn forget_cell(object: usize) {
+    with_state(|state, _| with_checker(state, |checker, _| checker.forget_cell(object)));
+}
+
+/// Report memory allocated at `address`.
+#[cfg(feature = "simulation")]
+pub(crate) fn allocated(address: usize, layout: std::alloc::Layout) {
+    sync_point(SyncOp::Alloc, address, None);
+    with_state(|state, _| {
+        with_checker(state, |checker, step| {
+            checker.allocate(address, layout, step)
+        });
+    });
+}
+
+/// Report memory freed at `address`. Returns whether the memory checker
+/// quarantined it, in which case the caller must not free it.
+#[cfg(feature = "simulation")]
+pub(crate) fn deallocated(address: usize) -> bool {
+    sync_point(SyncOp::Dealloc, address, None);
+    with_state(|state, _| {
+        let step = state.step();
+        state
+            .checker
+            .as_mut()
+            .is_some_and(|checker| checker.deallocate(address, step))
+    })
+    .unwrap_or(false)
+}
+
+/// Hand control to another runnable thread.
+///
+/// For waits that need another thread to make progress first. No-op
+/// outside a controlled thread.
+pub fn yield_now() {
+    with_current(|shared, thread| shared.yield_now(thread));
+}
+
+/// Index of the calling controlled thread, if any.
+#[must_use]
+pub fn current_thread() -> Option<usize> {
+    CURRENT.with(|current| current.borrow().as_ref().map(|(_, thread)| *thread))
+}
+
+/// Runs real threads one at a time under a seeded scheduler.
+pub struct ControlledRuntime {
+    scheduler: Scheduler,
+    memory: Option<WeakMemory>,
+    checker: Option<MemoryChecker>,
+}
+
+impl ControlledRuntime {
+    /// Create a runtime. The scheduler must schedule as many threads as
+    /// [`ControlledRuntime::run`] is given.
+    pub fn new(mut scheduler: Scheduler) -> Self {
+        scheduler.start_recording();
+        Self {
+            scheduler,
+            memory: None,
+            checker: None,
+        }
+    }
+
+    /// Simulate C11 weak memory for shim atomics, with `rng` choosing
+    /// which store each load reads.
+    pub fn with_weak_memory(mut self, rng: DeterministicRng) -> Self {
+        self.memory = Some(WeakMemory::new(rng));
+        self
+    }
+
+    /// Check shim accesses for data races, use after free, ABA and leaks
+    /// (see [`crate::memory_checker`]). Happens-before comes from the
+    /// memory model, so without weak memory a sequential one is added.
+    pub fn with_memory_checker(mut self) -> Self {
+        self.memory.get_or_insert_with(WeakMemory::sequential);
+        self.checker = Some(MemoryChecker::new());
+        self
+    }
+
+    /// Run the threads to completion, or until one fails.
+    pub fn run(self, threads: Vec<ThreadBody<'_>>) -> ControlledReport {
+        debug_assert_eq!(
+            threads.len(),
+            self.scheduler.threads_count(),
+            "Scheduler must schedule every thread"
+        );
+
+        let threads_count = threads.len();
+        let running = self.scheduler.current_thread();
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                scheduler: self.scheduler,
+                running,
+                finished: vec![false; threads_count],
+                switch_points_count: 0,
+                context_switches_count: 0,
+                trace: Vec::new(),
+                events: Vec::new(),
+                spawned: Vec::new(),
+                memory: self.memory,
+                checker: self.checker,
+                failure: None,
+            }),
+            turn: Condvar::new(),
+        });
+
+        std::thread::scope(|scope| {
+            for (thread, body) in threads.into_iter().enumerate() {
+                let shared = Arc::clone(&shared);
+                scope.spawn(move || run_thread(shared, thread, body));
+            }
+        });
+        // Spawned threads may spawn more, so take them one at a time
+        while let Some(handle) = shared.lock().spawned.pop() {
+            let _ = handle.join();
+        }
+
+        let mut state = shared.lock();
+        let memory_issues = state
+            .checker
+            .as_mut()
+            .map(MemoryChecker::finish)
+            .unwrap_or_default();
+        ControlledReport {
+            switch_points_count: state.switch_points_count,
+            context_switches_count: state.context_switches_count,
+            decisions: state.scheduler.recorded_decisions().to_vec(),
+            trace: state.trace.clone(),
+            events: state.events.clone(),
+            stale_loads_count: state
+                .memory
+                .as_ref()
+                .map_or(0, WeakMemory::stale_loads_count),
+            memory_issues,
+            failure: state.failure.clone(),
+        }
+    }
+}
+
+/// Body of one controlled OS thread.
+fn run_thread(shared: Arc<Shared>, thread: usize, body: ThreadBody<'_>) {
+    CURRENT.with(|current| *current.borrow_mut() = Some((Arc::clone(&shared), thread)));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let state = shared.lock();
+        shared.wait_turn(state, thread);
+        body();
+    }));
+
+    CURRENT.with(|current| *current.borrow_mut() = None);
+    let panic_message = match result {
+        Ok(()) => None,
+        Err(payload) if payload.is::<Stopped>() => None,
+        Err(payload) => Some(panic_message(payload.as_ref())),
+    };
+    shared.finish(thread, panic_message);
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Outcome of a controlled execution.
+#[derive(Debug, Clone)]
+pub struct ControlledReport {
+    /// Switch points reached, across all threads
+    pub switch_points_count: u64,
+    /// Times control moved to a different thread
+    pub context_switches_count: u64,
+    /// Scheduler decisions, forced switches included, for replay with
+    /// `Scheduler::set_script`
+    pub decisions: Vec<ScheduleDecision>,
+    /// Thread that reached each switch point
+    pub trace: Vec<usize>,
+    /// Synchronization operations reported through [`sync_point`]
+    pub events: Vec<SyncEvent>,
+    /// Atomic loads that returned a stale value under weak memory
+    pub stale_loads_count: u64,
+    /// Issues found by the memory checker
+    pub memory_issues: Vec<MemoryIssue>,
+    /// First panic, deadlock or livelock
+    pub failure: Option<String>,
+}
+
+impl ControlledReport {
+    /// Whether every thread ran to completion without memory issues.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.failure.is_none() && self.memory_issues.is_empty()
+    }
+
+    /// Format for display.
+    pub fn format(&self) -> String {
+        let mut result = format!(
+            "switch_points={} switches={} stale_loads={}",
+            self.switch_points_count, self.context_switches_count, self.stale_loads_count
+        );
+        if let Some(ref failure) = self.failure {
+            result.push_str(&format!("\n  Failure: {}", failure));
+        }
+        for issue in &self.memory_issues {
+            result.push_str(&format!("\n  Memory issue: {}", issue));
+        }
+        result
+    }
+
+    /// Counterexample of the execution run under DST seed `seed`: every
+    /// event as a thread action, and the memory issues at their steps.
+    pub fn counterexample(&self, seed: u64) -> Counterexample {
+        let mut counterexample = Counterexample::with_seed(seed);
+        counterexample.description = self
+            .failure
+            .clone()
+            .or_else(|| self.memory_issues.first().map(ToString::to_string));
+        for event in &self.events {
+            counterexample.add_action(ThreadAction {
+                thread_id: event.thread as u64,
+                step: event.step,
+                action: event.format(),
+                success: event.op != SyncOp::CasFailure,
+            });
+        }
+        for issue in &self.memory_issues {
+            counterexample.add_memory_issue(issue.clone());
+        }
+        counterexample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::DeterministicRng;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+    const INCREMENTS: u64 = 5;
+
+    /// Two threads incrementing a counter with a racy load/store.
+    fn racy_increments(seed: u64) -> (u64, ControlledReport) {
+        let counter = AtomicU64::new(0);
+        let increment = || {
+            for _ in 0..INCREMENTS {
+                switch_point();
+                let value = counter.load(Ordering::SeqCst);
+                switch_point();
+                counter.store(value + 1, Ordering::SeqCst);
+            }
+        };
+        let scheduler = Scheduler::new(DeterministicRng::new(seed), 2, 0.5);
+        let report =
+            ControlledRuntime::new(scheduler).run(vec![Box::new(increment), Box::new(increment)]);
+        (counter.load(Ordering::SeqCst), report)
+    }
+
+    #[test]
+    fn test_finds_lost_update() {
+        let lost = (1..=20).filter(|&seed| racy_increments(seed).0 < 2 * INCREMENTS);
+        assert!(lost.count() > 0, "no seed interleaved inside the increment");
+    }
+
+    #[test]
+    fn test_same_seed_same_interleaving() {
+        for seed in 1..=10 {
+            let (value1, report1) = racy_increments(seed);
+            let (value2, report2) = racy_increments(seed);
+            assert_eq!(value1, value2);
+            assert_eq!(report1.trace, report2.trace);
+            assert_eq!(report1.decisions, report2.decisions);
+        }
+    }
+
+    #[test]
+    fn test_scripted_replay() {
+        let (value, report) = racy_increments(3);
+
+        // Replay the recorded decisions under a different seed
+        let counter = AtomicU64::new(0);
+        let increment = || {
+            for _ in 0..INCREMENTS {
+                switch_point();
+                let value = counter.load(Ordering::SeqCst);
+                switch_point();
+                counter.store(value + 1, Ordering::SeqCst);
+            }
+        };
+        let mut scheduler = Scheduler::new(DeterministicRng::new(99), 2, 0.5);
+        scheduler.set_script(report.decisions.clone());
+        let replay =
+            ControlledRuntime::new(scheduler).run(vec![Box::new(increment), Box::new(increment)]);
+
+        assert_eq!(counter.load(Ordering::SeqCst), value);
+        assert_eq!(replay.trace, report.trace);
+    }
+
+    /// Four threads taking a spin lock, yielding while it is held.
+    fn spin_lock_threads(scheduler: Scheduler) -> ControlledReport {
+        let locked = AtomicBool::new(false);
+        let acquisitions = AtomicU64::new(0);
+        let body = || {
+            for _ in 0..3 {
+                switch_point();
+                while locked
+                    .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_err()
+                {
+                    yield_now();
+                }
+                switch_point();
+                acquisitions.fetch_add(1, Ordering::SeqCst);
+                locked.store(false, Ordering::SeqCst);
+            }
+        };
+        let report = ControlledRuntime::new(scheduler).run(vec![
+            Box::new(body),
+            Box::new(body),
+            Box::new(body),
+            Box::new(body),
+        ]);
+        assert!(report.passed(), "{}", report.format());
+        assert_eq!(acquisitions.load(Ordering::SeqCst), 12);
+        report
+    }
+
+    #[test]
+    fn test_scripted_replay_with_yields() {
+        for seed in 1..=10 {
+            let report = spin_lock_threads(Scheduler::new(DeterministicRng::new(seed), 4, 0.5));
+            assert!(
+                report.decisions.len() as u64 > report.switch_points_count,
+                "thread exits must be recorded"
+            );
+
+            for replay_seed in [seed, seed + 100] {
+                let mut scheduler = Scheduler::new(DeterministicRng::new(replay_seed), 4, 0.5);
+                scheduler.set_script(report.decisions.clone());
+                let replay = spin_lock_threads(scheduler);
+                assert_eq!(replay.trace, report.trace, "seed {}", seed);
+                assert_eq!(replay.decisions, report.decisions, "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cas_loop_never_loses_updates() {
+        for seed in 1..=20 {
+            let counter = AtomicU64::new(0);
+            let increment = || {
+                for _ in 0..INCREMENTS {
+                    loop {
+                        switch_point();
+                        let value = counter.load(Ordering::SeqCst);
+                        switch_point();
+                        if counter
+                            .compare_exchange(value, value + 1, Ordering::SeqCst, Ordering::SeqCst)
+                            .is_ok()
+                        {
+                            break;
+                        }
+                    }
+                }
+            };
+            let scheduler = Scheduler::new(DeterministicRng::new(seed), 3, 0.5);
+            let report = ControlledRuntime::new(scheduler).run(vec![
+                Box::new(increment),
+                Box::new(increment),
+                Box::new(increment),
+            ]);
+            assert!(report.passed(), "{}", report.format());
+            assert_eq!(counter.load(Ordering::SeqCst), 3 * INCREMENTS);
+        }
+    }
+
+    #[test]
+    fn test_panic_stops_all_threads() {
+        let scheduler = Scheduler::new(DeterministicRng::new(5), 2, 0.5);
+        let report = ControlledRuntime::new(scheduler).run(vec![
+            Box::new(|| loop {
+                switch_point();
+            }),
+            Box::new(|| {
+                switch_point();
+                panic!("invariant violated");
+            }),
+        ]);
+
+        let failure = report.failure.unwrap();
+        assert!(
+            failure.contains("thread 1 panicked: invariant violated"),
+            "{}",
+            failure
+        );
+    }
+
+    #[test]
+    fn test_spin_on_finished_thread_is_deadlock() {
+        let flag = AtomicBool::new(false);
+        let scheduler = Scheduler::new(DeterministicRng::new(5), 2, 0.5);
+        let report = ControlledRuntime::new(scheduler).run(vec![
+            Box::new(|| {
+                // Nobody ever sets the flag
+                while !flag.load(Ordering::SeqCst) {
+                    yield_now();
+                }
+            }),
+            Box::new(switch_point),
+        ]);
+
+        let failure = report.failure.unwrap();
+        assert!(failure.starts_with("deadlock: thread 0"), "{}", failure);
+    }
+
+    #[test]
+    fn test_spin_waits_for_other_thread() {
+        let flag = AtomicBool::new(false);
+        let scheduler = Scheduler::new(DeterministicRng::new(5), 2, 0.0);
+        let report = ControlledRuntime::new(scheduler).run(vec![
+            Box::new(|| {
+                while !flag.load(Ordering::SeqCst) {
+                    yield_now();
+                }
+            }),
+            Box::new(|| {
+                switch_point();
+                flag.store(true, Ordering::SeqCst);
+            }),
+        ]);
+        assert!(report.passed(), "{}", report.format());
+    }
+
+    #[test]
+    fn test_hooks_are_noops_outside_runtime() {
+        switch_point();
+        yield_now();
+        assert_eq!(current_thread(), None);
+    }
+}
@@ -6,7 +6,8 @@
 use crate::clock::SimClock;
 use crate::fault::{FaultConfig, FaultInjector};
 use crate::random::DeterministicRng;
-use crate::scheduler::Scheduler;
+use crate::scheduler::{Scheduler, YIELD_PROBABILITY_DEFAULT};
+use crate::strategy::StrategyConfig;
 
 /// Complete DST environment.
 ///
@@ -81,8 +82,9 @@ impl DstEnv {
         }
     }
 
-    /// Create with scheduler for multi-threaded tests.
-    pub fn with_scheduler(seed: u64, threads_count: usize) -> Self {
+    /// Create with scheduler for multi-threaded tests, picking threads with
+    /// `strategy`.
+    pub fn with_scheduler(seed: u64, threads_count: usize, strategy: StrategyConfig) -> Self {
         debug_assert!(seed != 0, "Seed should not be zero");
         debug_assert!(threads_count > 0, "Must have at least one thread");
 
@@ -95,7 +97,8 @@ impl DstEnv {
         let fault_rng = DeterministicRng::new(fault_seed);
         let fault = FaultInjector::with_default_config(fault_rng);
         let sched_rng = DeterministicRng::new(sched_seed);
-        let scheduler = Scheduler::with_defaults(sched_rng, threads_count);
+        let strategy = strategy.build(seed, threads_count, YIELD_PROBABILITY_DEFAULT);
+        let scheduler = Scheduler::with_strategy(sched_rng, threads_count, strategy);
 
         Self {
             seed,
@@ -239,7 +242,7 @@ mod tests {
 
     #[test]
     fn test_with_scheduler() {
-        let mut env = DstEnv::with_scheduler(12345, 4);
+        let mut env = DstEnv::with_scheduler(12345, 4, StrategyConfig::Random);
 
         assert!(env.scheduler().is_some());
         let sched = env.scheduler().unwrap();
@@ -7,9 +7,10 @@
 //! - Bit flips (memory corruption)
 
 use crate::random::DeterministicRng;
+use serde::{Deserialize, Serialize};
 
 /// Configuration for fault injection.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FaultConfig {
     /// Probability of a random failure (0.0 to 1.0)
     pub failure_probability: f64,
@@ -19,10 +20,19 @@ pub struct FaultConfig {
     pub delay_ns_max: u64,
     /// Probability of a crash
     pub crash_probability: f64,
+    /// Probability of a garbage collection pass before an operation
+    /// (SSI `Vacuum` fault point)
+    #[serde(default = "vacuum_probability_default")]
+    pub vacuum_probability: f64,
     /// Whether fault injection is enabled
     pub enabled: bool,
 }
 
+/// Vacuum probability of configs saved before it was configurable.
+fn vacuum_probability_default() -> f64 {
+    FaultConfig::default().vacuum_probability
+}
+
 impl Default for FaultConfig {
     fn default() -> Self {
         Self {
@@ -30,6 +40,7 @@ impl Default for FaultConfig {
             delay_probability: 0.05,    // 5% chance
             delay_ns_max: 10_000_000,   // 10ms max delay
             crash_probability: 0.001,   // 0.1% chance
+            vacuum_probability: 0.1,    // 10% chance
             enabled: true,
         }
     }
@@ -44,6 +55,7 @@ impl FaultConfig {
             delay_probability: 0.0,
             delay_ns_max: 0,
             crash_probability: 0.0,
+            vacuum_probability: 0.0,
             enabled: false,
         }
     }
@@ -56,6 +68,7 @@ impl FaultConfig {
             delay_probability: 0.2,     // 20% chance
             delay_ns_max: 100_000_000,  // 100ms max delay
             crash_probability: 0.01,    // 1% chance
+            vacuum_probability: 0.1,    // 10% chance
             enabled: true,
         }
     }
@@ -68,6 +81,7 @@ impl FaultConfig {
             delay_probability: 0.2,
             delay_ns_max: 50_000_000,
             crash_probability: 0.0,
+            vacuum_probability: 0.1,
             enabled: true,
         }
     }
@@ -103,6 +117,10 @@ impl FaultInjector {
             config.crash_probability >= 0.0 && config.crash_probability <= 1.0,
             "Crash probability must be in [0.0, 1.0]"
         );
+        debug_assert!(
+            config.vacuum_probability >= 0.0 && config.vacuum_probability <= 1.0,
+            "Vacuum probability must be in [0.0, 1.0]"
+        );
 
         Self {
             rng,
@@ -255,6 +273,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vacuum_probability_defaults_when_missing() {
+        let json = r#"{"failure_probability":0.0,"delay_probability":0.0,"delay_ns_max":0,"crash_probability":0.0,"enabled":true}"#;
+        let config: FaultConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.vacuum_probability, FaultConfig::default().vacuum_probability);
+    }
+
     #[test]
     fn test_fault_probability() {
         let rng = DeterministicRng::new(12345);
@@ -296,7 +321,7 @@ mod tests {
             let delay = injector.maybe_delay_ns();
             assert!(delay.is_some());
             let d = delay.unwrap();
-            assert!(d >= 1 && d <= 1_000_000);
+            assert!((1..=1_000_000).contains(&d));
         }
     }
 
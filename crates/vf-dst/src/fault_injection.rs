@@ -29,8 +29,9 @@
 //!
 //! Generated code is UNCHANGED. Faults happen in the test harness.
 
-use crate::fault::FaultInjector;
+use crate::fault::{FaultConfig, FaultInjector};
 use crate::random::DeterministicRng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 /// Fault injection points (between operations, not inside).
@@ -90,10 +91,18 @@ pub trait DstTestableStack: Send + Sync {
 impl<S: DstTestableStack> DstRunner<S> {
     /// Create a new DST runner.
     pub fn new(seed: u64) -> Self {
+        Self::with_fault_config(seed, FaultConfig::default())
+    }
+
+    /// Create a DST runner with an explicit fault configuration.
+    ///
+    /// Used to replay saved counterexamples under the faults they were
+    /// found with.
+    pub fn with_fault_config(seed: u64, fault_config: FaultConfig) -> Self {
         let rng = DeterministicRng::new(seed);
         let fault_injector = FaultInjector::new(
             DeterministicRng::new(seed.wrapping_add(1)),
-            crate::fault::FaultConfig::default(),
+            fault_config,
         );
 
         Self {
@@ -114,6 +123,11 @@ impl<S: DstTestableStack> DstRunner<S> {
         self.seed
     }
 
+    /// Get the fault configuration for reproduction.
+    pub fn fault_config(&self) -> &FaultConfig {
+        self.fault_injector.config()
+    }
+
     /// Push with fault injection at boundaries.
     ///
     /// The stack.push() call is PURE - no instrumentation.
@@ -258,7 +272,16 @@ pub fn run_dst_scenario<S: DstTestableStack>(
     seed: u64,
     operations: Vec<DstOp>,
 ) -> DstResult {
-    let mut runner: DstRunner<S> = DstRunner::new(seed);
+    run_dst_scenario_with_config::<S>(seed, FaultConfig::default(), operations)
+}
+
+/// Run a DST scenario under the given fault configuration.
+pub fn run_dst_scenario_with_config<S: DstTestableStack>(
+    seed: u64,
+    fault_config: FaultConfig,
+    operations: Vec<DstOp>,
+) -> DstResult {
+    let mut runner: DstRunner<S> = DstRunner::with_fault_config(seed, fault_config);
     let mut errors = Vec::new();
 
     for op in operations {
@@ -288,7 +311,7 @@ pub fn run_dst_scenario<S: DstTestableStack>(
 }
 
 /// DST operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DstOp {
     Push(u64),
     Pop,
@@ -5,9 +5,17 @@
 //! - Deterministic scheduling
 //! - Fault injection
 //! - Invariant checking at each step
+//!
+//! `run_concurrent` interleaves whole operations from one OS thread;
+//! `run_controlled` runs each thread for real under a
+//! [`ControlledRuntime`], switching inside operations.
 
-use crate::{DstEnv, FaultConfig, ScheduleDecision};
+use crate::controlled::{ControlledRuntime, ThreadBody};
+use crate::{DstEnv, FaultConfig, ScheduleDecision, Scheduler, StrategyConfig};
+use std::any::Any;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use vf_core::counterexample::Counterexample;
+use vf_core::{CheckerRegistry, TlaSpec};
 
 /// Configuration for DST test harness.
 #[derive(Debug, Clone)]
@@ -18,10 +26,17 @@ pub struct HarnessConfig {
     pub operations_per_thread: u64,
     /// Probability of context switch at yield points
     pub yield_probability: f64,
+    /// How the scheduler picks threads
+    pub strategy: StrategyConfig,
     /// Fault injection configuration
     pub fault_config: FaultConfig,
     /// Check invariants after every N operations (0 = never)
     pub invariant_check_interval: u64,
+    /// Simulate C11 weak memory in `run_controlled`
+    pub weak_memory: bool,
+    /// Check for data races, use after free, ABA and leaks in
+    /// `run_controlled`
+    pub check_memory: bool,
 }
 
 impl Default for HarnessConfig {
@@ -30,8 +45,11 @@ impl Default for HarnessConfig {
             threads_count: 4,
             operations_per_thread: 100,
             yield_probability: 0.2,
+            strategy: StrategyConfig::Random,
             fault_config: FaultConfig::default(),
             invariant_check_interval: 10,
+            weak_memory: false,
+            check_memory: false,
         }
     }
 }
@@ -43,8 +61,11 @@ impl HarnessConfig {
             threads_count: 8,
             operations_per_thread: 1000,
             yield_probability: 0.3,
+            strategy: StrategyConfig::Random,
             fault_config: FaultConfig::aggressive(),
             invariant_check_interval: 100,
+            weak_memory: false,
+            check_memory: false,
         }
     }
 
@@ -54,8 +75,11 @@ impl HarnessConfig {
             threads_count: 2,
             operations_per_thread: 50,
             yield_probability: 0.1,
+            strategy: StrategyConfig::Random,
             fault_config: FaultConfig::none(),
             invariant_check_interval: 10,
+            weak_memory: false,
+            check_memory: false,
         }
     }
 }
@@ -88,6 +112,13 @@ pub struct HarnessResult {
     pub all_invariants_held: bool,
     /// First violation (if any)
     pub first_violation: Option<String>,
+    /// Failing `run_controlled` execution, with its memory issues
+    pub counterexample: Option<Counterexample>,
+    /// Scheduler decisions of a `run_controlled` execution, for
+    /// `DstHarness::set_schedule`
+    pub schedule: Vec<ScheduleDecision>,
+    /// Thread at each switch point of a `run_controlled` execution
+    pub trace: Vec<usize>,
 }
 
 /// DST test harness for concurrent testing.
@@ -101,6 +132,11 @@ pub struct DstHarness {
     context_switches_count: AtomicU64,
     invariant_checks_count: AtomicU64,
     violation: std::sync::Mutex<Option<String>>,
+    counterexample: Option<Counterexample>,
+    /// Decisions `run_controlled` replays before the strategy takes over
+    script: Vec<ScheduleDecision>,
+    schedule: Vec<ScheduleDecision>,
+    trace: Vec<usize>,
     stopped: AtomicBool,
 }
 
@@ -115,7 +151,7 @@ impl DstHarness {
             config.threads_count
         );
 
-        let env = DstEnv::with_scheduler(seed, config.threads_count);
+        let env = DstEnv::with_scheduler(seed, config.threads_count, config.strategy);
 
         Self {
             env,
@@ -124,6 +160,10 @@ impl DstHarness {
             context_switches_count: AtomicU64::new(0),
             invariant_checks_count: AtomicU64::new(0),
             violation: std::sync::Mutex::new(None),
+            counterexample: None,
+            script: Vec::new(),
+            schedule: Vec::new(),
+            trace: Vec::new(),
             stopped: AtomicBool::new(false),
         }
     }
@@ -133,6 +173,15 @@ impl DstHarness {
         self.env.seed()
     }
 
+    /// Replay `schedule` in the next `run_controlled`, before the strategy
+    /// makes its own decisions.
+    ///
+    /// Used to re-execute a saved counterexample with the interleaving it
+    /// was found with (see [`crate::replay::replay_controlled`]).
+    pub fn set_schedule(&mut self, schedule: Vec<ScheduleDecision>) {
+        self.script = schedule;
+    }
+
     /// Get the environment for custom operations.
     pub fn env(&mut self) -> &mut DstEnv {
         &mut self.env
@@ -252,6 +301,7 @@ impl DstHarness {
                 }
                 // Force switch to another thread
                 if let Some(scheduler) = self.env.scheduler() {
+                    scheduler.finish_thread(current);
                     scheduler.force_switch();
                 }
                 continue;
@@ -293,6 +343,84 @@ impl DstHarness {
         self.build_result()
     }
 
+    /// Run a concurrent test on real threads under controlled scheduling.
+    ///
+    /// Each thread calls `execute(thread, step)` for every step on its own
+    /// OS thread, and context switches happen at the instrumented
+    /// operations inside it (see [`crate::controlled`]). Another thread may
+    /// be mid-operation at any switch point, so invariants are only checked
+    /// once every thread has finished. With `weak_memory` set, loads of
+    /// `sync` atomics may also return stale values (see
+    /// [`crate::weak_memory`]). With `check_memory` set, memory issues
+    /// found by [`crate::memory_checker`] are violations, and the result
+    /// carries them in its counterexample.
+    pub fn run_controlled<R, I>(&mut self, execute: R, mut check_invariants: I) -> HarnessResult
+    where
+        R: Fn(usize, u64) -> Result<(), String> + Sync,
+        I: FnMut() -> Result<(), String>,
+    {
+        let threads_count = self.config.threads_count;
+        let ops_per_thread = self.config.operations_per_thread;
+        let strategy = self.config.strategy.build(
+            self.env.seed(),
+            threads_count,
+            self.config.yield_probability,
+        );
+        let mut scheduler = Scheduler::with_strategy(self.env.fork_rng(), threads_count, strategy);
+        scheduler.set_script(std::mem::take(&mut self.script));
+        let mut runtime = ControlledRuntime::new(scheduler);
+        if self.config.weak_memory {
+            runtime = runtime.with_weak_memory(self.env.fork_rng());
+        }
+        if self.config.check_memory {
+            runtime = runtime.with_memory_checker();
+        }
+
+        let harness = &*self;
+        let execute = &execute;
+        let threads: Vec<ThreadBody<'_>> = (0..threads_count)
+            .map(|thread| -> ThreadBody<'_> {
+                Box::new(move || {
+                    for step in 0..ops_per_thread {
+                        if harness.is_stopped() {
+                            break;
+                        }
+                        if let Err(e) = execute(thread, step) {
+                            harness.stop_with_violation(format!("Thread {}: {}", thread, e));
+                            break;
+                        }
+                        harness.record_operation();
+                    }
+                })
+            })
+            .collect();
+        let report = runtime.run(threads);
+
+        self.context_switches_count
+            .fetch_add(report.context_switches_count, Ordering::Relaxed);
+        if let Some(ref failure) = report.failure {
+            self.stop_with_violation(failure.clone());
+        }
+        for issue in &report.memory_issues {
+            self.stop_with_violation(issue.to_string());
+        }
+        if !report.passed() {
+            self.counterexample = Some(report.counterexample(self.env.seed()));
+        }
+        self.schedule = report.decisions;
+        self.trace = report.trace;
+
+        // Final invariant check
+        if !self.is_stopped() {
+            self.record_invariant_check();
+            if let Err(e) = check_invariants() {
+                self.stop_with_violation(e);
+            }
+        }
+
+        self.build_result()
+    }
+
     fn build_result(&mut self) -> HarnessResult {
         let violation = self.violation.lock().unwrap().clone();
 
@@ -304,6 +432,9 @@ impl DstHarness {
             invariant_checks_count: self.invariant_checks_count.load(Ordering::Relaxed),
             all_invariants_held: violation.is_none(),
             first_violation: violation,
+            counterexample: self.counterexample.take(),
+            schedule: std::mem::take(&mut self.schedule),
+            trace: std::mem::take(&mut self.trace),
         }
     }
 }
@@ -335,9 +466,32 @@ impl HarnessResult {
     }
 }
 
+/// Check the invariants `spec` maps to `dst` with the registered checkers.
+///
+/// Meant for the invariant closure of [`DstHarness::run_concurrent`]:
+/// returns the first violation with its TLA+ line, or an error if no
+/// checker is bound to the spec for the target type.
+pub fn check_spec_invariants<T: Any>(
+    registry: &CheckerRegistry,
+    spec: &TlaSpec,
+    target: &T,
+    seed: u64,
+) -> Result<(), String> {
+    let results = registry
+        .check_evaluator(spec, "dst", target, Some(seed))
+        .map_err(|e| e.to_string())?;
+    match results.into_iter().find(|r| !r.holds) {
+        Some(failure) => Err(failure.format_status()),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::RefCell;
+    use vf_core::invariants::{RingBufferProperties, RingBufferPropertyChecker};
+    use vf_core::PropertyChecker;
 
     #[test]
     fn test_harness_single_threaded() {
@@ -398,7 +552,7 @@ mod tests {
         };
         let mut harness = DstHarness::new(12345, config);
 
-        let mut thread_counters = vec![0u64; 2];
+        let mut thread_counters = [0u64; 2];
 
         let result = harness.run_concurrent(
             |_env, _thread, step| Some(step),
@@ -414,4 +568,201 @@ mod tests {
         assert_eq!(thread_counters[1], 10);
         assert!(result.context_switches_count > 0);
     }
+
+    #[test]
+    fn test_harness_controlled_finds_lost_update() {
+        use crate::controlled::switch_point;
+
+        let config = HarnessConfig {
+            threads_count: 2,
+            operations_per_thread: 5,
+            yield_probability: 0.5,
+            ..HarnessConfig::quick()
+        };
+
+        // Load and store with a switch point in between, like an unchecked CAS
+        let run = |seed| {
+            let counter = AtomicU64::new(0);
+            let mut harness = DstHarness::new(seed, config.clone());
+            harness.run_controlled(
+                |_thread, _step| {
+                    switch_point();
+                    let value = counter.load(Ordering::SeqCst);
+                    switch_point();
+                    counter.store(value + 1, Ordering::SeqCst);
+                    Ok(())
+                },
+                || match counter.load(Ordering::SeqCst) {
+                    10 => Ok(()),
+                    n => Err(format!("lost update: counter is {}", n)),
+                },
+            )
+        };
+
+        let results: Vec<HarnessResult> = (1..=20).map(run).collect();
+        assert!(results.iter().all(|r| r.operations_count == 10));
+        assert!(results.iter().any(|r| r.context_switches_count > 0));
+        let failure = results.iter().find(|r| !r.all_invariants_held).unwrap();
+        assert!(failure.format().contains("lost update"), "{}", failure.format());
+    }
+
+    #[test]
+    #[cfg(feature = "simulation")]
+    fn test_harness_controlled_reports_data_race() {
+        use crate::sync::cell::UnsafeCell;
+        use vf_core::counterexample::MemoryIssue;
+
+        /// Counter both threads update with no synchronization at all
+        struct Racy(UnsafeCell<u64>);
+        unsafe impl Sync for Racy {}
+
+        let run = |seed, check_memory| {
+            let counter = &Racy(UnsafeCell::new(0));
+            let config = HarnessConfig {
+                threads_count: 2,
+                operations_per_thread: 3,
+                yield_probability: 0.5,
+                check_memory,
+                ..HarnessConfig::quick()
+            };
+            let mut harness = DstHarness::new(seed, config);
+            harness.run_controlled(
+                move |_thread, _step| {
+                    let value = counter.0.with(|value| unsafe { *value });
+                    counter.0.with_mut(|slot| unsafe { *slot = value + 1 });
+                    Ok(())
+                },
+                || Ok(()),
+            )
+        };
+
+        for seed in 1..=5 {
+            let result = run(seed, true);
+            assert!(!result.all_invariants_held);
+            assert!(result.format().contains("Data race"), "{}", result.format());
+            let counterexample = result.counterexample.unwrap();
+            assert_eq!(counterexample.dst_seed, Some(seed));
+            assert!(matches!(
+                counterexample.memory_issues[..],
+                [MemoryIssue::DataRace { .. }]
+            ));
+
+            // Without the checker the race goes unnoticed
+            let result = run(seed, false);
+            assert!(result.all_invariants_held, "{}", result.format());
+            assert!(result.counterexample.is_none());
+        }
+    }
+
+    #[test]
+    fn test_harness_strategy_picks_threads() {
+        use crate::controlled::switch_point;
+        use std::sync::Mutex;
+
+        let config = |strategy| HarnessConfig {
+            threads_count: 2,
+            operations_per_thread: 3,
+            strategy,
+            ..HarnessConfig::quick()
+        };
+
+        let mut order = Vec::new();
+        let mut harness = DstHarness::new(1, config(StrategyConfig::RoundRobin { quantum: 1 }));
+        let result = harness.run_concurrent(
+            |_env, _thread, step| Some(step),
+            |_env, thread, _op| {
+                order.push(thread);
+                Ok(())
+            },
+            || Ok(()),
+        );
+        assert!(result.all_invariants_held);
+        assert_eq!(order, vec![0, 1, 0, 1, 0, 1]);
+
+        // No preemptions: each thread runs to completion
+        let order = Mutex::new(Vec::new());
+        let strategy = StrategyConfig::PreemptionBounded { bound: 0, steps_estimate: 10 };
+        let mut harness = DstHarness::new(1, config(strategy));
+        let result = harness.run_controlled(
+            |thread, _step| {
+                switch_point();
+                order.lock().unwrap().push(thread);
+                Ok(())
+            },
+            || Ok(()),
+        );
+        assert!(result.all_invariants_held);
+        assert_eq!(*order.lock().unwrap(), vec![0, 0, 0, 1, 1, 1]);
+    }
+
+    /// Unbounded buffer that drops every message divisible by `drop_every`.
+    struct LossyBuffer {
+        produced: Vec<u64>,
+        contents: Vec<u64>,
+        drop_every: u64,
+    }
+
+    impl RingBufferProperties for LossyBuffer {
+        fn produced_messages(&self) -> Vec<u64> {
+            self.produced.clone()
+        }
+
+        fn consumed_messages(&self) -> Vec<u64> {
+            Vec::new()
+        }
+
+        fn current_contents(&self) -> Vec<u64> {
+            self.contents.clone()
+        }
+
+        fn capacity(&self) -> u64 {
+            u64::MAX
+        }
+    }
+
+    #[test]
+    fn test_check_spec_invariants_from_registry() {
+        let spec = TlaSpec::parse(
+            "---- MODULE ring_buffer ----\n\
+             * Line 47: NoLostMessages -> stateright, dst\n====",
+        )
+        .unwrap();
+        let registry = CheckerRegistry::builtin().with_binding::<LossyBuffer, _>(
+            "ring_buffer",
+            |buffer, seed| {
+                let checker = RingBufferPropertyChecker::new(buffer);
+                match seed {
+                    Some(seed) => checker.with_seed(seed).check_all(),
+                    None => checker.check_all(),
+                }
+            },
+        );
+        let buffer = RefCell::new(LossyBuffer {
+            produced: Vec::new(),
+            contents: Vec::new(),
+            drop_every: 7,
+        });
+
+        let mut harness = DstHarness::new(12345, HarnessConfig::quick());
+        let result = harness.run_concurrent(
+            |_env, thread, step| Some(thread as u64 * 1000 + step + 1),
+            |_env, _thread, msg| {
+                let mut buffer = buffer.borrow_mut();
+                buffer.produced.push(msg);
+                if msg % buffer.drop_every != 0 {
+                    buffer.contents.push(msg);
+                }
+                Ok(())
+            },
+            || check_spec_invariants(&registry, &spec, &*buffer.borrow(), 12345),
+        );
+
+        assert!(!result.all_invariants_held);
+        let violation = result.first_violation.unwrap();
+        assert!(violation.contains("NoLostMessages"), "{}", violation);
+        assert!(violation.contains("ring_buffer.tla:47"), "{}", violation);
+
+        let unbound = check_spec_invariants(&registry, &spec, &0u64, 12345);
+        assert!(unbound.unwrap_err().starts_with("no checker bound"));
+    }
 }
@@ -1,5 +1,8 @@
 //! DST-instrumented traits for lock-free data structures.
 //!
+//! Deprecated: code written against [`crate::sync`] gets the same yield
+//! points without depending on DST.
+//!
 //! Generated code must implement these traits to be testable with DST oracles.
 //! The key insight: yield points at CAS operations allow DST to control interleaving.
 //!
@@ -9,7 +9,18 @@
 //! ## Harnesses
 //!
 //! - `fault_injection`: Lock-free structures (Treiber Stack)
-//! - `ssi_harness`: Lock-based protocols (SSI transactions)
+//! - `ssi_harness`: Lock-based protocols (SSI, 2PL and MVCC transactions)
+//! - `controlled`: Real threads run one at a time, switching at
+//!   instrumented operations (`DstHarness::run_controlled`)
+//! - `sync`: Drop-in `std::sync`/`std::thread` shim whose operations are
+//!   the switch points, optionally with C11 weak memory (`weak_memory`)
+//!   and race, use-after-free, ABA and leak detection (`memory_checker`)
+//!
+//! The harnesses pick threads with a `strategy`: uniform random, PCT,
+//! preemption-bounded enumeration or round-robin.
+//!
+//! Failing runs can be shrunk to a minimal counterexample with `shrink`,
+//! then saved as JSON and replayed later with `replay`.
 //!
 //! ## Usage
 //!
@@ -41,31 +52,42 @@
 //! ```
 
 pub mod clock;
+pub mod controlled;
 pub mod env;
 pub mod fault;
 pub mod fault_injection;
 pub mod harness;
 pub mod loom_oracle;
+pub mod memory_checker;
 pub mod oracle_scheduler;
 pub mod random;
+pub mod replay;
 pub mod scheduler;
+pub mod shrink;
 pub mod ssi_harness;
 pub mod ssi_oracle;
+pub mod strategy;
+pub mod sync;
+pub mod weak_memory;
 
-// Deprecated - violates "code is disposable" principle
+// Deprecated - violates "code is disposable" principle; use `sync` instead
 #[doc(hidden)]
 pub mod instrumented;
 
 pub use clock::SimClock;
+pub use controlled::{ControlledReport, ControlledRuntime, SyncEvent, SyncOp};
 pub use env::DstEnv;
 pub use fault::{FaultConfig, FaultInjector};
-pub use fault_injection::{DstRunner, DstTestableStack, DstOp, FaultPoint, FaultType, run_dst_scenario};
-pub use harness::{DstHarness, HarnessConfig, HarnessResult};
+pub use fault_injection::{DstRunner, DstTestableStack, DstOp, FaultPoint, FaultType, run_dst_scenario, run_dst_scenario_with_config};
+pub use harness::{check_spec_invariants, DstHarness, HarnessConfig, HarnessResult};
 pub use loom_oracle::{LoomScenario, LoomOp, LoomTestableStack, generate_loom_test};
 pub use oracle_scheduler::{OracleScheduler, OracleSchedulerStats, OracleTrace};
 pub use random::DeterministicRng;
+pub use replay::{SavedCounterexample, SavedOps, ReplayError, ReplayResult, replay_controlled, replay_dst, replay_ssi, COUNTEREXAMPLE_FORMAT_VERSION};
 pub use scheduler::{ScheduleDecision, Scheduler};
-pub use ssi_harness::{DstTestableSsi, SsiDstRunner, SsiDstStats, SsiFaultPoint, SsiFaultType, DstSsiOp, run_ssi_scenario};
+pub use strategy::{Pct, PreemptionBounded, RoundRobin, SchedulingStrategy, StrategyConfig, UniformRandom};
+pub use shrink::{ShrinkResult, ddmin, shrink_dst, shrink_ssi, shrink_ssi_oracle, SHRINK_RUNS_MAX};
+pub use ssi_harness::{DstTestableSsi, SsiDstRunner, SsiDstStats, SsiFaultPoint, SsiFaultType, SsiProtocolRun, DstSsiOp, run_ssi_protocol, run_ssi_scenario};
 pub use ssi_oracle::{SsiOracleTrace, SsiOracleAction, SsiExpectedOutcome, SsiOracleReplayResult, replay_ssi_oracle, run_all_ssi_oracles};
 
 /// Get DST seed from environment or generate random one.
@@ -0,0 +1,501 @@
+//! Memory error detection for controlled executions.
+//!
+//! With the checker enabled (`ControlledRuntime::with_memory_checker`),
+//! the [`sync`](crate::sync) shim reports the accesses it performs and the
+//! checker looks for:
+//!
+//! - **Data races**: two accesses to the same
+//!   [`UnsafeCell`](crate::sync::cell::UnsafeCell), at least one of them a
+//!   write, by threads that do not synchronize. Happens-before comes from
+//!   the [`VectorClock`]s of the execution's
+//!   [`WeakMemory`](crate::weak_memory::WeakMemory) model.
+//! - **Use after free**: memory freed through
+//!   [`sync::alloc`](crate::sync::alloc) stays in quarantine until the
+//!   execution ends instead of going back to the allocator, so a shim
+//!   access inside it, or a second free, is caught instead of silently
+//!   hitting reused memory.
+//! - **ABA**: every store to a shim `AtomicPtr` is tagged with a version,
+//!   and each thread remembers the version it last read. A
+//!   compare-exchange that succeeds on the value it read, but whose
+//!   location held another pointer in between, is reported.
+//! - **Leaks**: allocations still live when the execution ends.
+//!
+//! Findings are [`MemoryIssue`]s, one per address (or freed block) and
+//! kind. Their steps are the steps of the
+//! [`SyncEvent`](crate::controlled::SyncEvent)s that caused them, so they
+//! line up with the counterexample's interleaving.
+
+use std::alloc::Layout;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::mem::{self, Discriminant};
+
+use vf_core::counterexample::MemoryIssue;
+
+/// Stores kept per pointer location for recognizing ABA.
+pub const POINTER_HISTORY_MAX: usize = 64;
+
+/// Happens-before clock: for every thread, the latest epoch known.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VectorClock {
+    epochs: Vec<u64>,
+}
+
+impl VectorClock {
+    /// Epoch of `thread`, 0 if none of its operations is known.
+    #[must_use]
+    pub fn get(&self, thread: usize) -> u64 {
+        self.epochs.get(thread).copied().unwrap_or(0)
+    }
+
+    pub fn increment(&mut self, thread: usize) {
+        if thread >= self.epochs.len() {
+            self.epochs.resize(thread + 1, 0);
+        }
+        self.epochs[thread] += 1;
+    }
+
+    pub fn join(&mut self, other: &VectorClock) {
+        if other.epochs.len() > self.epochs.len() {
+            self.epochs.resize(other.epochs.len(), 0);
+        }
+        for (epoch, &other) in self.epochs.iter_mut().zip(&other.epochs) {
+            *epoch = (*epoch).max(other);
+        }
+    }
+
+    /// Whether an operation of `thread` at `epoch` happens before the
+    /// owner of this clock.
+    #[must_use]
+    pub fn covers(&self, thread: usize, epoch: u64) -> bool {
+        self.get(thread) >= epoch
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Access {
+    thread: usize,
+    epoch: u64,
+}
+
+/// Accesses to one cell that later accesses must happen after.
+#[derive(Default)]
+struct Accesses {
+    write: Option<Access>,
+    /// Latest read of each thread since the last write
+    reads: Vec<Access>,
+}
+
+struct Block {
+    size: usize,
+    layout: Layout,
+    /// Step of the allocation, or of the free once quarantined
+    step: u64,
+}
+
+/// Stores to a pointer location, oldest first.
+#[derive(Default)]
+struct History {
+    version: u64,
+    values: VecDeque<(u64, u64)>,
+}
+
+/// Race, use-after-free, ABA and leak detection for one execution.
+#[derive(Default)]
+pub struct MemoryChecker {
+    accesses: HashMap<usize, Accesses>,
+    live: BTreeMap<usize, Block>,
+    quarantine: BTreeMap<usize, Block>,
+    histories: HashMap<usize, History>,
+    /// Version of each (thread, pointer location) the thread last read
+    seen: HashMap<(usize, usize), u64>,
+    reported: HashSet<(usize, Discriminant<MemoryIssue>)>,
+    issues: Vec<MemoryIssue>,
+}
+
+/// Block of `blocks` containing `address`, with its start.
+fn containing(blocks: &BTreeMap<usize, Block>, address: usize) -> Option<(usize, &Block)> {
+    blocks
+        .range(..=address)
+        .next_back()
+        .filter(|(&start, block)| address < start + block.size.max(1))
+        .map(|(&start, block)| (start, block))
+}
+
+impl MemoryChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issues found so far.
+    pub fn issues(&self) -> &[MemoryIssue] {
+        &self.issues
+    }
+
+    fn report(&mut self, address: usize, issue: MemoryIssue) {
+        if self.reported.insert((address, mem::discriminant(&issue))) {
+            self.issues.push(issue);
+        }
+    }
+
+    /// Check an access to `address` at `step` against freed memory.
+    pub fn check_use(&mut self, address: usize, step: u64) {
+        if let Some((start, freed)) = containing(&self.quarantine, address) {
+            let issue = MemoryIssue::UseAfterFree {
+                address: address as u64,
+                freed_at_step: freed.step,
+                used_at_step: step,
+            };
+            // One report per freed block
+            self.report(start, issue);
+        }
+    }
+
+    /// Non-atomic read of the cell at `address` by `thread`, whose
+    /// happens-before clock is `clock`.
+    pub fn read(&mut self, thread: usize, clock: &VectorClock, address: usize, step: u64) {
+        self.access(thread, clock, address, step, false);
+    }
+
+    /// Non-atomic write of the cell at `address`.
+    pub fn write(&mut self, thread: usize, clock: &VectorClock, address: usize, step: u64) {
+        self.access(thread, clock, address, step, true);
+    }
+
+    fn access(
+        &mut self,
+        thread: usize,
+        clock: &VectorClock,
+        address: usize,
+        step: u64,
+        write: bool,
+    ) {
+        self.check_use(address, step);
+        let access = Access {
+            thread,
+            epoch: clock.get(thread),
+        };
+        let ordered =
+            |other: &Access| other.thread == thread || clock.covers(other.thread, other.epoch);
+
+        let cell = self.accesses.entry(address).or_default();
+        let mut racing = cell
+            .write
+            .filter(|write| !ordered(write))
+            .map(|write| write.thread);
+        if write {
+            racing = racing.or_else(|| {
+                cell.reads
+                    .iter()
+                    .find(|read| !ordered(read))
+                    .map(|read| read.thread)
+            });
+            cell.write = Some(access);
+            cell.reads.clear();
+        } else {
+            cell.reads.retain(|read| read.thread != thread);
+            cell.reads.push(access);
+        }
+
+        if let Some(other) = racing {
+            let issue = MemoryIssue::DataRace {
+                address: address as u64,
+                thread_a: other as u64,
+                thread_b: thread as u64,
+                step,
+            };
+            self.report(address, issue);
+        }
+    }
+
+    /// Forget the accesses to a cell that no longer exists, so a new one
+    /// at the same address starts clean.
+    pub fn forget_cell(&mut self, address: usize) {
+        self.accesses.remove(&address);
+    }
+
+    /// Forget a pointer location that no longer exists.
+    pub fn forget_location(&mut self, location: usize) {
+        if self.histories.remove(&location).is_some() {
+            self.seen.retain(|&(_, seen), _| seen != location);
+        }
+    }
+
+    /// Memory allocated at `step`.
+    pub fn allocate(&mut self, address: usize, layout: Layout, step: u64) {
+        // The block may reuse memory freed before the checker saw it
+        let end = address + layout.size();
+        self.accesses
+            .retain(|&cell, _| !(address..end).contains(&cell));
+        self.live.insert(
+            address,
+            Block {
+                size: layout.size(),
+                layout,
+                step,
+            },
+        );
+    }
+
+    /// Memory freed at `step`. Returns whether the checker took the block
+    /// into quarantine, in which case the caller must not free it.
+    pub fn deallocate(&mut self, address: usize, step: u64) -> bool {
+        if let Some(block) = self.live.remove(&address) {
+            self.quarantine.insert(address, Block { step, ..block });
+            true
+        } else if let Some(freed) = self.quarantine.get(&address) {
+            // Double free: report it, and never free twice
+            let issue = MemoryIssue::UseAfterFree {
+                address: address as u64,
+                freed_at_step: freed.step,
+                used_at_step: step,
+            };
+            self.report(address, issue);
+            true
+        } else {
+            // Allocated before the execution started
+            false
+        }
+    }
+
+    /// Stores of pointer `location`, reset when its latest value is not
+    /// `current` (it was written behind the checker's back).
+    fn history(&mut self, location: usize, current: u64) -> &mut History {
+        let up_to_date = self
+            .histories
+            .get(&location)
+            .and_then(|history| history.values.back())
+            .is_some_and(|&(_, latest)| latest == current);
+        if !up_to_date {
+            self.forget_location(location);
+            let history = History {
+                version: 0,
+                values: VecDeque::from([(0, current)]),
+            };
+            self.histories.insert(location, history);
+        }
+        self.histories
+            .get_mut(&location)
+            .expect("location was just added")
+    }
+
+    /// `thread` read `value` from pointer `location`, whose actual value
+    /// is `latest` (they differ when weak memory returns a stale store).
+    pub fn pointer_read(&mut self, thread: usize, location: usize, latest: u64, value: u64) {
+        let history = self.history(location, latest);
+        let version = history
+            .values
+            .iter()
+            .rev()
+            .find(|&&(_, stored)| stored == value)
+            .map_or(history.version, |&(version, _)| version);
+        self.seen.insert((thread, location), version);
+    }
+
+    /// `thread` replaced `previous` with `value` at pointer `location`.
+    pub fn pointer_written(&mut self, thread: usize, location: usize, previous: u64, value: u64) {
+        let history = self.history(location, previous);
+        history.version += 1;
+        let version = history.version;
+        history.values.push_back((version, value));
+        if history.values.len() > POINTER_HISTORY_MAX {
+            history.values.pop_front();
+        }
+        self.seen.insert((thread, location), version);
+    }
+
+    /// `thread`'s compare-exchange at `step` replaced `current` with
+    /// `new` at pointer `location`.
+    pub fn pointer_exchanged(
+        &mut self,
+        thread: usize,
+        location: usize,
+        current: u64,
+        new: u64,
+        step: u64,
+    ) {
+        self.history(location, current);
+        let history = &self.histories[&location];
+        if let Some(&seen) = self.seen.get(&(thread, location)) {
+            // The thread expected `current` because it read it, but the
+            // location held something else in between
+            let read_current = history.values.contains(&(seen, current));
+            let intermediate = history
+                .values
+                .iter()
+                .find(|&&(version, value)| version > seen && value != current);
+            if let (true, Some(&(_, intermediate))) = (read_current, intermediate) {
+                let issue = MemoryIssue::AbaProblem {
+                    address: location as u64,
+                    original_value: current,
+                    intermediate_value: intermediate,
+                    final_value: current,
+                    step,
+                };
+                self.report(location, issue);
+            }
+        }
+        self.pointer_written(thread, location, current, new);
+    }
+
+    /// End of the execution: report allocations never freed, and free
+    /// the quarantined memory. Returns every issue found.
+    pub fn finish(&mut self) -> Vec<MemoryIssue> {
+        for (address, block) in mem::take(&mut self.live) {
+            let issue = MemoryIssue::MemoryLeak {
+                address: address as u64,
+                allocated_at_step: block.step,
+            };
+            self.report(address, issue);
+        }
+        for (address, block) in mem::take(&mut self.quarantine) {
+            // SAFETY: the block was allocated by `std::alloc` with this
+            // layout, and the code under test freed it, so it holds no
+            // live references into it.
+            unsafe { std::alloc::dealloc(address as *mut u8, block.layout) };
+        }
+        mem::take(&mut self.issues)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELL: usize = 0x10;
+    const HEAD: usize = 0x20;
+
+    fn clock(epochs: &[u64]) -> VectorClock {
+        VectorClock {
+            epochs: epochs.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_vector_clock_join_and_cover() {
+        let mut a = clock(&[2, 0]);
+        a.join(&clock(&[1, 3, 1]));
+        assert_eq!(a, clock(&[2, 3, 1]));
+        assert!(a.covers(1, 3));
+        assert!(!a.covers(1, 4));
+        assert!(!a.covers(5, 1));
+        a.increment(4);
+        assert_eq!(a.get(4), 1);
+    }
+
+    #[test]
+    fn test_unordered_accesses_race() {
+        let mut checker = MemoryChecker::new();
+        checker.write(0, &clock(&[1]), CELL, 1);
+        // Thread 1 knows nothing of thread 0's epoch 1
+        checker.read(1, &clock(&[0, 1]), CELL, 2);
+
+        assert!(matches!(
+            checker.issues(),
+            [MemoryIssue::DataRace {
+                thread_a: 0,
+                thread_b: 1,
+                step: 2,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_ordered_accesses_do_not_race() {
+        let mut checker = MemoryChecker::new();
+        checker.write(0, &clock(&[1]), CELL, 1);
+        checker.read(1, &clock(&[1, 1]), CELL, 2);
+        checker.read(2, &clock(&[1, 0, 1]), CELL, 3);
+        // The write must come after both reads
+        checker.write(0, &clock(&[2, 1, 1]), CELL, 4);
+        assert!(checker.issues().is_empty());
+
+        // Reads do not race with each other, but do with a later write
+        checker.read(1, &clock(&[2, 2]), CELL, 5);
+        checker.write(2, &clock(&[2, 0, 2]), CELL, 6);
+        assert_eq!(checker.issues().len(), 1);
+    }
+
+    #[test]
+    fn test_quarantine_catches_use_after_free_and_double_free() {
+        let layout = Layout::new::<u64>();
+        let address = unsafe { std::alloc::alloc(layout) } as usize;
+        let mut checker = MemoryChecker::new();
+        checker.allocate(address, layout, 1);
+        checker.check_use(address + 4, 2);
+        assert!(checker.deallocate(address, 3));
+        checker.check_use(address + 4, 4);
+        assert!(checker.deallocate(address, 5));
+
+        let issues = checker.finish();
+        // Both hit the same block: only the first is reported
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(
+            issues[0],
+            MemoryIssue::UseAfterFree {
+                freed_at_step: 3,
+                used_at_step: 4,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_finish_reports_leaks() {
+        let layout = Layout::new::<u64>();
+        let address = unsafe { std::alloc::alloc(layout) };
+        let mut checker = MemoryChecker::new();
+        checker.allocate(address as usize, layout, 7);
+        assert!(
+            !checker.deallocate(0x1000, 8),
+            "unknown block is freed by the caller"
+        );
+
+        let issues = checker.finish();
+        assert!(matches!(
+            issues[..],
+            [MemoryIssue::MemoryLeak {
+                allocated_at_step: 7,
+                ..
+            }]
+        ));
+        unsafe { std::alloc::dealloc(address, layout) };
+    }
+
+    #[test]
+    fn test_pointer_changed_and_back_is_aba() {
+        let (a, b) = (0xa0, 0xb0);
+        let mut checker = MemoryChecker::new();
+        checker.pointer_read(0, HEAD, a, a);
+        // Thread 1 pops A, pops B and pushes A back
+        checker.pointer_read(1, HEAD, a, a);
+        checker.pointer_exchanged(1, HEAD, a, b, 1);
+        checker.pointer_exchanged(1, HEAD, b, a, 2);
+        assert!(checker.issues().is_empty());
+
+        checker.pointer_exchanged(0, HEAD, a, 0, 3);
+        assert!(matches!(
+            checker.issues(),
+            [MemoryIssue::AbaProblem {
+                original_value: 0xa0,
+                intermediate_value: 0xb0,
+                final_value: 0xa0,
+                step: 3,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_fresh_read_is_not_aba() {
+        let (a, b) = (0xa0, 0xb0);
+        let mut checker = MemoryChecker::new();
+        checker.pointer_read(0, HEAD, a, a);
+        checker.pointer_written(1, HEAD, a, b);
+        checker.pointer_written(1, HEAD, b, a);
+        // Thread 0 reads again before its compare-exchange
+        checker.pointer_read(0, HEAD, a, a);
+        checker.pointer_exchanged(0, HEAD, a, 0, 4);
+        assert!(checker.issues().is_empty());
+    }
+}
@@ -230,9 +230,9 @@ impl OracleScheduler {
 
     /// Check if we're still following the oracle.
     pub fn is_following_oracle(&self) -> bool {
-        self.oracle.as_ref().map_or(false, |o| {
-            self.oracle_position < o.steps.len()
-        })
+        self.oracle
+            .as_ref()
+            .is_some_and(|o| self.oracle_position < o.steps.len())
     }
 
     /// Check if oracle is complete.
@@ -194,14 +194,14 @@ mod tests {
     #[test]
     fn test_fork() {
         let mut rng = DeterministicRng::new(12345);
-        let mut forked = rng.fork();
+        let forked = rng.fork();
 
         // Forked RNG has different seed
         assert_ne!(forked.seed(), 12345);
 
         // But the process is deterministic
         let mut rng2 = DeterministicRng::new(12345);
-        let mut forked2 = rng2.fork();
+        let forked2 = rng2.fork();
         assert_eq!(forked.seed(), forked2.seed());
     }
 
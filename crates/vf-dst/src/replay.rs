@@ -0,0 +1,709 @@
+//! Saved counterexamples and replay.
+//!
+//! A failing DST run is saved as versioned JSON holding everything needed
+//! to re-execute it: the seed, the fault configuration, the scheduler
+//! decisions and the operation sequence, next to the rendered
+//! `Counterexample`. Replaying runs the operations again against an
+//! implementation and reports whether the failure still reproduces, so a
+//! saved file doubles as a regression test.
+//!
+//! ```text
+//! failing run ──> SavedCounterexample ──> cex.json ──> replay_*() ──> still fails?
+//! ```
+//!
+//! The stack and SSI scenarios run sequentially, so their operation order
+//! is the interleaving. `DstHarness::run_controlled` runs switch inside
+//! operations, so their scheduler decisions are saved instead, and
+//! [`replay_controlled`] feeds them back through
+//! `DstHarness::set_schedule`. Their operations are closures, which the
+//! replay is given again.
+
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use vf_core::counterexample::{Counterexample, ThreadAction};
+
+use crate::fault::FaultConfig;
+use crate::fault_injection::{run_dst_scenario_with_config, DstOp, DstResult, DstTestableStack};
+use crate::harness::{DstHarness, HarnessConfig, HarnessResult};
+use crate::scheduler::ScheduleDecision;
+use crate::ssi_harness::{
+    execute_ssi_ops, DstSsiOp, DstTestableSsi, SsiDstRunner, SsiOperation, TxnId,
+};
+
+/// Version of the on-disk counterexample format.
+///
+/// Bump when a change would make older files replay differently.
+pub const COUNTEREXAMPLE_FORMAT_VERSION: u32 = 1;
+
+/// Operation sequence of a saved counterexample.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "harness", content = "ops", rename_all = "snake_case")]
+pub enum SavedOps {
+    /// Operations for `run_dst_scenario`
+    Stack(Vec<DstOp>),
+    /// `(scenario txn, op)` pairs for `run_ssi_scenario`
+    Ssi(Vec<(TxnId, DstSsiOp)>),
+    /// Shape of a `DstHarness::run_controlled` run
+    Controlled {
+        threads_count: usize,
+        operations_per_thread: u64,
+    },
+}
+
+impl SavedOps {
+    fn harness(&self) -> &'static str {
+        match self {
+            SavedOps::Stack(_) => "stack",
+            SavedOps::Ssi(_) => "ssi",
+            SavedOps::Controlled { .. } => "controlled",
+        }
+    }
+}
+
+/// A counterexample with everything needed to re-execute it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedCounterexample {
+    /// Format version, `COUNTEREXAMPLE_FORMAT_VERSION` when written
+    pub version: u32,
+    /// DST seed
+    pub seed: u64,
+    /// Fault configuration the failure was found with
+    pub fault_config: FaultConfig,
+    /// Scheduler decisions, in order (empty for sequential scenarios)
+    pub schedule: Vec<ScheduleDecision>,
+    /// Operation sequence
+    pub ops: SavedOps,
+    /// What went wrong, for humans
+    pub counterexample: Counterexample,
+}
+
+/// Error loading or replaying a saved counterexample.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// Reading or writing the file failed
+    Io(String),
+    /// Malformed JSON
+    Json(String),
+    /// Written by an incompatible version of the format
+    UnsupportedVersion { found: u64 },
+    /// Replayed against the wrong kind of harness
+    WrongHarness {
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(msg) => write!(f, "I/O error: {}", msg),
+            ReplayError::Json(msg) => write!(f, "invalid counterexample JSON: {}", msg),
+            ReplayError::UnsupportedVersion { found } => write!(
+                f,
+                "unsupported counterexample format version {} (expected {})",
+                found, COUNTEREXAMPLE_FORMAT_VERSION
+            ),
+            ReplayError::WrongHarness { expected, found } => write!(
+                f,
+                "counterexample is for the {} harness, not {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl SavedCounterexample {
+    /// Create a saved counterexample in the current format.
+    #[must_use]
+    pub fn new(
+        seed: u64,
+        fault_config: FaultConfig,
+        ops: SavedOps,
+        counterexample: Counterexample,
+    ) -> Self {
+        Self {
+            version: COUNTEREXAMPLE_FORMAT_VERSION,
+            seed,
+            fault_config,
+            schedule: Vec::new(),
+            ops,
+            counterexample,
+        }
+    }
+
+    /// Attach the scheduler decisions the failure was found with.
+    #[must_use]
+    pub fn with_schedule(mut self, schedule: Vec<ScheduleDecision>) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Save a failing `run_dst_scenario_with_config` run.
+    #[must_use]
+    pub fn from_dst_result(
+        fault_config: FaultConfig,
+        operations: Vec<DstOp>,
+        result: &DstResult,
+    ) -> Self {
+        debug_assert!(!result.passed, "Only failing runs are counterexamples");
+
+        let mut counterexample = Counterexample::new().with_description(result.format());
+        counterexample.dst_seed = Some(result.stats.seed);
+        Self::new(
+            result.stats.seed,
+            fault_config,
+            SavedOps::Stack(operations),
+            counterexample,
+        )
+    }
+
+    /// Save a failing SSI run, with the runner's history as the interleaving.
+    ///
+    /// `operations` are the ones passed to the runner, in order.
+    #[must_use]
+    pub fn from_ssi_runner<S: DstTestableSsi>(
+        runner: &SsiDstRunner<S>,
+        operations: Vec<(TxnId, DstSsiOp)>,
+    ) -> Self {
+        Self::new(
+            runner.seed(),
+            runner.fault_config().clone(),
+            SavedOps::Ssi(operations),
+            ssi_counterexample(runner),
+        )
+    }
+
+    /// Save a failing `DstHarness::run_controlled` run made with `config`,
+    /// with its scheduler decisions.
+    #[must_use]
+    pub fn from_harness_result(config: &HarnessConfig, result: &HarnessResult) -> Self {
+        debug_assert!(
+            !result.all_invariants_held,
+            "Only failing runs are counterexamples"
+        );
+
+        let counterexample = result.counterexample.clone().unwrap_or_else(|| {
+            Counterexample::with_seed(result.seed).with_description(result.format())
+        });
+        Self::new(
+            result.seed,
+            config.fault_config.clone(),
+            SavedOps::Controlled {
+                threads_count: config.threads_count,
+                operations_per_thread: config.operations_per_thread,
+            },
+            counterexample,
+        )
+        .with_schedule(result.schedule.clone())
+    }
+
+    /// Serialize as pretty-printed JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("counterexample is always serializable")
+    }
+
+    /// Parse from JSON, rejecting other format versions.
+    pub fn from_json(json: &str) -> Result<Self, ReplayError> {
+        // Check the version before the shape, so files from a newer format
+        // report the version rather than a confusing field error.
+        let raw: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| ReplayError::Json(e.to_string()))?;
+        let version = raw
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| ReplayError::Json("missing \"version\"".to_string()))?;
+        if version != u64::from(COUNTEREXAMPLE_FORMAT_VERSION) {
+            return Err(ReplayError::UnsupportedVersion { found: version });
+        }
+        serde_json::from_value(raw).map_err(|e| ReplayError::Json(e.to_string()))
+    }
+
+    /// Write to a file as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        std::fs::write(path, self.to_json()).map_err(|e| ReplayError::Io(e.to_string()))
+    }
+
+    /// Read from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let json = std::fs::read_to_string(path).map_err(|e| ReplayError::Io(e.to_string()))?;
+        Self::from_json(&json)
+    }
+}
+
+/// Outcome of replaying a saved counterexample.
+#[derive(Debug, Clone)]
+pub struct ReplayResult {
+    /// Whether the implementation still violates an invariant
+    pub still_fails: bool,
+    /// Result of the replayed run
+    pub report: String,
+    /// Thread at each switch point, for controlled runs
+    pub trace: Vec<usize>,
+}
+
+impl ReplayResult {
+    pub fn format(&self) -> String {
+        let status = if self.still_fails {
+            "REPRODUCED"
+        } else {
+            "NOT REPRODUCED"
+        };
+        format!("[{}] {}", status, self.report)
+    }
+}
+
+/// Replay a saved stack counterexample against `S`.
+pub fn replay_dst<S: DstTestableStack>(
+    saved: &SavedCounterexample,
+) -> Result<ReplayResult, ReplayError> {
+    let SavedOps::Stack(operations) = &saved.ops else {
+        return Err(ReplayError::WrongHarness {
+            expected: "stack",
+            found: saved.ops.harness(),
+        });
+    };
+
+    let result = run_dst_scenario_with_config::<S>(
+        saved.seed,
+        saved.fault_config.clone(),
+        operations.clone(),
+    );
+    Ok(ReplayResult {
+        still_fails: !result.passed,
+        report: result.format(),
+        trace: Vec::new(),
+    })
+}
+
+/// Replay a saved SSI counterexample against a fresh `ssi`.
+pub fn replay_ssi<S: DstTestableSsi>(
+    ssi: S,
+    saved: &SavedCounterexample,
+) -> Result<ReplayResult, ReplayError> {
+    let SavedOps::Ssi(operations) = &saved.ops else {
+        return Err(ReplayError::WrongHarness {
+            expected: "ssi",
+            found: saved.ops.harness(),
+        });
+    };
+
+    let mut runner = SsiDstRunner::with_fault_config(ssi, saved.seed, saved.fault_config.clone());
+    execute_ssi_ops(&mut runner, operations.clone());
+    let report = runner
+        .format_invariant_failures()
+        .unwrap_or_else(|| runner.stats().format());
+    Ok(ReplayResult {
+        still_fails: !runner.invariants_hold(),
+        report,
+        trace: Vec::new(),
+    })
+}
+
+/// Replay a saved `DstHarness::run_controlled` counterexample, following
+/// its scheduler decisions.
+///
+/// `execute` and `check_invariants` are the closures of the original run.
+/// The thread and operation counts and the fault configuration come from
+/// `saved`; everything else, such as weak memory, from `config`.
+pub fn replay_controlled<R, I>(
+    saved: &SavedCounterexample,
+    config: HarnessConfig,
+    execute: R,
+    check_invariants: I,
+) -> Result<ReplayResult, ReplayError>
+where
+    R: Fn(usize, u64) -> Result<(), String> + Sync,
+    I: FnMut() -> Result<(), String>,
+{
+    let SavedOps::Controlled {
+        threads_count,
+        operations_per_thread,
+    } = saved.ops
+    else {
+        return Err(ReplayError::WrongHarness {
+            expected: "controlled",
+            found: saved.ops.harness(),
+        });
+    };
+
+    let config = HarnessConfig {
+        threads_count,
+        operations_per_thread,
+        fault_config: saved.fault_config.clone(),
+        ..config
+    };
+    let mut harness = DstHarness::new(saved.seed, config);
+    harness.set_schedule(saved.schedule.clone());
+    let result = harness.run_controlled(execute, check_invariants);
+    Ok(ReplayResult {
+        still_fails: !result.all_invariants_held,
+        report: result.format(),
+        trace: result.trace,
+    })
+}
+
+/// Build a counterexample from an SSI runner's history, one thread per
+/// transaction.
+fn ssi_counterexample<S: DstTestableSsi>(runner: &SsiDstRunner<S>) -> Counterexample {
+    let mut counterexample = Counterexample::new();
+    counterexample.dst_seed = Some(runner.seed());
+    counterexample.description = runner.format_invariant_failures();
+
+    for (i, op) in runner.history().iter().enumerate() {
+        let (thread_id, action, success) = match op {
+            SsiOperation::Begin(txn) => (*txn, "begin".to_string(), true),
+            SsiOperation::BeginReadOnly(txn) => (*txn, "begin_ro".to_string(), true),
+            SsiOperation::Read { txn, key, value } => {
+                let value = value.map_or("None".to_string(), |v| v.to_string());
+                (*txn, format!("r(k{})={}", key, value), true)
+            }
+            SsiOperation::Scan {
+                txn,
+                range,
+                results,
+            } => {
+                let results: Vec<String> =
+                    results.iter().map(|(k, v)| format!("k{}={}", k, v)).collect();
+                (
+                    *txn,
+                    format!(
+                        "scan(k{}..=k{})=[{}]",
+                        range.start(),
+                        range.end(),
+                        results.join(",")
+                    ),
+                    true,
+                )
+            }
+            SsiOperation::Write { txn, key, value } => {
+                (*txn, format!("w(k{},{})", key, value), true)
+            }
+            SsiOperation::Commit(txn) => (*txn, "commit".to_string(), true),
+            SsiOperation::Abort(txn) => (*txn, "abort".to_string(), false),
+            SsiOperation::FaultInjected { txn, fault, point } => {
+                (*txn, format!("{:?}@{:?}", fault, point), false)
+            }
+            // Not a transaction's step: thread 0
+            SsiOperation::Vacuum(report) => {
+                let pruned: Vec<String> =
+                    report.versions.iter().map(|(k, t)| format!("k{}@T{}", k, t)).collect();
+                (0, format!("vacuum([{}])", pruned.join(",")), true)
+            }
+        };
+        counterexample.add_action(ThreadAction {
+            thread_id,
+            step: i as u64 + 1,
+            action,
+            success,
+        });
+    }
+    counterexample
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Mutex;
+
+    /// Stack that loses every value pushed after the second.
+    struct LossyStack {
+        values: Mutex<Vec<u64>>,
+        pushes: Mutex<u64>,
+    }
+
+    impl DstTestableStack for LossyStack {
+        fn new() -> Self {
+            Self {
+                values: Mutex::new(Vec::new()),
+                pushes: Mutex::new(0),
+            }
+        }
+
+        fn push(&self, value: u64) {
+            let mut pushes = self.pushes.lock().unwrap();
+            *pushes += 1;
+            if *pushes <= 2 {
+                self.values.lock().unwrap().push(value);
+            }
+        }
+
+        fn pop(&self) -> Option<u64> {
+            self.values.lock().unwrap().pop()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.values.lock().unwrap().is_empty()
+        }
+
+        fn get_contents(&self) -> Vec<u64> {
+            self.values.lock().unwrap().clone()
+        }
+    }
+
+    struct CorrectStack {
+        values: Mutex<Vec<u64>>,
+    }
+
+    impl DstTestableStack for CorrectStack {
+        fn new() -> Self {
+            Self {
+                values: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn push(&self, value: u64) {
+            self.values.lock().unwrap().push(value);
+        }
+
+        fn pop(&self) -> Option<u64> {
+            self.values.lock().unwrap().pop()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.values.lock().unwrap().is_empty()
+        }
+
+        fn get_contents(&self) -> Vec<u64> {
+            self.values.lock().unwrap().clone()
+        }
+    }
+
+    /// SSI that commits everything and reports every committed
+    /// transaction as a pivot of a dangerous structure.
+    struct PivotSsi {
+        next_txn: Mutex<TxnId>,
+        committed: Mutex<HashSet<TxnId>>,
+        data: Mutex<HashMap<u64, u64>>,
+    }
+
+    impl PivotSsi {
+        fn new() -> Self {
+            Self {
+                next_txn: Mutex::new(1),
+                committed: Mutex::new(HashSet::new()),
+                data: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    impl DstTestableSsi for PivotSsi {
+        fn begin(&self) -> TxnId {
+            let mut next = self.next_txn.lock().unwrap();
+            *next += 1;
+            *next - 1
+        }
+
+        fn read(&self, _txn: TxnId, key: u64) -> Option<u64> {
+            self.data.lock().unwrap().get(&key).copied()
+        }
+
+        fn write(&self, _txn: TxnId, key: u64, value: u64) -> bool {
+            self.data.lock().unwrap().insert(key, value);
+            true
+        }
+
+        fn commit(&self, txn: TxnId) -> bool {
+            self.committed.lock().unwrap().insert(txn);
+            true
+        }
+
+        fn abort(&self, _txn: TxnId) {}
+
+        fn is_active(&self, txn: TxnId) -> bool {
+            !self.committed.lock().unwrap().contains(&txn)
+        }
+
+        fn committed_txns(&self) -> HashSet<TxnId> {
+            self.committed.lock().unwrap().clone()
+        }
+
+        fn get_current_value(&self, key: u64) -> Option<u64> {
+            self.data.lock().unwrap().get(&key).copied()
+        }
+
+        fn get_conflict_flags(&self, _txn: TxnId) -> (bool, bool) {
+            (true, true)
+        }
+    }
+
+    fn lossy_counterexample() -> SavedCounterexample {
+        let ops = vec![DstOp::Push(1), DstOp::Push(2), DstOp::Push(3)];
+        let result =
+            run_dst_scenario_with_config::<LossyStack>(7, FaultConfig::none(), ops.clone());
+        assert!(!result.passed);
+        SavedCounterexample::from_dst_result(FaultConfig::none(), ops, &result)
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let saved = lossy_counterexample().with_schedule(vec![
+            ScheduleDecision::Continue,
+            ScheduleDecision::SwitchTo(1),
+        ]);
+
+        let json = saved.to_json();
+        assert!(json.contains("\"version\": 1"));
+        assert!(json.contains("\"harness\": \"stack\""));
+
+        let loaded = SavedCounterexample::from_json(&json).unwrap();
+        assert_eq!(loaded.seed, 7);
+        assert_eq!(loaded.fault_config, FaultConfig::none());
+        assert_eq!(loaded.schedule, saved.schedule);
+        assert_eq!(loaded.ops, saved.ops);
+        assert_eq!(loaded.counterexample.dst_seed, Some(7));
+        assert_eq!(
+            loaded.counterexample.description,
+            saved.counterexample.description
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_versions() {
+        let json = lossy_counterexample()
+            .to_json()
+            .replace("\"version\": 1", "\"version\": 99");
+        assert_eq!(
+            SavedCounterexample::from_json(&json).unwrap_err(),
+            ReplayError::UnsupportedVersion { found: 99 }
+        );
+        assert!(matches!(
+            SavedCounterexample::from_json("{\"seed\": 1}"),
+            Err(ReplayError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_replay_stack() {
+        let path = std::env::temp_dir().join(format!("vf-dst-cex-{}.json", std::process::id()));
+        lossy_counterexample().save(&path).unwrap();
+        let saved = SavedCounterexample::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let replay = replay_dst::<LossyStack>(&saved).unwrap();
+        assert!(replay.still_fails, "{}", replay.format());
+        assert!(replay.report.contains("NoLostElements"));
+
+        // The fixed implementation passes the same counterexample
+        let replay = replay_dst::<CorrectStack>(&saved).unwrap();
+        assert!(!replay.still_fails, "{}", replay.format());
+
+        assert_eq!(
+            replay_ssi(PivotSsi::new(), &saved).unwrap_err(),
+            ReplayError::WrongHarness {
+                expected: "ssi",
+                found: "stack"
+            }
+        );
+    }
+
+    /// Racy counter increments, run under the controlled harness.
+    fn replay_racy_increments(saved: &SavedCounterexample) -> Result<ReplayResult, ReplayError> {
+        use crate::controlled::switch_point;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let counter = AtomicU64::new(0);
+        replay_controlled(
+            saved,
+            HarnessConfig::quick(),
+            |_thread, _step| {
+                switch_point();
+                let value = counter.load(Ordering::SeqCst);
+                switch_point();
+                counter.store(value + 1, Ordering::SeqCst);
+                Ok(())
+            },
+            || match counter.load(Ordering::SeqCst) {
+                9 => Ok(()),
+                n => Err(format!("lost update: counter is {}", n)),
+            },
+        )
+    }
+
+    #[test]
+    fn test_replay_controlled() {
+        use crate::controlled::switch_point;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let config = HarnessConfig {
+            threads_count: 3,
+            operations_per_thread: 3,
+            yield_probability: 0.5,
+            ..HarnessConfig::quick()
+        };
+        let (seed, result) = (1..=20)
+            .map(|seed| {
+                let counter = AtomicU64::new(0);
+                let mut harness = DstHarness::new(seed, config.clone());
+                let result = harness.run_controlled(
+                    |_thread, _step| {
+                        switch_point();
+                        let value = counter.load(Ordering::SeqCst);
+                        switch_point();
+                        counter.store(value + 1, Ordering::SeqCst);
+                        Ok(())
+                    },
+                    || match counter.load(Ordering::SeqCst) {
+                        9 => Ok(()),
+                        n => Err(format!("lost update: counter is {}", n)),
+                    },
+                );
+                (seed, result)
+            })
+            .find(|(_, result)| !result.all_invariants_held)
+            .expect("no seed lost an update");
+
+        let saved = SavedCounterexample::from_harness_result(&config, &result);
+        assert_eq!(saved.seed, seed);
+        assert!(!saved.schedule.is_empty());
+        let saved = SavedCounterexample::from_json(&saved.to_json()).unwrap();
+
+        let replay = replay_racy_increments(&saved).unwrap();
+        assert!(replay.still_fails, "{}", replay.format());
+        assert_eq!(replay.trace, result.trace);
+
+        // The schedule, not the seed, decides the interleaving
+        let reseeded = SavedCounterexample {
+            seed: seed + 1000,
+            ..saved.clone()
+        };
+        let replay = replay_racy_increments(&reseeded).unwrap();
+        assert!(replay.still_fails, "{}", replay.format());
+        assert_eq!(replay.trace, result.trace);
+
+        assert_eq!(
+            replay_dst::<CorrectStack>(&saved).unwrap_err(),
+            ReplayError::WrongHarness {
+                expected: "stack",
+                found: "controlled"
+            }
+        );
+    }
+
+    #[test]
+    fn test_replay_ssi() {
+        let ops = vec![
+            (1, DstSsiOp::Begin),
+            (1, DstSsiOp::Read(1)),
+            (1, DstSsiOp::Write(2, 10)),
+            (1, DstSsiOp::Commit),
+        ];
+        let mut runner = SsiDstRunner::with_fault_config(PivotSsi::new(), 11, FaultConfig::none());
+        execute_ssi_ops(&mut runner, ops.clone());
+        assert!(!runner.invariants_hold());
+
+        let saved = SavedCounterexample::from_ssi_runner(&runner, ops);
+        assert_eq!(saved.counterexample.interleaving.len(), 4);
+        assert!(saved.counterexample.render_diagram().contains("w(k2,10)"));
+
+        let loaded = SavedCounterexample::from_json(&saved.to_json()).unwrap();
+        let replay = replay_ssi(PivotSsi::new(), &loaded).unwrap();
+        assert!(replay.still_fails, "{}", replay.format());
+        assert!(replay.report.contains("INVARIANT_FAILED"));
+    }
+}
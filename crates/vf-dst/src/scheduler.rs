@@ -1,12 +1,16 @@
 //! Deterministic thread scheduler for DST.
 //!
 //! Controls thread interleaving in a reproducible way.
-//! This is the core mechanism for finding concurrency bugs.
+//! This is the core mechanism for finding concurrency bugs. Which thread
+//! runs next is up to a [`SchedulingStrategy`] (see [`crate::strategy`]).
 
 use crate::random::DeterministicRng;
+use crate::strategy::{ScheduleContext, SchedulingStrategy, UniformRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 /// Thread scheduling decision.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ScheduleDecision {
     /// Continue executing the current thread
     Continue,
@@ -23,14 +27,19 @@ pub enum ScheduleDecision {
 /// the same interleaving.
 pub struct Scheduler {
     rng: DeterministicRng,
+    strategy: Box<dyn SchedulingStrategy>,
     /// Number of threads being scheduled
     threads_count: usize,
+    /// Whether each thread has finished
+    finished: Vec<bool>,
     /// Current thread index
     current_thread: usize,
-    /// Probability of yielding on each decision point
-    yield_probability: f64,
     /// Schedule decisions made
     decisions_count: u64,
+    /// Decisions to replay before falling back to the RNG
+    script: VecDeque<ScheduleDecision>,
+    /// Decisions made so far, if recording is enabled
+    recorded: Option<Vec<ScheduleDecision>>,
 }
 
 /// Maximum threads to schedule.
@@ -39,14 +48,30 @@ const THREADS_COUNT_MAX: usize = 64;
 /// Maximum decisions before warning.
 const DECISIONS_COUNT_WARNING_MAX: u64 = 10_000_000;
 
+/// Yield probability of [`Scheduler::with_defaults`].
+pub const YIELD_PROBABILITY_DEFAULT: f64 = 0.1;
+
 impl Scheduler {
-    /// Create a new scheduler.
+    /// Create a new scheduler switching at random.
     ///
     /// # Arguments
     /// - `rng`: Deterministic RNG for scheduling decisions
     /// - `threads_count`: Number of threads to schedule
     /// - `yield_probability`: Probability of yielding on each decision
     pub fn new(rng: DeterministicRng, threads_count: usize, yield_probability: f64) -> Self {
+        Self::with_strategy(
+            rng,
+            threads_count,
+            Box::new(UniformRandom::new(yield_probability)),
+        )
+    }
+
+    /// Create a scheduler that lets `strategy` pick the threads.
+    pub fn with_strategy(
+        rng: DeterministicRng,
+        threads_count: usize,
+        strategy: Box<dyn SchedulingStrategy>,
+    ) -> Self {
         debug_assert!(threads_count > 0, "Must have at least one thread");
         debug_assert!(
             threads_count <= THREADS_COUNT_MAX,
@@ -54,23 +79,48 @@ impl Scheduler {
             threads_count,
             THREADS_COUNT_MAX
         );
-        debug_assert!(
-            (0.0..=1.0).contains(&yield_probability),
-            "Yield probability must be in [0.0, 1.0]"
-        );
 
         Self {
             rng,
+            strategy,
             threads_count,
+            finished: vec![false; threads_count],
             current_thread: 0,
-            yield_probability,
             decisions_count: 0,
+            script: VecDeque::new(),
+            recorded: None,
         }
     }
 
+    /// Replay the given decisions before making random ones.
+    ///
+    /// Used to re-execute a saved counterexample with the interleaving
+    /// it was found with.
+    pub fn set_script(&mut self, decisions: Vec<ScheduleDecision>) {
+        debug_assert!(
+            decisions.iter().all(|d| match d {
+                ScheduleDecision::SwitchTo(thread) => *thread < self.threads_count,
+                _ => true,
+            }),
+            "Scripted switch to a thread that does not exist"
+        );
+        self.script = decisions.into();
+    }
+
+    /// Record every decision so it can be saved with a counterexample.
+    pub fn start_recording(&mut self) {
+        self.recorded = Some(Vec::new());
+    }
+
+    /// Decisions made so far (empty unless recording is enabled).
+    #[must_use]
+    pub fn recorded_decisions(&self) -> &[ScheduleDecision] {
+        self.recorded.as_deref().unwrap_or(&[])
+    }
+
     /// Create with default yield probability (10%).
     pub fn with_defaults(rng: DeterministicRng, threads_count: usize) -> Self {
-        Self::new(rng, threads_count, 0.1)
+        Self::new(rng, threads_count, YIELD_PROBABILITY_DEFAULT)
     }
 
     /// Get the current thread index.
@@ -96,43 +146,81 @@ impl Scheduler {
             "Very high number of scheduling decisions - possible infinite loop"
         );
 
+        // Draw even when scripted, so the RNG and strategy stay in step
+        // with the recorded run
+        let current = self.current_thread;
+        let drawn = self.decide_strategy();
+        let decision = match self.script.pop_front() {
+            Some(scripted) => {
+                self.current_thread = match scripted {
+                    ScheduleDecision::SwitchTo(thread) => thread,
+                    _ => current,
+                };
+                scripted
+            }
+            None => drawn,
+        };
+
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push(decision);
+        }
+        decision
+    }
+
+    fn decide_strategy(&mut self) -> ScheduleDecision {
         if self.threads_count == 1 {
             return ScheduleDecision::Continue;
         }
 
-        if self.rng.gen_bool(self.yield_probability) {
-            // Pick a different thread
-            let other = self.pick_other_thread();
-            self.current_thread = other;
-            ScheduleDecision::SwitchTo(other)
-        } else {
+        let next = self.strategy.next_thread(ScheduleContext {
+            current: self.current_thread,
+            finished: &self.finished,
+            rng: &mut self.rng,
+        });
+        debug_assert!(next < self.threads_count);
+        if next == self.current_thread {
             ScheduleDecision::Continue
+        } else {
+            self.current_thread = next;
+            ScheduleDecision::SwitchTo(next)
         }
     }
 
-    /// Force a context switch to a random thread.
+    /// Force a context switch to another thread, chosen by the strategy.
+    ///
+    /// Recorded and scripted as a [`ScheduleDecision::SwitchTo`], in order
+    /// with the decisions of [`Scheduler::decide`].
     pub fn force_switch(&mut self) -> usize {
         self.decisions_count += 1;
 
-        if self.threads_count == 1 {
-            return 0;
+        let drawn = if self.threads_count == 1 {
+            0
+        } else {
+            self.strategy.switch_thread(ScheduleContext {
+                current: self.current_thread,
+                finished: &self.finished,
+                rng: &mut self.rng,
+            })
+        };
+        let other = match self.script.pop_front() {
+            Some(ScheduleDecision::SwitchTo(thread)) => thread,
+            // A script that does not match the run: follow the strategy
+            Some(_) | None => drawn,
+        };
+        debug_assert!(other < self.threads_count);
+
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push(ScheduleDecision::SwitchTo(other));
         }
-
-        let other = self.pick_other_thread();
         self.current_thread = other;
         other
     }
 
-    /// Pick a random thread other than the current one.
-    fn pick_other_thread(&mut self) -> usize {
-        debug_assert!(self.threads_count > 1);
-
-        loop {
-            let candidate = self.rng.gen_range(0..self.threads_count);
-            if candidate != self.current_thread {
-                return candidate;
-            }
-        }
+    /// Mark `thread` finished: strategies that track runnable threads
+    /// never pick it again.
+    pub fn finish_thread(&mut self, thread: usize) {
+        debug_assert!(thread < self.threads_count);
+        self.finished[thread] = true;
     }
 
     /// Set the current thread explicitly.
@@ -159,6 +247,7 @@ impl Scheduler {
         );
         let idx = self.threads_count;
         self.threads_count += 1;
+        self.finished.push(false);
         idx
     }
 
@@ -170,6 +259,7 @@ impl Scheduler {
         debug_assert!(self.threads_count > 1, "Cannot remove last thread");
 
         self.threads_count -= 1;
+        self.finished.remove(thread);
 
         if self.current_thread == thread {
             // Switch to thread 0 or the previous one
@@ -240,6 +330,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scripted_replay() {
+        let mut recording = Scheduler::new(DeterministicRng::new(7), 4, 0.5);
+        recording.start_recording();
+        let original: Vec<_> = (0..50).map(|_| recording.decide()).collect();
+        assert_eq!(recording.recorded_decisions(), original.as_slice());
+
+        // A different seed follows the script, then its own RNG
+        let mut replay = Scheduler::new(DeterministicRng::new(99), 4, 0.5);
+        replay.set_script(recording.recorded_decisions().to_vec());
+        for expected in &original {
+            assert_eq!(replay.decide(), *expected);
+        }
+        assert_eq!(replay.current_thread(), recording.current_thread());
+    }
+
+    #[test]
+    fn test_scripted_forced_switches() {
+        let mut recording = Scheduler::new(DeterministicRng::new(7), 4, 0.5);
+        recording.start_recording();
+        let mut original = Vec::new();
+        for i in 0..50 {
+            if i % 3 == 0 {
+                original.push(ScheduleDecision::SwitchTo(recording.force_switch()));
+            } else {
+                original.push(recording.decide());
+            }
+        }
+        assert_eq!(recording.recorded_decisions(), original.as_slice());
+
+        // Same seed: the script and the RNG agree past the script's end
+        let mut replay = Scheduler::new(DeterministicRng::new(7), 4, 0.5);
+        replay.set_script(original[..25].to_vec());
+        for (i, expected) in original.iter().enumerate() {
+            if i % 3 == 0 {
+                assert_eq!(ScheduleDecision::SwitchTo(replay.force_switch()), *expected);
+            } else {
+                assert_eq!(replay.decide(), *expected);
+            }
+        }
+    }
+
     #[test]
     fn test_force_switch() {
         let rng = DeterministicRng::new(12345);
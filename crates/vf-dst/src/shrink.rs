@@ -0,0 +1,531 @@
+//! Counterexample shrinking.
+//!
+//! A failing DST run can have thousands of operations, most of them
+//! irrelevant. Shrinking re-runs the scenario with the same seed on ever
+//! smaller inputs, keeping a candidate only if the *same* invariant still
+//! fails, until no single step makes progress:
+//!
+//! 1. Disable fault injection (if the failure does not need faults)
+//! 2. Remove whole transactions (SSI only; each one is a client thread)
+//! 3. Remove individual operations
+//!
+//! Steps 2 and 3 use delta debugging (Zeller's ddmin). The result is a
+//! `SavedCounterexample`, so it can be rendered, saved and replayed.
+
+use std::collections::HashSet;
+
+use crate::fault::FaultConfig;
+use crate::fault_injection::{run_dst_scenario_with_config, DstOp, DstResult, DstTestableStack};
+use crate::replay::{SavedCounterexample, SavedOps};
+use crate::ssi_harness::{execute_ssi_ops, DstSsiOp, DstTestableSsi, SsiDstRunner, TxnId};
+use crate::ssi_oracle::{SsiOracleAction, SsiOracleTrace};
+use vf_core::counterexample::Counterexample;
+
+/// Maximum scenario runs spent on shrinking one counterexample.
+///
+/// When the budget runs out, the smallest failing input found so far is
+/// returned.
+pub const SHRINK_RUNS_MAX: u64 = 10_000;
+
+/// A minimized counterexample.
+#[derive(Debug, Clone)]
+pub struct ShrinkResult {
+    /// The invariant the original and the shrunk runs both violate
+    pub invariant: &'static str,
+    /// Operations in the original failing run
+    pub original_ops_count: usize,
+    /// Scenario runs spent shrinking
+    pub runs_count: u64,
+    /// The shrunk run: seed, fault config, operations and counterexample
+    pub saved: SavedCounterexample,
+}
+
+impl ShrinkResult {
+    /// Seed that reproduces the shrunk counterexample.
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.saved.seed
+    }
+
+    /// The shrunk counterexample.
+    #[must_use]
+    pub fn counterexample(&self) -> &Counterexample {
+        &self.saved.counterexample
+    }
+
+    /// Operations left after shrinking.
+    #[must_use]
+    pub fn ops_count(&self) -> usize {
+        saved_len(&self.saved.ops)
+    }
+
+    pub fn format(&self) -> String {
+        format!(
+            "DST_SEED={} {}: shrunk {} -> {} ops in {} runs (faults {})",
+            self.seed(),
+            self.invariant,
+            self.original_ops_count,
+            self.ops_count(),
+            self.runs_count,
+            if self.saved.fault_config.enabled {
+                "needed"
+            } else {
+                "disabled"
+            },
+        )
+    }
+}
+
+/// Counts scenario runs against `SHRINK_RUNS_MAX`.
+struct Budget {
+    runs_count: u64,
+}
+
+impl Budget {
+    fn new() -> Self {
+        Self { runs_count: 0 }
+    }
+
+    /// Take one run from the budget, or `false` if it is spent.
+    fn take(&mut self) -> bool {
+        if self.runs_count >= SHRINK_RUNS_MAX {
+            return false;
+        }
+        self.runs_count += 1;
+        true
+    }
+}
+
+/// Delta debugging: a 1-minimal subsequence of `items` for which
+/// `still_fails` holds.
+///
+/// `still_fails(items)` is assumed to hold. The result is 1-minimal:
+/// removing any single remaining item makes the failure go away (or the
+/// caller's predicate stopped accepting candidates, e.g. out of budget).
+pub fn ddmin<T: Clone>(items: Vec<T>, mut still_fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut items = items;
+    let mut granularity = 2;
+
+    while items.len() >= 2 {
+        let chunk_len = items.len().div_ceil(granularity);
+        let chunks: Vec<(usize, usize)> = (0..items.len())
+            .step_by(chunk_len)
+            .map(|start| (start, (start + chunk_len).min(items.len())))
+            .collect();
+
+        // Reduce to a subset, then to a complement
+        let subset = chunks
+            .iter()
+            .map(|&(start, end)| items[start..end].to_vec())
+            .find(|candidate| still_fails(candidate));
+        if let Some(candidate) = subset {
+            items = candidate;
+            granularity = 2;
+            continue;
+        }
+
+        let complement = chunks
+            .iter()
+            .map(|&(start, end)| [&items[..start], &items[end..]].concat())
+            .find(|candidate| still_fails(candidate));
+        if let Some(candidate) = complement {
+            items = candidate;
+            granularity = (granularity - 1).max(2);
+            continue;
+        }
+
+        if granularity >= items.len() {
+            break;
+        }
+        granularity = (granularity * 2).min(items.len());
+    }
+
+    items
+}
+
+/// Invariants a stack run violated, in `DstResult::format` order.
+fn dst_violations(result: &DstResult) -> Vec<&'static str> {
+    let mut violations = Vec::new();
+    if !result.no_lost_elements {
+        violations.push("NoLostElements");
+    }
+    if !result.no_duplicates {
+        violations.push("NoDuplicates");
+    }
+    violations
+}
+
+/// Shrink a failing `run_dst_scenario_with_config` run.
+///
+/// Returns `None` if the scenario does not fail.
+pub fn shrink_dst<S: DstTestableStack>(
+    seed: u64,
+    fault_config: FaultConfig,
+    operations: Vec<DstOp>,
+) -> Option<ShrinkResult> {
+    let original_ops_count = operations.len();
+    let mut budget = Budget::new();
+
+    let original =
+        run_dst_scenario_with_config::<S>(seed, fault_config.clone(), operations.clone());
+    let invariant = *dst_violations(&original).first()?;
+    let mut fails = |config: &FaultConfig, ops: &[DstOp]| {
+        budget.take()
+            && dst_violations(&run_dst_scenario_with_config::<S>(
+                seed,
+                config.clone(),
+                ops.to_vec(),
+            ))
+            .contains(&invariant)
+    };
+
+    let mut fault_config = fault_config;
+    if fault_config.enabled && fails(&FaultConfig::none(), &operations) {
+        fault_config = FaultConfig::none();
+    }
+    let operations = ddmin(operations, |ops| fails(&fault_config, ops));
+
+    let result = run_dst_scenario_with_config::<S>(seed, fault_config.clone(), operations.clone());
+    let mut saved = SavedCounterexample::from_dst_result(fault_config, operations, &result);
+    note_shrink(
+        &mut saved.counterexample,
+        original_ops_count,
+        saved_len(&saved.ops),
+    );
+    Some(ShrinkResult {
+        invariant,
+        original_ops_count,
+        runs_count: budget.runs_count,
+        saved,
+    })
+}
+
+/// Invariants an SSI run violated.
+fn ssi_violations<S: DstTestableSsi>(runner: &SsiDstRunner<S>) -> Vec<&'static str> {
+    runner
+        .check_invariants()
+        .iter()
+        .filter(|r| !r.holds)
+        .map(|r| r.name)
+        .collect()
+}
+
+fn run_ssi<S: DstTestableSsi>(
+    ssi: S,
+    seed: u64,
+    fault_config: &FaultConfig,
+    ops: &[(TxnId, DstSsiOp)],
+) -> SsiDstRunner<S> {
+    let mut runner = SsiDstRunner::with_fault_config(ssi, seed, fault_config.clone());
+    execute_ssi_ops(&mut runner, ops.to_vec());
+    runner
+}
+
+/// Shrink a failing SSI scenario.
+///
+/// `ssi_factory` must return a fresh store for every run. Returns `None`
+/// if the scenario does not violate an SSI invariant.
+pub fn shrink_ssi<S: DstTestableSsi>(
+    ssi_factory: impl Fn() -> S,
+    seed: u64,
+    fault_config: FaultConfig,
+    operations: Vec<(TxnId, DstSsiOp)>,
+) -> Option<ShrinkResult> {
+    let original_ops_count = operations.len();
+    let mut budget = Budget::new();
+
+    let original = run_ssi(ssi_factory(), seed, &fault_config, &operations);
+    let invariant = *ssi_violations(&original).first()?;
+    let mut fails = |config: &FaultConfig, ops: &[(TxnId, DstSsiOp)]| {
+        budget.take()
+            && ssi_violations(&run_ssi(ssi_factory(), seed, config, ops)).contains(&invariant)
+    };
+
+    let mut fault_config = fault_config;
+    if fault_config.enabled && fails(&FaultConfig::none(), &operations) {
+        fault_config = FaultConfig::none();
+    }
+
+    // Whole transactions first: far fewer candidates than single ops
+    let mut txns: Vec<TxnId> = Vec::new();
+    for (txn, _) in &operations {
+        if !txns.contains(txn) {
+            txns.push(*txn);
+        }
+    }
+    let keep_txns = |ops: &[(TxnId, DstSsiOp)], txns: &[TxnId]| -> Vec<(TxnId, DstSsiOp)> {
+        let txns: HashSet<TxnId> = txns.iter().copied().collect();
+        ops.iter()
+            .filter(|(txn, _)| txns.contains(txn))
+            .cloned()
+            .collect()
+    };
+    let txns = ddmin(txns, |txns| {
+        fails(&fault_config, &keep_txns(&operations, txns))
+    });
+    let operations = keep_txns(&operations, &txns);
+
+    let operations = ddmin(operations, |ops| fails(&fault_config, ops));
+
+    let runner = run_ssi(ssi_factory(), seed, &fault_config, &operations);
+    let mut saved = SavedCounterexample::from_ssi_runner(&runner, operations);
+    note_shrink(
+        &mut saved.counterexample,
+        original_ops_count,
+        saved_len(&saved.ops),
+    );
+    Some(ShrinkResult {
+        invariant,
+        original_ops_count,
+        runs_count: budget.runs_count,
+        saved,
+    })
+}
+
+/// Shrink an SSI oracle trace that violates an SSI invariant.
+///
+/// The trace is replayed as an SSI scenario under the default fault
+/// configuration, as `replay_ssi_oracle` does, so the result can be
+/// replayed with `replay_ssi`.
+pub fn shrink_ssi_oracle<S: DstTestableSsi>(
+    ssi_factory: impl Fn() -> S,
+    seed: u64,
+    oracle: &SsiOracleTrace,
+) -> Option<ShrinkResult> {
+    let operations = oracle
+        .actions
+        .iter()
+        .map(|action| match *action {
+            SsiOracleAction::Begin(txn) => (txn as TxnId, DstSsiOp::Begin),
+            SsiOracleAction::Read(txn, key) => (txn as TxnId, DstSsiOp::Read(key as u64)),
+            SsiOracleAction::Scan(txn, start, end) => {
+                (txn as TxnId, DstSsiOp::Scan(start as u64, end as u64))
+            }
+            // Same value encoding as `replay_ssi_oracle`
+            SsiOracleAction::Write(txn, key) => (
+                txn as TxnId,
+                DstSsiOp::Write(key as u64, (txn as u64) * 100 + key as u64),
+            ),
+            SsiOracleAction::Commit(txn) => (txn as TxnId, DstSsiOp::Commit),
+            SsiOracleAction::Abort(txn) => (txn as TxnId, DstSsiOp::Abort),
+        })
+        .collect();
+    shrink_ssi(ssi_factory, seed, FaultConfig::default(), operations)
+}
+
+fn saved_len(ops: &SavedOps) -> usize {
+    match ops {
+        SavedOps::Stack(ops) => ops.len(),
+        SavedOps::Ssi(ops) => ops.len(),
+        SavedOps::Controlled {
+            threads_count,
+            operations_per_thread,
+        } => *threads_count * *operations_per_thread as usize,
+    }
+}
+
+fn note_shrink(counterexample: &mut Counterexample, original_ops_count: usize, ops_count: usize) {
+    let note = format!(
+        "shrunk from {} to {} operations",
+        original_ops_count, ops_count
+    );
+    counterexample.description = Some(match counterexample.description.take() {
+        Some(description) => format!("{}\n({})", description.trim_end(), note),
+        None => note,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::{replay_dst, replay_ssi};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_ddmin_finds_minimal_subset() {
+        let items: Vec<u32> = (0..100).collect();
+        let mut runs = 0;
+        let result = ddmin(items, |candidate| {
+            runs += 1;
+            candidate.contains(&13) && candidate.contains(&71)
+        });
+        assert_eq!(result, vec![13, 71]);
+        assert!(runs < 200, "ddmin took {} runs", runs);
+    }
+
+    /// Stack that silently drops pushes once it holds three values.
+    struct BoundedStack {
+        values: Mutex<Vec<u64>>,
+    }
+
+    impl DstTestableStack for BoundedStack {
+        fn new() -> Self {
+            Self {
+                values: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn push(&self, value: u64) {
+            let mut values = self.values.lock().unwrap();
+            if values.len() < 3 {
+                values.push(value);
+            }
+        }
+
+        fn pop(&self) -> Option<u64> {
+            self.values.lock().unwrap().pop()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.values.lock().unwrap().is_empty()
+        }
+
+        fn get_contents(&self) -> Vec<u64> {
+            self.values.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn test_shrink_dst() {
+        let mut operations = Vec::new();
+        for i in 0..500 {
+            operations.push(DstOp::Push(i));
+            if i % 3 == 0 {
+                operations.push(DstOp::Pop);
+            }
+        }
+
+        let result = shrink_dst::<BoundedStack>(42, FaultConfig::aggressive(), operations).unwrap();
+        assert_eq!(result.invariant, "NoLostElements");
+        assert_eq!(result.ops_count(), 4, "{}", result.format());
+        assert!(!result.saved.fault_config.enabled);
+        assert_eq!(result.counterexample().dst_seed, Some(42));
+        assert!(result
+            .counterexample()
+            .description
+            .as_deref()
+            .unwrap()
+            .contains("to 4 operations"));
+
+        let replay = replay_dst::<BoundedStack>(&result.saved).unwrap();
+        assert!(replay.still_fails);
+    }
+
+    #[test]
+    fn test_shrink_passing_scenario() {
+        let operations = vec![DstOp::Push(1), DstOp::Pop];
+        assert!(shrink_dst::<BoundedStack>(1, FaultConfig::none(), operations).is_none());
+    }
+
+    /// SSI that commits everything and reports transactions that wrote
+    /// key 7 as the pivot of a dangerous structure.
+    struct Key7Ssi {
+        next_txn: Mutex<TxnId>,
+        committed: Mutex<HashSet<TxnId>>,
+        wrote_key7: Mutex<HashSet<TxnId>>,
+        data: Mutex<HashMap<u64, u64>>,
+    }
+
+    impl Key7Ssi {
+        fn new() -> Self {
+            Self {
+                next_txn: Mutex::new(1),
+                committed: Mutex::new(HashSet::new()),
+                wrote_key7: Mutex::new(HashSet::new()),
+                data: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    impl DstTestableSsi for Key7Ssi {
+        fn begin(&self) -> TxnId {
+            let mut next = self.next_txn.lock().unwrap();
+            *next += 1;
+            *next - 1
+        }
+
+        fn read(&self, _txn: TxnId, key: u64) -> Option<u64> {
+            self.data.lock().unwrap().get(&key).copied()
+        }
+
+        fn write(&self, txn: TxnId, key: u64, value: u64) -> bool {
+            if key == 7 {
+                self.wrote_key7.lock().unwrap().insert(txn);
+            }
+            self.data.lock().unwrap().insert(key, value);
+            true
+        }
+
+        fn commit(&self, txn: TxnId) -> bool {
+            self.committed.lock().unwrap().insert(txn);
+            true
+        }
+
+        fn abort(&self, _txn: TxnId) {}
+
+        fn is_active(&self, txn: TxnId) -> bool {
+            !self.committed.lock().unwrap().contains(&txn)
+        }
+
+        fn committed_txns(&self) -> HashSet<TxnId> {
+            self.committed.lock().unwrap().clone()
+        }
+
+        fn get_current_value(&self, key: u64) -> Option<u64> {
+            self.data.lock().unwrap().get(&key).copied()
+        }
+
+        fn get_conflict_flags(&self, txn: TxnId) -> (bool, bool) {
+            let pivot = self.wrote_key7.lock().unwrap().contains(&txn);
+            (pivot, pivot)
+        }
+    }
+
+    #[test]
+    fn test_shrink_ssi() {
+        // 30 transactions with distinct write keys; only txn 17 writes key 7
+        let mut operations = Vec::new();
+        for txn in 1..=30u64 {
+            let key = if txn == 17 { 7 } else { 100 + txn };
+            operations.push((txn, DstSsiOp::Begin));
+            operations.push((txn, DstSsiOp::Read(1)));
+            operations.push((txn, DstSsiOp::Write(key, txn)));
+            operations.push((txn, DstSsiOp::Commit));
+        }
+
+        let result = shrink_ssi(Key7Ssi::new, 9, FaultConfig::none(), operations).unwrap();
+        assert_eq!(result.invariant, "NoCommittedDangerousStructures");
+        assert_eq!(
+            result.saved.ops,
+            SavedOps::Ssi(vec![
+                (17, DstSsiOp::Begin),
+                (17, DstSsiOp::Write(7, 17)),
+                (17, DstSsiOp::Commit),
+            ]),
+            "{}",
+            result.format()
+        );
+
+        let replay = replay_ssi(Key7Ssi::new(), &result.saved).unwrap();
+        assert!(replay.still_fails);
+    }
+
+    #[test]
+    fn test_shrink_ssi_oracle() {
+        let mut oracle = SsiOracleTrace::new("key7");
+        for txn in 1..=5u8 {
+            oracle.add(SsiOracleAction::Begin(txn));
+            oracle.add(SsiOracleAction::Write(txn, txn + 5));
+            oracle.add(SsiOracleAction::Commit(txn));
+        }
+
+        // Only txn 2 writes key 7; whether faults are needed depends on the seed
+        let result = shrink_ssi_oracle(Key7Ssi::new, 3, &oracle).unwrap();
+        assert!(result.ops_count() <= 3, "{}", result.format());
+        assert!(matches!(
+            &result.saved.ops,
+            SavedOps::Ssi(ops) if ops.iter().all(|(txn, _)| *txn == 2)
+        ));
+    }
+}
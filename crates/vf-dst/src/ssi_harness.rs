@@ -12,12 +12,48 @@
 //! |-------------|-------------------|
 //! | BeforeBegin | Connection failure before transaction starts |
 //! | AfterBegin | Crash after begin, before any operations |
-//! | BeforeRead | Network timeout before read |
-//! | AfterRead | Crash after read, SIREAD lock acquired |
+//! | BeforeRead | Network timeout before read or scan |
+//! | AfterRead | Crash after read or scan, SIREAD lock acquired |
 //! | BeforeWrite | Allocation failure before write |
 //! | AfterWrite | Crash after write, lock held |
 //! | BeforeCommit | Network partition before commit |
 //! | AfterCommit | Crash after commit, before client notified |
+//! | Vacuum | Garbage collection runs before an operation (not a failure) |
+//!
+//! # Vacuum
+//!
+//! At the `Vacuum` fault point the runner calls [`DstTestableSsi::vacuum`]
+//! with probability [`FaultConfig::vacuum_probability`], so pruning
+//! interleaves with live transactions. Each pass that removes anything is
+//! recorded as an [`SsiOperation::Vacuum`], and the versions it pruned are
+//! checked against every snapshot live at the time. Like the other fault
+//! points it only fires while fault injection is enabled, so runs with
+//! [`FaultConfig::none()`] never vacuum.
+//!
+//! # Lock-Based Protocols
+//!
+//! The same runner drives strict 2PL and plain MVCC stores. A read or
+//! write that waits on another transaction's lock fails with
+//! `SsiFaultType::LockWait` and records nothing; the client retries it
+//! later. An operation after which the store no longer considers the
+//! transaction active (a deadlock victim) is recorded as an abort.
+//! [`run_ssi_protocol`] runs one scenario with one seed against a store
+//! and checks the resulting history with `elle`, so protocols can be
+//! compared on identical inputs.
+//!
+//! # Read-Only Transactions
+//!
+//! A read-only transaction may be deferrable: it waits for a safe snapshot
+//! before it begins. The runner reports that wait as
+//! `SsiFaultType::LockWait`. Whether a snapshot ended up safe is taken
+//! from the store at commit, like the conflict flags.
+//!
+//! # Range Scans
+//!
+//! A scan reads every key in an inclusive range. In the histories it
+//! produces, keys the scan did not return were read as absent, so a
+//! concurrent insert into the range is a (predicate) anti-dependency
+//! and a phantom shows up as a dependency cycle like any other.
 //!
 //! # Architecture
 //!
@@ -36,8 +72,11 @@
 
 use crate::fault::{FaultConfig, FaultInjector};
 use crate::random::DeterministicRng;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::RangeInclusive;
 
+use vf_core::invariants::elle::{self, IsolationReport, Mop, TxnHistory, TxnOutcome};
 use vf_core::invariants::ssi::{SsiHistory, InvariantResult};
 
 /// Transaction identifier.
@@ -60,6 +99,7 @@ pub enum SsiFaultPoint {
     AfterWrite,
     BeforeCommit,
     AfterCommit,
+    Vacuum,
 }
 
 /// Types of faults that can be injected.
@@ -75,6 +115,26 @@ pub enum SsiFaultType {
     Delay,
     /// Out of memory
     OutOfMemory,
+    /// Operation waits on a lock held by another transaction (not injected)
+    LockWait,
+}
+
+/// What one vacuum pass removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VacuumReport {
+    /// Pruned versions, as (key, writer)
+    pub versions: Vec<(KeyId, TxnId)>,
+    /// Released SIREAD locks, key and range
+    pub siread_locks: usize,
+    /// Finished transactions whose state was dropped
+    pub txns: usize,
+}
+
+impl VacuumReport {
+    /// Whether the pass removed nothing.
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty() && self.siread_locks == 0 && self.txns == 0
+    }
 }
 
 /// Result of an SSI operation.
@@ -87,9 +147,26 @@ pub trait DstTestableSsi: Send + Sync {
     /// Begin a new transaction, returns transaction ID.
     fn begin(&self) -> TxnId;
 
+    /// Begin a read-only transaction. A deferrable one waits for a safe
+    /// snapshot: `None` means it would have to wait and did not begin.
+    /// Default: a regular transaction (the store has no read-only mode)
+    fn begin_read_only(&self, _deferrable: bool) -> Option<TxnId> {
+        Some(self.begin())
+    }
+
     /// Read a key within a transaction.
     fn read(&self, txn: TxnId, key: KeyId) -> Option<Value>;
 
+    /// Read every present key in an inclusive range, in key order.
+    ///
+    /// Default: a point read of each key in the range, which is fine for
+    /// the small key spaces DST uses but takes no lock on the range itself
+    fn scan(&self, txn: TxnId, range: RangeInclusive<KeyId>) -> Vec<(KeyId, Value)> {
+        range
+            .filter_map(|key| self.read(txn, key).map(|value| (key, value)))
+            .collect()
+    }
+
     /// Write a key within a transaction.
     fn write(&self, txn: TxnId, key: KeyId, value: Value) -> bool;
 
@@ -102,7 +179,7 @@ pub trait DstTestableSsi: Send + Sync {
     /// Check if transaction is active.
     fn is_active(&self, txn: TxnId) -> bool;
 
-    /// Get all committed transaction IDs.
+    /// Get committed transaction IDs (a store may forget those vacuum pruned).
     fn committed_txns(&self) -> HashSet<TxnId>;
 
     /// Get current value of a key (for invariant checking).
@@ -113,6 +190,25 @@ pub trait DstTestableSsi: Send + Sync {
     fn get_conflict_flags(&self, _txn: TxnId) -> (bool, bool) {
         (false, false)
     }
+
+    /// Whether a read-only transaction's snapshot is known to be safe
+    /// (for invariant checking). Default: false
+    fn is_safe_snapshot(&self, _txn: TxnId) -> bool {
+        false
+    }
+
+    /// Prune versions, SIREAD locks and transaction state no live or
+    /// future snapshot can observe. Default: nothing to collect
+    fn vacuum(&self) -> VacuumReport {
+        VacuumReport::default()
+    }
+
+    /// Whether the transaction's last read or write is waiting on a lock
+    /// held by another transaction, and so did not take effect.
+    /// Default: false (the store never blocks)
+    fn is_waiting(&self, _txn: TxnId) -> bool {
+        false
+    }
 }
 
 /// DST runner for SSI implementations.
@@ -120,16 +216,14 @@ pub struct SsiDstRunner<S> {
     ssi: S,
     rng: DeterministicRng,
     fault_injector: FaultInjector,
+    vacuum_rng: DeterministicRng,
     seed: u64,
 
     // Tracking for invariant verification
     operations: Vec<SsiOperation>,
     active_txns: HashSet<TxnId>,
-
-    // Timestamps for history building
-    timestamp: u64,
-    txn_start_ts: HashMap<TxnId, u64>,
-    txn_commit_ts: HashMap<TxnId, u64>,
+    /// Flags of finished transactions, read before vacuum can drop them
+    finished_flags: HashMap<TxnId, TxnFlags>,
 
     // Statistics
     operations_count: u64,
@@ -137,60 +231,96 @@ pub struct SsiDstRunner<S> {
     txns_started: u64,
     txns_committed: u64,
     txns_aborted: u64,
+    lock_waits: u64,
+    vacuums: u64,
+}
+
+/// Conflict flags and snapshot safety the store reported for a
+/// transaction when it committed or aborted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxnFlags {
+    pub in_conflict: bool,
+    pub out_conflict: bool,
+    pub safe_snapshot: bool,
 }
 
 /// Recorded operation for history.
 #[derive(Debug, Clone)]
 pub enum SsiOperation {
     Begin(TxnId),
+    BeginReadOnly(TxnId),
     Read { txn: TxnId, key: KeyId, value: Option<Value> },
+    Scan { txn: TxnId, range: RangeInclusive<KeyId>, results: Vec<(KeyId, Value)> },
     Write { txn: TxnId, key: KeyId, value: Value },
     Commit(TxnId),
     Abort(TxnId),
     FaultInjected { txn: TxnId, fault: SsiFaultType, point: SsiFaultPoint },
+    Vacuum(VacuumReport),
 }
 
 impl<S: DstTestableSsi> SsiDstRunner<S> {
     /// Create a new DST runner.
     pub fn new(ssi: S, seed: u64) -> Self {
+        Self::with_fault_config(ssi, seed, FaultConfig::default())
+    }
+
+    /// Create a DST runner with an explicit fault configuration.
+    pub fn with_fault_config(ssi: S, seed: u64, fault_config: FaultConfig) -> Self {
         let rng = DeterministicRng::new(seed);
         let fault_injector = FaultInjector::new(
             DeterministicRng::new(seed.wrapping_add(1)),
-            FaultConfig::default(),
+            fault_config,
         );
 
         Self {
             ssi,
             rng,
             fault_injector,
+            vacuum_rng: DeterministicRng::new(seed.wrapping_add(2)),
             seed,
             operations: Vec::new(),
             active_txns: HashSet::new(),
-            timestamp: 1,
-            txn_start_ts: HashMap::new(),
-            txn_commit_ts: HashMap::new(),
+            finished_flags: HashMap::new(),
             operations_count: 0,
             faults_injected: 0,
             txns_started: 0,
             txns_committed: 0,
             txns_aborted: 0,
+            lock_waits: 0,
+            vacuums: 0,
         }
     }
 
-    /// Advance timestamp.
-    fn tick(&mut self) -> u64 {
-        let ts = self.timestamp;
-        self.timestamp += 1;
-        ts
-    }
-
     /// Get the seed for reproduction.
     pub fn seed(&self) -> u64 {
         self.seed
     }
 
+    /// Get the fault configuration for reproduction.
+    pub fn fault_config(&self) -> &FaultConfig {
+        self.fault_injector.config()
+    }
+
+    /// The implementation under test, for checking its own invariants.
+    pub fn ssi(&self) -> &S {
+        &self.ssi
+    }
+
     /// Begin a transaction with fault injection.
     pub fn begin(&mut self) -> SsiResult<TxnId> {
+        self.begin_txn(None)
+    }
+
+    /// Begin a read-only transaction with fault injection.
+    pub fn begin_read_only(&mut self, deferrable: bool) -> SsiResult<TxnId> {
+        self.begin_txn(Some(deferrable))
+    }
+
+    /// Begin a read-write (`None`) or read-only (`Some(deferrable)`) transaction.
+    fn begin_txn(&mut self, read_only: Option<bool>) -> SsiResult<TxnId> {
+        // Fault point: background vacuum
+        self.maybe_vacuum();
+
         // Fault point: before begin
         if let Some(fault) = self.maybe_inject_fault(SsiFaultPoint::BeforeBegin) {
             self.faults_injected += 1;
@@ -198,13 +328,25 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
         }
 
         // Execute PURE operation
-        let txn = self.ssi.begin();
-        let ts = self.tick();
-        self.txn_start_ts.insert(txn, ts);
         self.operations_count += 1;
+        let txn = match read_only {
+            None => {
+                let txn = self.ssi.begin();
+                self.operations.push(SsiOperation::Begin(txn));
+                txn
+            }
+            Some(deferrable) => {
+                let Some(txn) = self.ssi.begin_read_only(deferrable) else {
+                    // Deferrable begin waiting for a safe snapshot
+                    self.lock_waits += 1;
+                    return Err(SsiFaultType::LockWait);
+                };
+                self.operations.push(SsiOperation::BeginReadOnly(txn));
+                txn
+            }
+        };
         self.txns_started += 1;
         self.active_txns.insert(txn);
-        self.operations.push(SsiOperation::Begin(txn));
 
         // Fault point: after begin
         if let Some(fault) = self.maybe_inject_fault(SsiFaultPoint::AfterBegin) {
@@ -227,6 +369,9 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
             return Err(SsiFaultType::SystemAbort);
         }
 
+        // Fault point: background vacuum
+        self.maybe_vacuum();
+
         // Fault point: before read
         if let Some(fault) = self.maybe_inject_fault(SsiFaultPoint::BeforeRead) {
             self.faults_injected += 1;
@@ -241,6 +386,7 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
         // Execute PURE operation
         let value = self.ssi.read(txn, key);
         self.operations_count += 1;
+        self.check_lock_outcome(txn)?;
         self.operations.push(SsiOperation::Read { txn, key, value });
 
         // Fault point: after read
@@ -258,12 +404,64 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
         Ok(value)
     }
 
+    /// Scan an inclusive key range with fault injection.
+    pub fn scan(
+        &mut self,
+        txn: TxnId,
+        range: RangeInclusive<KeyId>,
+    ) -> SsiResult<Vec<(KeyId, Value)>> {
+        if !self.active_txns.contains(&txn) {
+            return Err(SsiFaultType::SystemAbort);
+        }
+
+        // Fault point: background vacuum
+        self.maybe_vacuum();
+
+        // Fault point: before read
+        if let Some(fault) = self.maybe_inject_fault(SsiFaultPoint::BeforeRead) {
+            self.faults_injected += 1;
+            self.operations.push(SsiOperation::FaultInjected {
+                txn,
+                fault,
+                point: SsiFaultPoint::BeforeRead,
+            });
+            return Err(fault);
+        }
+
+        // Execute PURE operation
+        let results = self.ssi.scan(txn, range.clone());
+        self.operations_count += 1;
+        self.check_lock_outcome(txn)?;
+        self.operations.push(SsiOperation::Scan {
+            txn,
+            range,
+            results: results.clone(),
+        });
+
+        // Fault point: after read
+        if let Some(fault) = self.maybe_inject_fault(SsiFaultPoint::AfterRead) {
+            self.faults_injected += 1;
+            self.operations.push(SsiOperation::FaultInjected {
+                txn,
+                fault,
+                point: SsiFaultPoint::AfterRead,
+            });
+            // Scan completed, range SIREAD lock acquired, but client crashes
+            return Err(fault);
+        }
+
+        Ok(results)
+    }
+
     /// Write with fault injection.
     pub fn write(&mut self, txn: TxnId, key: KeyId, value: Value) -> SsiResult<bool> {
         if !self.active_txns.contains(&txn) {
             return Err(SsiFaultType::SystemAbort);
         }
 
+        // Fault point: background vacuum
+        self.maybe_vacuum();
+
         // Fault point: before write
         if let Some(fault) = self.maybe_inject_fault(SsiFaultPoint::BeforeWrite) {
             self.faults_injected += 1;
@@ -280,7 +478,10 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
         // Execute PURE operation
         let success = self.ssi.write(txn, key, value);
         self.operations_count += 1;
-        self.operations.push(SsiOperation::Write { txn, key, value });
+        self.check_lock_outcome(txn)?;
+        if success {
+            self.operations.push(SsiOperation::Write { txn, key, value });
+        }
 
         // Fault point: after write
         if let Some(fault) = self.maybe_inject_fault(SsiFaultPoint::AfterWrite) {
@@ -303,6 +504,9 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
             return Err(SsiFaultType::SystemAbort);
         }
 
+        // Fault point: background vacuum
+        self.maybe_vacuum();
+
         // Fault point: before commit
         if let Some(fault) = self.maybe_inject_fault(SsiFaultPoint::BeforeCommit) {
             self.faults_injected += 1;
@@ -322,15 +526,11 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
         self.operations_count += 1;
 
         if success {
-            let ts = self.tick();
-            self.txn_commit_ts.insert(txn, ts);
             self.txns_committed += 1;
-            self.active_txns.remove(&txn);
-            self.operations.push(SsiOperation::Commit(txn));
+            self.finish(txn, SsiOperation::Commit(txn));
         } else {
             self.txns_aborted += 1;
-            self.active_txns.remove(&txn);
-            self.operations.push(SsiOperation::Abort(txn));
+            self.finish(txn, SsiOperation::Abort(txn));
         }
 
         // Fault point: after commit
@@ -352,9 +552,62 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
     pub fn abort(&mut self, txn: TxnId) {
         if self.active_txns.contains(&txn) {
             self.ssi.abort(txn);
-            self.active_txns.remove(&txn);
             self.txns_aborted += 1;
-            self.operations.push(SsiOperation::Abort(txn));
+            self.finish(txn, SsiOperation::Abort(txn));
+        }
+    }
+
+    /// Record that a transaction committed or aborted, with its flags.
+    ///
+    /// The flags are read now, while the store still has them: a later
+    /// vacuum may drop the transaction's state.
+    fn finish(&mut self, txn: TxnId, outcome: SsiOperation) {
+        let (in_conflict, out_conflict) = self.ssi.get_conflict_flags(txn);
+        let flags = TxnFlags {
+            in_conflict,
+            out_conflict,
+            safe_snapshot: self.ssi.is_safe_snapshot(txn),
+        };
+        self.finished_flags.insert(txn, flags);
+        self.active_txns.remove(&txn);
+        self.operations.push(outcome);
+    }
+
+    /// Flags a transaction had when it committed or aborted.
+    pub fn txn_flags(&self, txn: TxnId) -> Option<TxnFlags> {
+        self.finished_flags.get(&txn).copied()
+    }
+
+    /// Surface a lock wait or a protocol abort after a read or write.
+    fn check_lock_outcome(&mut self, txn: TxnId) -> SsiResult<()> {
+        if !self.ssi.is_active(txn) {
+            // Aborted by the store, e.g. as a deadlock victim
+            self.txns_aborted += 1;
+            self.finish(txn, SsiOperation::Abort(txn));
+            return Err(SsiFaultType::SystemAbort);
+        }
+        if self.ssi.is_waiting(txn) {
+            self.lock_waits += 1;
+            return Err(SsiFaultType::LockWait);
+        }
+        Ok(())
+    }
+
+    /// Vacuum fault point: maybe run a vacuum pass, recording it if it
+    /// removed anything.
+    fn maybe_vacuum(&mut self) {
+        let config = self.fault_injector.config();
+        if !config.enabled {
+            return;
+        }
+        let probability = config.vacuum_probability;
+        if !self.vacuum_rng.gen_bool(probability) {
+            return;
+        }
+        let report = self.ssi.vacuum();
+        if !report.is_empty() {
+            self.vacuums += 1;
+            self.operations.push(SsiOperation::Vacuum(report));
         }
     }
 
@@ -383,6 +636,8 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
             txns_started: self.txns_started,
             txns_committed: self.txns_committed,
             txns_aborted: self.txns_aborted,
+            lock_waits: self.lock_waits,
+            vacuums: self.vacuums,
         }
     }
 
@@ -394,64 +649,163 @@ impl<S: DstTestableSsi> SsiDstRunner<S> {
     /// Convert operation history to SsiHistory for invariant checking.
     ///
     /// This bridges the DST operation log to the vf-core invariant checker.
-    pub fn to_ssi_history(&self) -> SsiHistory {
+    /// The position in the operation log is used as the logical clock, and
+    /// the version a read observed is inferred from the value it returned
+    /// when the read happened (see `WriteIndex::version_read`).
+    ///
+    /// Returns an error when a read cannot be attributed to a single
+    /// writer, since guessing could report anomalies that did not happen
+    /// or hide ones that did.
+    pub fn to_ssi_history(&self) -> Result<SsiHistory, String> {
         let mut history = SsiHistory::new();
+        let mut writes = WriteIndex::default();
 
-        // Track which txn wrote which version of each key
-        let mut key_versions: HashMap<KeyId, TxnId> = HashMap::new();
-
-        for op in &self.operations {
+        for (i, op) in self.operations.iter().enumerate() {
+            let ts = i as u64 + 1;
             match op {
                 SsiOperation::Begin(txn) => {
-                    let ts = self.txn_start_ts.get(txn).copied().unwrap_or(0);
+                    writes.begun.insert(*txn, ts);
                     history.begin(*txn, ts);
                 }
-                SsiOperation::Read { txn, key, value: _ } => {
-                    // Find the version we read (which txn wrote it)
-                    let version = key_versions.get(key).copied();
-                    let ts = self.tick_for_op();
+                SsiOperation::BeginReadOnly(txn) => {
+                    writes.begun.insert(*txn, ts);
+                    history.begin_read_only(*txn, ts);
+                }
+                SsiOperation::Read { txn, key, value } => {
+                    let version = match value {
+                        Some(v) => writes.version_read(*txn, *key, *v)?,
+                        None => None,
+                    };
                     history.read(*txn, *key, version, ts);
                 }
-                SsiOperation::Write { txn, key, value: _ } => {
-                    key_versions.insert(*key, *txn);
-                    let ts = self.tick_for_op();
+                SsiOperation::Scan {
+                    txn,
+                    range,
+                    results,
+                } => {
+                    let observed = results
+                        .iter()
+                        .map(|&(key, v)| Ok((key, writes.version_read(*txn, key, v)?)))
+                        .collect::<Result<_, String>>()?;
+                    history.range_read(*txn, *range.start(), *range.end(), observed, ts);
+                }
+                SsiOperation::Write { txn, key, value } => {
+                    writes.write(*txn, *key, *value);
                     history.write(*txn, *key, ts);
                 }
                 SsiOperation::Commit(txn) => {
-                    // Get conflict flags from the implementation
-                    let (in_c, out_c) = self.ssi.get_conflict_flags(*txn);
-                    if in_c {
+                    // Conflict flags the implementation reported at commit
+                    let flags = self.txn_flags(*txn).unwrap_or_default();
+                    if flags.in_conflict {
                         history.set_in_conflict(*txn);
                     }
-                    if out_c {
+                    if flags.out_conflict {
                         history.set_out_conflict(*txn);
                     }
-                    let ts = self.txn_commit_ts.get(txn).copied().unwrap_or(0);
+                    if flags.safe_snapshot {
+                        history.mark_safe(*txn);
+                    }
+                    writes.committed.insert(*txn, ts);
                     history.commit(*txn, ts);
                 }
                 SsiOperation::Abort(txn) => {
-                    history.abort(*txn);
+                    history.abort_at(*txn, ts);
                 }
                 SsiOperation::FaultInjected { .. } => {
                     // Faults don't affect history
                 }
+                SsiOperation::Vacuum(report) => {
+                    history.vacuum(report.versions.clone(), ts);
+                }
             }
         }
 
-        history
+        Ok(history)
     }
 
-    /// Helper for timestamp in history building (doesn't actually tick).
-    fn tick_for_op(&self) -> u64 {
-        self.timestamp
+    /// Convert operations to a read-write register history for `elle`.
+    ///
+    /// Unlike `to_ssi_history`, this keeps the values transactions actually
+    /// read and wrote, so versions are inferred by the isolation checker
+    /// rather than here. Transactions that never finished are `Unknown`.
+    ///
+    /// A scan becomes a read of each key it returned, plus a read of
+    /// nothing for each key in its range that some transaction writes.
+    pub fn to_txn_history(&self) -> TxnHistory {
+        let written: BTreeSet<KeyId> = self
+            .operations
+            .iter()
+            .filter_map(|op| match op {
+                SsiOperation::Write { key, .. } => Some(*key),
+                _ => None,
+            })
+            .collect();
+        let mut order: Vec<TxnId> = Vec::new();
+        let mut mops: HashMap<TxnId, Vec<Mop>> = HashMap::new();
+        let mut outcomes: HashMap<TxnId, TxnOutcome> = HashMap::new();
+
+        for op in &self.operations {
+            match op {
+                SsiOperation::Begin(txn) | SsiOperation::BeginReadOnly(txn) => {
+                    order.push(*txn);
+                    outcomes.insert(*txn, TxnOutcome::Unknown);
+                }
+                SsiOperation::Read { txn, key, value } => {
+                    mops.entry(*txn).or_default().push(Mop::Read {
+                        key: *key,
+                        value: *value,
+                    });
+                }
+                SsiOperation::Scan {
+                    txn,
+                    range,
+                    results,
+                } => {
+                    let returned: HashMap<KeyId, Value> = results.iter().copied().collect();
+                    let keys: BTreeSet<KeyId> = written
+                        .range(range.clone())
+                        .copied()
+                        .chain(returned.keys().copied())
+                        .collect();
+                    mops.entry(*txn).or_default().extend(keys.into_iter().map(|key| {
+                        Mop::Read {
+                            key,
+                            value: returned.get(&key).copied(),
+                        }
+                    }));
+                }
+                SsiOperation::Write { txn, key, value } => {
+                    mops.entry(*txn).or_default().push(Mop::Write {
+                        key: *key,
+                        value: *value,
+                    });
+                }
+                SsiOperation::Commit(txn) => {
+                    outcomes.insert(*txn, TxnOutcome::Committed);
+                }
+                SsiOperation::Abort(txn) => {
+                    outcomes.insert(*txn, TxnOutcome::Aborted);
+                }
+                SsiOperation::FaultInjected { .. } | SsiOperation::Vacuum(_) => {}
+            }
+        }
+
+        let mut history = TxnHistory::new();
+        for txn in order {
+            let outcome = outcomes.get(&txn).copied().unwrap_or(TxnOutcome::Unknown);
+            history.push(txn, outcome, mops.remove(&txn).unwrap_or_default());
+        }
+        history
     }
 
     /// Check all SSI invariants against the current history.
     ///
     /// Returns a vector of results for each invariant.
     pub fn check_invariants(&self) -> Vec<InvariantResult> {
-        let history = self.to_ssi_history();
-        vf_core::invariants::ssi::check_all(&history)
+        match self.to_ssi_history() {
+            Ok(history) => vf_core::invariants::ssi::check_all(&history),
+            Err(message) => vec![InvariantResult::violated("UnambiguousReads", message)],
+        }
     }
 
     /// Check if all invariants hold.
@@ -527,15 +881,19 @@ pub struct SsiDstStats {
     pub txns_started: u64,
     pub txns_committed: u64,
     pub txns_aborted: u64,
+    pub lock_waits: u64,
+    pub vacuums: u64,
 }
 
 impl SsiDstStats {
     pub fn format(&self) -> String {
         format!(
-            "DST_SEED={} ops={} faults={} txns(started={} committed={} aborted={})",
+            "DST_SEED={} ops={} faults={} waits={} vacuums={} txns(started={} committed={} aborted={})",
             self.seed,
             self.operations_count,
             self.faults_injected,
+            self.lock_waits,
+            self.vacuums,
             self.txns_started,
             self.txns_committed,
             self.txns_aborted
@@ -544,10 +902,15 @@ impl SsiDstStats {
 }
 
 /// DST operation for scenario replay.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DstSsiOp {
     Begin,
+    BeginReadOnly,
+    /// Read-only, waiting for a safe snapshot
+    BeginDeferrable,
     Read(KeyId),
+    /// Scan the inclusive key range
+    Scan(KeyId, KeyId),
     Write(KeyId, Value),
     Commit,
     Abort,
@@ -560,6 +923,75 @@ pub fn run_ssi_scenario<S: DstTestableSsi>(
     operations: Vec<(TxnId, DstSsiOp)>,
 ) -> SsiDstResult {
     let mut runner = SsiDstRunner::new(ssi, seed);
+    let errors = execute_ssi_ops(&mut runner, operations);
+
+    SsiDstResult {
+        passed: true, // Faults are expected, not failures
+        stats: runner.stats(),
+        fault_errors: errors,
+    }
+}
+
+/// One protocol's run of a scenario.
+#[derive(Debug, Clone)]
+pub struct SsiProtocolRun {
+    /// Protocol name, for reports
+    pub protocol: &'static str,
+    pub stats: SsiDstStats,
+    /// Read-write register history the run produced
+    pub history: TxnHistory,
+    /// Anomalies `elle` found in the history
+    pub isolation: IsolationReport,
+}
+
+impl SsiProtocolRun {
+    pub fn format(&self) -> String {
+        let level = self
+            .isolation
+            .strongest_satisfied()
+            .map_or("none", |level| level.name());
+        format!(
+            "[{}] isolation={} anomalies={} {}",
+            self.protocol,
+            level,
+            self.isolation.anomalies.len(),
+            self.stats.format()
+        )
+    }
+}
+
+/// Run a scenario against one protocol and check the history's isolation.
+///
+/// Running the same operations with the same seed against several stores
+/// injects the same faults into each, so the runs differ only in how the
+/// protocols resolve conflicts.
+pub fn run_ssi_protocol<S: DstTestableSsi>(
+    protocol: &'static str,
+    ssi: S,
+    seed: u64,
+    operations: Vec<(TxnId, DstSsiOp)>,
+) -> SsiProtocolRun {
+    let mut runner = SsiDstRunner::new(ssi, seed);
+    execute_ssi_ops(&mut runner, operations);
+
+    let history = runner.to_txn_history();
+    let isolation = elle::check(&history);
+    SsiProtocolRun {
+        protocol,
+        stats: runner.stats(),
+        history,
+        isolation,
+    }
+}
+
+/// Execute scenario operations on a runner, returning the faults hit.
+///
+/// Scenario transaction ids are mapped to the ids the implementation
+/// hands out from `begin`.
+pub(crate) fn execute_ssi_ops<S: DstTestableSsi>(
+    runner: &mut SsiDstRunner<S>,
+    operations: Vec<(TxnId, DstSsiOp)>,
+) -> Vec<String> {
     let mut txn_map: HashMap<TxnId, TxnId> = HashMap::new(); // scenario txn -> actual txn
     let mut errors = Vec::new();
 
@@ -574,6 +1006,12 @@ pub fn run_ssi_scenario<S: DstTestableSsi>(
                     Err(e) => Err(e),
                 }
             }
+            DstSsiOp::BeginReadOnly | DstSsiOp::BeginDeferrable => {
+                let deferrable = op == DstSsiOp::BeginDeferrable;
+                runner.begin_read_only(deferrable).map(|actual_txn| {
+                    txn_map.insert(scenario_txn, actual_txn);
+                })
+            }
             DstSsiOp::Read(key) => {
                 if let Some(&actual_txn) = txn_map.get(&scenario_txn) {
                     runner.read(actual_txn, key).map(|_| ())
@@ -581,6 +1019,13 @@ pub fn run_ssi_scenario<S: DstTestableSsi>(
                     Err(SsiFaultType::SystemAbort)
                 }
             }
+            DstSsiOp::Scan(start, end) => {
+                if let Some(&actual_txn) = txn_map.get(&scenario_txn) {
+                    runner.scan(actual_txn, start..=end).map(|_| ())
+                } else {
+                    Err(SsiFaultType::SystemAbort)
+                }
+            }
             DstSsiOp::Write(key, value) => {
                 if let Some(&actual_txn) = txn_map.get(&scenario_txn) {
                     runner.write(actual_txn, key, value).map(|_| ())
@@ -610,10 +1055,71 @@ pub fn run_ssi_scenario<S: DstTestableSsi>(
         }
     }
 
-    SsiDstResult {
-        passed: true, // Faults are expected, not failures
-        stats: runner.stats(),
-        fault_errors: errors,
+    errors
+}
+
+/// The writes `to_ssi_history` has seen so far, used to decide which
+/// version each read observed.
+#[derive(Default)]
+struct WriteIndex {
+    /// Log position each transaction's snapshot was taken at
+    begun: HashMap<TxnId, u64>,
+    /// Log position each transaction committed at
+    committed: HashMap<TxnId, u64>,
+    /// Last value each transaction wrote to each key
+    last_writes: HashMap<(TxnId, KeyId), Value>,
+    /// Every transaction that ever wrote each value to each key
+    writers: HashMap<(KeyId, Value), BTreeSet<TxnId>>,
+}
+
+impl WriteIndex {
+    fn write(&mut self, txn: TxnId, key: KeyId, value: Value) {
+        self.last_writes.insert((txn, key), value);
+        self.writers.entry((key, value)).or_default().insert(txn);
+    }
+
+    /// Writer of the version `txn` observed when it read `value` for `key`.
+    ///
+    /// The reader's own write of that value comes first. Otherwise it is
+    /// the latest transaction that committed that value before the
+    /// reader's snapshot, which is the version a snapshot returns. Only if
+    /// there is none are other writers considered (aborted, still pending
+    /// or committed later), since then the read itself is an anomaly, and
+    /// more than one of those is an error.
+    fn version_read(
+        &self,
+        txn: TxnId,
+        key: KeyId,
+        value: Value,
+    ) -> Result<Option<TxnId>, String> {
+        let Some(writers) = self.writers.get(&(key, value)) else {
+            return Ok(None);
+        };
+        if writers.contains(&txn) {
+            return Ok(Some(txn));
+        }
+
+        let snapshot = self.begun.get(&txn).copied().unwrap_or(0);
+        let latest_visible = writers
+            .iter()
+            .filter_map(|writer| {
+                let committed = self.committed.get(writer).copied()?;
+                let visible = committed < snapshot
+                    && self.last_writes.get(&(*writer, key)) == Some(&value);
+                visible.then_some((committed, *writer))
+            })
+            .max();
+        if let Some((_, writer)) = latest_visible {
+            return Ok(Some(writer));
+        }
+
+        match writers.iter().copied().collect::<Vec<_>>()[..] {
+            [writer] => Ok(Some(writer)),
+            ref candidates => Err(format!(
+                "T{} read k{}={}, which transactions {:?} all wrote",
+                txn, key, value, candidates
+            )),
+        }
     }
 }
 
@@ -734,6 +1240,70 @@ mod tests {
         println!("{}", result.format());
     }
 
+    /// Runner over `MockSsi` that never injects faults.
+    fn runner_without_faults() -> SsiDstRunner<MockSsi> {
+        SsiDstRunner::with_fault_config(MockSsi::new(), 12345, FaultConfig::none())
+    }
+
+    #[test]
+    fn test_read_attributed_to_committed_writer() {
+        let mut runner = runner_without_faults();
+        let t1 = runner.begin().unwrap();
+        runner.write(t1, 1, 5).unwrap();
+        runner.commit(t1).unwrap();
+        let t2 = runner.begin().unwrap();
+
+        // An aborted transaction writes the same value after the snapshot
+        let t3 = runner.begin().unwrap();
+        runner.write(t3, 1, 5).unwrap();
+        runner.abort(t3);
+
+        assert_eq!(runner.read(t2, 1).unwrap(), Some(5));
+        runner.commit(t2).unwrap();
+
+        let results = runner.check_invariants();
+        assert!(results.iter().all(|r| r.holds), "{:?}", results);
+    }
+
+    #[test]
+    fn test_reused_value_attributed_to_latest_writer() {
+        let mut runner = runner_without_faults();
+        for _ in 0..2 {
+            let writer = runner.begin().unwrap();
+            runner.write(writer, 1, 5).unwrap();
+            runner.commit(writer).unwrap();
+        }
+        let reader = runner.begin().unwrap();
+        assert_eq!(runner.read(reader, 1).unwrap(), Some(5));
+        runner.commit(reader).unwrap();
+
+        let history = runner.to_ssi_history().unwrap();
+        let read = &history.reads[0];
+        assert_eq!((read.txn, read.version), (reader, Some(2)));
+        let results = runner.check_invariants();
+        assert!(results.iter().all(|r| r.holds), "{:?}", results);
+    }
+
+    #[test]
+    fn test_ambiguous_read_rejected() {
+        // Two pending transactions write the value the reader sees
+        let mut runner = runner_without_faults();
+        for _ in 0..2 {
+            let writer = runner.begin().unwrap();
+            runner.write(writer, 1, 5).unwrap();
+        }
+        let reader = runner.begin().unwrap();
+        assert_eq!(runner.read(reader, 1).unwrap(), Some(5));
+
+        let error = runner.to_ssi_history().unwrap_err();
+        assert!(error.contains("T3 read k1=5"), "{}", error);
+        let results = runner.check_invariants();
+        assert!(matches!(
+            &results[..],
+            [InvariantResult { name: "UnambiguousReads", holds: false, .. }]
+        ));
+    }
+
     #[test]
     fn test_determinism() {
         let ops = vec![
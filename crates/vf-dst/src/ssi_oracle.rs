@@ -31,6 +31,7 @@ use crate::ssi_harness::{DstTestableSsi, SsiDstRunner, SsiFaultType, SsiResult};
 pub enum SsiOracleAction {
     Begin(u8),
     Read(u8, u8),      // txn, key
+    Scan(u8, u8, u8),  // txn, first key, last key (inclusive)
     Write(u8, u8),     // txn, key
     Commit(u8),
     Abort(u8),
@@ -179,6 +180,46 @@ impl SsiOracleTrace {
         trace
     }
 
+    /// Phantom write skew pattern.
+    ///
+    /// T1 and T2 both scan an empty range, then each inserts a different
+    /// key into it. No key is read and written by both, so only the range
+    /// SIREAD locks see the dangerous structure.
+    pub fn phantom_write_skew() -> Self {
+        let mut trace = Self::new("phantom_write_skew")
+            .with_description("Both txns scan a range and insert into it - one must abort")
+            .with_expected_outcome(SsiExpectedOutcome::DangerousStructureAbort(1));
+
+        trace.add(SsiOracleAction::Begin(1));
+        trace.add(SsiOracleAction::Begin(2));
+        trace.add(SsiOracleAction::Scan(1, 1, 10)); // T1 scans K1..=K10 (empty)
+        trace.add(SsiOracleAction::Scan(2, 1, 10)); // T2 scans K1..=K10 (empty)
+        trace.add(SsiOracleAction::Write(2, 5));    // T2 inserts K5 into T1's range
+        trace.add(SsiOracleAction::Commit(2));
+        trace.add(SsiOracleAction::Write(1, 7));    // T1 inserts K7 into T2's range
+        trace.add(SsiOracleAction::Commit(1));      // T1 must abort
+
+        trace
+    }
+
+    /// Disjoint ranges - an insert outside the scanned range is no conflict.
+    pub fn disjoint_ranges() -> Self {
+        let mut trace = Self::new("disjoint_ranges")
+            .with_description("Inserts outside each other's scanned range - both commit")
+            .with_expected_outcome(SsiExpectedOutcome::AllCommit);
+
+        trace.add(SsiOracleAction::Begin(1));
+        trace.add(SsiOracleAction::Begin(2));
+        trace.add(SsiOracleAction::Scan(1, 1, 4));  // T1 scans K1..=K4
+        trace.add(SsiOracleAction::Scan(2, 5, 8));  // T2 scans K5..=K8
+        trace.add(SsiOracleAction::Write(1, 6));    // T1 inserts into T2's range
+        trace.add(SsiOracleAction::Write(2, 9));    // T2 inserts outside T1's range
+        trace.add(SsiOracleAction::Commit(1));
+        trace.add(SsiOracleAction::Commit(2));      // Only one rw edge: both commit
+
+        trace
+    }
+
     /// All pre-built SSI oracles.
     pub fn all_oracles() -> Vec<Self> {
         vec![
@@ -187,6 +228,8 @@ impl SsiOracleTrace {
             Self::sequential_writes(),
             Self::single_conflict_flag(),
             Self::read_only(),
+            Self::phantom_write_skew(),
+            Self::disjoint_ranges(),
         ]
     }
 }
@@ -268,6 +311,19 @@ pub fn replay_ssi_oracle<S: DstTestableSsi>(
                     Err(SsiFaultType::SystemAbort)
                 }
             }
+            SsiOracleAction::Scan(txn, start, end) => {
+                if let Some(&actual_txn) = txn_map.get(txn) {
+                    match runner.scan(actual_txn, *start as u64..=*end as u64) {
+                        Ok(_) => {
+                            actions_executed += 1;
+                            Ok(())
+                        }
+                        Err(e) => Err(e),
+                    }
+                } else {
+                    Err(SsiFaultType::SystemAbort)
+                }
+            }
             SsiOracleAction::Write(txn, key) => {
                 if let Some(&actual_txn) = txn_map.get(txn) {
                     // Use txn * 100 + key as value for tracing
@@ -454,6 +510,17 @@ mod tests {
         assert!(result.invariants_hold);
     }
 
+    #[test]
+    fn test_replay_disjoint_ranges() {
+        let ssi = MockSsi::new();
+        let oracle = SsiOracleTrace::disjoint_ranges();
+        let result = replay_ssi_oracle(ssi, 12345, &oracle);
+
+        println!("{}", result.format());
+        assert!(result.invariants_hold);
+        assert_eq!(result.committed_txns.len(), 2);
+    }
+
     #[test]
     fn test_replay_sequential_writes() {
         let ssi = MockSsi::new();
@@ -0,0 +1,551 @@
+//! Scheduling strategies for the DST [`Scheduler`](crate::Scheduler).
+//!
+//! At every switch point the scheduler asks its [`SchedulingStrategy`]
+//! which thread runs next; when a thread cannot continue (it waits for
+//! another one, or finished) it asks for a different thread instead. The
+//! strategies:
+//!
+//! - [`UniformRandom`]: a coin flip with the yield probability at every
+//!   switch point, switching to a uniformly chosen thread. Good at short
+//!   races, but an ordering that needs one thread to run many steps
+//!   uninterrupted is exponentially unlikely.
+//! - [`Pct`]: probabilistic concurrency testing (Burckhardt et al., ASPLOS
+//!   2010). Threads get random priorities and the highest-priority
+//!   runnable thread runs; at `depth - 1` random steps the running
+//!   thread's priority drops below all others. With `n` threads and `k`
+//!   steps, each run finds a given bug of depth `d` (one needing `d`
+//!   ordering constraints) with probability at least `1 / (n * k^(d-1))`.
+//! - [`PreemptionBounded`]: the `index`-th schedule with at most `bound`
+//!   preemptions, so running indices `0..schedules_count` enumerates them
+//!   all. Most concurrency bugs need only one or two preemptions.
+//! - [`RoundRobin`]: each thread runs a fixed number of switch points,
+//!   in index order.
+//!
+//! [`StrategyConfig`] selects one from `HarnessConfig` or
+//! `DstEnv::with_scheduler`.
+
+use crate::random::DeterministicRng;
+
+/// What a strategy sees at a scheduling point.
+pub struct ScheduleContext<'a> {
+    /// Thread that reached the scheduling point
+    pub current: usize,
+    /// Whether each thread has finished
+    pub finished: &'a [bool],
+    /// The scheduler's RNG, the only source of randomness
+    pub rng: &'a mut DeterministicRng,
+}
+
+impl ScheduleContext<'_> {
+    pub fn threads_count(&self) -> usize {
+        self.finished.len()
+    }
+
+    /// First unfinished thread other than the current one, searching from
+    /// `start` in index order.
+    pub fn other_runnable(&self, start: usize) -> Option<usize> {
+        let threads_count = self.threads_count();
+        (0..threads_count)
+            .map(|offset| (start + offset) % threads_count)
+            .find(|&thread| thread != self.current && !self.finished[thread])
+    }
+}
+
+/// Decides which thread runs at each scheduling point.
+pub trait SchedulingStrategy: Send + Sync {
+    /// Thread to run after a switch point of `context.current`; the
+    /// current thread continues if it is returned.
+    fn next_thread(&mut self, context: ScheduleContext<'_>) -> usize;
+
+    /// Thread to hand control to when the current thread cannot continue.
+    /// Returns another thread if there is one.
+    fn switch_thread(&mut self, context: ScheduleContext<'_>) -> usize;
+}
+
+/// Strategy to build for each run.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StrategyConfig {
+    /// [`UniformRandom`] with the configured yield probability
+    #[default]
+    Random,
+    /// [`Pct`] for bugs of depth up to `depth` in about `steps_estimate`
+    /// switch points
+    Pct { depth: usize, steps_estimate: u64 },
+    /// [`PreemptionBounded`]: DST seed `s` runs schedule `s - 1`, and
+    /// seed 0 the last one
+    PreemptionBounded { bound: usize, steps_estimate: u64 },
+    /// [`RoundRobin`] with `quantum` switch points per turn
+    RoundRobin { quantum: u64 },
+}
+
+impl StrategyConfig {
+    /// Build the strategy for the run with DST seed `seed`.
+    pub fn build(
+        self,
+        seed: u64,
+        threads_count: usize,
+        yield_probability: f64,
+    ) -> Box<dyn SchedulingStrategy> {
+        match self {
+            StrategyConfig::Random => Box::new(UniformRandom::new(yield_probability)),
+            StrategyConfig::Pct {
+                depth,
+                steps_estimate,
+            } => Box::new(Pct::new(depth, steps_estimate)),
+            StrategyConfig::PreemptionBounded {
+                bound,
+                steps_estimate,
+            } => Box::new(PreemptionBounded::new(
+                bound,
+                steps_estimate,
+                threads_count,
+                // Schedule indices wrap around, so seed 0 is still valid
+                seed.wrapping_sub(1),
+            )),
+            StrategyConfig::RoundRobin { quantum } => Box::new(RoundRobin::new(quantum)),
+        }
+    }
+}
+
+/// Uniformly chosen other thread. Finished threads are not skipped: the
+/// caller moves on to the next runnable one.
+fn pick_other_thread(context: &mut ScheduleContext<'_>) -> usize {
+    debug_assert!(context.threads_count() > 1);
+    loop {
+        let candidate = context.rng.gen_range(0..context.threads_count());
+        if candidate != context.current {
+            return candidate;
+        }
+    }
+}
+
+/// Switch to a uniformly chosen thread with a fixed probability.
+pub struct UniformRandom {
+    yield_probability: f64,
+}
+
+impl UniformRandom {
+    pub fn new(yield_probability: f64) -> Self {
+        debug_assert!(
+            (0.0..=1.0).contains(&yield_probability),
+            "Yield probability must be in [0.0, 1.0]"
+        );
+        Self { yield_probability }
+    }
+}
+
+impl SchedulingStrategy for UniformRandom {
+    fn next_thread(&mut self, mut context: ScheduleContext<'_>) -> usize {
+        if context.rng.gen_bool(self.yield_probability) {
+            pick_other_thread(&mut context)
+        } else {
+            context.current
+        }
+    }
+
+    fn switch_thread(&mut self, mut context: ScheduleContext<'_>) -> usize {
+        pick_other_thread(&mut context)
+    }
+}
+
+/// Probabilistic concurrency testing: run the highest-priority thread,
+/// and demote the running thread at `depth - 1` random change points.
+pub struct Pct {
+    depth: usize,
+    steps_estimate: u64,
+    /// Priority of each thread; change points assign `1..depth`, random
+    /// priorities are at least `depth`
+    priorities: Vec<u64>,
+    /// Step of each change point, in the order of the priority it assigns
+    change_points: Vec<u64>,
+    /// Threads that could not continue since the last step
+    waiting: Vec<bool>,
+    step: u64,
+}
+
+impl Pct {
+    /// Strategy for bugs of depth up to `depth` in executions of about
+    /// `steps_estimate` switch points.
+    pub fn new(depth: usize, steps_estimate: u64) -> Self {
+        debug_assert!(depth > 0, "Depth must be at least 1");
+        debug_assert!(steps_estimate > 0, "Steps estimate must be positive");
+        Self {
+            depth,
+            steps_estimate,
+            priorities: Vec::new(),
+            change_points: Vec::new(),
+            waiting: Vec::new(),
+            step: 0,
+        }
+    }
+
+    /// Draw change points on the first call, and a random priority for
+    /// every thread not seen yet (including spawned ones).
+    fn prepare(&mut self, context: &mut ScheduleContext<'_>) {
+        if self.step == 0 && self.change_points.is_empty() {
+            let points_count = (self.depth as u64 - 1).min(self.steps_estimate);
+            while (self.change_points.len() as u64) < points_count {
+                let point = context.rng.gen_range(1..=self.steps_estimate);
+                if !self.change_points.contains(&point) {
+                    self.change_points.push(point);
+                }
+            }
+        }
+        while self.priorities.len() < context.threads_count() {
+            let priority = self.depth as u64 + context.rng.gen_range(0..u64::from(u32::MAX));
+            if !self.priorities.contains(&priority) {
+                self.priorities.push(priority);
+            }
+        }
+        self.waiting.resize(context.threads_count(), false);
+    }
+
+    /// Highest-priority unfinished thread for which `eligible` holds,
+    /// ties to the lowest index.
+    fn highest(
+        &self,
+        context: &ScheduleContext<'_>,
+        eligible: impl Fn(usize) -> bool,
+    ) -> Option<usize> {
+        (0..context.threads_count())
+            .filter(|&thread| !context.finished[thread] && eligible(thread))
+            .max_by_key(|&thread| (self.priorities[thread], std::cmp::Reverse(thread)))
+    }
+}
+
+impl SchedulingStrategy for Pct {
+    fn next_thread(&mut self, mut context: ScheduleContext<'_>) -> usize {
+        self.prepare(&mut context);
+        self.step += 1;
+        if let Some(index) = self.change_points.iter().position(|&p| p == self.step) {
+            self.priorities[context.current] = index as u64 + 1;
+        }
+        // The step may have unblocked the waiting threads
+        self.waiting.fill(false);
+        self.highest(&context, |_| true).unwrap_or(context.current)
+    }
+
+    /// A waiting thread would keep the highest priority and spin, so it
+    /// is passed over until another thread takes a step. Its priority is
+    /// unchanged: demoting it for good would act as an extra change point.
+    fn switch_thread(&mut self, mut context: ScheduleContext<'_>) -> usize {
+        self.prepare(&mut context);
+        self.waiting[context.current] = true;
+        let current = context.current;
+        self.highest(&context, |thread| !self.waiting[thread])
+            .or_else(|| self.highest(&context, |thread| thread != current))
+            .unwrap_or(current)
+    }
+}
+
+/// `n` choose `k`, saturating.
+fn binomial(n: u64, k: u64) -> u64 {
+    if k > n {
+        return 0;
+    }
+    let mut result: u128 = 1;
+    for i in 0..k.min(n - k) {
+        result = result * u128::from(n - i) / u128::from(i + 1);
+        if result > u128::from(u64::MAX) {
+            return u64::MAX;
+        }
+    }
+    result as u64
+}
+
+/// Every schedule with at most `bound` preemptions, one per index.
+///
+/// A preemption is a switch away from a thread that could continue; a
+/// schedule is the set of switch points (among the first
+/// `steps_estimate`) where one happens, and the thread each switches to.
+/// Index 0 never preempts, then come all schedules with one preemption,
+/// and so on. Otherwise threads run to completion or until they wait, and
+/// control passes on in index order.
+pub struct PreemptionBounded {
+    /// Steps at which to preempt, ascending, with the offset of the
+    /// thread to switch to from the current one
+    preemptions: Vec<(u64, usize)>,
+    step: u64,
+}
+
+impl PreemptionBounded {
+    /// Number of schedules with at most `bound` preemptions among
+    /// `steps_estimate` switch points of `threads_count` threads.
+    #[must_use]
+    pub fn schedules_count(bound: usize, steps_estimate: u64, threads_count: usize) -> u64 {
+        let targets = threads_count.saturating_sub(1) as u64;
+        if targets == 0 {
+            return 1;
+        }
+        (0..=bound as u64).fold(0u64, |count, preemptions| {
+            let placements = binomial(steps_estimate, preemptions);
+            let choices = targets.saturating_pow(preemptions as u32);
+            count.saturating_add(placements.saturating_mul(choices))
+        })
+    }
+
+    /// The `index`-th schedule, wrapping around past
+    /// [`PreemptionBounded::schedules_count`].
+    pub fn new(bound: usize, steps_estimate: u64, threads_count: usize, index: u64) -> Self {
+        let targets = threads_count.saturating_sub(1) as u64;
+        let mut rank = index % Self::schedules_count(bound, steps_estimate, threads_count);
+        let mut preemptions = Vec::new();
+        for count in 0..=bound as u64 {
+            if targets == 0 {
+                break;
+            }
+            let choices = targets.saturating_pow(count as u32);
+            let schedules = binomial(steps_estimate, count).saturating_mul(choices);
+            if rank >= schedules {
+                rank -= schedules;
+                continue;
+            }
+
+            let (mut placement, mut choice) = (rank / choices, rank % choices);
+            let mut step = 1;
+            for remaining in (1..=count).rev() {
+                // Skip the placements whose next preemption is at `step`
+                loop {
+                    let with_step = binomial(steps_estimate - step, remaining - 1);
+                    if placement < with_step {
+                        break;
+                    }
+                    placement -= with_step;
+                    step += 1;
+                }
+                preemptions.push((step, (choice % targets) as usize));
+                choice /= targets;
+                step += 1;
+            }
+            break;
+        }
+        Self {
+            preemptions,
+            step: 0,
+        }
+    }
+}
+
+impl SchedulingStrategy for PreemptionBounded {
+    fn next_thread(&mut self, context: ScheduleContext<'_>) -> usize {
+        self.step += 1;
+        match self.preemptions.first() {
+            Some(&(step, offset)) if step == self.step => {
+                self.preemptions.remove(0);
+                let target = context.current + 1 + offset;
+                context.other_runnable(target).unwrap_or(context.current)
+            }
+            _ => context.current,
+        }
+    }
+
+    fn switch_thread(&mut self, context: ScheduleContext<'_>) -> usize {
+        context
+            .other_runnable(context.current + 1)
+            .unwrap_or(context.current)
+    }
+}
+
+/// Run each thread for `quantum` switch points, in index order.
+pub struct RoundRobin {
+    quantum: u64,
+    /// Switch points the current thread ran in its turn
+    ran: u64,
+}
+
+impl RoundRobin {
+    pub fn new(quantum: u64) -> Self {
+        debug_assert!(quantum > 0, "Quantum must be positive");
+        Self { quantum, ran: 0 }
+    }
+}
+
+impl SchedulingStrategy for RoundRobin {
+    fn next_thread(&mut self, context: ScheduleContext<'_>) -> usize {
+        self.ran += 1;
+        if self.ran < self.quantum {
+            return context.current;
+        }
+        self.ran = 0;
+        context
+            .other_runnable(context.current + 1)
+            .unwrap_or(context.current)
+    }
+
+    fn switch_thread(&mut self, context: ScheduleContext<'_>) -> usize {
+        self.ran = 0;
+        context
+            .other_runnable(context.current + 1)
+            .unwrap_or(context.current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::{ScheduleDecision, Scheduler};
+
+    const STEPS: u64 = 30;
+
+    /// Two threads of `STEPS` steps: thread 0 counts its progress, and
+    /// thread 1's first step reads it. Returns what it read, and how many
+    /// times a thread that could continue was switched away from.
+    fn first_read(scheduler: Scheduler) -> (u64, usize) {
+        first_read_by(scheduler, 1)
+    }
+
+    /// [`first_read`] with `reader` reading the other thread's progress;
+    /// the reader waits until the other thread has taken a step.
+    fn first_read_by(mut scheduler: Scheduler, reader: usize) -> (u64, usize) {
+        let counter = 1 - reader;
+        let mut progress = [0u64; 2];
+        let mut read = None;
+        let mut preemptions = 0;
+        loop {
+            let current = scheduler.current_thread();
+            if progress[current] == STEPS || (current == reader && progress[counter] == 0) {
+                scheduler.force_switch();
+                continue;
+            }
+            if current == reader && progress[reader] == 0 {
+                read = Some(progress[counter]);
+            }
+            progress[current] += 1;
+            if progress[current] < STEPS {
+                if let ScheduleDecision::SwitchTo(_) = scheduler.decide() {
+                    preemptions += 1;
+                }
+            } else if progress.iter().all(|&p| p == STEPS) {
+                break;
+            } else {
+                scheduler.finish_thread(current);
+                scheduler.force_switch();
+            }
+        }
+        (read.unwrap(), preemptions)
+    }
+
+    fn scheduler(seed: u64, strategy: Box<dyn SchedulingStrategy>) -> Scheduler {
+        Scheduler::with_strategy(DeterministicRng::new(seed), 2, strategy)
+    }
+
+    #[test]
+    fn test_pct_finds_depth_two_ordering() {
+        // Thread 1 must start exactly after thread 0's 15th step
+        let found = |strategy: &dyn Fn() -> Box<dyn SchedulingStrategy>| {
+            (1..=1000)
+                .filter(|&seed| first_read(scheduler(seed, strategy())).0 == 15)
+                .count()
+        };
+        let pct = found(&|| Box::new(Pct::new(2, 2 * STEPS)));
+        let random = found(&|| Box::new(UniformRandom::new(0.5)));
+
+        // PCT: 1 / (2 * 60) per run; the coin flip needs 15 heads in a row
+        assert!(pct >= 3, "PCT found the ordering {} times", pct);
+        assert!(random < pct, "random {} vs PCT {}", random, pct);
+    }
+
+    #[test]
+    fn test_pct_finds_depth_two_ordering_after_wait() {
+        // Thread 0 runs first and waits for thread 1, which must then be
+        // preempted exactly after its 15th step
+        let pct = (1..=1000)
+            .filter(|&seed| {
+                let scheduler = scheduler(seed, Box::new(Pct::new(2, 2 * STEPS)));
+                first_read_by(scheduler, 0).0 == 15
+            })
+            .count();
+
+        // 1 / (2 * 60) per run, as without the wait
+        assert!(pct >= 3, "PCT found the ordering {} times", pct);
+    }
+
+    #[test]
+    fn test_pct_depth_one_never_preempts() {
+        for seed in 1..=50 {
+            let (read, preemptions) = first_read(scheduler(seed, Box::new(Pct::new(1, 2 * STEPS))));
+            // The first switch point moves to the highest priority thread
+            assert!(
+                preemptions <= 1,
+                "seed {}: {} preemptions",
+                seed,
+                preemptions
+            );
+            assert!(read == 1 || read == STEPS, "seed {}: read {}", seed, read);
+        }
+    }
+
+    #[test]
+    fn test_preemption_bounded_schedules_count() {
+        assert_eq!(PreemptionBounded::schedules_count(0, 60, 2), 1);
+        assert_eq!(PreemptionBounded::schedules_count(1, 60, 2), 61);
+        assert_eq!(
+            PreemptionBounded::schedules_count(2, 10, 3),
+            1 + 10 * 2 + 45 * 4
+        );
+        assert_eq!(PreemptionBounded::schedules_count(3, 10, 1), 1);
+    }
+
+    #[test]
+    fn test_preemption_bounded_enumerates_every_schedule() {
+        let run = |bound, index| {
+            let strategy = PreemptionBounded::new(bound, STEPS, 2, index);
+            first_read(scheduler(1, Box::new(strategy)))
+        };
+
+        assert_eq!(run(0, 0), (STEPS, 0));
+
+        // One preemption: thread 1 can start after any step of thread 0
+        let count = PreemptionBounded::schedules_count(1, STEPS, 2);
+        let mut reads: Vec<u64> = (0..count).map(|index| run(1, index).0).collect();
+        reads.sort_unstable();
+        reads.dedup();
+        assert_eq!(reads, (1..=STEPS).collect::<Vec<_>>());
+
+        let count = PreemptionBounded::schedules_count(2, STEPS, 2);
+        assert!((0..count).all(|index| run(2, index).1 <= 2));
+        // Indices wrap around
+        assert_eq!(run(2, count + 5), run(2, 5));
+    }
+
+    #[test]
+    fn test_preemption_bounded_config_seeds() {
+        let run = |seed| {
+            let config = StrategyConfig::PreemptionBounded {
+                bound: 1,
+                steps_estimate: STEPS,
+            };
+            first_read(scheduler(1, config.build(seed, 2, 0.0)))
+        };
+        let schedule = |index| {
+            first_read(scheduler(
+                1,
+                Box::new(PreemptionBounded::new(1, STEPS, 2, index)),
+            ))
+        };
+
+        assert_eq!(run(1), schedule(0));
+        assert_eq!(run(7), schedule(6));
+        assert_eq!(run(0), schedule(u64::MAX));
+    }
+
+    #[test]
+    fn test_round_robin_takes_turns() {
+        let strategy = Box::new(RoundRobin::new(2));
+        let mut scheduler = Scheduler::with_strategy(DeterministicRng::new(1), 3, strategy);
+        let threads: Vec<_> = (0..6)
+            .map(|_| {
+                scheduler.decide();
+                scheduler.current_thread()
+            })
+            .collect();
+        assert_eq!(threads, vec![0, 1, 1, 2, 2, 0]);
+
+        // Finished threads lose their turn
+        scheduler.finish_thread(1);
+        scheduler.decide();
+        scheduler.decide();
+        assert_eq!(scheduler.current_thread(), 2);
+        assert_eq!(scheduler.force_switch(), 0);
+    }
+}
@@ -0,0 +1,142 @@
+//! Controlled allocation, mirroring `std::alloc`.
+//!
+//! Allocations and frees are switch points. Under the memory checker,
+//! freed memory is quarantined until the execution ends instead of being
+//! returned to the allocator, so a use after free reads the old block and
+//! is reported rather than corrupting a new one (see
+//! [`crate::memory_checker`]).
+
+use crate::controlled;
+
+pub use std::alloc::{handle_alloc_error, Layout};
+
+/// Allocate memory as described by `layout`.
+///
+/// # Safety
+///
+/// Same contract as [`std::alloc::alloc`].
+pub unsafe fn alloc(layout: Layout) -> *mut u8 {
+    let ptr = std::alloc::alloc(layout);
+    if !ptr.is_null() {
+        controlled::allocated(ptr as usize, layout);
+    }
+    ptr
+}
+
+/// Allocate zero-initialized memory as described by `layout`.
+///
+/// # Safety
+///
+/// Same contract as [`std::alloc::alloc_zeroed`].
+pub unsafe fn alloc_zeroed(layout: Layout) -> *mut u8 {
+    let ptr = std::alloc::alloc_zeroed(layout);
+    if !ptr.is_null() {
+        controlled::allocated(ptr as usize, layout);
+    }
+    ptr
+}
+
+/// Free memory allocated by [`alloc`] or [`alloc_zeroed`].
+///
+/// # Safety
+///
+/// Same contract as [`std::alloc::dealloc`].
+pub unsafe fn dealloc(ptr: *mut u8, layout: Layout) {
+    if !controlled::deallocated(ptr as usize) {
+        std::alloc::dealloc(ptr, layout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controlled::{ControlledReport, ControlledRuntime, SyncOp};
+    use crate::random::DeterministicRng;
+    use crate::scheduler::Scheduler;
+    use crate::sync::atomic::{AtomicU64, Ordering};
+    use vf_core::counterexample::MemoryIssue;
+
+    fn run_checked(body: impl FnOnce() + Send) -> ControlledReport {
+        let scheduler = Scheduler::new(DeterministicRng::new(1), 1, 0.5);
+        ControlledRuntime::new(scheduler)
+            .with_memory_checker()
+            .run(vec![Box::new(body)])
+    }
+
+    /// Step of the first event with `op`.
+    fn step_of(report: &ControlledReport, op: SyncOp) -> u64 {
+        report.events.iter().find(|e| e.op == op).unwrap().step
+    }
+
+    #[test]
+    fn test_use_after_free_is_reported() {
+        let report = run_checked(|| unsafe {
+            let layout = Layout::new::<AtomicU64>();
+            let node = alloc(layout) as *mut AtomicU64;
+            node.write(AtomicU64::new(1));
+            dealloc(node as *mut u8, layout);
+            // Still mapped: the block is quarantined
+            (*node).load(Ordering::SeqCst);
+        });
+
+        match report.memory_issues[..] {
+            [MemoryIssue::UseAfterFree {
+                freed_at_step,
+                used_at_step,
+                ..
+            }] => {
+                assert_eq!(freed_at_step, step_of(&report, SyncOp::Dealloc));
+                assert_eq!(used_at_step, step_of(&report, SyncOp::Load));
+            }
+            _ => panic!("{}", report.format()),
+        }
+    }
+
+    #[test]
+    fn test_double_free_is_reported() {
+        let report = run_checked(|| unsafe {
+            let layout = Layout::new::<u64>();
+            let block = alloc_zeroed(layout);
+            dealloc(block, layout);
+            dealloc(block, layout);
+        });
+        assert!(
+            matches!(report.memory_issues[..], [MemoryIssue::UseAfterFree { .. }]),
+            "{}",
+            report.format()
+        );
+    }
+
+    #[test]
+    fn test_leak_is_reported() {
+        let layout = Layout::new::<u64>();
+        let leaked = std::sync::Mutex::new(0);
+        let report = run_checked(|| {
+            *leaked.lock().unwrap() = unsafe { alloc(layout) } as usize;
+        });
+
+        let address = leaked.into_inner().unwrap();
+        match report.memory_issues[..] {
+            [MemoryIssue::MemoryLeak {
+                address: leak,
+                allocated_at_step,
+            }] => {
+                assert_eq!(leak, address as u64);
+                assert_eq!(allocated_at_step, step_of(&report, SyncOp::Alloc));
+            }
+            _ => panic!("{}", report.format()),
+        }
+        // Outside the execution memory is freed right away
+        unsafe { dealloc(address as *mut u8, layout) };
+    }
+
+    #[test]
+    fn test_freed_memory_is_clean() {
+        let report = run_checked(|| unsafe {
+            let layout = Layout::new::<u64>();
+            let block = alloc(layout);
+            dealloc(block, layout);
+        });
+        assert!(report.passed(), "{}", report.format());
+    }
+}
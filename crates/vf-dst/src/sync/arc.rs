@@ -0,0 +1,134 @@
+//! Controlled `Arc`.
+
+use std::fmt;
+use std::ops::Deref;
+
+use crate::controlled::{self, SyncOp};
+
+/// A `std::sync::Arc` whose clone and drop are switch points.
+///
+/// Reference counts are shared memory: a thread can be preempted between
+/// cloning an `Arc` and using it, or between its last two drops.
+pub struct Arc<T: ?Sized> {
+    inner: std::sync::Arc<T>,
+}
+
+impl<T> Arc<T> {
+    pub fn new(value: T) -> Self {
+        let inner = std::sync::Arc::new(value);
+        controlled::sync_point(SyncOp::ArcClone, allocation(&inner), None);
+        Self { inner }
+    }
+}
+
+impl<T: ?Sized> Arc<T> {
+    pub fn strong_count(this: &Self) -> usize {
+        std::sync::Arc::strong_count(&this.inner)
+    }
+
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        std::sync::Arc::ptr_eq(&this.inner, &other.inner)
+    }
+
+    pub fn as_ptr(this: &Self) -> *const T {
+        std::sync::Arc::as_ptr(&this.inner)
+    }
+
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        std::sync::Arc::get_mut(&mut this.inner)
+    }
+}
+
+/// Address of the shared allocation, identifying it in sync events.
+fn allocation<T: ?Sized>(inner: &std::sync::Arc<T>) -> usize {
+    std::sync::Arc::as_ptr(inner) as *const () as usize
+}
+
+impl<T: ?Sized> Clone for Arc<T> {
+    fn clone(&self) -> Self {
+        controlled::sync_point(SyncOp::ArcClone, allocation(&self.inner), None);
+        Self {
+            inner: std::sync::Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Arc<T> {
+    fn drop(&mut self) {
+        controlled::sync_point(SyncOp::ArcDrop, allocation(&self.inner), None);
+    }
+}
+
+impl<T: ?Sized> Deref for Arc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for Arc<T> {
+    fn as_ref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Default> Default for Arc<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for Arc<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for Arc<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for Arc<T> {}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Arc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for Arc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controlled::ControlledRuntime;
+    use crate::random::DeterministicRng;
+    use crate::scheduler::Scheduler;
+
+    #[test]
+    fn test_clone_and_drop_are_recorded() {
+        let shared = Arc::new(5);
+        let scheduler = Scheduler::new(DeterministicRng::new(1), 2, 0.5);
+        let report = ControlledRuntime::new(scheduler).run(vec![
+            Box::new(|| assert_eq!(*shared.clone(), 5)),
+            Box::new(|| assert_eq!(*shared.clone(), 5)),
+        ]);
+        assert!(report.passed(), "{}", report.format());
+
+        let ops: Vec<_> = report.events.iter().map(|e| e.op).collect();
+        assert_eq!(ops.iter().filter(|&&op| op == SyncOp::ArcClone).count(), 2);
+        assert_eq!(ops.iter().filter(|&&op| op == SyncOp::ArcDrop).count(), 2);
+        assert!(report
+            .events
+            .iter()
+            .all(|e| e.object == Arc::as_ptr(&shared) as usize));
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+}
@@ -0,0 +1,524 @@
+//! Controlled atomics, mirroring `std::sync::atomic`.
+//!
+//! Every operation is a switch point followed by the std operation, so a
+//! CAS loop can be preempted between its load and its compare-exchange.
+//! Under weak memory, loads return the value the model picks instead of
+//! the latest one (see [`crate::weak_memory`]). With the memory checker,
+//! accesses inside freed memory are reported, and so is a pointer
+//! compare-exchange that succeeds on a recycled pointer (ABA, see
+//! [`crate::memory_checker`]).
+
+use std::fmt;
+
+use crate::controlled::{self, SyncOp};
+
+pub use std::sync::atomic::Ordering;
+
+/// An atomic fence; a switch point like any other atomic operation.
+pub fn fence(order: Ordering) {
+    controlled::sync_point(SyncOp::Fence, 0, Some(order));
+    std::sync::atomic::fence(order);
+    controlled::atomic_fence(order);
+}
+
+/// Event for a compare-exchange: a failed one only reads.
+fn cas_event(succeeded: bool) -> SyncOp {
+    if succeeded {
+        SyncOp::ReadModifyWrite
+    } else {
+        SyncOp::CasFailure
+    }
+}
+
+/// Atomic values as the weak memory model stores them.
+trait Bits: Copy {
+    /// Whether values are pointers, which are checked for ABA
+    const POINTER: bool = false;
+
+    fn to_bits(self) -> u64;
+    fn from_bits(bits: u64) -> Self;
+}
+
+macro_rules! int_bits {
+    ($($value:ty),+) => {
+        $(
+            impl Bits for $value {
+                fn to_bits(self) -> u64 {
+                    self as u64
+                }
+
+                fn from_bits(bits: u64) -> Self {
+                    bits as $value
+                }
+            }
+        )+
+    };
+}
+
+int_bits!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl Bits for bool {
+    fn to_bits(self) -> u64 {
+        self as u64
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        bits != 0
+    }
+}
+
+impl<T> Bits for *mut T {
+    const POINTER: bool = true;
+
+    fn to_bits(self) -> u64 {
+        self as usize as u64
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        bits as usize as *mut T
+    }
+}
+
+/// Methods shared by every atomic type.
+macro_rules! atomic_common {
+    ($atomic:ident $(<$t:ident>)?, $value:ty) => {
+        impl$(<$t>)? $atomic$(<$t>)? {
+            pub const fn new(value: $value) -> Self {
+                Self {
+                    inner: std::sync::atomic::$atomic::new(value),
+                }
+            }
+
+            pub fn get_mut(&mut self) -> &mut $value {
+                // Exclusive access: older stores can no longer be read
+                controlled::forget_atomic(self.address());
+                self.inner.get_mut()
+            }
+
+            pub fn into_inner(mut self) -> $value {
+                *self.inner.get_mut()
+            }
+
+            fn address(&self) -> usize {
+                self as *const Self as usize
+            }
+
+            pub fn load(&self, order: Ordering) -> $value {
+                controlled::sync_point(SyncOp::Load, self.address(), Some(order));
+                let latest = self.inner.load(order);
+                let value = controlled::atomic_load(
+                    self.address(),
+                    order,
+                    <$value as Bits>::POINTER,
+                    latest.to_bits(),
+                );
+                Bits::from_bits(value)
+            }
+
+            pub fn store(&self, value: $value, order: Ordering) {
+                controlled::sync_point(SyncOp::Store, self.address(), Some(order));
+                let previous = self.inner.load(Ordering::Relaxed);
+                self.inner.store(value, order);
+                controlled::atomic_store(
+                    self.address(),
+                    order,
+                    <$value as Bits>::POINTER,
+                    previous.to_bits(),
+                    value.to_bits(),
+                );
+            }
+
+            pub fn swap(&self, value: $value, order: Ordering) -> $value {
+                controlled::sync_point(SyncOp::ReadModifyWrite, self.address(), Some(order));
+                let previous = self.inner.swap(value, order);
+                controlled::atomic_read_modify_write(
+                    self.address(),
+                    order,
+                    <$value as Bits>::POINTER,
+                    previous.to_bits(),
+                    value.to_bits(),
+                );
+                previous
+            }
+
+            pub fn compare_exchange(
+                &self,
+                current: $value,
+                new: $value,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$value, $value> {
+                controlled::switch_point();
+                let result = self.inner.compare_exchange(current, new, success, failure);
+                let order = if result.is_ok() { success } else { failure };
+                // Recorded first, so memory issues point at this event
+                controlled::record_event(cas_event(result.is_ok()), self.address(), Some(order));
+                let pointer = <$value as Bits>::POINTER;
+                match result {
+                    Ok(previous) => controlled::atomic_compare_exchange(
+                        self.address(),
+                        success,
+                        pointer,
+                        previous.to_bits(),
+                        new.to_bits(),
+                    ),
+                    Err(latest) => {
+                        controlled::atomic_read_latest(self.address(), failure, pointer, latest.to_bits())
+                    }
+                }
+                result
+            }
+
+            /// Never fails spuriously: the scheduler explores preemption
+            /// instead.
+            pub fn compare_exchange_weak(
+                &self,
+                current: $value,
+                new: $value,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$value, $value> {
+                self.compare_exchange(current, new, success, failure)
+            }
+
+            pub fn fetch_update<F>(
+                &self,
+                set_order: Ordering,
+                fetch_order: Ordering,
+                mut f: F,
+            ) -> Result<$value, $value>
+            where
+                F: FnMut($value) -> Option<$value>,
+            {
+                let mut previous = self.load(fetch_order);
+                while let Some(next) = f(previous) {
+                    match self.compare_exchange_weak(previous, next, set_order, fetch_order) {
+                        Ok(value) => return Ok(value),
+                        Err(value) => previous = value,
+                    }
+                }
+                Err(previous)
+            }
+        }
+
+        impl$(<$t>)? Drop for $atomic$(<$t>)? {
+            fn drop(&mut self) {
+                // The address may be reused by a new atomic
+                controlled::forget_atomic(self.address());
+            }
+        }
+
+        impl$(<$t>)? From<$value> for $atomic$(<$t>)? {
+            fn from(value: $value) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl$(<$t>)? fmt::Debug for $atomic$(<$t>)? {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.inner.fmt(f)
+            }
+        }
+    };
+}
+
+/// Read-modify-write operations of the form `fetch_op(value, order)`.
+macro_rules! atomic_fetch_ops {
+    ($atomic:ident, $value:ty, $($op:ident),+) => {
+        impl $atomic {
+            $(
+                pub fn $op(&self, value: $value, order: Ordering) -> $value {
+                    controlled::sync_point(SyncOp::ReadModifyWrite, self.address(), Some(order));
+                    let previous = self.inner.$op(value, order);
+                    // Nothing else runs until the next switch point
+                    let stored = self.inner.load(Ordering::Relaxed);
+                    controlled::atomic_read_modify_write(
+                        self.address(),
+                        order,
+                        false,
+                        previous.to_bits(),
+                        stored.to_bits(),
+                    );
+                    previous
+                }
+            )+
+        }
+    };
+}
+
+macro_rules! atomic_int {
+    ($atomic:ident, $value:ty) => {
+        #[doc = concat!("Controlled `std::sync::atomic::", stringify!($atomic), "`.")]
+        #[derive(Default)]
+        pub struct $atomic {
+            inner: std::sync::atomic::$atomic,
+        }
+
+        atomic_common!($atomic, $value);
+        atomic_fetch_ops!(
+            $atomic, $value, fetch_add, fetch_sub, fetch_and, fetch_nand, fetch_or, fetch_xor,
+            fetch_max, fetch_min
+        );
+    };
+}
+
+atomic_int!(AtomicU8, u8);
+atomic_int!(AtomicU16, u16);
+atomic_int!(AtomicU32, u32);
+atomic_int!(AtomicU64, u64);
+atomic_int!(AtomicUsize, usize);
+atomic_int!(AtomicI8, i8);
+atomic_int!(AtomicI16, i16);
+atomic_int!(AtomicI32, i32);
+atomic_int!(AtomicI64, i64);
+atomic_int!(AtomicIsize, isize);
+
+/// Controlled `std::sync::atomic::AtomicBool`.
+#[derive(Default)]
+pub struct AtomicBool {
+    inner: std::sync::atomic::AtomicBool,
+}
+
+atomic_common!(AtomicBool, bool);
+atomic_fetch_ops!(AtomicBool, bool, fetch_and, fetch_nand, fetch_or, fetch_xor);
+
+/// Controlled `std::sync::atomic::AtomicPtr`.
+pub struct AtomicPtr<T> {
+    inner: std::sync::atomic::AtomicPtr<T>,
+}
+
+atomic_common!(AtomicPtr<T>, *mut T);
+
+impl<T> Default for AtomicPtr<T> {
+    fn default() -> Self {
+        Self::new(std::ptr::null_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controlled::ControlledReport;
+    use crate::controlled::ControlledRuntime;
+    use crate::random::DeterministicRng;
+    use crate::scheduler::Scheduler;
+    use vf_core::counterexample::MemoryIssue;
+
+    const INCREMENTS: u64 = 5;
+
+    /// Two threads incrementing with a racy load/store and no explicit
+    /// switch points: the shim provides them.
+    fn racy_increments(seed: u64) -> u64 {
+        let counter = AtomicU64::new(0);
+        let increment = || {
+            for _ in 0..INCREMENTS {
+                let value = counter.load(Ordering::SeqCst);
+                counter.store(value + 1, Ordering::SeqCst);
+            }
+        };
+        let scheduler = Scheduler::new(DeterministicRng::new(seed), 2, 0.5);
+        let report =
+            ControlledRuntime::new(scheduler).run(vec![Box::new(increment), Box::new(increment)]);
+        assert!(report.passed(), "{}", report.format());
+        counter.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn test_finds_lost_update_without_explicit_switch_points() {
+        let lost = (1..=20).filter(|&seed| racy_increments(seed) < 2 * INCREMENTS);
+        assert!(lost.count() > 0, "no seed interleaved inside the increment");
+    }
+
+    #[test]
+    fn test_fetch_add_never_loses_updates() {
+        for seed in 1..=20 {
+            let counter = AtomicUsize::new(0);
+            let increment = || {
+                for _ in 0..INCREMENTS {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            };
+            let scheduler = Scheduler::new(DeterministicRng::new(seed), 2, 0.5);
+            let report = ControlledRuntime::new(scheduler)
+                .run(vec![Box::new(increment), Box::new(increment)]);
+            assert!(report.passed(), "{}", report.format());
+            assert_eq!(counter.into_inner(), 2 * INCREMENTS as usize);
+        }
+    }
+
+    #[test]
+    fn test_events_record_op_and_ordering() {
+        let flag = AtomicBool::new(false);
+        let scheduler = Scheduler::new(DeterministicRng::new(1), 1, 0.5);
+        let report = ControlledRuntime::new(scheduler).run(vec![Box::new(|| {
+            flag.store(true, Ordering::Release);
+            let _ = flag.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire);
+            flag.load(Ordering::Acquire);
+        })]);
+
+        let events: Vec<_> = report
+            .events
+            .iter()
+            .map(|e| (e.step, e.op, e.ordering))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (1, SyncOp::Store, Some(Ordering::Release)),
+                (2, SyncOp::CasFailure, Some(Ordering::Acquire)),
+                (3, SyncOp::Load, Some(Ordering::Acquire)),
+            ]
+        );
+        assert!(report.events.iter().all(|e| e.object == flag.address()));
+    }
+
+    /// Thread 0 publishes data behind a flag. Returns the data thread 1
+    /// read after seeing the flag.
+    fn message_passing(seed: u64, store_order: Ordering, load_order: Ordering) -> u64 {
+        let data = AtomicU64::new(0);
+        let flag = AtomicBool::new(false);
+        let read = std::sync::Mutex::new(None);
+        let scheduler = Scheduler::new(DeterministicRng::new(seed), 2, 0.5);
+        let report = ControlledRuntime::new(scheduler)
+            .with_weak_memory(DeterministicRng::new(seed + 1))
+            .run(vec![
+                Box::new(|| {
+                    data.store(42, Ordering::Relaxed);
+                    flag.store(true, store_order);
+                }),
+                Box::new(|| {
+                    while !flag.load(load_order) {}
+                    *read.lock().unwrap() = Some(data.load(Ordering::Relaxed));
+                }),
+            ]);
+        assert!(report.passed(), "{}", report.format());
+        read.into_inner().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_weak_memory_finds_relaxed_publication() {
+        let stale = (1..=20)
+            .filter(|&seed| message_passing(seed, Ordering::Relaxed, Ordering::Relaxed) == 0);
+        assert!(stale.count() > 0, "no seed read the data before its store");
+    }
+
+    #[test]
+    fn test_weak_memory_respects_release_acquire() {
+        for seed in 1..=20 {
+            assert_eq!(
+                message_passing(seed, Ordering::Release, Ordering::Acquire),
+                42
+            );
+        }
+    }
+
+    #[test]
+    fn test_weak_memory_through_mutex() {
+        for seed in 1..=20 {
+            let data = AtomicU64::new(0);
+            let lock = crate::sync::Mutex::new(false);
+            let scheduler = Scheduler::new(DeterministicRng::new(seed), 2, 0.5);
+            let report = ControlledRuntime::new(scheduler)
+                .with_weak_memory(DeterministicRng::new(seed + 1))
+                .run(vec![
+                    Box::new(|| {
+                        data.store(42, Ordering::Relaxed);
+                        *lock.lock().unwrap() = true;
+                    }),
+                    Box::new(|| {
+                        if *lock.lock().unwrap() {
+                            assert_eq!(data.load(Ordering::Relaxed), 42);
+                        }
+                    }),
+                ]);
+            assert!(report.passed(), "{}", report.format());
+        }
+    }
+
+    /// Thread 0 reads the head, thread 1 swaps it to `b` and back to `a`,
+    /// then thread 0's compare-exchange succeeds. With `reload`, thread 0
+    /// reads the head again first.
+    fn recycled_head(seed: u64, reload: bool) -> ControlledReport {
+        let (mut a, mut b) = (1u8, 2u8);
+        let head = AtomicPtr::new(&mut a as *mut u8);
+        let (first, second) = (&mut a as *mut u8 as usize, &mut b as *mut u8 as usize);
+        let loaded = AtomicBool::new(false);
+        let recycled = AtomicBool::new(false);
+        let scheduler = Scheduler::new(DeterministicRng::new(seed), 2, 0.5);
+        ControlledRuntime::new(scheduler)
+            .with_memory_checker()
+            .run(vec![
+                Box::new(|| {
+                    let mut seen = head.load(Ordering::Acquire);
+                    loaded.store(true, Ordering::Release);
+                    while !recycled.load(Ordering::Acquire) {
+                        crate::sync::thread::yield_now();
+                    }
+                    if reload {
+                        seen = head.load(Ordering::Acquire);
+                    }
+                    let swapped = head.compare_exchange(
+                        seen,
+                        std::ptr::null_mut(),
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    );
+                    assert!(swapped.is_ok());
+                }),
+                Box::new(|| {
+                    while !loaded.load(Ordering::Acquire) {
+                        crate::sync::thread::yield_now();
+                    }
+                    head.store(second as *mut u8, Ordering::Release);
+                    head.store(first as *mut u8, Ordering::Release);
+                    recycled.store(true, Ordering::Release);
+                }),
+            ])
+    }
+
+    #[test]
+    fn test_recycled_pointer_is_aba() {
+        for seed in 1..=10 {
+            let report = recycled_head(seed, false);
+            let cas_step = report
+                .events
+                .iter()
+                .find(|e| e.op == SyncOp::ReadModifyWrite)
+                .unwrap()
+                .step;
+            match report.memory_issues[..] {
+                [MemoryIssue::AbaProblem {
+                    original_value,
+                    intermediate_value,
+                    final_value,
+                    step,
+                    ..
+                }] => {
+                    assert_eq!(original_value, final_value);
+                    assert_ne!(original_value, intermediate_value);
+                    assert_eq!(step, cas_step);
+                }
+                _ => panic!("{}", report.format()),
+            }
+
+            let report = recycled_head(seed, true);
+            assert!(report.passed(), "{}", report.format());
+        }
+    }
+
+    #[test]
+    fn test_plain_atomics_outside_runtime() {
+        let value = AtomicI32::new(1);
+        assert_eq!(value.fetch_sub(3, Ordering::SeqCst), 1);
+        assert_eq!(
+            value.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| Some(v * 2)),
+            Ok(-2)
+        );
+        assert_eq!(value.load(Ordering::SeqCst), -4);
+
+        let mut data = 7;
+        let ptr = AtomicPtr::default();
+        assert!(ptr.swap(&mut data, Ordering::SeqCst).is_null());
+        assert_eq!(ptr.load(Ordering::SeqCst), &mut data as *mut i32);
+    }
+}
@@ -0,0 +1,185 @@
+//! Controlled `UnsafeCell`, with loom's closure-based API.
+//!
+//! Shared non-atomic data is only reachable through [`UnsafeCell::with`]
+//! and [`UnsafeCell::with_mut`], so under the memory checker every access
+//! is checked for data races (see [`crate::memory_checker`]). Without the
+//! `simulation` feature both just call the closure with the raw pointer.
+
+#[cfg(feature = "simulation")]
+use crate::controlled;
+
+/// A `std::cell::UnsafeCell` whose accesses are switch points.
+pub struct UnsafeCell<T: ?Sized> {
+    inner: std::cell::UnsafeCell<T>,
+}
+
+impl<T> UnsafeCell<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            inner: std::cell::UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        let this = std::mem::ManuallyDrop::new(self);
+        #[cfg(feature = "simulation")]
+        controlled::forget_cell(super::address(&*this));
+        // SAFETY: `this` is never dropped, so the value is moved out once
+        unsafe { std::ptr::read(&this.inner) }.into_inner()
+    }
+}
+
+impl<T: ?Sized> UnsafeCell<T> {
+    /// Read access: call `f` with a pointer to the value.
+    pub fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        #[cfg(feature = "simulation")]
+        controlled::cell_access(super::address(self), false);
+        f(self.inner.get())
+    }
+
+    /// Write access: call `f` with a mutable pointer to the value.
+    pub fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        #[cfg(feature = "simulation")]
+        controlled::cell_access(super::address(self), true);
+        f(self.inner.get())
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+}
+
+impl<T: ?Sized> Drop for UnsafeCell<T> {
+    fn drop(&mut self) {
+        // The address may be reused by a new cell
+        #[cfg(feature = "simulation")]
+        controlled::forget_cell(super::address(self));
+    }
+}
+
+impl<T: Default> Default for UnsafeCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for UnsafeCell<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controlled::{ControlledReport, ControlledRuntime};
+    use crate::random::DeterministicRng;
+    use crate::scheduler::Scheduler;
+    use crate::sync::atomic::{AtomicBool, Ordering};
+    use crate::sync::thread;
+
+    struct Publication {
+        data: UnsafeCell<u64>,
+        ready: AtomicBool,
+    }
+
+    // SAFETY: `data` is only accessed through the shim, which checks it
+    unsafe impl Sync for Publication {}
+
+    /// Thread 0 writes the data then sets the flag; thread 1 waits for the
+    /// flag, then reads the data.
+    fn publish(seed: u64, store_order: Ordering, load_order: Ordering) -> ControlledReport {
+        let shared = &Publication {
+            data: UnsafeCell::new(0),
+            ready: AtomicBool::new(false),
+        };
+        let scheduler = Scheduler::new(DeterministicRng::new(seed), 2, 0.5);
+        ControlledRuntime::new(scheduler)
+            .with_memory_checker()
+            .run(vec![
+                Box::new(move || {
+                    shared.data.with_mut(|data| unsafe { *data = 42 });
+                    shared.ready.store(true, store_order);
+                }),
+                Box::new(move || {
+                    while !shared.ready.load(load_order) {
+                        thread::yield_now();
+                    }
+                    assert_eq!(shared.data.with(|data| unsafe { *data }), 42);
+                }),
+            ])
+    }
+
+    #[test]
+    fn test_release_acquire_publication_is_race_free() {
+        for seed in 1..=10 {
+            let report = publish(seed, Ordering::Release, Ordering::Acquire);
+            assert!(report.passed(), "{}", report.format());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "simulation")]
+    fn test_relaxed_publication_races() {
+        use vf_core::counterexample::MemoryIssue;
+
+        for seed in 1..=10 {
+            let report = publish(seed, Ordering::Relaxed, Ordering::Relaxed);
+            assert!(report.failure.is_none(), "{}", report.format());
+            let step = match report.memory_issues[..] {
+                [MemoryIssue::DataRace {
+                    thread_a: 0,
+                    thread_b: 1,
+                    step,
+                    ..
+                }] => step,
+                _ => panic!("{}", report.format()),
+            };
+
+            // The race points at thread 1's read in the counterexample
+            let counterexample = report.counterexample(seed);
+            assert_eq!(counterexample.memory_issues.len(), 1);
+            assert_eq!(counterexample.interleaving.len(), report.events.len());
+            let action = &counterexample.interleaving[step as usize - 1];
+            assert_eq!((action.thread_id, action.step), (1, step));
+            assert!(action.action.starts_with("Read 0x"), "{}", action.action);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "simulation")]
+    fn test_lock_protected_cell_is_race_free() {
+        use crate::controlled::SyncOp;
+        use crate::sync::Mutex;
+
+        struct Counter {
+            lock: Mutex<()>,
+            value: UnsafeCell<u64>,
+        }
+        // SAFETY: `value` is only accessed under `lock`
+        unsafe impl Sync for Counter {}
+
+        for seed in 1..=10 {
+            let counter = Counter {
+                lock: Mutex::new(()),
+                value: UnsafeCell::new(0),
+            };
+            let shared = &counter;
+            let increment = move || {
+                for _ in 0..3 {
+                    let _guard = shared.lock.lock().unwrap();
+                    let value = shared.value.with(|value| unsafe { *value });
+                    shared.value.with_mut(|slot| unsafe { *slot = value + 1 });
+                }
+            };
+            let scheduler = Scheduler::new(DeterministicRng::new(seed), 2, 0.5);
+            let report = ControlledRuntime::new(scheduler)
+                .with_memory_checker()
+                .run(vec![Box::new(increment), Box::new(increment)]);
+            assert!(report.passed(), "{}", report.format());
+            assert_eq!(counter.value.into_inner(), 6);
+            let writes = report.events.iter().filter(|e| e.op == SyncOp::Write);
+            assert_eq!(writes.count(), 6);
+        }
+    }
+}
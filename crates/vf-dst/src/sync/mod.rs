@@ -0,0 +1,138 @@
+//! Drop-in replacements for `std::sync` and `std::thread` that DST can
+//! control.
+//!
+//! Like loom, code under test imports its primitives from here instead of
+//! from std:
+//!
+//! ```rust
+//! use vf_dst::sync::atomic::{AtomicU64, Ordering};
+//! use vf_dst::sync::{Arc, Mutex};
+//! ```
+//!
+//! Shared non-atomic data goes in a [`cell::UnsafeCell`], and raw
+//! allocations through [`alloc`], so the memory checker can see them.
+//!
+//! Inside a [`ControlledRuntime`](crate::ControlledRuntime) execution,
+//! every atomic, lock, `Arc`, cell, allocation and thread operation is a
+//! switch point and is recorded as a
+//! [`SyncEvent`](crate::controlled::SyncEvent). Outside one the wrappers
+//! go straight to std.
+//!
+//! The wrappers only exist with the `simulation` feature, which is off by
+//! default: without it this module re-exports the std types themselves,
+//! so production builds of the code under test carry no DST-specific
+//! calls at all. This replaces the deprecated `instrumented` traits, which
+//! made generated code pass a `DstContext` around. Enable the feature for
+//! tests only, from `[dev-dependencies]`:
+//!
+//! ```toml
+//! [dependencies]
+//! vf-dst = "0.1"
+//!
+//! [dev-dependencies]
+//! vf-dst = { version = "0.1", features = ["simulation"] }
+//! ```
+//!
+//! # Limits
+//!
+//! - There is no `Condvar`: waiting code spins with `thread::yield_now`.
+//! - A contended lock hands control to another thread, so waiting for a
+//!   lock that no runnable thread will release is reported as a deadlock.
+//! - Atomics are sequentially consistent unless the execution simulates
+//!   weak memory (see [`crate::weak_memory`]).
+
+#[cfg(feature = "simulation")]
+pub mod alloc;
+#[cfg(feature = "simulation")]
+mod arc;
+#[cfg(feature = "simulation")]
+pub mod atomic;
+pub mod cell;
+#[cfg(feature = "simulation")]
+mod mutex;
+#[cfg(feature = "simulation")]
+mod rwlock;
+#[cfg(feature = "simulation")]
+pub mod thread;
+
+#[cfg(feature = "simulation")]
+pub use arc::Arc;
+#[cfg(feature = "simulation")]
+pub use mutex::{Mutex, MutexGuard};
+#[cfg(feature = "simulation")]
+pub use rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(feature = "simulation")]
+pub use std::sync::{LockResult, PoisonError, TryLockError, TryLockResult};
+
+#[cfg(not(feature = "simulation"))]
+pub use std::sync::{
+    Arc, LockResult, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    TryLockError, TryLockResult,
+};
+
+/// Plain `std::alloc`.
+#[cfg(not(feature = "simulation"))]
+pub mod alloc {
+    pub use std::alloc::{alloc, alloc_zeroed, dealloc, handle_alloc_error, Layout};
+}
+
+/// Plain `std::sync::atomic`.
+#[cfg(not(feature = "simulation"))]
+pub mod atomic {
+    pub use std::sync::atomic::*;
+}
+
+/// Plain `std::thread`.
+#[cfg(not(feature = "simulation"))]
+pub mod thread {
+    pub use std::thread::*;
+}
+
+/// Apply `f` to the guard of a lock result, keeping the poison flag.
+#[cfg(feature = "simulation")]
+fn map_lock_result<G, H>(result: LockResult<G>, f: impl FnOnce(G) -> H) -> LockResult<H> {
+    match result {
+        Ok(guard) => Ok(f(guard)),
+        Err(poisoned) => Err(PoisonError::new(f(poisoned.into_inner()))),
+    }
+}
+
+/// Apply `f` to the guard of a try-lock result.
+#[cfg(feature = "simulation")]
+fn map_try_lock_result<G, H>(result: TryLockResult<G>, f: impl FnOnce(G) -> H) -> TryLockResult<H> {
+    match result {
+        Ok(guard) => Ok(f(guard)),
+        Err(TryLockError::Poisoned(poisoned)) => Err(TryLockError::Poisoned(PoisonError::new(f(
+            poisoned.into_inner(),
+        )))),
+        Err(TryLockError::WouldBlock) => Err(TryLockError::WouldBlock),
+    }
+}
+
+/// Spin on `try_acquire` under the controlled runtime, letting other
+/// threads run until it succeeds. Outside it, block with `acquire`.
+#[cfg(feature = "simulation")]
+fn acquire<G>(
+    try_acquire: impl Fn() -> TryLockResult<G>,
+    acquire: impl FnOnce() -> LockResult<G>,
+) -> LockResult<G> {
+    use crate::controlled;
+
+    controlled::switch_point();
+    if controlled::current_thread().is_none() {
+        return acquire();
+    }
+    loop {
+        match try_acquire() {
+            Ok(guard) => return Ok(guard),
+            Err(TryLockError::Poisoned(poisoned)) => return Err(poisoned),
+            Err(TryLockError::WouldBlock) => controlled::yield_now(),
+        }
+    }
+}
+
+/// Address of a shim object, identifying it in sync events.
+#[cfg(feature = "simulation")]
+fn address<T: ?Sized>(object: &T) -> usize {
+    object as *const T as *const () as usize
+}
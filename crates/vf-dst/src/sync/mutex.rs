@@ -0,0 +1,163 @@
+//! Controlled `Mutex`.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use super::{acquire, address, map_lock_result, map_try_lock_result, LockResult, TryLockResult};
+use crate::controlled::{self, SyncOp};
+
+/// A `std::sync::Mutex` whose lock and unlock are switch points.
+#[derive(Default)]
+pub struct Mutex<T: ?Sized> {
+    inner: std::sync::Mutex<T>,
+}
+
+impl<T> Mutex<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            inner: std::sync::Mutex::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        let result = acquire(|| self.inner.try_lock(), || self.inner.lock());
+        controlled::record_event(SyncOp::Lock, address(self), None);
+        map_lock_result(result, |inner| MutexGuard {
+            object: address(self),
+            inner,
+        })
+    }
+
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        controlled::switch_point();
+        let result = self.inner.try_lock();
+        if result.is_ok() {
+            controlled::record_event(SyncOp::Lock, address(self), None);
+        }
+        map_try_lock_result(result, |inner| MutexGuard {
+            object: address(self),
+            inner,
+        })
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.inner.is_poisoned()
+    }
+
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.inner.get_mut()
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+/// Guard of a [`Mutex`]; dropping it is a switch point.
+pub struct MutexGuard<'a, T: ?Sized> {
+    object: usize,
+    inner: std::sync::MutexGuard<'a, T>,
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        // Still locked here: the std guard is released right after
+        controlled::sync_point(SyncOp::Unlock, self.object, None);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controlled::ControlledRuntime;
+    use crate::random::DeterministicRng;
+    use crate::scheduler::Scheduler;
+
+    const INCREMENTS: u64 = 5;
+
+    #[test]
+    fn test_locked_increments_never_lost() {
+        for seed in 1..=20 {
+            let counter = Mutex::new(0);
+            let increment = || {
+                for _ in 0..INCREMENTS {
+                    let mut value = counter.lock().unwrap();
+                    let read = *value;
+                    // Would lose updates if the lock did not exclude others
+                    crate::controlled::switch_point();
+                    *value = read + 1;
+                }
+            };
+            let scheduler = Scheduler::new(DeterministicRng::new(seed), 3, 0.5);
+            let report = ControlledRuntime::new(scheduler).run(vec![
+                Box::new(increment),
+                Box::new(increment),
+                Box::new(increment),
+            ]);
+            assert!(report.passed(), "{}", report.format());
+            assert_eq!(*counter.lock().unwrap(), 3 * INCREMENTS);
+        }
+    }
+
+    #[test]
+    fn test_lock_events_pair_up() {
+        let mutex = Mutex::new(());
+        let scheduler = Scheduler::new(DeterministicRng::new(3), 2, 0.5);
+        let report = ControlledRuntime::new(scheduler).run(vec![
+            Box::new(|| drop(mutex.lock().unwrap())),
+            Box::new(|| drop(mutex.lock().unwrap())),
+        ]);
+        assert!(report.passed(), "{}", report.format());
+
+        // Each Lock is followed by the same thread's Unlock
+        let ops: Vec<_> = report.events.iter().map(|e| (e.thread, e.op)).collect();
+        assert_eq!(ops.len(), 4);
+        for pair in ops.chunks(2) {
+            assert_eq!(pair[0].1, SyncOp::Lock);
+            assert_eq!(pair[1], (pair[0].0, SyncOp::Unlock));
+        }
+    }
+
+    #[test]
+    fn test_try_lock_fails_while_held() {
+        let mutex = Mutex::new(1);
+        let guard = mutex.lock().unwrap();
+        assert!(mutex.try_lock().is_err());
+        drop(guard);
+        assert_eq!(*mutex.try_lock().unwrap(), 1);
+    }
+}
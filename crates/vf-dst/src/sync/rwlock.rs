@@ -0,0 +1,194 @@
+//! Controlled `RwLock`.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use super::{acquire, address, map_lock_result, map_try_lock_result, LockResult, TryLockResult};
+use crate::controlled::{self, SyncOp};
+
+/// A `std::sync::RwLock` whose lock and unlock are switch points.
+#[derive(Default)]
+pub struct RwLock<T: ?Sized> {
+    inner: std::sync::RwLock<T>,
+}
+
+impl<T> RwLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            inner: std::sync::RwLock::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+        let result = acquire(|| self.inner.try_read(), || self.inner.read());
+        controlled::record_event(SyncOp::ReadLock, address(self), None);
+        map_lock_result(result, |inner| RwLockReadGuard {
+            object: address(self),
+            inner,
+        })
+    }
+
+    pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+        let result = acquire(|| self.inner.try_write(), || self.inner.write());
+        controlled::record_event(SyncOp::Lock, address(self), None);
+        map_lock_result(result, |inner| RwLockWriteGuard {
+            object: address(self),
+            inner,
+        })
+    }
+
+    pub fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        controlled::switch_point();
+        let result = self.inner.try_read();
+        if result.is_ok() {
+            controlled::record_event(SyncOp::ReadLock, address(self), None);
+        }
+        map_try_lock_result(result, |inner| RwLockReadGuard {
+            object: address(self),
+            inner,
+        })
+    }
+
+    pub fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        controlled::switch_point();
+        let result = self.inner.try_write();
+        if result.is_ok() {
+            controlled::record_event(SyncOp::Lock, address(self), None);
+        }
+        map_try_lock_result(result, |inner| RwLockWriteGuard {
+            object: address(self),
+            inner,
+        })
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.inner.is_poisoned()
+    }
+
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.inner.get_mut()
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+/// Shared guard of a [`RwLock`]; dropping it is a switch point.
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    object: usize,
+    inner: std::sync::RwLockReadGuard<'a, T>,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        controlled::sync_point(SyncOp::ReadUnlock, self.object, None);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+/// Exclusive guard of a [`RwLock`]; dropping it is a switch point.
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    object: usize,
+    inner: std::sync::RwLockWriteGuard<'a, T>,
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        controlled::sync_point(SyncOp::Unlock, self.object, None);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controlled::ControlledRuntime;
+    use crate::random::DeterministicRng;
+    use crate::scheduler::Scheduler;
+
+    #[test]
+    fn test_readers_never_see_half_written_pair() {
+        for seed in 1..=20 {
+            let pair = RwLock::new((0u64, 0u64));
+            let scheduler = Scheduler::new(DeterministicRng::new(seed), 3, 0.5);
+            let writer = || {
+                for i in 1..=5 {
+                    let mut guard = pair.write().unwrap();
+                    guard.0 = i;
+                    crate::controlled::switch_point();
+                    guard.1 = i;
+                }
+            };
+            let reader = || {
+                for _ in 0..5 {
+                    let guard = pair.read().unwrap();
+                    assert_eq!(guard.0, guard.1, "torn read");
+                }
+            };
+            let report = ControlledRuntime::new(scheduler).run(vec![
+                Box::new(writer),
+                Box::new(reader),
+                Box::new(reader),
+            ]);
+            assert!(report.passed(), "{}", report.format());
+        }
+    }
+
+    #[test]
+    fn test_try_write_fails_while_read() {
+        let lock = RwLock::new(1);
+        let guard = lock.read().unwrap();
+        assert!(lock.try_write().is_err());
+        assert_eq!(*lock.try_read().unwrap(), 1);
+        drop(guard);
+        *lock.try_write().unwrap() = 2;
+        assert_eq!(lock.into_inner().unwrap(), 2);
+    }
+}
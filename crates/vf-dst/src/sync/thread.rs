@@ -0,0 +1,150 @@
+//! Controlled threads, mirroring `std::thread`.
+//!
+//! Spawning from a controlled thread adds the child to the running
+//! execution, so it is scheduled like the threads the execution started
+//! with. Anywhere else `spawn` starts a plain OS thread.
+
+use std::sync::Mutex;
+
+use crate::controlled::{self, SyncOp};
+
+pub use std::thread::{current, panicking, sleep, Result, Thread, ThreadId};
+
+/// Hand control to another thread.
+///
+/// Under the controlled runtime, yielding with no other runnable thread
+/// is reported as a deadlock.
+pub fn yield_now() {
+    if controlled::current_thread().is_some() {
+        controlled::yield_now();
+    } else {
+        std::thread::yield_now();
+    }
+}
+
+/// Spawn a thread, joining the current execution if there is one.
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let output = std::sync::Arc::new(Mutex::new(None));
+    let slot = std::sync::Arc::clone(&output);
+    let body = Box::new(move || {
+        let value = f();
+        *slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(value);
+    });
+
+    let inner = match controlled::spawn(body) {
+        Ok(thread) => {
+            // Recorded before the child can run, which orders its events
+            controlled::record_event(SyncOp::Spawn, thread, None);
+            controlled::switch_point();
+            Inner::Controlled(thread)
+        }
+        Err(body) => Inner::Os(std::thread::spawn(body)),
+    };
+    JoinHandle { inner, output }
+}
+
+enum Inner {
+    /// Index of a thread in the current controlled execution
+    Controlled(usize),
+    Os(std::thread::JoinHandle<()>),
+}
+
+/// Owned permission to join a spawned thread.
+pub struct JoinHandle<T> {
+    inner: Inner,
+    output: std::sync::Arc<Mutex<Option<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Wait for the thread to finish and return its result.
+    ///
+    /// A controlled thread that panics fails the whole execution, so
+    /// joining it unwinds the caller rather than returning `Err`.
+    pub fn join(self) -> Result<T> {
+        match self.inner {
+            Inner::Controlled(thread) => {
+                controlled::switch_point();
+                while !controlled::is_finished(thread) {
+                    controlled::yield_now();
+                }
+                controlled::record_event(SyncOp::Join, thread, None);
+            }
+            Inner::Os(handle) => handle.join()?,
+        }
+        let value = self.output.lock().unwrap_or_else(|e| e.into_inner()).take();
+        value.ok_or_else(|| Box::new("spawned thread panicked") as _)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        match self.inner {
+            Inner::Controlled(thread) => controlled::is_finished(thread),
+            Inner::Os(ref handle) => handle.is_finished(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controlled::ControlledRuntime;
+    use crate::random::DeterministicRng;
+    use crate::scheduler::Scheduler;
+    use crate::sync::atomic::{AtomicU64, Ordering};
+    use crate::sync::Arc;
+
+    #[test]
+    fn test_spawned_threads_join_execution() {
+        for seed in 1..=10 {
+            let scheduler = Scheduler::new(DeterministicRng::new(seed), 1, 0.5);
+            let report = ControlledRuntime::new(scheduler).run(vec![Box::new(|| {
+                let counter = Arc::new(AtomicU64::new(0));
+                let handles: Vec<_> = (0..3)
+                    .map(|i| {
+                        let counter = counter.clone();
+                        spawn(move || {
+                            counter.fetch_add(1, Ordering::SeqCst);
+                            i * 10
+                        })
+                    })
+                    .collect();
+                let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+                assert_eq!(results, vec![0, 10, 20]);
+                assert_eq!(counter.load(Ordering::SeqCst), 3);
+            })]);
+            assert!(report.passed(), "{}", report.format());
+
+            // Children ran as controlled threads 1..=3
+            let threads: std::collections::BTreeSet<_> =
+                report.events.iter().map(|e| e.thread).collect();
+            assert_eq!(threads.into_iter().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+            let spawns = report.events.iter().filter(|e| e.op == SyncOp::Spawn);
+            let joins = report.events.iter().filter(|e| e.op == SyncOp::Join);
+            assert_eq!((spawns.count(), joins.count()), (3, 3));
+        }
+    }
+
+    #[test]
+    fn test_child_panic_fails_execution() {
+        let scheduler = Scheduler::new(DeterministicRng::new(2), 1, 0.5);
+        let report = ControlledRuntime::new(scheduler).run(vec![Box::new(|| {
+            let _ = spawn(|| panic!("child failed")).join();
+        })]);
+
+        let failure = report.failure.unwrap();
+        assert!(
+            failure.contains("thread 1 panicked: child failed"),
+            "{}",
+            failure
+        );
+    }
+
+    #[test]
+    fn test_plain_threads_outside_runtime() {
+        let handle = spawn(|| 42);
+        assert_eq!(handle.join().unwrap(), 42);
+    }
+}
@@ -0,0 +1,534 @@
+//! C11 weak memory simulation for the DST atomic shim.
+//!
+//! A controlled execution runs one thread at a time, so every atomic load
+//! sees the latest store: the execution is sequentially consistent, and a
+//! missing `Acquire`/`Release` never shows. With weak memory enabled
+//! (`ControlledRuntime::with_weak_memory`), every atomic location keeps a
+//! buffer of its recent stores, and a load may return any store the C11
+//! model allows, chosen by the seed.
+//!
+//! # Model
+//!
+//! Stores to a location are totally ordered (its modification order).
+//! Each thread has a *view*: for every location, the oldest store it may
+//! still read. A load picks a store at or after the view and moves the
+//! view up to it, which gives coherence. Views only grow through
+//! synchronization:
+//!
+//! - A release store publishes the storing thread's view; an acquire load
+//!   that reads it joins that view. Read-modify-writes continue the
+//!   release sequence of the store they read.
+//! - Fences: a relaxed load of a released store is acquired by a later
+//!   acquire fence; a relaxed store after a release fence publishes the
+//!   view at the fence.
+//! - `SeqCst` operations read the latest store and share one global view.
+//! - Unlocking a lock publishes the thread's view to the next locker;
+//!   spawning and joining copy views between parent and child.
+//!
+//! Read-modify-writes and failed compare-exchanges always read the latest
+//! store. Only accesses through [`crate::sync`] are modeled: non-atomic
+//! memory and std primitives are always up to date.
+//!
+//! Every release also publishes the thread's [`VectorClock`], so the
+//! model knows which operations happen before which; the
+//! [`crate::memory_checker`] uses it to find data races. A
+//! [`WeakMemory::sequential`] model only tracks happens-before, and
+//! every load reads the latest store.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::Ordering;
+
+use crate::memory_checker::VectorClock;
+use crate::random::DeterministicRng;
+
+/// Stores kept per location; older ones can no longer be read.
+pub const STORES_PER_LOCATION_MAX: usize = 16;
+
+/// For each location, the index of the oldest store a thread may read.
+type View = BTreeMap<usize, u64>;
+
+fn join(view: &mut View, other: &View) {
+    for (&location, &index) in other {
+        let entry = view.entry(location).or_insert(index);
+        *entry = (*entry).max(index);
+    }
+}
+
+fn is_acquire(order: Ordering) -> bool {
+    matches!(
+        order,
+        Ordering::Acquire | Ordering::AcqRel | Ordering::SeqCst
+    )
+}
+
+fn is_release(order: Ordering) -> bool {
+    matches!(
+        order,
+        Ordering::Release | Ordering::AcqRel | Ordering::SeqCst
+    )
+}
+
+/// What a thread knows: the stores it may still read, and the
+/// operations of every thread that happen before it.
+#[derive(Clone, Default)]
+struct Frontier {
+    view: View,
+    clock: VectorClock,
+}
+
+impl Frontier {
+    fn join(&mut self, other: &Frontier) {
+        join(&mut self.view, &other.view);
+        self.clock.join(&other.clock);
+    }
+}
+
+struct Store {
+    /// Position in the execution's store order, increasing across
+    /// locations so views stay valid when a location is reset
+    index: u64,
+    value: u64,
+    /// Frontier published to threads that acquire this store
+    release: Option<Frontier>,
+}
+
+#[derive(Default)]
+struct ThreadView {
+    known: Frontier,
+    /// Frontier published by relaxed stores after a release fence
+    fence_release: Option<Frontier>,
+    /// Frontiers of released stores read by relaxed loads, acquired by
+    /// the next acquire fence
+    fence_acquire: Frontier,
+}
+
+/// Store buffers and thread views of one controlled execution.
+pub struct WeakMemory {
+    /// Picks which store each load reads; `None` always reads the latest
+    rng: Option<DeterministicRng>,
+    locations: HashMap<usize, VecDeque<Store>>,
+    threads: Vec<ThreadView>,
+    /// Frontiers published by lock releases
+    locks: HashMap<usize, Frontier>,
+    /// Frontier shared by `SeqCst` operations
+    seq_cst: Frontier,
+    stores_count: u64,
+    stale_loads_count: u64,
+}
+
+impl WeakMemory {
+    /// Create the model. `rng` picks which store each load reads.
+    pub fn new(rng: DeterministicRng) -> Self {
+        Self::with_rng(Some(rng))
+    }
+
+    /// Create a model that only tracks happens-before: every load reads
+    /// the latest store, as without weak memory.
+    pub fn sequential() -> Self {
+        Self::with_rng(None)
+    }
+
+    fn with_rng(rng: Option<DeterministicRng>) -> Self {
+        Self {
+            rng,
+            locations: HashMap::new(),
+            threads: Vec::new(),
+            locks: HashMap::new(),
+            seq_cst: Frontier::default(),
+            stores_count: 0,
+            stale_loads_count: 0,
+        }
+    }
+
+    /// Loads that returned a store older than the latest.
+    pub fn stale_loads_count(&self) -> u64 {
+        self.stale_loads_count
+    }
+
+    /// Happens-before clock of `thread`. Its own entry is the epoch of
+    /// its current operations, which the next release ends.
+    pub fn clock(&mut self, thread: usize) -> &VectorClock {
+        &self.thread(thread).known.clock
+    }
+
+    fn thread(&mut self, thread: usize) -> &mut ThreadView {
+        while thread >= self.threads.len() {
+            let mut added = ThreadView::default();
+            added.known.clock.increment(self.threads.len());
+            self.threads.push(added);
+        }
+        &mut self.threads[thread]
+    }
+
+    /// Publish what `thread` knows, and start a new epoch so its later
+    /// operations are not covered by the published clock.
+    fn release(&mut self, thread: usize) -> Frontier {
+        let known = &mut self.thread(thread).known;
+        let published = known.clone();
+        known.clock.increment(thread);
+        published
+    }
+
+    /// Stores of `location`, reset when `current` shows the location was
+    /// written behind the model's back (a new object at a reused address).
+    fn stores(&mut self, location: usize, current: u64) -> &mut VecDeque<Store> {
+        let up_to_date = self
+            .locations
+            .get(&location)
+            .and_then(|stores| stores.back())
+            .is_some_and(|latest| latest.value == current);
+        if !up_to_date {
+            // The initial value happens before every access
+            let index = self.stores_count;
+            self.stores_count += 1;
+            let initial = Store {
+                index,
+                value: current,
+                release: None,
+            };
+            self.locations.insert(location, VecDeque::from([initial]));
+        }
+        self.locations
+            .get_mut(&location)
+            .expect("location was just added")
+    }
+
+    /// Read the store at `position` of `location` into `thread`'s view.
+    fn read(&mut self, thread: usize, location: usize, position: usize, order: Ordering) -> u64 {
+        let store = &self.locations[&location][position];
+        let (index, value, release) = (store.index, store.value, store.release.clone());
+        let thread = self.thread(thread);
+        join(&mut thread.known.view, &View::from([(location, index)]));
+        if let Some(release) = release {
+            if is_acquire(order) {
+                thread.known.join(&release);
+            } else {
+                thread.fence_acquire.join(&release);
+            }
+        }
+        value
+    }
+
+    /// Append a store made by `thread` to `location`.
+    fn write(&mut self, thread: usize, location: usize, value: u64, mut release: Option<Frontier>) {
+        let index = self.stores_count;
+        self.stores_count += 1;
+        let stored = View::from([(location, index)]);
+        join(&mut self.thread(thread).known.view, &stored);
+        if let Some(ref mut release) = release {
+            join(&mut release.view, &stored);
+        }
+
+        let stores = self
+            .locations
+            .get_mut(&location)
+            .expect("location has stores");
+        stores.push_back(Store {
+            index,
+            value,
+            release,
+        });
+        if stores.len() > STORES_PER_LOCATION_MAX {
+            stores.pop_front();
+        }
+    }
+
+    /// Frontier a store by `thread` with ordering `order` publishes.
+    fn release_frontier(&mut self, thread: usize, order: Ordering) -> Option<Frontier> {
+        if is_release(order) {
+            Some(self.release(thread))
+        } else {
+            self.thread(thread).fence_release.clone()
+        }
+    }
+
+    fn seq_cst_fence(&mut self, thread: usize) {
+        let seq_cst = self.seq_cst.clone();
+        self.thread(thread).known.join(&seq_cst);
+        self.seq_cst = self.release(thread);
+    }
+
+    /// Atomic load by `thread`; `latest` is the location's actual value.
+    /// Returns the value the load reads.
+    pub fn load(&mut self, thread: usize, location: usize, order: Ordering, latest: u64) -> u64 {
+        if order == Ordering::SeqCst {
+            self.seq_cst_fence(thread);
+        }
+        let floor = self
+            .thread(thread)
+            .known
+            .view
+            .get(&location)
+            .copied()
+            .unwrap_or(0);
+        let stores = self.stores(location, latest);
+        let newest = stores.len() - 1;
+        let oldest = stores
+            .partition_point(|store| store.index < floor)
+            .min(newest);
+
+        let position = match self.rng {
+            Some(ref mut rng) if order != Ordering::SeqCst => rng.gen_range(oldest..=newest),
+            _ => newest,
+        };
+        if position != newest {
+            self.stale_loads_count += 1;
+        }
+        self.read(thread, location, position, order)
+    }
+
+    /// Atomic store by `thread`; `previous` is the location's actual value
+    /// before it.
+    pub fn store(
+        &mut self,
+        thread: usize,
+        location: usize,
+        order: Ordering,
+        previous: u64,
+        value: u64,
+    ) {
+        if order == Ordering::SeqCst {
+            self.seq_cst_fence(thread);
+        }
+        self.stores(location, previous);
+        let release = self.release_frontier(thread, order);
+        self.write(thread, location, value, release);
+        if order == Ordering::SeqCst {
+            self.seq_cst_fence(thread);
+        }
+    }
+
+    /// Successful read-modify-write by `thread`, which read `previous`.
+    pub fn read_modify_write(
+        &mut self,
+        thread: usize,
+        location: usize,
+        order: Ordering,
+        previous: u64,
+        value: u64,
+    ) {
+        if order == Ordering::SeqCst {
+            self.seq_cst_fence(thread);
+        }
+        let newest = self.stores(location, previous).len() - 1;
+        self.read(thread, location, newest, order);
+
+        // Continue the release sequence of the store that was read
+        let read_release = self.locations[&location][newest].release.clone();
+        let release = match (self.release_frontier(thread, order), read_release) {
+            (Some(mut own), Some(read)) => {
+                own.join(&read);
+                Some(own)
+            }
+            (own, read) => own.or(read),
+        };
+        self.write(thread, location, value, release);
+        if order == Ordering::SeqCst {
+            self.seq_cst_fence(thread);
+        }
+    }
+
+    /// Failed compare-exchange by `thread`, which read `latest`.
+    pub fn read_latest(&mut self, thread: usize, location: usize, order: Ordering, latest: u64) {
+        if order == Ordering::SeqCst {
+            self.seq_cst_fence(thread);
+        }
+        let newest = self.stores(location, latest).len() - 1;
+        self.read(thread, location, newest, order);
+    }
+
+    pub fn fence(&mut self, thread: usize, order: Ordering) {
+        if is_acquire(order) {
+            let view = self.thread(thread);
+            let pending = std::mem::take(&mut view.fence_acquire);
+            view.known.join(&pending);
+        }
+        if is_release(order) {
+            let published = self.release(thread);
+            self.thread(thread).fence_release = Some(published);
+        }
+        if order == Ordering::SeqCst {
+            self.seq_cst_fence(thread);
+        }
+    }
+
+    /// Forget `location`, whose object was dropped or mutably borrowed.
+    pub fn forget(&mut self, location: usize) {
+        self.locations.remove(&location);
+    }
+
+    /// `thread` releases lock `object`.
+    pub fn unlock(&mut self, thread: usize, object: usize) {
+        let published = self.release(thread);
+        self.locks.entry(object).or_default().join(&published);
+    }
+
+    /// `thread` acquires lock `object`.
+    pub fn lock(&mut self, thread: usize, object: usize) {
+        let released = self.locks.get(&object).cloned().unwrap_or_default();
+        self.thread(thread).known.join(&released);
+    }
+
+    /// `parent` spawned `child`.
+    pub fn spawn(&mut self, parent: usize, child: usize) {
+        let mut published = self.release(parent);
+        published.clock.increment(child);
+        self.thread(child).known = published;
+    }
+
+    /// `parent` joined the finished `child`.
+    pub fn join(&mut self, parent: usize, child: usize) {
+        let known = self.thread(child).known.clone();
+        self.thread(parent).known.join(&known);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: usize = 0x10;
+    const FLAG: usize = 0x20;
+
+    /// Message passing: thread 0 writes data then sets a flag; thread 1
+    /// sees the flag. Returns every data value thread 1 can then read.
+    fn message_passing(store_order: Ordering, load_order: Ordering) -> Vec<u64> {
+        let mut seen = Vec::new();
+        for seed in 1..=50 {
+            let mut memory = WeakMemory::new(DeterministicRng::new(seed));
+            memory.store(0, DATA, Ordering::Relaxed, 0, 42);
+            memory.store(0, FLAG, store_order, 0, 1);
+
+            // Spin until the flag is seen
+            while memory.load(1, FLAG, load_order, 1) == 0 {}
+            let data = memory.load(1, DATA, Ordering::Relaxed, 42);
+            if !seen.contains(&data) {
+                seen.push(data);
+            }
+        }
+        seen.sort_unstable();
+        seen
+    }
+
+    #[test]
+    fn test_relaxed_message_passing_reads_stale_data() {
+        assert_eq!(
+            message_passing(Ordering::Relaxed, Ordering::Relaxed),
+            vec![0, 42]
+        );
+    }
+
+    #[test]
+    fn test_release_acquire_message_passing() {
+        assert_eq!(
+            message_passing(Ordering::Release, Ordering::Acquire),
+            vec![42]
+        );
+        assert_eq!(
+            message_passing(Ordering::SeqCst, Ordering::SeqCst),
+            vec![42]
+        );
+        // Release alone is not enough
+        assert_eq!(
+            message_passing(Ordering::Release, Ordering::Relaxed),
+            vec![0, 42]
+        );
+    }
+
+    #[test]
+    fn test_fences_synchronize_relaxed_accesses() {
+        for seed in 1..=50 {
+            let mut memory = WeakMemory::new(DeterministicRng::new(seed));
+            memory.store(0, DATA, Ordering::Relaxed, 0, 42);
+            memory.fence(0, Ordering::Release);
+            memory.store(0, FLAG, Ordering::Relaxed, 0, 1);
+
+            while memory.load(1, FLAG, Ordering::Relaxed, 1) == 0 {}
+            memory.fence(1, Ordering::Acquire);
+            assert_eq!(memory.load(1, DATA, Ordering::Relaxed, 42), 42);
+        }
+    }
+
+    #[test]
+    fn test_reads_are_coherent() {
+        for seed in 1..=50 {
+            let mut memory = WeakMemory::new(DeterministicRng::new(seed));
+            for value in 1..=5 {
+                memory.store(0, DATA, Ordering::Relaxed, value - 1, value);
+            }
+
+            // Another thread never reads backwards, and the writer never
+            // reads before its own last store
+            let mut last = 0;
+            for _ in 0..20 {
+                let value = memory.load(1, DATA, Ordering::Relaxed, 5);
+                assert!(value >= last, "read {} after {}", value, last);
+                last = value;
+            }
+            assert_eq!(memory.load(0, DATA, Ordering::Relaxed, 5), 5);
+        }
+    }
+
+    #[test]
+    fn test_release_sequence_continues_through_rmw() {
+        for seed in 1..=50 {
+            let mut memory = WeakMemory::new(DeterministicRng::new(seed));
+            memory.store(0, DATA, Ordering::Relaxed, 0, 42);
+            memory.store(0, FLAG, Ordering::Release, 0, 1);
+            // A relaxed increment by another thread keeps the release
+            memory.read_modify_write(2, FLAG, Ordering::Relaxed, 1, 2);
+
+            while memory.load(1, FLAG, Ordering::Acquire, 2) != 2 {}
+            assert_eq!(memory.load(1, DATA, Ordering::Relaxed, 42), 42);
+        }
+    }
+
+    #[test]
+    fn test_lock_handoff_synchronizes() {
+        const LOCK: usize = 0x30;
+        for seed in 1..=50 {
+            let mut memory = WeakMemory::new(DeterministicRng::new(seed));
+            memory.lock(0, LOCK);
+            memory.store(0, DATA, Ordering::Relaxed, 0, 42);
+            memory.unlock(0, LOCK);
+
+            memory.lock(1, LOCK);
+            assert_eq!(memory.load(1, DATA, Ordering::Relaxed, 42), 42);
+        }
+    }
+
+    #[test]
+    fn test_clocks_follow_synchronization() {
+        let mut memory = WeakMemory::sequential();
+        let epoch = memory.clock(0).get(0);
+        memory.store(0, DATA, Ordering::Relaxed, 0, 42);
+        memory.store(0, FLAG, Ordering::Release, 0, 1);
+        let later = memory.clock(0).get(0);
+        assert!(later > epoch);
+
+        // Acquiring the flag orders thread 0's accesses before it, but not
+        // the ones after the release
+        assert_eq!(memory.load(1, FLAG, Ordering::Acquire, 1), 1);
+        assert!(memory.clock(1).covers(0, epoch));
+        assert!(!memory.clock(1).covers(0, later));
+
+        assert_eq!(memory.load(2, FLAG, Ordering::Relaxed, 1), 1);
+        assert!(!memory.clock(2).covers(0, epoch));
+        memory.fence(2, Ordering::Acquire);
+        assert!(memory.clock(2).covers(0, epoch));
+        assert_eq!(memory.stale_loads_count(), 0);
+    }
+
+    #[test]
+    fn test_reused_address_starts_fresh() {
+        let mut memory = WeakMemory::new(DeterministicRng::new(1));
+        memory.store(0, DATA, Ordering::Relaxed, 0, 1);
+        memory.store(0, DATA, Ordering::Relaxed, 1, 2);
+
+        // A new object at the same address, with a value the model never saw
+        for _ in 0..20 {
+            assert_eq!(memory.load(1, DATA, Ordering::Relaxed, 7), 7);
+        }
+        assert_eq!(memory.stale_loads_count(), 0);
+    }
+}
@@ -108,7 +108,7 @@ pub fn score_load_balancing(
         0.0
     };
 
-    (imbalance_score + 300.0 - violation_penalty).max(0.0).min(1000.0)
+    (imbalance_score + 300.0 - violation_penalty).clamp(0.0, 1000.0)
 }
 
 // ---------------------------------------------------------------------------
@@ -262,7 +262,7 @@ mod tests {
 
         for (name, s) in [("txn", s1), ("tcp", s2), ("lb", s3), ("cloud", s4), ("cache", s5)] {
             assert!(
-                s >= 0.0 && s <= 1000.0,
+                (0.0..=1000.0).contains(&s),
                 "{} score {} out of bounds",
                 name,
                 s
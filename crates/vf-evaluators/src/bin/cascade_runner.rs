@@ -61,6 +61,12 @@ struct Cli {
     #[arg(long, default_value_t = 1000)]
     dst_iterations: u64,
 
+    /// vf-core checkout for trait specs that use its checkers
+    /// (default: the VF_CORE_PATH environment variable, else the checkout
+    /// this binary was built from).
+    #[arg(long)]
+    vf_core_path: Option<PathBuf>,
+
     /// Include throughput benchmark in output.
     ///
     /// Runs a quick benchmark after cascade passes and includes
@@ -145,7 +151,7 @@ async fn main() {
     };
 
     // Configure cascade
-    let config = CascadeConfig {
+    let mut config = CascadeConfig {
         max_level,
         fail_fast: true,
         timeout: Duration::from_secs(timeout_secs),
@@ -153,6 +159,9 @@ async fn main() {
         dst_iterations: cli.dst_iterations,
         ..CascadeConfig::default()
     };
+    if cli.vf_core_path.is_some() {
+        config.vf_core_path = cli.vf_core_path;
+    }
 
     let cascade = EvaluatorCascade::new(config);
 
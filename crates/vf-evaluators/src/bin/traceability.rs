@@ -0,0 +1,64 @@
+//! vf-traceability — spec-to-checker traceability and coverage report.
+//!
+//! Lists every invariant in the EVALUATOR MAPPING of each spec under
+//! `specs/` with the vf-core checker, stateright model, loom test, DST
+//! harness, Kani proof and Verus proof that cover it, and the claimed
+//! tools that nothing covers.
+//!
+//! # Usage
+//!
+//! ```bash
+//! vf-traceability                      # Markdown matrix
+//! vf-traceability --format json
+//! vf-traceability --fail-on-gaps       # exit 1 if any claim is unbacked
+//! ```
+
+use std::path::PathBuf;
+use std::process;
+
+use clap::Parser;
+
+use vf_evaluators::TraceabilityMatrix;
+
+/// Report which code verifies each TLA+ invariant.
+#[derive(Parser, Debug)]
+#[command(name = "vf-traceability")]
+#[command(about = "Spec-to-checker traceability matrix")]
+struct Cli {
+    /// Repository root (containing `specs/` and `crates/`).
+    #[arg(long, default_value = ".")]
+    root: PathBuf,
+
+    /// Output format: markdown or json.
+    #[arg(long, default_value = "markdown")]
+    format: String,
+
+    /// Exit with status 1 if a claimed tool has no evidence.
+    #[arg(long)]
+    fail_on_gaps: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let matrix = match TraceabilityMatrix::build(&cli.root) {
+        Ok(matrix) => matrix,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            process::exit(1);
+        }
+    };
+
+    match cli.format.to_lowercase().as_str() {
+        "markdown" | "md" => print!("{}", matrix.to_markdown()),
+        "json" => println!("{}", matrix.to_json()),
+        other => {
+            eprintln!("Error: unknown format '{other}' (expected markdown or json)");
+            process::exit(1);
+        }
+    }
+
+    if cli.fail_on_gaps && matrix.gaps_count() > 0 {
+        process::exit(1);
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Runs evaluators in order, stopping at the first failure.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use crate::level0_rustc;
@@ -87,6 +87,11 @@ pub struct CascadeConfig {
     pub dst_iterations: u64,
     /// Verus thread count
     pub verus_threads: usize,
+    /// vf-core checkout added to temp crates as a dev-dependency, so trait
+    /// specs can use its checkers behind the `checkers` feature
+    /// (default: the `VF_CORE_PATH` environment variable, else the
+    /// checkout this crate was built from, if it still exists)
+    pub vf_core_path: Option<PathBuf>,
 }
 
 impl Default for CascadeConfig {
@@ -101,10 +106,25 @@ impl Default for CascadeConfig {
             dst_seed: None,
             dst_iterations: 1000,
             verus_threads: num_cpus::get(),
+            vf_core_path: default_vf_core_path(
+                std::env::var_os("VF_CORE_PATH").map(PathBuf::from),
+            ),
         }
     }
 }
 
+/// `configured`, or else the vf-core next to this crate's source, if the
+/// binary runs where it was built.
+fn default_vf_core_path(configured: Option<PathBuf>) -> Option<PathBuf> {
+    configured.or_else(|| {
+        let checkout = Path::new(env!("CARGO_MANIFEST_DIR")).join("../vf-core");
+        checkout
+            .join("Cargo.toml")
+            .is_file()
+            .then(|| checkout.canonicalize().unwrap_or(checkout))
+    })
+}
+
 impl CascadeConfig {
     /// Fast config for quick iteration.
     pub fn fast() -> Self {
@@ -271,7 +291,7 @@ impl EvaluatorCascade {
         }
 
         // Write Cargo.toml
-        let cargo_toml = Self::cargo_toml_template();
+        let cargo_toml = self.cargo_toml();
         if let Err(e) = tokio::fs::write(temp_dir.join("Cargo.toml"), cargo_toml).await {
             return CascadeResult::from_results(vec![EvaluatorResult::fail(
                 "setup",
@@ -389,7 +409,7 @@ impl EvaluatorCascade {
         }
 
         // Write Cargo.toml
-        let cargo_toml = Self::cargo_toml_template();
+        let cargo_toml = self.cargo_toml();
 
         if let Err(e) = tokio::fs::write(temp_dir.join("Cargo.toml"), cargo_toml).await {
             return CascadeResult::from_results(vec![EvaluatorResult::fail(
@@ -440,11 +460,22 @@ crossbeam-epoch = "0.9"
 [dev-dependencies]
 loom = "0.7"
 proptest = "1.4"
-
-[features]
-default = []
 "#
     }
+
+    /// Cargo.toml for temp crates, with `vf-core` and the `checkers`
+    /// feature when `vf_core_path` is set.
+    fn cargo_toml(&self) -> String {
+        let mut cargo_toml = Self::cargo_toml_template().to_string();
+        match &self.config.vf_core_path {
+            Some(path) => cargo_toml.push_str(&format!(
+                "vf-core = {{ path = {:?} }}\n\n[features]\ndefault = [\"checkers\"]\ncheckers = []\n",
+                path
+            )),
+            None => cargo_toml.push_str("\n[features]\ndefault = []\n"),
+        }
+        cargo_toml
+    }
 }
 
 /// Strip any existing #[cfg(test)] mod tests { ... } block from generated code.
@@ -582,4 +613,42 @@ pub struct Bar {
         let stripped = strip_tests_module(code);
         assert!(stripped.contains("pub struct Bar"));
     }
+
+    #[test]
+    fn test_cargo_toml_without_vf_core() {
+        let cascade = EvaluatorCascade::new(CascadeConfig {
+            vf_core_path: None,
+            ..CascadeConfig::default()
+        });
+        let cargo_toml = cascade.cargo_toml();
+
+        assert!(!cargo_toml.contains("vf-core"));
+        assert!(cargo_toml.ends_with("[features]\ndefault = []\n"));
+    }
+
+    #[test]
+    fn test_default_vf_core_path() {
+        let configured = PathBuf::from("/opt/vf/crates/vf-core");
+        assert_eq!(
+            default_vf_core_path(Some(configured.clone())),
+            Some(configured)
+        );
+
+        // Falls back to this checkout's vf-core
+        let checkout = default_vf_core_path(None).unwrap();
+        assert!(checkout.ends_with("vf-core"), "{}", checkout.display());
+        assert!(checkout.join("src/invariants/elle.rs").is_file());
+    }
+
+    #[test]
+    fn test_cargo_toml_with_vf_core() {
+        let cascade = EvaluatorCascade::new(CascadeConfig {
+            vf_core_path: Some(PathBuf::from("/opt/vf/crates/vf-core")),
+            ..CascadeConfig::default()
+        });
+        let cargo_toml = cascade.cargo_toml();
+
+        assert!(cargo_toml.contains("vf-core = { path = \"/opt/vf/crates/vf-core\" }"));
+        assert!(cargo_toml.contains("default = [\"checkers\"]"));
+    }
 }
@@ -306,12 +306,10 @@ fn extract_dst_error(stderr: &str, stdout: &str) -> (String, Option<Counterexamp
         }
 
         // Look for panic message
-        if line.contains("panicked at") {
+        let is_panic = line.contains("panicked at");
+        let is_assertion = line.contains("assertion failed") || line.contains("Invariant violated");
+        if is_panic || (is_assertion && error.is_empty()) {
             error = line.to_string();
-        } else if line.contains("assertion failed") || line.contains("Invariant violated") {
-            if error.is_empty() {
-                error = line.to_string();
-            }
         }
     }
 
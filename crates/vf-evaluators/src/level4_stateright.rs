@@ -304,10 +304,9 @@ fn extract_stateright_error(output: &str) -> (String, Option<Counterexample>) {
         }
 
         // Capture assertion failures
-        if line.contains("assertion failed") || line.contains("panicked at") {
-            if error.is_empty() {
-                error = line.to_string();
-            }
+        let is_failure = line.contains("assertion failed") || line.contains("panicked at");
+        if is_failure && error.is_empty() {
+            error = line.to_string();
         }
     }
 
@@ -220,10 +220,10 @@ fn extract_kani_error(output: &str) -> (String, Option<Counterexample>) {
         let trimmed = line.trim();
 
         // Look for verification failure
-        if trimmed.contains("VERIFICATION:- FAILED") || trimmed.contains("FAILED") && trimmed.contains("harness") {
-            if error.is_empty() {
-                error = trimmed.to_string();
-            }
+        let is_failure = trimmed.contains("VERIFICATION:- FAILED")
+            || trimmed.contains("FAILED") && trimmed.contains("harness");
+        if is_failure && error.is_empty() {
+            error = trimmed.to_string();
         }
 
         // Look for failed property
@@ -246,10 +246,8 @@ fn extract_kani_error(output: &str) -> (String, Option<Counterexample>) {
         }
 
         // Capture assertion failures
-        if trimmed.contains("assertion") && trimmed.contains("FAILURE") {
-            if error.is_empty() {
-                error = trimmed.to_string();
-            }
+        if trimmed.contains("assertion") && trimmed.contains("FAILURE") && error.is_empty() {
+            error = trimmed.to_string();
         }
     }
 
@@ -257,6 +257,7 @@ fn extract_counterexample(output: &str) -> Option<vf_core::Counterexample> {
             memory_issues: Vec::new(),
             dst_seed: None,
             description: None,
+            loop_to_step: None,
         })
     }
 }
@@ -281,14 +282,14 @@ pub ghost struct GhostStack {
     pub contents: Seq<u64>,
 }
 
-// Invariant: NoLostElements (TLA+ Line 45)
+// Invariant: NoLostElements
 // Every pushed element is either in the stack or was popped
 pub open spec fn no_lost_elements(gs: GhostStack) -> bool {
     forall|e: u64| gs.pushed.contains(e) ==>
         gs.contents.contains(e) || gs.popped.contains(e)
 }
 
-// Invariant: NoDuplicates (TLA+ Line 58)
+// Invariant: NoDuplicates
 // No element appears twice in the stack
 pub open spec fn no_duplicates(gs: GhostStack) -> bool {
     forall|i: int, j: int|
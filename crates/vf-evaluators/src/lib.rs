@@ -26,6 +26,7 @@ pub mod level4_stateright;
 pub mod level5_kani;
 pub mod level6_verus;
 pub mod result;
+pub mod traceability;
 
 pub use cascade::{CascadeConfig, EvaluatorCascade, EvaluatorLevel};
 pub use level3_dst::{DstConfig, InlineResult as DstInlineResult};
@@ -33,3 +34,4 @@ pub use level4_stateright::{InlineResult as StaterightInlineResult, StaterightCo
 pub use level5_kani::{KaniConfig, PROOF_HARNESS_TEMPLATE};
 pub use level6_verus::{VerusConfig, generate_stack_proof_template as verus_stack_proof_template};
 pub use result::{CascadeResult, EvaluatorResult};
+pub use traceability::{InvariantTrace, TraceabilityMatrix};
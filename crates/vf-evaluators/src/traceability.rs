@@ -0,0 +1,764 @@
+//! Spec-to-checker traceability matrix.
+//!
+//! Reads every spec under `specs/`, lists each invariant of its EVALUATOR
+//! MAPPING, and cross-references the harnesses that verify it:
+//!
+//! | Column | Evidence |
+//! |--------|----------|
+//! | checker | `vf_core::registry::BUILTIN_CHECKERS` declares a checker for it |
+//! | stateright | a `Property` of a stateright model |
+//! | loom | a `#[test]` that runs `loom::model` |
+//! | dst | a `#[test]` that drives a vf-dst runner such as `DstEnv` |
+//! | kani | a `#[kani::proof]` harness |
+//! | verus | a `proof fn` in a `verus!` block |
+//!
+//! A harness covers an invariant when it calls the registry checker for
+//! it, directly or through a function in the same file, or when a
+//! `// covers: <checker>::<invariant>` annotation sits right above it
+//! (`<module>::<invariant>` for invariants no checker reports). Other
+//! comments and string literals are ignored, so a doc comment or code
+//! template that names a tool or an invariant is not evidence. A gap is
+//! a tool the mapping claims with no such harness, e.g. epoch_gc's
+//! `BoundedMemory -> kani` when no Kani harness exists.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use vf_core::registry::{CheckerRegistry, BUILTIN_CHECKERS};
+use vf_core::{ParseError, TlaSpec};
+
+/// Verification tools cross-referenced by the matrix.
+pub const TOOLS: &[&str] = &["stateright", "loom", "dst", "kani", "verus"];
+
+/// Maximum directory depth scanned for sources and specs.
+const SCAN_DEPTH_MAX: u32 = 8;
+
+/// Annotation tying the harness below it to an invariant.
+const COVERS_MARKER: &str = "covers:";
+
+/// vf-dst entry points that make a `#[test]` a DST harness.
+const DST_RUNNERS: &[&str] = &[
+    "ControlledRuntime",
+    "DstEnv",
+    "DstHarness",
+    "DstRunner",
+    "SsiDstRunner",
+    "replay_controlled",
+    "replay_dst",
+    "replay_ssi",
+    "replay_ssi_oracle",
+    "run_all_ssi_oracles",
+    "run_dst_scenario",
+    "run_dst_scenario_with_config",
+    "run_ssi_protocol",
+    "run_ssi_scenario",
+];
+
+/// Stateright property constructors.
+const STATERIGHT_PROPERTIES: &[&str] = &[
+    "Property::always(",
+    "Property::eventually(",
+    "Property::sometimes(",
+];
+
+/// One invariant and everything that verifies it.
+#[derive(Debug, Clone, Serialize)]
+pub struct InvariantTrace {
+    /// TLA+ module name
+    pub module: String,
+    /// Spec path relative to the repository root
+    pub spec: String,
+    /// Invariant name
+    pub invariant: String,
+    /// Line in the spec
+    pub line: u32,
+    /// Tools the EVALUATOR MAPPING claims check it
+    pub claimed: Vec<String>,
+    /// vf-core checker covering it
+    pub checker: Option<String>,
+    /// Tool -> source files that check it
+    pub evidence: BTreeMap<String, Vec<String>>,
+    /// Claimed tools with no evidence
+    pub gaps: Vec<String>,
+}
+
+/// Traceability matrix for every spec in a repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceabilityMatrix {
+    pub invariants: Vec<InvariantTrace>,
+}
+
+impl TraceabilityMatrix {
+    /// Build the matrix for the repository at `root`.
+    pub fn build(root: &Path) -> Result<Self, ParseError> {
+        debug_assert!(root.is_dir(), "Repository root must be a directory");
+
+        let registry = CheckerRegistry::builtin();
+        let sources: Vec<(String, Vec<Harness>)> = files_with_extension(root, "crates", "rs")
+            .into_iter()
+            .filter_map(|path| {
+                let content = std::fs::read_to_string(&path).ok()?;
+                Some((relative(root, &path), harnesses(&content)))
+            })
+            .filter(|(_, harnesses)| !harnesses.is_empty())
+            .collect();
+
+        let mut invariants = Vec::new();
+        for path in files_with_extension(root, "specs", "tla") {
+            let spec = TlaSpec::from_file(&path)?;
+            let spec_path = relative(root, &path);
+
+            for inv in spec.invariants.iter().filter(|i| !i.evaluators.is_empty()) {
+                // "kani (with epoch GC)" claims kani
+                let claimed: Vec<String> = inv
+                    .evaluators
+                    .iter()
+                    .filter_map(|e| e.split_whitespace().next())
+                    .map(str::to_string)
+                    .collect();
+
+                let mut evidence = BTreeMap::new();
+                for tool in TOOLS {
+                    let files: Vec<String> = sources
+                        .iter()
+                        .filter(|(_, harnesses)| {
+                            harnesses
+                                .iter()
+                                .any(|h| h.tool == *tool && h.covers(&spec.name, &inv.name))
+                        })
+                        .map(|(file, _)| file.clone())
+                        .collect();
+                    if !files.is_empty() {
+                        evidence.insert(tool.to_string(), files);
+                    }
+                }
+                let gaps = claimed
+                    .iter()
+                    .filter(|tool| !evidence.contains_key(*tool))
+                    .cloned()
+                    .collect();
+
+                invariants.push(InvariantTrace {
+                    module: spec.name.clone(),
+                    spec: spec_path.clone(),
+                    invariant: inv.name.clone(),
+                    line: inv.line,
+                    claimed,
+                    checker: registry
+                        .checker_for(&spec.name, &inv.name)
+                        .map(|c| c.checker.to_string()),
+                    evidence,
+                    gaps,
+                });
+            }
+        }
+
+        Ok(Self { invariants })
+    }
+
+    /// Number of claimed tool checks with no evidence.
+    #[must_use]
+    pub fn gaps_count(&self) -> usize {
+        self.invariants.iter().map(|i| i.gaps.len()).sum()
+    }
+
+    /// Render as a Markdown table.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("| Spec | Invariant | Line | Claimed | Checker |");
+        for tool in TOOLS {
+            let _ = write!(out, " {} |", tool);
+        }
+        out.push_str(" Gaps |\n|------|-----------|------|---------|---------|");
+        for _ in TOOLS {
+            out.push_str("------|");
+        }
+        out.push_str("------|\n");
+
+        for inv in &self.invariants {
+            let _ = write!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                inv.module,
+                inv.invariant,
+                inv.line,
+                inv.claimed.join(", "),
+                inv.checker.as_deref().unwrap_or("-")
+            );
+            for tool in TOOLS {
+                let cell = match inv.evidence.get(*tool) {
+                    Some(_) => "yes",
+                    None if inv.claimed.iter().any(|c| c == tool) => "**missing**",
+                    None => "-",
+                };
+                let _ = write!(out, " {} |", cell);
+            }
+            let _ = writeln!(out, " {} |", inv.gaps.join(", "));
+        }
+
+        let _ = write!(
+            out,
+            "\n{} invariants, {} gaps\n",
+            self.invariants.len(),
+            self.gaps_count()
+        );
+        out
+    }
+
+    /// Render as pretty-printed JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("matrix serializes")
+    }
+}
+
+/// A verification harness found in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Harness {
+    /// Tool that runs it, one of [`TOOLS`]
+    tool: &'static str,
+    /// Invariants it covers, as (module, invariant)
+    covers: Vec<(String, String)>,
+}
+
+impl Harness {
+    fn covers(&self, module: &str, invariant: &str) -> bool {
+        self.covers.iter().any(|(m, i)| m == module && i == invariant)
+    }
+}
+
+/// A harness before its annotations are attached.
+struct Item {
+    tool: &'static str,
+    /// Offset of its first attribute, or of the item itself
+    start: usize,
+    covers: Vec<(String, String)>,
+}
+
+/// Every harness in a Rust source file.
+fn harnesses(source: &str) -> Vec<Harness> {
+    let stripped = strip(source);
+    let code = stripped.code.as_str();
+    let fns = fn_items(code);
+    let verus_blocks = macro_bodies(code, "verus!");
+
+    let mut items = Vec::new();
+    for item in &fns {
+        // Follow calls into functions of the same file, one level deep
+        let body = &code[item.body.clone()];
+        let mut reached = body.to_string();
+        for helper in fns.iter().filter(|f| f.name != item.name && names(body, &f.name)) {
+            reached.push_str(&code[helper.body.clone()]);
+        }
+
+        let attributes = attributes_before(code, item.start);
+        let tool = if attributes.iter().any(|a| a == "#[kani::proof]") {
+            Some("kani")
+        } else if verus_blocks.iter().any(|b| b.contains(&item.start))
+            && code[..item.start].trim_end().ends_with("proof")
+        {
+            Some("verus")
+        } else if attributes.iter().any(|a| a == "#[test]" || a.starts_with("#[tokio::test")) {
+            test_tool(&reached)
+        } else {
+            None
+        };
+        let Some(tool) = tool else {
+            continue;
+        };
+
+        let covers = BUILTIN_CHECKERS
+            .iter()
+            .filter(|c| names(&reached, c.checker))
+            .flat_map(|c| c.invariants.iter().map(|i| (c.module.to_string(), i.to_string())))
+            .collect();
+        items.push(Item {
+            tool,
+            start: attributes_start(code, item.start).unwrap_or(item.start),
+            covers,
+        });
+    }
+    for pattern in STATERIGHT_PROPERTIES {
+        for (offset, _) in code.match_indices(pattern) {
+            let line_start = code[..offset].rfind('\n').map_or(0, |n| n + 1);
+            let indent = code[line_start..].len() - code[line_start..].trim_start().len();
+            items.push(Item {
+                tool: "stateright",
+                start: line_start + indent,
+                covers: Vec::new(),
+            });
+        }
+    }
+
+    // Each annotation belongs to the next harness, with only attributes
+    // in between
+    for (offset, target) in &stripped.covers {
+        let next = items
+            .iter_mut()
+            .filter(|item| item.start >= *offset)
+            .min_by_key(|item| item.start);
+        if let Some(item) = next {
+            if only_attributes(&code[*offset..item.start]) {
+                item.covers.push(resolve(target));
+            }
+        }
+    }
+
+    items
+        .into_iter()
+        .filter(|item| !item.covers.is_empty())
+        .map(|item| Harness {
+            tool: item.tool,
+            covers: item.covers,
+        })
+        .collect()
+}
+
+/// Tool a `#[test]` exercises, from the code it runs.
+fn test_tool(code: &str) -> Option<&'static str> {
+    if code.contains("loom::model") {
+        Some("loom")
+    } else if code.contains(".checker()") {
+        Some("stateright")
+    } else if DST_RUNNERS.iter().any(|runner| names(code, runner)) {
+        Some("dst")
+    } else {
+        None
+    }
+}
+
+/// Resolve a `<checker>::<invariant>` or `<module>::<invariant>` target.
+fn resolve(target: &str) -> (String, String) {
+    let (scope, invariant) = target.rsplit_once("::").unwrap_or(("", target));
+    let module = BUILTIN_CHECKERS
+        .iter()
+        .find(|c| c.checker == scope)
+        .map_or(scope, |c| c.module);
+    (module.to_string(), invariant.to_string())
+}
+
+/// Whether `content` names `invariant` as a whole word.
+fn names(content: &str, invariant: &str) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    content.match_indices(invariant).any(|(i, _)| {
+        let before = content[..i].chars().next_back();
+        let after = content[i + invariant.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+/// Source with comments and the contents of literals blanked out.
+struct Stripped {
+    /// Code only, with every newline and code offset of the source line kept
+    code: String,
+    /// `// covers:` annotations, as (offset in `code`, target)
+    covers: Vec<(usize, String)>,
+}
+
+fn strip(source: &str) -> Stripped {
+    let chars: Vec<char> = source.chars().collect();
+    let mut code = String::with_capacity(source.len());
+    let mut covers = Vec::new();
+    let blank = |code: &mut String, c: char| code.push(if c == '\n' { '\n' } else { ' ' });
+    let is_word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '/' && next == Some('/') {
+            let end = chars[i..].iter().position(|&c| c == '\n').map_or(chars.len(), |n| i + n);
+            let text: String = chars[i + 2..end].iter().collect();
+            let text = text.trim_start_matches(['/', '!']).trim();
+            if let Some(target) = text.strip_prefix(COVERS_MARKER) {
+                covers.push((code.len(), target.trim().to_string()));
+            }
+            chars[i..end].iter().for_each(|&c| blank(&mut code, c));
+            i = end;
+        } else if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    code.push_str("  ");
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    code.push_str("  ");
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    blank(&mut code, chars[i]);
+                    i += 1;
+                }
+            }
+        } else if c == 'r' && !is_word(i.checked_sub(1).and_then(|p| chars.get(p))) && {
+            let hashes = chars[i + 1..].iter().take_while(|&&c| c == '#').count();
+            chars.get(i + 1 + hashes) == Some(&'"')
+        } {
+            // Raw string: r"..." or r#"..."#
+            let hashes = chars[i + 1..].iter().take_while(|&&c| c == '#').count();
+            let open = i + 2 + hashes;
+            chars[i..open].iter().for_each(|&c| code.push(c));
+            let mut j = open;
+            while j < chars.len() {
+                if chars[j] == '"' && chars[j + 1..].iter().take(hashes).filter(|&&c| c == '#').count() == hashes {
+                    break;
+                }
+                blank(&mut code, chars[j]);
+                j += 1;
+            }
+            let close = (j + 1 + hashes).min(chars.len());
+            chars[j.min(chars.len())..close].iter().for_each(|&c| code.push(c));
+            i = close;
+        } else if c == '"' {
+            code.push('"');
+            let mut j = i + 1;
+            while j < chars.len() && chars[j] != '"' {
+                if chars[j] == '\\' {
+                    blank(&mut code, chars[j]);
+                    j += 1;
+                }
+                if let Some(&c) = chars.get(j) {
+                    blank(&mut code, c);
+                }
+                j += 1;
+            }
+            if j < chars.len() {
+                code.push('"');
+            }
+            i = j + 1;
+        } else if c == '\'' && (next == Some('\\') || chars.get(i + 2) == Some(&'\'')) {
+            // Char literal; a lifetime has no closing quote
+            let end = if next == Some('\\') {
+                chars[i + 3..].iter().position(|&c| c == '\'').map_or(chars.len() - 1, |n| i + 3 + n)
+            } else {
+                i + 2
+            };
+            code.push('\'');
+            chars[i + 1..end].iter().for_each(|&c| blank(&mut code, c));
+            code.push('\'');
+            i = end + 1;
+        } else {
+            code.push(c);
+            i += 1;
+        }
+    }
+
+    Stripped { code, covers }
+}
+
+/// A function with a body.
+struct FnItem {
+    name: String,
+    /// Offset of the `fn` keyword
+    start: usize,
+    /// Offsets of the body, braces included
+    body: Range<usize>,
+}
+
+fn fn_items(code: &str) -> Vec<FnItem> {
+    let mut items = Vec::new();
+    for (start, _) in code.match_indices("fn ") {
+        if code[..start].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            continue;
+        }
+        let rest = code[start + 3..].trim_start();
+        let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        if name.is_empty() {
+            continue;
+        }
+        let Some(open) = code[start..].find(['{', ';']).map(|n| start + n) else {
+            continue;
+        };
+        if code[open..].starts_with(';') {
+            continue;
+        }
+        if let Some(close) = matching(code, open, '{', '}') {
+            items.push(FnItem {
+                name,
+                start,
+                body: open..close + 1,
+            });
+        }
+    }
+    items
+}
+
+/// Bodies of invocations of a block macro such as `verus!`.
+fn macro_bodies(code: &str, name: &str) -> Vec<Range<usize>> {
+    code.match_indices(name)
+        .filter_map(|(offset, _)| {
+            let after = offset + name.len();
+            let open = after + (code[after..].len() - code[after..].trim_start().len());
+            if !code[open..].starts_with('{') {
+                return None;
+            }
+            matching(code, open, '{', '}').map(|close| open..close + 1)
+        })
+        .collect()
+}
+
+/// Offset of the bracket closing the one at `open`.
+fn matching(code: &str, open: usize, left: char, right: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (offset, c) in code[open..].char_indices() {
+        if c == left {
+            depth += 1;
+        } else if c == right {
+            depth -= 1;
+            if depth == 0 {
+                return Some(open + offset);
+            }
+        }
+    }
+    None
+}
+
+/// Attributes on the item whose keyword is at `start`, whitespace removed.
+fn attributes_before(code: &str, start: usize) -> Vec<String> {
+    let mut attributes = Vec::new();
+    let mut end = qualifiers_start(code, start);
+    while let Some(open) = attribute_open(code, end) {
+        let text: String = code[open..end].chars().filter(|c| !c.is_whitespace()).collect();
+        attributes.push(text);
+        end = open;
+    }
+    attributes
+}
+
+/// Offset of the first attribute on the item whose keyword is at `start`.
+fn attributes_start(code: &str, start: usize) -> Option<usize> {
+    let mut end = qualifiers_start(code, start);
+    let mut first = None;
+    while let Some(open) = attribute_open(code, end) {
+        first = Some(open);
+        end = open;
+    }
+    first
+}
+
+/// Start of `pub`, `async` and similar words before an item keyword.
+fn qualifiers_start(code: &str, start: usize) -> usize {
+    let mut end = start;
+    loop {
+        let before = code[..end].trim_end();
+        let word_start = before
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(0, |n| n + 1);
+        match &before[word_start..] {
+            "pub" | "async" | "unsafe" | "const" | "proof" => end = word_start,
+            _ => return end,
+        }
+    }
+}
+
+/// Offset of the `#` of an attribute that ends right before `end`.
+fn attribute_open(code: &str, end: usize) -> Option<usize> {
+    let before = code[..end].trim_end();
+    if !before.ends_with(']') {
+        return None;
+    }
+    let mut depth = 0usize;
+    for (offset, c) in before.char_indices().rev() {
+        match c {
+            ']' => depth += 1,
+            '[' => {
+                depth -= 1;
+                if depth == 0 {
+                    return before[..offset].ends_with('#').then(|| offset - 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether `code` is only whitespace and attributes.
+fn only_attributes(code: &str) -> bool {
+    let mut rest = code.trim_start();
+    while rest.starts_with("#[") {
+        match matching(rest, 1, '[', ']') {
+            Some(close) => rest = rest[close + 1..].trim_start(),
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Files under `root/dir` with the given extension, sorted.
+fn files_with_extension(root: &Path, dir: &str, extension: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    collect(&root.join(dir), extension, 0, &mut found);
+    found.sort();
+    found
+}
+
+fn collect(dir: &Path, extension: &str, depth: u32, found: &mut Vec<PathBuf>) {
+    if depth > SCAN_DEPTH_MAX {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_dir() {
+            if path.file_name().is_some_and(|n| n != "target") {
+                collect(&path, extension, depth + 1, found);
+            }
+        } else if path.extension().is_some_and(|e| e == extension) {
+            found.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> TraceabilityMatrix {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        TraceabilityMatrix::build(&root.canonicalize().unwrap()).unwrap()
+    }
+
+    fn row<'m>(matrix: &'m TraceabilityMatrix, module: &str, name: &str) -> &'m InvariantTrace {
+        matrix
+            .invariants
+            .iter()
+            .find(|i| i.module == module && i.invariant == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_claimed_kani_without_harness_is_a_gap() {
+        let matrix = matrix();
+        let bounded = row(&matrix, "epoch_gc", "BoundedMemory");
+
+        assert_eq!(bounded.spec, "specs/lockfree/epoch_gc.tla");
+        assert_eq!(bounded.claimed, vec!["kani", "dst"]);
+        assert_eq!(bounded.checker.as_deref(), Some("EpochGcPropertyChecker"));
+        assert!(bounded.gaps.contains(&"kani".to_string()));
+    }
+
+    #[test]
+    fn test_evidence_found_for_treiber_stack() {
+        let matrix = matrix();
+        let no_lost = row(&matrix, "treiber_stack", "NoLostElements");
+
+        assert_eq!(no_lost.checker.as_deref(), Some("StackPropertyChecker"));
+        assert_eq!(
+            no_lost.evidence["stateright"],
+            vec!["crates/vf-stateright/src/treiber_stack.rs"]
+        );
+        assert!(no_lost.evidence.contains_key("dst"));
+        assert!(no_lost.gaps.is_empty(), "{:?}", no_lost.gaps);
+
+        // Mentioned only in the Verus proof template string
+        assert!(!no_lost.evidence.contains_key("verus"));
+
+        // Claimed as "kani (with epoch GC)"
+        let aba = row(&matrix, "treiber_stack", "ABA_Safety");
+        assert_eq!(aba.claimed, vec!["loom", "kani"]);
+    }
+
+    #[test]
+    fn test_mentions_in_comments_and_strings_are_not_evidence() {
+        let source = r##"
+            //! The loom tests check NoLostElements with StackPropertyChecker.
+            // #[kani::proof]
+            // fn proof_lost() { StackPropertyChecker::new(); }
+
+            const TEMPLATE: &str = r#"
+                verus! { proof fn lost() { StackPropertyChecker::check(); } }
+            "#;
+
+            /* #[test] fn nested() { /* loom::model */ StackPropertyChecker } */
+            #[test]
+            fn test_template() {
+                assert!(TEMPLATE.contains("loom::model(StackPropertyChecker)"));
+                let quote = '"';
+                assert_eq!(quote, '"');
+            }
+        "##;
+        assert_eq!(harnesses(source), vec![]);
+    }
+
+    #[test]
+    fn test_harness_items_are_evidence() {
+        let source = r#"
+            /// Pushed values are kept.
+            // covers: StackPropertyChecker::NoLostElements
+            #[kani::proof]
+            #[kani::unwind(5)]
+            fn proof_push() {}
+
+            #[kani::proof]
+            fn proof_unannotated() {}
+
+            fn run(seed: u64) {
+                let env = DstEnv::new(seed);
+                StackPropertyChecker::new(&env).check_all();
+            }
+
+            #[test]
+            fn test_dst() {
+                run(1);
+            }
+
+            fn properties() {
+                vec![
+                    // covers: ms_queue::TailReachable
+                    Property::always("TailReachable", |_, s: &State| s.tail_reachable()),
+                ]
+            }
+        "#;
+        let found = harnesses(source);
+        let covers = |tool: &str| -> Vec<String> {
+            found
+                .iter()
+                .filter(|h| h.tool == tool)
+                .flat_map(|h| h.covers.iter().map(|(m, i)| format!("{m}::{i}")))
+                .collect()
+        };
+
+        assert_eq!(covers("kani"), vec!["treiber_stack::NoLostElements"]);
+        assert_eq!(covers("dst").len(), 5);
+        assert!(covers("dst").contains(&"treiber_stack::ABA_Safety".to_string()));
+        assert_eq!(covers("stateright"), vec!["ms_queue::TailReachable"]);
+    }
+
+    #[test]
+    fn test_renders_markdown_and_json() {
+        let matrix = matrix();
+        let markdown = matrix.to_markdown();
+        assert!(markdown.starts_with("| Spec | Invariant | Line |"));
+        assert!(markdown.contains("| epoch_gc | BoundedMemory |"));
+        assert!(markdown.contains("**missing**"));
+
+        let json: serde_json::Value = serde_json::from_str(&matrix.to_json()).unwrap();
+        assert_eq!(
+            json["invariants"].as_array().unwrap().len(),
+            matrix.invariants.len()
+        );
+    }
+
+    #[test]
+    fn test_names_whole_words() {
+        assert!(names("checks NoDuplicates.", "NoDuplicates"));
+        assert!(!names("NoDuplicatesEver", "NoDuplicates"));
+        assert!(!names("check_NoLost", "NoLost"));
+    }
+}
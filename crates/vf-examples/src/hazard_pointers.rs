@@ -0,0 +1,539 @@
+//! Hazard Pointers - Lock-free memory reclamation.
+//!
+//! # TLA+ Specification
+//!
+//! This implementation corresponds to `specs/lockfree/hazard_pointers.tla`.
+//!
+//! # Invariants
+//!
+//! | Property | TLA+ Line | Verified By |
+//! |----------|-----------|-------------|
+//! | NoReclaimWhileProtected | 66 | DST (TrackedDomain) |
+//! | ProtectedIsPublished | 73 | structural (`protect` publishes before validating) |
+//! | RetiredUnreachable | 81 | DST (callers retire only unlinked nodes) |
+//! | BoundedGarbage | 90 | DST, `retired_count` |
+//!
+//! # Memory Safety
+//!
+//! Built on std atomics and raw pointers only, with no crossbeam-epoch,
+//! so the domain and `TreiberStack<u64, HazardDomain>` can run under Miri:
+//!
+//! ```bash
+//! cargo +nightly miri test -p vf-examples hazard
+//! ```
+//!
+//! # Protocol
+//!
+//! A reader stores the pointer it is about to dereference in its hazard
+//! slot and re-reads the source; if the source still holds the pointer,
+//! the node was reachable after the hazard became visible, so no scan
+//! that starts later can free it. Writers retire nodes only after
+//! unlinking them. Once `scan_threshold` nodes are retired, a scan frees
+//! every retired node that no slot holds and keeps the rest.
+
+use std::collections::{HashMap, HashSet};
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use vf_core::invariants::hazard_pointers::HazardPointerProperties;
+
+/// Maximum hazard slots per domain (TigerStyle: explicit limit).
+pub const HAZARD_SLOTS_MAX: usize = 64;
+
+/// Default retired count that triggers a scan.
+///
+/// Twice the slot count, so every scan frees at least half of the list.
+pub const SCAN_THRESHOLD_DEFAULT: u64 = 2 * HAZARD_SLOTS_MAX as u64;
+
+/// One hazard slot, owned by at most one `HazardPointer` at a time.
+struct HazardSlot {
+    active: AtomicBool,
+    protected: AtomicPtr<()>,
+}
+
+/// A retired node and how to free it.
+struct Retired {
+    ptr: *mut (),
+    reclaim: unsafe fn(*mut ()),
+    next: *mut Retired,
+}
+
+/// A hazard pointer domain: a fixed set of hazard slots and a shared
+/// lock-free list of retired nodes.
+pub struct HazardDomain {
+    slots: Box<[HazardSlot]>,
+    /// Treiber list of retired nodes
+    retired: AtomicPtr<Retired>,
+    retired_count: AtomicU64,
+    reclaimed_count: AtomicU64,
+    scan_threshold: u64,
+}
+
+impl HazardDomain {
+    /// Create a domain with `SCAN_THRESHOLD_DEFAULT`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_scan_threshold(SCAN_THRESHOLD_DEFAULT)
+    }
+
+    /// Create a domain that scans once `threshold` nodes are retired.
+    ///
+    /// A threshold at or below the number of slots in use may scan
+    /// without freeing anything.
+    #[must_use]
+    pub fn with_scan_threshold(threshold: u64) -> Self {
+        assert!(threshold > 0, "Scan threshold must be positive");
+
+        let slots = (0..HAZARD_SLOTS_MAX)
+            .map(|_| HazardSlot {
+                active: AtomicBool::new(false),
+                protected: AtomicPtr::new(ptr::null_mut()),
+            })
+            .collect();
+        Self {
+            slots,
+            retired: AtomicPtr::new(ptr::null_mut()),
+            retired_count: AtomicU64::new(0),
+            reclaimed_count: AtomicU64::new(0),
+            scan_threshold: threshold,
+        }
+    }
+
+    /// Claim a free hazard slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all `HAZARD_SLOTS_MAX` slots are in use.
+    #[must_use]
+    pub fn acquire(&self) -> HazardPointer<'_> {
+        for slot in self.slots.iter() {
+            if !slot.active.load(Ordering::Relaxed)
+                && slot
+                    .active
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return HazardPointer { slot };
+            }
+        }
+        panic!("All {} hazard slots in use", HAZARD_SLOTS_MAX);
+    }
+
+    /// Retire `ptr`, freeing it once no hazard slot protects it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from `Box::into_raw`, must already be unlinked from
+    /// every shared location (so no new protection can validate it), must
+    /// not be retired twice, and must be safe to drop on any thread.
+    pub unsafe fn retire<T>(&self, ptr: *mut T) {
+        debug_assert!(!ptr.is_null(), "Cannot retire null");
+
+        unsafe fn reclaim<T>(ptr: *mut ()) {
+            drop(Box::from_raw(ptr.cast::<T>()));
+        }
+
+        let node = Box::into_raw(Box::new(Retired {
+            ptr: ptr.cast(),
+            reclaim: reclaim::<T>,
+            next: ptr::null_mut(),
+        }));
+        // Count before publishing, so a scan never decrements below zero
+        let count = self.retired_count.fetch_add(1, Ordering::Relaxed) + 1;
+        self.push_retired(node, node);
+
+        if count >= self.scan_threshold {
+            self.scan();
+        }
+    }
+
+    /// Free every retired node that no hazard slot protects.
+    pub fn scan(&self) {
+        // Take the whole list, so concurrent scans never share a node
+        let mut node = self.retired.swap(ptr::null_mut(), Ordering::Acquire);
+        if node.is_null() {
+            return;
+        }
+
+        // Every node here was unlinked before it was retired. Ordered
+        // after the unlink, this fence makes any hazard published before
+        // a successful validation visible to the loads below.
+        fence(Ordering::SeqCst);
+        let protected: Vec<*mut ()> = self
+            .slots
+            .iter()
+            .map(|slot| slot.protected.load(Ordering::SeqCst))
+            .filter(|p| !p.is_null())
+            .collect();
+
+        let mut kept_first: *mut Retired = ptr::null_mut();
+        let mut kept_last: *mut Retired = ptr::null_mut();
+        let mut reclaimed = 0;
+        while !node.is_null() {
+            // Safety: the swap above gave this scan exclusive ownership
+            let next = unsafe { (*node).next };
+            if protected.contains(unsafe { &(*node).ptr }) {
+                unsafe { (*node).next = kept_first };
+                if kept_last.is_null() {
+                    kept_last = node;
+                }
+                kept_first = node;
+            } else {
+                // Safety: unlinked and unprotected, so no thread can reach it
+                let retired = unsafe { Box::from_raw(node) };
+                unsafe { (retired.reclaim)(retired.ptr) };
+                reclaimed += 1;
+            }
+            node = next;
+        }
+
+        if !kept_first.is_null() {
+            self.push_retired(kept_first, kept_last);
+        }
+        self.retired_count.fetch_sub(reclaimed, Ordering::Relaxed);
+        self.reclaimed_count.fetch_add(reclaimed, Ordering::Relaxed);
+    }
+
+    /// Push the owned chain `first..=last` onto the retired list.
+    fn push_retired(&self, first: *mut Retired, last: *mut Retired) {
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            // Safety: the chain is not yet shared
+            unsafe { (*last).next = head };
+            match self.retired.compare_exchange_weak(
+                head,
+                first,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Retired nodes not yet freed.
+    #[must_use]
+    pub fn retired_count(&self) -> u64 {
+        self.retired_count.load(Ordering::Relaxed)
+    }
+
+    /// Retired nodes freed so far.
+    #[must_use]
+    pub fn reclaimed_count(&self) -> u64 {
+        self.reclaimed_count.load(Ordering::Relaxed)
+    }
+
+    /// Retired count that triggers a scan.
+    #[must_use]
+    pub fn scan_threshold(&self) -> u64 {
+        self.scan_threshold
+    }
+
+    /// Hazard slots currently claimed.
+    #[must_use]
+    pub fn active_slots(&self) -> u64 {
+        self.slots
+            .iter()
+            .filter(|slot| slot.active.load(Ordering::Relaxed))
+            .count() as u64
+    }
+}
+
+impl Default for HazardDomain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for HazardDomain {
+    fn drop(&mut self) {
+        // No HazardPointer outlives the borrow, so nothing is protected
+        let mut node = *self.retired.get_mut();
+        while !node.is_null() {
+            let retired = unsafe { Box::from_raw(node) };
+            unsafe { (retired.reclaim)(retired.ptr) };
+            node = retired.next;
+        }
+    }
+}
+
+/// A claimed hazard slot. Released on drop.
+pub struct HazardPointer<'d> {
+    slot: &'d HazardSlot,
+}
+
+impl HazardPointer<'_> {
+    /// Load `src` and protect the result until the next `protect`,
+    /// `reset` or drop.
+    ///
+    /// The returned pointer may be dereferenced while protected if every
+    /// node stored in `src` is retired to this slot's domain only after
+    /// being unlinked from `src`.
+    ///
+    /// # TLA+ Mapping
+    ///
+    /// Corresponds to Publish + Validate in hazard_pointers.tla
+    pub fn protect<T>(&mut self, src: &AtomicPtr<T>) -> *mut T {
+        let mut ptr = src.load(Ordering::Relaxed);
+        loop {
+            self.slot.protected.store(ptr.cast(), Ordering::SeqCst);
+            let current = src.load(Ordering::SeqCst);
+            if current == ptr {
+                return ptr;
+            }
+            ptr = current;
+        }
+    }
+
+    /// Clear the slot.
+    ///
+    /// # TLA+ Mapping
+    ///
+    /// Corresponds to Clear in hazard_pointers.tla
+    pub fn reset(&mut self) {
+        self.slot
+            .protected
+            .store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Drop for HazardPointer<'_> {
+    fn drop(&mut self) {
+        self.reset();
+        self.slot.active.store(false, Ordering::Release);
+    }
+}
+
+/// Object whose drop is recorded, so DST can observe reclamation.
+struct TrackedObject {
+    id: u64,
+    freed: Arc<Mutex<HashSet<u64>>>,
+}
+
+impl Drop for TrackedObject {
+    fn drop(&mut self) {
+        self.freed.lock().unwrap().insert(self.id);
+    }
+}
+
+/// Tracking wrapper for single-threaded DST verification.
+///
+/// Drives a `HazardDomain` with logical threads (one hazard slot each)
+/// and shared cells holding tracked objects, recording what each thread
+/// has validated and which objects the domain actually freed.
+pub struct TrackedDomain<'d> {
+    domain: &'d HazardDomain,
+    /// Shared locations; each links one object
+    cells: Vec<AtomicPtr<TrackedObject>>,
+    /// Logical threads' hazard slots
+    threads: Vec<HazardPointer<'d>>,
+    /// Map: thread -> validated object
+    protected: HashMap<u64, u64>,
+    /// Objects retired so far (freed ones included)
+    retired: HashSet<u64>,
+    /// Objects dropped by the domain
+    freed: Arc<Mutex<HashSet<u64>>>,
+    next_id: u64,
+}
+
+impl<'d> TrackedDomain<'d> {
+    /// Create `cell_count` linked objects and `thread_count` logical threads.
+    pub fn new(domain: &'d HazardDomain, cell_count: usize, thread_count: usize) -> Self {
+        debug_assert!(cell_count > 0, "Need at least one cell");
+        debug_assert!(
+            thread_count <= HAZARD_SLOTS_MAX,
+            "More threads than hazard slots"
+        );
+
+        let mut tracked = Self {
+            domain,
+            cells: Vec::with_capacity(cell_count),
+            threads: (0..thread_count).map(|_| domain.acquire()).collect(),
+            protected: HashMap::new(),
+            retired: HashSet::new(),
+            freed: Arc::new(Mutex::new(HashSet::new())),
+            next_id: 0,
+        };
+        for _ in 0..cell_count {
+            let object = tracked.allocate();
+            tracked.cells.push(AtomicPtr::new(object));
+        }
+        tracked
+    }
+
+    fn allocate(&mut self) -> *mut TrackedObject {
+        self.next_id += 1;
+        Box::into_raw(Box::new(TrackedObject {
+            id: self.next_id,
+            freed: Arc::clone(&self.freed),
+        }))
+    }
+
+    /// Thread `thread` protects and reads the object in `cell`.
+    pub fn protect(&mut self, thread: usize, cell: usize) -> u64 {
+        let ptr = self.threads[thread].protect(&self.cells[cell]);
+        // Safety: protected and validated as linked
+        let id = unsafe { (*ptr).id };
+        self.protected.insert(thread as u64, id);
+        id
+    }
+
+    /// Thread `thread` drops its reference.
+    pub fn clear(&mut self, thread: usize) {
+        self.threads[thread].reset();
+        self.protected.remove(&(thread as u64));
+    }
+
+    /// Link a fresh object into `cell` and retire the old one.
+    pub fn replace(&mut self, cell: usize) {
+        let object = self.allocate();
+        let old = self.cells[cell].swap(object, Ordering::SeqCst);
+        // Safety: read before retiring; no longer linked anywhere
+        self.retired.insert(unsafe { (*old).id });
+        unsafe { self.domain.retire(old) };
+    }
+
+    /// Objects allocated so far.
+    #[must_use]
+    pub fn allocated_count(&self) -> u64 {
+        self.next_id
+    }
+
+    /// Objects dropped so far.
+    #[must_use]
+    pub fn freed_count(&self) -> u64 {
+        self.freed.lock().unwrap().len() as u64
+    }
+}
+
+impl Drop for TrackedDomain<'_> {
+    fn drop(&mut self) {
+        // Linked objects were never retired; the domain frees the rest
+        for cell in &mut self.cells {
+            drop(unsafe { Box::from_raw(*cell.get_mut()) });
+        }
+    }
+}
+
+impl HazardPointerProperties for TrackedDomain<'_> {
+    fn hazards(&self) -> HashMap<u64, HashSet<u64>> {
+        // A validated protection is exactly what the slot publishes
+        self.protected()
+    }
+
+    fn protected(&self) -> HashMap<u64, HashSet<u64>> {
+        (0..self.threads.len() as u64)
+            .map(|tid| (tid, self.protected.get(&tid).copied().into_iter().collect()))
+            .collect()
+    }
+
+    fn reachable_objects(&self) -> HashSet<u64> {
+        self.cells
+            .iter()
+            .map(|cell| unsafe { (*cell.load(Ordering::SeqCst)).id })
+            .collect()
+    }
+
+    fn retired_objects(&self) -> HashSet<u64> {
+        let freed = self.freed.lock().unwrap();
+        self.retired.difference(&freed).copied().collect()
+    }
+
+    fn freed_objects(&self) -> HashSet<u64> {
+        self.freed.lock().unwrap().clone()
+    }
+
+    fn hazards_per_thread(&self) -> u64 {
+        1
+    }
+
+    fn scan_threshold(&self) -> u64 {
+        self.domain.scan_threshold()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_core::invariants::hazard_pointers::HazardPointerPropertyChecker;
+    use vf_core::PropertyChecker;
+    use vf_dst::{get_or_generate_seed, DstEnv, FaultConfig};
+
+    #[test]
+    fn test_protected_node_survives_scan() {
+        let domain = HazardDomain::with_scan_threshold(1);
+        let mut tracked = TrackedDomain::new(&domain, 1, 1);
+
+        let id = tracked.protect(0, 0);
+        tracked.replace(0);
+        assert_eq!(domain.retired_count(), 1, "Protected node must be kept");
+        assert!(!tracked.freed_objects().contains(&id));
+
+        tracked.clear(0);
+        domain.scan();
+        assert_eq!(domain.retired_count(), 0);
+        assert!(tracked.freed_objects().contains(&id));
+    }
+
+    #[test]
+    fn test_slots_released_on_drop() {
+        let domain = HazardDomain::new();
+        let first = domain.acquire();
+        let second = domain.acquire();
+        assert_eq!(domain.active_slots(), 2);
+
+        drop(first);
+        drop(second);
+        assert_eq!(domain.active_slots(), 0);
+    }
+
+    #[test]
+    fn test_dst_invariants() {
+        let seed = get_or_generate_seed();
+        let mut env = DstEnv::with_fault_config(seed, FaultConfig::none());
+        let domain = HazardDomain::with_scan_threshold(8);
+        let mut tracked = TrackedDomain::new(&domain, 4, 3);
+
+        let iterations = std::env::var("DST_ITERATIONS")
+            .map(|s| s.parse().unwrap())
+            .unwrap_or(1000);
+
+        for _ in 0..iterations {
+            let thread = env.rng().gen_range(0..3_u64) as usize;
+            let cell = env.rng().gen_range(0..4_u64) as usize;
+
+            match env.rng().gen_range(0..4_u8) {
+                0 => {
+                    tracked.protect(thread, cell);
+                }
+                1 => tracked.clear(thread),
+                2 => tracked.replace(cell),
+                _ => domain.scan(),
+            }
+
+            let checker = HazardPointerPropertyChecker::new(&tracked).with_seed(seed);
+            for result in checker.check_all() {
+                assert!(
+                    result.holds,
+                    "{} violated at {}: {:?}",
+                    result.name,
+                    env.format_seed(),
+                    result.violation
+                );
+            }
+        }
+
+        let allocated = tracked.allocated_count();
+        let freed = Arc::clone(&tracked.freed);
+        drop(tracked);
+        drop(domain);
+        assert_eq!(
+            freed.lock().unwrap().len() as u64,
+            allocated,
+            "Leak at {}",
+            env.format_seed()
+        );
+    }
+}
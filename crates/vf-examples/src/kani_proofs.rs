@@ -31,6 +31,7 @@ mod proofs {
     ///
     /// For any value pushed onto the stack, it must be present
     /// in the stack contents immediately after.
+    // covers: StackPropertyChecker::NoLostElements
     #[kani::proof]
     #[kani::unwind(5)]
     fn proof_push_preserves_elements() {
@@ -75,6 +76,7 @@ mod proofs {
     /// Proof of LIFO ordering.
     ///
     /// When we push v1 then v2, popping must return v2 first (last-in, first-out).
+    // covers: StackPropertyChecker::LIFO_Order
     #[kani::proof]
     #[kani::unwind(4)]
     fn proof_lifo_order() {
@@ -199,6 +201,5 @@ mod tests {
     #[test]
     fn test_proofs_module_exists() {
         // This test just verifies the module structure is valid
-        assert!(true);
     }
 }
@@ -12,21 +12,36 @@
 //! # Lock-Free Modules
 //!
 //! - `treiber_stack`: Correct reference implementation with epoch-based GC
+//! - `ms_queue`: Michael-Scott FIFO queue with epoch-based GC
+//! - `hazard_pointers`: Hazard pointer reclamation domain (Miri-friendly)
 //! - `loom_stack`: Loom-compatible stack for concurrency testing
 //! - `buggy_stacks`: Intentionally buggy implementations for testing the cascade
+//! - `stale_next_stack`: Buggy stack only DST with weak memory catches
 //! - `kani_proofs`: Kani bounded model checking proofs
 //!
 //! # Lock-Based Modules
 //!
 //! - `ssi`: Serializable Snapshot Isolation (PostgreSQL SERIALIZABLE)
+//! - `two_phase_locking`: Strict 2PL with deadlock detection
+//! - `mvcc`: Multi-version snapshot isolation (first-committer-wins)
 
 pub mod buggy_stacks;
+pub mod hazard_pointers;
 pub mod kani_proofs;
 pub mod loom_stack;
+pub mod ms_queue;
+pub mod mvcc;
 pub mod ssi;
+pub mod stale_next_stack;
 pub mod treiber_stack;
+pub mod two_phase_locking;
 
 pub use buggy_stacks::{LostElementStack, MissingRetryStack, WrongOrderingStack};
+pub use hazard_pointers::{HazardDomain, HazardPointer, TrackedDomain};
 pub use loom_stack::LoomStack;
-pub use ssi::SsiStore;
-pub use treiber_stack::{TrackedStack, TreiberStack};
+pub use ms_queue::{MsQueue, TrackedQueue};
+pub use mvcc::MvccStore;
+pub use ssi::{SnapshotSafety, SsiStore};
+pub use stale_next_stack::StaleNextStack;
+pub use treiber_stack::{EpochReclaim, Reclaim, TrackedStack, TreiberStack};
+pub use two_phase_locking::TwoPhaseLockingStore;
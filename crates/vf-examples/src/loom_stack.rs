@@ -299,6 +299,7 @@ mod loom_tests {
     use loom::sync::Arc;
     use loom::thread;
 
+    // covers: StackPropertyChecker::NoLostElements
     #[test]
     fn test_push_push() {
         loom::model(|| {
@@ -361,6 +362,7 @@ mod loom_tests {
         });
     }
 
+    // covers: StackPropertyChecker::NoDuplicates
     #[test]
     fn test_concurrent_pop() {
         loom::model(|| {
@@ -385,6 +387,7 @@ mod loom_tests {
         });
     }
 
+    // covers: StackPropertyChecker::NoLostElements
     #[test]
     fn test_no_lost_elements() {
         loom::model(|| {
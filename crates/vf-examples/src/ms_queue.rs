@@ -0,0 +1,591 @@
+//! Michael-Scott Queue - Lock-free FIFO queue implementation.
+//!
+//! # TLA+ Specification
+//!
+//! This implementation corresponds to `specs/lockfree/ms_queue.tla`.
+//!
+//! # Invariants
+//!
+//! | Property | TLA+ Line | Verified By |
+//! |----------|-----------|-------------|
+//! | NoLostElements | 80 | DST, atomic counters |
+//! | NoDuplicates | 88 | DST, concurrent drain |
+//! | FIFO_Order | 97 | DST (single-threaded replay), per-producer order |
+//! | Linearizability | 107 | history search (TrackedQueue) |
+//! | TailReachable | 115 | stateright (structural) |
+//!
+//! # Memory Safety
+//!
+//! Uses epoch-based garbage collection from crossbeam-epoch. A dequeued
+//! dummy node is only reclaimed once no thread can still be reading it,
+//! which also rules out ABA on `head` and `tail`.
+//!
+//! # Lock-Free Guarantee
+//!
+//! A thread that finds `tail` lagging behind the last node advances it
+//! before retrying, so no thread ever waits for a stalled enqueuer to
+//! finish its second CAS.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crossbeam_epoch::{self as epoch, Atomic, Owned, Shared};
+
+use vf_core::invariants::queue::{QueueHistory, QueueProperties};
+
+/// Maximum queue size (TigerStyle: explicit limit).
+pub const QUEUE_SIZE_MAX: u64 = 1_000_000;
+
+/// A lock-free Michael-Scott queue.
+///
+/// The classic non-blocking queue by Maged Michael and Michael Scott (1996):
+/// a singly linked list with a dummy node at `head`. Enqueue links at the
+/// tail with one CAS and swings `tail` with a second; dequeue swings `head`.
+///
+/// Memory safety is guaranteed by epoch-based reclamation from crossbeam.
+pub struct MsQueue<T> {
+    /// Pointer to the dummy node
+    head: Atomic<Node<T>>,
+    /// Pointer to the last or second-to-last node
+    tail: Atomic<Node<T>>,
+    /// Atomic size counter (for NoLostElements verification)
+    size: AtomicU64,
+    /// Atomic enqueue counter
+    enqueue_count: AtomicU64,
+    /// Atomic dequeue counter
+    dequeue_count: AtomicU64,
+}
+
+/// Node in the queue.
+struct Node<T> {
+    value: T,
+    next: Atomic<Node<T>>,
+}
+
+impl<T> MsQueue<T> {
+    /// Get current size.
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Get total enqueue operations.
+    #[must_use]
+    pub fn enqueue_count(&self) -> u64 {
+        self.enqueue_count.load(Ordering::Relaxed)
+    }
+
+    /// Get total dequeue operations (including empty dequeues).
+    #[must_use]
+    pub fn dequeue_count(&self) -> u64 {
+        self.dequeue_count.load(Ordering::Relaxed)
+    }
+
+    /// Check if the queue is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        let guard = epoch::pin();
+        let head = self.head.load(Ordering::Acquire, &guard);
+        // Safety: head always points at the dummy node, protected by the guard
+        unsafe { head.deref() }
+            .next
+            .load(Ordering::Acquire, &guard)
+            .is_null()
+    }
+}
+
+impl MsQueue<u64> {
+    /// Create a new empty queue holding only the dummy node.
+    #[must_use]
+    pub fn new() -> Self {
+        let queue = Self {
+            head: Atomic::null(),
+            tail: Atomic::null(),
+            size: AtomicU64::new(0),
+            enqueue_count: AtomicU64::new(0),
+            dequeue_count: AtomicU64::new(0),
+        };
+
+        // Safety: the queue is not shared yet
+        let guard = unsafe { epoch::unprotected() };
+        let dummy = Owned::new(Node {
+            value: 0,
+            next: Atomic::null(),
+        })
+        .into_shared(guard);
+        queue.head.store(dummy, Ordering::Relaxed);
+        queue.tail.store(dummy, Ordering::Relaxed);
+        queue
+    }
+
+    /// Enqueue a value at the back of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if queue size exceeds `QUEUE_SIZE_MAX` (debug builds only).
+    ///
+    /// # TLA+ Mapping
+    ///
+    /// Corresponds to EnqAlloc + EnqRead + EnqLink + EnqSwing in ms_queue.tla
+    pub fn enqueue(&self, value: u64) {
+        // TigerStyle: Validate input
+        debug_assert!(
+            self.size.load(Ordering::Relaxed) < QUEUE_SIZE_MAX,
+            "Queue size limit exceeded"
+        );
+
+        let guard = epoch::pin();
+
+        // Phase 1: Allocate new node (EnqAlloc)
+        let node = Owned::new(Node {
+            value,
+            next: Atomic::null(),
+        })
+        .into_shared(&guard);
+
+        loop {
+            // Phase 2: Read tail and its successor (EnqRead)
+            let tail = self.tail.load(Ordering::Acquire, &guard);
+            // Safety: tail is never null and protected by the epoch guard
+            let tail_ref = unsafe { tail.deref() };
+            let next = tail_ref.next.load(Ordering::Acquire, &guard);
+
+            // Phase 3: Link after the last node, or help tail forward (EnqLink)
+            if !next.is_null() {
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                );
+                continue;
+            }
+
+            if tail_ref
+                .next
+                .compare_exchange(
+                    Shared::null(),
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                )
+                .is_ok()
+            {
+                // Phase 4: Swing tail; failure means another thread helped (EnqSwing)
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                );
+                self.size.fetch_add(1, Ordering::Relaxed);
+                self.enqueue_count.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+
+    /// Dequeue the value at the front of the queue.
+    ///
+    /// Returns `None` if the queue is empty.
+    ///
+    /// # TLA+ Mapping
+    ///
+    /// Corresponds to DeqRead + DeqCAS in ms_queue.tla
+    pub fn dequeue(&self) -> Option<u64> {
+        let guard = epoch::pin();
+
+        loop {
+            // Phase 1: Read head, tail and the dummy's successor (DeqRead)
+            let head = self.head.load(Ordering::Acquire, &guard);
+            let tail = self.tail.load(Ordering::Acquire, &guard);
+            // Safety: head is never null and protected by the epoch guard
+            let next = unsafe { head.deref() }.next.load(Ordering::Acquire, &guard);
+
+            // Phase 2: Report empty, help tail, or swing head (DeqCAS)
+            if next.is_null() {
+                self.dequeue_count.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+
+            if head == tail {
+                // Tail is lagging behind head's successor - help and retry
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                );
+                continue;
+            }
+
+            // Safety: next is not null and protected by the epoch guard
+            let value = unsafe { next.deref() }.value;
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed, &guard)
+                .is_ok()
+            {
+                // The old dummy is unreachable; next is the new dummy
+                unsafe {
+                    guard.defer_destroy(head);
+                }
+
+                self.size.fetch_sub(1, Ordering::Relaxed);
+                self.dequeue_count.fetch_add(1, Ordering::Relaxed);
+                return Some(value);
+            }
+        }
+    }
+
+    /// Get current contents (front to back) for verification.
+    ///
+    /// Note: This traverses the queue and is not thread-safe for concurrent
+    /// modification. Use only when queue is quiescent or in single-threaded tests.
+    pub fn get_contents(&self) -> Vec<u64> {
+        let guard = epoch::pin();
+        let mut result = Vec::new();
+        let head = self.head.load(Ordering::Acquire, &guard);
+        // Skip the dummy node
+        let mut current = unsafe { head.deref() }.next.load(Ordering::Acquire, &guard);
+
+        while !current.is_null() {
+            let node = unsafe { current.deref() };
+            result.push(node.value);
+            current = node.next.load(Ordering::Acquire, &guard);
+        }
+
+        result
+    }
+}
+
+impl Default for MsQueue<u64> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tracking wrapper for single-threaded DST verification.
+///
+/// This wrapper adds operation history tracking for FIFO order verification.
+/// Each operation is recorded as an invoke/response interval, so concurrent
+/// histories can still be checked for linearizability.
+pub struct TrackedQueue {
+    inner: MsQueue<u64>,
+    /// Enqueued elements (for NoLostElements)
+    enqueued: std::sync::Mutex<HashSet<u64>>,
+    /// Dequeued elements (for NoLostElements)
+    dequeued: std::sync::Mutex<HashSet<u64>>,
+    /// Operation history (for FIFO verification)
+    history: std::sync::Mutex<QueueHistory>,
+    /// Logical clock for invoke/response steps (for Linearizability)
+    clock: AtomicU64,
+}
+
+impl TrackedQueue {
+    /// Create a new tracked queue.
+    pub fn new() -> Self {
+        Self {
+            inner: MsQueue::new(),
+            enqueued: std::sync::Mutex::new(HashSet::new()),
+            dequeued: std::sync::Mutex::new(HashSet::new()),
+            history: std::sync::Mutex::new(QueueHistory::new()),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Enqueue with tracking.
+    pub fn enqueue(&self, value: u64) {
+        let invoked_at = self.tick();
+        self.inner.enqueue(value);
+        let step = self.tick();
+        self.enqueued.lock().unwrap().insert(value);
+        self.history
+            .lock()
+            .unwrap()
+            .record_enqueue_interval(0, value, invoked_at, step);
+    }
+
+    /// Dequeue with tracking.
+    pub fn dequeue(&self) -> Option<u64> {
+        let invoked_at = self.tick();
+        let result = self.inner.dequeue();
+        let step = self.tick();
+        if let Some(v) = result {
+            self.dequeued.lock().unwrap().insert(v);
+        }
+        self.history
+            .lock()
+            .unwrap()
+            .record_dequeue_interval(0, result, invoked_at, step);
+        result
+    }
+
+    /// Advance the logical clock, returning the new step.
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Get inner queue for direct access.
+    pub fn inner(&self) -> &MsQueue<u64> {
+        &self.inner
+    }
+}
+
+impl Default for TrackedQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QueueProperties for TrackedQueue {
+    fn enqueued_elements(&self) -> HashSet<u64> {
+        self.enqueued.lock().unwrap().clone()
+    }
+
+    fn dequeued_elements(&self) -> HashSet<u64> {
+        self.dequeued.lock().unwrap().clone()
+    }
+
+    fn current_contents(&self) -> Vec<u64> {
+        self.inner.get_contents()
+    }
+
+    fn history(&self) -> QueueHistory {
+        self.history.lock().unwrap().clone()
+    }
+}
+
+/// QueueProperties for MsQueue (limited - no history tracking).
+///
+/// For full FIFO verification, use `TrackedQueue` in single-threaded tests.
+impl QueueProperties for MsQueue<u64> {
+    fn enqueued_elements(&self) -> HashSet<u64> {
+        // Cannot track individual elements without Mutex
+        HashSet::new()
+    }
+
+    fn dequeued_elements(&self) -> HashSet<u64> {
+        HashSet::new()
+    }
+
+    fn current_contents(&self) -> Vec<u64> {
+        self.get_contents()
+    }
+
+    fn history(&self) -> QueueHistory {
+        // FIFO verification requires TrackedQueue
+        QueueHistory::new()
+    }
+}
+
+// Safety: Queue is thread-safe
+unsafe impl<T: Send> Send for MsQueue<T> {}
+unsafe impl<T: Send> Sync for MsQueue<T> {}
+
+impl<T> Drop for MsQueue<T> {
+    fn drop(&mut self) {
+        // Clean up the dummy node and all remaining nodes
+        let guard = epoch::pin();
+        let mut current = self.head.load(Ordering::Relaxed, &guard);
+
+        while !current.is_null() {
+            let node = unsafe { current.into_owned() };
+            current = node.next.load(Ordering::Relaxed, &guard);
+            drop(node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_core::invariants::queue::QueuePropertyChecker;
+    use vf_core::PropertyChecker;
+    use vf_dst::{get_or_generate_seed, DstEnv, FaultConfig};
+
+    #[test]
+    fn test_basic_enqueue_dequeue() {
+        let queue = MsQueue::new();
+        assert!(queue.is_empty());
+
+        queue.enqueue(1);
+        queue.enqueue(2);
+        queue.enqueue(3);
+        assert!(!queue.is_empty());
+
+        assert_eq!(queue.dequeue(), Some(1));
+        assert_eq!(queue.dequeue(), Some(2));
+        assert_eq!(queue.dequeue(), Some(3));
+        assert_eq!(queue.dequeue(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_enqueue_zero() {
+        // The dummy node is found by pointer, so its value is not reserved
+        let queue = MsQueue::new();
+        queue.enqueue(0);
+        queue.enqueue(1);
+
+        assert_eq!(queue.dequeue(), Some(0));
+        assert_eq!(queue.dequeue(), Some(1));
+        assert_eq!(queue.dequeue(), None);
+    }
+
+    #[test]
+    fn test_invariants_basic() {
+        let queue = TrackedQueue::new();
+
+        queue.enqueue(1);
+        queue.enqueue(2);
+        queue.dequeue();
+
+        let checker = QueuePropertyChecker::new(&queue);
+        assert!(checker.all_hold(), "Invariants should hold");
+    }
+
+    #[test]
+    fn test_dst_with_faults() {
+        let seed = get_or_generate_seed();
+        let mut env = DstEnv::new(seed);
+        let queue = TrackedQueue::new();
+        let checker = QueuePropertyChecker::new(&queue);
+
+        let iterations = std::env::var("DST_ITERATIONS")
+            .map(|s| s.parse().unwrap())
+            .unwrap_or(1000);
+
+        let mut values_to_enqueue: Vec<u64> = (1..=100).collect();
+        env.rng().shuffle(&mut values_to_enqueue);
+        let mut enqueue_idx = 0;
+
+        for _ in 0..iterations {
+            env.maybe_delay();
+
+            let op = env.rng().gen_range(0..4_u8);
+
+            match op {
+                0 | 1 if enqueue_idx < values_to_enqueue.len() => {
+                    queue.enqueue(values_to_enqueue[enqueue_idx]);
+                    enqueue_idx += 1;
+                }
+                2 => {
+                    queue.dequeue();
+                }
+                _ => {
+                    assert!(
+                        checker.all_hold(),
+                        "Invariant violated at {}",
+                        env.format_seed()
+                    );
+                }
+            }
+
+            let delay = env.rng().gen_range(1..100_u64);
+            env.clock().advance_us(delay);
+        }
+
+        assert!(
+            checker.all_hold(),
+            "Final invariant check failed at {}",
+            env.format_seed()
+        );
+
+        println!("DST with faults completed: {}", env.stats());
+    }
+
+    #[test]
+    fn test_dst_single_threaded() {
+        let seed = get_or_generate_seed();
+        let mut env = DstEnv::with_fault_config(seed, FaultConfig::none());
+        let queue = TrackedQueue::new();
+
+        for value in 1..=50 {
+            queue.enqueue(value);
+            if env.rng().gen_bool(0.3) {
+                queue.dequeue();
+            }
+        }
+        while queue.dequeue().is_some() {}
+
+        let checker = QueuePropertyChecker::new(&queue);
+        assert!(
+            checker.all_hold(),
+            "Invariant violated at {}",
+            env.format_seed()
+        );
+        assert!(queue.inner().is_empty());
+    }
+
+    #[test]
+    fn test_lock_free_concurrent() {
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+
+        const PRODUCERS: u64 = 4;
+        const ITEMS_PER_PRODUCER: u64 = 500;
+
+        let queue = Arc::new(MsQueue::new());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = vec![];
+
+        for p in 0..PRODUCERS {
+            let q = Arc::clone(&queue);
+            handles.push(thread::spawn(move || {
+                for i in 1..=ITEMS_PER_PRODUCER {
+                    q.enqueue(p * 10_000 + i);
+                }
+            }));
+        }
+
+        for _ in 0..4 {
+            let q = Arc::clone(&queue);
+            let r = Arc::clone(&received);
+            handles.push(thread::spawn(move || {
+                let mut local = Vec::new();
+                for _ in 0..ITEMS_PER_PRODUCER {
+                    if let Some(v) = q.dequeue() {
+                        local.push(v);
+                    }
+                }
+                r.lock().unwrap().push(local);
+            }));
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let mut drained = Vec::new();
+        while let Some(v) = queue.dequeue() {
+            drained.push(v);
+        }
+
+        // FIFO per producer: each consumer sees a producer's values in order
+        let mut all = Vec::new();
+        for local in received.lock().unwrap().iter().chain([&drained]) {
+            for p in 0..PRODUCERS {
+                let from_p: Vec<u64> = local.iter().copied().filter(|v| v / 10_000 == p).collect();
+                assert!(
+                    from_p.windows(2).all(|w| w[0] < w[1]),
+                    "FIFO order violated for producer {}",
+                    p
+                );
+            }
+            all.extend_from_slice(local);
+        }
+
+        // No lost and no duplicated elements
+        let unique: HashSet<u64> = all.iter().copied().collect();
+        assert_eq!(all.len() as u64, PRODUCERS * ITEMS_PER_PRODUCER);
+        assert_eq!(unique.len(), all.len(), "Element dequeued twice");
+        assert_eq!(queue.enqueue_count(), PRODUCERS * ITEMS_PER_PRODUCER);
+        assert_eq!(queue.size(), 0, "Queue should be empty after draining");
+    }
+}
@@ -0,0 +1,346 @@
+//! Multi-version concurrency control (MVCC) with snapshot isolation.
+//!
+//! Every commit installs new versions stamped with its commit timestamp.
+//! Reads see the snapshot taken at begin and never block; concurrent
+//! writers of a key are resolved by first-committer-wins at commit time.
+//!
+//! This is the isolation level many databases call REPEATABLE READ. It
+//! permits write skew: compare with `SsiStore`, which adds conflict
+//! tracking on top of the same snapshots to rule it out.
+//!
+//! # Invariants (from `specs/ssi/snapshot_isolation.tla`)
+//!
+//! 1. `FirstCommitterWins`: Concurrent writers of a key never both commit
+//! 2. `SnapshotRead`: Reads see the newest version as of the snapshot
+//! 3. `CommitAfterSnapshot`: Commit timestamps are unique and follow the snapshot
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+use vf_core::invariants::ssi::TxnStatus;
+use vf_core::invariants::{SnapshotIsolationProperties, SnapshotReadRecord};
+use vf_dst::ssi_harness::{DstTestableSsi, KeyId, TxnId, Value};
+
+/// A committed version of a key.
+#[derive(Debug, Clone)]
+struct Version {
+    value: Value,
+    writer_txn: TxnId,
+    commit_ts: u64,
+}
+
+/// Transaction state.
+#[derive(Debug, Clone)]
+struct TxnState {
+    status: TxnStatus,
+    /// Latest commit timestamp visible to this transaction.
+    snapshot_ts: u64,
+    commit_ts: Option<u64>,
+    /// Buffered writes, installed as versions at commit.
+    writes: BTreeMap<KeyId, Value>,
+    reads: Vec<SnapshotReadRecord>,
+}
+
+/// All MVCC state in a single lock, like `SsiStore`.
+#[derive(Debug)]
+struct MvccInner {
+    /// Timestamp of the latest commit.
+    clock: u64,
+    next_txn: TxnId,
+    txns: HashMap<TxnId, TxnState>,
+    /// Versioned data: Key -> versions (newest first).
+    versions: HashMap<KeyId, Vec<Version>>,
+    committed: HashSet<TxnId>,
+}
+
+impl MvccInner {
+    fn new() -> Self {
+        Self {
+            clock: 0,
+            next_txn: 1,
+            txns: HashMap::new(),
+            versions: HashMap::new(),
+            committed: HashSet::new(),
+        }
+    }
+
+    fn active(&mut self, txn: TxnId) -> Option<&mut TxnState> {
+        self.txns
+            .get_mut(&txn)
+            .filter(|s| s.status == TxnStatus::Active)
+    }
+
+    /// Newest version of `key` committed at or before `ts`.
+    fn visible_version(&self, key: KeyId, ts: u64) -> Option<&Version> {
+        self.versions.get(&key)?.iter().find(|v| v.commit_ts <= ts)
+    }
+
+    /// Whether a version of `key` was committed after `ts`.
+    fn written_since(&self, key: KeyId, ts: u64) -> bool {
+        self.versions
+            .get(&key)
+            .and_then(|versions| versions.first())
+            .is_some_and(|newest| newest.commit_ts > ts)
+    }
+}
+
+/// MVCC store providing snapshot isolation.
+///
+/// Thread-safe storage with multi-version concurrency control.
+pub struct MvccStore {
+    inner: Mutex<MvccInner>,
+}
+
+impl MvccStore {
+    /// Create a new MVCC store.
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(MvccInner::new()),
+        }
+    }
+}
+
+impl Default for MvccStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DstTestableSsi for MvccStore {
+    fn begin(&self) -> TxnId {
+        let mut inner = self.inner.lock().unwrap();
+
+        let txn = inner.next_txn;
+        inner.next_txn += 1;
+        let snapshot_ts = inner.clock;
+        inner.txns.insert(
+            txn,
+            TxnState {
+                status: TxnStatus::Active,
+                snapshot_ts,
+                commit_ts: None,
+                writes: BTreeMap::new(),
+                reads: Vec::new(),
+            },
+        );
+
+        txn
+    }
+
+    fn read(&self, txn: TxnId, key: KeyId) -> Option<Value> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let state = inner.active(txn)?;
+        let snapshot_ts = state.snapshot_ts;
+
+        // Own pending write first, then the snapshot
+        let (value, from) = match state.writes.get(&key) {
+            Some(value) => (Some(*value), Some(txn)),
+            None => match inner.visible_version(key, snapshot_ts) {
+                Some(version) => (Some(version.value), Some(version.writer_txn)),
+                None => (None, None),
+            },
+        };
+
+        if let Some(state) = inner.active(txn) {
+            state.reads.push(SnapshotReadRecord { key, from });
+        }
+        value
+    }
+
+    fn write(&self, txn: TxnId, key: KeyId, value: Value) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        // Writers never block; conflicts are resolved at commit
+        match inner.active(txn) {
+            Some(state) => {
+                state.writes.insert(key, value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn commit(&self, txn: TxnId) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        let Some(state) = inner.active(txn) else {
+            return false;
+        };
+        let snapshot_ts = state.snapshot_ts;
+        let writes = std::mem::take(&mut state.writes);
+
+        // First committer wins: a concurrent transaction already
+        // committed a version of a key we wrote
+        if writes.keys().any(|k| inner.written_since(*k, snapshot_ts)) {
+            if let Some(state) = inner.txns.get_mut(&txn) {
+                state.status = TxnStatus::Aborted;
+                state.writes = writes;
+            }
+            return false;
+        }
+
+        inner.clock += 1;
+        let commit_ts = inner.clock;
+        for (&key, &value) in &writes {
+            inner.versions.entry(key).or_default().insert(
+                0,
+                Version {
+                    value,
+                    writer_txn: txn,
+                    commit_ts,
+                },
+            );
+        }
+
+        if let Some(state) = inner.txns.get_mut(&txn) {
+            state.status = TxnStatus::Committed;
+            state.commit_ts = Some(commit_ts);
+            state.writes = writes;
+        }
+        inner.committed.insert(txn);
+
+        true
+    }
+
+    fn abort(&self, txn: TxnId) {
+        let mut inner = self.inner.lock().unwrap();
+
+        if let Some(state) = inner.active(txn) {
+            state.status = TxnStatus::Aborted;
+        }
+    }
+
+    fn is_active(&self, txn: TxnId) -> bool {
+        self.inner.lock().unwrap().active(txn).is_some()
+    }
+
+    fn committed_txns(&self) -> HashSet<TxnId> {
+        self.inner.lock().unwrap().committed.clone()
+    }
+
+    fn get_current_value(&self, key: KeyId) -> Option<Value> {
+        let inner = self.inner.lock().unwrap();
+        inner.visible_version(key, inner.clock).map(|v| v.value)
+    }
+}
+
+impl SnapshotIsolationProperties for MvccStore {
+    fn txn_statuses(&self) -> HashMap<u64, TxnStatus> {
+        let inner = self.inner.lock().unwrap();
+        inner.txns.iter().map(|(t, s)| (*t, s.status)).collect()
+    }
+
+    fn snapshots(&self) -> HashMap<u64, u64> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .iter()
+            .map(|(t, s)| (*t, s.snapshot_ts))
+            .collect()
+    }
+
+    fn commit_timestamps(&self) -> HashMap<u64, u64> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .iter()
+            .filter_map(|(t, s)| Some((*t, s.commit_ts?)))
+            .collect()
+    }
+
+    fn write_sets(&self) -> HashMap<u64, HashSet<u64>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .iter()
+            .map(|(t, s)| (*t, s.writes.keys().copied().collect()))
+            .collect()
+    }
+
+    fn read_records(&self) -> HashMap<u64, Vec<SnapshotReadRecord>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .iter()
+            .map(|(t, s)| (*t, s.reads.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_core::invariants::SnapshotIsolationPropertyChecker;
+    use vf_core::PropertyChecker;
+
+    fn assert_invariants(store: &MvccStore) {
+        for result in SnapshotIsolationPropertyChecker::new(store).check_all() {
+            assert!(result.holds, "{}: {:?}", result.name, result.violation);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_reads_ignore_later_commits() {
+        let store = MvccStore::new();
+
+        let t1 = store.begin();
+        assert!(store.write(t1, 1, 100));
+        assert!(store.commit(t1));
+
+        let t2 = store.begin();
+        assert_eq!(store.read(t2, 1), Some(100));
+
+        let t3 = store.begin();
+        assert!(store.write(t3, 1, 200));
+        assert_eq!(store.read(t3, 1), Some(200));
+        assert!(store.commit(t3));
+
+        // T2 still sees its snapshot; readers never block or abort
+        assert_eq!(store.read(t2, 1), Some(100));
+        assert!(store.commit(t2));
+        assert_eq!(store.get_current_value(1), Some(200));
+        assert_invariants(&store);
+    }
+
+    #[test]
+    fn test_first_committer_wins() {
+        let store = MvccStore::new();
+
+        let t1 = store.begin();
+        let t2 = store.begin();
+        assert!(store.write(t1, 1, 10));
+        assert!(store.write(t2, 1, 20));
+
+        assert!(store.commit(t1));
+        assert!(!store.commit(t2), "T2 should lose to the first committer");
+        assert!(!store.is_active(t2));
+        assert_eq!(store.get_current_value(1), Some(10));
+        assert_invariants(&store);
+    }
+
+    #[test]
+    fn test_write_skew_commits() {
+        let store = MvccStore::new();
+
+        let setup = store.begin();
+        store.write(setup, 1, 1);
+        store.write(setup, 2, 1);
+        assert!(store.commit(setup));
+
+        // Each transaction reads both keys and writes a different one
+        let t1 = store.begin();
+        let t2 = store.begin();
+        store.read(t1, 1);
+        store.read(t1, 2);
+        store.read(t2, 1);
+        store.read(t2, 2);
+        assert!(store.write(t1, 1, 0));
+        assert!(store.write(t2, 2, 0));
+
+        // SI lets both commit: not serializable, but every SI invariant holds
+        assert!(store.commit(t1));
+        assert!(store.commit(t2));
+        assert_invariants(&store);
+    }
+}
@@ -7,8 +7,22 @@
 //!
 //! - **Snapshot**: Each transaction sees data from its start time
 //! - **SIREAD locks**: Track reads (persist after commit for conflict detection)
+//! - **Range SIREAD locks**: Track scans, so a later insert into a scanned
+//!   range conflicts even though no one read that key (phantom protection)
 //! - **Conflict flags**: `in_conflict` and `out_conflict` per transaction
 //! - **Dangerous structure**: Transaction with BOTH flags = potential cycle
+//!   (checked at commit, and at read time when the pivot already committed)
+//! - **Safe snapshot**: A read-only transaction whose concurrent read-write
+//!   transactions all finished, none committing with an `out_conflict`.
+//!   It can no longer be part of a dangerous structure, so it drops its
+//!   SIREAD locks and reads without conflict tracking. A deferrable
+//!   read-only transaction waits until it can start on a safe snapshot.
+//! - **Vacuum**: Everything older than the oldest active snapshot (the
+//!   horizon) is garbage: versions superseded before it, and the SIREAD
+//!   locks and state of transactions that finished before it. No live or
+//!   future transaction can read those versions or conflict with those
+//!   transactions, so nothing about them is kept and the store's size is
+//!   bounded by what live transactions can see.
 //!
 //! # Invariants (from `specs/ssi/serializable_snapshot_isolation.tla`)
 //!
@@ -16,6 +30,12 @@
 //! 2. `SnapshotConsistency`: Reads see consistent snapshot
 //! 3. `Serializable`: No dangerous structures at commit time
 //!
+//! Like snapshot isolation in the SSI paper, a transaction reads its own
+//! pending writes, and a write over a version committed after its snapshot
+//! fails (first updater wins) rather than silently losing that update.
+//! History checkers that infer versions from the values transactions read
+//! and wrote rely on both.
+//!
 //! # Architecture
 //!
 //! ```text
@@ -29,9 +49,10 @@
 //! ```
 
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::RangeInclusive;
 use std::sync::Mutex;
 
-use vf_dst::ssi_harness::{DstTestableSsi, KeyId, TxnId, Value};
+use vf_dst::ssi_harness::{DstTestableSsi, KeyId, TxnId, VacuumReport, Value};
 
 /// Transaction status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -49,6 +70,17 @@ pub struct Version {
     pub commit_timestamp: u64,
 }
 
+/// Safety of a read-only transaction's snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotSafety {
+    /// Read-write transactions active at the snapshot, not finished yet.
+    Pending(BTreeSet<TxnId>),
+    /// None of them committed with an `out_conflict`.
+    Safe,
+    /// One did: the transaction keeps running under full SSI.
+    Unsafe,
+}
+
 /// Transaction state.
 #[derive(Debug, Clone)]
 pub struct TxnState {
@@ -63,6 +95,10 @@ pub struct TxnState {
     pub in_conflict: bool,
     /// Outgoing rw-conflict flag.
     pub out_conflict: bool,
+    /// Snapshot safety, for read-only transactions.
+    pub read_only: Option<SnapshotSafety>,
+    /// Commit timestamp, once committed.
+    pub commit_ts: Option<u64>,
 }
 
 impl TxnState {
@@ -74,9 +110,15 @@ impl TxnState {
             read_set: BTreeSet::new(),
             in_conflict: false,
             out_conflict: false,
+            read_only: None,
+            commit_ts: None,
         }
     }
 
+    fn is_safe_snapshot(&self) -> bool {
+        self.read_only == Some(SnapshotSafety::Safe)
+    }
+
     /// Check if transaction has dangerous structure.
     fn has_dangerous_structure(&self) -> bool {
         self.in_conflict && self.out_conflict
@@ -98,10 +140,11 @@ struct SsiInner {
     write_locks: HashMap<KeyId, Option<TxnId>>,
     /// SIREAD locks: Key -> Set of TxnIds that read.
     siread_locks: HashMap<KeyId, BTreeSet<TxnId>>,
+    /// Range SIREAD locks: (scanned range, TxnId), like PostgreSQL's
+    /// predicate locks on index pages.
+    siread_ranges: Vec<(RangeInclusive<KeyId>, TxnId)>,
     /// Versioned data: Key -> List of versions (newest first).
     data: HashMap<KeyId, Vec<Version>>,
-    /// Committed transaction IDs.
-    committed: HashSet<TxnId>,
 }
 
 impl SsiInner {
@@ -112,8 +155,8 @@ impl SsiInner {
             txns: HashMap::new(),
             write_locks: HashMap::new(),
             siread_locks: HashMap::new(),
+            siread_ranges: Vec::new(),
             data: HashMap::new(),
-            committed: HashSet::new(),
         }
     }
 
@@ -123,6 +166,138 @@ impl SsiInner {
         ts
     }
 
+    /// Start a transaction, read-only when `read_only` is set.
+    fn begin(&mut self, read_only: Option<SnapshotSafety>) -> TxnId {
+        let txn = self.next_txn;
+        self.next_txn += 1;
+
+        let snapshot_ts = self.timestamp;
+        self.tick();
+
+        let mut state = TxnState::new(snapshot_ts);
+        state.read_only = read_only;
+        self.txns.insert(txn, state);
+
+        txn
+    }
+
+    /// Active read-write transactions (those a new snapshot has to wait on).
+    fn active_read_write_txns(&self) -> BTreeSet<TxnId> {
+        self.txns
+            .iter()
+            .filter(|(_, state)| state.status == TxnStatus::Active && state.read_only.is_none())
+            .map(|(&txn, _)| txn)
+            .collect()
+    }
+
+    /// Update pending read-only snapshots once `finished` commits or aborts.
+    ///
+    /// A snapshot turns unsafe if `finished` committed with an
+    /// `out_conflict`, and safe once nothing is left to wait for. A safe
+    /// transaction releases its SIREAD locks.
+    fn refresh_safe_snapshots(&mut self, finished: TxnId) {
+        let Some(finished_state) = self.txns.get(&finished) else {
+            return;
+        };
+        let unsafe_commit =
+            finished_state.status == TxnStatus::Committed && finished_state.out_conflict;
+
+        let mut now_safe = Vec::new();
+        for (&txn, state) in self.txns.iter_mut() {
+            let Some(SnapshotSafety::Pending(waiting)) = &mut state.read_only else {
+                continue;
+            };
+            if !waiting.remove(&finished) {
+                continue;
+            }
+            if unsafe_commit {
+                state.read_only = Some(SnapshotSafety::Unsafe);
+            } else if waiting.is_empty() {
+                state.read_only = Some(SnapshotSafety::Safe);
+                now_safe.push(txn);
+            }
+        }
+
+        for txn in now_safe {
+            self.release_siread_locks(txn);
+        }
+    }
+
+    /// Drop every SIREAD lock a transaction holds.
+    fn release_siread_locks(&mut self, txn: TxnId) {
+        for locks in self.siread_locks.values_mut() {
+            locks.remove(&txn);
+        }
+        self.siread_ranges.retain(|(_, holder)| *holder != txn);
+    }
+
+    /// Oldest snapshot a live or future transaction reads from.
+    fn horizon(&self) -> u64 {
+        self.txns
+            .values()
+            .filter(|state| state.status == TxnStatus::Active)
+            .map(|state| state.snapshot_ts)
+            .min()
+            .unwrap_or(self.timestamp)
+    }
+
+    /// Prune everything no snapshot at or after the horizon can observe.
+    fn vacuum(&mut self) -> VacuumReport {
+        let horizon = self.horizon();
+        let mut report = VacuumReport::default();
+
+        // Keep pending versions, versions newer than the horizon and the
+        // newest one at or below it (newest first, so the first seen)
+        for (&key, versions) in self.data.iter_mut() {
+            let mut horizon_version_kept = false;
+            versions.retain(|v| {
+                if v.commit_timestamp == u64::MAX || v.commit_timestamp > horizon {
+                    return true;
+                }
+                if !horizon_version_kept {
+                    horizon_version_kept = true;
+                    return true;
+                }
+                report.versions.push((key, v.writer_txn));
+                false
+            });
+        }
+        report.versions.sort_unstable();
+
+        // Every live transaction started after these finished
+        let expired: BTreeSet<TxnId> = self
+            .txns
+            .iter()
+            .filter(|(_, state)| match state.status {
+                TxnStatus::Active => false,
+                TxnStatus::Committed => state.commit_ts.is_some_and(|ts| ts < horizon),
+                TxnStatus::Aborted => true,
+            })
+            .map(|(&txn, _)| txn)
+            .collect();
+
+        for locks in self.siread_locks.values_mut() {
+            let held = locks.len();
+            locks.retain(|holder| !expired.contains(holder));
+            report.siread_locks += held - locks.len();
+        }
+        self.siread_locks.retain(|_, locks| !locks.is_empty());
+        let held = self.siread_ranges.len();
+        self.siread_ranges
+            .retain(|(_, holder)| !expired.contains(holder));
+        report.siread_locks += held - self.siread_ranges.len();
+        self.write_locks.retain(|_, holder| holder.is_some());
+
+        // No live snapshot can conflict with them, so nothing is kept
+        for txn in expired {
+            if self.txns.remove(&txn).is_some() {
+                report.txns += 1;
+            }
+        }
+
+        report
+    }
+
     /// Find the version visible at a snapshot timestamp.
     fn visible_version(&self, key: KeyId, snapshot_ts: u64) -> Option<Version> {
         if let Some(versions) = self.data.get(&key) {
@@ -136,23 +311,41 @@ impl SsiInner {
         None
     }
 
+    /// Value a transaction reads: its own pending write, else its snapshot.
+    fn read_value(&self, txn: TxnId, key: KeyId, snapshot_ts: u64) -> Option<Value> {
+        let own_write = self.data.get(&key).and_then(|versions| {
+            versions
+                .iter()
+                .find(|v| v.writer_txn == txn && v.commit_timestamp == u64::MAX)
+                .map(|v| v.value)
+        });
+        own_write.or_else(|| self.visible_version(key, snapshot_ts).map(|v| v.value))
+    }
+
     /// Find concurrent readers of a key (for conflict detection during write).
+    ///
+    /// Includes scanners whose range covers the key, read or not.
     fn concurrent_readers(&self, txn: TxnId, key: KeyId) -> BTreeSet<TxnId> {
         let mut readers = BTreeSet::new();
 
-        if let Some(holders) = self.siread_locks.get(&key) {
-            for &holder in holders {
-                if holder == txn {
-                    continue;
-                }
+        let key_holders = self.siread_locks.get(&key).into_iter().flatten().copied();
+        let range_holders = self
+            .siread_ranges
+            .iter()
+            .filter(|(range, _)| range.contains(&key))
+            .map(|(_, holder)| *holder);
 
-                if let Some(holder_state) = self.txns.get(&holder) {
-                    match holder_state.status {
-                        TxnStatus::Active | TxnStatus::Committed => {
-                            readers.insert(holder);
-                        }
-                        TxnStatus::Aborted => {}
+        for holder in key_holders.chain(range_holders) {
+            if holder == txn {
+                continue;
+            }
+
+            if let Some(holder_state) = self.txns.get(&holder) {
+                match holder_state.status {
+                    TxnStatus::Active | TxnStatus::Committed => {
+                        readers.insert(holder);
                     }
+                    TxnStatus::Aborted => {}
                 }
             }
         }
@@ -160,6 +353,64 @@ impl SsiInner {
         readers
     }
 
+    /// Record rw-conflicts from a reader to writers newer than its snapshot.
+    fn flag_newer_writers(&mut self, txn: TxnId, newer_writers: &BTreeSet<TxnId>) {
+        for &writer in newer_writers {
+            if let Some(writer_state) = self.txns.get_mut(&writer) {
+                writer_state.in_conflict = true;
+            }
+        }
+
+        if !newer_writers.is_empty() {
+            if let Some(state) = self.txns.get_mut(&txn) {
+                state.out_conflict = true;
+            }
+        }
+    }
+
+    /// Whether a newer writer already committed as the pivot of a
+    /// dangerous structure: reading past it would complete the cycle.
+    fn has_committed_pivot(&self, newer_writers: &BTreeSet<TxnId>) -> bool {
+        newer_writers.iter().any(|writer| {
+            self.txns
+                .get(writer)
+                .is_some_and(|state| state.status == TxnStatus::Committed && state.out_conflict)
+        })
+    }
+
+    /// Abort an active transaction, dropping its writes and SIREAD locks.
+    fn abort(&mut self, txn: TxnId) {
+        // Check transaction is active
+        let txn_state = match self.txns.get(&txn) {
+            Some(state) if state.status == TxnStatus::Active => state.clone(),
+            _ => return,
+        };
+
+        // Mark as aborted
+        if let Some(state) = self.txns.get_mut(&txn) {
+            state.status = TxnStatus::Aborted;
+        }
+
+        // Release write locks
+        for &key in &txn_state.write_set {
+            if self.write_locks.get(&key) == Some(&Some(txn)) {
+                self.write_locks.insert(key, None);
+            }
+        }
+
+        // Remove pending writes
+        for &key in &txn_state.write_set {
+            if let Some(versions) = self.data.get_mut(&key) {
+                versions.retain(|v| v.writer_txn != txn);
+            }
+        }
+
+        // Remove SIREAD locks
+        self.release_siread_locks(txn);
+
+        self.refresh_safe_snapshots(txn);
+    }
+
     /// Find writers that wrote after our snapshot (for conflict detection during read).
     fn newer_writers(&self, txn: TxnId, key: KeyId, snapshot_ts: u64) -> BTreeSet<TxnId> {
         let mut writers = BTreeSet::new();
@@ -201,6 +452,16 @@ impl SsiStore {
             inner: Mutex::new(SsiInner::new()),
         }
     }
+
+    /// Snapshot safety of a read-only transaction (`None` for read-write,
+    /// or once vacuumed).
+    pub fn snapshot_safety(&self, txn: TxnId) -> Option<SnapshotSafety> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .get(&txn)
+            .and_then(|state| state.read_only.clone())
+    }
 }
 
 impl Default for SsiStore {
@@ -211,17 +472,23 @@ impl Default for SsiStore {
 
 impl DstTestableSsi for SsiStore {
     fn begin(&self) -> TxnId {
-        let mut inner = self.inner.lock().unwrap();
-
-        let txn = inner.next_txn;
-        inner.next_txn += 1;
+        self.inner.lock().unwrap().begin(None)
+    }
 
-        let snapshot_ts = inner.timestamp;
-        inner.tick();
+    fn begin_read_only(&self, deferrable: bool) -> Option<TxnId> {
+        let mut inner = self.inner.lock().unwrap();
 
-        inner.txns.insert(txn, TxnState::new(snapshot_ts));
+        let concurrent = inner.active_read_write_txns();
+        let safety = if concurrent.is_empty() {
+            SnapshotSafety::Safe
+        } else if deferrable {
+            // Wait for a snapshot no read-write transaction overlaps
+            return None;
+        } else {
+            SnapshotSafety::Pending(concurrent)
+        };
 
-        txn
+        Some(inner.begin(Some(safety)))
     }
 
     fn read(&self, txn: TxnId, key: KeyId) -> Option<Value> {
@@ -235,40 +502,88 @@ impl DstTestableSsi for SsiStore {
 
         let snapshot_ts = txn_state.snapshot_ts;
 
+        // A safe snapshot needs no conflict tracking
+        if txn_state.is_safe_snapshot() {
+            return inner.visible_version(key, snapshot_ts).map(|v| v.value);
+        }
+
         // Check for rw-conflicts with newer writers
         let newer_writers = inner.newer_writers(txn, key, snapshot_ts);
+        if inner.has_committed_pivot(&newer_writers) {
+            inner.abort(txn);
+            return None;
+        }
+        inner.flag_newer_writers(txn, &newer_writers);
 
-        // Update conflict flags
-        for &writer in &newer_writers {
-            if let Some(writer_state) = inner.txns.get_mut(&writer) {
-                writer_state.in_conflict = true;
-            }
+        // Record the read
+        if let Some(state) = inner.txns.get_mut(&txn) {
+            state.read_set.insert(key);
         }
 
-        if !newer_writers.is_empty() {
-            if let Some(state) = inner.txns.get_mut(&txn) {
-                state.out_conflict = true;
+        // Add SIREAD lock
+        inner.siread_locks.entry(key).or_default().insert(txn);
+
+        // Own pending write first, then the snapshot
+        inner.read_value(txn, key, snapshot_ts)
+    }
+
+    fn scan(&self, txn: TxnId, range: RangeInclusive<KeyId>) -> Vec<(KeyId, Value)> {
+        let mut inner = self.inner.lock().unwrap();
+
+        // Check transaction is active
+        let (snapshot_ts, safe) = match inner.txns.get(&txn) {
+            Some(state) if state.status == TxnStatus::Active => {
+                (state.snapshot_ts, state.is_safe_snapshot())
+            }
+            _ => return Vec::new(),
+        };
+
+        // Every key ever written in the range, including pending inserts
+        let keys: BTreeSet<KeyId> = inner
+            .data
+            .keys()
+            .filter(|key| range.contains(key))
+            .copied()
+            .collect();
+
+        // Check for rw-conflicts with newer writers anywhere in the range
+        let mut newer_writers = BTreeSet::new();
+        let mut results = Vec::new();
+        for key in keys {
+            newer_writers.extend(inner.newer_writers(txn, key, snapshot_ts));
+            if let Some(value) = inner.read_value(txn, key, snapshot_ts) {
+                results.push((key, value));
             }
         }
+        if safe {
+            // A safe snapshot needs no conflict tracking
+            return results;
+        }
+        if inner.has_committed_pivot(&newer_writers) {
+            inner.abort(txn);
+            return Vec::new();
+        }
+        inner.flag_newer_writers(txn, &newer_writers);
 
         // Record the read
         if let Some(state) = inner.txns.get_mut(&txn) {
-            state.read_set.insert(key);
+            state.read_set.extend(results.iter().map(|(key, _)| *key));
         }
 
-        // Add SIREAD lock
-        inner.siread_locks.entry(key).or_default().insert(txn);
+        // Lock the range, not just the keys it returned
+        inner.siread_ranges.push((range, txn));
 
-        // Get visible version
-        inner.visible_version(key, snapshot_ts).map(|v| v.value)
+        results
     }
 
     fn write(&self, txn: TxnId, key: KeyId, value: Value) -> bool {
         let mut inner = self.inner.lock().unwrap();
 
-        // Check transaction is active
-        match inner.txns.get(&txn) {
-            Some(state) if state.status == TxnStatus::Active => {}
+        // Check transaction is active and may write
+        let snapshot_ts = match inner.txns.get(&txn) {
+            Some(state) if state.status == TxnStatus::Active && state.read_only.is_none() => {
+                state.snapshot_ts
+            }
             _ => return false,
         };
 
@@ -278,6 +593,17 @@ impl DstTestableSsi for SsiStore {
             return false; // Lock held by another transaction
         }
 
+        // First updater wins: a version committed after our snapshot
+        // would be silently overwritten (lost update)
+        let overwrites_newer = inner.data.get(&key).is_some_and(|versions| {
+            versions
+                .iter()
+                .any(|v| v.commit_timestamp > snapshot_ts && v.commit_timestamp != u64::MAX)
+        });
+        if overwrites_newer {
+            return false;
+        }
+
         // Check for rw-conflicts with concurrent readers
         let concurrent_readers = inner.concurrent_readers(txn, key);
 
@@ -357,6 +683,7 @@ impl DstTestableSsi for SsiStore {
                 }
             }
 
+            inner.refresh_safe_snapshots(txn);
             return false;
         }
 
@@ -372,7 +699,7 @@ impl DstTestableSsi for SsiStore {
                     }
                 }
                 // Sort by commit timestamp (newest first)
-                versions.sort_by(|a, b| b.commit_timestamp.cmp(&a.commit_timestamp));
+                versions.sort_by_key(|v| std::cmp::Reverse(v.commit_timestamp));
             }
         }
 
@@ -386,48 +713,18 @@ impl DstTestableSsi for SsiStore {
         // Mark as committed
         if let Some(state) = inner.txns.get_mut(&txn) {
             state.status = TxnStatus::Committed;
+            state.commit_ts = Some(commit_ts);
         }
 
-        // Track committed transactions
-        inner.committed.insert(txn);
-
         // SIREAD locks persist after commit (for conflict detection)
 
+        inner.refresh_safe_snapshots(txn);
+
         true
     }
 
     fn abort(&self, txn: TxnId) {
-        let mut inner = self.inner.lock().unwrap();
-
-        // Check transaction is active
-        let txn_state = match inner.txns.get(&txn) {
-            Some(state) if state.status == TxnStatus::Active => state.clone(),
-            _ => return,
-        };
-
-        // Mark as aborted
-        if let Some(state) = inner.txns.get_mut(&txn) {
-            state.status = TxnStatus::Aborted;
-        }
-
-        // Release write locks
-        for &key in &txn_state.write_set {
-            if inner.write_locks.get(&key) == Some(&Some(txn)) {
-                inner.write_locks.insert(key, None);
-            }
-        }
-
-        // Remove pending writes
-        for &key in &txn_state.write_set {
-            if let Some(versions) = inner.data.get_mut(&key) {
-                versions.retain(|v| v.writer_txn != txn);
-            }
-        }
-
-        // Remove SIREAD locks
-        for locks in inner.siread_locks.values_mut() {
-            locks.remove(&txn);
-        }
+        self.inner.lock().unwrap().abort(txn);
     }
 
     fn is_active(&self, txn: TxnId) -> bool {
@@ -441,7 +738,14 @@ impl DstTestableSsi for SsiStore {
     }
 
     fn committed_txns(&self) -> HashSet<TxnId> {
-        self.inner.lock().unwrap().committed.clone()
+        self.inner
+            .lock()
+            .unwrap()
+            .txns
+            .iter()
+            .filter(|(_, state)| state.status == TxnStatus::Committed)
+            .map(|(&txn, _)| txn)
+            .collect()
     }
 
     fn get_current_value(&self, key: KeyId) -> Option<Value> {
@@ -449,13 +753,24 @@ impl DstTestableSsi for SsiStore {
         inner.visible_version(key, inner.timestamp).map(|v| v.value)
     }
 
+    fn is_safe_snapshot(&self, txn: TxnId) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .get(&txn)
+            .is_some_and(|state| state.is_safe_snapshot())
+    }
+
+    fn vacuum(&self) -> VacuumReport {
+        self.inner.lock().unwrap().vacuum()
+    }
+
     fn get_conflict_flags(&self, txn: TxnId) -> (bool, bool) {
         let inner = self.inner.lock().unwrap();
         inner
             .txns
             .get(&txn)
-            .map(|state| (state.in_conflict, state.out_conflict))
-            .unwrap_or((false, false))
+            .map_or((false, false), |state| (state.in_conflict, state.out_conflict))
     }
 }
 
@@ -598,4 +913,275 @@ mod tests {
         // T1 should still be able to commit (only out_conflict, no in_conflict)
         assert!(store.commit(t1), "T1 should commit with only out_conflict");
     }
+
+    #[test]
+    fn test_read_sees_own_pending_write() {
+        let store = SsiStore::new();
+
+        let t1 = store.begin();
+        assert!(store.write(t1, 1, 100));
+        assert!(store.commit(t1));
+
+        // T2 reads its own uncommitted write, not its snapshot
+        let t2 = store.begin();
+        let t3 = store.begin();
+        assert!(store.write(t2, 1, 200));
+        assert_eq!(store.read(t2, 1), Some(200));
+
+        // Other transactions still see the committed version
+        assert_eq!(store.read(t3, 1), Some(100));
+    }
+
+    #[test]
+    fn test_first_updater_wins() {
+        let store = SsiStore::new();
+
+        let t1 = store.begin();
+        assert!(store.write(t1, 1, 100));
+        assert!(store.commit(t1));
+
+        // T2's snapshot predates T3's update of K1
+        let t2 = store.begin();
+        let t3 = store.begin();
+        assert!(store.write(t3, 1, 200));
+        assert!(store.commit(t3));
+
+        // The lock is free, but overwriting T3 would lose its update
+        assert!(!store.write(t2, 1, 300));
+        assert!(store.write(t2, 2, 300));
+
+        // A transaction that began after T3 may update K1
+        let t4 = store.begin();
+        assert!(store.write(t4, 1, 400));
+        assert!(store.commit(t4));
+    }
+
+    #[test]
+    fn test_scan_sees_snapshot_and_own_writes() {
+        let store = SsiStore::new();
+
+        let setup = store.begin();
+        store.write(setup, 3, 30);
+        store.write(setup, 20, 200);
+        store.commit(setup);
+
+        // T1 starts before T2 inserts K5
+        let t1 = store.begin();
+        let t2 = store.begin();
+        assert!(store.write(t2, 5, 50));
+        assert!(store.commit(t2));
+
+        assert!(store.write(t1, 7, 70));
+        assert_eq!(store.scan(t1, 1..=10), vec![(3, 30), (7, 70)]);
+    }
+
+    #[test]
+    fn test_phantom_write_skew_aborts() {
+        let store = SsiStore::new();
+
+        // Both scan the same empty range
+        let t1 = store.begin();
+        let t2 = store.begin();
+        assert!(store.scan(t1, 1..=10).is_empty());
+        assert!(store.scan(t2, 1..=10).is_empty());
+
+        // Each inserts a key the other never read, but did scan past
+        assert!(store.write(t2, 5, 50));
+        assert!(store.commit(t2));
+        assert!(store.write(t1, 7, 70));
+
+        // T1: in_conflict (T2 scanned K7) + out_conflict (missed K5)
+        assert!(!store.commit(t1), "T1 should abort on the phantom");
+    }
+
+    #[test]
+    fn test_disjoint_ranges_commit() {
+        let store = SsiStore::new();
+
+        let t1 = store.begin();
+        let t2 = store.begin();
+        store.scan(t1, 1..=4);
+        store.scan(t2, 5..=8);
+
+        // Only T1's insert lands in a scanned range
+        assert!(store.write(t1, 6, 60));
+        assert!(store.write(t2, 9, 90));
+        assert!(store.commit(t1));
+        assert!(store.commit(t2));
+    }
+
+    #[test]
+    fn test_safe_snapshot_releases_siread_locks() {
+        let store = SsiStore::new();
+
+        let setup = store.begin();
+        store.write(setup, 1, 10);
+        store.commit(setup);
+
+        // The reporting query starts while T1 is still running
+        let t1 = store.begin();
+        store.read(t1, 2);
+        let report = store.begin_read_only(false).unwrap();
+        assert_eq!(
+            store.snapshot_safety(report),
+            Some(SnapshotSafety::Pending(BTreeSet::from([t1])))
+        );
+        assert_eq!(store.read(report, 1), Some(10));
+
+        // T1 commits without an out_conflict: the snapshot is safe
+        assert!(store.commit(t1));
+        assert!(store.is_safe_snapshot(report));
+
+        // Its SIREAD lock on K1 is gone, so T2's write conflicts with no one
+        let t2 = store.begin();
+        assert!(store.write(t2, 1, 20));
+        assert_eq!(store.get_conflict_flags(t2), (false, false));
+        assert!(store.commit(t2));
+
+        // And it keeps reading its snapshot without picking up flags
+        assert_eq!(store.read(report, 1), Some(10));
+        assert_eq!(store.get_conflict_flags(report), (false, false));
+        assert!(store.commit(report));
+    }
+
+    #[test]
+    fn test_out_conflict_commit_makes_snapshot_unsafe() {
+        let store = SsiStore::new();
+
+        // T1 reads K1, which T2 then overwrites: T1 gets out_conflict
+        let t1 = store.begin();
+        store.read(t1, 1);
+        let t2 = store.begin();
+        assert!(store.write(t2, 1, 20));
+        assert!(store.commit(t2));
+
+        let report = store.begin_read_only(false).unwrap();
+        assert!(store.commit(t1));
+        assert_eq!(store.snapshot_safety(report), Some(SnapshotSafety::Unsafe));
+    }
+
+    #[test]
+    fn test_deferrable_waits_for_safe_snapshot() {
+        let store = SsiStore::new();
+
+        let t1 = store.begin();
+        assert_eq!(store.begin_read_only(true), None);
+
+        assert!(store.commit(t1));
+        let report = store.begin_read_only(true).unwrap();
+        assert!(store.is_safe_snapshot(report));
+
+        // Read-only transactions cannot write
+        assert!(!store.write(report, 1, 10));
+        assert!(store.commit(report));
+    }
+
+    #[test]
+    fn test_read_past_committed_pivot_aborts() {
+        let store = SsiStore::new();
+
+        // T1 reads K1, which T2 overwrites: T1 gets out_conflict
+        let t1 = store.begin();
+        let reader = store.begin();
+        store.read(t1, 1);
+        let t2 = store.begin();
+        assert!(store.write(t2, 1, 10));
+        assert!(store.commit(t2));
+
+        // T1 writes K2 and commits as a pivot with only out_conflict so far
+        assert!(store.write(t1, 2, 20));
+        assert!(store.commit(t1));
+
+        // Reading T1's write from an older snapshot would add reader -rw-> T1
+        assert_eq!(store.read(reader, 2), None);
+        assert!(!store.is_active(reader));
+        assert_eq!(store.get_conflict_flags(t1), (false, true));
+    }
+
+    #[test]
+    fn test_vacuum_keeps_versions_live_snapshots_read() {
+        let store = SsiStore::new();
+
+        let setup = store.begin();
+        store.write(setup, 1, 10);
+        store.commit(setup);
+
+        // T1's snapshot holds K1=10 while two newer versions commit
+        let t1 = store.begin();
+        for value in [20, 30] {
+            let writer = store.begin();
+            assert!(store.write(writer, 1, value));
+            assert!(store.commit(writer));
+        }
+
+        // Everything from T1's snapshot on stays
+        assert!(store.vacuum().versions.is_empty());
+        assert_eq!(store.read(t1, 1), Some(10));
+        let t2 = store.begin();
+        assert_eq!(store.read(t2, 1), Some(30));
+
+        // Once both are done, only the newest version is left
+        assert!(store.commit(t1));
+        assert!(store.commit(t2));
+        assert_eq!(store.vacuum().versions, vec![(1, setup), (1, setup + 2)]);
+        assert_eq!(store.get_current_value(1), Some(30));
+    }
+
+    #[test]
+    fn test_vacuum_releases_finished_siread_locks() {
+        let store = SsiStore::new();
+
+        // T1 reads K1 and commits while T2 is still running
+        let t1 = store.begin();
+        let t2 = store.begin();
+        store.read(t1, 1);
+        store.scan(t1, 5..=8);
+        assert!(store.commit(t1));
+
+        // T2 is concurrent with T1, so T1's locks must stay
+        assert_eq!(store.vacuum().siread_locks, 0);
+        assert!(store.write(t2, 1, 10));
+        assert_eq!(store.get_conflict_flags(t2), (true, false));
+        assert!(store.commit(t2));
+
+        // Nothing live overlaps either of them now
+        let report = store.vacuum();
+        assert_eq!(report.siread_locks, 2);
+        assert_eq!(report.txns, 2);
+        assert!(store.committed_txns().is_empty());
+        assert_eq!(store.get_conflict_flags(t2), (false, false));
+
+        let t3 = store.begin();
+        assert!(store.write(t3, 6, 60));
+        assert_eq!(store.get_conflict_flags(t3), (false, false));
+    }
+
+    #[test]
+    fn test_vacuum_keeps_state_bounded() {
+        let store = SsiStore::new();
+        let size = |store: &SsiStore| {
+            let inner = store.inner.lock().unwrap();
+            let versions: usize = inner.data.values().map(Vec::len).sum();
+            let siread_locks: usize = inner.siread_locks.values().map(BTreeSet::len).sum();
+            inner.txns.len() + versions + siread_locks + inner.siread_ranges.len()
+        };
+
+        // Read-write transactions over a few keys, one pass per round
+        let mut sizes = Vec::new();
+        for round in 0..1000u64 {
+            let reader = store.begin();
+            let writer = store.begin();
+            store.read(reader, round % 4);
+            store.scan(reader, 0..=3);
+            assert!(store.write(writer, round % 4, round));
+            store.commit(writer);
+            store.commit(reader);
+            store.vacuum();
+            sizes.push(size(&store));
+        }
+
+        // After warm-up the store holds one version per key and nothing else
+        assert!(sizes.iter().all(|&n| n <= 8), "{:?}", &sizes[..20]);
+        assert_eq!(sizes[999], sizes[99]);
+    }
 }
@@ -0,0 +1,220 @@
+//! Buggy stack whose relaxed loads only fail under weak memory.
+//!
+//! Like `WrongOrderingStack` in `buggy_stacks`, but `next` is an atomic,
+//! so there is no data race on it for miri to report. Every load and CAS
+//! is Relaxed, so a pop does not synchronize with the push that published
+//! the node, and its `next` load may return a pointer older than the one
+//! the push stored. The pop then swings `head` past live nodes, losing
+//! elements (NoLostElements).
+//!
+//! | Implementation | Bug | Caught By |
+//! |----------------|-----|-----------|
+//! | StaleNextStack | Relaxed `next` load reads a stale pointer | DST (weak memory) |
+//!
+//! The stack uses `vf_dst::sync` primitives, so DST controls its atomics;
+//! outside a controlled execution they behave like std. With sequentially
+//! consistent DST the bug never shows.
+
+use std::collections::HashSet;
+use std::ptr;
+
+use vf_core::invariants::stack::{StackHistory, StackProperties};
+use vf_dst::sync::atomic::{AtomicPtr, Ordering};
+use vf_dst::sync::Mutex;
+
+/// Stack with relaxed loads of an atomic `next` pointer.
+///
+/// BUG: Uses Relaxed ordering instead of Acquire/Release, so a pop can
+/// read a stale `next` and unlink nodes pushed after it.
+///
+/// CAUGHT BY: DST with weak memory (NoLostElements)
+pub struct StaleNextStack {
+    head: AtomicPtr<StaleNextNode>,
+    pushed: Mutex<HashSet<u64>>,
+    popped: Mutex<HashSet<u64>>,
+}
+
+struct StaleNextNode {
+    value: u64,
+    next: AtomicPtr<StaleNextNode>,
+}
+
+impl StaleNextStack {
+    pub fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            pushed: Mutex::new(HashSet::new()),
+            popped: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn push(&self, value: u64) {
+        let new_node = Box::into_raw(Box::new(StaleNextNode {
+            value,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }));
+
+        loop {
+            // BUG: Should be Acquire, not Relaxed
+            let head = self.head.load(Ordering::Relaxed);
+            unsafe { (*new_node).next.store(head, Ordering::Relaxed); }
+
+            if self.head.compare_exchange(
+                head,
+                new_node,
+                Ordering::Relaxed,  // BUG: Should be Release
+                Ordering::Relaxed,
+            ).is_ok() {
+                self.pushed.lock().unwrap().insert(value);
+                break;
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<u64> {
+        loop {
+            // BUG: Should be Acquire
+            let head = self.head.load(Ordering::Relaxed);
+            if head.is_null() {
+                return None;
+            }
+
+            // BUG: May return a `next` older than the pushing thread's store
+            let next = unsafe { (*head).next.load(Ordering::Relaxed) };
+            let value = unsafe { (*head).value };
+
+            if self.head.compare_exchange(
+                head,
+                next,
+                Ordering::Relaxed,  // BUG: Should be Release
+                Ordering::Relaxed,
+            ).is_ok() {
+                self.popped.lock().unwrap().insert(value);
+                unsafe { drop(Box::from_raw(head)); }
+                return Some(value);
+            }
+        }
+    }
+
+    fn get_contents(&self) -> Vec<u64> {
+        let mut result = Vec::new();
+        let mut current = self.head.load(Ordering::Acquire);
+        while !current.is_null() {
+            result.push(unsafe { (*current).value });
+            current = unsafe { (*current).next.load(Ordering::Acquire) };
+        }
+        result
+    }
+}
+
+impl Default for StaleNextStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for StaleNextStack {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+unsafe impl Send for StaleNextStack {}
+unsafe impl Sync for StaleNextStack {}
+
+impl StackProperties for StaleNextStack {
+    fn pushed_elements(&self) -> HashSet<u64> {
+        self.pushed.lock().unwrap().clone()
+    }
+
+    fn popped_elements(&self) -> HashSet<u64> {
+        self.popped.lock().unwrap().clone()
+    }
+
+    fn current_contents(&self) -> Vec<u64> {
+        self.get_contents()
+    }
+
+    fn history(&self) -> StackHistory {
+        StackHistory::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_core::invariants::stack::StackPropertyChecker;
+    use vf_core::PropertyChecker;
+    use vf_dst::sync::atomic::AtomicBool;
+    use vf_dst::{DstHarness, HarnessConfig};
+
+    #[test]
+    fn test_stale_next_single_threaded() {
+        let stack = StaleNextStack::new();
+
+        // Basic operations still work without weak memory
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    /// DST with weak memory catches what the single-threaded test cannot
+    #[test]
+    fn test_stale_next_weak_memory_dst() {
+        const PUSHES: u64 = 4;
+        let config = HarnessConfig {
+            threads_count: 2,
+            operations_per_thread: PUSHES,
+            yield_probability: 0.5,
+            ..HarnessConfig::quick()
+        };
+
+        // Thread 0 pushes, then thread 1 pops. Popping only after the last
+        // push means no node is freed while a stale load can return it.
+        let run = |seed, weak_memory| {
+            let stack = StaleNextStack::new();
+            // Relaxed, so the popper does not synchronize with the pusher
+            let pushed_all = AtomicBool::new(false);
+            let config = HarnessConfig {
+                weak_memory,
+                ..config.clone()
+            };
+            let mut harness = DstHarness::new(seed, config);
+            harness.run_controlled(
+                |thread, step| {
+                    if thread == 0 {
+                        stack.push(step + 1);
+                        if step + 1 == PUSHES {
+                            pushed_all.store(true, Ordering::Relaxed);
+                        }
+                    } else {
+                        while !pushed_all.load(Ordering::Relaxed) {}
+                        stack.pop();
+                    }
+                    Ok(())
+                },
+                || {
+                    let results = StackPropertyChecker::new(&stack).check_all();
+                    let no_lost = results.into_iter().find(|r| r.name == "NoLostElements");
+                    no_lost.unwrap().violation.map_or(Ok(()), Err)
+                },
+            )
+        };
+
+        // Sequentially consistent runs never see the stale pointer
+        assert!((1..=20).all(|seed| run(seed, false).all_invariants_held));
+
+        let failing: Vec<_> = (1..=20)
+            .map(|seed| run(seed, true))
+            .filter(|r| !r.all_invariants_held)
+            .collect();
+        assert!(!failing.is_empty(), "no seed read a stale next pointer");
+        let violation = failing[0].first_violation.as_ref().unwrap();
+        assert!(violation.contains("neither in stack nor popped"), "{}", violation);
+    }
+}
@@ -8,16 +8,20 @@
 //!
 //! | Property | TLA+ Line | Verified By |
 //! |----------|-----------|-------------|
-//! | NoLostElements | 45 | DST, atomic counters |
-//! | NoDuplicates | 58 | DST, atomic counters |
-//! | LIFO_Order | 72 | DST (single-threaded replay) |
-//! | Linearizability | 89 | loom (LoomStack) |
-//! | ABA_Safety | 103 | epoch GC (structural) |
+//! | NoLostElements | 66 | DST, atomic counters |
+//! | NoDuplicates | 75 | DST, atomic counters |
+//! | LIFO_Order | 87 | DST (replay in linearization order) |
+//! | Linearizability | 102 | loom (LoomStack), history search (TrackedStack) |
+//! | ABA_Safety | 116 | epoch GC or hazard pointers (structural) |
 //!
 //! # Memory Safety
 //!
-//! Uses epoch-based garbage collection from crossbeam-epoch to prevent
-//! the ABA problem and use-after-free.
+//! Popped nodes are freed through a `Reclaim` strategy, which prevents
+//! the ABA problem and use-after-free. The default, `EpochReclaim`, uses
+//! epoch-based garbage collection from crossbeam-epoch.
+//! `TreiberStack<u64, HazardDomain>` uses hazard pointer reclamation
+//! instead (see `hazard_pointers`), for checkers such as Miri that
+//! reject crossbeam-epoch.
 //!
 //! # Lock-Free Guarantee
 //!
@@ -26,25 +30,96 @@
 //! compare-and-swap operations with no blocking.
 
 use std::collections::HashSet;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
 
-use crossbeam_epoch::{self as epoch, Atomic, Owned};
+use crossbeam_epoch::{self as epoch, Guard};
 
 use vf_core::invariants::stack::{StackHistory, StackProperties};
 
+use crate::hazard_pointers::{HazardDomain, HazardPointer};
+
 /// Maximum stack size (TigerStyle: explicit limit).
 pub const STACK_SIZE_MAX: u64 = 1_000_000;
 
+/// How a stack keeps popped nodes alive while other threads may still
+/// read them, and frees them afterwards.
+pub trait Reclaim {
+    /// Protection held for the duration of one operation.
+    type Guard<'r>
+    where
+        Self: 'r;
+
+    /// Start an operation.
+    fn pin(&self) -> Self::Guard<'_>;
+
+    /// Load `src`; the result may be dereferenced while `guard` is held.
+    fn protect<N>(guard: &mut Self::Guard<'_>, src: &AtomicPtr<N>) -> *mut N;
+
+    /// Free `node` once no guard can still read it.
+    ///
+    /// # Safety
+    ///
+    /// `node` must come from `Box::into_raw`, must already be unlinked,
+    /// must not be retired twice, and must be safe to drop on any thread.
+    unsafe fn retire<N>(&self, guard: &mut Self::Guard<'_>, node: *mut N);
+}
+
+/// Epoch-based reclamation from crossbeam-epoch.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EpochReclaim;
+
+impl Reclaim for EpochReclaim {
+    type Guard<'r> = Guard;
+
+    fn pin(&self) -> Guard {
+        epoch::pin()
+    }
+
+    fn protect<N>(_guard: &mut Guard, src: &AtomicPtr<N>) -> *mut N {
+        src.load(Ordering::Acquire)
+    }
+
+    unsafe fn retire<N>(&self, guard: &mut Guard, node: *mut N) {
+        guard.defer_unchecked(move || drop(Box::from_raw(node)));
+    }
+}
+
+/// Hazard pointer reclamation.
+///
+/// # TLA+ Mapping
+///
+/// `protect` is Publish + Validate and `retire` is Clear + UnlinkRetire
+/// in hazard_pointers.tla; the pop CAS does the unlink
+impl Reclaim for HazardDomain {
+    type Guard<'r> = HazardPointer<'r>;
+
+    fn pin(&self) -> HazardPointer<'_> {
+        self.acquire()
+    }
+
+    fn protect<N>(guard: &mut HazardPointer<'_>, src: &AtomicPtr<N>) -> *mut N {
+        guard.protect(src)
+    }
+
+    unsafe fn retire<N>(&self, guard: &mut HazardPointer<'_>, node: *mut N) {
+        guard.reset();
+        HazardDomain::retire(self, node);
+    }
+}
+
 /// A lock-free Treiber stack.
 ///
 /// This is the classic lock-free stack design by R. Kent Treiber (1986).
 /// Operations are linearizable and lock-free (at least one thread makes
 /// progress in any execution).
 ///
-/// Memory safety is guaranteed by epoch-based reclamation from crossbeam.
-pub struct TreiberStack<T> {
+/// Memory safety is guaranteed by the reclamation strategy `R`.
+pub struct TreiberStack<T, R = EpochReclaim> {
     /// Pointer to top node
-    head: Atomic<Node<T>>,
+    head: AtomicPtr<Node<T>>,
+    /// Reclamation strategy for popped nodes
+    reclaim: R,
     /// Atomic size counter (for NoLostElements verification)
     size: AtomicU64,
     /// Atomic push counter
@@ -55,18 +130,27 @@ pub struct TreiberStack<T> {
     step: AtomicU64,
 }
 
-/// Node in the stack.
+/// Node in the stack. `next` is immutable once published.
 struct Node<T> {
     value: T,
-    next: Atomic<Node<T>>,
+    next: *mut Node<T>,
 }
 
 impl<T> TreiberStack<T> {
-    /// Create a new empty stack.
+    /// Create a new empty stack with epoch-based reclamation.
     #[must_use]
     pub fn new() -> Self {
+        Self::with_reclaim(EpochReclaim)
+    }
+}
+
+impl<T, R> TreiberStack<T, R> {
+    /// Create a new empty stack freeing popped nodes through `reclaim`.
+    #[must_use]
+    pub fn with_reclaim(reclaim: R) -> Self {
         Self {
-            head: Atomic::null(),
+            head: AtomicPtr::new(ptr::null_mut()),
+            reclaim,
             size: AtomicU64::new(0),
             push_count: AtomicU64::new(0),
             pop_count: AtomicU64::new(0),
@@ -77,8 +161,7 @@ impl<T> TreiberStack<T> {
     /// Check if the stack is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        let guard = epoch::pin();
-        self.head.load(Ordering::Acquire, &guard).is_null()
+        self.head.load(Ordering::Acquire).is_null()
     }
 
     /// Get current size.
@@ -98,9 +181,15 @@ impl<T> TreiberStack<T> {
     pub fn pop_count(&self) -> u64 {
         self.pop_count.load(Ordering::Relaxed)
     }
+
+    /// The reclamation strategy.
+    #[must_use]
+    pub fn reclaim(&self) -> &R {
+        &self.reclaim
+    }
 }
 
-impl TreiberStack<u64> {
+impl<R: Reclaim> TreiberStack<u64, R> {
     /// Push a value onto the stack.
     ///
     /// This operation is lock-free: it will complete in bounded time
@@ -121,44 +210,30 @@ impl TreiberStack<u64> {
             "Stack size limit exceeded"
         );
 
-        let guard = epoch::pin();
-
         // Phase 1: Allocate new node (PushAlloc)
-        let new_node = Owned::new(Node {
+        let node = Box::into_raw(Box::new(Node {
             value,
-            next: Atomic::null(),
-        });
+            next: ptr::null_mut(),
+        }));
 
         // Phase 2 & 3: CAS loop (PushRead + PushCAS)
-        let mut node = new_node;
+        let mut head = self.head.load(Ordering::Relaxed);
         loop {
-            // Read current head
-            let head = self.head.load(Ordering::Acquire, &guard);
-
-            // Set new node's next to current head
-            node.next.store(head, Ordering::Relaxed);
-
-            // Attempt CAS
-            match self.head.compare_exchange(
-                head,
-                node,
-                Ordering::Release,
-                Ordering::Relaxed,
-                &guard,
-            ) {
-                Ok(_) => {
-                    // Success - update counters atomically
-                    self.size.fetch_add(1, Ordering::Relaxed);
-                    self.push_count.fetch_add(1, Ordering::Relaxed);
-                    self.step.fetch_add(1, Ordering::Relaxed);
-                    break;
-                }
-                Err(e) => {
-                    // CAS failed - retry with same node
-                    node = e.new;
-                }
+            // Safety: node is not yet published
+            unsafe { (*node).next = head };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                // CAS failed - retry with same node
+                Err(current) => head = current,
             }
         }
+
+        self.size.fetch_add(1, Ordering::Relaxed);
+        self.push_count.fetch_add(1, Ordering::Relaxed);
+        self.step.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Pop a value from the stack.
@@ -169,11 +244,11 @@ impl TreiberStack<u64> {
     ///
     /// Corresponds to PopRead + PopCAS in treiber_stack.tla
     pub fn pop(&self) -> Option<u64> {
-        let guard = epoch::pin();
+        let mut guard = self.reclaim.pin();
 
         loop {
             // Read current head
-            let head = self.head.load(Ordering::Acquire, &guard);
+            let head = R::protect(&mut guard, &self.head);
 
             if head.is_null() {
                 // Stack is empty
@@ -182,35 +257,22 @@ impl TreiberStack<u64> {
                 return None;
             }
 
-            // Safety: head is not null and protected by epoch guard
-            let head_ref = unsafe { head.deref() };
-            let value = head_ref.value;
-            let next = head_ref.next.load(Ordering::Acquire, &guard);
-
-            // Attempt CAS
-            match self.head.compare_exchange(
-                head,
-                next,
-                Ordering::Release,
-                Ordering::Relaxed,
-                &guard,
-            ) {
-                Ok(_) => {
-                    // Success - defer cleanup and update counters
-                    unsafe {
-                        guard.defer_destroy(head);
-                    }
+            // Safety: head is not null and protected by the guard
+            let (value, next) = unsafe { ((*head).value, (*head).next) };
 
-                    self.size.fetch_sub(1, Ordering::Relaxed);
-                    self.pop_count.fetch_add(1, Ordering::Relaxed);
-                    self.step.fetch_add(1, Ordering::Relaxed);
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                // Safety: unlinked by the CAS above, and only the thread
+                // whose CAS succeeded retires it
+                unsafe { self.reclaim.retire(&mut guard, head) };
 
-                    return Some(value);
-                }
-                Err(_) => {
-                    // CAS failed - retry
-                    continue;
-                }
+                self.size.fetch_sub(1, Ordering::Relaxed);
+                self.pop_count.fetch_add(1, Ordering::Relaxed);
+                self.step.fetch_add(1, Ordering::Relaxed);
+                return Some(value);
             }
         }
     }
@@ -220,23 +282,22 @@ impl TreiberStack<u64> {
     /// Note: This traverses the stack and is not thread-safe for concurrent
     /// modification. Use only when stack is quiescent or in single-threaded tests.
     pub fn get_contents(&self) -> Vec<u64> {
-        let guard = epoch::pin();
         let mut result = Vec::new();
-        let mut current = self.head.load(Ordering::Acquire, &guard);
+        let mut current = self.head.load(Ordering::Acquire);
 
         while !current.is_null() {
-            let node = unsafe { current.deref() };
+            let node = unsafe { &*current };
             result.push(node.value);
-            current = node.next.load(Ordering::Acquire, &guard);
+            current = node.next;
         }
 
         result
     }
 }
 
-impl Default for TreiberStack<u64> {
+impl<R: Reclaim + Default> Default for TreiberStack<u64, R> {
     fn default() -> Self {
-        Self::new()
+        Self::with_reclaim(R::default())
     }
 }
 
@@ -244,60 +305,84 @@ impl Default for TreiberStack<u64> {
 ///
 /// This wrapper adds operation history tracking for LIFO order verification.
 /// Use this in single-threaded DST tests where you need full history replay.
-/// For concurrent tests, use `TreiberStack` directly with atomic counter checks.
-pub struct TrackedStack {
-    inner: TreiberStack<u64>,
+/// Each operation is recorded as an invoke/response interval, so concurrent
+/// histories can still be checked for linearizability.
+pub struct TrackedStack<R = EpochReclaim> {
+    inner: TreiberStack<u64, R>,
     /// Pushed elements (for NoLostElements)
     pushed: std::sync::Mutex<HashSet<u64>>,
     /// Popped elements (for NoLostElements)
     popped: std::sync::Mutex<HashSet<u64>>,
     /// Operation history (for LIFO verification)
     history: std::sync::Mutex<StackHistory>,
+    /// Logical clock for invoke/response steps (for Linearizability)
+    clock: AtomicU64,
 }
 
 impl TrackedStack {
     /// Create a new tracked stack.
     pub fn new() -> Self {
+        Self::with_reclaim(EpochReclaim)
+    }
+}
+
+impl<R: Reclaim> TrackedStack<R> {
+    /// Create a new tracked stack freeing popped nodes through `reclaim`.
+    pub fn with_reclaim(reclaim: R) -> Self {
         Self {
-            inner: TreiberStack::new(),
+            inner: TreiberStack::with_reclaim(reclaim),
             pushed: std::sync::Mutex::new(HashSet::new()),
             popped: std::sync::Mutex::new(HashSet::new()),
             history: std::sync::Mutex::new(StackHistory::new()),
+            clock: AtomicU64::new(0),
         }
     }
 
     /// Push with tracking.
     pub fn push(&self, value: u64) {
+        let invoked_at = self.tick();
         self.inner.push(value);
-        let step = self.inner.step.load(Ordering::Relaxed);
+        let step = self.tick();
         self.pushed.lock().unwrap().insert(value);
-        self.history.lock().unwrap().record_push(0, value, step);
+        self.history
+            .lock()
+            .unwrap()
+            .record_push_interval(0, value, invoked_at, step);
     }
 
     /// Pop with tracking.
     pub fn pop(&self) -> Option<u64> {
+        let invoked_at = self.tick();
         let result = self.inner.pop();
-        let step = self.inner.step.load(Ordering::Relaxed);
+        let step = self.tick();
         if let Some(v) = result {
             self.popped.lock().unwrap().insert(v);
         }
-        self.history.lock().unwrap().record_pop(0, result, step);
+        self.history
+            .lock()
+            .unwrap()
+            .record_pop_interval(0, result, invoked_at, step);
         result
     }
 
+    /// Advance the logical clock, returning the new step.
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
     /// Get inner stack for direct access.
-    pub fn inner(&self) -> &TreiberStack<u64> {
+    pub fn inner(&self) -> &TreiberStack<u64, R> {
         &self.inner
     }
 }
 
-impl Default for TrackedStack {
+impl<R: Reclaim + Default> Default for TrackedStack<R> {
     fn default() -> Self {
-        Self::new()
+        Self::with_reclaim(R::default())
     }
 }
 
-impl StackProperties for TrackedStack {
+impl<R: Reclaim> StackProperties for TrackedStack<R> {
     fn pushed_elements(&self) -> HashSet<u64> {
         self.pushed.lock().unwrap().clone()
     }
@@ -318,7 +403,7 @@ impl StackProperties for TrackedStack {
 /// StackProperties for TreiberStack (limited - no history tracking).
 ///
 /// For full LIFO verification, use `TrackedStack` in single-threaded tests.
-impl StackProperties for TreiberStack<u64> {
+impl<R: Reclaim> StackProperties for TreiberStack<u64, R> {
     fn pushed_elements(&self) -> HashSet<u64> {
         // Cannot track individual elements without Mutex
         // Return empty set - NoLostElements check will use counters instead
@@ -341,20 +426,18 @@ impl StackProperties for TreiberStack<u64> {
     }
 }
 
-// Safety: Stack is thread-safe
-unsafe impl<T: Send> Send for TreiberStack<T> {}
-unsafe impl<T: Send> Sync for TreiberStack<T> {}
+// Safety: nodes are only shared through the atomic head and freed
+// through the reclamation strategy
+unsafe impl<T: Send, R: Send> Send for TreiberStack<T, R> {}
+unsafe impl<T: Send, R: Sync> Sync for TreiberStack<T, R> {}
 
-impl<T> Drop for TreiberStack<T> {
+impl<T, R> Drop for TreiberStack<T, R> {
     fn drop(&mut self) {
-        // Clean up remaining nodes
-        let guard = epoch::pin();
-        let mut current = self.head.load(Ordering::Relaxed, &guard);
-
+        // Clean up remaining nodes; the strategy frees retired ones
+        let mut current = *self.head.get_mut();
         while !current.is_null() {
-            let node = unsafe { current.into_owned() };
-            current = node.next.load(Ordering::Relaxed, &guard);
-            drop(node);
+            let node = unsafe { Box::from_raw(current) };
+            current = node.next;
         }
     }
 }
@@ -571,4 +654,71 @@ mod tests {
         assert_eq!(pushed, 400, "Expected 400 pushes");
         assert_eq!(size, 0, "Stack should be empty after draining");
     }
+
+    #[test]
+    fn test_hazard_stack_lifo_order() {
+        let stack = TrackedStack::with_reclaim(HazardDomain::new());
+
+        for i in 1..=10 {
+            stack.push(i);
+        }
+        assert_eq!(stack.inner().get_contents(), (1..=10).rev().collect::<Vec<_>>());
+        assert_eq!(stack.pushed_elements().len(), 10);
+
+        for i in (1..=10).rev() {
+            assert_eq!(stack.pop(), Some(i), "LIFO order violated");
+        }
+        assert_eq!(stack.pop(), None);
+        assert!(stack.inner().is_empty());
+        assert_eq!(stack.popped_elements().len(), 10);
+
+        let checker = StackPropertyChecker::new(&stack);
+        assert!(checker.all_hold(), "{:?}", checker.check_all());
+    }
+
+    #[test]
+    fn test_hazard_stack_concurrent() {
+        use std::sync::Arc;
+        use std::thread;
+
+        let stack = Arc::new(TreiberStack::with_reclaim(
+            HazardDomain::with_scan_threshold(16),
+        ));
+        let mut handles = vec![];
+
+        // Each thread pushes and pops its own values, interleaved with
+        // the others, so popped nodes are retired while others read head
+        for i in 0..4_u64 {
+            let s = Arc::clone(&stack);
+            handles.push(thread::spawn(move || {
+                let mut popped = vec![];
+                for j in 1..=200 {
+                    s.push(i * 1000 + j);
+                    if j % 2 == 0 {
+                        popped.extend(s.pop());
+                    }
+                }
+                popped
+            }));
+        }
+
+        let mut seen = HashSet::new();
+        for handle in handles {
+            for value in handle.join().unwrap() {
+                assert!(seen.insert(value), "Value {} popped twice", value);
+            }
+        }
+        while let Some(value) = stack.pop() {
+            assert!(seen.insert(value), "Value {} popped twice", value);
+        }
+
+        // NoLostElements: every push comes back exactly once
+        assert_eq!(seen.len(), 800);
+        assert_eq!(stack.size(), 0);
+        assert!(
+            stack.reclaim().reclaimed_count() > 0,
+            "Scans should free nodes"
+        );
+        assert!(stack.reclaim().retired_count() <= 16 + 4);
+    }
 }
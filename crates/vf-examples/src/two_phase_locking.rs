@@ -0,0 +1,452 @@
+//! Strict two-phase locking (2PL) with deadlock detection.
+//!
+//! Single-version store where reads take shared locks and writes take
+//! exclusive locks, all held until commit or abort. Serializable by
+//! construction, at the price of readers blocking writers.
+//!
+//! # Blocking Without Threads
+//!
+//! A conflicting request does not park the caller. It records a
+//! waits-for edge and fails (`read` returns `None`, `write` returns
+//! `false`, `is_waiting` returns `true`); the client retries later, once
+//! the holder has finished. A request whose wait would close a cycle in
+//! the waits-for graph aborts the requester as the deadlock victim.
+//!
+//! # Invariants (from `specs/ssi/two_phase_locking.tla`)
+//!
+//! 1. `LockCompatibility`: An exclusive lock is never shared
+//! 2. `StrictTwoPhase`: Locks are released only at commit or abort
+//! 3. `AccessesLocked`: Everything a running transaction touched stays locked
+//! 4. `NoDeadlock`: The waits-for graph stays acyclic
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
+
+use vf_core::invariants::ssi::TxnStatus;
+use vf_core::invariants::TwoPhaseLockingProperties;
+use vf_dst::ssi_harness::{DstTestableSsi, KeyId, TxnId, Value};
+
+/// Lock modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+/// Outcome of a lock request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LockRequest {
+    Granted,
+    Waiting,
+    DeadlockVictim,
+}
+
+/// Transaction state.
+#[derive(Debug, Clone)]
+struct TxnState {
+    status: TxnStatus,
+    read_set: BTreeSet<KeyId>,
+    /// Buffered writes, applied at commit.
+    writes: BTreeMap<KeyId, Value>,
+    /// Transactions whose locks the pending request waits on.
+    waits_for: BTreeSet<TxnId>,
+}
+
+impl TxnState {
+    fn new() -> Self {
+        Self {
+            status: TxnStatus::Active,
+            read_set: BTreeSet::new(),
+            writes: BTreeMap::new(),
+            waits_for: BTreeSet::new(),
+        }
+    }
+}
+
+/// All 2PL state in a single lock, like `SsiStore`.
+#[derive(Debug)]
+struct LockingInner {
+    next_txn: TxnId,
+    txns: HashMap<TxnId, TxnState>,
+    /// Shared locks: Key -> holders.
+    shared: HashMap<KeyId, BTreeSet<TxnId>>,
+    /// Exclusive locks: Key -> holder.
+    exclusive: HashMap<KeyId, TxnId>,
+    /// Committed data.
+    data: HashMap<KeyId, Value>,
+    committed: HashSet<TxnId>,
+    deadlocks: u64,
+}
+
+impl LockingInner {
+    fn new() -> Self {
+        Self {
+            next_txn: 1,
+            txns: HashMap::new(),
+            shared: HashMap::new(),
+            exclusive: HashMap::new(),
+            data: HashMap::new(),
+            committed: HashSet::new(),
+            deadlocks: 0,
+        }
+    }
+
+    fn is_active(&self, txn: TxnId) -> bool {
+        self.txns
+            .get(&txn)
+            .is_some_and(|s| s.status == TxnStatus::Active)
+    }
+
+    /// Other transactions holding locks on `key` that conflict with `mode`.
+    fn blockers(&self, txn: TxnId, key: KeyId, mode: LockMode) -> BTreeSet<TxnId> {
+        let mut blockers: BTreeSet<TxnId> = self.exclusive.get(&key).copied().into_iter().collect();
+        if mode == LockMode::Exclusive {
+            if let Some(holders) = self.shared.get(&key) {
+                blockers.extend(holders);
+            }
+        }
+        blockers.remove(&txn);
+        blockers
+    }
+
+    /// Whether `from` reaches `to` in the waits-for graph.
+    fn waits_transitively(&self, from: TxnId, to: TxnId) -> bool {
+        let mut stack = vec![from];
+        let mut seen = HashSet::new();
+        while let Some(txn) = stack.pop() {
+            if txn == to {
+                return true;
+            }
+            if seen.insert(txn) {
+                if let Some(state) = self.txns.get(&txn) {
+                    stack.extend(&state.waits_for);
+                }
+            }
+        }
+        false
+    }
+
+    fn acquire(&mut self, txn: TxnId, key: KeyId, mode: LockMode) -> LockRequest {
+        debug_assert!(self.is_active(txn), "Only running transactions take locks");
+
+        let blockers = self.blockers(txn, key, mode);
+        if blockers.is_empty() {
+            match mode {
+                LockMode::Shared => {
+                    self.shared.entry(key).or_default().insert(txn);
+                }
+                LockMode::Exclusive => {
+                    self.exclusive.insert(key, txn);
+                }
+            }
+            if let Some(state) = self.txns.get_mut(&txn) {
+                state.waits_for.clear();
+            }
+            return LockRequest::Granted;
+        }
+
+        // Waiting on a transaction that (transitively) waits on us deadlocks
+        if blockers.iter().any(|b| self.waits_transitively(*b, txn)) {
+            self.deadlocks += 1;
+            self.finish(txn, TxnStatus::Aborted);
+            return LockRequest::DeadlockVictim;
+        }
+
+        if let Some(state) = self.txns.get_mut(&txn) {
+            state.waits_for = blockers;
+        }
+        LockRequest::Waiting
+    }
+
+    /// End a transaction, releasing its locks and its waiters.
+    fn finish(&mut self, txn: TxnId, status: TxnStatus) {
+        debug_assert!(status != TxnStatus::Active);
+
+        let Some(state) = self.txns.get_mut(&txn) else {
+            return;
+        };
+        state.status = status;
+        state.waits_for.clear();
+        let writes = std::mem::take(&mut state.writes);
+
+        if status == TxnStatus::Committed {
+            self.data.extend(writes);
+            self.committed.insert(txn);
+        }
+
+        for holders in self.shared.values_mut() {
+            holders.remove(&txn);
+        }
+        self.shared.retain(|_, holders| !holders.is_empty());
+        self.exclusive.retain(|_, holder| *holder != txn);
+        for other in self.txns.values_mut() {
+            other.waits_for.remove(&txn);
+        }
+    }
+}
+
+/// Strict 2PL store.
+///
+/// Thread-safe storage with lock-based concurrency control.
+pub struct TwoPhaseLockingStore {
+    inner: Mutex<LockingInner>,
+}
+
+impl TwoPhaseLockingStore {
+    /// Create a new 2PL store.
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(LockingInner::new()),
+        }
+    }
+
+    /// Number of transactions aborted as deadlock victims.
+    pub fn deadlocks_count(&self) -> u64 {
+        self.inner.lock().unwrap().deadlocks
+    }
+}
+
+impl Default for TwoPhaseLockingStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DstTestableSsi for TwoPhaseLockingStore {
+    fn begin(&self) -> TxnId {
+        let mut inner = self.inner.lock().unwrap();
+
+        let txn = inner.next_txn;
+        inner.next_txn += 1;
+        inner.txns.insert(txn, TxnState::new());
+
+        txn
+    }
+
+    fn read(&self, txn: TxnId, key: KeyId) -> Option<Value> {
+        let mut inner = self.inner.lock().unwrap();
+
+        if !inner.is_active(txn) {
+            return None;
+        }
+        if inner.acquire(txn, key, LockMode::Shared) != LockRequest::Granted {
+            return None;
+        }
+
+        let state = inner.txns.get_mut(&txn)?;
+        state.read_set.insert(key);
+        let own_write = state.writes.get(&key).copied();
+        own_write.or_else(|| inner.data.get(&key).copied())
+    }
+
+    fn write(&self, txn: TxnId, key: KeyId, value: Value) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        if !inner.is_active(txn) {
+            return false;
+        }
+        if inner.acquire(txn, key, LockMode::Exclusive) != LockRequest::Granted {
+            return false;
+        }
+
+        if let Some(state) = inner.txns.get_mut(&txn) {
+            state.writes.insert(key, value);
+        }
+        true
+    }
+
+    fn commit(&self, txn: TxnId) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        if !inner.is_active(txn) {
+            return false;
+        }
+        // Every access is already locked: nothing left to conflict with
+        inner.finish(txn, TxnStatus::Committed);
+        true
+    }
+
+    fn abort(&self, txn: TxnId) {
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.is_active(txn) {
+            inner.finish(txn, TxnStatus::Aborted);
+        }
+    }
+
+    fn is_active(&self, txn: TxnId) -> bool {
+        self.inner.lock().unwrap().is_active(txn)
+    }
+
+    fn committed_txns(&self) -> HashSet<TxnId> {
+        self.inner.lock().unwrap().committed.clone()
+    }
+
+    fn get_current_value(&self, key: KeyId) -> Option<Value> {
+        self.inner.lock().unwrap().data.get(&key).copied()
+    }
+
+    fn is_waiting(&self, txn: TxnId) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .txns
+            .get(&txn)
+            .is_some_and(|s| !s.waits_for.is_empty())
+    }
+}
+
+impl TwoPhaseLockingProperties for TwoPhaseLockingStore {
+    fn txn_statuses(&self) -> HashMap<u64, TxnStatus> {
+        let inner = self.inner.lock().unwrap();
+        inner.txns.iter().map(|(t, s)| (*t, s.status)).collect()
+    }
+
+    fn shared_locks(&self) -> HashMap<u64, HashSet<u64>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .shared
+            .iter()
+            .map(|(k, holders)| (*k, holders.iter().copied().collect()))
+            .collect()
+    }
+
+    fn exclusive_locks(&self) -> HashMap<u64, HashSet<u64>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .exclusive
+            .iter()
+            .map(|(k, holder)| (*k, HashSet::from([*holder])))
+            .collect()
+    }
+
+    fn read_sets(&self) -> HashMap<u64, HashSet<u64>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .iter()
+            .map(|(t, s)| (*t, s.read_set.iter().copied().collect()))
+            .collect()
+    }
+
+    fn write_sets(&self) -> HashMap<u64, HashSet<u64>> {
+        // Committed writes are applied and dropped; only running
+        // transactions' writes are constrained by the spec
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .iter()
+            .map(|(t, s)| (*t, s.writes.keys().copied().collect()))
+            .collect()
+    }
+
+    fn waits_for(&self) -> HashMap<u64, HashSet<u64>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .txns
+            .iter()
+            .map(|(t, s)| (*t, s.waits_for.iter().copied().collect()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vf_core::invariants::TwoPhaseLockingPropertyChecker;
+    use vf_core::PropertyChecker;
+
+    fn assert_invariants(store: &TwoPhaseLockingStore) {
+        for result in TwoPhaseLockingPropertyChecker::new(store).check_all() {
+            assert!(result.holds, "{}: {:?}", result.name, result.violation);
+        }
+    }
+
+    #[test]
+    fn test_reader_blocks_writer_until_commit() {
+        let store = TwoPhaseLockingStore::new();
+
+        let t1 = store.begin();
+        assert_eq!(store.read(t1, 1), None);
+
+        // T2 waits on T1's shared lock
+        let t2 = store.begin();
+        assert!(!store.write(t2, 1, 200));
+        assert!(store.is_waiting(t2));
+        assert!(store.is_active(t2));
+        assert_invariants(&store);
+
+        // T1 finishes; the retried write goes through
+        assert!(store.commit(t1));
+        assert!(!store.is_waiting(t2));
+        assert!(store.write(t2, 1, 200));
+        assert!(store.commit(t2));
+        assert_eq!(store.get_current_value(1), Some(200));
+        assert_invariants(&store);
+    }
+
+    #[test]
+    fn test_lock_upgrade_and_own_writes() {
+        let store = TwoPhaseLockingStore::new();
+
+        let t1 = store.begin();
+        assert_eq!(store.read(t1, 1), None);
+        assert!(store.write(t1, 1, 10));
+        assert_eq!(store.read(t1, 1), Some(10));
+        assert_invariants(&store);
+
+        // Uncommitted writes are neither visible nor readable
+        let t2 = store.begin();
+        assert_eq!(store.read(t2, 1), None);
+        assert!(store.is_waiting(t2));
+        assert_eq!(store.get_current_value(1), None);
+
+        store.abort(t1);
+        assert_eq!(store.read(t2, 1), None);
+        assert!(!store.is_waiting(t2));
+        assert_invariants(&store);
+    }
+
+    #[test]
+    fn test_deadlock_aborts_requester() {
+        let store = TwoPhaseLockingStore::new();
+
+        let t1 = store.begin();
+        let t2 = store.begin();
+        assert!(store.write(t1, 1, 10));
+        assert!(store.write(t2, 2, 20));
+
+        // T1 waits on T2, then T2 would wait on T1
+        assert!(!store.write(t1, 2, 11));
+        assert!(store.is_waiting(t1));
+        assert!(!store.write(t2, 1, 21));
+        assert!(!store.is_active(t2), "T2 should be the deadlock victim");
+        assert_eq!(store.deadlocks_count(), 1);
+        assert_invariants(&store);
+
+        // The victim's locks are released, so T1 proceeds
+        assert!(store.write(t1, 2, 11));
+        assert!(store.commit(t1));
+        assert_eq!(store.get_current_value(2), Some(11));
+    }
+
+    #[test]
+    fn test_write_skew_is_serialized() {
+        let store = TwoPhaseLockingStore::new();
+
+        let t1 = store.begin();
+        let t2 = store.begin();
+        store.read(t1, 1);
+        store.read(t1, 2);
+        store.read(t2, 1);
+        store.read(t2, 2);
+
+        // Each writer waits on the other's shared lock: one must die
+        assert!(!store.write(t1, 1, 10));
+        assert!(!store.write(t2, 2, 20));
+        assert!(!store.is_active(t2));
+        assert!(store.write(t1, 1, 10));
+        assert!(store.commit(t1));
+        assert_eq!(store.committed_txns(), HashSet::from([t1]));
+    }
+}
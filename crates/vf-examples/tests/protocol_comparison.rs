@@ -0,0 +1,225 @@
+//! Compares the transaction protocols under the same DST harness.
+//!
+//! `SsiStore`, `TwoPhaseLockingStore` and `MvccStore` run the same
+//! scenarios with the same seed, and `elle` classifies each history.
+//! Random DST runs then check each store against its own spec:
+//!
+//! - `specs/ssi/two_phase_locking.tla`: LockCompatibility, StrictTwoPhase,
+//!   AccessesLocked, NoDeadlock
+//! - `specs/ssi/snapshot_isolation.tla`: FirstCommitterWins, SnapshotRead,
+//!   CommitAfterSnapshot
+
+use vf_core::invariants::elle::IsolationLevel;
+use vf_core::invariants::{SnapshotIsolationPropertyChecker, TwoPhaseLockingPropertyChecker};
+use vf_core::PropertyChecker;
+use vf_dst::ssi_harness::{
+    run_ssi_protocol, DstSsiOp, DstTestableSsi, SsiDstRunner, SsiProtocolRun, TxnId,
+};
+use vf_dst::DeterministicRng;
+use vf_examples::{MvccStore, SsiStore, TwoPhaseLockingStore};
+
+/// Seed under which the scenarios below see no injected faults.
+const SCENARIO_SEED: u64 = 42;
+
+/// Get seed from environment or generate random one.
+fn get_or_generate_seed() -> u64 {
+    match std::env::var("DST_SEED") {
+        Ok(s) => {
+            let seed: u64 = s.parse().expect("DST_SEED must be a valid u64");
+            println!("DST_SEED={} (from environment)", seed);
+            seed
+        }
+        Err(_) => {
+            let seed = rand::random::<u64>();
+            println!("DST_SEED={} (randomly generated)", seed);
+            seed
+        }
+    }
+}
+
+/// Run a scenario against all three protocols.
+fn run_all(ops: Vec<(TxnId, DstSsiOp)>) -> [SsiProtocolRun; 3] {
+    let runs = [
+        run_ssi_protocol("ssi", SsiStore::new(), SCENARIO_SEED, ops.clone()),
+        run_ssi_protocol(
+            "2pl",
+            TwoPhaseLockingStore::new(),
+            SCENARIO_SEED,
+            ops.clone(),
+        ),
+        run_ssi_protocol("si", MvccStore::new(), SCENARIO_SEED, ops),
+    ];
+    for run in &runs {
+        println!("{}", run.format());
+        assert_eq!(run.stats.faults_injected, 0, "{}", run.format());
+    }
+    runs
+}
+
+#[test]
+fn test_write_skew_separates_protocols() {
+    // Both transactions read both keys and each writes a different one
+    let ops = vec![
+        (1, DstSsiOp::Begin),
+        (1, DstSsiOp::Write(1, 1)),
+        (1, DstSsiOp::Write(2, 2)),
+        (1, DstSsiOp::Commit),
+        (2, DstSsiOp::Begin),
+        (3, DstSsiOp::Begin),
+        (2, DstSsiOp::Read(1)),
+        (2, DstSsiOp::Read(2)),
+        (3, DstSsiOp::Read(1)),
+        (3, DstSsiOp::Read(2)),
+        (2, DstSsiOp::Write(1, 3)),
+        (3, DstSsiOp::Write(2, 4)),
+        (2, DstSsiOp::Commit),
+        (3, DstSsiOp::Commit),
+    ];
+
+    let [ssi, locking, si] = run_all(ops);
+
+    assert!(ssi.isolation.satisfies(IsolationLevel::Serializable));
+    assert!(locking.isolation.satisfies(IsolationLevel::Serializable));
+    // 2PL blocks the first write and picks a deadlock victim on the second
+    assert!(locking.stats.lock_waits > 0);
+    assert!(locking.stats.txns_aborted > 0);
+
+    // SI commits both: snapshot isolated, but not serializable
+    assert_eq!(si.stats.txns_committed, 3);
+    assert!(si.isolation.satisfies(IsolationLevel::SnapshotIsolation));
+    assert!(!si.isolation.satisfies(IsolationLevel::Serializable));
+}
+
+#[test]
+fn test_lost_update_prevented_by_all() {
+    // Both transactions read the key and then overwrite it
+    let ops = vec![
+        (1, DstSsiOp::Begin),
+        (2, DstSsiOp::Begin),
+        (1, DstSsiOp::Read(1)),
+        (2, DstSsiOp::Read(1)),
+        (1, DstSsiOp::Write(1, 1)),
+        (2, DstSsiOp::Write(1, 2)),
+        (1, DstSsiOp::Commit),
+        (2, DstSsiOp::Commit),
+    ];
+
+    // SSI refuses the second write, 2PL makes it a deadlock victim and SI
+    // aborts it at commit; either way only one update survives
+    for run in run_all(ops) {
+        assert!(
+            run.isolation.satisfies(IsolationLevel::Serializable),
+            "{}",
+            run.format()
+        );
+    }
+}
+
+#[test]
+fn test_phantom_write_skew_separates_protocols() {
+    // Both transactions scan an empty range and insert into it
+    let ops = vec![
+        (1, DstSsiOp::Begin),
+        (2, DstSsiOp::Begin),
+        (1, DstSsiOp::Scan(1, 10)),
+        (2, DstSsiOp::Scan(1, 10)),
+        (1, DstSsiOp::Write(5, 1)),
+        (2, DstSsiOp::Write(7, 2)),
+        (1, DstSsiOp::Commit),
+        (2, DstSsiOp::Commit),
+    ];
+
+    let [ssi, locking, si] = run_all(ops);
+
+    // SSI's range SIREAD locks and 2PL's per-key read locks both catch it
+    assert!(ssi.isolation.satisfies(IsolationLevel::Serializable));
+    assert!(ssi.stats.txns_aborted > 0);
+    assert!(locking.isolation.satisfies(IsolationLevel::Serializable));
+
+    // SI commits both inserts: neither saw the other's
+    assert_eq!(si.stats.txns_committed, 2);
+    assert!(!si.isolation.satisfies(IsolationLevel::Serializable));
+}
+
+/// Drive a store with random interleaved transactions over few keys.
+///
+/// Values are unique so the isolation checker can tell every version apart.
+fn random_run<S: DstTestableSsi>(runner: &mut SsiDstRunner<S>, seed: u64) {
+    let mut rng = DeterministicRng::new(seed);
+    let mut active_txns = Vec::new();
+    let mut next_value = 1;
+
+    for _ in 0..200 {
+        if active_txns.len() < 2 || rng.gen_bool(0.2) {
+            if let Ok(txn) = runner.begin() {
+                active_txns.push(txn);
+            }
+            continue;
+        }
+
+        let idx = rng.gen_range(0..active_txns.len());
+        let txn = active_txns[idx];
+        let key = rng.gen_range(1..4u64);
+        match rng.gen_range(0..10u32) {
+            0..=3 => {
+                let _ = runner.read(txn, key);
+            }
+            4..=6 => {
+                let _ = runner.write(txn, key, next_value);
+                next_value += 1;
+            }
+            7..=8 => {
+                active_txns.remove(idx);
+                let _ = runner.commit(txn);
+            }
+            _ => {
+                active_txns.remove(idx);
+                runner.abort(txn);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_two_phase_locking_invariants_after_dst_run() {
+    let seed = get_or_generate_seed();
+    let mut runner = SsiDstRunner::new(TwoPhaseLockingStore::new(), seed);
+    random_run(&mut runner, seed);
+    println!("{}", runner.stats().format());
+
+    for result in TwoPhaseLockingPropertyChecker::new(runner.ssi())
+        .with_seed(seed)
+        .check_all()
+    {
+        assert!(result.holds, "{}: {:?}", result.name, result.violation);
+    }
+    let report = vf_core::invariants::elle::check(&runner.to_txn_history());
+    assert!(
+        report.satisfies(IsolationLevel::Serializable),
+        "2PL history is not serializable (weakest violated: {:?}, seed {})",
+        report.weakest_violated,
+        seed
+    );
+}
+
+#[test]
+fn test_snapshot_isolation_invariants_after_dst_run() {
+    let seed = get_or_generate_seed();
+    let mut runner = SsiDstRunner::new(MvccStore::new(), seed);
+    random_run(&mut runner, seed);
+    println!("{}", runner.stats().format());
+
+    for result in SnapshotIsolationPropertyChecker::new(runner.ssi())
+        .with_seed(seed)
+        .check_all()
+    {
+        assert!(result.holds, "{}: {:?}", result.name, result.violation);
+    }
+    let report = vf_core::invariants::elle::check(&runner.to_txn_history());
+    assert!(
+        report.satisfies(IsolationLevel::SnapshotIsolation),
+        "MVCC history is not snapshot isolated (weakest violated: {:?}, seed {})",
+        report.weakest_violated,
+        seed
+    );
+}
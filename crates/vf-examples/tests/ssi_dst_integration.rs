@@ -7,7 +7,9 @@
 
 use std::collections::HashSet;
 
-use vf_dst::ssi_harness::{DstSsiOp, DstTestableSsi, SsiDstRunner, run_ssi_scenario};
+use vf_core::invariants::elle::{check, IsolationLevel};
+use vf_dst::{DeterministicRng, FaultConfig};
+use vf_dst::ssi_harness::{DstSsiOp, DstTestableSsi, SsiDstRunner, SsiOperation, run_ssi_scenario};
 use vf_dst::ssi_oracle::{SsiOracleTrace, replay_ssi_oracle, run_all_ssi_oracles};
 use vf_examples::SsiStore;
 
@@ -44,9 +46,9 @@ fn test_ssi_store_with_dst_runner() {
         match op_type {
             0 => {
                 // Begin new transaction
-                match runner.begin() {
-                    Ok(txn) => active_txns.push(txn),
-                    Err(_) => {} // Fault injected, expected
+                // Err means a fault was injected, which is expected
+                if let Ok(txn) = runner.begin() {
+                    active_txns.push(txn);
                 }
             }
             1 => {
@@ -290,6 +292,259 @@ fn test_ssi_invariants_after_dst_run() {
     println!("SSI invariants verified: {}", runner.stats().format());
 }
 
+#[test]
+fn test_ssi_store_isolation_after_dst_run() {
+    let seed = get_or_generate_seed();
+    let store = SsiStore::new();
+    let mut runner = SsiDstRunner::new(store, seed);
+    let mut rng = DeterministicRng::new(seed);
+
+    // Interleave several transactions over few keys; values are unique so
+    // the isolation checker can tell every version apart
+    let mut active_txns = Vec::new();
+    let mut next_value = 1;
+
+    for _ in 0..200 {
+        if active_txns.len() < 2 || rng.gen_bool(0.2) {
+            if let Ok(txn) = runner.begin() {
+                active_txns.push(txn);
+            }
+            continue;
+        }
+
+        let idx = rng.gen_range(0..active_txns.len());
+        let txn = active_txns[idx];
+        let key = rng.gen_range(1..4u64);
+        match rng.gen_range(0..10u32) {
+            0..=3 => {
+                let _ = runner.read(txn, key);
+            }
+            4..=6 => {
+                let _ = runner.write(txn, key, next_value);
+                next_value += 1;
+            }
+            7..=8 => {
+                active_txns.remove(idx);
+                let _ = runner.commit(txn);
+            }
+            _ => {
+                active_txns.remove(idx);
+                runner.abort(txn);
+            }
+        }
+    }
+
+    let report = check(&runner.to_txn_history());
+    for anomaly in &report.anomalies {
+        println!("  {}", anomaly);
+    }
+    assert!(
+        report.satisfies(IsolationLevel::Serializable),
+        "SsiStore history is not serializable (weakest violated: {:?}, seed {})",
+        report.weakest_violated,
+        seed
+    );
+}
+
+#[test]
+fn test_ssi_read_only_txns_after_dst_run() {
+    let seed = get_or_generate_seed();
+    let store = SsiStore::new();
+    let mut runner = SsiDstRunner::new(store, seed);
+    let mut rng = DeterministicRng::new(seed);
+
+    // Reporting queries scan and read next to read-write transactions
+    let mut active_txns = Vec::new();
+    let mut read_only = HashSet::new();
+    let mut next_value = 1;
+
+    for _ in 0..200 {
+        if active_txns.len() < 2 || rng.gen_bool(0.2) {
+            let begun = match rng.gen_range(0..4u32) {
+                0 => runner.begin_read_only(false),
+                1 => runner.begin_read_only(true),
+                _ => runner.begin(),
+            };
+            if let Ok(txn) = begun {
+                if runner.ssi().snapshot_safety(txn).is_some() {
+                    read_only.insert(txn);
+                }
+                active_txns.push(txn);
+            }
+            continue;
+        }
+
+        let idx = rng.gen_range(0..active_txns.len());
+        let txn = active_txns[idx];
+        let key = rng.gen_range(1..4u64);
+        match rng.gen_range(0..10u32) {
+            0..=2 => {
+                let _ = runner.read(txn, key);
+            }
+            3 => {
+                let _ = runner.scan(txn, 1..=3);
+            }
+            4..=6 if !read_only.contains(&txn) => {
+                let _ = runner.write(txn, key, next_value);
+                next_value += 1;
+            }
+            4..=8 => {
+                active_txns.remove(idx);
+                let _ = runner.commit(txn);
+            }
+            _ if read_only.contains(&txn) => {
+                active_txns.remove(idx);
+                let _ = runner.commit(txn);
+            }
+            _ => {
+                active_txns.remove(idx);
+                runner.abort(txn);
+            }
+        }
+    }
+
+    let safe: HashSet<_> = read_only
+        .iter()
+        .copied()
+        .filter(|&txn| runner.txn_flags(txn).is_some_and(|flags| flags.safe_snapshot))
+        .collect();
+    println!("{} read-only transactions, {} on safe snapshots", read_only.len(), safe.len());
+
+    // Only the store aborts read-only transactions, and never a safe one
+    for op in runner.history() {
+        if let SsiOperation::Abort(txn) = op {
+            assert!(!safe.contains(txn), "safe T{} aborted, seed {}", txn, seed);
+        }
+    }
+
+    // ReadOnlyNoWrites and SafeSnapshotAcyclic among them
+    runner.assert_invariants();
+    let report = check(&runner.to_txn_history());
+    assert!(
+        report.satisfies(IsolationLevel::Serializable),
+        "SsiStore history is not serializable (weakest violated: {:?}, seed {})",
+        report.weakest_violated,
+        seed
+    );
+}
+
+#[test]
+fn test_ssi_vacuum_during_dst_run() {
+    let seed = get_or_generate_seed();
+    // No failures: a transaction leaked by a fault after begin would pin
+    // the vacuum horizon for the rest of the run
+    let store = SsiStore::new();
+    let mut runner = SsiDstRunner::with_fault_config(store, seed, FaultConfig::delays_only());
+    let mut rng = DeterministicRng::new(seed);
+
+    // Short transactions over few keys, so versions pile up quickly
+    let mut active_txns = Vec::new();
+    let mut next_value = 1;
+
+    for _ in 0..1000 {
+        if active_txns.len() < 2 || rng.gen_bool(0.2) {
+            if let Ok(txn) = runner.begin() {
+                active_txns.push(txn);
+            }
+            continue;
+        }
+
+        let idx = rng.gen_range(0..active_txns.len());
+        let txn = active_txns[idx];
+        let key = rng.gen_range(1..4u64);
+        match rng.gen_range(0..10u32) {
+            0..=2 => {
+                let _ = runner.read(txn, key);
+            }
+            3 => {
+                let _ = runner.scan(txn, 1..=3);
+            }
+            4..=6 => {
+                let _ = runner.write(txn, key, next_value);
+                next_value += 1;
+            }
+            7..=8 => {
+                active_txns.remove(idx);
+                let _ = runner.commit(txn);
+            }
+            _ => {
+                active_txns.remove(idx);
+                runner.abort(txn);
+            }
+        }
+    }
+
+    let pruned: usize = runner
+        .history()
+        .iter()
+        .filter_map(|op| match op {
+            SsiOperation::Vacuum(report) => Some(report.versions.len()),
+            _ => None,
+        })
+        .sum();
+    let stats = runner.stats();
+    println!("{}, {} versions pruned", stats.format(), pruned);
+    assert!(stats.vacuums > 0, "no vacuum pass removed anything, seed {}", seed);
+
+    // VacuumKeepsVisibleVersions among them
+    runner.assert_invariants();
+    let report = check(&runner.to_txn_history());
+    assert!(
+        report.satisfies(IsolationLevel::Serializable),
+        "SsiStore history is not serializable (weakest violated: {:?}, seed {})",
+        report.weakest_violated,
+        seed
+    );
+}
+
+#[test]
+fn test_ssi_vacuum_fault_point_configurable() {
+    // Each overwrite of K1 leaves an older version to prune
+    let run = |vacuum_probability| {
+        let config = FaultConfig {
+            vacuum_probability,
+            ..FaultConfig::delays_only()
+        };
+        let mut runner = SsiDstRunner::with_fault_config(SsiStore::new(), 55555, config);
+        for value in 1..=20 {
+            let txn = runner.begin().unwrap();
+            runner.write(txn, 1, value).unwrap();
+            runner.commit(txn).unwrap();
+        }
+        runner.assert_invariants();
+        runner.stats().vacuums
+    };
+
+    assert_eq!(run(0.0), 0, "vacuum ran with the fault point disabled");
+    assert!(run(1.0) > 0, "no vacuum pass removed anything");
+}
+
+#[test]
+fn test_ssi_flags_survive_vacuum() {
+    let store = SsiStore::new();
+    let mut runner = SsiDstRunner::with_fault_config(store, 66666, FaultConfig::none());
+
+    // T1 reads K1 and T2 overwrites it: T1 -rw-> T2
+    let t1 = runner.begin().unwrap();
+    let t2 = runner.begin().unwrap();
+    runner.read(t1, 1).unwrap();
+    runner.write(t2, 1, 100).unwrap();
+    runner.commit(t2).unwrap();
+    runner.commit(t1).unwrap();
+
+    // Nothing is live, so the store forgets both transactions
+    let report = runner.ssi().vacuum();
+    assert_eq!(report.txns, 2);
+    assert_eq!(runner.ssi().get_conflict_flags(t2), (false, false));
+
+    // The runner kept the flags they finished with
+    let flags = runner.txn_flags(t2).unwrap();
+    assert!(flags.in_conflict && !flags.out_conflict);
+    let flags = runner.txn_flags(t1).unwrap();
+    assert!(!flags.in_conflict && flags.out_conflict);
+    runner.assert_invariants();
+}
+
 #[test]
 fn test_ssi_invariants_serial_execution() {
     // Verify invariants hold for a simple serial execution
@@ -313,6 +568,25 @@ fn test_ssi_invariants_serial_execution() {
     }
 }
 
+#[test]
+fn test_ssi_rejected_write_not_recorded() {
+    let store = SsiStore::new();
+    let mut runner = SsiDstRunner::new(store, 77777);
+
+    let t1 = runner.begin().unwrap();
+    let t2 = runner.begin().unwrap();
+    assert!(runner.write(t1, 1, 100).unwrap());
+
+    // T1 holds the write lock on K1, so the store rejects T2's write
+    assert!(!runner.write(t2, 1, 200).unwrap());
+    let t2_writes = runner
+        .history()
+        .iter()
+        .filter(|op| matches!(op, SsiOperation::Write { txn, .. } if *txn == t2))
+        .count();
+    assert_eq!(t2_writes, 0, "a rejected write must not appear in the history");
+}
+
 #[test]
 fn test_ssi_invariants_dangerous_structure_aborted() {
     // Verify that a dangerous structure transaction is aborted,
@@ -420,6 +694,29 @@ fn test_ssi_oracle_replay_read_only() {
     assert_eq!(result.committed_txns.len(), 2, "Both transactions should commit");
 }
 
+#[test]
+fn test_ssi_oracle_replay_phantom_write_skew() {
+    // Neither txn reads a key the other writes: only the range locks see it
+    let oracle = SsiOracleTrace::phantom_write_skew();
+    let store = SsiStore::new();
+    let result = replay_ssi_oracle(store, 12345, &oracle);
+
+    println!("{}", result.format());
+    assert!(result.invariants_hold, "Phantom write skew must not commit");
+    assert!(result.expected_outcome_met, "T1 should abort on the phantom");
+}
+
+#[test]
+fn test_ssi_oracle_replay_disjoint_ranges() {
+    let oracle = SsiOracleTrace::disjoint_ranges();
+    let store = SsiStore::new();
+    let result = replay_ssi_oracle(store, 12345, &oracle);
+
+    println!("{}", result.format());
+    assert!(result.invariants_hold, "Invariants should hold for disjoint ranges");
+    assert_eq!(result.committed_txns.len(), 2, "Both should commit with one rw edge");
+}
+
 #[test]
 fn test_ssi_run_all_oracles() {
     let results = run_all_ssi_oracles(SsiStore::new, 42);
@@ -227,25 +227,6 @@ struct ApiRequest<'a> {
     stream: bool,
 }
 
-/// API response body.
-#[derive(Deserialize)]
-struct ApiResponse {
-    content: Vec<ContentBlock>,
-    #[allow(dead_code)]
-    model: String,
-    #[allow(dead_code)]
-    stop_reason: Option<String>,
-}
-
-/// Content block in response.
-#[derive(Deserialize)]
-struct ContentBlock {
-    #[serde(rename = "type")]
-    content_type: String,
-    #[serde(default)]
-    text: String,
-}
-
 /// Streaming event from SSE response.
 #[derive(Deserialize)]
 struct StreamEvent {
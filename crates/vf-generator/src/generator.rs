@@ -419,7 +419,7 @@ impl CodeGenerator {
             .map_err(GeneratorError::ClientError)?;
 
         extract_code_block(&response)
-            .ok_or_else(|| GeneratorError::NoCodeInResponse(response))
+            .ok_or(GeneratorError::NoCodeInResponse(response))
     }
 
     /// Fix code based on verification failure.
@@ -448,7 +448,7 @@ impl CodeGenerator {
             .map_err(GeneratorError::ClientError)?;
 
         extract_code_block(&response)
-            .ok_or_else(|| GeneratorError::NoCodeInResponse(response))
+            .ok_or(GeneratorError::NoCodeInResponse(response))
     }
 
     /// Request performance improvement while preserving correctness.
@@ -476,7 +476,7 @@ impl CodeGenerator {
             .map_err(GeneratorError::ClientError)?;
 
         extract_code_block(&response)
-            .ok_or_else(|| GeneratorError::NoCodeInResponse(response))
+            .ok_or(GeneratorError::NoCodeInResponse(response))
     }
 
     /// Verify code using the evaluator cascade.
@@ -13,7 +13,7 @@ pub const DST_INSTRUMENTED_TEMPLATE: &str = r#"
 //! DST-instrumented Treiber Stack
 //!
 //! Generated from TLA+ spec: treiber_stack.tla
-//! Invariants: NoLostElements (line 45), NoDuplicates (line 58)
+//! Invariants: NoLostElements (line 66), NoDuplicates (line 75)
 
 use std::collections::HashSet;
 use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
@@ -0,0 +1,273 @@
+//! # vf-macros
+//!
+//! Compile-time binding of Rust property checkers to TLA+ spec lines.
+//!
+//! `#[tla_invariant(spec = "treiber_stack.tla", name = "NoLostElements")]`
+//! finds `treiber_stack.tla` under the workspace's `specs/` directory,
+//! looks up the line where `NoLostElements ==` is defined, and defines
+//! `TLA_LINE` in the annotated function's body:
+//!
+//! ```
+//! use vf_macros::tla_invariant;
+//!
+//! #[tla_invariant(spec = "treiber_stack.tla", name = "NoLostElements")]
+//! fn no_lost_elements_line() -> u32 {
+//!     TLA_LINE
+//! }
+//!
+//! assert!(no_lost_elements_line() > 0);
+//! ```
+//!
+//! The build fails if the spec is missing or no longer defines the
+//! operator, so a renamed invariant cannot leave a stale line behind:
+//!
+//! ```compile_fail
+//! use vf_macros::tla_invariant;
+//!
+//! #[tla_invariant(spec = "treiber_stack.tla", name = "NoSuchInvariant")]
+//! fn missing() -> u32 {
+//!     TLA_LINE
+//! }
+//! ```
+//!
+//! The spec file is included in the build, so editing it re-resolves
+//! every line that refers to it.
+//!
+//! # Specs Directory
+//!
+//! Specs are searched under the directory named by the `VF_SPECS_DIR`
+//! environment variable, resolved against the annotated crate's manifest
+//! directory when relative. Without it, the nearest `specs/` directory
+//! above the crate is used, which only exists in a checkout of the
+//! repository: builds from a packaged or vendored copy of a crate using
+//! `#[tla_invariant]` must set `VF_SPECS_DIR`. Cargo does not track the
+//! variable, so crates built before it changed need a `cargo clean -p`.
+
+use std::path::{Path, PathBuf};
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, ItemFn, LitStr};
+
+/// Directory holding the TLA+ specs, relative to the workspace root.
+const SPECS_DIR: &str = "specs";
+
+/// Environment variable overriding the specs directory.
+const SPECS_DIR_ENV: &str = "VF_SPECS_DIR";
+
+/// Maximum directory depth searched below `specs/`.
+const SPEC_DEPTH_MAX: u32 = 4;
+
+/// Bind a checker function to the line of a TLA+ invariant.
+///
+/// Takes `spec = "<file>.tla"` and `name = "<Operator>"`, and defines
+/// `const TLA_LINE: u32` at the start of the function body.
+#[proc_macro_attribute]
+pub fn tla_invariant(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut spec: Option<LitStr> = None;
+    let mut name: Option<LitStr> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("spec") {
+            spec = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `spec` or `name`"))
+        }
+    });
+    parse_macro_input!(args with parser);
+    let mut function = parse_macro_input!(item as ItemFn);
+
+    let (Some(spec), Some(name)) = (spec, name) else {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "expected #[tla_invariant(spec = \"<file>.tla\", name = \"<Operator>\")]",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let path = match find_spec(&spec.value()) {
+        Ok(path) => path,
+        Err(message) => {
+            return syn::Error::new_spanned(&spec, message)
+                .to_compile_error()
+                .into()
+        }
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            return syn::Error::new_spanned(&spec, format!("cannot read {}: {}", path.display(), e))
+                .to_compile_error()
+                .into()
+        }
+    };
+    let Some(line) = definition_line(&content, &name.value()) else {
+        return syn::Error::new_spanned(
+            &name,
+            format!("{} does not define `{}`", spec.value(), name.value()),
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let path = path.to_string_lossy().into_owned();
+    let body = &function.block;
+    function.block = syn::parse_quote!({
+        const TLA_LINE: u32 = #line;
+        // Rebuild when the spec changes
+        const _: &[u8] = include_bytes!(#path);
+        #body
+    });
+    quote!(#function).into()
+}
+
+/// Find `file` under the specs directory (see the crate docs).
+fn find_spec(file: &str) -> Result<PathBuf, String> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?;
+    let configured = std::env::var_os(SPECS_DIR_ENV).map(PathBuf::from);
+    let specs_dir = specs_dir(configured, Path::new(&manifest_dir))?;
+    find_spec_in(&specs_dir, file)
+}
+
+/// The configured specs directory, or else the nearest `specs/` directory
+/// above `manifest_dir`.
+fn specs_dir(configured: Option<PathBuf>, manifest_dir: &Path) -> Result<PathBuf, String> {
+    if let Some(dir) = configured {
+        let dir = manifest_dir.join(dir);
+        return if dir.is_dir() {
+            Ok(dir)
+        } else {
+            Err(format!("{} is not a directory: {}", SPECS_DIR_ENV, dir.display()))
+        };
+    }
+
+    manifest_dir
+        .ancestors()
+        .map(|dir| dir.join(SPECS_DIR))
+        .find(|dir| dir.is_dir())
+        .ok_or_else(|| {
+            format!(
+                "no {}/ directory above {}; set {} to the specs directory",
+                SPECS_DIR,
+                manifest_dir.display(),
+                SPECS_DIR_ENV
+            )
+        })
+}
+
+fn find_spec_in(specs_dir: &Path, file: &str) -> Result<PathBuf, String> {
+    let mut found = Vec::new();
+    search(specs_dir, file, 0, &mut found);
+    match found.len() {
+        0 => Err(format!("{} not found under {}", file, specs_dir.display())),
+        1 => Ok(found.remove(0)),
+        _ => Err(format!("{} is ambiguous: {:?}", file, found)),
+    }
+}
+
+fn search(dir: &Path, file: &str, depth: u32, found: &mut Vec<PathBuf>) {
+    if depth > SPEC_DEPTH_MAX {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            search(&path, file, depth + 1, found);
+        } else if path.file_name().is_some_and(|n| n == file) {
+            found.push(path);
+        }
+    }
+}
+
+/// 1-based line of the top-level definition `name == ...` or
+/// `name(args) == ...`.
+fn definition_line(content: &str, name: &str) -> Option<u32> {
+    debug_assert!(!name.is_empty(), "Operator name must not be empty");
+
+    content
+        .lines()
+        .position(|line| {
+            let Some(rest) = line.strip_prefix(name) else {
+                return false;
+            };
+            let rest = rest.trim_start();
+            let rest = match rest.strip_prefix('(') {
+                Some(params) => match params.find(')') {
+                    Some(close) => params[close + 1..].trim_start(),
+                    None => return false,
+                },
+                None => rest,
+            };
+            rest.starts_with("==")
+        })
+        .map(|index| index as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = "---- MODULE m ----
+VARIABLES s
+
+NoLost ==
+    s = s
+
+NoLostElements == TRUE
+
+Push(x) == s' = s
+
+  Nested == FALSE
+====";
+
+    #[test]
+    fn test_definition_line() {
+        assert_eq!(definition_line(SPEC, "NoLostElements"), Some(7));
+        assert_eq!(definition_line(SPEC, "NoLost"), Some(4));
+        assert_eq!(definition_line(SPEC, "Push"), Some(9));
+    }
+
+    #[test]
+    fn test_definition_line_missing() {
+        assert_eq!(definition_line(SPEC, "NoLostElement"), None);
+        assert_eq!(definition_line(SPEC, "Nested"), None);
+        assert_eq!(definition_line(SPEC, "s"), None);
+    }
+
+    #[test]
+    fn test_find_spec() {
+        let path = find_spec("treiber_stack.tla").unwrap();
+        assert!(path.ends_with("specs/lockfree/treiber_stack.tla"));
+        assert!(find_spec("no_such_spec.tla").is_err());
+    }
+
+    #[test]
+    fn test_specs_dir() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let searched = specs_dir(None, manifest_dir).unwrap();
+        assert!(searched.ends_with(SPECS_DIR));
+
+        // Relative to the manifest directory, or absolute
+        let configured = specs_dir(Some(PathBuf::from("../../specs/lockfree")), manifest_dir);
+        let configured = configured.unwrap();
+        assert!(find_spec_in(&configured, "treiber_stack.tla").is_ok());
+        assert!(find_spec_in(&configured, "two_phase_locking.tla").is_err());
+        assert_eq!(
+            specs_dir(Some(configured.clone()), Path::new("/nonexistent")).unwrap(),
+            configured
+        );
+
+        let error = specs_dir(Some(PathBuf::from("no_such_dir")), manifest_dir).unwrap_err();
+        assert!(error.starts_with(SPECS_DIR_ENV), "{}", error);
+        let error = specs_dir(None, Path::new("/")).unwrap_err();
+        assert!(error.contains(SPECS_DIR_ENV), "{}", error);
+    }
+}
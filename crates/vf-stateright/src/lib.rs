@@ -30,14 +30,23 @@
 //! ## Modules
 //!
 //! - `treiber_stack`: Lock-free Treiber Stack (CAS-based)
+//! - `ms_queue`: Lock-free Michael-Scott Queue (CAS-based, with helping)
 //! - `ssi`: Serializable Snapshot Isolation (lock-based transactions)
+//! - `two_phase_locking`: Strict two-phase locking with deadlock detection
+//! - `snapshot_isolation`: Multi-version snapshot isolation (first-committer-wins)
 
+pub mod ms_queue;
 pub mod oracle;
+pub mod snapshot_isolation;
 pub mod ssi;
 pub mod treiber_stack;
+pub mod two_phase_locking;
 pub mod verifier;
 
+pub use ms_queue::{QueueAction, QueueModel, QueueState};
 pub use oracle::{Oracle, OracleAction, OracleActionType, OracleCategory, OracleExtractor};
+pub use snapshot_isolation::{MvccAction, MvccModel, MvccState, ReadRecord};
 pub use ssi::{SsiAction, SsiOracle, SsiOracleCategory, SsiOracleExtractor, SsiState, TxnId, TxnStatus};
 pub use treiber_stack::{StackAction, StackModel, StackState};
+pub use two_phase_locking::{LockMode, LockingAction, LockingModel, LockingState};
 pub use verifier::{VerifiableStack, VerificationResult, VerifierConfig, verify_implementation};
@@ -0,0 +1,579 @@
+//! Stateright model for the Michael-Scott queue.
+//!
+//! This model mirrors `specs/lockfree/ms_queue.tla` and can be used
+//! for exhaustive state space exploration.
+
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+use stateright::Model;
+use vf_core::StateSnapshot;
+
+/// Unique identifier for a node.
+pub type NodeId = u64;
+
+/// Unique identifier for a thread.
+pub type ThreadId = u64;
+
+/// Id of the dummy node allocated by `Init`.
+const DUMMY_NODE_ID: NodeId = 0;
+
+/// A node in the queue. The dummy node has no value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Node {
+    pub value: Option<u64>,
+    pub next: Option<NodeId>,
+}
+
+/// Thread-local state for ongoing operations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ThreadState {
+    Idle,
+    EnqAllocated {
+        node_id: NodeId,
+        value: u64,
+    },
+    EnqReadTail {
+        node_id: NodeId,
+        value: u64,
+        observed_tail: NodeId,
+        observed_next: Option<NodeId>,
+    },
+    EnqLinked {
+        node_id: NodeId,
+        observed_tail: NodeId,
+    },
+    DeqRead {
+        observed_head: NodeId,
+        observed_tail: NodeId,
+        observed_next: Option<NodeId>,
+    },
+}
+
+/// State of the Michael-Scott queue model.
+///
+/// Mirrors the TLA+ spec's state variables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueueState {
+    /// Pointer to the dummy node (corresponds to TLA+ `head`)
+    pub head: NodeId,
+    /// Pointer to the last or second-to-last node (corresponds to TLA+ `tail`)
+    pub tail: NodeId,
+    /// All nodes in the system (corresponds to TLA+ `nodes`)
+    pub nodes: BTreeMap<NodeId, Node>,
+    /// Counter for allocating new node IDs
+    pub node_id_next: NodeId,
+    /// Elements in enqueue linearization order (corresponds to TLA+ `enqueued`)
+    pub enqueued: Vec<u64>,
+    /// Elements in dequeue linearization order (corresponds to TLA+ `dequeued`)
+    pub dequeued: Vec<u64>,
+    /// Thread states
+    pub threads: BTreeMap<ThreadId, ThreadState>,
+}
+
+impl QueueState {
+    /// Create initial state with given number of threads.
+    pub fn new(threads_count: u64) -> Self {
+        debug_assert!(threads_count > 0, "Must have at least one thread");
+        debug_assert!(
+            threads_count <= 8,
+            "Model checking with many threads is slow"
+        );
+
+        let mut threads = BTreeMap::new();
+        for tid in 0..threads_count {
+            threads.insert(tid, ThreadState::Idle);
+        }
+
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            DUMMY_NODE_ID,
+            Node {
+                value: None,
+                next: None,
+            },
+        );
+
+        Self {
+            head: DUMMY_NODE_ID,
+            tail: DUMMY_NODE_ID,
+            nodes,
+            node_id_next: DUMMY_NODE_ID + 1,
+            enqueued: Vec::new(),
+            dequeued: Vec::new(),
+            threads,
+        }
+    }
+
+    /// Get current queue contents (front to back) by traversing from
+    /// the dummy node's successor.
+    pub fn contents(&self) -> Vec<u64> {
+        let mut result = Vec::new();
+        let mut current = self.nodes.get(&self.head).and_then(|n| n.next);
+
+        while let Some(node_id) = current {
+            match self.nodes.get(&node_id) {
+                Some(node) => {
+                    if let Some(value) = node.value {
+                        result.push(value);
+                    }
+                    current = node.next;
+                }
+                None => break,
+            }
+        }
+
+        result
+    }
+
+    /// Capture the state as a snapshot in TLA+ value syntax, so the
+    /// spec's own operators can be evaluated against it.
+    pub fn snapshot(&self, step: u64, description: &str) -> StateSnapshot {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|(id, node)| {
+                format!(
+                    "{} :> [value |-> {}, next |-> {}]",
+                    id,
+                    node.value.map_or("NULL".to_string(), |v| v.to_string()),
+                    node.next.map_or("NULL".to_string(), |n| n.to_string()),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" @@ ");
+
+        StateSnapshot {
+            step,
+            description: description.to_string(),
+            variables: vec![
+                ("head".to_string(), self.head.to_string()),
+                ("tail".to_string(), self.tail.to_string()),
+                ("nodes".to_string(), format!("({})", nodes)),
+                ("next_node_id".to_string(), self.node_id_next.to_string()),
+                ("enqueued".to_string(), format_sequence(&self.enqueued)),
+                ("dequeued".to_string(), format_sequence(&self.dequeued)),
+            ],
+        }
+    }
+
+    // ========== Invariants (from TLA+ spec) ==========
+
+    /// Line 80: NoLostElements
+    ///
+    /// Every element that was enqueued is either in the queue or was dequeued.
+    pub fn no_lost_elements(&self) -> bool {
+        let contents = self.contents();
+        self.enqueued
+            .iter()
+            .all(|e| contents.contains(e) || self.dequeued.contains(e))
+    }
+
+    /// Line 88: NoDuplicates
+    ///
+    /// No element appears twice in the queue or is dequeued twice.
+    pub fn no_duplicates(&self) -> bool {
+        let contents = self.contents();
+        all_distinct(&contents) && all_distinct(&self.dequeued)
+    }
+
+    /// Line 97: FIFO_Order
+    ///
+    /// The i-th successful dequeue returns the i-th enqueued element.
+    pub fn fifo_order(&self) -> bool {
+        self.dequeued.len() <= self.enqueued.len()
+            && self
+                .dequeued
+                .iter()
+                .zip(&self.enqueued)
+                .all(|(d, e)| d == e)
+    }
+
+    /// Line 107: Linearizability
+    ///
+    /// The list holds exactly the enqueued elements not yet dequeued, in order.
+    pub fn linearizability(&self) -> bool {
+        self.dequeued.len() <= self.enqueued.len()
+            && self.contents() == self.enqueued[self.dequeued.len()..]
+    }
+
+    /// Line 115: TailReachable
+    ///
+    /// Tail is reachable from head and lags the last node by at most one link.
+    pub fn tail_reachable(&self) -> bool {
+        let mut current = Some(self.head);
+        while let Some(node_id) = current {
+            if node_id == self.tail {
+                let next = self.nodes.get(&node_id).and_then(|n| n.next);
+                return next
+                    .and_then(|n| self.nodes.get(&n))
+                    .map_or(true, |n| n.next.is_none());
+            }
+            current = self.nodes.get(&node_id).and_then(|n| n.next);
+        }
+        false
+    }
+
+    /// Combined invariant check.
+    pub fn invariants_hold(&self) -> bool {
+        self.no_lost_elements()
+            && self.no_duplicates()
+            && self.fifo_order()
+            && self.linearizability()
+            && self.tail_reachable()
+    }
+
+    fn next_of(&self, node_id: NodeId) -> Option<NodeId> {
+        self.nodes.get(&node_id).and_then(|n| n.next)
+    }
+}
+
+fn all_distinct(values: &[u64]) -> bool {
+    values
+        .iter()
+        .enumerate()
+        .all(|(i, v)| !values[..i].contains(v))
+}
+
+fn format_sequence(values: &[u64]) -> String {
+    let items: Vec<String> = values.iter().map(u64::to_string).collect();
+    format!("<<{}>>", items.join(", "))
+}
+
+/// Actions that threads can take.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum QueueAction {
+    /// Thread allocates a new node for enqueue
+    EnqAlloc { thread: ThreadId, value: u64 },
+    /// Thread reads tail and its successor
+    EnqRead { thread: ThreadId },
+    /// Thread attempts CAS on the last node's next (or helps tail forward)
+    EnqLink { thread: ThreadId },
+    /// Thread attempts CAS to swing tail to its new node
+    EnqSwing { thread: ThreadId },
+    /// Thread reads head, tail and the dummy's successor
+    DeqRead { thread: ThreadId },
+    /// Thread attempts CAS on head (or reports empty, or helps tail)
+    DeqCas { thread: ThreadId },
+}
+
+/// Model for bounded model checking.
+pub struct QueueModel {
+    pub threads_count: u64,
+    pub values: Vec<u64>,
+}
+
+impl QueueModel {
+    /// Create a new model with given parameters.
+    pub fn new(threads_count: u64, values: Vec<u64>) -> Self {
+        debug_assert!(threads_count > 0);
+        debug_assert!(!values.is_empty());
+
+        Self {
+            threads_count,
+            values,
+        }
+    }
+}
+
+impl Model for QueueModel {
+    type State = QueueState;
+    type Action = QueueAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![QueueState::new(self.threads_count)]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        for (&tid, thread_state) in &state.threads {
+            match thread_state {
+                ThreadState::Idle => {
+                    // Values already allocated by any thread are in the system
+                    for &value in &self.values {
+                        let allocated = state.nodes.values().any(|n| n.value == Some(value));
+                        if !allocated {
+                            actions.push(QueueAction::EnqAlloc { thread: tid, value });
+                        }
+                    }
+                    actions.push(QueueAction::DeqRead { thread: tid });
+                }
+                ThreadState::EnqAllocated { .. } => {
+                    actions.push(QueueAction::EnqRead { thread: tid });
+                }
+                ThreadState::EnqReadTail { .. } => {
+                    actions.push(QueueAction::EnqLink { thread: tid });
+                }
+                ThreadState::EnqLinked { .. } => {
+                    actions.push(QueueAction::EnqSwing { thread: tid });
+                }
+                ThreadState::DeqRead { .. } => {
+                    actions.push(QueueAction::DeqCas { thread: tid });
+                }
+            }
+        }
+    }
+
+    fn next_state(&self, state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        let mut next = state.clone();
+
+        match action {
+            QueueAction::EnqAlloc { thread, value } => {
+                let node_id = next.node_id_next;
+                next.node_id_next += 1;
+
+                next.nodes.insert(
+                    node_id,
+                    Node {
+                        value: Some(value),
+                        next: None,
+                    },
+                );
+
+                next.threads
+                    .insert(thread, ThreadState::EnqAllocated { node_id, value });
+            }
+
+            QueueAction::EnqRead { thread } => {
+                if let Some(ThreadState::EnqAllocated { node_id, value }) =
+                    next.threads.get(&thread).cloned()
+                {
+                    let observed_next = next.next_of(next.tail);
+                    next.threads.insert(
+                        thread,
+                        ThreadState::EnqReadTail {
+                            node_id,
+                            value,
+                            observed_tail: next.tail,
+                            observed_next,
+                        },
+                    );
+                }
+            }
+
+            QueueAction::EnqLink { thread } => {
+                if let Some(ThreadState::EnqReadTail {
+                    node_id,
+                    value,
+                    observed_tail,
+                    observed_next,
+                }) = next.threads.get(&thread).cloned()
+                {
+                    if observed_next.is_none() && next.next_of(observed_tail).is_none() {
+                        // CAS on next succeeds: linearization point
+                        if let Some(last) = next.nodes.get_mut(&observed_tail) {
+                            last.next = Some(node_id);
+                        }
+                        next.enqueued.push(value);
+                        next.threads.insert(
+                            thread,
+                            ThreadState::EnqLinked {
+                                node_id,
+                                observed_tail,
+                            },
+                        );
+                    } else {
+                        // Tail was lagging or another enqueue won: help, then retry
+                        if let Some(succ) = observed_next {
+                            if next.tail == observed_tail {
+                                next.tail = succ;
+                            }
+                        }
+                        next.threads
+                            .insert(thread, ThreadState::EnqAllocated { node_id, value });
+                    }
+                }
+            }
+
+            QueueAction::EnqSwing { thread } => {
+                if let Some(ThreadState::EnqLinked {
+                    node_id,
+                    observed_tail,
+                }) = next.threads.get(&thread).cloned()
+                {
+                    // CAS may fail if another thread already helped
+                    if next.tail == observed_tail {
+                        next.tail = node_id;
+                    }
+                    next.threads.insert(thread, ThreadState::Idle);
+                }
+            }
+
+            QueueAction::DeqRead { thread } => {
+                let observed_next = next.next_of(next.head);
+                next.threads.insert(
+                    thread,
+                    ThreadState::DeqRead {
+                        observed_head: next.head,
+                        observed_tail: next.tail,
+                        observed_next,
+                    },
+                );
+            }
+
+            QueueAction::DeqCas { thread } => {
+                if let Some(ThreadState::DeqRead {
+                    observed_head,
+                    observed_tail,
+                    observed_next,
+                }) = next.threads.get(&thread).cloned()
+                {
+                    // A stale snapshot, or a queue that was empty at the
+                    // read, leaves the state unchanged
+                    let succ = observed_next.filter(|_| next.head == observed_head);
+                    if let Some(succ) = succ {
+                        if observed_head == observed_tail {
+                            // Tail is lagging behind head's successor - help
+                            if next.tail == observed_tail {
+                                next.tail = succ;
+                            }
+                        } else {
+                            // CAS on head succeeds: successor becomes the new dummy
+                            next.head = succ;
+                            if let Some(value) = next.nodes.get(&succ).and_then(|n| n.value) {
+                                next.dequeued.push(value);
+                            }
+                        }
+                    }
+                    next.threads.insert(thread, ThreadState::Idle);
+                }
+            }
+        }
+
+        Some(next)
+    }
+
+    fn properties(&self) -> Vec<stateright::Property<Self>> {
+        vec![
+            // covers: QueuePropertyChecker::NoLostElements
+            stateright::Property::always("NoLostElements", |_model: &Self, state: &Self::State| {
+                state.no_lost_elements()
+            }),
+            // covers: QueuePropertyChecker::NoDuplicates
+            stateright::Property::always("NoDuplicates", |_model: &Self, state: &Self::State| {
+                state.no_duplicates()
+            }),
+            // covers: QueuePropertyChecker::FIFO_Order
+            stateright::Property::always("FIFO_Order", |_model: &Self, state: &Self::State| {
+                state.fifo_order()
+            }),
+            // covers: QueuePropertyChecker::Linearizability
+            stateright::Property::always(
+                "Linearizability",
+                |_model: &Self, state: &Self::State| state.linearizability(),
+            ),
+            // covers: ms_queue::TailReachable
+            stateright::Property::always("TailReachable", |_model: &Self, state: &Self::State| {
+                state.tail_reachable()
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stateright::Checker;
+    use std::collections::{HashSet, VecDeque};
+    use vf_core::tla_spec::{parse_module, Evaluator};
+
+    #[test]
+    fn test_initial_state() {
+        let state = QueueState::new(2);
+        assert_eq!(state.head, state.tail);
+        assert_eq!(state.nodes.len(), 1);
+        assert!(state.contents().is_empty());
+        assert!(state.invariants_hold());
+    }
+
+    #[test]
+    fn test_model_checking_small() {
+        let model = QueueModel::new(2, vec![1, 2]);
+
+        model
+            .checker()
+            .threads(1)
+            .spawn_bfs()
+            .join()
+            .assert_properties();
+    }
+
+    #[test]
+    fn test_invariants_agree_with_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/lockfree/ms_queue.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let mut evaluator = Evaluator::new(&module).with_model_constants();
+
+        // Walk every reachable state and evaluate the spec's own operators.
+        let model = QueueModel::new(2, vec![1, 2]);
+        let mut seen = HashSet::new();
+        let mut frontier: VecDeque<QueueState> = model.init_states().into();
+        let mut actions = Vec::new();
+        while let Some(state) = frontier.pop_front() {
+            if !seen.insert(state.clone()) {
+                continue;
+            }
+            evaluator.load_state(&state.snapshot(0, "")).unwrap();
+            assert_eq!(
+                evaluator.check("NoLostElements").unwrap(),
+                state.no_lost_elements()
+            );
+            assert_eq!(
+                evaluator.check("NoDuplicates").unwrap(),
+                state.no_duplicates()
+            );
+            assert_eq!(evaluator.check("FIFO_Order").unwrap(), state.fifo_order());
+            assert_eq!(
+                evaluator.check("Linearizability").unwrap(),
+                state.linearizability()
+            );
+            assert_eq!(
+                evaluator.check("TailReachable").unwrap(),
+                state.tail_reachable()
+            );
+
+            actions.clear();
+            model.actions(&state, &mut actions);
+            for action in actions.drain(..) {
+                frontier.extend(model.next_state(&state, action));
+            }
+        }
+        assert!(seen.len() > 100, "explored {} states", seen.len());
+    }
+
+    #[test]
+    fn test_broken_link_detected() {
+        // Element 2 is enqueued but the link to it was dropped.
+        let mut state = QueueState::new(1);
+        state.nodes.insert(
+            1,
+            Node {
+                value: Some(1),
+                next: None,
+            },
+        );
+        state.nodes.get_mut(&0).unwrap().next = Some(1);
+        state.node_id_next = 3;
+        state.enqueued = vec![1, 2];
+
+        assert!(!state.no_lost_elements());
+        assert!(!state.linearizability());
+        assert!(state.fifo_order());
+    }
+
+    #[test]
+    #[ignore] // Slower test, run with --ignored
+    fn test_model_checking_medium() {
+        let model = QueueModel::new(3, vec![1, 2, 3]);
+
+        model
+            .checker()
+            .threads(num_cpus::get())
+            .spawn_bfs()
+            .join()
+            .assert_properties();
+    }
+}
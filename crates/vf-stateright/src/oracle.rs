@@ -127,7 +127,7 @@ impl Oracle {
 
     /// Format as a human-readable trace.
     pub fn format_trace(&self) -> String {
-        let mut output = format!("Oracle: {} [{}]\n", self.name, format!("{:?}", self.category));
+        let mut output = format!("Oracle: {} [{:?}]\n", self.name, self.category);
         output.push_str(&format!("Description: {}\n", self.description));
         output.push_str("Trace:\n");
 
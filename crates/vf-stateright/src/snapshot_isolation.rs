@@ -0,0 +1,418 @@
+//! Stateright model for multi-version snapshot isolation.
+//!
+//! This model mirrors `specs/ssi/snapshot_isolation.tla` and can be used
+//! for exhaustive state space exploration.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::Hash;
+
+use stateright::Model;
+use vf_core::StateSnapshot;
+
+use crate::ssi::{KeyId, Timestamp, TxnId, TxnStatus};
+use crate::two_phase_locking::{format_function, format_set_function, format_status_function};
+
+/// A read and the writer of the version it saw (`None` = initial value).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReadRecord {
+    pub key: KeyId,
+    pub from: Option<TxnId>,
+}
+
+/// State of the snapshot isolation model.
+///
+/// Mirrors the TLA+ spec's state variables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MvccState {
+    /// Transaction status (`NotStarted` is the spec's `"idle"`)
+    pub txn_status: BTreeMap<TxnId, TxnStatus>,
+    /// Timestamp of the latest commit
+    pub clock: Timestamp,
+    /// Txn -> timestamp its reads see
+    pub snapshot: BTreeMap<TxnId, Timestamp>,
+    /// Txn -> commit timestamp (0 until committed)
+    pub commit_ts: BTreeMap<TxnId, Timestamp>,
+    /// Txn -> keys written
+    pub writes: BTreeMap<TxnId, BTreeSet<KeyId>>,
+    /// Txn -> reads performed
+    pub reads: BTreeMap<TxnId, BTreeSet<ReadRecord>>,
+}
+
+impl MvccState {
+    /// Create initial state with given transaction set.
+    pub fn new(txns: &[TxnId]) -> Self {
+        Self {
+            txn_status: txns.iter().map(|&t| (t, TxnStatus::NotStarted)).collect(),
+            clock: 0,
+            snapshot: txns.iter().map(|&t| (t, 0)).collect(),
+            commit_ts: txns.iter().map(|&t| (t, 0)).collect(),
+            writes: txns.iter().map(|&t| (t, BTreeSet::new())).collect(),
+            reads: txns.iter().map(|&t| (t, BTreeSet::new())).collect(),
+        }
+    }
+
+    fn committed(&self) -> impl Iterator<Item = TxnId> + '_ {
+        self.txn_status
+            .iter()
+            .filter(|(_, &s)| s == TxnStatus::Committed)
+            .map(|(&t, _)| t)
+    }
+
+    /// Writer of the version of `key` visible at `ts` (`None` = initial value).
+    pub fn visible(&self, key: KeyId, ts: Timestamp) -> Option<TxnId> {
+        self.committed()
+            .filter(|w| self.writes[w].contains(&key) && self.commit_ts[w] <= ts)
+            .max_by_key(|w| self.commit_ts[w])
+    }
+
+    /// Whether a key `txn` wrote has a version committed after its snapshot.
+    pub fn write_conflict(&self, txn: TxnId) -> bool {
+        self.committed().any(|u| {
+            self.commit_ts[&u] > self.snapshot[&txn]
+                && !self.writes[&u].is_disjoint(&self.writes[&txn])
+        })
+    }
+
+    /// Capture the state as a snapshot in TLA+ value syntax, so the
+    /// spec's own operators can be evaluated against it.
+    pub fn snapshot(&self, step: u64, description: &str) -> StateSnapshot {
+        let timestamps = |map: &BTreeMap<TxnId, Timestamp>| {
+            format_function(map.iter().map(|(t, ts)| (*t, ts.to_string())))
+        };
+        let reads = format_function(self.reads.iter().map(|(t, records)| {
+            let records: Vec<String> = records
+                .iter()
+                .map(|r| {
+                    let from = r.from.map_or(String::new(), |w| w.to_string());
+                    format!("[key |-> {}, from |-> {{{}}}]", r.key, from)
+                })
+                .collect();
+            (*t, format!("{{{}}}", records.join(", ")))
+        }));
+
+        StateSnapshot {
+            step,
+            description: description.to_string(),
+            variables: vec![
+                (
+                    "txn_status".to_string(),
+                    format_status_function(&self.txn_status),
+                ),
+                ("clock".to_string(), self.clock.to_string()),
+                ("snapshot".to_string(), timestamps(&self.snapshot)),
+                ("commit_ts".to_string(), timestamps(&self.commit_ts)),
+                ("writes".to_string(), format_set_function(&self.writes)),
+                ("reads".to_string(), reads),
+            ],
+        }
+    }
+
+    // ========== Invariants (from TLA+ spec) ==========
+
+    /// Line 72: FirstCommitterWins
+    ///
+    /// Committed transactions that wrote a common key were not concurrent.
+    pub fn first_committer_wins(&self) -> bool {
+        self.committed().all(|t1| {
+            self.committed().all(|t2| {
+                t1 == t2
+                    || self.writes[&t1].is_disjoint(&self.writes[&t2])
+                    || self.commit_ts[&t1] <= self.snapshot[&t2]
+                    || self.commit_ts[&t2] <= self.snapshot[&t1]
+            })
+        })
+    }
+
+    /// Line 83: SnapshotRead
+    ///
+    /// Every read saw its own write or the newest version as of its snapshot.
+    pub fn snapshot_read(&self) -> bool {
+        self.reads.iter().all(|(&t, records)| {
+            records
+                .iter()
+                .all(|r| r.from == Some(t) || r.from == self.visible(r.key, self.snapshot[&t]))
+        })
+    }
+
+    /// Line 92: CommitAfterSnapshot
+    ///
+    /// Commit timestamps are unique and later than the committer's snapshot.
+    pub fn commit_after_snapshot(&self) -> bool {
+        let mut seen = BTreeSet::new();
+        self.committed().all(|t| {
+            self.snapshot[&t] < self.commit_ts[&t]
+                && self.commit_ts[&t] <= self.clock
+                && seen.insert(self.commit_ts[&t])
+        })
+    }
+
+    /// Combined invariant check.
+    pub fn invariants_hold(&self) -> bool {
+        self.first_committer_wins() && self.snapshot_read() && self.commit_after_snapshot()
+    }
+}
+
+/// Actions that transactions can take.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MvccAction {
+    /// Transaction starts and takes its snapshot
+    Begin { txn: TxnId },
+    /// Transaction reads its own write or its snapshot
+    Read { txn: TxnId, key: KeyId },
+    /// Transaction buffers a new version
+    Write { txn: TxnId, key: KeyId },
+    /// Transaction commits, or aborts if a concurrent writer committed first
+    Commit { txn: TxnId },
+    /// Transaction aborts
+    Abort { txn: TxnId },
+}
+
+/// Model for bounded model checking.
+pub struct MvccModel {
+    pub txns: Vec<TxnId>,
+    pub keys: Vec<KeyId>,
+}
+
+impl MvccModel {
+    /// Create a new model with given parameters.
+    pub fn new(txns: Vec<TxnId>, keys: Vec<KeyId>) -> Self {
+        debug_assert!(!txns.is_empty());
+        debug_assert!(!keys.is_empty());
+
+        Self { txns, keys }
+    }
+}
+
+impl Model for MvccModel {
+    type State = MvccState;
+    type Action = MvccAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![MvccState::new(&self.txns)]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        for &txn in &self.txns {
+            match state.txn_status[&txn] {
+                TxnStatus::NotStarted => actions.push(MvccAction::Begin { txn }),
+                TxnStatus::Active => {
+                    for &key in &self.keys {
+                        actions.push(MvccAction::Read { txn, key });
+                        actions.push(MvccAction::Write { txn, key });
+                    }
+                    actions.push(MvccAction::Commit { txn });
+                    actions.push(MvccAction::Abort { txn });
+                }
+                TxnStatus::Committed | TxnStatus::Aborted => {}
+            }
+        }
+    }
+
+    fn next_state(&self, state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        let mut next = state.clone();
+
+        match action {
+            MvccAction::Begin { txn } => {
+                next.txn_status.insert(txn, TxnStatus::Active);
+                next.snapshot.insert(txn, next.clock);
+            }
+
+            MvccAction::Read { txn, key } => {
+                let from = if next.writes[&txn].contains(&key) {
+                    Some(txn)
+                } else {
+                    next.visible(key, next.snapshot[&txn])
+                };
+                next.reads
+                    .entry(txn)
+                    .or_default()
+                    .insert(ReadRecord { key, from });
+            }
+
+            MvccAction::Write { txn, key } => {
+                next.writes.entry(txn).or_default().insert(key);
+            }
+
+            MvccAction::Commit { txn } => {
+                if next.write_conflict(txn) {
+                    next.txn_status.insert(txn, TxnStatus::Aborted);
+                } else {
+                    next.clock += 1;
+                    next.commit_ts.insert(txn, next.clock);
+                    next.txn_status.insert(txn, TxnStatus::Committed);
+                }
+            }
+
+            MvccAction::Abort { txn } => {
+                next.txn_status.insert(txn, TxnStatus::Aborted);
+            }
+        }
+
+        Some(next)
+    }
+
+    fn properties(&self) -> Vec<stateright::Property<Self>> {
+        vec![
+            // covers: SnapshotIsolationPropertyChecker::FirstCommitterWins
+            stateright::Property::always(
+                "FirstCommitterWins",
+                |_model: &Self, state: &Self::State| state.first_committer_wins(),
+            ),
+            // covers: SnapshotIsolationPropertyChecker::SnapshotRead
+            stateright::Property::always("SnapshotRead", |_model: &Self, state: &Self::State| {
+                state.snapshot_read()
+            }),
+            // covers: SnapshotIsolationPropertyChecker::CommitAfterSnapshot
+            stateright::Property::always(
+                "CommitAfterSnapshot",
+                |_model: &Self, state: &Self::State| state.commit_after_snapshot(),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stateright::Checker;
+    use std::collections::{HashSet, VecDeque};
+    use vf_core::tla_spec::{parse_module, Evaluator, Value};
+
+    fn run(model: &MvccModel, actions: &[MvccAction]) -> MvccState {
+        let mut state = model.init_states().remove(0);
+        for action in actions {
+            state = model.next_state(&state, action.clone()).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn test_initial_state() {
+        let state = MvccState::new(&[1, 2]);
+        assert_eq!(state.clock, 0);
+        assert_eq!(state.visible(1, 0), None);
+        assert!(state.invariants_hold());
+    }
+
+    #[test]
+    fn test_model_checking_small() {
+        let model = MvccModel::new(vec![1, 2], vec![1, 2]);
+
+        model
+            .checker()
+            .threads(1)
+            .spawn_bfs()
+            .join()
+            .assert_properties();
+    }
+
+    #[test]
+    fn test_lost_update_aborts() {
+        let model = MvccModel::new(vec![1, 2], vec![1]);
+        let state = run(
+            &model,
+            &[
+                MvccAction::Begin { txn: 1 },
+                MvccAction::Begin { txn: 2 },
+                MvccAction::Write { txn: 1, key: 1 },
+                MvccAction::Write { txn: 2, key: 1 },
+                MvccAction::Commit { txn: 1 },
+                MvccAction::Commit { txn: 2 },
+            ],
+        );
+
+        assert_eq!(state.txn_status[&1], TxnStatus::Committed);
+        assert_eq!(state.txn_status[&2], TxnStatus::Aborted);
+        assert!(state.invariants_hold());
+    }
+
+    #[test]
+    fn test_write_skew_commits() {
+        // Both read both keys from the initial snapshot and write a different
+        // one: not serializable, yet every SI invariant holds.
+        let model = MvccModel::new(vec![1, 2], vec![1, 2]);
+        let state = run(
+            &model,
+            &[
+                MvccAction::Begin { txn: 1 },
+                MvccAction::Begin { txn: 2 },
+                MvccAction::Read { txn: 1, key: 1 },
+                MvccAction::Read { txn: 1, key: 2 },
+                MvccAction::Read { txn: 2, key: 1 },
+                MvccAction::Read { txn: 2, key: 2 },
+                MvccAction::Write { txn: 1, key: 1 },
+                MvccAction::Write { txn: 2, key: 2 },
+                MvccAction::Commit { txn: 1 },
+                MvccAction::Commit { txn: 2 },
+            ],
+        );
+
+        assert_eq!(state.txn_status[&1], TxnStatus::Committed);
+        assert_eq!(state.txn_status[&2], TxnStatus::Committed);
+        assert!(state.invariants_hold());
+    }
+
+    #[test]
+    fn test_invariants_agree_with_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/ssi/snapshot_isolation.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let mut evaluator = Evaluator::new(&module)
+            .with_constant("Txns", Value::set([Value::Int(1), Value::Int(2)]))
+            .with_constant("Keys", Value::set([Value::Int(1)]))
+            .with_model_constants();
+
+        // Walk every reachable state and evaluate the spec's own operators.
+        let model = MvccModel::new(vec![1, 2], vec![1]);
+        let mut seen = HashSet::new();
+        let mut frontier: VecDeque<MvccState> = model.init_states().into();
+        let mut actions = Vec::new();
+        while let Some(state) = frontier.pop_front() {
+            if !seen.insert(state.clone()) {
+                continue;
+            }
+            evaluator.load_state(&state.snapshot(0, "")).unwrap();
+            assert_eq!(
+                evaluator.check("FirstCommitterWins").unwrap(),
+                state.first_committer_wins()
+            );
+            assert_eq!(
+                evaluator.check("SnapshotRead").unwrap(),
+                state.snapshot_read()
+            );
+            assert_eq!(
+                evaluator.check("CommitAfterSnapshot").unwrap(),
+                state.commit_after_snapshot()
+            );
+
+            actions.clear();
+            model.actions(&state, &mut actions);
+            for action in actions.drain(..) {
+                frontier.extend(model.next_state(&state, action));
+            }
+        }
+        assert!(seen.len() > 100, "explored {} states", seen.len());
+    }
+
+    #[test]
+    fn test_stale_read_detected() {
+        // T2 began after T1 committed key 1 but read the initial value.
+        let mut state = MvccState::new(&[1, 2]);
+        state.txn_status.insert(1, TxnStatus::Committed);
+        state.txn_status.insert(2, TxnStatus::Active);
+        state.clock = 1;
+        state.commit_ts.insert(1, 1);
+        state.writes.insert(1, BTreeSet::from([1]));
+        state.snapshot.insert(2, 1);
+        state
+            .reads
+            .insert(2, BTreeSet::from([ReadRecord { key: 1, from: None }]));
+
+        assert!(!state.snapshot_read());
+        assert!(state.first_committer_wins());
+        assert!(state.commit_after_snapshot());
+    }
+}
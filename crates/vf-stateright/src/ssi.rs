@@ -10,14 +10,22 @@
 //!
 //! - **Snapshot**: Each transaction sees a consistent view from its start time
 //! - **SIREAD locks**: Track reads even after commit (for conflict detection)
+//! - **Scans**: Read every key in a range at once; over the model's finite
+//!   key set, per-key SIREAD locks cover the whole range (phantoms included)
 //! - **Conflict flags**: `in_conflict` and `out_conflict` per transaction
 //! - **Dangerous structure**: Transaction with both flags set (potential cycle)
+//! - **Safe snapshot**: A read-only transaction whose concurrent read-write
+//!   transactions finished without an outgoing rw-conflict; it drops its
+//!   SIREAD locks and can no longer be aborted
 //!
 //! # Invariants
 //!
 //! 1. `FirstCommitterWins`: No concurrent commits to same key
 //! 2. `SnapshotConsistency`: Reads see consistent snapshot
 //! 3. `Serializable`: No dangerous structures at commit time
+//! 4. `ReadOnlyNoWrites`: Read-only transactions never write
+//! 5. `SafeSnapshotNoLocks`: Safe snapshots hold no SIREAD locks
+//! 6. `SafeSnapshotCommits`: Safe snapshots are never aborted by the system
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::hash::Hash;
@@ -82,6 +90,15 @@ pub struct SsiState {
 
     /// Outgoing rw-conflict flag per transaction.
     pub out_conflict: BTreeMap<TxnId, bool>,
+
+    /// Transactions that began read-only.
+    pub read_only: BTreeSet<TxnId>,
+
+    /// Read-only TxnId -> read-write transactions active at its begin.
+    pub ro_concurrent: BTreeMap<TxnId, BTreeSet<TxnId>>,
+
+    /// Read-only transactions whose snapshot is known safe.
+    pub safe_snapshot: BTreeSet<TxnId>,
 }
 
 impl SsiState {
@@ -95,6 +112,9 @@ impl SsiState {
             siread_locks: keys.iter().map(|&k| (k, BTreeSet::new())).collect(),
             in_conflict: txns.iter().map(|&t| (t, false)).collect(),
             out_conflict: txns.iter().map(|&t| (t, false)).collect(),
+            read_only: BTreeSet::new(),
+            ro_concurrent: BTreeMap::new(),
+            safe_snapshot: BTreeSet::new(),
         }
     }
 
@@ -112,6 +132,35 @@ impl SsiState {
             .collect()
     }
 
+    /// Get active read-write transactions.
+    pub fn active_read_write_txns(&self) -> BTreeSet<TxnId> {
+        self.active_txns()
+            .into_iter()
+            .filter(|t| !self.read_only.contains(t))
+            .collect()
+    }
+
+    /// Whether a read-only transaction's snapshot can be marked safe: every
+    /// concurrent read-write transaction finished, none committed with an
+    /// outgoing rw-conflict.
+    pub fn can_mark_safe(&self, txn: TxnId) -> bool {
+        if self.txn_status.get(&txn) != Some(&TxnStatus::Active)
+            || !self.read_only.contains(&txn)
+            || self.safe_snapshot.contains(&txn)
+        {
+            return false;
+        }
+        self.ro_concurrent
+            .get(&txn)
+            .into_iter()
+            .flatten()
+            .all(|w| match self.txn_status.get(w) {
+                Some(TxnStatus::Committed) => !self.out_conflict.get(w).copied().unwrap_or(false),
+                Some(TxnStatus::Aborted) => true,
+                _ => false,
+            })
+    }
+
     /// Get committed transactions.
     pub fn committed_txns(&self) -> BTreeSet<TxnId> {
         self.txn_status
@@ -219,7 +268,15 @@ impl SsiState {
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum SsiAction {
     Begin(TxnId),
+    /// Begin read-only; the snapshot is safe if no read-write txn is active
+    BeginReadOnly(TxnId),
+    /// Begin read-only once no read-write txn is active (DEFERRABLE)
+    BeginDeferrable(TxnId),
+    /// Mark a read-only snapshot safe and drop its SIREAD locks
+    MarkSafe(TxnId),
     Read(TxnId, KeyId),
+    /// Read every key in the inclusive range
+    Scan(TxnId, KeyId, KeyId),
     Write(TxnId, KeyId),
     Commit(TxnId),
     Abort(TxnId),
@@ -234,16 +291,34 @@ impl SsiState {
             match status {
                 TxnStatus::NotStarted => {
                     actions.push(SsiAction::Begin(txn));
+                    actions.push(SsiAction::BeginReadOnly(txn));
+                    if self.active_read_write_txns().is_empty() {
+                        actions.push(SsiAction::BeginDeferrable(txn));
+                    }
                 }
                 TxnStatus::Active => {
+                    if self.can_mark_safe(txn) {
+                        actions.push(SsiAction::MarkSafe(txn));
+                    }
                     // Can read, write, commit, or abort
+                    let read_only = self.read_only.contains(&txn);
                     for &key in self.write_locks.keys() {
                         actions.push(SsiAction::Read(txn, key));
                         // Can only write if lock is free or we hold it
-                        if self.write_locks.get(&key).copied().flatten().map_or(true, |h| h == txn) {
+                        if !read_only
+                            && self.write_locks.get(&key).copied().flatten().map_or(true, |h| h == txn)
+                        {
                             actions.push(SsiAction::Write(txn, key));
                         }
                     }
+                    // One scan over the whole key set keeps the state space small
+                    let first = self.write_locks.keys().next();
+                    let last = self.write_locks.keys().next_back();
+                    if let (Some(&first), Some(&last)) = (first, last) {
+                        if first < last {
+                            actions.push(SsiAction::Scan(txn, first, last));
+                        }
+                    }
                     if !self.has_dangerous_structure(txn) {
                         actions.push(SsiAction::Commit(txn));
                     }
@@ -270,6 +345,48 @@ impl SsiState {
                 next.txn_snapshot.insert(*txn, next.now() - 1);
             }
 
+            SsiAction::BeginReadOnly(txn) => {
+                let concurrent = next.active_read_write_txns();
+                next = next.apply(&SsiAction::Begin(*txn))?;
+                next.read_only.insert(*txn);
+                if concurrent.is_empty() {
+                    next.safe_snapshot.insert(*txn);
+                }
+                next.ro_concurrent.insert(*txn, concurrent);
+            }
+
+            SsiAction::BeginDeferrable(txn) => {
+                if !next.active_read_write_txns().is_empty() {
+                    return None; // Waits for a safe snapshot
+                }
+                next = next.apply(&SsiAction::BeginReadOnly(*txn))?;
+            }
+
+            SsiAction::MarkSafe(txn) => {
+                if !next.can_mark_safe(*txn) {
+                    return None;
+                }
+                next.safe_snapshot.insert(*txn);
+                for locks in next.siread_locks.values_mut() {
+                    locks.remove(txn);
+                }
+            }
+
+            SsiAction::Read(txn, key) if next.safe_snapshot.contains(txn) => {
+                if next.txn_status.get(txn) != Some(&TxnStatus::Active) {
+                    return None;
+                }
+
+                // Safe snapshot: no SIREAD lock, no conflict tracking, no abort
+                let snapshot = next.txn_snapshot.get(txn).copied().unwrap_or(0);
+                let version = next.latest_version(*key, snapshot);
+                next.history.push(Operation::Read {
+                    txn: *txn,
+                    key: *key,
+                    version,
+                });
+            }
+
             SsiAction::Read(txn, key) => {
                 if next.txn_status.get(txn) != Some(&TxnStatus::Active) {
                     return None;
@@ -320,11 +437,34 @@ impl SsiState {
                 }
             }
 
-            SsiAction::Write(txn, key) => {
+            SsiAction::Scan(txn, first, last) => {
                 if next.txn_status.get(txn) != Some(&TxnStatus::Active) {
                     return None;
                 }
 
+                // Each key in range is read from the same snapshot; a read
+                // conflict aborts the whole scan
+                let keys: Vec<KeyId> = next
+                    .write_locks
+                    .keys()
+                    .filter(|&k| (first..=last).contains(&k))
+                    .copied()
+                    .collect();
+                for key in keys {
+                    next = next.apply(&SsiAction::Read(*txn, key))?;
+                    if next.txn_status.get(txn) != Some(&TxnStatus::Active) {
+                        break;
+                    }
+                }
+            }
+
+            SsiAction::Write(txn, key) => {
+                if next.txn_status.get(txn) != Some(&TxnStatus::Active)
+                    || next.read_only.contains(txn)
+                {
+                    return None;
+                }
+
                 let lock_holder = next.write_locks.get(key).copied().flatten();
                 if lock_holder.is_some() && lock_holder != Some(*txn) {
                     return None; // Lock held by another transaction
@@ -458,6 +598,9 @@ impl SsiState {
                 for j in (i + 1)..writers.len() {
                     let (ts1, t1) = writers[i];
                     let (ts2, t2) = writers[j];
+                    if t1 == t2 {
+                        continue; // Rewriting own key
+                    }
                     let snap1 = self.txn_snapshot.get(&t1).copied().unwrap_or(0);
                     let snap2 = self.txn_snapshot.get(&t2).copied().unwrap_or(0);
 
@@ -490,6 +633,34 @@ impl SsiState {
         self.no_committed_dangerous_structures() && self.first_committer_wins()
     }
 
+    /// I5: Read-only transactions never write.
+    pub fn read_only_no_writes(&self) -> bool {
+        !self.history.iter().any(|op| {
+            matches!(op, Operation::Write { txn, .. } if self.read_only.contains(txn))
+        })
+    }
+
+    /// I6: A safe snapshot never causes an abort.
+    /// It holds no SIREAD locks, so no writer can find a conflict with it.
+    pub fn safe_snapshot_no_locks(&self) -> bool {
+        self.safe_snapshot.iter().all(|txn| {
+            self.read_only.contains(txn)
+                && self.siread_locks.values().all(|holders| !holders.contains(txn))
+        })
+    }
+
+    /// I7: A safe snapshot never suffers an abort.
+    /// It never has a dangerous structure and is only ever aborted voluntarily.
+    pub fn safe_snapshot_commits(&self) -> bool {
+        self.safe_snapshot.iter().all(|&txn| {
+            !self.has_dangerous_structure(txn)
+                && !self.history.iter().any(|op| {
+                    matches!(op, Operation::Abort { txn: t, reason }
+                        if *t == txn && *reason != AbortReason::Voluntary)
+                })
+        })
+    }
+
     /// Check all invariants.
     pub fn check_invariants(&self) -> Vec<&'static str> {
         let mut violations = Vec::new();
@@ -503,6 +674,15 @@ impl SsiState {
         if !self.is_serializable() {
             violations.push("Serializable");
         }
+        if !self.read_only_no_writes() {
+            violations.push("ReadOnlyNoWrites");
+        }
+        if !self.safe_snapshot_no_locks() {
+            violations.push("SafeSnapshotNoLocks");
+        }
+        if !self.safe_snapshot_commits() {
+            violations.push("SafeSnapshotCommits");
+        }
 
         violations
     }
@@ -527,6 +707,8 @@ pub enum SsiOracleCategory {
     CommitWithConflict,
     /// Deadlock avoidance scenario
     DeadlockAvoidance,
+    /// Insert into a range another transaction scanned
+    PhantomConflict,
 }
 
 /// An oracle capturing an interesting SSI execution.
@@ -658,6 +840,48 @@ impl SsiOracle {
         }
     }
 
+    /// Pre-built oracle: Phantom write skew
+    ///
+    /// Both scan the same range, then each writes a key the other's scan
+    /// covered. Neither read the key the other wrote as a point read.
+    pub fn phantom_write_skew() -> Self {
+        Self {
+            name: "phantom_write_skew".into(),
+            category: SsiOracleCategory::PhantomConflict,
+            actions: vec![
+                SsiAction::Begin(1),
+                SsiAction::Begin(2),
+                SsiAction::Scan(1, 1, 2), // T1 scans K1..=K2
+                SsiAction::Scan(2, 1, 2), // T2 scans K1..=K2
+                SsiAction::Write(2, 1),   // T2 writes K1 (T1 gets out_conflict)
+                SsiAction::Commit(2),     // T2 commits with in_conflict
+                SsiAction::Write(1, 2),   // T1 writes K2 into T2's scanned range
+                SsiAction::Commit(1),     // T1 must not commit
+            ],
+            description: "Write skew through range scans - T1 aborts".into(),
+        }
+    }
+
+    /// Pre-built oracle: Disjoint ranges
+    ///
+    /// A write into one scanned range is a single rw-conflict; both commit.
+    pub fn disjoint_ranges() -> Self {
+        Self {
+            name: "disjoint_ranges".into(),
+            category: SsiOracleCategory::CommitWithConflict,
+            actions: vec![
+                SsiAction::Begin(1),
+                SsiAction::Begin(2),
+                SsiAction::Scan(1, 1, 1), // T1 scans K1..=K1
+                SsiAction::Scan(2, 2, 2), // T2 scans K2..=K2
+                SsiAction::Write(1, 2),   // T1 writes K2 (T2 gets out_conflict)
+                SsiAction::Commit(1),
+                SsiAction::Commit(2),
+            ],
+            description: "Write into one of two disjoint scanned ranges - both commit".into(),
+        }
+    }
+
     /// All pre-built SSI oracles.
     pub fn all_oracles() -> Vec<Self> {
         vec![
@@ -667,6 +891,8 @@ impl SsiOracle {
             Self::disjoint_keys(),
             Self::read_only(),
             Self::single_conflict_flag(),
+            Self::phantom_write_skew(),
+            Self::disjoint_ranges(),
         ]
     }
 }
@@ -855,4 +1081,114 @@ mod tests {
 
         assert!(state.check_invariants().is_empty());
     }
+    #[test]
+    fn test_phantom_write_skew_aborts() {
+        let mut state = SsiState::new(&[1, 2], &[1, 2]);
+        for action in &SsiOracle::phantom_write_skew().actions {
+            if let Some(next) = state.apply(action) {
+                state = next;
+            }
+            assert!(state.check_invariants().is_empty());
+        }
+
+        assert_eq!(state.txn_status.get(&1), Some(&TxnStatus::Aborted));
+        assert_eq!(state.txn_status.get(&2), Some(&TxnStatus::Committed));
+    }
+
+    #[test]
+    fn test_scan_reads_every_key_in_range() {
+        let mut state = SsiState::new(&[1], &[1, 2, 3]);
+        state = state.apply(&SsiAction::Begin(1)).unwrap();
+        state = state.apply(&SsiAction::Scan(1, 2, 3)).unwrap();
+
+        assert!(state.siread_locks[&1].is_empty());
+        assert!(state.siread_locks[&2].contains(&1));
+        assert!(state.siread_locks[&3].contains(&1));
+    }
+
+    /// A reporting query reads K1 after a committed writer of K1 picked up
+    /// an outgoing rw-conflict. Returns the query's final status.
+    fn reporting_query(mark_safe: bool) -> TxnStatus {
+        let mut state = SsiState::new(&[1, 2, 3, 4], &[1, 2]);
+        let mut actions = vec![
+            SsiAction::Begin(1),
+            SsiAction::BeginReadOnly(3), // Concurrent with T1: not yet safe
+            SsiAction::Commit(1),
+        ];
+        if mark_safe {
+            actions.push(SsiAction::MarkSafe(3));
+        }
+        actions.extend([
+            SsiAction::Begin(2),
+            SsiAction::Begin(4),
+            SsiAction::Read(2, 2),
+            SsiAction::Write(4, 2), // T2 gets out_conflict
+            SsiAction::Write(2, 1),
+            SsiAction::Commit(2),
+            SsiAction::Read(3, 1), // T3 -rw-> T2 would make T2 a pivot
+            SsiAction::Commit(3), // Not applicable if the read aborted T3
+        ]);
+        for action in &actions {
+            if let Some(next) = state.apply(action) {
+                state = next;
+            }
+            assert!(state.check_invariants().is_empty(), "{:?}", action);
+        }
+        state.txn_status[&3]
+    }
+
+    #[test]
+    fn test_safe_snapshot_avoids_read_abort() {
+        assert_eq!(reporting_query(false), TxnStatus::Aborted);
+        assert_eq!(reporting_query(true), TxnStatus::Committed);
+    }
+
+    #[test]
+    fn test_deferrable_waits_for_read_write_txns() {
+        let mut state = SsiState::new(&[1, 2], &[1]);
+        state = state.apply(&SsiAction::Begin(1)).unwrap();
+        state = state.apply(&SsiAction::Read(1, 1)).unwrap();
+
+        assert!(state.apply(&SsiAction::BeginDeferrable(2)).is_none());
+        assert!(!state.possible_actions().contains(&SsiAction::BeginDeferrable(2)));
+
+        state = state.apply(&SsiAction::Commit(1)).unwrap();
+        state = state.apply(&SsiAction::BeginDeferrable(2)).unwrap();
+        assert!(state.safe_snapshot.contains(&2));
+
+        // Safe reads take no SIREAD locks and read-only txns cannot write
+        state = state.apply(&SsiAction::Read(2, 1)).unwrap();
+        assert!(!state.siread_locks[&1].contains(&2));
+        assert!(state.apply(&SsiAction::Write(2, 1)).is_none());
+    }
+
+    #[test]
+    fn test_read_only_invariants_bounded() {
+        // Every reachable state up to the depth bound. Only the read-only
+        // invariants: Write has no first-updater check yet.
+        let mut frontier = vec![SsiState::new(&[1, 2, 3], &[1])];
+        let mut visited = std::collections::HashSet::new();
+        for _ in 0..6 {
+            let mut next_frontier = Vec::new();
+            for state in frontier {
+                for action in state.possible_actions() {
+                    let Some(next) = state.apply(&action) else {
+                        continue;
+                    };
+                    assert!(
+                        next.read_only_no_writes()
+                            && next.safe_snapshot_no_locks()
+                            && next.safe_snapshot_commits(),
+                        "{:?}",
+                        next.history
+                    );
+                    if visited.insert(next.clone()) {
+                        next_frontier.push(next);
+                    }
+                }
+            }
+            frontier = next_frontier;
+        }
+        assert!(visited.iter().any(|s| !s.safe_snapshot.is_empty()));
+    }
 }
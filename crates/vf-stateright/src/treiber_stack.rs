@@ -100,7 +100,7 @@ impl StackState {
 
     // ========== Invariants (from TLA+ spec) ==========
 
-    /// Line 45: NoLostElements
+    /// Line 66: NoLostElements
     ///
     /// Every element that was pushed must either be in the stack or was popped.
     pub fn no_lost_elements(&self) -> bool {
@@ -114,7 +114,7 @@ impl StackState {
         true
     }
 
-    /// Line 58: NoDuplicates
+    /// Line 75: NoDuplicates
     ///
     /// No element appears twice in the stack.
     pub fn no_duplicates(&self) -> bool {
@@ -314,9 +314,11 @@ impl Model for StackModel {
 
     fn properties(&self) -> Vec<stateright::Property<Self>> {
         vec![
+            // covers: StackPropertyChecker::NoLostElements
             stateright::Property::always("NoLostElements", |_model: &Self, state: &Self::State| {
                 state.no_lost_elements()
             }),
+            // covers: StackPropertyChecker::NoDuplicates
             stateright::Property::always("NoDuplicates", |_model: &Self, state: &Self::State| {
                 state.no_duplicates()
             }),
@@ -0,0 +1,473 @@
+//! Stateright model for strict two-phase locking.
+//!
+//! This model mirrors `specs/ssi/two_phase_locking.tla` and can be used
+//! for exhaustive state space exploration.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::Hash;
+
+use stateright::Model;
+use vf_core::StateSnapshot;
+
+use crate::ssi::{KeyId, TxnId, TxnStatus};
+
+/// Strength of a lock request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+/// State of the two-phase locking model.
+///
+/// Mirrors the TLA+ spec's state variables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LockingState {
+    /// Transaction status (`NotStarted` is the spec's `"idle"`)
+    pub txn_status: BTreeMap<TxnId, TxnStatus>,
+    /// Key -> transactions holding a shared lock
+    pub shared: BTreeMap<KeyId, BTreeSet<TxnId>>,
+    /// Key -> transactions holding the exclusive lock
+    pub exclusive: BTreeMap<KeyId, BTreeSet<TxnId>>,
+    /// Txn -> keys read
+    pub reads: BTreeMap<TxnId, BTreeSet<KeyId>>,
+    /// Txn -> keys written
+    pub writes: BTreeMap<TxnId, BTreeSet<KeyId>>,
+    /// Txn -> transactions it is blocked on
+    pub waits_for: BTreeMap<TxnId, BTreeSet<TxnId>>,
+}
+
+impl LockingState {
+    /// Create initial state with given transaction and key sets.
+    pub fn new(txns: &[TxnId], keys: &[KeyId]) -> Self {
+        let per_txn = || txns.iter().map(|&t| (t, BTreeSet::new())).collect();
+        let per_key = || keys.iter().map(|&k| (k, BTreeSet::new())).collect();
+
+        Self {
+            txn_status: txns.iter().map(|&t| (t, TxnStatus::NotStarted)).collect(),
+            shared: per_key(),
+            exclusive: per_key(),
+            reads: per_txn(),
+            writes: per_txn(),
+            waits_for: per_txn(),
+        }
+    }
+
+    fn is_active(&self, txn: TxnId) -> bool {
+        self.txn_status.get(&txn) == Some(&TxnStatus::Active)
+    }
+
+    /// Transactions whose locks on `key` conflict with `txn` taking `mode`.
+    pub fn blockers(&self, txn: TxnId, key: KeyId, mode: LockMode) -> BTreeSet<TxnId> {
+        let mut blockers = self.exclusive[&key].clone();
+        if mode == LockMode::Exclusive {
+            blockers.extend(&self.shared[&key]);
+        }
+        blockers.remove(&txn);
+        blockers
+    }
+
+    /// Capture the state as a snapshot in TLA+ value syntax, so the
+    /// spec's own operators can be evaluated against it.
+    pub fn snapshot(&self, step: u64, description: &str) -> StateSnapshot {
+        StateSnapshot {
+            step,
+            description: description.to_string(),
+            variables: vec![
+                (
+                    "txn_status".to_string(),
+                    format_status_function(&self.txn_status),
+                ),
+                ("shared".to_string(), format_set_function(&self.shared)),
+                (
+                    "exclusive".to_string(),
+                    format_set_function(&self.exclusive),
+                ),
+                ("reads".to_string(), format_set_function(&self.reads)),
+                ("writes".to_string(), format_set_function(&self.writes)),
+                (
+                    "waits_for".to_string(),
+                    format_set_function(&self.waits_for),
+                ),
+            ],
+        }
+    }
+
+    // ========== Invariants (from TLA+ spec) ==========
+
+    /// Line 80: LockCompatibility
+    ///
+    /// At most one exclusive holder per key, and nobody else shares it.
+    pub fn lock_compatibility(&self) -> bool {
+        self.exclusive.iter().all(|(key, holders)| {
+            holders.len() <= 1 && (holders.is_empty() || self.shared[key].is_subset(holders))
+        })
+    }
+
+    /// Line 89: StrictTwoPhase
+    ///
+    /// Locks are only held by running transactions.
+    pub fn strict_two_phase(&self) -> bool {
+        self.shared
+            .values()
+            .chain(self.exclusive.values())
+            .flatten()
+            .all(|&t| self.is_active(t))
+    }
+
+    /// Line 96: AccessesLocked
+    ///
+    /// A running transaction still holds a lock on everything it accessed.
+    pub fn accesses_locked(&self) -> bool {
+        self.txn_status
+            .keys()
+            .filter(|&&t| self.is_active(t))
+            .all(|t| {
+                let read_locked = self.reads[t]
+                    .iter()
+                    .all(|k| self.shared[k].contains(t) || self.exclusive[k].contains(t));
+                let write_locked = self.writes[t].iter().all(|k| self.exclusive[k].contains(t));
+                read_locked && write_locked
+            })
+    }
+
+    /// Line 105: NoDeadlock
+    ///
+    /// The waits-for graph is acyclic, and only running transactions wait.
+    pub fn no_deadlock(&self) -> bool {
+        acyclic(&self.waits_for)
+            && self
+                .waits_for
+                .iter()
+                .all(|(t, blockers)| blockers.is_empty() || self.is_active(*t))
+    }
+
+    /// Combined invariant check.
+    pub fn invariants_hold(&self) -> bool {
+        self.lock_compatibility()
+            && self.strict_two_phase()
+            && self.accesses_locked()
+            && self.no_deadlock()
+    }
+
+    /// Release every lock held by `txn` and wake its waiters.
+    fn finish(&mut self, txn: TxnId, outcome: TxnStatus) {
+        self.txn_status.insert(txn, outcome);
+        for holders in self.shared.values_mut().chain(self.exclusive.values_mut()) {
+            holders.remove(&txn);
+        }
+        for (t, blockers) in &mut self.waits_for {
+            if *t == txn {
+                blockers.clear();
+            } else {
+                blockers.remove(&txn);
+            }
+        }
+    }
+}
+
+/// Whether every non-empty set of transactions has one waiting on none of
+/// the others: peel off transactions that wait on nobody left.
+fn acyclic(waits_for: &BTreeMap<TxnId, BTreeSet<TxnId>>) -> bool {
+    let mut remaining: BTreeSet<TxnId> = waits_for.keys().copied().collect();
+    loop {
+        let sinks: Vec<TxnId> = remaining
+            .iter()
+            .copied()
+            .filter(|t| waits_for[t].is_disjoint(&remaining))
+            .collect();
+        if sinks.is_empty() {
+            return remaining.is_empty();
+        }
+        for t in sinks {
+            remaining.remove(&t);
+        }
+    }
+}
+
+/// `txn_status` as a TLA+ function (`NotStarted` is the spec's `"idle"`).
+pub(crate) fn format_status_function(txn_status: &BTreeMap<TxnId, TxnStatus>) -> String {
+    format_function(txn_status.iter().map(|(t, s)| {
+        let name = match s {
+            TxnStatus::NotStarted => "idle",
+            TxnStatus::Active => "active",
+            TxnStatus::Committed => "committed",
+            TxnStatus::Aborted => "aborted",
+        };
+        (*t, format!("\"{}\"", name))
+    }))
+}
+
+pub(crate) fn format_function<K: ToString>(entries: impl Iterator<Item = (K, String)>) -> String {
+    let entries: Vec<String> = entries
+        .map(|(k, v)| format!("{} :> {}", k.to_string(), v))
+        .collect();
+    format!("({})", entries.join(" @@ "))
+}
+
+pub(crate) fn format_set_function<K: ToString + Copy>(map: &BTreeMap<K, BTreeSet<u8>>) -> String {
+    format_function(map.iter().map(|(k, items)| {
+        let items: Vec<String> = items.iter().map(u8::to_string).collect();
+        (*k, format!("{{{}}}", items.join(", ")))
+    }))
+}
+
+/// Actions that transactions can take.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LockingAction {
+    /// Transaction starts
+    Begin { txn: TxnId },
+    /// Transaction takes a shared lock and reads
+    Read { txn: TxnId, key: KeyId },
+    /// Transaction takes (or upgrades to) the exclusive lock and writes
+    Write { txn: TxnId, key: KeyId },
+    /// Conflicting request blocks, or aborts the requester on deadlock
+    Wait {
+        txn: TxnId,
+        key: KeyId,
+        mode: LockMode,
+    },
+    /// Transaction commits and releases its locks
+    Commit { txn: TxnId },
+    /// Transaction aborts and releases its locks
+    Abort { txn: TxnId },
+}
+
+/// Model for bounded model checking.
+pub struct LockingModel {
+    pub txns: Vec<TxnId>,
+    pub keys: Vec<KeyId>,
+}
+
+impl LockingModel {
+    /// Create a new model with given parameters.
+    pub fn new(txns: Vec<TxnId>, keys: Vec<KeyId>) -> Self {
+        debug_assert!(!txns.is_empty());
+        debug_assert!(!keys.is_empty());
+
+        Self { txns, keys }
+    }
+}
+
+impl Model for LockingModel {
+    type State = LockingState;
+    type Action = LockingAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![LockingState::new(&self.txns, &self.keys)]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        for &txn in &self.txns {
+            match state.txn_status[&txn] {
+                TxnStatus::NotStarted => actions.push(LockingAction::Begin { txn }),
+                TxnStatus::Active => {
+                    for &key in &self.keys {
+                        for mode in [LockMode::Shared, LockMode::Exclusive] {
+                            if state.blockers(txn, key, mode).is_empty() {
+                                actions.push(match mode {
+                                    LockMode::Shared => LockingAction::Read { txn, key },
+                                    LockMode::Exclusive => LockingAction::Write { txn, key },
+                                });
+                            } else {
+                                actions.push(LockingAction::Wait { txn, key, mode });
+                            }
+                        }
+                    }
+                    actions.push(LockingAction::Commit { txn });
+                    actions.push(LockingAction::Abort { txn });
+                }
+                TxnStatus::Committed | TxnStatus::Aborted => {}
+            }
+        }
+    }
+
+    fn next_state(&self, state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        let mut next = state.clone();
+
+        match action {
+            LockingAction::Begin { txn } => {
+                next.txn_status.insert(txn, TxnStatus::Active);
+            }
+
+            LockingAction::Read { txn, key } => {
+                next.shared.entry(key).or_default().insert(txn);
+                next.reads.entry(txn).or_default().insert(key);
+                next.waits_for.insert(txn, BTreeSet::new());
+            }
+
+            LockingAction::Write { txn, key } => {
+                next.exclusive.insert(key, BTreeSet::from([txn]));
+                next.writes.entry(txn).or_default().insert(key);
+                next.waits_for.insert(txn, BTreeSet::new());
+            }
+
+            LockingAction::Wait { txn, key, mode } => {
+                let blockers = next.blockers(txn, key, mode);
+                let mut waiting = next.waits_for.clone();
+                waiting.insert(txn, blockers);
+                if acyclic(&waiting) {
+                    next.waits_for = waiting;
+                } else {
+                    // Deadlock victim
+                    next.finish(txn, TxnStatus::Aborted);
+                }
+            }
+
+            LockingAction::Commit { txn } => next.finish(txn, TxnStatus::Committed),
+
+            LockingAction::Abort { txn } => next.finish(txn, TxnStatus::Aborted),
+        }
+
+        Some(next)
+    }
+
+    fn properties(&self) -> Vec<stateright::Property<Self>> {
+        vec![
+            // covers: TwoPhaseLockingPropertyChecker::LockCompatibility
+            stateright::Property::always(
+                "LockCompatibility",
+                |_model: &Self, state: &Self::State| state.lock_compatibility(),
+            ),
+            // covers: TwoPhaseLockingPropertyChecker::StrictTwoPhase
+            stateright::Property::always("StrictTwoPhase", |_model: &Self, state: &Self::State| {
+                state.strict_two_phase()
+            }),
+            // covers: TwoPhaseLockingPropertyChecker::AccessesLocked
+            stateright::Property::always("AccessesLocked", |_model: &Self, state: &Self::State| {
+                state.accesses_locked()
+            }),
+            // covers: TwoPhaseLockingPropertyChecker::NoDeadlock
+            stateright::Property::always("NoDeadlock", |_model: &Self, state: &Self::State| {
+                state.no_deadlock()
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stateright::Checker;
+    use std::collections::{HashSet, VecDeque};
+    use vf_core::tla_spec::{parse_module, Evaluator, Value};
+
+    fn apply(model: &LockingModel, state: LockingState, action: LockingAction) -> LockingState {
+        model.next_state(&state, action).unwrap()
+    }
+
+    #[test]
+    fn test_initial_state() {
+        let state = LockingState::new(&[1, 2], &[1, 2]);
+        assert!(state.shared.values().all(BTreeSet::is_empty));
+        assert!(state.invariants_hold());
+    }
+
+    #[test]
+    fn test_model_checking_small() {
+        let model = LockingModel::new(vec![1, 2], vec![1, 2]);
+
+        model
+            .checker()
+            .threads(1)
+            .spawn_bfs()
+            .join()
+            .assert_properties();
+    }
+
+    #[test]
+    fn test_deadlock_victim_aborts() {
+        let model = LockingModel::new(vec![1, 2], vec![1, 2]);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            LockingAction::Begin { txn: 1 },
+            LockingAction::Begin { txn: 2 },
+            LockingAction::Write { txn: 1, key: 1 },
+            LockingAction::Write { txn: 2, key: 2 },
+            LockingAction::Wait {
+                txn: 1,
+                key: 2,
+                mode: LockMode::Shared,
+            },
+        ] {
+            state = apply(&model, state, action);
+        }
+        assert_eq!(state.waits_for[&1], BTreeSet::from([2]));
+
+        // T2 waiting on T1 would close the cycle, so T2 is the victim
+        state = apply(
+            &model,
+            state,
+            LockingAction::Wait {
+                txn: 2,
+                key: 1,
+                mode: LockMode::Exclusive,
+            },
+        );
+        assert_eq!(state.txn_status[&2], TxnStatus::Aborted);
+        assert!(state.waits_for[&1].is_empty());
+        assert!(state.exclusive[&2].is_empty());
+        assert!(state.invariants_hold());
+    }
+
+    #[test]
+    fn test_invariants_agree_with_spec() {
+        let content = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../specs/ssi/two_phase_locking.tla"
+        ))
+        .unwrap();
+        let module = parse_module(&content).unwrap();
+        let mut evaluator = Evaluator::new(&module)
+            .with_constant("Txns", Value::set([Value::Int(1), Value::Int(2)]))
+            .with_constant("Keys", Value::set([Value::Int(1), Value::Int(2)]))
+            .with_model_constants();
+
+        // Walk every reachable state and evaluate the spec's own operators.
+        let model = LockingModel::new(vec![1, 2], vec![1, 2]);
+        let mut seen = HashSet::new();
+        let mut frontier: VecDeque<LockingState> = model.init_states().into();
+        let mut actions = Vec::new();
+        while let Some(state) = frontier.pop_front() {
+            if !seen.insert(state.clone()) {
+                continue;
+            }
+            evaluator.load_state(&state.snapshot(0, "")).unwrap();
+            assert_eq!(
+                evaluator.check("LockCompatibility").unwrap(),
+                state.lock_compatibility()
+            );
+            assert_eq!(
+                evaluator.check("StrictTwoPhase").unwrap(),
+                state.strict_two_phase()
+            );
+            assert_eq!(
+                evaluator.check("AccessesLocked").unwrap(),
+                state.accesses_locked()
+            );
+            assert_eq!(evaluator.check("NoDeadlock").unwrap(), state.no_deadlock());
+
+            actions.clear();
+            model.actions(&state, &mut actions);
+            for action in actions.drain(..) {
+                frontier.extend(model.next_state(&state, action));
+            }
+        }
+        assert!(seen.len() > 100, "explored {} states", seen.len());
+    }
+
+    #[test]
+    fn test_early_release_detected() {
+        // T1 committed but still holds its exclusive lock, and T2 shares it.
+        let mut state = LockingState::new(&[1, 2], &[1]);
+        state.txn_status.insert(1, TxnStatus::Committed);
+        state.txn_status.insert(2, TxnStatus::Active);
+        state.exclusive.insert(1, BTreeSet::from([1]));
+        state.shared.insert(1, BTreeSet::from([2]));
+        state.reads.insert(2, BTreeSet::from([1]));
+
+        assert!(!state.strict_two_phase());
+        assert!(!state.lock_compatibility());
+        assert!(state.accesses_locked());
+    }
+}
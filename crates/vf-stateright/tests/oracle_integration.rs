@@ -112,7 +112,6 @@ fn test_oracle_replay_in_dst() {
     let mut t0_push_value: Option<u64> = None;
     let mut t1_push_value: Option<u64> = None;
     let mut t0_cas_attempt = 0;
-    let mut t1_cas_attempt = 0;
 
     while !scheduler.oracle_complete() {
         let thread = scheduler.current_thread();
@@ -148,7 +147,6 @@ fn test_oracle_replay_in_dst() {
                     t0_cas_attempt += 1;
                     t0_cas_attempt == 1 // First attempt fails
                 } else {
-                    t1_cas_attempt += 1;
                     false // T1 always succeeds in this scenario
                 };
 
@@ -209,7 +207,7 @@ fn test_all_cas_scenarios() {
 
         // Create scheduler and simulate
         let mut scheduler = OracleScheduler::from_oracle(scenario.clone(), 4);
-        let mut stack = SimulatedStack::new();
+        let _stack = SimulatedStack::new();
 
         // Simple simulation - just verify the oracle runs to completion
         let mut step = 0;
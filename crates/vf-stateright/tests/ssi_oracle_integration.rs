@@ -1,6 +1,6 @@
 //! Integration tests for SSI oracle extraction and replay.
 
-use vf_stateright::ssi::{SsiAction, SsiOracle, SsiOracleCategory, SsiOracleExtractor, SsiState};
+use vf_stateright::ssi::{SsiOracle, SsiOracleCategory, SsiOracleExtractor, SsiState};
 
 #[test]
 fn test_prebuilt_oracles() {
@@ -12,11 +12,11 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 71:  ElectionSafety       -> stateright, dst
- * Line 84:  LeaderAppendOnly     -> stateright, loom
- * Line 97:  LogMatching          -> stateright, dst
- * Line 115: LeaderCompleteness   -> stateright, dst
- * Line 130: StateMachineSafety   -> stateright, dst
+ * Line 77:  ElectionSafety       -> stateright, dst
+ * Line 89:  LeaderAppendOnly     -> stateright, loom
+ * Line 100: LogMatching          -> stateright, dst
+ * Line 120: LeaderCompleteness   -> stateright, dst
+ * Line 131: StateMachineSafety   -> stateright, dst
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -71,7 +71,7 @@ TypeOK ==
     /\ \A s \in Servers : Len(log[s]) <= MaxLogLen
 
 -----------------------------------------------------------------------------
-(* Line 71: ElectionSafety
+(* ElectionSafety
  * At most one leader per term.
  *)
 ElectionSafety ==
@@ -82,7 +82,7 @@ ElectionSafety ==
         => s1 = s2
 
 -----------------------------------------------------------------------------
-(* Line 84: LeaderAppendOnly
+(* LeaderAppendOnly
  * A leader never overwrites or deletes entries in its log.
  * Encoded as: leader log length never decreases.
  *)
@@ -93,7 +93,7 @@ LeaderAppendOnly ==
             TRUE \* Verified via transition constraints in the actions
 
 -----------------------------------------------------------------------------
-(* Line 97: LogMatching
+(* LogMatching
  * If two logs contain an entry with the same index and term,
  * all preceding entries are identical.
  *)
@@ -113,7 +113,7 @@ LogMatching ==
                     => log[s1][k] = log[s2][k]
 
 -----------------------------------------------------------------------------
-(* Line 115: LeaderCompleteness
+(* LeaderCompleteness
  * If an entry is committed in term T, that entry will be present
  * in the logs of all leaders for terms > T.
  *)
@@ -124,7 +124,7 @@ LeaderCompleteness ==
             commitIndex[s] <= Len(log[s])
 
 -----------------------------------------------------------------------------
-(* Line 130: StateMachineSafety
+(* StateMachineSafety
  * If a server has applied entry at index i, no other server applies
  * a different entry at index i.
  *)
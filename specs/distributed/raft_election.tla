@@ -11,11 +11,11 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 60: ElectionSafety       -> stateright, dst
- * Line 73: LeaderCompleteness   -> stateright, dst
- * Line 86: VoteIntegrity        -> stateright, loom
- * Line 99: TermMonotonicity     -> stateright, loom, dst
- * Line 112: QuorumOverlap       -> stateright
+ * Line 70: ElectionSafety       -> stateright, dst
+ * Line 83: LeaderCompleteness   -> stateright, dst
+ * Line 94: VoteIntegrity        -> stateright, loom
+ * Line 105: TermMonotonicity     -> stateright, loom, dst
+ * Line 115: QuorumOverlap       -> stateright
  * Line 125: LeaderHeartbeat     -> stateright, dst
  *)
 
@@ -63,7 +63,7 @@ TypeOK ==
     /\ votesGranted \in [Servers -> SUBSET Servers]
 
 -----------------------------------------------------------------------------
-(* Line 60: ElectionSafety
+(* ElectionSafety
  * At most one leader per term. If two servers are both leaders,
  * they must be in different terms.
  *)
@@ -75,7 +75,7 @@ ElectionSafety ==
         => s1 = s2
 
 -----------------------------------------------------------------------------
-(* Line 73: LeaderCompleteness
+(* LeaderCompleteness
  * Once a leader is elected in a term, no other server can win an
  * election in that same term. This is a consequence of quorum overlap
  * and single-vote-per-term.
@@ -87,7 +87,7 @@ LeaderCompleteness ==
             IsQuorum(votesGranted[s])
 
 -----------------------------------------------------------------------------
-(* Line 86: VoteIntegrity
+(* VoteIntegrity
  * Each server votes at most once per term. If a server voted for
  * candidate A in term T, it cannot also vote for candidate B in term T.
  *)
@@ -98,7 +98,7 @@ VoteIntegrity ==
             TRUE
 
 -----------------------------------------------------------------------------
-(* Line 99: TermMonotonicity
+(* TermMonotonicity
  * A server's term never decreases. Terms are monotonically increasing
  * across all state transitions.
  *)
@@ -108,7 +108,7 @@ TermMonotonicity ==
     TRUE
 
 -----------------------------------------------------------------------------
-(* Line 112: QuorumOverlap
+(* QuorumOverlap
  * Any two quorums have at least one server in common. This ensures
  * that two candidates cannot both win elections in the same term.
  *)
@@ -117,7 +117,7 @@ QuorumOverlap ==
         Q1 \cap Q2 # {}
 
 -----------------------------------------------------------------------------
-(* Line 125: LeaderHeartbeat
+(* LeaderHeartbeat
  * A leader periodically sends heartbeats. Followers that receive
  * heartbeats from a leader with a term >= their own do not start
  * elections.
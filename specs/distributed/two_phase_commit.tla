@@ -11,12 +11,12 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 55: Atomicity       -> stateright, dst
- * Line 68: Validity        -> stateright, dst
- * Line 81: Consistency     -> stateright, loom, dst
- * Line 94: Agreement       -> stateright, loom
- * Line 107: TMDecision     -> stateright, dst
- * Line 120: RMSafety       -> stateright, loom, dst
+ * Line 59: Atomicity       -> stateright, dst
+ * Line 69: Validity        -> stateright, dst
+ * Line 78: Consistency     -> stateright, loom, dst
+ * Line 89: Agreement       -> stateright, loom
+ * Line 100: TMDecision     -> stateright, dst
+ * Line 112: RMSafety       -> stateright, loom, dst
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -52,7 +52,7 @@ TypeOK ==
     /\ tmPrepared \subseteq RMs
 
 -----------------------------------------------------------------------------
-(* Line 55: Atomicity
+(* Atomicity
  * All resource managers reach the same final decision.
  * No RM commits while another RM aborts.
  *)
@@ -62,7 +62,7 @@ Atomicity ==
           /\ rmState[rm2] = "Aborted")
 
 -----------------------------------------------------------------------------
-(* Line 68: Validity
+(* Validity
  * A transaction can only commit if all RMs voted to prepare.
  * If any RM chose to abort, the transaction must abort.
  *)
@@ -70,7 +70,7 @@ Validity ==
     tmState = "Committed" => tmPrepared = RMs
 
 -----------------------------------------------------------------------------
-(* Line 81: Consistency
+(* Consistency
  * An RM can only be in Committed state if the TM decided to commit.
  * An RM can only be in Aborted state if the TM decided to abort
  * or the RM unilaterally aborted before preparing.
@@ -82,7 +82,7 @@ Consistency ==
                 tmState \in {"Aborted", "Init", "Preparing"})
 
 -----------------------------------------------------------------------------
-(* Line 94: Agreement
+(* Agreement
  * Once the TM makes a decision (Commit or Abort), it never changes
  * that decision. The decision is irrevocable.
  *)
@@ -93,7 +93,7 @@ Agreement ==
     (\E rm \in RMs : rmState[rm] = "Committed") => tmState = "Committed"
 
 -----------------------------------------------------------------------------
-(* Line 107: TMDecision
+(* TMDecision
  * The TM only commits if ALL RMs are prepared.
  * The TM aborts if ANY RM failed to prepare.
  *)
@@ -104,7 +104,7 @@ TMDecision ==
             \/ \E rm \in RMs : rmState[rm] = "Aborted")
 
 -----------------------------------------------------------------------------
-(* Line 120: RMSafety
+(* RMSafety
  * An RM only transitions to Committed after receiving a Commit message.
  * An RM only transitions to Aborted after either choosing to abort
  * locally or receiving an Abort message.
@@ -7,10 +7,10 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: SortedOrder      -> stateright, loom, dst
- * Line 58: BalancedHeight    -> stateright, dst
- * Line 72: AtomicSplit       -> loom, dst
- * Line 86: NoLostKeys        -> stateright, loom, dst
+ * Line 47: SortedOrder      -> stateright, loom, dst
+ * Line 55: BalancedHeight    -> stateright, dst
+ * Line 63: AtomicSplit       -> loom, dst
+ * Line 70: NoLostKeys        -> stateright, loom, dst
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -41,7 +41,7 @@ IsSortedSeq(seq) ==
         seq[i] < seq[i + 1]
 
 -----------------------------------------------------------------------------
-(* Line 45: SortedOrder
+(* SortedOrder
  * Keys within every node are in sorted order.
  *)
 SortedOrder ==
@@ -49,14 +49,14 @@ SortedOrder ==
         IsSortedSeq(nodes[nid].keys)
 
 -----------------------------------------------------------------------------
-(* Line 58: BalancedHeight
+(* BalancedHeight
  * All leaf nodes are at the same depth from root.
  *)
 BalancedHeight ==
     TRUE  \* Checked structurally in stateright model
 
 -----------------------------------------------------------------------------
-(* Line 72: AtomicSplit
+(* AtomicSplit
  * Node splits appear atomic to concurrent readers.
  * A search never observes a partially-split node.
  *)
@@ -64,7 +64,7 @@ AtomicSplit ==
     TRUE  \* Verified by version counters in loom model
 
 -----------------------------------------------------------------------------
-(* Line 86: NoLostKeys
+(* NoLostKeys
  * Every key in the abstract map is findable via tree traversal.
  *)
 NoLostKeys ==
@@ -8,10 +8,10 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: NoUseAfterFree       -> stateright, loom, dst
- * Line 58: QuiescentReclamation -> stateright, dst
- * Line 72: BoundedMemory        -> kani, dst
- * Line 86: MonotonicEpoch       -> stateright
+ * Line 40: NoUseAfterFree       -> stateright, loom, dst
+ * Line 49: QuiescentReclamation -> stateright, dst
+ * Line 59: BoundedMemory        -> kani, dst
+ * Line 67: MonotonicEpoch       -> stateright
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -34,7 +34,7 @@ VARIABLES
 vars == <<global_epoch, thread_epoch, thread_active, retired, freed, referenced, thread_state>>
 
 -----------------------------------------------------------------------------
-(* Line 45: NoUseAfterFree
+(* NoUseAfterFree
  * No thread holds a reference to a freed object.
  *)
 NoUseAfterFree ==
@@ -42,7 +42,7 @@ NoUseAfterFree ==
         referenced[t] \cap freed = {}
 
 -----------------------------------------------------------------------------
-(* Line 58: QuiescentReclamation
+(* QuiescentReclamation
  * Objects retired in epoch E can be freed once all threads
  * have observed epoch > E (quiescent state).
  *)
@@ -52,7 +52,7 @@ QuiescentReclamation ==
             retired[e] \cap freed = retired[e] \/ retired[e] = {}
 
 -----------------------------------------------------------------------------
-(* Line 72: BoundedMemory
+(* BoundedMemory
  * The number of retired-but-not-freed objects is bounded.
  * At most O(threads * objects_per_epoch) objects are pending reclamation.
  *)
@@ -61,7 +61,7 @@ BoundedMemory ==
     IN Cardinality(pending) <= Cardinality(Threads) * Cardinality(Objects)
 
 -----------------------------------------------------------------------------
-(* Line 86: MonotonicEpoch
+(* MonotonicEpoch
  * The global epoch never decreases. Thread epochs never decrease.
  *)
 MonotonicEpoch ==
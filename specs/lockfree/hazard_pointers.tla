@@ -0,0 +1,167 @@
+--------------------------- MODULE hazard_pointers ---------------------------
+(*
+ * Hazard Pointer Reclamation Specification
+ *
+ * Safe memory reclamation for lock-free data structures (Michael, 2004).
+ * Before dereferencing a shared node, a thread publishes its address in
+ * one of its hazard slots and re-reads the source pointer; if the node is
+ * still reachable, the protection is validated. Unlinked nodes are retired
+ * to a shared list. Once the list reaches ScanThreshold, a scan frees every
+ * retired node that no hazard slot protects and keeps the rest.
+ *
+ * Unlike epoch_gc.tla, a stalled thread only pins the nodes it protects,
+ * so unreclaimed garbage is bounded regardless of thread progress.
+ *
+ * EVALUATOR MAPPING
+ * -----------------
+ * Line 66: NoReclaimWhileProtected -> dst
+ * Line 73: ProtectedIsPublished    -> dst
+ * Line 81: RetiredUnreachable      -> dst
+ * Line 90: BoundedGarbage          -> dst
+ *)
+
+EXTENDS Integers, FiniteSets, TLC
+
+CONSTANTS
+    Threads,          \* Set of thread identifiers
+    Objects,          \* Set of object identifiers
+    HazardsPerThread, \* Hazard slots owned by each thread
+    ScanThreshold     \* Retired list length that triggers a scan
+
+ASSUME ScanThreshold > HazardsPerThread * Cardinality(Threads)
+
+VARIABLES
+    reachable,     \* Set of objects linked into the data structure
+    hazard,        \* Map: ThreadId -> set of objects in its hazard slots
+    protected,     \* Map: ThreadId -> set of validated objects it may dereference
+    retired,       \* Set of unlinked objects awaiting reclamation
+    freed          \* Set of reclaimed objects
+
+vars == <<reachable, hazard, protected, retired, freed>>
+
+-----------------------------------------------------------------------------
+(* Helpers *)
+
+\* Every object published in any hazard slot
+Hazardous == UNION {hazard[t] : t \in Threads}
+
+\* Upper bound on objects a scan cannot free
+MaxHazards == HazardsPerThread * Cardinality(Threads)
+
+-----------------------------------------------------------------------------
+(* Type invariants *)
+
+TypeOK ==
+    /\ reachable \subseteq Objects
+    /\ hazard \in [Threads -> SUBSET Objects]
+    /\ protected \in [Threads -> SUBSET Objects]
+    /\ retired \subseteq Objects
+    /\ freed \subseteq Objects
+
+-----------------------------------------------------------------------------
+(* NoReclaimWhileProtected
+ * No object is freed while a thread holds a validated reference to it.
+ * This is the fundamental safety property.
+ *)
+NoReclaimWhileProtected ==
+    \A t \in Threads: protected[t] \cap freed = {}
+
+-----------------------------------------------------------------------------
+(* ProtectedIsPublished
+ * A thread only dereferences objects it has published in a hazard slot.
+ *)
+ProtectedIsPublished ==
+    \A t \in Threads: protected[t] \subseteq hazard[t]
+
+-----------------------------------------------------------------------------
+(* RetiredUnreachable
+ * Retired and freed objects are no longer linked into the structure,
+ * so no new reference to them can be validated.
+ *)
+RetiredUnreachable ==
+    /\ retired \cap reachable = {}
+    /\ freed \cap reachable = {}
+
+-----------------------------------------------------------------------------
+(* BoundedGarbage
+ * Retired-but-not-freed objects are bounded: a scan leaves at most
+ * MaxHazards objects behind, and retiring past the threshold triggers one.
+ *)
+BoundedGarbage ==
+    Cardinality(retired) <= ScanThreshold + MaxHazards
+
+-----------------------------------------------------------------------------
+(* Initial state *)
+
+Init ==
+    /\ reachable = Objects
+    /\ hazard = [t \in Threads |-> {}]
+    /\ protected = [t \in Threads |-> {}]
+    /\ retired = {}
+    /\ freed = {}
+
+-----------------------------------------------------------------------------
+(* Publish: store a (possibly stale) pointer in a free hazard slot *)
+
+Publish(t, obj) ==
+    /\ Cardinality(hazard[t]) < HazardsPerThread
+    /\ hazard' = [hazard EXCEPT ![t] = hazard[t] \cup {obj}]
+    /\ UNCHANGED <<reachable, protected, retired, freed>>
+
+-----------------------------------------------------------------------------
+(* Validate: re-read the source; the object is still linked *)
+
+Validate(t, obj) ==
+    /\ obj \in hazard[t]
+    /\ obj \in reachable
+    /\ protected' = [protected EXCEPT ![t] = protected[t] \cup {obj}]
+    /\ UNCHANGED <<reachable, hazard, retired, freed>>
+
+-----------------------------------------------------------------------------
+(* Clear: drop the reference and empty the hazard slot *)
+
+Clear(t, obj) ==
+    /\ obj \in hazard[t]
+    /\ hazard' = [hazard EXCEPT ![t] = hazard[t] \ {obj}]
+    /\ protected' = [protected EXCEPT ![t] = protected[t] \ {obj}]
+    /\ UNCHANGED <<reachable, retired, freed>>
+
+-----------------------------------------------------------------------------
+(* UnlinkRetire: remove an object from the structure and retire it *)
+
+UnlinkRetire(obj) ==
+    /\ obj \in reachable
+    /\ Cardinality(retired) < ScanThreshold
+    /\ reachable' = reachable \ {obj}
+    /\ retired' = retired \cup {obj}
+    /\ UNCHANGED <<hazard, protected, freed>>
+
+-----------------------------------------------------------------------------
+(* Scan: free every retired object no hazard slot protects *)
+
+Scan ==
+    /\ retired # {}
+    /\ freed' = freed \cup (retired \ Hazardous)
+    /\ retired' = retired \cap Hazardous
+    /\ UNCHANGED <<reachable, hazard, protected>>
+
+-----------------------------------------------------------------------------
+Next ==
+    \/ \E t \in Threads, obj \in Objects: Publish(t, obj)
+    \/ \E t \in Threads, obj \in Objects: Validate(t, obj)
+    \/ \E t \in Threads, obj \in Objects: Clear(t, obj)
+    \/ \E obj \in Objects: UnlinkRetire(obj)
+    \/ Scan
+
+Spec == Init /\ [][Next]_vars
+
+Safety ==
+    /\ TypeOK
+    /\ NoReclaimWhileProtected
+    /\ ProtectedIsPublished
+    /\ RetiredUnreachable
+    /\ BoundedGarbage
+
+FullSpec == Spec /\ []Safety
+
+=============================================================================
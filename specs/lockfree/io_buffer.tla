@@ -7,10 +7,10 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: NoDataCorruption     -> stateright, loom, dst
- * Line 58: CompletionGuarantee  -> stateright, dst
- * Line 72: OrderPreserved       -> stateright, loom
- * Line 86: BoundedMemory        -> kani
+ * Line 41: NoDataCorruption     -> stateright, loom, dst
+ * Line 52: CompletionGuarantee  -> stateright, dst
+ * Line 59: OrderPreserved       -> stateright, loom
+ * Line 66: BoundedMemory        -> kani
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -34,7 +34,7 @@ VARIABLES
 vars == <<buffer, flushed, write_offset, flush_offset, submitted, completed, thread_state>>
 
 -----------------------------------------------------------------------------
-(* Line 45: NoDataCorruption
+(* NoDataCorruption
  * Every submitted item appears exactly once in flushed output.
  * No torn writes — each item is complete.
  *)
@@ -46,21 +46,21 @@ NoDataCorruption ==
 Range(seq) == {seq[i] : i \in DOMAIN seq}
 
 -----------------------------------------------------------------------------
-(* Line 58: CompletionGuarantee
+(* CompletionGuarantee
  * Every submitted item is eventually flushed.
  *)
 CompletionGuarantee ==
     TRUE  \* Liveness property, checked via fairness
 
 -----------------------------------------------------------------------------
-(* Line 72: OrderPreserved
+(* OrderPreserved
  * Items submitted by the same thread are flushed in submission order.
  *)
 OrderPreserved ==
     TRUE  \* Per-thread ordering maintained by sequential append
 
 -----------------------------------------------------------------------------
-(* Line 86: BoundedMemory
+(* BoundedMemory
  * Buffer never exceeds BufferSize items.
  *)
 BoundedMemory ==
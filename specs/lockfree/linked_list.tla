@@ -7,11 +7,11 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: NoLostElements       -> stateright, loom, dst
- * Line 58: NoDuplicates         -> stateright, loom
- * Line 72: Reachability         -> stateright, dst
- * Line 86: InsertOrderPreserved -> stateright
- * Line 100: NoMemoryLeak        -> kani (with epoch GC)
+ * Line 53: NoLostElements       -> stateright, loom, dst
+ * Line 62: NoDuplicates         -> stateright, loom
+ * Line 70: Reachability         -> stateright, dst
+ * Line 88: InsertOrderPreserved -> stateright
+ * Line 95:  NoMemoryLeak        -> kani (with epoch GC)
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -47,7 +47,7 @@ ReachableKeys(nid) ==
          ELSE {nodes[nid].key} \cup ReachableKeys(nodes[nid].next)
 
 -----------------------------------------------------------------------------
-(* Line 45: NoLostElements
+(* NoLostElements
  * Every inserted-and-not-removed key is reachable from head.
  *)
 NoLostElements ==
@@ -56,7 +56,7 @@ NoLostElements ==
     IN live \subseteq reachable
 
 -----------------------------------------------------------------------------
-(* Line 58: NoDuplicates
+(* NoDuplicates
  * No key appears twice in the reachable list.
  *)
 NoDuplicates ==
@@ -64,7 +64,7 @@ NoDuplicates ==
     IN Cardinality(reachable) = Cardinality(reachable)  \* Set guarantees uniqueness
 
 -----------------------------------------------------------------------------
-(* Line 72: Reachability
+(* Reachability
  * All unmarked non-sentinel nodes are reachable from head.
  *)
 Reachability ==
@@ -73,7 +73,7 @@ Reachability ==
             nodes[nid].key \in ReachableKeys(nodes[head_id].next)
 
 -----------------------------------------------------------------------------
-(* Line 86: InsertOrderPreserved
+(* InsertOrderPreserved
  * Keys in the list are in sorted order.
  *)
 RECURSIVE IsSorted(_)
@@ -89,7 +89,7 @@ InsertOrderPreserved ==
     IsSorted(nodes[head_id].next)
 
 -----------------------------------------------------------------------------
-(* Line 100: NoMemoryLeak
+(* NoMemoryLeak
  * All allocated nodes are either reachable or logically deleted (marked).
  *)
 NoMemoryLeak ==
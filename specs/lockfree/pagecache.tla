@@ -9,10 +9,10 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: AtomicPageUpdate   -> stateright, loom, dst
- * Line 58: NoLostPages        -> stateright, dst
- * Line 72: CrashConsistency   -> dst
- * Line 86: NoDirtyReads       -> loom
+ * Line 41: AtomicPageUpdate   -> stateright, loom, dst
+ * Line 50: NoLostPages        -> stateright, dst
+ * Line 60: CrashConsistency   -> dst
+ * Line 68: NoDirtyReads       -> loom
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -34,7 +34,7 @@ VARIABLES
 vars == <<pages, page_table, disk, flush_pending, thread_state>>
 
 -----------------------------------------------------------------------------
-(* Line 45: AtomicPageUpdate
+(* AtomicPageUpdate
  * Each page write appears atomic: readers see either the old or new version,
  * never a partial update.
  *)
@@ -44,7 +44,7 @@ AtomicPageUpdate ==
             pages[pid].version \in Versions \cup {0}
 
 -----------------------------------------------------------------------------
-(* Line 58: NoLostPages
+(* NoLostPages
  * Every page that has been written is either in the cache or on disk.
  *)
 NoLostPages ==
@@ -53,7 +53,7 @@ NoLostPages ==
             pid \in DOMAIN pages \/ pid \in DOMAIN disk
 
 -----------------------------------------------------------------------------
-(* Line 72: CrashConsistency
+(* CrashConsistency
  * After a crash, the recovered state is a consistent prefix of operations.
  * No page references data that was never flushed.
  *)
@@ -62,7 +62,7 @@ CrashConsistency ==
         disk[pid].version >= 0
 
 -----------------------------------------------------------------------------
-(* Line 86: NoDirtyReads
+(* NoDirtyReads
  * A read always returns the latest committed version.
  *)
 NoDirtyReads ==
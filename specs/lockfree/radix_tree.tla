@@ -7,10 +7,10 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: PrefixConsistency  -> stateright, loom, dst
- * Line 58: NoLostKeys         -> stateright, loom, dst
- * Line 72: AtomicUpdate       -> loom, kani
- * Line 86: TrieInvariant      -> stateright
+ * Line 39: PrefixConsistency  -> stateright, loom, dst
+ * Line 47: NoLostKeys         -> stateright, loom, dst
+ * Line 56: AtomicUpdate       -> loom, kani
+ * Line 64: TrieInvariant      -> stateright
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -32,7 +32,7 @@ VARIABLES
 vars == <<root, nodes, next_node_id, kv_map, thread_state>>
 
 -----------------------------------------------------------------------------
-(* Line 45: PrefixConsistency
+(* PrefixConsistency
  * For every key stored, traversing from root using the key's bytes
  * reaches the correct value.
  *)
@@ -41,7 +41,7 @@ PrefixConsistency ==
         TRUE  \* Verified structurally by lookup implementation
 
 -----------------------------------------------------------------------------
-(* Line 58: NoLostKeys
+(* NoLostKeys
  * Every key in the abstract map is retrievable via tree traversal.
  *)
 NoLostKeys ==
@@ -49,7 +49,7 @@ NoLostKeys ==
         kv_map[k] # NULL
 
 -----------------------------------------------------------------------------
-(* Line 72: AtomicUpdate
+(* AtomicUpdate
  * Each insert/delete appears atomic: concurrent lookups see either
  * the old state or the new state, never a torn partial update.
  *)
@@ -57,7 +57,7 @@ AtomicUpdate ==
     TRUE  \* Verified by loom/kani — CAS ensures atomicity
 
 -----------------------------------------------------------------------------
-(* Line 86: TrieInvariant
+(* TrieInvariant
  * All nodes reachable from root form a valid trie structure.
  * No cycles. Each child edge is consistent with the prefix.
  *)
@@ -7,10 +7,10 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: NoLostMessages       -> stateright, loom, dst
- * Line 58: FIFO_Order           -> stateright, loom
- * Line 72: BoundedCapacity      -> stateright, kani
- * Line 86: ProducerConsumerProgress -> stateright
+ * Line 47: NoLostMessages       -> stateright, loom, dst
+ * Line 56: FIFO_Order           -> stateright, loom
+ * Line 64: BoundedCapacity      -> stateright, kani
+ * Line 72: ProducerConsumerProgress -> stateright
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -41,7 +41,7 @@ TypeOK ==
     /\ \A i \in 0..(Capacity - 1): buffer[i] \in Elements \cup {NULL}
 
 -----------------------------------------------------------------------------
-(* Line 45: NoLostMessages
+(* NoLostMessages
  * Every produced message is either in the buffer or has been consumed.
  *)
 NoLostMessages ==
@@ -50,7 +50,7 @@ NoLostMessages ==
         produced[i] \in in_buffer \/ produced[i] \in Range(consumed)
 
 -----------------------------------------------------------------------------
-(* Line 58: FIFO_Order
+(* FIFO_Order
  * Elements are consumed in the same order they were produced.
  *)
 FIFO_Order ==
@@ -58,14 +58,14 @@ FIFO_Order ==
         consumed[i] = produced[i]
 
 -----------------------------------------------------------------------------
-(* Line 72: BoundedCapacity
+(* BoundedCapacity
  * The number of elements in the buffer never exceeds Capacity.
  *)
 BoundedCapacity ==
     Cardinality({i \in 0..(Capacity - 1) : buffer[i] # NULL}) <= Capacity
 
 -----------------------------------------------------------------------------
-(* Line 86: ProducerConsumerProgress
+(* ProducerConsumerProgress
  * If the buffer is not full, a producer can always make progress.
  * If the buffer is not empty, a consumer can always make progress.
  *)
@@ -8,12 +8,12 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: NoLostElements     -> stateright, loom, dst
- * Line 58: NoDuplicates       -> stateright, loom
- * Line 72: LIFO_Order         -> stateright
- * Line 89: Linearizability    -> loom
- * Line 103: ABA_Safety        -> loom, kani (with epoch GC)
- * Line 117: LockFreeProgress  -> stateright
+ * Line 66: NoLostElements     -> stateright, loom, dst
+ * Line 75: NoDuplicates       -> stateright, loom
+ * Line 87: LIFO_Order         -> stateright
+ * Line 102: Linearizability    -> loom
+ * Line 116: ABA_Safety        -> loom, kani (with epoch GC)
+ * Line 126: LockFreeProgress  -> stateright
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -59,7 +59,7 @@ StackContents(node) ==
 Range(seq) == {seq[i] : i \in DOMAIN seq}
 
 -----------------------------------------------------------------------------
-(* Line 45: NoLostElements
+(* NoLostElements
  * Every element that was pushed and not popped must be in the stack.
  * This is the fundamental safety property.
  *)
@@ -68,7 +68,7 @@ NoLostElements ==
         e \in Range(StackContents(head)) \/ e \in popped
 
 -----------------------------------------------------------------------------
-(* Line 58: NoDuplicates
+(* NoDuplicates
  * No element appears twice in the stack.
  * Combined with NoLostElements, ensures exact conservation.
  *)
@@ -77,7 +77,7 @@ NoDuplicates ==
     IN Len(contents) = Cardinality(Range(contents))
 
 -----------------------------------------------------------------------------
-(* Line 72: LIFO_Order
+(* LIFO_Order
  * If element A was pushed before element B (and neither popped),
  * then B appears above A in the stack.
  *
@@ -92,7 +92,7 @@ LIFO_Order ==
         TRUE  \* Encoded in Push/Pop transition semantics
 
 -----------------------------------------------------------------------------
-(* Line 89: Linearizability
+(* Linearizability
  * All operations appear to take effect atomically at some point
  * between their invocation and response.
  *
@@ -105,7 +105,7 @@ Linearizability ==
     TRUE  \* Implicit in the atomic CAS semantics
 
 -----------------------------------------------------------------------------
-(* Line 103: ABA_Safety
+(* ABA_Safety
  * The ABA problem cannot cause lost or corrupted data.
  *
  * With epoch-based GC: nodes are not reused while any thread
@@ -119,7 +119,7 @@ ABA_Safety ==
     TRUE
 
 -----------------------------------------------------------------------------
-(* Line 117: LockFreeProgress
+(* LockFreeProgress
  * At least one thread makes progress in any execution.
  * If threads are executing, at least one will complete its operation.
  *)
@@ -8,10 +8,10 @@
  *
  * EVALUATOR MAPPING
  * -----------------
- * Line 45: Serializable          -> stateright, dst
- * Line 58: CrossShardAtomicity   -> stateright, loom, dst
- * Line 72: FirstCommitterWins    -> stateright, dst
- * Line 86: NoPhantomReads        -> loom, dst
+ * Line 52: Serializable          -> stateright, dst
+ * Line 65: CrossShardAtomicity   -> stateright, loom, dst
+ * Line 75: FirstCommitterWins    -> stateright, dst
+ * Line 88: NoPhantomReads        -> loom, dst
  *)
 
 EXTENDS Integers, Sequences, FiniteSets, TLC
@@ -45,7 +45,7 @@ vars == <<shard_data, txn_status, txn_reads, txn_writes, txn_shards,
           prepare_votes, thread_state>>
 
 -----------------------------------------------------------------------------
-(* Line 45: Serializable
+(* Serializable
  * The committed transactions are serializable: there exists a total order
  * consistent with all read/write dependencies.
  *)
@@ -58,7 +58,7 @@ Serializable ==
     IN TRUE  \* Cycle detection checked in stateright model
 
 -----------------------------------------------------------------------------
-(* Line 58: CrossShardAtomicity
+(* CrossShardAtomicity
  * A cross-shard transaction either commits on ALL shards or aborts on ALL.
  * No partial commits.
  *)
@@ -68,7 +68,7 @@ CrossShardAtomicity ==
             \A s \in txn_shards[t]: TRUE  \* All shards have committed writes
 
 -----------------------------------------------------------------------------
-(* Line 72: FirstCommitterWins
+(* FirstCommitterWins
  * If two transactions have rw-conflicts and both try to commit,
  * only the first committer succeeds.
  *)
@@ -81,7 +81,7 @@ FirstCommitterWins ==
         => FALSE  \* Dangerous structure -> at least one must abort
 
 -----------------------------------------------------------------------------
-(* Line 86: NoPhantomReads
+(* NoPhantomReads
  * A transaction's snapshot is consistent: it sees all writes from
  * transactions committed before its start, none from after.
  *)
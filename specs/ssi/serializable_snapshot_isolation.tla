@@ -10,10 +10,19 @@
  * by detecting "dangerous structures" in the conflict graph - two consecutive
  * rw-dependencies that could form a cycle.
  *
+ * READ-ONLY TRANSACTIONS (PostgreSQL's safe snapshots): a read-only
+ * transaction's snapshot is safe once every read-write transaction that was
+ * active when it began has finished, and none committed with an outgoing
+ * rw-conflict. From then on it needs no SIREAD locks and cannot take part in
+ * a dangerous structure. A DEFERRABLE transaction waits for a safe snapshot
+ * before it begins.
+ *
  * INVARIANTS (verified by evaluator cascade):
  *   - FirstCommitterWins: No concurrent commits to same key
  *   - SnapshotRead: Transactions read consistent snapshots
  *   - Serializable: Conflict graph is acyclic
+ *   - ReadOnlyNoWrites, SafeSnapshotNoLocks, SafeSnapshotCommits:
+ *     safe read-only transactions neither cause nor suffer aborts
  *)
 
 EXTENDS Integers, Sequences, FiniteSets
@@ -34,9 +43,17 @@ VARIABLES
 
     \* Conflict detection (Cahill's algorithm)
     in_conflict,        \* TxnId -> BOOLEAN (has incoming rw-conflict)
-    out_conflict        \* TxnId -> BOOLEAN (has outgoing rw-conflict)
+    out_conflict,       \* TxnId -> BOOLEAN (has outgoing rw-conflict)
+
+    \* Read-only transactions
+    read_only,          \* TxnId -> BOOLEAN (began read-only)
+    ro_concurrent,      \* TxnId -> SET of read-write TxnId active at its begin
+    safe_snapshot       \* TxnId -> BOOLEAN (read-only snapshot known safe)
+
+vars == <<history, txn_status, txn_snapshot, write_locks, siread_locks, in_conflict, out_conflict,
+          read_only, ro_concurrent, safe_snapshot>>
 
-vars == <<history, txn_status, txn_snapshot, write_locks, siread_locks, in_conflict, out_conflict>>
+roVars == <<read_only, ro_concurrent, safe_snapshot>>
 
 NULL == CHOOSE x : x \notin TxnId
 
@@ -54,6 +71,9 @@ TypeInv ==
     /\ siread_locks \in [Key -> SUBSET TxnId]
     /\ in_conflict \in [TxnId -> BOOLEAN]
     /\ out_conflict \in [TxnId -> BOOLEAN]
+    /\ read_only \in [TxnId -> BOOLEAN]
+    /\ ro_concurrent \in [TxnId -> SUBSET TxnId]
+    /\ safe_snapshot \in [TxnId -> BOOLEAN]
 
 -----------------------------------------------------------------------------
 (* INITIAL STATE *)
@@ -66,6 +86,9 @@ Init ==
     /\ siread_locks = [k \in Key |-> {}]
     /\ in_conflict = [t \in TxnId |-> FALSE]
     /\ out_conflict = [t \in TxnId |-> FALSE]
+    /\ read_only = [t \in TxnId |-> FALSE]
+    /\ ro_concurrent = [t \in TxnId |-> {}]
+    /\ safe_snapshot = [t \in TxnId |-> FALSE]
 
 -----------------------------------------------------------------------------
 (* HELPER OPERATORS *)
@@ -79,6 +102,9 @@ ActiveTxns == {t \in TxnId : txn_status[t] = "active"}
 \* Committed transactions
 CommittedTxns == {t \in TxnId : txn_status[t] = "committed"}
 
+\* Active read-write transactions
+ActiveReadWriteTxns == {t \in ActiveTxns : ~read_only[t]}
+
 \* Latest committed write to key visible at snapshot time
 LatestVersion(key, snapshot_time) ==
     LET committed_writes == {i \in 1..Len(history) :
@@ -105,11 +131,52 @@ Begin(txn) ==
     /\ history' = Append(history, [op |-> "begin", txn |-> txn, key |-> NULL, version |-> NULL])
     /\ txn_status' = [txn_status EXCEPT ![txn] = "active"]
     /\ txn_snapshot' = [txn_snapshot EXCEPT ![txn] = Now]
+    /\ UNCHANGED <<write_locks, siread_locks, in_conflict, out_conflict, roVars>>
+
+\* Begin a read-only transaction; its snapshot is safe if no read-write
+\* transaction is running
+BeginReadOnly(txn) ==
+    /\ txn_status[txn] = "not_started"
+    /\ history' = Append(history, [op |-> "begin", txn |-> txn, key |-> NULL, version |-> NULL])
+    /\ txn_status' = [txn_status EXCEPT ![txn] = "active"]
+    /\ txn_snapshot' = [txn_snapshot EXCEPT ![txn] = Now]
+    /\ read_only' = [read_only EXCEPT ![txn] = TRUE]
+    /\ ro_concurrent' = [ro_concurrent EXCEPT ![txn] = ActiveReadWriteTxns]
+    /\ safe_snapshot' = [safe_snapshot EXCEPT ![txn] = ActiveReadWriteTxns = {}]
     /\ UNCHANGED <<write_locks, siread_locks, in_conflict, out_conflict>>
 
+\* Begin a DEFERRABLE read-only transaction: wait until the snapshot is safe
+BeginDeferrable(txn) ==
+    /\ ActiveReadWriteTxns = {}
+    /\ BeginReadOnly(txn)
+
+\* A read-only snapshot becomes safe once its concurrent read-write
+\* transactions have finished without committing an outgoing rw-conflict.
+\* It no longer needs its SIREAD locks.
+MarkSafe(txn) ==
+    /\ txn_status[txn] = "active"
+    /\ read_only[txn]
+    /\ ~safe_snapshot[txn]
+    /\ \A w \in ro_concurrent[txn] : txn_status[w] \in {"committed", "aborted"}
+    /\ ~\E w \in ro_concurrent[txn] : txn_status[w] = "committed" /\ out_conflict[w]
+    /\ safe_snapshot' = [safe_snapshot EXCEPT ![txn] = TRUE]
+    /\ siread_locks' = [k \in Key |-> siread_locks[k] \ {txn}]
+    /\ UNCHANGED <<history, txn_status, txn_snapshot, write_locks, in_conflict, out_conflict,
+                   read_only, ro_concurrent>>
+
+\* Read from a safe snapshot: no SIREAD lock, no conflict tracking, no abort
+SafeRead(txn, key) ==
+    /\ txn_status[txn] = "active"
+    /\ safe_snapshot[txn]
+    /\ history' = Append(history, [op |-> "read", txn |-> txn, key |-> key,
+                                   version |-> LatestVersion(key, txn_snapshot[txn])])
+    /\ UNCHANGED <<txn_status, txn_snapshot, write_locks, siread_locks, in_conflict, out_conflict,
+                   roVars>>
+
 \* Read a key
 Read(txn, key) ==
     /\ txn_status[txn] = "active"
+    /\ ~safe_snapshot[txn]
     /\ LET version == IF write_locks[key] = txn
                       THEN txn  \* Read own write
                       ELSE LatestVersion(key, txn_snapshot[txn])
@@ -129,7 +196,7 @@ Read(txn, key) ==
            /\ in_conflict' = [in_conflict EXCEPT ![txn] = FALSE]
            /\ out_conflict' = [out_conflict EXCEPT ![txn] = FALSE]
            /\ siread_locks' = [k \in Key |-> siread_locks[k] \ {txn}]
-           /\ UNCHANGED <<txn_snapshot, write_locks>>
+           /\ UNCHANGED <<txn_snapshot, write_locks, roVars>>
          ELSE
            \* Perform read
            /\ history' = Append(history, [op |-> "read", txn |-> txn, key |-> key, version |-> version])
@@ -147,11 +214,12 @@ Read(txn, key) ==
                     IF t \in newer_writers THEN TRUE ELSE in_conflict[t]]
                 /\ out_conflict' = [out_conflict EXCEPT ![txn] =
                     IF newer_writers /= {} THEN TRUE ELSE @]
-           /\ UNCHANGED <<txn_status, txn_snapshot, write_locks>>
+           /\ UNCHANGED <<txn_status, txn_snapshot, write_locks, roVars>>
 
 \* Write a key
 Write(txn, key) ==
     /\ txn_status[txn] = "active"
+    /\ ~read_only[txn]
     /\ write_locks[key] \in {NULL, txn}  \* Can acquire lock or already hold it
     /\ LET \* Find concurrent readers (SIREAD lock holders)
            concurrent_readers == {r \in siread_locks[key] :
@@ -176,7 +244,7 @@ Write(txn, key) ==
            /\ in_conflict' = [in_conflict EXCEPT ![txn] = FALSE]
            /\ out_conflict' = [out_conflict EXCEPT ![txn] = FALSE]
            /\ siread_locks' = [k \in Key |-> siread_locks[k] \ {txn}]
-           /\ UNCHANGED txn_snapshot
+           /\ UNCHANGED <<txn_snapshot, roVars>>
          ELSE
            \* Perform write
            /\ history' = Append(history, [op |-> "write", txn |-> txn, key |-> key, version |-> NULL])
@@ -186,7 +254,7 @@ Write(txn, key) ==
                IF t \in concurrent_readers THEN TRUE ELSE out_conflict[t]]
            /\ in_conflict' = [in_conflict EXCEPT ![txn] =
                IF concurrent_readers /= {} THEN TRUE ELSE @]
-           /\ UNCHANGED <<txn_status, txn_snapshot, siread_locks>>
+           /\ UNCHANGED <<txn_status, txn_snapshot, siread_locks, roVars>>
 
 \* Commit a transaction
 Commit(txn) ==
@@ -197,7 +265,7 @@ Commit(txn) ==
     \* Release write locks
     /\ write_locks' = [k \in Key |-> IF write_locks[k] = txn THEN NULL ELSE write_locks[k]]
     \* SIREAD locks persist after commit (for conflict detection)
-    /\ UNCHANGED <<txn_snapshot, siread_locks, in_conflict, out_conflict>>
+    /\ UNCHANGED <<txn_snapshot, siread_locks, in_conflict, out_conflict, roVars>>
 
 \* Abort a transaction (voluntary or forced)
 Abort(txn) ==
@@ -208,14 +276,18 @@ Abort(txn) ==
     /\ siread_locks' = [k \in Key |-> siread_locks[k] \ {txn}]
     /\ in_conflict' = [in_conflict EXCEPT ![txn] = FALSE]
     /\ out_conflict' = [out_conflict EXCEPT ![txn] = FALSE]
-    /\ UNCHANGED txn_snapshot
+    /\ UNCHANGED <<txn_snapshot, roVars>>
 
 -----------------------------------------------------------------------------
 (* NEXT STATE *)
 
 Next ==
     \/ \E t \in TxnId : Begin(t)
+    \/ \E t \in TxnId : BeginReadOnly(t)
+    \/ \E t \in TxnId : BeginDeferrable(t)
+    \/ \E t \in TxnId : MarkSafe(t)
     \/ \E t \in TxnId, k \in Key : Read(t, k)
+    \/ \E t \in TxnId, k \in Key : SafeRead(t, k)
     \/ \E t \in TxnId, k \in Key : Write(t, k)
     \/ \E t \in TxnId : Commit(t)
     \/ \E t \in TxnId : Abort(t)
@@ -267,4 +339,23 @@ NoLostWrites ==
             => \* The write is in the version chain
                TRUE  \* (simplified - full check requires version ordering)
 
+\* I5: Read-only transactions never write
+ReadOnlyNoWrites ==
+    \A i \in 1..Len(history) :
+        history[i].op = "write" => ~read_only[history[i].txn]
+
+\* I6: A safe snapshot never causes an abort
+\* It holds no SIREAD locks, so no writer can find a conflict with it
+SafeSnapshotNoLocks ==
+    \A t \in TxnId :
+        safe_snapshot[t] =>
+            /\ read_only[t]
+            /\ \A k \in Key : t \notin siread_locks[k]
+
+\* I7: A safe snapshot never suffers an abort
+\* SafeRead cannot abort, and it can always commit
+SafeSnapshotCommits ==
+    \A t \in TxnId :
+        (safe_snapshot[t] /\ txn_status[t] = "active") => ~HasDangerousStructure(t)
+
 =============================================================================
@@ -0,0 +1,175 @@
+--------------------------- MODULE snapshot_isolation ---------------------------
+(*
+ * Multi-Version Snapshot Isolation (SI)
+ *
+ * Every write creates a new version of the key stamped with the writer's
+ * commit timestamp. A transaction reads from the snapshot taken when it
+ * began: for each key, the newest version committed at or before that
+ * timestamp, or its own write. Readers never block and are never blocked.
+ *
+ * Concurrent writers of the same key are resolved by first-committer-wins:
+ * a transaction aborts at commit if a key it wrote has a version committed
+ * after its snapshot (Berenson et al., 1995).
+ *
+ * SI is NOT serializable: two transactions that read overlapping keys and
+ * write disjoint ones (write skew) both commit. Ruling that out is what
+ * serializable_snapshot_isolation.tla adds on top of this spec.
+ *
+ * EVALUATOR MAPPING
+ * -----------------
+ * Line 72: FirstCommitterWins  -> stateright, dst
+ * Line 83: SnapshotRead        -> stateright, dst
+ * Line 92: CommitAfterSnapshot -> stateright, dst
+ *)
+
+EXTENDS Integers, FiniteSets
+
+CONSTANTS
+    Txns,       \* Set of transaction identifiers
+    Keys        \* Set of keys
+
+VARIABLES
+    txn_status, \* Txn -> {"idle", "active", "committed", "aborted"}
+    clock,      \* Timestamp of the latest commit
+    snapshot,   \* Txn -> timestamp its reads see
+    commit_ts,  \* Txn -> commit timestamp (0 until committed)
+    writes,     \* Txn -> set of keys written
+    reads       \* Txn -> set of [key, from]; from is the writer read ({} = initial)
+
+vars == <<txn_status, clock, snapshot, commit_ts, writes, reads>>
+
+-----------------------------------------------------------------------------
+(* Helpers *)
+
+Committed == {t \in Txns : txn_status[t] = "committed"}
+
+\* Committed transactions that wrote k at or before timestamp ts
+WritersBefore(k, ts) ==
+    {w \in Committed : k \in writes[w] /\ commit_ts[w] <= ts}
+
+\* Writer of the version of k visible at ts ({} = initial value)
+Visible(k, ts) ==
+    {w \in WritersBefore(k, ts) :
+        \A v \in WritersBefore(k, ts) : commit_ts[v] <= commit_ts[w]}
+
+-----------------------------------------------------------------------------
+(* Type invariant *)
+
+TypeOK ==
+    /\ txn_status \in [Txns -> {"idle", "active", "committed", "aborted"}]
+    /\ clock \in Nat
+    /\ snapshot \in [Txns -> Nat]
+    /\ commit_ts \in [Txns -> Nat]
+    /\ writes \in [Txns -> SUBSET Keys]
+    /\ reads \in [Txns -> SUBSET [key : Keys, from : SUBSET Txns]]
+
+-----------------------------------------------------------------------------
+(* FirstCommitterWins
+ * Two committed transactions that wrote the same key were not concurrent:
+ * one committed before the other took its snapshot. This rules out lost
+ * updates.
+ *)
+FirstCommitterWins ==
+    \A t1, t2 \in Committed :
+        (t1 /= t2 /\ writes[t1] \cap writes[t2] /= {}) =>
+            \/ commit_ts[t1] <= snapshot[t2]
+            \/ commit_ts[t2] <= snapshot[t1]
+
+-----------------------------------------------------------------------------
+(* SnapshotRead
+ * Every read saw the transaction's own write or the newest version
+ * committed as of its snapshot, never a concurrent or aborted write.
+ *)
+SnapshotRead ==
+    \A t \in Txns :
+        \A r \in reads[t] :
+            r.from = {t} \/ r.from = Visible(r.key, snapshot[t])
+
+-----------------------------------------------------------------------------
+(* CommitAfterSnapshot
+ * Commit timestamps are unique and later than the committer's snapshot.
+ *)
+CommitAfterSnapshot ==
+    \A t \in Committed :
+        /\ snapshot[t] < commit_ts[t]
+        /\ commit_ts[t] <= clock
+        /\ \A u \in Committed \ {t} : commit_ts[u] /= commit_ts[t]
+
+-----------------------------------------------------------------------------
+(* Initial state *)
+
+Init ==
+    /\ txn_status = [t \in Txns |-> "idle"]
+    /\ clock = 0
+    /\ snapshot = [t \in Txns |-> 0]
+    /\ commit_ts = [t \in Txns |-> 0]
+    /\ writes = [t \in Txns |-> {}]
+    /\ reads = [t \in Txns |-> {}]
+
+-----------------------------------------------------------------------------
+(* Begin: take a snapshot of everything committed so far *)
+
+Begin(t) ==
+    /\ txn_status[t] = "idle"
+    /\ txn_status' = [txn_status EXCEPT ![t] = "active"]
+    /\ snapshot' = [snapshot EXCEPT ![t] = clock]
+    /\ UNCHANGED <<clock, commit_ts, writes, reads>>
+
+-----------------------------------------------------------------------------
+(* Read: own write first, then the snapshot *)
+
+Read(t, k) ==
+    LET from == IF k \in writes[t] THEN {t} ELSE Visible(k, snapshot[t])
+    IN /\ txn_status[t] = "active"
+       /\ reads' = [reads EXCEPT ![t] = reads[t] \cup {[key |-> k, from |-> from]}]
+       /\ UNCHANGED <<txn_status, clock, snapshot, commit_ts, writes>>
+
+-----------------------------------------------------------------------------
+(* Write: buffer a new version, installed at commit *)
+
+Write(t, k) ==
+    /\ txn_status[t] = "active"
+    /\ writes' = [writes EXCEPT ![t] = writes[t] \cup {k}]
+    /\ UNCHANGED <<txn_status, clock, snapshot, commit_ts, reads>>
+
+-----------------------------------------------------------------------------
+(* Commit: first committer wins *)
+
+\* A key t wrote has a version committed after t's snapshot
+WriteConflict(t) ==
+    \E u \in Committed : commit_ts[u] > snapshot[t] /\ writes[u] \cap writes[t] /= {}
+
+Commit(t) ==
+    /\ txn_status[t] = "active"
+    /\ IF WriteConflict(t)
+       THEN /\ txn_status' = [txn_status EXCEPT ![t] = "aborted"]
+            /\ UNCHANGED <<clock, commit_ts>>
+       ELSE /\ clock' = clock + 1
+            /\ commit_ts' = [commit_ts EXCEPT ![t] = clock + 1]
+            /\ txn_status' = [txn_status EXCEPT ![t] = "committed"]
+    /\ UNCHANGED <<snapshot, writes, reads>>
+
+Abort(t) ==
+    /\ txn_status[t] = "active"
+    /\ txn_status' = [txn_status EXCEPT ![t] = "aborted"]
+    /\ UNCHANGED <<clock, snapshot, commit_ts, writes, reads>>
+
+-----------------------------------------------------------------------------
+Next ==
+    \/ \E t \in Txns : Begin(t)
+    \/ \E t \in Txns, k \in Keys : Read(t, k)
+    \/ \E t \in Txns, k \in Keys : Write(t, k)
+    \/ \E t \in Txns : Commit(t)
+    \/ \E t \in Txns : Abort(t)
+
+Spec == Init /\ [][Next]_vars
+
+Safety ==
+    /\ TypeOK
+    /\ FirstCommitterWins
+    /\ SnapshotRead
+    /\ CommitAfterSnapshot
+
+FullSpec == Spec /\ []Safety
+
+=============================================================================
@@ -0,0 +1,204 @@
+--------------------------- MODULE two_phase_locking ---------------------------
+(*
+ * Strict Two-Phase Locking (2PL) with Deadlock Detection
+ *
+ * Reads take a shared lock and writes an exclusive lock on the key. A
+ * transaction only acquires locks while it runs and releases all of them
+ * at once when it commits or aborts (strict 2PL), which makes every
+ * committed history conflict-serializable (Eswaran et al., 1976).
+ *
+ * A request that conflicts with a lock held by another transaction waits.
+ * Waiting is recorded in a waits-for graph; a request whose wait would
+ * close a cycle aborts the requester as the deadlock victim instead, so
+ * the graph stays acyclic.
+ *
+ * Unlike serializable_snapshot_isolation.tla, readers block writers and
+ * read-only transactions can abort (as deadlock victims).
+ *
+ * EVALUATOR MAPPING
+ * -----------------
+ * Line 80: LockCompatibility  -> stateright, dst
+ * Line 89: StrictTwoPhase     -> stateright, dst
+ * Line 96: AccessesLocked     -> stateright, dst
+ * Line 105: NoDeadlock        -> stateright, dst
+ *)
+
+EXTENDS Integers, FiniteSets
+
+CONSTANTS
+    Txns,       \* Set of transaction identifiers
+    Keys        \* Set of keys
+
+VARIABLES
+    txn_status, \* Txn -> {"idle", "active", "committed", "aborted"}
+    shared,     \* Key -> set of transactions holding a shared lock
+    exclusive,  \* Key -> set of transactions holding the exclusive lock
+    reads,      \* Txn -> set of keys read
+    writes,     \* Txn -> set of keys written
+    waits_for   \* Txn -> set of transactions it is blocked on
+
+vars == <<txn_status, shared, exclusive, reads, writes, waits_for>>
+
+-----------------------------------------------------------------------------
+(* Helpers *)
+
+Active == {t \in Txns : txn_status[t] = "active"}
+
+\* Transactions whose locks on k conflict with t taking the given mode
+Blockers(t, k, mode) ==
+    IF mode = "shared"
+    THEN exclusive[k] \ {t}
+    ELSE (shared[k] \cup exclusive[k]) \ {t}
+
+\* Every non-empty set of transactions has one waiting on none of the others
+Acyclic(w) ==
+    \A S \in SUBSET Txns : S /= {} => \E t \in S : w[t] \cap S = {}
+
+\* Keys on which t holds a lock of at least the given strength
+HeldBy(t, mode) ==
+    IF mode = "shared"
+    THEN {k \in Keys : t \in shared[k] \cup exclusive[k]}
+    ELSE {k \in Keys : t \in exclusive[k]}
+
+-----------------------------------------------------------------------------
+(* Type invariant *)
+
+TypeOK ==
+    /\ txn_status \in [Txns -> {"idle", "active", "committed", "aborted"}]
+    /\ shared \in [Keys -> SUBSET Txns]
+    /\ exclusive \in [Keys -> SUBSET Txns]
+    /\ reads \in [Txns -> SUBSET Keys]
+    /\ writes \in [Txns -> SUBSET Keys]
+    /\ waits_for \in [Txns -> SUBSET Txns]
+
+-----------------------------------------------------------------------------
+(* LockCompatibility
+ * At most one transaction holds the exclusive lock on a key, and no other
+ * transaction shares it meanwhile. This is what makes conflicting
+ * accesses of concurrent transactions impossible.
+ *)
+LockCompatibility ==
+    \A k \in Keys :
+        /\ Cardinality(exclusive[k]) <= 1
+        /\ exclusive[k] /= {} => shared[k] \subseteq exclusive[k]
+
+-----------------------------------------------------------------------------
+(* StrictTwoPhase
+ * Locks are only held by running transactions: nothing is released early.
+ *)
+StrictTwoPhase ==
+    \A k \in Keys : shared[k] \cup exclusive[k] \subseteq Active
+
+-----------------------------------------------------------------------------
+(* AccessesLocked
+ * A running transaction still holds a lock on everything it accessed.
+ *)
+AccessesLocked ==
+    \A t \in Active :
+        /\ reads[t] \subseteq HeldBy(t, "shared")
+        /\ writes[t] \subseteq HeldBy(t, "exclusive")
+
+-----------------------------------------------------------------------------
+(* NoDeadlock
+ * The waits-for graph is acyclic, and only running transactions wait.
+ *)
+NoDeadlock ==
+    /\ Acyclic(waits_for)
+    /\ \A t \in Txns : waits_for[t] /= {} => t \in Active
+
+-----------------------------------------------------------------------------
+(* Initial state *)
+
+Init ==
+    /\ txn_status = [t \in Txns |-> "idle"]
+    /\ shared = [k \in Keys |-> {}]
+    /\ exclusive = [k \in Keys |-> {}]
+    /\ reads = [t \in Txns |-> {}]
+    /\ writes = [t \in Txns |-> {}]
+    /\ waits_for = [t \in Txns |-> {}]
+
+-----------------------------------------------------------------------------
+(* Begin *)
+
+Begin(t) ==
+    /\ txn_status[t] = "idle"
+    /\ txn_status' = [txn_status EXCEPT ![t] = "active"]
+    /\ UNCHANGED <<shared, exclusive, reads, writes, waits_for>>
+
+-----------------------------------------------------------------------------
+(* Finish: release every lock and wake transactions waiting on t *)
+
+Finish(t, outcome) ==
+    /\ txn_status' = [txn_status EXCEPT ![t] = outcome]
+    /\ shared' = [k \in Keys |-> shared[k] \ {t}]
+    /\ exclusive' = [k \in Keys |-> exclusive[k] \ {t}]
+    /\ waits_for' = [u \in Txns |-> IF u = t THEN {} ELSE waits_for[u] \ {t}]
+    /\ UNCHANGED <<reads, writes>>
+
+-----------------------------------------------------------------------------
+(* Read: take a shared lock *)
+
+Read(t, k) ==
+    /\ txn_status[t] = "active"
+    /\ Blockers(t, k, "shared") = {}
+    /\ shared' = [shared EXCEPT ![k] = shared[k] \cup {t}]
+    /\ reads' = [reads EXCEPT ![t] = reads[t] \cup {k}]
+    /\ waits_for' = [waits_for EXCEPT ![t] = {}]
+    /\ UNCHANGED <<txn_status, exclusive, writes>>
+
+-----------------------------------------------------------------------------
+(* Write: take (or upgrade to) the exclusive lock *)
+
+Write(t, k) ==
+    /\ txn_status[t] = "active"
+    /\ Blockers(t, k, "exclusive") = {}
+    /\ exclusive' = [exclusive EXCEPT ![k] = {t}]
+    /\ writes' = [writes EXCEPT ![t] = writes[t] \cup {k}]
+    /\ waits_for' = [waits_for EXCEPT ![t] = {}]
+    /\ UNCHANGED <<txn_status, shared, reads>>
+
+-----------------------------------------------------------------------------
+(* Wait: a conflicting request blocks unless it would deadlock *)
+
+Wait(t, k, mode) ==
+    LET blockers == Blockers(t, k, mode)
+        waiting == [waits_for EXCEPT ![t] = blockers]
+    IN /\ txn_status[t] = "active"
+       /\ blockers /= {}
+       /\ IF Acyclic(waiting)
+          THEN /\ waits_for' = waiting
+               /\ UNCHANGED <<txn_status, shared, exclusive, reads, writes>>
+          ELSE Finish(t, "aborted")    \* Deadlock victim
+
+-----------------------------------------------------------------------------
+(* Commit and Abort *)
+
+Commit(t) ==
+    /\ txn_status[t] = "active"
+    /\ Finish(t, "committed")
+
+Abort(t) ==
+    /\ txn_status[t] = "active"
+    /\ Finish(t, "aborted")
+
+-----------------------------------------------------------------------------
+Next ==
+    \/ \E t \in Txns : Begin(t)
+    \/ \E t \in Txns, k \in Keys : Read(t, k)
+    \/ \E t \in Txns, k \in Keys : Write(t, k)
+    \/ \E t \in Txns, k \in Keys, m \in {"shared", "exclusive"} : Wait(t, k, m)
+    \/ \E t \in Txns : Commit(t)
+    \/ \E t \in Txns : Abort(t)
+
+Spec == Init /\ [][Next]_vars
+
+Safety ==
+    /\ TypeOK
+    /\ LockCompatibility
+    /\ StrictTwoPhase
+    /\ AccessesLocked
+    /\ NoDeadlock
+
+FullSpec == Spec /\ []Safety
+
+=============================================================================
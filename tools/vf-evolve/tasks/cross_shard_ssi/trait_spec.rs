@@ -226,4 +226,65 @@ mod tests {
             "At least some txns must succeed"
         );
     }
+
+    // Needs vf-core, which the cascade adds from the checkout it was built
+    // from, or from VF_CORE_PATH (or --vf-core-path) elsewhere
+    #[cfg(feature = "checkers")]
+    #[test]
+    fn test_concurrent_history_serializable() {
+        use std::sync::Mutex;
+        use vf_core::invariants::elle::{check, IsolationLevel, Mop, TxnHistory, TxnOutcome};
+
+        const NUM_THREADS: usize = 4;
+        const ROUNDS: usize = 50;
+        const NUM_SHARDS: usize = 2;
+        const KEYS_PER_SHARD: u64 = 2;
+
+        let ssi = Arc::new(CrossShardSsi::new(NUM_SHARDS));
+        let history = Arc::new(Mutex::new(TxnHistory::new()));
+
+        std::thread::scope(|s| {
+            for t in 0..NUM_THREADS {
+                let ssi = Arc::clone(&ssi);
+                let history = Arc::clone(&history);
+                s.spawn(move || {
+                    for r in 0..ROUNDS {
+                        // Read two keys, then write one of them back with a
+                        // unique value (read skew and write skew both possible)
+                        let n = (t * ROUNDS + r) as u64;
+                        let slots = NUM_SHARDS as u64 * KEYS_PER_SHARD;
+                        let a = n.wrapping_mul(7) % slots;
+                        let b = (a + 1 + n % (slots - 1)) % slots;
+                        let w = if n % 2 == 0 { a } else { b };
+                        let shard = |k: u64| (k / KEYS_PER_SHARD) as usize;
+                        let key = |k: u64| k % KEYS_PER_SHARD;
+
+                        let txn = ssi.begin_txn();
+                        let mut mops = Vec::new();
+                        for k in [a, b] {
+                            let value = ssi.read(txn, shard(k), key(k));
+                            mops.push(Mop::Read { key: k, value });
+                        }
+                        let value = n + 1;
+                        ssi.write(txn, shard(w), key(w), value);
+                        mops.push(Mop::Write { key: w, value });
+
+                        let outcome = match ssi.commit(txn) {
+                            Ok(()) => TxnOutcome::Committed,
+                            Err(_) => TxnOutcome::Aborted,
+                        };
+                        history.lock().unwrap().push(n, outcome, mops);
+                    }
+                });
+            }
+        });
+
+        let report = check(&history.lock().unwrap());
+        assert!(
+            report.satisfies(IsolationLevel::Serializable),
+            "Concurrent history is not serializable (weakest violated: {:?}): {:?}",
+            report.weakest_violated,
+            report.anomalies.first().map(|a| a.to_string())
+        );
+    }
 }
@@ -0,0 +1,36 @@
+/// Michael-Scott Queue - Initial Seed (Mutex-based blocking implementation)
+/// ShinkaEvolve will evolve this from Blocking -> LockFree -> WaitFree
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+pub struct MsQueue<T> {
+    inner: Mutex<VecDeque<T>>,
+}
+
+impl<T> MsQueue<T> {
+    pub fn new() -> Self {
+        MsQueue {
+            inner: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn enqueue(&self, value: T) {
+        let mut guard = self.inner.lock().unwrap();
+        guard.push_back(value);
+    }
+
+    pub fn dequeue(&self) -> Option<T> {
+        let mut guard = self.inner.lock().unwrap();
+        guard.pop_front()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let guard = self.inner.lock().unwrap();
+        guard.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        let guard = self.inner.lock().unwrap();
+        guard.len()
+    }
+}
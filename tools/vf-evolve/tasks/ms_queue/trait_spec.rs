@@ -0,0 +1,237 @@
+/// Trait specification tests for MsQueue<T>
+///
+/// The evolved code must provide:
+///   pub struct MsQueue<T> { ... }
+///   impl<T> MsQueue<T> {
+///       pub fn new() -> Self;
+///       pub fn enqueue(&self, val: T);
+///       pub fn dequeue(&self) -> Option<T>;
+///       pub fn is_empty(&self) -> bool;
+///   }
+///
+/// Unbounded MPMC FIFO queue. All operations must be lock-free and safe
+/// for concurrent access.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_new_queue_is_empty() {
+        let queue: MsQueue<i32> = MsQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.dequeue(), None);
+    }
+
+    #[test]
+    fn test_enqueue_dequeue_single() {
+        let queue = MsQueue::new();
+        queue.enqueue(42);
+        assert!(!queue.is_empty());
+        assert_eq!(queue.dequeue(), Some(42));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_fifo_order() {
+        let queue = MsQueue::new();
+        queue.enqueue(1);
+        queue.enqueue(2);
+        queue.enqueue(3);
+        assert_eq!(queue.dequeue(), Some(1));
+        assert_eq!(queue.dequeue(), Some(2));
+        assert_eq!(queue.dequeue(), Some(3));
+        assert_eq!(queue.dequeue(), None);
+    }
+
+    #[test]
+    fn test_interleaved_enqueue_dequeue() {
+        let queue = MsQueue::new();
+        queue.enqueue(10);
+        queue.enqueue(20);
+        assert_eq!(queue.dequeue(), Some(10));
+        queue.enqueue(30);
+        assert_eq!(queue.dequeue(), Some(20));
+        assert_eq!(queue.dequeue(), Some(30));
+        assert_eq!(queue.dequeue(), None);
+    }
+
+    #[test]
+    fn test_reuse_after_drain() {
+        let queue = MsQueue::new();
+        for round in 0..5 {
+            for i in 0..10 {
+                queue.enqueue(round * 10 + i);
+            }
+            for i in 0..10 {
+                assert_eq!(queue.dequeue(), Some(round * 10 + i));
+            }
+            assert!(queue.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_concurrent_enqueue_no_lost_elements() {
+        const NUM_THREADS: usize = 8;
+        const OPS_PER_THREAD: usize = 1000;
+
+        let queue = Arc::new(MsQueue::new());
+
+        std::thread::scope(|s| {
+            for t in 0..NUM_THREADS {
+                let queue = Arc::clone(&queue);
+                s.spawn(move || {
+                    for i in 0..OPS_PER_THREAD {
+                        queue.enqueue(t * OPS_PER_THREAD + i);
+                    }
+                });
+            }
+        });
+
+        let mut dequeued = Vec::new();
+        while let Some(val) = queue.dequeue() {
+            dequeued.push(val);
+        }
+
+        assert_eq!(dequeued.len(), NUM_THREADS * OPS_PER_THREAD);
+        let set: HashSet<usize> = dequeued.iter().cloned().collect();
+        assert_eq!(set.len(), dequeued.len(), "No duplicates allowed");
+
+        // FIFO per producer: each thread's values come out in enqueue order
+        for t in 0..NUM_THREADS {
+            let from_t: Vec<usize> = dequeued
+                .iter()
+                .cloned()
+                .filter(|v| v / OPS_PER_THREAD == t)
+                .collect();
+            assert!(
+                from_t.windows(2).all(|w| w[0] < w[1]),
+                "FIFO order violated for producer {}",
+                t
+            );
+        }
+    }
+
+    #[test]
+    fn test_concurrent_producer_consumer() {
+        const NUM_ITEMS: usize = 10_000;
+
+        let queue = Arc::new(MsQueue::new());
+
+        std::thread::scope(|s| {
+            let producer_queue = Arc::clone(&queue);
+            s.spawn(move || {
+                for i in 0..NUM_ITEMS {
+                    producer_queue.enqueue(i);
+                }
+            });
+
+            let consumer_queue = Arc::clone(&queue);
+            let consumer = s.spawn(move || {
+                let mut received = Vec::with_capacity(NUM_ITEMS);
+                while received.len() < NUM_ITEMS {
+                    if let Some(val) = consumer_queue.dequeue() {
+                        received.push(val);
+                    }
+                    // Spin-retry if empty
+                }
+                received
+            });
+
+            let received = consumer.join().unwrap();
+            for (i, &val) in received.iter().enumerate() {
+                assert_eq!(val, i, "FIFO order violated at index {}", i);
+            }
+        });
+    }
+
+    #[test]
+    fn test_concurrent_mixed_enqueue_dequeue() {
+        const NUM_THREADS: usize = 8;
+        const OPS_PER_THREAD: usize = 500;
+
+        let queue = Arc::new(MsQueue::new());
+        let enqueue_count = Arc::new(AtomicUsize::new(0));
+        let dequeue_count = Arc::new(AtomicUsize::new(0));
+
+        std::thread::scope(|s| {
+            for t in 0..NUM_THREADS {
+                let queue = Arc::clone(&queue);
+                let enqueue_count = Arc::clone(&enqueue_count);
+                let dequeue_count = Arc::clone(&dequeue_count);
+                s.spawn(move || {
+                    for i in 0..OPS_PER_THREAD {
+                        if (t + i) % 2 == 0 {
+                            queue.enqueue(t * OPS_PER_THREAD + i);
+                            enqueue_count.fetch_add(1, Ordering::Relaxed);
+                        } else if queue.dequeue().is_some() {
+                            dequeue_count.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut remaining = 0usize;
+        while queue.dequeue().is_some() {
+            remaining += 1;
+        }
+
+        let total_enqueued = enqueue_count.load(Ordering::Relaxed);
+        let total_dequeued = dequeue_count.load(Ordering::Relaxed) + remaining;
+        assert_eq!(total_enqueued, total_dequeued, "enqueued = dequeued invariant");
+    }
+}
+
+/// Property-based tests that scale with compute budget.
+/// Set VF_PROPTEST_CASES env var to control number of cases (default 100).
+/// Disabled under Miri (too slow for interpretation).
+#[cfg(all(test, not(miri)))]
+mod prop_tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::VecDeque;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+
+        #[test]
+        fn prop_fifo_ordering(
+            values in prop::collection::vec(0u64..10000, 1..200)
+        ) {
+            let queue = MsQueue::new();
+            for &v in &values {
+                queue.enqueue(v);
+            }
+            let mut dequeued = Vec::new();
+            while let Some(v) = queue.dequeue() {
+                dequeued.push(v);
+            }
+            prop_assert_eq!(dequeued, values, "Must be FIFO order");
+        }
+
+        #[test]
+        fn prop_matches_sequential_queue(
+            ops in prop::collection::vec(prop::bool::ANY, 1..200),
+            seed_val in 0u64..10000
+        ) {
+            let queue = MsQueue::new();
+            let mut model = VecDeque::new();
+            let mut val = seed_val;
+
+            for &is_enqueue in &ops {
+                if is_enqueue {
+                    queue.enqueue(val);
+                    model.push_back(val);
+                    val += 1;
+                } else {
+                    prop_assert_eq!(queue.dequeue(), model.pop_front());
+                }
+                prop_assert_eq!(queue.is_empty(), model.is_empty());
+            }
+        }
+    }
+}